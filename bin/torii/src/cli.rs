@@ -3,12 +3,92 @@
 //! Use a `Cli` struct to parse the CLI arguments
 //! and to have flexibility in the future to add more commands
 //! that may not start Torii directly.
-use clap::Parser;
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+use starknet::core::types::Felt;
 use torii_cli::ToriiArgs;
 
 #[derive(Parser)]
 #[command(name = "torii", author, version = env!("TORII_VERSION_SPEC"), about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     #[command(flatten)]
     pub args: ToriiArgs,
 }
+
+/// Commands run against an existing database, without starting the indexer nor the servers.
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Inspect and maintain a database.
+    #[command(subcommand)]
+    Db(DbCommand),
+    /// Move the cursors of the world contracts back, so that the next run indexes again from a
+    /// block. Token contracts are left untouched.
+    Reindex(ReindexArgs),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DbCommand {
+    /// Registered models, row counts and contract cursors.
+    Info(DbArgs),
+    /// Check the tables of the registered models and look for orphan rows.
+    Verify(DbArgs),
+    /// Rebuild the database file to reclaim the space of deleted rows. Torii must be stopped.
+    Vacuum(DbArgs),
+    /// Copy the WAL into the database file and truncate it.
+    Checkpoint(DbArgs),
+    /// Write a compacted and consistent copy of the database, safe to take while Torii runs.
+    Export(ExportArgs),
+    /// Take a gzipped snapshot of the database with its manifest, to bootstrap replicas from
+    /// with `--snapshot.url`. Safe to take while Torii runs.
+    Snapshot(SnapshotArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct DbArgs {
+    /// Directory of the database, as given to `--db-dir`.
+    #[arg(long, value_name = "PATH")]
+    pub db_dir: PathBuf,
+
+    /// Print the output as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    #[command(flatten)]
+    pub db: DbArgs,
+
+    /// File to write the copy to. It must not exist.
+    #[arg(value_name = "OUTPUT")]
+    pub output: PathBuf,
+}
+
+#[derive(Debug, Args)]
+pub struct SnapshotArgs {
+    #[command(flatten)]
+    pub db: DbArgs,
+
+    /// Directory to write the snapshot and its manifest to.
+    #[arg(value_name = "OUTPUT_DIR")]
+    pub output_dir: PathBuf,
+}
+
+#[derive(Debug, Args)]
+pub struct ReindexArgs {
+    #[command(flatten)]
+    pub db: DbArgs,
+
+    /// First block to index again.
+    #[arg(long, value_name = "BLOCK")]
+    pub from_block: u64,
+
+    /// World contracts to rewind, all of them if not set.
+    #[arg(long, value_name = "ADDRESS", value_delimiter = ',')]
+    pub contracts: Vec<Felt>,
+}
@@ -0,0 +1,162 @@
+//! Commands run against an existing database, without networking.
+
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{bail, Context};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::SqlitePool;
+use torii_sqlite::constants::DATABASE_FILE;
+use torii_sqlite::{maintenance, snapshot};
+
+use crate::cli::{Command, DbArgs, DbCommand};
+
+/// Time to wait for the lock of a database written by a running Torii.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub async fn run(command: Command) -> anyhow::Result<()> {
+    match command {
+        Command::Db(DbCommand::Info(args)) => {
+            let pool = connect(&args.db_dir, true).await?;
+            let info = maintenance::info(&pool).await?;
+            if args.json {
+                println!("{}", serde_json::to_string_pretty(&info)?);
+                return Ok(());
+            }
+
+            println!("Size: {} bytes ({} free)", info.size_bytes, info.free_bytes);
+            println!("\nContracts:");
+            for contract in &info.contracts {
+                println!(
+                    "  {} {:<8} head={} last_block_timestamp={}",
+                    contract.contract_address,
+                    contract.contract_type,
+                    display(contract.head),
+                    display(contract.last_block_timestamp)
+                );
+            }
+            println!("\nModels:");
+            for model in &info.models {
+                println!(
+                    "  {} ({}) rows={}",
+                    model.tag,
+                    model.world_address,
+                    model
+                        .rows
+                        .map_or("missing table".to_string(), |rows| rows.to_string())
+                );
+            }
+            println!("\nTables:");
+            for (table, rows) in &info.tables {
+                println!("  {} rows={}", table, rows);
+            }
+        }
+        Command::Db(DbCommand::Verify(args)) => {
+            let pool = connect(&args.db_dir, true).await?;
+            let issues = maintenance::verify(&pool).await?;
+            if args.json {
+                println!("{}", serde_json::to_string_pretty(&issues)?);
+            } else {
+                for issue in &issues {
+                    println!("{}", issue);
+                }
+            }
+
+            if !issues.is_empty() {
+                bail!("Found {} issues", issues.len());
+            }
+            if !args.json {
+                println!("No issues found");
+            }
+        }
+        Command::Db(DbCommand::Vacuum(args)) => {
+            let pool = connect(&args.db_dir, false).await?;
+            maintenance::vacuum(&pool).await?;
+            println!("Vacuumed {}", args.db_dir.join(DATABASE_FILE).display());
+        }
+        Command::Db(DbCommand::Checkpoint(args)) => {
+            let pool = connect(&args.db_dir, false).await?;
+            let checkpoint = maintenance::checkpoint(&pool).await?;
+            if args.json {
+                println!("{}", serde_json::to_string_pretty(&checkpoint)?);
+            } else {
+                println!(
+                    "Checkpointed {} of {} WAL frames{}",
+                    checkpoint.checkpointed_frames,
+                    checkpoint.log_frames,
+                    if checkpoint.busy {
+                        ", the database is busy"
+                    } else {
+                        ""
+                    }
+                );
+            }
+        }
+        Command::Db(DbCommand::Export(args)) => {
+            if args.output.exists() {
+                bail!("{} already exists", args.output.display());
+            }
+
+            let pool = connect(&args.db.db_dir, true).await?;
+            maintenance::export(&pool, &args.output).await?;
+            println!("Exported to {}", args.output.display());
+        }
+        Command::Db(DbCommand::Snapshot(args)) => {
+            let pool = connect(&args.db.db_dir, true).await?;
+            let (path, manifest) =
+                snapshot::create(&pool, &args.output_dir, env!("TORII_VERSION_SPEC")).await?;
+            if args.db.json {
+                println!("{}", serde_json::to_string_pretty(&manifest)?);
+            } else {
+                println!(
+                    "Snapshot written to {} ({} bytes, sha256 {})",
+                    path.display(),
+                    manifest.size_bytes,
+                    manifest.sha256
+                );
+            }
+        }
+        Command::Reindex(args) => {
+            let pool = connect(&args.db.db_dir, false).await?;
+            let contracts = maintenance::rewind(&pool, args.from_block, &args.contracts).await?;
+            if args.db.json {
+                println!("{}", serde_json::to_string_pretty(&contracts)?);
+            } else if contracts.is_empty() {
+                println!("No world contract is past block {}", args.from_block);
+            } else {
+                for contract in &contracts {
+                    println!(
+                        "{} will be indexed again from block {}",
+                        contract, args.from_block
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Opens the database of `db_dir`, which must exist. Migrations aren't applied, the database is
+/// left as Torii wrote it.
+async fn connect(db_dir: &Path, read_only: bool) -> anyhow::Result<SqlitePool> {
+    let path = db_dir.join(DATABASE_FILE);
+    if !path.exists() {
+        bail!("No database at {}", path.display());
+    }
+
+    let options = SqliteConnectOptions::from_str(&path.to_string_lossy())?
+        .read_only(read_only)
+        .busy_timeout(BUSY_TIMEOUT);
+
+    SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await
+        .with_context(|| format!("Failed to open {}", path.display()))
+}
+
+fn display(value: Option<i64>) -> String {
+    value.map_or("-".to_string(), |value| value.to_string())
+}
@@ -10,33 +10,65 @@
 //!   documentation for usage details. This is **not recommended on Windows**. See [here](https://rust-lang.github.io/rfcs/1974-global-allocators.html#jemalloc)
 //!   for more info.
 
+use std::sync::Arc;
+
 use clap::Parser;
 use cli::Cli;
 use torii_runner::Runner;
 use tracing_indicatif::IndicatifLayer;
 use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::reload;
 use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::EnvFilter;
 use tracing_subscriber::Registry;
 
 mod cli;
+mod commands;
+mod telemetry;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    // The config file only configures the indexing, not the commands run against a database
+    let args = match cli.command {
+        Some(_) => None,
+        None => Some(cli.args.with_config_file()?),
+    };
+
     // Set the global tracing subscriber
     let filter_layer =
         EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("torii=info"));
+    // Reloadable so that the admin API can change the log filter at runtime
+    let (filter_layer, filter_handle) = reload::Layer::new(filter_layer);
 
     let indicatif_layer = IndicatifLayer::new();
 
+    // The spans are only exported when indexing
+    let otlp_layer = match &args {
+        Some(args) => telemetry::otlp_layer(&args.tracing)?,
+        None => None,
+    };
+
     Registry::default()
         .with(tracing_subscriber::fmt::layer())
         .with(filter_layer)
         .with(indicatif_layer)
+        .with(otlp_layer)
         .init();
 
-    let args = Cli::parse().args.with_config_file()?;
-    let runner = Runner::new(args, env!("TORII_VERSION_SPEC").to_string());
-    runner.run().await?;
-    Ok(())
+    let Some(args) = args else {
+        return commands::run(cli.command.expect("args are only skipped for a command")).await;
+    };
+
+    let runner = Runner::new(args, env!("TORII_VERSION_SPEC").to_string()).with_log_filter(
+        Arc::new(move |directives: &str| {
+            let filter = EnvFilter::try_new(directives).map_err(|e| e.to_string())?;
+            filter_handle.reload(filter).map_err(|e| e.to_string())
+        }),
+    );
+    let result = runner.run().await;
+
+    // Blocks until the spans left are exported
+    tokio::task::spawn_blocking(telemetry::shutdown).await?;
+    result
 }
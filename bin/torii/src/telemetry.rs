@@ -0,0 +1,151 @@
+//! Export of the spans to an OTLP collector.
+//!
+//! A trace is the indexing of a block range: its fetch, the processing tasks, the queries and the
+//! commit of the executor, then the publishes of the updates and their delivery to the
+//! subscribers.
+
+use opentelemetry::trace::TraceError;
+use opentelemetry::{global, KeyValue};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::trace::{Config, Sampler, Tracer};
+use opentelemetry_sdk::{runtime, Resource};
+use torii_cli::TracingOptions;
+use tracing::Subscriber;
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Layer exporting the spans to the OTLP collector of the options, none if there isn't any.
+pub fn otlp_layer<S>(
+    options: &TracingOptions,
+) -> Result<Option<OpenTelemetryLayer<S, Tracer>>, TraceError>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    let Some(endpoint) = &options.otlp_endpoint else {
+        return Ok(None);
+    };
+
+    // The root spans are sampled at the ratio, their children along with them
+    let sampler = Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(options.sample_ratio)));
+    let tracer =
+        opentelemetry_otlp::new_pipeline()
+            .tracing()
+            .with_exporter(
+                opentelemetry_otlp::new_exporter()
+                    .tonic()
+                    .with_endpoint(endpoint),
+            )
+            .with_trace_config(Config::default().with_sampler(sampler).with_resource(
+                Resource::new([KeyValue::new("service.name", options.service_name.clone())]),
+            ))
+            .install_batch(runtime::Tokio)?;
+
+    Ok(Some(tracing_opentelemetry::layer().with_tracer(tracer)))
+}
+
+/// Exports the spans left, to be called before exiting.
+pub fn shutdown() {
+    global::shutdown_tracer_provider();
+}
+
+#[cfg(test)]
+mod tests {
+    use opentelemetry_proto::tonic::collector::trace::v1::trace_service_server::{
+        TraceService, TraceServiceServer,
+    };
+    use opentelemetry_proto::tonic::collector::trace::v1::{
+        ExportTraceServiceRequest, ExportTraceServiceResponse,
+    };
+    use opentelemetry_proto::tonic::common::v1::any_value::Value;
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::Server;
+    use tonic::{Request, Response, Status};
+    use tracing::info_span;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+
+    // Stand-in of an OTLP collector, forwarding the exported spans
+    struct Collector(mpsc::UnboundedSender<ExportTraceServiceRequest>);
+
+    #[tonic::async_trait]
+    impl TraceService for Collector {
+        async fn export(
+            &self,
+            request: Request<ExportTraceServiceRequest>,
+        ) -> Result<Response<ExportTraceServiceResponse>, Status> {
+            let _ = self.0.send(request.into_inner());
+            Ok(Response::new(ExportTraceServiceResponse {
+                partial_success: None,
+            }))
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_export_spans() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        tokio::spawn(
+            Server::builder()
+                .add_service(TraceServiceServer::new(Collector(tx)))
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+
+        let options = TracingOptions {
+            otlp_endpoint: Some(endpoint),
+            ..Default::default()
+        };
+        let subscriber = tracing_subscriber::registry().with(otlp_layer(&options).unwrap());
+        tracing::subscriber::with_default(subscriber, || {
+            info_span!("index_range").in_scope(|| {
+                info_span!("task").in_scope(|| {});
+            });
+        });
+        // Flushes the batch of the spans to the collector
+        tokio::task::spawn_blocking(shutdown).await.unwrap();
+
+        let mut requests = vec![];
+        while let Ok(request) = rx.try_recv() {
+            requests.push(request);
+        }
+        let resource_spans = requests
+            .iter()
+            .flat_map(|request| &request.resource_spans)
+            .collect::<Vec<_>>();
+
+        assert!(resource_spans.iter().all(|resource_spans| {
+            resource_spans
+                .resource
+                .as_ref()
+                .unwrap()
+                .attributes
+                .iter()
+                .any(|attribute| {
+                    attribute.key == "service.name"
+                        && attribute
+                            .value
+                            .as_ref()
+                            .and_then(|value| value.value.as_ref())
+                            == Some(&Value::StringValue("torii".to_string()))
+                })
+        }));
+
+        let spans = resource_spans
+            .iter()
+            .flat_map(|resource_spans| &resource_spans.scope_spans)
+            .flat_map(|scope_spans| &scope_spans.spans)
+            .collect::<Vec<_>>();
+        assert_eq!(spans.len(), 2);
+        let range = spans
+            .iter()
+            .find(|span| span.name == "index_range")
+            .unwrap();
+        let task = spans.iter().find(|span| span.name == "task").unwrap();
+        assert_eq!(task.trace_id, range.trace_id);
+        assert_eq!(task.parent_span_id, range.span_id);
+        assert!(range.parent_span_id.is_empty());
+    }
+}
@@ -0,0 +1,269 @@
+//! Runtime operations of a running Torii, shared by the HTTP and gRPC admin APIs.
+//!
+//! Changes to the indexing are sent to the engine, which applies them between two fetches.
+//! Subscriptions are listed and closed through the registries of the servers serving them.
+
+use std::fmt::Debug;
+use std::sync::{Arc, RwLock};
+
+use serde::Serialize;
+use starknet::core::types::Felt;
+use tokio::sync::{mpsc, oneshot};
+use torii_proto::ContractDefinition;
+
+/// Number of commands waiting for the engine before new ones are refused.
+const COMMAND_BUFFER_SIZE: usize = 16;
+
+/// A runtime operation applied by the engine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EngineCommand {
+    /// Starts indexing a contract, from its starting block.
+    AddContract(ContractDefinition),
+    /// Stops indexing a contract. Its indexed data is kept.
+    RemoveContract(Felt),
+    /// Queues a metadata refresh of a token contract and all of its tokens.
+    RefreshMetadata(Felt),
+    /// Rebuilds the full-text search index from the indexed data.
+    RebuildSearchIndex,
+    /// Processes the events of a block range again, for the world contracts among `contracts`
+    /// or all of them if empty. The cursors are left untouched. The engine refuses ranges too
+    /// large to be processed while holding it.
+    ReindexRange {
+        from_block: u64,
+        to_block: u64,
+        contracts: Vec<Felt>,
+    },
+    /// Stops fetching new blocks until resumed.
+    Pause,
+    Resume,
+}
+
+/// Where the engine sends back the outcome of a command.
+pub type CommandReply = oneshot::Sender<Result<(), String>>;
+
+/// Receiving end of the engine commands.
+pub type CommandReceiver = mpsc::Receiver<(EngineCommand, CommandReply)>;
+
+/// Replaces the log filter of the process, from `RUST_LOG` style directives.
+pub type LogFilter = Arc<dyn Fn(&str) -> Result<(), String> + Send + Sync>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SubscriptionInfo {
+    pub id: u64,
+    /// What is subscribed to, like `entities` or `token_balances`.
+    pub kind: String,
+}
+
+/// Open subscriptions of a server.
+pub trait Subscriptions: Send + Sync + Debug {
+    fn list(&self) -> Vec<SubscriptionInfo>;
+
+    /// Closes the stream of a subscription, returns whether it existed.
+    fn kill(&self, kind: &str, id: u64) -> bool;
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AdminError {
+    #[error("The engine is not running")]
+    EngineStopped,
+    #[error("Too many pending commands")]
+    Busy,
+    #[error("{0}")]
+    Command(String),
+    #[error("Log filter can't be changed at runtime")]
+    NoLogFilter,
+    #[error("Invalid log filter: {0}")]
+    InvalidLogFilter(String),
+    #[error("Unknown {0} subscription: {1}")]
+    UnknownSubscription(String, u64),
+}
+
+pub struct Admin {
+    commands: mpsc::Sender<(EngineCommand, CommandReply)>,
+    log_filter: Option<LogFilter>,
+    log_directives: RwLock<Option<String>>,
+    subscriptions: RwLock<Vec<Arc<dyn Subscriptions>>>,
+}
+
+impl Debug for Admin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Admin")
+            .field("log_directives", &self.log_directives)
+            .field("subscriptions", &self.subscriptions)
+            .finish()
+    }
+}
+
+impl Admin {
+    /// The admin and the receiver of the commands to hand to the engine.
+    pub fn new(log_filter: Option<LogFilter>) -> (Self, CommandReceiver) {
+        let (commands, receiver) = mpsc::channel(COMMAND_BUFFER_SIZE);
+        let admin = Self {
+            commands,
+            log_filter,
+            log_directives: RwLock::new(None),
+            subscriptions: RwLock::new(vec![]),
+        };
+
+        (admin, receiver)
+    }
+
+    /// Sends a command to the engine and waits for it to be applied.
+    pub async fn execute(&self, command: EngineCommand) -> Result<(), AdminError> {
+        let (reply, outcome) = oneshot::channel();
+        self.commands
+            .try_send((command, reply))
+            .map_err(|e| match e {
+                mpsc::error::TrySendError::Full(_) => AdminError::Busy,
+                mpsc::error::TrySendError::Closed(_) => AdminError::EngineStopped,
+            })?;
+
+        outcome
+            .await
+            .map_err(|_| AdminError::EngineStopped)?
+            .map_err(AdminError::Command)
+    }
+
+    /// The directives of the log filter, if changed at runtime.
+    pub fn log_filter(&self) -> Option<String> {
+        self.log_directives.read().unwrap().clone()
+    }
+
+    pub fn set_log_filter(&self, directives: &str) -> Result<(), AdminError> {
+        let log_filter = self.log_filter.as_ref().ok_or(AdminError::NoLogFilter)?;
+        log_filter(directives).map_err(AdminError::InvalidLogFilter)?;
+        *self.log_directives.write().unwrap() = Some(directives.to_string());
+        Ok(())
+    }
+
+    pub fn register_subscriptions(&self, subscriptions: Arc<dyn Subscriptions>) {
+        self.subscriptions.write().unwrap().push(subscriptions);
+    }
+
+    pub fn subscriptions(&self) -> Vec<SubscriptionInfo> {
+        self.subscriptions
+            .read()
+            .unwrap()
+            .iter()
+            .flat_map(|subscriptions| subscriptions.list())
+            .collect()
+    }
+
+    pub fn kill_subscription(&self, kind: &str, id: u64) -> Result<(), AdminError> {
+        let killed = self
+            .subscriptions
+            .read()
+            .unwrap()
+            .iter()
+            .any(|subscriptions| subscriptions.kill(kind, id));
+        if killed {
+            Ok(())
+        } else {
+            Err(AdminError::UnknownSubscription(kind.to_string(), id))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    struct TestSubscriptions(Mutex<Vec<u64>>);
+
+    impl Subscriptions for TestSubscriptions {
+        fn list(&self) -> Vec<SubscriptionInfo> {
+            self.0
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|id| SubscriptionInfo {
+                    id: *id,
+                    kind: "entities".to_string(),
+                })
+                .collect()
+        }
+
+        fn kill(&self, kind: &str, id: u64) -> bool {
+            if kind != "entities" {
+                return false;
+            }
+
+            let mut ids = self.0.lock().unwrap();
+            let len = ids.len();
+            ids.retain(|other| *other != id);
+            ids.len() != len
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let (admin, mut receiver) = Admin::new(None);
+
+        tokio::spawn(async move {
+            while let Some((command, reply)) = receiver.recv().await {
+                let outcome = match command {
+                    EngineCommand::Pause => Ok(()),
+                    _ => Err("unsupported".to_string()),
+                };
+                reply.send(outcome).unwrap();
+            }
+        });
+
+        assert!(admin.execute(EngineCommand::Pause).await.is_ok());
+        assert!(matches!(
+            admin.execute(EngineCommand::RebuildSearchIndex).await,
+            Err(AdminError::Command(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_engine_stopped() {
+        let (admin, receiver) = Admin::new(None);
+        drop(receiver);
+
+        assert!(matches!(
+            admin.execute(EngineCommand::Resume).await,
+            Err(AdminError::EngineStopped)
+        ));
+    }
+
+    #[test]
+    fn test_subscriptions() {
+        let (admin, _receiver) = Admin::new(None);
+        admin.register_subscriptions(Arc::new(TestSubscriptions(Mutex::new(vec![1, 2]))));
+        admin.register_subscriptions(Arc::new(TestSubscriptions(Mutex::new(vec![3]))));
+
+        assert_eq!(admin.subscriptions().len(), 3);
+        assert!(admin.kill_subscription("tokens", 3).is_err());
+        assert!(admin.kill_subscription("entities", 3).is_ok());
+        assert!(matches!(
+            admin.kill_subscription("entities", 3),
+            Err(AdminError::UnknownSubscription(_, 3))
+        ));
+        assert_eq!(admin.subscriptions().len(), 2);
+    }
+
+    #[test]
+    fn test_log_filter() {
+        let (admin, _receiver) = Admin::new(None);
+        assert!(matches!(
+            admin.set_log_filter("torii=debug"),
+            Err(AdminError::NoLogFilter)
+        ));
+
+        let log_filter: LogFilter = Arc::new(|directives| {
+            if directives.is_empty() {
+                Err("empty".to_string())
+            } else {
+                Ok(())
+            }
+        });
+        let (admin, _receiver) = Admin::new(Some(log_filter));
+        assert!(admin.set_log_filter("").is_err());
+        assert!(admin.set_log_filter("torii=debug").is_ok());
+        assert_eq!(admin.log_filter().as_deref(), Some("torii=debug"));
+    }
+}
@@ -0,0 +1,327 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::str::FromStr;
+
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+
+use crate::{AuthError, Capability, ConfigError, Principal};
+
+/// Claims of a token. The capabilities are either a space separated OAuth `scope` or a
+/// `capabilities` array, unknown ones are ignored.
+#[derive(Debug, Deserialize)]
+struct Claims {
+    sub: String,
+    #[serde(default)]
+    scope: Option<String>,
+    #[serde(default)]
+    capabilities: Vec<String>,
+    #[serde(default)]
+    rate_limit: Option<u32>,
+}
+
+/// Verifies JWTs against the keys of a local JWKS file.
+#[derive(Debug)]
+pub struct JwtVerifier {
+    jwks: JwkSet,
+    issuer: Option<String>,
+    audience: Option<String>,
+}
+
+impl JwtVerifier {
+    pub fn from_file(
+        path: &Path,
+        issuer: Option<String>,
+        audience: Option<String>,
+    ) -> Result<Self, ConfigError> {
+        let content = std::fs::read_to_string(path)?;
+        let jwks: JwkSet = serde_json::from_str(&content)?;
+        if jwks.keys.is_empty() {
+            return Err(ConfigError::EmptyJwks);
+        }
+
+        Ok(Self {
+            jwks,
+            issuer,
+            audience,
+        })
+    }
+
+    pub fn verify(&self, token: &str) -> Result<Principal, AuthError> {
+        let header = decode_header(token).map_err(|_| AuthError::Unauthenticated)?;
+
+        // Without a key id, a set of a single key is unambiguous
+        let jwk = match &header.kid {
+            Some(kid) => self.jwks.find(kid),
+            None if self.jwks.keys.len() == 1 => self.jwks.keys.first(),
+            None => None,
+        }
+        .ok_or(AuthError::Unauthenticated)?;
+
+        // The algorithm of the token must be the one of the key, if the key pins it
+        if let Some(key_algorithm) = jwk.common.key_algorithm {
+            // The encryption algorithms of keys don't sign tokens
+            let algorithm = Algorithm::from_str(&key_algorithm.to_string())
+                .map_err(|_| AuthError::Unauthenticated)?;
+            if algorithm != header.alg {
+                return Err(AuthError::Unauthenticated);
+            }
+        }
+
+        let key = DecodingKey::from_jwk(jwk).map_err(|_| AuthError::Unauthenticated)?;
+        let mut validation = Validation::new(header.alg);
+        // The issuer and the audience are only checked when present, unless required
+        let mut required_claims = vec!["exp", "sub"];
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+            required_claims.push("iss");
+        }
+        match &self.audience {
+            Some(audience) => {
+                validation.set_audience(&[audience]);
+                required_claims.push("aud");
+            }
+            None => validation.validate_aud = false,
+        }
+        validation.set_required_spec_claims(&required_claims);
+
+        let claims = decode::<Claims>(token, &key, &validation)
+            .map_err(|_| AuthError::Unauthenticated)?
+            .claims;
+
+        let capabilities = claims
+            .scope
+            .iter()
+            .flat_map(|scope| scope.split_whitespace())
+            .chain(claims.capabilities.iter().map(String::as_str))
+            .filter_map(|capability| capability.parse().ok())
+            .collect::<HashSet<Capability>>();
+
+        Ok(Principal {
+            id: format!("jwt:{}", claims.sub),
+            capabilities,
+            rate_limit: claims.rate_limit,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde_json::{json, Value};
+    use tempfile::NamedTempFile;
+
+    use super::*;
+
+    const SECRET: &[u8] = b"torii-test-secret-one-0123456789";
+    const OTHER_SECRET: &[u8] = b"torii-test-secret-two-0123456789";
+
+    // The secrets, base64url encoded, pinned to HS256
+    fn key(kid: &str, secret: &str) -> Value {
+        json!({ "kty": "oct", "kid": kid, "alg": "HS256", "k": secret })
+    }
+
+    fn verifier(keys: Vec<Value>, issuer: Option<&str>, audience: Option<&str>) -> JwtVerifier {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(json!({ "keys": keys }).to_string().as_bytes())
+            .unwrap();
+
+        JwtVerifier::from_file(
+            file.path(),
+            issuer.map(str::to_string),
+            audience.map(str::to_string),
+        )
+        .unwrap()
+    }
+
+    fn one_key() -> JwtVerifier {
+        verifier(
+            vec![key("k1", "dG9yaWktdGVzdC1zZWNyZXQtb25lLTAxMjM0NTY3ODk")],
+            None,
+            None,
+        )
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    fn token(kid: Option<&str>, algorithm: Algorithm, secret: &[u8], claims: Value) -> String {
+        let mut header = Header::new(algorithm);
+        header.kid = kid.map(str::to_string);
+        encode(&header, &claims, &EncodingKey::from_secret(secret)).unwrap()
+    }
+
+    #[test]
+    fn test_verify() {
+        let verifier = one_key();
+
+        let principal = verifier
+            .verify(&token(
+                Some("k1"),
+                Algorithm::HS256,
+                SECRET,
+                json!({
+                    "sub": "alice",
+                    "exp": now() + 3600,
+                    "scope": "read sql unknown",
+                    "capabilities": ["mcp"],
+                    "rate_limit": 30
+                }),
+            ))
+            .unwrap();
+        assert_eq!(
+            principal,
+            Principal {
+                id: "jwt:alice".to_string(),
+                capabilities: HashSet::from([Capability::Read, Capability::Sql, Capability::Mcp]),
+                rate_limit: Some(30),
+            }
+        );
+
+        // a single key is used without a key id
+        let principal = verifier
+            .verify(&token(
+                None,
+                Algorithm::HS256,
+                SECRET,
+                json!({ "sub": "bob", "exp": now() + 3600 }),
+            ))
+            .unwrap();
+        assert_eq!(principal.id, "jwt:bob");
+        assert!(principal.capabilities.is_empty());
+        assert_eq!(principal.rate_limit, None);
+
+        // a token signed with another secret
+        assert_eq!(
+            verifier.verify(&token(
+                Some("k1"),
+                Algorithm::HS256,
+                OTHER_SECRET,
+                json!({ "sub": "alice", "exp": now() + 3600 }),
+            )),
+            Err(AuthError::Unauthenticated)
+        );
+        assert_eq!(
+            verifier.verify("not a token"),
+            Err(AuthError::Unauthenticated)
+        );
+    }
+
+    #[test]
+    fn test_verify_claims() {
+        let verifier = one_key();
+        let verify = |claims| verifier.verify(&token(Some("k1"), Algorithm::HS256, SECRET, claims));
+
+        // expired, past the leeway of a minute
+        assert_eq!(
+            verify(json!({ "sub": "alice", "exp": now() - 3600 })),
+            Err(AuthError::Unauthenticated)
+        );
+        assert_eq!(
+            verify(json!({ "sub": "alice" })),
+            Err(AuthError::Unauthenticated)
+        );
+        assert_eq!(
+            verify(json!({ "exp": now() + 3600 })),
+            Err(AuthError::Unauthenticated)
+        );
+    }
+
+    #[test]
+    fn test_verify_key() {
+        let verifier = verifier(
+            vec![
+                key("k1", "dG9yaWktdGVzdC1zZWNyZXQtb25lLTAxMjM0NTY3ODk"),
+                key("k2", "dG9yaWktdGVzdC1zZWNyZXQtdHdvLTAxMjM0NTY3ODk"),
+            ],
+            None,
+            None,
+        );
+        let claims = json!({ "sub": "alice", "exp": now() + 3600 });
+
+        assert!(verifier
+            .verify(&token(
+                Some("k2"),
+                Algorithm::HS256,
+                OTHER_SECRET,
+                claims.clone()
+            ))
+            .is_ok());
+        // the key of the id is used, not any key of the set
+        assert_eq!(
+            verifier.verify(&token(Some("k2"), Algorithm::HS256, SECRET, claims.clone())),
+            Err(AuthError::Unauthenticated)
+        );
+        assert_eq!(
+            verifier.verify(&token(Some("k3"), Algorithm::HS256, SECRET, claims.clone())),
+            Err(AuthError::Unauthenticated)
+        );
+        // several keys are ambiguous without a key id
+        assert_eq!(
+            verifier.verify(&token(None, Algorithm::HS256, SECRET, claims.clone())),
+            Err(AuthError::Unauthenticated)
+        );
+        // the algorithm pinned by the key, even if the token verifies with another one
+        assert_eq!(
+            verifier.verify(&token(Some("k1"), Algorithm::HS384, SECRET, claims)),
+            Err(AuthError::Unauthenticated)
+        );
+    }
+
+    #[test]
+    fn test_verify_issuer_and_audience() {
+        let verifier = verifier(
+            vec![key("k1", "dG9yaWktdGVzdC1zZWNyZXQtb25lLTAxMjM0NTY3ODk")],
+            Some("https://issuer.example"),
+            Some("torii"),
+        );
+        let verify = |claims| verifier.verify(&token(Some("k1"), Algorithm::HS256, SECRET, claims));
+
+        assert!(verify(json!({
+            "sub": "alice",
+            "exp": now() + 3600,
+            "iss": "https://issuer.example",
+            "aud": "torii"
+        }))
+        .is_ok());
+        assert_eq!(
+            verify(json!({
+                "sub": "alice",
+                "exp": now() + 3600,
+                "iss": "https://other.example",
+                "aud": "torii"
+            })),
+            Err(AuthError::Unauthenticated)
+        );
+        assert_eq!(
+            verify(json!({
+                "sub": "alice",
+                "exp": now() + 3600,
+                "iss": "https://issuer.example",
+                "aud": "other"
+            })),
+            Err(AuthError::Unauthenticated)
+        );
+        // the issuer and the audience of the verifier are required
+        assert_eq!(
+            verify(json!({ "sub": "alice", "exp": now() + 3600, "aud": "torii" })),
+            Err(AuthError::Unauthenticated)
+        );
+        assert_eq!(
+            verify(json!({
+                "sub": "alice",
+                "exp": now() + 3600,
+                "iss": "https://issuer.example"
+            })),
+            Err(AuthError::Unauthenticated)
+        );
+    }
+}
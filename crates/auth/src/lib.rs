@@ -0,0 +1,435 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+pub mod jwt;
+pub mod quota;
+
+use jwt::JwtVerifier;
+use quota::Quotas;
+
+/// Header carrying a static API key, as an alternative to `Authorization: Bearer <key>`.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Query parameter carrying a credential, for clients which can't set headers like browser
+/// websockets.
+pub const API_KEY_PARAM: &str = "api_key";
+
+/// What a principal is allowed to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// Queries over HTTP, GraphQL and gRPC.
+    Read,
+    /// Streaming subscriptions over GraphQL and gRPC.
+    Subscribe,
+    /// Publishing offchain messages.
+    PublishMessage,
+    /// Raw SQL queries.
+    Sql,
+    /// The MCP endpoint.
+    Mcp,
+    /// The admin API operating the indexer. Never granted when authentication is disabled.
+    Admin,
+    /// The change log of a primary, for followers.
+    Replicate,
+}
+
+impl Capability {
+    pub const ALL: [Capability; 7] = [
+        Capability::Read,
+        Capability::Subscribe,
+        Capability::PublishMessage,
+        Capability::Sql,
+        Capability::Mcp,
+        Capability::Admin,
+        Capability::Replicate,
+    ];
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Capability::Read => "read",
+            Capability::Subscribe => "subscribe",
+            Capability::PublishMessage => "publish_message",
+            Capability::Sql => "sql",
+            Capability::Mcp => "mcp",
+            Capability::Admin => "admin",
+            Capability::Replicate => "replicate",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Capability {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Capability::ALL
+            .into_iter()
+            .find(|capability| capability.to_string() == s.trim())
+            .ok_or_else(|| ConfigError::UnknownCapability(s.to_string()))
+    }
+}
+
+/// A static API key, with its capabilities and its quota of requests per minute.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub key: String,
+    pub capabilities: Vec<Capability>,
+    #[serde(default)]
+    pub rate_limit: Option<u32>,
+}
+
+impl FromStr for ApiKey {
+    type Err = ConfigError;
+
+    /// Parses `<key>:<capability>,<capability>[:<requests per minute>]`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().split(':');
+        let key = parts.next().unwrap_or_default();
+        let capabilities = parts.next().unwrap_or_default();
+        let rate_limit = parts.next();
+        if key.is_empty() || capabilities.is_empty() || parts.next().is_some() {
+            return Err(ConfigError::InvalidApiKey(s.to_string()));
+        }
+
+        Ok(Self {
+            key: key.to_string(),
+            capabilities: capabilities
+                .split(',')
+                .map(Capability::from_str)
+                .collect::<Result<_, _>>()?,
+            rate_limit: rate_limit
+                .map(|rate_limit| {
+                    rate_limit
+                        .parse()
+                        .map_err(|_| ConfigError::InvalidApiKey(s.to_string()))
+                })
+                .transpose()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AuthConfig {
+    pub api_keys: Vec<ApiKey>,
+    /// JWKS file of the keys verifying JWTs.
+    pub jwks_path: Option<PathBuf>,
+    pub jwt_issuer: Option<String>,
+    pub jwt_audience: Option<String>,
+    /// Quota of requests per minute of the principals which don't set their own.
+    pub default_rate_limit: Option<u32>,
+    /// Capabilities of the requests without credentials.
+    pub anonymous: Vec<Capability>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Unknown capability: {0}")]
+    UnknownCapability(String),
+    #[error("Invalid API key, expected <key>:<capability>,...[:<requests per minute>]: {0}")]
+    InvalidApiKey(String),
+    #[error("Failed to read JWKS: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid JWKS: {0}")]
+    Jwks(#[from] serde_json::Error),
+    #[error("JWKS has no keys")]
+    EmptyJwks,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AuthError {
+    #[error("Missing or invalid credentials")]
+    Unauthenticated,
+    #[error("Missing capability: {0}")]
+    Forbidden(Capability),
+    #[error("Rate limit exceeded, retry after {}s", .retry_after.as_secs().max(1))]
+    RateLimited { retry_after: Duration },
+}
+
+/// The authenticated client of a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal {
+    pub id: String,
+    pub capabilities: HashSet<Capability>,
+    pub rate_limit: Option<u32>,
+}
+
+impl Principal {
+    fn anonymous(capabilities: HashSet<Capability>) -> Self {
+        Self {
+            id: "anonymous".to_string(),
+            capabilities,
+            rate_limit: None,
+        }
+    }
+}
+
+/// An API key as the authenticator keeps it, only its digest is compared to the credentials.
+#[derive(Debug)]
+struct StoredApiKey {
+    digest: [u8; 32],
+    /// Identifies the key in quotas and logs, without revealing it.
+    id: String,
+    capabilities: HashSet<Capability>,
+    rate_limit: Option<u32>,
+}
+
+impl From<ApiKey> for StoredApiKey {
+    fn from(api_key: ApiKey) -> Self {
+        let digest: [u8; 32] = Sha256::digest(api_key.key.as_bytes()).into();
+        let id = digest[..8]
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>();
+
+        Self {
+            digest,
+            id: format!("key:{}", id),
+            capabilities: api_key.capabilities.into_iter().collect(),
+            rate_limit: api_key.rate_limit,
+        }
+    }
+}
+
+/// Authenticates the credentials of requests and enforces the capabilities and quotas of their
+/// principal. Without API keys nor JWKS, every request is allowed.
+#[derive(Debug, Default)]
+pub struct Authenticator {
+    api_keys: Vec<StoredApiKey>,
+    jwt: Option<JwtVerifier>,
+    default_rate_limit: Option<u32>,
+    anonymous: HashSet<Capability>,
+    quotas: Quotas,
+}
+
+impl Authenticator {
+    pub fn new(config: AuthConfig) -> Result<Self, ConfigError> {
+        let jwt = config
+            .jwks_path
+            .map(|path| JwtVerifier::from_file(&path, config.jwt_issuer, config.jwt_audience))
+            .transpose()?;
+
+        Ok(Self {
+            api_keys: config
+                .api_keys
+                .into_iter()
+                .map(StoredApiKey::from)
+                .collect(),
+            jwt,
+            default_rate_limit: config.default_rate_limit,
+            anonymous: config.anonymous.into_iter().collect(),
+            quotas: Quotas::default(),
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.api_keys.is_empty() || self.jwt.is_some()
+    }
+
+    /// Authenticates a credential, an API key or a JWT, and checks that its principal has
+    /// `capability` and is within its quota.
+    pub fn authorize(
+        &self,
+        credential: Option<&str>,
+        capability: Capability,
+    ) -> Result<Principal, AuthError> {
+        if !self.is_enabled() {
+            // The admin API requires credentials, it is only served when authentication is set up
+            if capability == Capability::Admin {
+                return Err(AuthError::Unauthenticated);
+            }
+            return Ok(Principal::anonymous(
+                Capability::ALL
+                    .into_iter()
+                    .filter(|capability| *capability != Capability::Admin)
+                    .collect(),
+            ));
+        }
+
+        let principal = match credential {
+            None => {
+                // Anonymous requests have no quota, only their capabilities can be restricted
+                return if self.anonymous.contains(&capability) {
+                    Ok(Principal::anonymous(self.anonymous.clone()))
+                } else {
+                    Err(AuthError::Unauthenticated)
+                };
+            }
+            Some(credential) => self.authenticate(credential)?,
+        };
+
+        if !principal.capabilities.contains(&capability) {
+            return Err(AuthError::Forbidden(capability));
+        }
+
+        if let Some(rate_limit) = principal.rate_limit.or(self.default_rate_limit) {
+            self.quotas
+                .check(&principal.id, rate_limit)
+                .map_err(|retry_after| AuthError::RateLimited { retry_after })?;
+        }
+
+        Ok(principal)
+    }
+
+    fn authenticate(&self, credential: &str) -> Result<Principal, AuthError> {
+        // Every key is compared, in constant time, so that the timing doesn't tell how close a
+        // credential is to one of them
+        let digest: [u8; 32] = Sha256::digest(credential.as_bytes()).into();
+        let api_key = self.api_keys.iter().fold(None, |found, api_key| {
+            if bool::from(api_key.digest.ct_eq(&digest)) {
+                Some(api_key)
+            } else {
+                found
+            }
+        });
+        if let Some(api_key) = api_key {
+            return Ok(Principal {
+                id: api_key.id.clone(),
+                capabilities: api_key.capabilities.clone(),
+                rate_limit: api_key.rate_limit,
+            });
+        }
+
+        match &self.jwt {
+            Some(jwt) => jwt.verify(credential),
+            None => Err(AuthError::Unauthenticated),
+        }
+    }
+}
+
+/// The credential of a request, from its `Authorization: Bearer` header or else its API key
+/// header.
+pub fn credential<'a>(authorization: Option<&'a str>, api_key: Option<&'a str>) -> Option<&'a str> {
+    authorization
+        .and_then(|authorization| {
+            authorization
+                .strip_prefix("Bearer ")
+                .or_else(|| authorization.strip_prefix("bearer "))
+        })
+        .or(api_key)
+        .map(str::trim)
+        .filter(|credential| !credential.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn authenticator() -> Authenticator {
+        Authenticator::new(AuthConfig {
+            api_keys: vec![
+                "partner:read,subscribe:2".parse().unwrap(),
+                "admin:read,subscribe,publish_message,sql,mcp"
+                    .parse()
+                    .unwrap(),
+            ],
+            anonymous: vec![Capability::Read],
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_api_key() {
+        let key: ApiKey = "partner:read,sql:60".parse().unwrap();
+        assert_eq!(key.key, "partner");
+        assert_eq!(key.capabilities, vec![Capability::Read, Capability::Sql]);
+        assert_eq!(key.rate_limit, Some(60));
+
+        let key: ApiKey = "partner:publish_message".parse().unwrap();
+        assert_eq!(key.capabilities, vec![Capability::PublishMessage]);
+        assert_eq!(key.rate_limit, None);
+
+        assert!("partner".parse::<ApiKey>().is_err());
+        assert!(":read".parse::<ApiKey>().is_err());
+        assert!("partner:write".parse::<ApiKey>().is_err());
+        assert!("partner:read:many".parse::<ApiKey>().is_err());
+    }
+
+    #[test]
+    fn test_disabled() {
+        let authenticator = Authenticator::default();
+        assert!(!authenticator.is_enabled());
+        assert!(authenticator.authorize(None, Capability::Sql).is_ok());
+        assert_eq!(
+            authenticator.authorize(None, Capability::Admin),
+            Err(AuthError::Unauthenticated)
+        );
+    }
+
+    #[test]
+    fn test_authorize() {
+        let authenticator = authenticator();
+
+        assert!(authenticator.authorize(None, Capability::Read).is_ok());
+        assert_eq!(
+            authenticator.authorize(None, Capability::Subscribe),
+            Err(AuthError::Unauthenticated)
+        );
+        assert_eq!(
+            authenticator.authorize(Some("unknown"), Capability::Read),
+            Err(AuthError::Unauthenticated)
+        );
+        assert_eq!(
+            authenticator.authorize(Some("partner"), Capability::Sql),
+            Err(AuthError::Forbidden(Capability::Sql))
+        );
+        assert!(authenticator
+            .authorize(Some("admin"), Capability::Sql)
+            .is_ok());
+        assert_eq!(
+            authenticator.authorize(Some("admin"), Capability::Admin),
+            Err(AuthError::Forbidden(Capability::Admin))
+        );
+
+        // The quota of the partner key is 2 requests per minute
+        assert!(authenticator
+            .authorize(Some("partner"), Capability::Read)
+            .is_ok());
+        assert!(authenticator
+            .authorize(Some("partner"), Capability::Subscribe)
+            .is_ok());
+        assert!(matches!(
+            authenticator.authorize(Some("partner"), Capability::Read),
+            Err(AuthError::RateLimited { .. })
+        ));
+        assert!(authenticator
+            .authorize(Some("admin"), Capability::Read)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_principal_id() {
+        let authenticator = authenticator();
+
+        let principal = authenticator
+            .authorize(Some("partner"), Capability::Read)
+            .unwrap();
+        // The first bytes of the SHA-256 digest of the key, never the key itself
+        assert_eq!(principal.id, "key:7f3fa48ca8856781");
+        assert!(!principal.id.contains("partner"));
+
+        assert_eq!(
+            authenticator.authorize(Some("partne"), Capability::Read),
+            Err(AuthError::Unauthenticated)
+        );
+    }
+
+    #[test]
+    fn test_credential() {
+        assert_eq!(credential(Some("Bearer token"), None), Some("token"));
+        assert_eq!(credential(Some("Bearer token"), Some("key")), Some("token"));
+        assert_eq!(credential(Some("Basic abc"), Some("key")), Some("key"));
+        assert_eq!(credential(None, Some(" ")), None);
+        assert_eq!(credential(None, None), None);
+    }
+}
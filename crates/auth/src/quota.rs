@@ -0,0 +1,85 @@
+use std::time::{Duration, Instant};
+
+use dashmap::DashMap;
+
+/// Token buckets of the principals, refilled continuously up to their quota per minute.
+#[derive(Debug, Default)]
+pub struct Quotas {
+    buckets: DashMap<String, Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl Quotas {
+    /// Takes a token from the bucket of `principal`, or returns the time until one is available.
+    pub fn check(&self, principal: &str, per_minute: u32) -> Result<(), Duration> {
+        self.check_at(principal, per_minute, Instant::now())
+    }
+
+    fn check_at(&self, principal: &str, per_minute: u32, now: Instant) -> Result<(), Duration> {
+        if per_minute == 0 {
+            return Err(Duration::from_secs(60));
+        }
+
+        let capacity = per_minute as f64;
+        let rate = capacity / 60.0;
+
+        let mut bucket = self
+            .buckets
+            .entry(principal.to_string())
+            .or_insert_with(|| Bucket {
+                tokens: capacity,
+                updated_at: now,
+            });
+
+        let elapsed = now.saturating_duration_since(bucket.updated_at);
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * rate).min(capacity);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / rate))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quota_refill() {
+        let quotas = Quotas::default();
+        let start = Instant::now();
+
+        for _ in 0..60 {
+            assert!(quotas.check_at("key", 60, start).is_ok());
+        }
+        let retry_after = quotas.check_at("key", 60, start).unwrap_err();
+        assert!(retry_after <= Duration::from_secs(1));
+
+        // Other principals have their own bucket
+        assert!(quotas.check_at("other", 60, start).is_ok());
+
+        // A token per second is refilled
+        assert!(quotas
+            .check_at("key", 60, start + Duration::from_secs(1))
+            .is_ok());
+        assert!(quotas
+            .check_at("key", 60, start + Duration::from_secs(1))
+            .is_err());
+
+        // Never more than the quota
+        let later = start + Duration::from_secs(3600);
+        for _ in 0..60 {
+            assert!(quotas.check_at("key", 60, later).is_ok());
+        }
+        assert!(quotas.check_at("key", 60, later).is_err());
+    }
+}
@@ -0,0 +1,285 @@
+//! Bridges between the memory broker of a process and an external broker.
+
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use metrics::counter;
+use tokio::task::JoinHandle;
+use torii_proto::schema::EntityWithMetadata;
+use torii_proto::{
+    AchievementProgression, Activity, AggregationEntry, Contract, EventWithMetadata, Model, Token,
+    TokenBalance, TokenTransfer, Transaction,
+};
+use tracing::warn;
+
+use crate::types::{Topic, Update};
+use crate::{Broker, BrokerError, MemoryBroker};
+
+const LOG_TARGET: &str = "torii::broker::bridge";
+
+/// Number of times the publication of an update is attempted before dropping it.
+pub const PUBLISH_ATTEMPTS: u32 = 3;
+/// Delay before the first retry of a publication, doubled on every retry.
+pub const RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// Publishes the updates of type `T` of this process to `broker`, until the process ends.
+///
+/// The publication of an update is retried, the next updates waiting for it to keep their
+/// order. An update still failing after [`PUBLISH_ATTEMPTS`] is dropped and counted in the
+/// `torii_broker_dropped_updates_total` metric.
+pub async fn forward<T, B>(broker: Arc<B>) -> Result<(), BrokerError>
+where
+    T: Topic + Debug + Clone + Send + Sync + 'static,
+    B: Broker<T> + ?Sized,
+{
+    let mut updates = MemoryBroker::<Update<T>>::subscribe_raw();
+    while let Some(update) = updates.next().await {
+        let mut delay = RETRY_DELAY;
+        for attempt in 1..=PUBLISH_ATTEMPTS {
+            match broker.publish(update.clone()).await {
+                Ok(()) => break,
+                Err(e) if attempt < PUBLISH_ATTEMPTS => {
+                    warn!(target: LOG_TARGET, topic = T::TOPIC, attempt, error = ?e, "Forwarding update, retrying.");
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+                Err(e) => {
+                    // A lost update is better than a stalled indexer
+                    counter!("torii_broker_dropped_updates_total", "topic" => T::TOPIC)
+                        .increment(1);
+                    warn!(target: LOG_TARGET, topic = T::TOPIC, error = ?e, "Forwarding update, dropped.");
+                }
+            }
+        }
+    }
+
+    Err(BrokerError::Closed(T::TOPIC))
+}
+
+/// Publishes the updates of type `T` of `broker` to the subscribers of this process.
+pub async fn relay<T, B>(broker: Arc<B>) -> Result<(), BrokerError>
+where
+    T: Topic + Debug + Clone + Send + Sync + 'static,
+    B: Broker<T> + ?Sized,
+{
+    let mut updates = broker.subscribe_raw().await?;
+    while let Some(update) = updates.next().await {
+        MemoryBroker::publish(update);
+    }
+
+    Err(BrokerError::Closed(T::TOPIC))
+}
+
+/// A broker of every update type of Torii.
+pub trait UpdateBroker:
+    Broker<EntityWithMetadata<false>>
+    + Broker<EntityWithMetadata<true>>
+    + Broker<Contract>
+    + Broker<Model>
+    + Broker<Token>
+    + Broker<TokenBalance>
+    + Broker<TokenTransfer>
+    + Broker<EventWithMetadata>
+    + Broker<Transaction>
+    + Broker<AggregationEntry>
+    + Broker<Activity>
+    + Broker<AchievementProgression>
+    + 'static
+{
+}
+
+impl<B> UpdateBroker for B where
+    B: Broker<EntityWithMetadata<false>>
+        + Broker<EntityWithMetadata<true>>
+        + Broker<Contract>
+        + Broker<Model>
+        + Broker<Token>
+        + Broker<TokenBalance>
+        + Broker<TokenTransfer>
+        + Broker<EventWithMetadata>
+        + Broker<Transaction>
+        + Broker<AggregationEntry>
+        + Broker<Activity>
+        + Broker<AchievementProgression>
+        + 'static
+{
+}
+
+macro_rules! spawn_all {
+    ($bridge:ident, $broker:expr) => {
+        vec![
+            tokio::spawn($bridge::<EntityWithMetadata<false>, _>($broker.clone())),
+            tokio::spawn($bridge::<EntityWithMetadata<true>, _>($broker.clone())),
+            tokio::spawn($bridge::<Contract, _>($broker.clone())),
+            tokio::spawn($bridge::<Model, _>($broker.clone())),
+            tokio::spawn($bridge::<Token, _>($broker.clone())),
+            tokio::spawn($bridge::<TokenBalance, _>($broker.clone())),
+            tokio::spawn($bridge::<TokenTransfer, _>($broker.clone())),
+            tokio::spawn($bridge::<EventWithMetadata, _>($broker.clone())),
+            tokio::spawn($bridge::<Transaction, _>($broker.clone())),
+            tokio::spawn($bridge::<AggregationEntry, _>($broker.clone())),
+            tokio::spawn($bridge::<Activity, _>($broker.clone())),
+            tokio::spawn($bridge::<AchievementProgression, _>($broker.clone())),
+        ]
+    };
+}
+
+/// Forwards every update of this process to `broker`, for an indexer sharing its updates with
+/// other processes.
+pub fn forward_all<B: UpdateBroker>(broker: Arc<B>) -> Vec<JoinHandle<Result<(), BrokerError>>> {
+    spawn_all!(forward, broker)
+}
+
+/// Relays every update of `broker` to the subscribers of this process, for a process serving the
+/// database of an indexer running elsewhere.
+pub fn relay_all<B: UpdateBroker>(broker: Arc<B>) -> Vec<JoinHandle<Result<(), BrokerError>>> {
+    spawn_all!(relay, broker)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use futures_channel::mpsc::{self, UnboundedReceiver};
+    use futures_util::stream::BoxStream;
+    use tokio::time::timeout;
+
+    use super::*;
+
+    /// Fails the publications of the updates in `failures`, once per occurrence, and records the
+    /// others. Its subscription streams the updates sent to `updates`.
+    #[derive(Debug)]
+    struct FlakyBroker<T> {
+        failures: Mutex<Vec<T>>,
+        published: Mutex<Vec<T>>,
+        updates: Mutex<Option<UnboundedReceiver<Update<T>>>>,
+    }
+
+    impl<T> FlakyBroker<T> {
+        fn new(failures: Vec<T>) -> Self {
+            Self {
+                failures: Mutex::new(failures),
+                published: Mutex::new(Vec::new()),
+                updates: Mutex::new(None),
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl<T> Broker<T> for FlakyBroker<T>
+    where
+        T: Topic + PartialEq + Debug + Clone + Send + Sync + 'static,
+    {
+        async fn publish(&self, update: Update<T>) -> Result<(), BrokerError> {
+            let mut failures = self.failures.lock().unwrap();
+            if let Some(i) = failures.iter().position(|f| *f == update.inner) {
+                failures.remove(i);
+                return Err(BrokerError::Closed(T::TOPIC));
+            }
+            self.published.lock().unwrap().push(update.inner);
+            Ok(())
+        }
+
+        async fn subscribe_raw(&self) -> Result<BoxStream<'static, Update<T>>, BrokerError> {
+            Ok(self.updates.lock().unwrap().take().unwrap().boxed())
+        }
+    }
+
+    macro_rules! message {
+        ($name:ident, $topic:literal) => {
+            #[derive(Debug, Clone, PartialEq)]
+            struct $name(u32);
+
+            impl Topic for $name {
+                const TOPIC: &'static str = $topic;
+            }
+        };
+    }
+
+    message!(Retried, "retried");
+    message!(Dropped, "dropped");
+    message!(Relayed, "relayed");
+
+    /// Forwards the updates of type `T` to `broker`, once it subscribed to them.
+    async fn spawn_forward<T>(broker: Arc<FlakyBroker<T>>)
+    where
+        T: Topic + PartialEq + Debug + Clone + Send + Sync + 'static,
+    {
+        tokio::spawn(forward::<T, _>(broker));
+        while MemoryBroker::<Update<T>>::subscriber_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    /// Waits for `broker` to have published `count` updates.
+    async fn published<T: Clone>(broker: &FlakyBroker<T>, count: usize) -> Vec<T> {
+        timeout(Duration::from_secs(5), async {
+            loop {
+                let published = broker.published.lock().unwrap().clone();
+                if published.len() >= count {
+                    return published;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_forward_retries_in_order() {
+        let broker = Arc::new(FlakyBroker::new(vec![Retried(1), Retried(1)]));
+        spawn_forward(broker.clone()).await;
+
+        for i in 0..3 {
+            MemoryBroker::publish(Update::new(Retried(i), false));
+        }
+
+        assert_eq!(
+            published(&broker, 3).await,
+            vec![Retried(0), Retried(1), Retried(2)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_forward_drops_failing_update() {
+        let failures = vec![Dropped(0); PUBLISH_ATTEMPTS as usize];
+        let broker = Arc::new(FlakyBroker::new(failures));
+        spawn_forward(broker.clone()).await;
+
+        MemoryBroker::publish(Update::new(Dropped(0), false));
+        MemoryBroker::publish(Update::new(Dropped(1), false));
+
+        assert_eq!(published(&broker, 1).await, vec![Dropped(1)]);
+        assert!(broker.failures.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_relay() {
+        let (tx, rx) = mpsc::unbounded();
+        let broker = Arc::new(FlakyBroker::<Relayed>::new(vec![]));
+        *broker.updates.lock().unwrap() = Some(rx);
+        let mut updates = MemoryBroker::<Update<Relayed>>::subscribe_raw();
+        let relay = tokio::spawn(relay::<Relayed, _>(broker));
+
+        tx.unbounded_send(Update::new(Relayed(0), true)).unwrap();
+        tx.unbounded_send(Update::new(Relayed(1), false)).unwrap();
+        for (inner, optimistic) in [(Relayed(0), true), (Relayed(1), false)] {
+            let update = timeout(Duration::from_secs(5), updates.next())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!((update.inner, update.optimistic), (inner, optimistic));
+        }
+
+        // The relay ends with the subscription to the external broker
+        drop(tx);
+        let result = timeout(Duration::from_secs(5), relay)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(result, Err(BrokerError::Closed("relayed"))));
+    }
+}
@@ -2,12 +2,56 @@
 //!
 //! This crate provides different message broker implementations for
 //! real-time communication and subscription management.
+//!
+//! [`MemoryBroker`] fans updates out within a process. An external broker like [`NatsBroker`]
+//! carries them across processes: the indexer forwards its updates to it with
+//! [`bridge::forward_all`], and the processes serving subscriptions relay them to their own
+//! memory broker with [`bridge::relay_all`].
 
+pub mod bridge;
 pub mod memory;
+pub mod nats;
 pub mod types;
 
+use std::fmt::Debug;
+
+use futures_util::stream::BoxStream;
+
 // Re-export commonly used types from memory module
 pub use memory::{MemoryBroker, Senders};
+pub use nats::NatsBroker;
+
+use crate::types::Update;
+
+#[derive(Debug, thiserror::Error)]
+pub enum BrokerError {
+    #[error(transparent)]
+    Serialization(#[from] serde_json::Error),
+    #[error(transparent)]
+    Connect(#[from] async_nats::ConnectError),
+    #[error(transparent)]
+    Publish(#[from] async_nats::PublishError),
+    #[error(transparent)]
+    Flush(#[from] async_nats::client::FlushError),
+    #[error(transparent)]
+    Subscribe(#[from] async_nats::SubscribeError),
+    #[error("Subscription to {0} closed")]
+    Closed(&'static str),
+}
+
+/// A broker of the updates of type `T`.
+#[async_trait::async_trait]
+pub trait Broker<T>: Send + Sync + Debug
+where
+    T: Debug + Clone + Send + Sync + 'static,
+{
+    /// Publishes an update to the subscribers. The updates published by a process are received
+    /// in the order they were published, so are the ones of an entity.
+    async fn publish(&self, update: Update<T>) -> Result<(), BrokerError>;
+
+    /// Stream of the updates published from now on, optimistic or not.
+    async fn subscribe_raw(&self) -> Result<BoxStream<'static, Update<T>>, BrokerError>;
+}
 
 #[cfg(test)]
 mod test;
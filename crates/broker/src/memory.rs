@@ -6,10 +6,15 @@ use std::task::{Context, Poll};
 
 use dashmap::DashMap;
 use futures_channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use futures_util::stream::BoxStream;
 use futures_util::{Stream, StreamExt};
 use slab::Slab;
+use tracing::info_span;
 
 use crate::types::Update;
+use crate::{Broker, BrokerError};
+
+const LOG_TARGET: &str = "torii::broker::memory";
 
 static SUBSCRIBERS: LazyLock<DashMap<TypeId, Box<dyn Any + Send + Sync>>> =
     LazyLock::new(Default::default);
@@ -55,12 +60,40 @@ pub struct MemoryBroker<U>(PhantomData<U>)
 where
     U: std::fmt::Debug + Clone + Send + Sync + 'static;
 
+impl<U> MemoryBroker<U>
+where
+    U: std::fmt::Debug + Clone + Send + Sync + 'static,
+{
+    /// A handle on the broker of the process, for the code generic over [`Broker`].
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<U> Default for MemoryBroker<U>
+where
+    U: std::fmt::Debug + Clone + Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> MemoryBroker<Update<T>>
 where
     T: std::fmt::Debug + Clone + Send + Sync + 'static,
 {
     /// Publish an update message that all subscription streams can receive.
-    pub fn publish(msg: Update<T>) {
+    pub fn publish(mut msg: Update<T>) {
+        // The subscribers deliver the update in the span of its publish
+        msg.span = info_span!(
+            target: LOG_TARGET,
+            parent: &msg.span,
+            "publish",
+            update = std::any::type_name::<T>(),
+            optimistic = msg.optimistic
+        );
+
         with_senders::<Update<T>, _, _>(|senders| {
             for (_, sender) in senders.0.iter_mut() {
                 sender.start_send(msg.clone()).ok();
@@ -99,6 +132,18 @@ where
         })
     }
 
+    /// Subscribe to the optimistic updates, or to the non-optimistic ones, keeping the span they
+    /// were published in to deliver them in.
+    pub fn subscribe_traced(optimistic: bool) -> impl Stream<Item = Update<T>> {
+        Self::subscribe_raw()
+            .filter(move |u| futures_util::future::ready(u.is_optimistic() == optimistic))
+    }
+
+    /// Number of open subscription streams of the specified subscription type.
+    pub fn subscriber_count() -> usize {
+        with_senders::<Update<T>, _, _>(|senders| senders.0.len())
+    }
+
     /// Execute the given function with the _subscribers_ of the specified subscription type.
     pub fn with_subscribers<F, R>(f: F) -> R
     where
@@ -107,3 +152,18 @@ where
         with_senders(f)
     }
 }
+
+#[async_trait::async_trait]
+impl<T> Broker<T> for MemoryBroker<Update<T>>
+where
+    T: std::fmt::Debug + Clone + Send + Sync + 'static,
+{
+    async fn publish(&self, update: Update<T>) -> Result<(), BrokerError> {
+        Self::publish(update);
+        Ok(())
+    }
+
+    async fn subscribe_raw(&self) -> Result<BoxStream<'static, Update<T>>, BrokerError> {
+        Ok(Self::subscribe_raw().boxed())
+    }
+}
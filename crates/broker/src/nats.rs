@@ -0,0 +1,228 @@
+//! A broker over [NATS](https://nats.io), to fan updates out across processes.
+//!
+//! The updates of a type are published as JSON on the subject `<prefix>.<topic>`. NATS delivers
+//! the messages of a connection in the order they were published, so a subscriber receives the
+//! updates of an entity in order.
+
+use std::fmt::Debug;
+
+use futures_util::stream::BoxStream;
+use futures_util::StreamExt;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tracing::warn;
+
+use crate::types::{Topic, Update};
+use crate::{Broker, BrokerError};
+
+const LOG_TARGET: &str = "torii::broker::nats";
+
+pub const DEFAULT_PREFIX: &str = "torii";
+
+#[derive(Clone)]
+pub struct NatsBroker {
+    client: async_nats::Client,
+    prefix: String,
+}
+
+impl Debug for NatsBroker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NatsBroker")
+            .field("prefix", &self.prefix)
+            .finish()
+    }
+}
+
+impl NatsBroker {
+    /// Connects to a NATS server, `nats://host:port`. Brokers with the same prefix exchange
+    /// updates.
+    pub async fn connect(url: &str, prefix: impl Into<String>) -> Result<Self, BrokerError> {
+        Ok(Self {
+            client: async_nats::connect(url).await?,
+            prefix: prefix.into(),
+        })
+    }
+
+    fn subject<T: Topic>(&self) -> String {
+        format!("{}.{}", self.prefix, T::TOPIC)
+    }
+
+    /// Waits for the updates published so far to be sent to the server.
+    pub async fn flush(&self) -> Result<(), BrokerError> {
+        Ok(self.client.flush().await?)
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> Broker<T> for NatsBroker
+where
+    T: Topic + Serialize + DeserializeOwned + Debug + Clone + Send + Sync + 'static,
+{
+    async fn publish(&self, update: Update<T>) -> Result<(), BrokerError> {
+        let payload = serde_json::to_vec(&update)?;
+        self.client
+            .publish(self.subject::<T>(), payload.into())
+            .await?;
+        Ok(())
+    }
+
+    async fn subscribe_raw(&self) -> Result<BoxStream<'static, Update<T>>, BrokerError> {
+        let subscriber = self.client.subscribe(self.subject::<T>()).await?;
+
+        Ok(subscriber
+            .filter_map(|message| async move {
+                match serde_json::from_slice::<Update<T>>(&message.payload) {
+                    Ok(update) => Some(update),
+                    Err(e) => {
+                        warn!(target: LOG_TARGET, subject = %message.subject, error = ?e, "Decoding update.");
+                        None
+                    }
+                }
+            })
+            .boxed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use serde::Deserialize;
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc;
+    use tokio::time::{timeout, Duration};
+
+    use super::*;
+
+    /// Subscriptions of the stand-in server, by subject, with their connection and id.
+    type Subscriptions = Arc<Mutex<HashMap<String, Vec<(mpsc::UnboundedSender<Vec<u8>>, String)>>>>;
+
+    /// A stand-in NATS server, speaking just enough of the protocol to route the messages
+    /// published to the exact subjects subscribed to.
+    async fn stand_in_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let subscriptions = Subscriptions::default();
+
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let subscriptions = subscriptions.clone();
+                tokio::spawn(async move {
+                    let (reader, mut writer) = stream.into_split();
+                    let (tx, mut rx) = mpsc::unbounded_channel::<Vec<u8>>();
+                    tokio::spawn(async move {
+                        while let Some(bytes) = rx.recv().await {
+                            if writer.write_all(&bytes).await.is_err() {
+                                break;
+                            }
+                        }
+                    });
+
+                    let info = format!(
+                        "INFO {{\"server_id\":\"stand-in\",\"version\":\"2.10.0\",\"go\":\"go1.22\",\
+                         \"host\":\"127.0.0.1\",\"port\":{},\"max_payload\":1048576,\"proto\":1,\
+                         \"headers\":true}}\r\n",
+                        addr.port()
+                    );
+                    tx.send(info.into_bytes()).unwrap();
+
+                    let mut reader = BufReader::new(reader);
+                    let mut line = String::new();
+                    while reader.read_line(&mut line).await.unwrap_or(0) > 0 {
+                        let args = line.split_whitespace().collect::<Vec<_>>();
+                        match args.first().map(|op| op.to_uppercase()).as_deref() {
+                            Some("PING") => tx.send(b"PONG\r\n".to_vec()).unwrap(),
+                            Some("SUB") => {
+                                let (subject, sid) = (args[1], args[args.len() - 1]);
+                                subscriptions
+                                    .lock()
+                                    .unwrap()
+                                    .entry(subject.to_string())
+                                    .or_default()
+                                    .push((tx.clone(), sid.to_string()));
+                            }
+                            Some("PUB") => {
+                                let subject = args[1].to_string();
+                                let size: usize = args[args.len() - 1].parse().unwrap();
+                                let mut payload = vec![0; size + 2];
+                                reader.read_exact(&mut payload).await.unwrap();
+                                payload.truncate(size);
+
+                                for (subscriber, sid) in subscriptions
+                                    .lock()
+                                    .unwrap()
+                                    .get(&subject)
+                                    .into_iter()
+                                    .flatten()
+                                {
+                                    let mut message =
+                                        format!("MSG {} {} {}\r\n", subject, sid, size)
+                                            .into_bytes();
+                                    message.extend_from_slice(&payload);
+                                    message.extend_from_slice(b"\r\n");
+                                    let _ = subscriber.send(message);
+                                }
+                            }
+                            _ => {}
+                        }
+                        line.clear();
+                    }
+                });
+            }
+        });
+
+        format!("nats://{}", addr)
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Position {
+        entity: u32,
+        x: u32,
+    }
+
+    impl Topic for Position {
+        const TOPIC: &'static str = "positions";
+    }
+
+    #[tokio::test]
+    async fn test_updates_across_connections_in_order() {
+        let url = stand_in_server().await;
+        let publisher = NatsBroker::connect(&url, DEFAULT_PREFIX).await.unwrap();
+        let subscriber = NatsBroker::connect(&url, DEFAULT_PREFIX).await.unwrap();
+        let other_prefix = NatsBroker::connect(&url, "other").await.unwrap();
+
+        let mut updates = Broker::<Position>::subscribe_raw(&subscriber)
+            .await
+            .unwrap();
+        let mut other_updates = Broker::<Position>::subscribe_raw(&other_prefix)
+            .await
+            .unwrap();
+        subscriber.flush().await.unwrap();
+        other_prefix.flush().await.unwrap();
+        // The subscriptions are on other connections than the publications
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let published = (0..10)
+            .map(|x| Update::new(Position { entity: x % 2, x }, x % 3 == 0))
+            .collect::<Vec<_>>();
+        for update in &published {
+            publisher.publish(update.clone()).await.unwrap();
+        }
+        publisher.flush().await.unwrap();
+
+        for update in published {
+            let received = timeout(Duration::from_secs(5), updates.next())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(received.inner, update.inner);
+            assert_eq!(received.optimistic, update.optimistic);
+        }
+        assert!(timeout(Duration::from_millis(100), other_updates.next())
+            .await
+            .is_err());
+    }
+}
@@ -313,5 +313,9 @@ mod tests {
 
         // Should have 2 more subscribers now
         assert_eq!(count_with_subscribers, initial_count + 2);
+        assert_eq!(
+            MemoryBroker::<Update<WithSubscribersMsg>>::subscriber_count(),
+            count_with_subscribers
+        );
     }
 }
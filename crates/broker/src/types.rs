@@ -1,12 +1,32 @@
-#[derive(Debug, Clone)]
+use serde::{Deserialize, Serialize};
+use tracing::Span;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Update<T> {
     pub inner: T,
     pub optimistic: bool,
+    /// Span the update was published in, the parent of the spans of its delivery. It isn't
+    /// shared with the other processes.
+    #[serde(skip, default = "Span::none")]
+    pub span: Span,
 }
 
 impl<T> Update<T> {
     pub fn new(inner: T, optimistic: bool) -> Self {
-        Self { inner, optimistic }
+        Self {
+            inner,
+            optimistic,
+            span: Span::current(),
+        }
+    }
+
+    /// An update published in `span` rather than in the current span.
+    pub fn with_span(inner: T, optimistic: bool, span: Span) -> Self {
+        Self {
+            inner,
+            optimistic,
+            span,
+        }
     }
 
     pub fn into_inner(self) -> T {
@@ -50,3 +70,33 @@ pub type TransactionUpdate = Update<torii_proto::Transaction>;
 pub type AggregationUpdate = Update<torii_proto::AggregationEntry>;
 pub type ActivityUpdate = Update<torii_proto::Activity>;
 pub type AchievementProgressionUpdate = Update<torii_proto::AchievementProgression>;
+
+/// Subject of the updates of a type on external brokers.
+pub trait Topic {
+    const TOPIC: &'static str;
+}
+
+macro_rules! topics {
+    ($($ty:ty => $topic:literal),* $(,)?) => {
+        $(
+            impl Topic for $ty {
+                const TOPIC: &'static str = $topic;
+            }
+        )*
+    };
+}
+
+topics! {
+    torii_proto::schema::EntityWithMetadata<false> => "entities",
+    torii_proto::schema::EntityWithMetadata<true> => "event_messages",
+    torii_proto::Contract => "contracts",
+    torii_proto::Model => "models",
+    torii_proto::Token => "tokens",
+    torii_proto::TokenBalance => "token_balances",
+    torii_proto::TokenTransfer => "token_transfers",
+    torii_proto::EventWithMetadata => "events",
+    torii_proto::Transaction => "transactions",
+    torii_proto::AggregationEntry => "aggregations",
+    torii_proto::Activity => "activities",
+    torii_proto::AchievementProgression => "achievement_progressions",
+}
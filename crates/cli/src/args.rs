@@ -94,6 +94,11 @@ pub struct ToriiArgs {
     #[merge]
     pub grpc: GrpcOptions,
 
+    #[cfg(feature = "server")]
+    #[command(flatten)]
+    #[merge]
+    pub graphql: GraphqlOptions,
+
     #[cfg(feature = "server")]
     #[command(flatten)]
     #[merge]
@@ -103,6 +108,26 @@ pub struct ToriiArgs {
     #[command(flatten)]
     #[merge]
     pub search: SearchOptions,
+
+    #[cfg(feature = "server")]
+    #[command(flatten)]
+    #[merge]
+    pub auth: AuthOptions,
+
+    #[cfg(feature = "server")]
+    #[command(flatten)]
+    #[merge]
+    pub replication: ReplicationOptions,
+
+    #[cfg(feature = "server")]
+    #[command(flatten)]
+    #[merge]
+    pub broker: BrokerOptions,
+
+    #[cfg(feature = "server")]
+    #[command(flatten)]
+    #[merge]
+    pub tracing: TracingOptions,
 }
 
 impl Default for ToriiArgs {
@@ -130,9 +155,19 @@ impl Default for ToriiArgs {
             #[cfg(feature = "server")]
             grpc: GrpcOptions::default(),
             #[cfg(feature = "server")]
+            graphql: GraphqlOptions::default(),
+            #[cfg(feature = "server")]
             messaging: MessagingOptions::default(),
             #[cfg(feature = "server")]
             search: SearchOptions::default(),
+            #[cfg(feature = "server")]
+            auth: AuthOptions::default(),
+            #[cfg(feature = "server")]
+            replication: ReplicationOptions::default(),
+            #[cfg(feature = "server")]
+            broker: BrokerOptions::default(),
+            #[cfg(feature = "server")]
+            tracing: TracingOptions::default(),
         }
     }
 }
@@ -148,6 +183,9 @@ impl ToriiArgs {
         // the CLI (self) takes precedence over the config file.
         self.merge(Some(&config));
         self.db_dir = self.db_dir.map(|dir| dir.expand_path());
+        // The values of the config file aren't parsed by the CLI
+        #[cfg(feature = "server")]
+        check_sample_ratio(self.tracing.sample_ratio)?;
 
         Ok(self)
     }
@@ -410,4 +448,40 @@ mod test {
             Some(vec!["*".to_string()])
         );
     }
+
+    #[test]
+    fn test_sample_ratio() {
+        let args = ToriiArgs::parse_from([
+            "torii",
+            "--tracing.otlp_endpoint",
+            "http://localhost:4317",
+            "--tracing.sample_ratio",
+            "0.5",
+        ]);
+        assert_eq!(args.tracing.sample_ratio, 0.5);
+
+        for ratio in ["1.5", "-0.1", "half"] {
+            assert!(ToriiArgs::try_parse_from([
+                "torii",
+                "--tracing.otlp_endpoint",
+                "http://localhost:4317",
+                "--tracing.sample_ratio",
+                ratio,
+            ])
+            .is_err());
+        }
+
+        // The ratio of the config file is checked as well
+        let content = r#"
+        [tracing]
+        otlp_endpoint = "http://localhost:4317"
+        sample_ratio = 2.0
+        "#;
+        let path = std::env::temp_dir().join("torii-config-sample-ratio.toml");
+        std::fs::write(&path, content).unwrap();
+        let path_str = path.to_string_lossy().to_string();
+
+        let args = ToriiArgs::parse_from(["torii", "--config", path_str.as_str()]);
+        assert!(args.with_config_file().is_err());
+    }
 }
@@ -9,6 +9,7 @@ use merge_options::MergeOptions;
 use serde::ser::SerializeSeq;
 use serde::{Deserialize, Serialize};
 use starknet::core::types::Felt;
+use torii_auth::{ApiKey, Capability};
 use torii_proto::{ContractDefinition, ContractType};
 use torii_sqlite_types::{Aggregation, AggregatorConfig, Hook, HookEvent, ModelIndices, SortOrder};
 
@@ -33,6 +34,13 @@ pub const DEFAULT_GRPC_HTTP2_KEEPALIVE_TIMEOUT_SECS: u64 = 10;
 pub const DEFAULT_GRPC_MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
 
 pub const DEFAULT_ERC_MAX_METADATA_TASKS: usize = 100;
+pub const DEFAULT_ERC_METADATA_WORKERS: usize = 10;
+pub const DEFAULT_ERC_IMAGE_QUALITY: u8 = 80;
+/// Default max-age in seconds of the Cache-Control header of served images
+pub const DEFAULT_ERC_IMAGE_CACHE_MAX_AGE: u64 = 3600;
+/// Default timeout in milliseconds of a single IPFS/Arweave gateway request, the one of
+/// `torii_processors::fetch::DEFAULT_GATEWAY_TIMEOUT`
+pub const DEFAULT_ERC_GATEWAY_TIMEOUT: u64 = 5000;
 pub const DEFAULT_DATABASE_WAL_AUTO_CHECKPOINT: u64 = 10000;
 /// Default WAL size threshold for TRUNCATE checkpoint (100MB in bytes)
 pub const DEFAULT_DATABASE_WAL_TRUNCATE_SIZE_THRESHOLD: u64 = 100 * 1024 * 1024;
@@ -42,6 +50,18 @@ pub const DEFAULT_DATABASE_BUSY_TIMEOUT: u64 = 60_000;
 pub const DEFAULT_DATABASE_ACQUIRE_TIMEOUT: u64 = 30_000;
 pub const DEFAULT_DATABASE_IDLE_TIMEOUT: u64 = 600_000;
 pub const DEFAULT_DATABASE_MAX_CONNECTIONS: u32 = 100;
+/// Default maximum number of rows returned by a raw SQL query
+pub const DEFAULT_SQL_QUERY_MAX_ROWS: usize = 10_000;
+/// Default maximum size in bytes of the result of a raw SQL query (16MB)
+pub const DEFAULT_SQL_QUERY_MAX_BYTES: usize = 16 * 1024 * 1024;
+/// Default timeout in milliseconds of a raw SQL query
+pub const DEFAULT_SQL_QUERY_TIMEOUT: u64 = 5000;
+/// Default maximum number of rows streamed by a raw SQL query
+pub const DEFAULT_SQL_STREAM_MAX_ROWS: usize = 10_000_000;
+/// Default timeout in milliseconds of a streamed raw SQL query (5 minutes)
+pub const DEFAULT_SQL_STREAM_TIMEOUT: u64 = 300_000;
+/// Default maximum number of raw SQL queries a client can run concurrently
+pub const DEFAULT_SQL_QUERY_MAX_CONCURRENT: usize = 2;
 pub const DEFAULT_MESSAGING_MAX_AGE: u64 = 300_000;
 pub const DEFAULT_MESSAGING_FUTURE_TOLERANCE: u64 = 60_000;
 
@@ -65,6 +85,12 @@ pub const DEFAULT_SEARCH_MIN_QUERY_LENGTH: usize = 2;
 /// Default snippet length for search result highlighting
 pub const DEFAULT_SEARCH_SNIPPET_LENGTH: usize = 64;
 
+// GraphQL defaults
+/// Default maximum depth of GraphQL queries
+pub const DEFAULT_GRAPHQL_MAX_DEPTH: usize = 20;
+/// Default maximum cost of GraphQL queries
+pub const DEFAULT_GRAPHQL_MAX_COST: u64 = 50_000;
+
 #[derive(Debug, clap::Args, Clone, Serialize, Deserialize, PartialEq, MergeOptions)]
 #[serde(default)]
 #[command(next_help_heading = "Relay options")]
@@ -452,6 +478,100 @@ pub struct ErcOptions {
         help = "Blacklist of contract addresses (hex) that should NOT process metadata updates. Takes precedence over whitelist."
     )]
     pub metadata_update_blacklist: Vec<String>,
+
+    /// IPFS HTTP gateways to fetch metadata and images from, tried in order.
+    /// If empty, public gateways are used.
+    #[arg(
+        long = "erc.ipfs_gateways",
+        value_delimiter = ',',
+        help = "IPFS HTTP gateways (e.g. https://ipfs.io/ipfs/) tried in order when fetching metadata and images. If empty, public gateways are used."
+    )]
+    pub ipfs_gateways: Vec<String>,
+
+    /// Arweave gateways to fetch metadata and images from, tried in order.
+    /// If empty, public gateways are used.
+    #[arg(
+        long = "erc.arweave_gateways",
+        value_delimiter = ',',
+        help = "Arweave gateways (e.g. https://arweave.net/) tried in order when fetching metadata and images. If empty, public gateways are used."
+    )]
+    pub arweave_gateways: Vec<String>,
+
+    /// Timeout in milliseconds of a single gateway request before failing over to the next one.
+    #[arg(
+        long = "erc.gateway_timeout",
+        default_value_t = DEFAULT_ERC_GATEWAY_TIMEOUT,
+        help = "Timeout in milliseconds of a single gateway request before failing over to the next gateway."
+    )]
+    pub gateway_timeout: u64,
+
+    /// Whether to fall back to the IPFS API once every IPFS gateway failed.
+    #[arg(
+        long = "erc.ipfs_api_fallback",
+        default_value_t = true,
+        help = "Whether to fall back to the IPFS API once every IPFS gateway failed."
+    )]
+    pub ipfs_api_fallback: bool,
+
+    /// Path to the content-addressed cache of fetched metadata and images.
+    /// Defaults to a `cache` directory in the artifacts path.
+    #[arg(
+        long = "erc.cache_path",
+        help = "Path to the content-addressed cache of fetched metadata and images. Defaults to a cache directory in the artifacts path."
+    )]
+    pub cache_path: Option<Utf8PathBuf>,
+
+    /// How long in seconds HTTP(S) content is served from the cache. 0 disables caching of
+    /// HTTP(S) content, IPFS and Arweave content is always cached.
+    #[arg(
+        long = "erc.http_cache_ttl",
+        default_value_t = 0,
+        help = "How long in seconds HTTP(S) content is served from the cache. 0 disables caching of HTTP(S) content. IPFS and Arweave content is always cached."
+    )]
+    pub http_cache_ttl: u64,
+
+    /// Whether to register tokens without metadata and fetch it in the background. When false,
+    /// metadata is fetched during indexing and only failed fetches are retried in the background.
+    #[arg(
+        long = "erc.metadata_queue",
+        default_value_t = false,
+        help = "Whether to register tokens without metadata and fetch it in the background with the metadata workers. When false, metadata is fetched during indexing and only failed fetches are retried in the background."
+    )]
+    pub metadata_queue: bool,
+
+    /// The number of background workers refreshing token metadata.
+    #[arg(
+        long = "erc.metadata_workers",
+        default_value_t = DEFAULT_ERC_METADATA_WORKERS,
+        help = "The number of background workers refreshing token metadata."
+    )]
+    pub metadata_workers: usize,
+
+    /// Maximum size in megabytes of the artifacts directory. Once exceeded, the images of the
+    /// least recently served tokens are evicted. 0 means unbounded.
+    #[arg(
+        long = "erc.artifacts_max_size",
+        default_value_t = 0,
+        help = "Maximum size in megabytes of the artifacts directory. Once exceeded, the images of the least recently served tokens are evicted. 0 means unbounded."
+    )]
+    pub artifacts_max_size: u64,
+
+    /// Default encoding quality (1-100) of images served as JPEG or AVIF.
+    #[arg(
+        long = "erc.image_quality",
+        default_value_t = DEFAULT_ERC_IMAGE_QUALITY,
+        value_parser = clap::value_parser!(u8).range(1..=100),
+        help = "Default encoding quality (1-100) of images served as JPEG or AVIF, when the request doesn't set one."
+    )]
+    pub image_quality: u8,
+
+    /// Max-age in seconds of the Cache-Control header of served images.
+    #[arg(
+        long = "erc.image_cache_max_age",
+        default_value_t = DEFAULT_ERC_IMAGE_CACHE_MAX_AGE,
+        help = "Max-age in seconds of the Cache-Control header of served images."
+    )]
+    pub image_cache_max_age: u64,
 }
 
 impl Default for ErcOptions {
@@ -464,6 +584,17 @@ impl Default for ErcOptions {
             metadata_updates: true,
             metadata_update_whitelist: vec![],
             metadata_update_blacklist: vec![],
+            ipfs_gateways: vec![],
+            arweave_gateways: vec![],
+            gateway_timeout: DEFAULT_ERC_GATEWAY_TIMEOUT,
+            ipfs_api_fallback: true,
+            cache_path: None,
+            http_cache_ttl: 0,
+            metadata_queue: false,
+            metadata_workers: DEFAULT_ERC_METADATA_WORKERS,
+            artifacts_max_size: 0,
+            image_quality: DEFAULT_ERC_IMAGE_QUALITY,
+            image_cache_max_age: DEFAULT_ERC_IMAGE_CACHE_MAX_AGE,
         }
     }
 }
@@ -758,6 +889,59 @@ pub struct SqlOptions {
                 Reduce this value on memory-constrained systems."
     )]
     pub journal_size_limit: u64,
+
+    /// Maximum number of rows returned by a raw SQL query.
+    #[arg(
+        long = "sql.query_max_rows",
+        default_value_t = DEFAULT_SQL_QUERY_MAX_ROWS,
+        help = "Maximum number of rows returned by a raw SQL query of the /sql endpoint in \
+                JSON, the ExecuteSql RPC and the MCP query tool. Larger results are rejected."
+    )]
+    pub query_max_rows: usize,
+
+    /// Maximum size in bytes of the result of a raw SQL query.
+    #[arg(
+        long = "sql.query_max_bytes",
+        default_value_t = DEFAULT_SQL_QUERY_MAX_BYTES,
+        help = "Maximum size in bytes of the result of a raw SQL query. Larger results are \
+                rejected."
+    )]
+    pub query_max_bytes: usize,
+
+    /// Timeout in milliseconds of a raw SQL query.
+    #[arg(
+        long = "sql.query_timeout",
+        default_value_t = DEFAULT_SQL_QUERY_TIMEOUT,
+        help = "Timeout in milliseconds after which a raw SQL query is interrupted."
+    )]
+    pub query_timeout: u64,
+
+    /// Maximum number of rows streamed by a raw SQL query.
+    #[arg(
+        long = "sql.stream_max_rows",
+        default_value_t = DEFAULT_SQL_STREAM_MAX_ROWS,
+        help = "Maximum number of rows streamed by a raw SQL query of the /sql endpoint in \
+                NDJSON, CSV or Arrow and of the ExecuteSqlStream RPC. The stream ends with an \
+                error after that many rows. The rows are sent as they are read, so their size \
+                isn't bounded."
+    )]
+    pub stream_max_rows: usize,
+
+    /// Timeout in milliseconds of a streamed raw SQL query.
+    #[arg(
+        long = "sql.stream_timeout",
+        default_value_t = DEFAULT_SQL_STREAM_TIMEOUT,
+        help = "Timeout in milliseconds after which a streamed raw SQL query is interrupted."
+    )]
+    pub stream_timeout: u64,
+
+    /// Maximum number of raw SQL queries a client can run concurrently.
+    #[arg(
+        long = "sql.query_max_concurrent",
+        default_value_t = DEFAULT_SQL_QUERY_MAX_CONCURRENT,
+        help = "Maximum number of raw SQL queries a single client can run concurrently."
+    )]
+    pub query_max_concurrent: usize,
 }
 
 impl Default for SqlOptions {
@@ -784,6 +968,12 @@ impl Default for SqlOptions {
             temp_store: DEFAULT_DATABASE_TEMP_STORE.to_string(),
             mmap_size: DEFAULT_DATABASE_MMAP_SIZE,
             journal_size_limit: DEFAULT_DATABASE_JOURNAL_SIZE_LIMIT,
+            query_max_rows: DEFAULT_SQL_QUERY_MAX_ROWS,
+            query_max_bytes: DEFAULT_SQL_QUERY_MAX_BYTES,
+            query_timeout: DEFAULT_SQL_QUERY_TIMEOUT,
+            stream_max_rows: DEFAULT_SQL_STREAM_MAX_ROWS,
+            stream_timeout: DEFAULT_SQL_STREAM_TIMEOUT,
+            query_max_concurrent: DEFAULT_SQL_QUERY_MAX_CONCURRENT,
         }
     }
 }
@@ -958,6 +1148,25 @@ pub struct SnapshotOptions {
         help = "Optional version of the torii the snapshot has been made from. This is only used to give a warning if there is a version mismatch between the snapshot and this torii."
     )]
     pub version: Option<String>,
+
+    /// Manifest of the snapshot to download
+    #[arg(
+        long = "snapshot.manifest",
+        help = "URL of the manifest of the snapshot, which gives its checksum, version and \
+                cursors. Defaults to the snapshot URL followed by `.manifest.json`, and the \
+                download is not verified if there is none there. A manifest given here that \
+                can't be fetched fails the start."
+    )]
+    pub manifest: Option<String>,
+
+    /// Directory of the snapshots taken through the admin API
+    #[arg(
+        long = "snapshot.dir",
+        value_name = "PATH",
+        help = "Directory where snapshots are written and served from. Enables `POST \
+                /snapshots` for principals with the admin capability and `GET /snapshots`."
+    )]
+    pub dir: Option<PathBuf>,
 }
 
 #[derive(Debug, clap::Args, Clone, Serialize, Deserialize, PartialEq, MergeOptions)]
@@ -1086,6 +1295,17 @@ pub struct GrpcOptions {
         help = "Maximum size in bytes for gRPC messages (both incoming and outgoing). Default is 16MB."
     )]
     pub max_message_size: usize,
+
+    /// Proxies trusted to forward the address of their clients.
+    #[arg(
+        long = "grpc.trusted_proxies",
+        value_delimiter = ',',
+        help = "Comma separated list of addresses of the proxies in front of the gRPC server. The \
+                raw SQL limits of a request forwarded by one of them apply to the address it \
+                forwards in x-forwarded-for, instead of the proxy. The HTTP server of torii is \
+                always trusted."
+    )]
+    pub trusted_proxies: Vec<IpAddr>,
 }
 
 impl GrpcOptions {
@@ -1126,10 +1346,315 @@ impl Default for GrpcOptions {
             http2_keepalive_interval: DEFAULT_GRPC_HTTP2_KEEPALIVE_INTERVAL_SECS,
             http2_keepalive_timeout: DEFAULT_GRPC_HTTP2_KEEPALIVE_TIMEOUT_SECS,
             max_message_size: DEFAULT_GRPC_MAX_MESSAGE_SIZE,
+            trusted_proxies: vec![],
         }
     }
 }
 
+#[derive(Debug, clap::Args, Clone, Serialize, Deserialize, PartialEq, MergeOptions)]
+#[serde(default)]
+#[command(next_help_heading = "GraphQL options")]
+pub struct GraphqlOptions {
+    /// Maximum depth of GraphQL queries. Set to 0 to disable.
+    #[arg(
+        long = "graphql.max_depth",
+        default_value_t = DEFAULT_GRAPHQL_MAX_DEPTH,
+        help = "Maximum depth of GraphQL queries, deeper queries are rejected. Set to 0 to disable."
+    )]
+    pub max_depth: usize,
+
+    /// Maximum cost of GraphQL queries. Set to 0 to disable.
+    #[arg(
+        long = "graphql.max_cost",
+        default_value_t = DEFAULT_GRAPHQL_MAX_COST,
+        help = "Maximum cost of GraphQL queries, more expensive queries are rejected. Every field \
+                costs 1 and the fields of a connection are paid once per item of the page \
+                (first/last/limit). Set to 0 to disable."
+    )]
+    pub max_cost: u64,
+
+    /// Path to a JSON manifest of persisted queries.
+    #[arg(
+        long = "graphql.persisted_queries",
+        value_name = "PATH",
+        help = "Path to a JSON manifest of persisted queries, either a list of queries or an \
+                object mapping the SHA-256 hashes of the queries to the queries. Clients can \
+                send the hash of a persisted query instead of its text."
+    )]
+    pub persisted_queries: Option<PathBuf>,
+
+    /// Only allow the persisted queries.
+    #[arg(
+        long = "graphql.persisted_queries_only",
+        default_value_t = false,
+        requires = "persisted_queries",
+        help = "Refuse the GraphQL queries that aren't in the persisted queries manifest."
+    )]
+    pub persisted_queries_only: bool,
+}
+
+impl Default for GraphqlOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_GRAPHQL_MAX_DEPTH,
+            max_cost: DEFAULT_GRAPHQL_MAX_COST,
+            persisted_queries: None,
+            persisted_queries_only: false,
+        }
+    }
+}
+
+#[derive(Debug, clap::Args, Clone, Serialize, Deserialize, PartialEq, Default, MergeOptions)]
+#[serde(default)]
+#[command(next_help_heading = "Auth options")]
+pub struct AuthOptions {
+    /// Static API keys, with their capabilities and quota.
+    /// Format: "key:capability,capability[:requests_per_minute];another_key:capability"
+    #[arg(
+        long = "auth.api_keys",
+        value_name = "KEYS",
+        value_delimiter = ';',
+        value_parser = ApiKey::from_str,
+        help = "Static API keys, sent as `Authorization: Bearer <key>`, an `x-api-key` header or \
+                an `api_key` query parameter. Format: \
+                \"key:capability,capability[:requests_per_minute];another_key:capability\". \
+                Capabilities are read, subscribe, publish_message, sql, mcp, admin and \
+                replicate."
+    )]
+    pub api_keys: Vec<ApiKey>,
+
+    /// Path to a JWKS file of the keys verifying JWTs.
+    #[arg(
+        long = "auth.jwks",
+        value_name = "PATH",
+        help = "Path to a JWKS file of the keys verifying JWTs. The capabilities of a token are \
+                its `scope` or `capabilities` claim, and its quota its `rate_limit` claim."
+    )]
+    pub jwks: Option<PathBuf>,
+
+    /// Issuer that JWTs must have.
+    #[arg(long = "auth.jwt_issuer", value_name = "ISSUER", requires = "jwks")]
+    pub jwt_issuer: Option<String>,
+
+    /// Audience that JWTs must have.
+    #[arg(long = "auth.jwt_audience", value_name = "AUDIENCE", requires = "jwks")]
+    pub jwt_audience: Option<String>,
+
+    /// Quota of requests per minute of the keys and tokens without their own.
+    #[arg(
+        long = "auth.default_rate_limit",
+        value_name = "REQUESTS_PER_MINUTE",
+        help = "Quota of requests per minute of the API keys and tokens which don't set their \
+                own. Unlimited by default."
+    )]
+    pub default_rate_limit: Option<u32>,
+
+    /// Capabilities of the requests without credentials.
+    #[arg(
+        long = "auth.anonymous",
+        value_name = "CAPABILITIES",
+        value_delimiter = ',',
+        value_parser = Capability::from_str,
+        help = "Capabilities of the requests without credentials, once API keys or a JWKS are \
+                configured. None by default."
+    )]
+    pub anonymous: Vec<Capability>,
+}
+
+pub const DEFAULT_REPLICATION_RETENTION: u64 = 100_000;
+pub const DEFAULT_REPLICATION_POLL_INTERVAL: u64 = 1000;
+pub const DEFAULT_REPLICATION_BATCH_SIZE: u32 = 100;
+
+#[derive(Debug, clap::Args, Clone, Serialize, Deserialize, PartialEq, MergeOptions)]
+#[serde(default)]
+#[command(next_help_heading = "Replication options")]
+pub struct ReplicationOptions {
+    /// Write a change log for followers.
+    #[arg(
+        long = "replication.log",
+        default_value_t = false,
+        help = "Write the changes of every committed batch to a log, served at \
+                `/replication/changes` to the principals with the replicate capability."
+    )]
+    pub log: bool,
+
+    /// Batches kept in the change log.
+    #[arg(
+        long = "replication.retention",
+        value_name = "BATCHES",
+        default_value_t = DEFAULT_REPLICATION_RETENTION,
+        help = "Number of batches kept in the change log, 0 to keep all of them. A follower \
+                behind the oldest one has to be bootstrapped again from a snapshot."
+    )]
+    pub retention: u64,
+
+    /// Primary to follow.
+    #[arg(
+        long = "replication.primary",
+        value_name = "URL",
+        help = "HTTP endpoint of a primary Torii writing a change log. This Torii then follows it \
+                instead of indexing: it applies the changes of the primary and serves them \
+                without fetching the chain. Start it from a snapshot of the primary with \
+                `--snapshot.url`, and with the same SQL, ERC and activity options."
+    )]
+    pub primary: Option<String>,
+
+    /// API key for the primary.
+    #[arg(
+        long = "replication.api_key",
+        value_name = "KEY",
+        requires = "primary",
+        help = "API key with the replicate capability, if the primary requires authentication."
+    )]
+    pub api_key: Option<String>,
+
+    /// Polling interval of the primary in ms.
+    #[arg(
+        long = "replication.poll_interval",
+        value_name = "MILLISECONDS",
+        default_value_t = DEFAULT_REPLICATION_POLL_INTERVAL,
+        help = "Interval in milliseconds at which a follower polls the primary once it caught up."
+    )]
+    pub poll_interval: u64,
+
+    /// Batches fetched per request.
+    #[arg(
+        long = "replication.batch_size",
+        value_name = "BATCHES",
+        default_value_t = DEFAULT_REPLICATION_BATCH_SIZE,
+        help = "Maximum number of batches a follower fetches per request."
+    )]
+    pub batch_size: u32,
+}
+
+impl Default for ReplicationOptions {
+    fn default() -> Self {
+        Self {
+            log: false,
+            retention: DEFAULT_REPLICATION_RETENTION,
+            primary: None,
+            api_key: None,
+            poll_interval: DEFAULT_REPLICATION_POLL_INTERVAL,
+            batch_size: DEFAULT_REPLICATION_BATCH_SIZE,
+        }
+    }
+}
+
+pub const DEFAULT_BROKER_PREFIX: &str = "torii";
+
+#[derive(Debug, clap::Args, Clone, Serialize, Deserialize, PartialEq, MergeOptions)]
+#[serde(default)]
+#[command(next_help_heading = "Broker options")]
+pub struct BrokerOptions {
+    /// NATS server to share the updates through.
+    #[arg(
+        long = "broker.url",
+        value_name = "URL",
+        help = "NATS server, `nats://host:port`, the updates of the subscriptions are published \
+                to, for other Torii processes to relay them."
+    )]
+    pub url: Option<String>,
+
+    /// Prefix of the subjects of the updates.
+    #[arg(
+        long = "broker.prefix",
+        value_name = "PREFIX",
+        default_value = DEFAULT_BROKER_PREFIX,
+        help = "Prefix of the NATS subjects of the updates. Torii processes sharing a prefix \
+                share their updates."
+    )]
+    pub prefix: String,
+
+    /// Relay the updates of the broker instead of indexing.
+    #[arg(
+        long = "broker.relay",
+        default_value_t = false,
+        requires = "url",
+        help = "Serve the database of an indexer running elsewhere, on the same host, instead \
+                of indexing. The updates of the subscriptions are the ones the indexer \
+                publishes to the broker."
+    )]
+    pub relay: bool,
+}
+
+impl Default for BrokerOptions {
+    fn default() -> Self {
+        Self {
+            url: None,
+            prefix: DEFAULT_BROKER_PREFIX.to_string(),
+            relay: false,
+        }
+    }
+}
+
+pub const DEFAULT_TRACING_SERVICE_NAME: &str = "torii";
+pub const DEFAULT_TRACING_SAMPLE_RATIO: f64 = 1.0;
+
+#[derive(Debug, clap::Args, Clone, Serialize, Deserialize, PartialEq, MergeOptions)]
+#[serde(default)]
+#[command(next_help_heading = "Tracing options")]
+pub struct TracingOptions {
+    /// OTLP collector the spans are exported to.
+    #[arg(
+        long = "tracing.otlp_endpoint",
+        value_name = "URL",
+        help = "OTLP gRPC collector, `http://host:4317`, the spans of the indexing and of the \
+                subscriptions are exported to. Spans aren't exported if not set."
+    )]
+    pub otlp_endpoint: Option<String>,
+
+    /// Name of the service of the exported spans.
+    #[arg(
+        long = "tracing.service_name",
+        value_name = "NAME",
+        default_value = DEFAULT_TRACING_SERVICE_NAME,
+        requires = "otlp_endpoint",
+        help = "Name of the service of the exported spans."
+    )]
+    pub service_name: String,
+
+    /// Ratio of the traces exported.
+    #[arg(
+        long = "tracing.sample_ratio",
+        value_name = "RATIO",
+        default_value_t = DEFAULT_TRACING_SAMPLE_RATIO,
+        value_parser = parse_sample_ratio,
+        requires = "otlp_endpoint",
+        help = "Ratio, between 0 and 1, of the traces exported. A trace is the indexing of a \
+                block range, from its fetch to the delivery of its updates."
+    )]
+    pub sample_ratio: f64,
+}
+
+impl Default for TracingOptions {
+    fn default() -> Self {
+        Self {
+            otlp_endpoint: None,
+            service_name: DEFAULT_TRACING_SERVICE_NAME.to_string(),
+            sample_ratio: DEFAULT_TRACING_SAMPLE_RATIO,
+        }
+    }
+}
+
+// Parses a ratio of the traces exported, between 0 and 1
+fn parse_sample_ratio(part: &str) -> anyhow::Result<f64> {
+    let ratio = part
+        .parse::<f64>()
+        .with_context(|| format!("Expected a ratio, found {}", part))?;
+    check_sample_ratio(ratio)
+}
+
+pub(crate) fn check_sample_ratio(ratio: f64) -> anyhow::Result<f64> {
+    if !(0.0..=1.0).contains(&ratio) {
+        return Err(anyhow::anyhow!(
+            "Invalid sample ratio {}. Expected a ratio between 0 and 1",
+            ratio
+        ));
+    }
+
+    Ok(ratio)
+}
+
 // Parses clap cli argument which is expected to be in the format:
 // - model-tag:field1,field2;othermodel-tag:field3,field4
 fn parse_model_indices(part: &str) -> anyhow::Result<ModelIndices> {
@@ -4,24 +4,27 @@ use crypto_bigint::U256;
 use starknet::core::types::Felt;
 use torii_grpc_client::{
     AchievementProgressionUpdateStreaming, ActivityUpdateStreaming, AggregationUpdateStreaming,
-    ContractUpdateStreaming, EntityUpdateStreaming, EventUpdateStreaming, TokenBalanceStreaming,
-    TokenTransferUpdateStreaming, TokenUpdateStreaming, TransactionUpdateStreaming, WorldClient,
+    ContractUpdateStreaming, EntityUpdateStreaming, EventUpdateStreaming, SqlRowStreaming,
+    TokenBalanceStreaming, TokenTransferUpdateStreaming, TokenUpdateStreaming,
+    TransactionUpdateStreaming, WorldClient,
 };
 use torii_proto::proto::world::{
-    RetrieveAchievementsResponse, RetrieveActivitiesResponse, RetrieveAggregationsResponse,
-    RetrieveContractsResponse, RetrieveControllersResponse, RetrieveEntitiesResponse,
-    RetrieveEventsResponse, RetrievePlayerAchievementsResponse, RetrieveTokenBalancesResponse,
-    RetrieveTokenContractsResponse, RetrieveTokenTransfersResponse, RetrieveTokensResponse,
+    AggregateEntitiesResponse, RetrieveAchievementsResponse, RetrieveActivitiesResponse,
+    RetrieveAggregationsResponse, RetrieveContractsResponse, RetrieveControllersResponse,
+    RetrieveEntitiesResponse, RetrieveEventsResponse, RetrievePlayerAchievementsResponse,
+    RetrieveTokenBalancesResponse, RetrieveTokenContractsResponse,
+    RetrieveTokenHolderStatsResponse, RetrieveTokenTransfersResponse, RetrieveTokensResponse,
     RetrieveTransactionsResponse,
 };
 use torii_proto::schema::Entity;
 use torii_proto::{
     Achievement, AchievementQuery, Activity, ActivityQuery, AggregationEntry, AggregationQuery,
-    Clause, Contract, ContractQuery, Controller, ControllerQuery, Event, EventQuery, KeysClause,
-    Message, Page, PlayerAchievementEntry, PlayerAchievementQuery, Query, SearchQuery,
-    SearchResponse, SqlRow, Token, TokenBalance, TokenBalanceQuery, TokenContract,
-    TokenContractQuery, TokenQuery, TokenTransfer, TokenTransferQuery, Transaction,
-    TransactionFilter, TransactionQuery, World,
+    Clause, Contract, ContractQuery, Controller, ControllerQuery, EntityAggregate,
+    EntityAggregateQuery, Event, EventQuery, KeysClause, Message, Page, PlayerAchievementEntry,
+    PlayerAchievementQuery, Query, SearchQuery, SearchResponse, SqlRow, Token, TokenBalance,
+    TokenBalanceQuery, TokenContract, TokenContractQuery, TokenHolderStats, TokenHolderStatsQuery,
+    TokenQuery, TokenTransfer, TokenTransferQuery, Transaction, TransactionFilter,
+    TransactionQuery, World,
 };
 
 use crate::error::Error;
@@ -154,6 +157,17 @@ impl Client {
         })
     }
 
+    /// Retrieves holder statistics (holder counts, top holders, distribution) for token contracts.
+    pub async fn token_holder_stats(
+        &self,
+        query: TokenHolderStatsQuery,
+    ) -> Result<Vec<TokenHolderStats>, Error> {
+        let mut grpc_client = self.inner.clone();
+        let RetrieveTokenHolderStatsResponse { stats } =
+            grpc_client.retrieve_token_holder_stats(query).await?;
+        Ok(stats.into_iter().map(Into::into).collect())
+    }
+
     /// Retrieves token contracts matching the query parameters.
     pub async fn token_contracts(
         &self,
@@ -453,6 +467,18 @@ impl Client {
         })
     }
 
+    /// Aggregates the entities of a model matching the query clause, grouped by some of their
+    /// members. e.g. the count of units per faction.
+    pub async fn aggregate_entities(
+        &self,
+        query: EntityAggregateQuery,
+    ) -> Result<Vec<EntityAggregate>, Error> {
+        let mut grpc_client = self.inner.clone();
+        let AggregateEntitiesResponse { aggregates } =
+            grpc_client.aggregate_entities(query).await?;
+        Ok(aggregates.into_iter().map(Into::into).collect())
+    }
+
     /// Similary to entities, this function retrieves event messages matching the query parameter.
     pub async fn event_messages(&self, query: Query) -> Result<Page<Entity>, Error> {
         let mut grpc_client = self.inner.clone();
@@ -706,6 +732,14 @@ impl Client {
         Ok(rows)
     }
 
+    /// Executes a SQL query and streams its rows, in chunks, as the server reads them. The rows
+    /// are not buffered by the server, unlike [`Client::sql`], but the same limits apply.
+    pub async fn sql_stream(&self, query: String) -> Result<SqlRowStreaming, Error> {
+        let mut grpc_client = self.inner.clone();
+        let stream = grpc_client.execute_sql_stream(query).await?;
+        Ok(stream)
+    }
+
     /// Perform a full-text search across indexed entities using FTS5.
     ///
     /// # Arguments
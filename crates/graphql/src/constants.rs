@@ -41,6 +41,21 @@ pub const MODEL_ORDER_FIELD_TYPE_NAME: &str = "World__ModelOrderField";
 pub const TOKEN_BALANCE_TYPE_NAME: &str = "Token__Balance";
 pub const TOKEN_TRANSFER_TYPE_NAME: &str = "Token__Transfer";
 pub const TOKEN_UNION_TYPE_NAME: &str = "ERC__Token";
+pub const AGGREGATION_TYPE_NAME: &str = "World__Aggregation";
+pub const ACTIVITY_TYPE_NAME: &str = "World__Activity";
+pub const ACTIVITY_ACTION_TYPE_NAME: &str = "World__ActivityAction";
+pub const ACHIEVEMENT_TYPE_NAME: &str = "World__Achievement";
+pub const ACHIEVEMENT_TASK_TYPE_NAME: &str = "World__AchievementTask";
+pub const ACHIEVEMENT_PROGRESSION_TYPE_NAME: &str = "World__AchievementProgression";
+pub const PLAYER_ACHIEVEMENT_TYPE_NAME: &str = "World__PlayerAchievement";
+pub const PLAYER_ACHIEVEMENT_STATS_TYPE_NAME: &str = "World__PlayerAchievementStats";
+pub const PLAYER_ACHIEVEMENT_PROGRESS_TYPE_NAME: &str = "World__PlayerAchievementProgress";
+pub const TASK_PROGRESS_TYPE_NAME: &str = "World__TaskProgress";
+pub const SEARCH_RESULT_TYPE_NAME: &str = "World__SearchResult";
+pub const TABLE_SEARCH_RESULT_TYPE_NAME: &str = "World__TableSearchResult";
+pub const SEARCH_MATCH_TYPE_NAME: &str = "World__SearchMatch";
+pub const SEARCH_MATCH_FIELD_TYPE_NAME: &str = "World__SearchMatchField";
+pub const CONTRACT_TYPE_NAME: &str = "World__Contract";
 // pub const ERC721_METADATA_TYPE_NAME: &str = "ERC721__Metadata";
 
 pub const ERC20_TYPE_NAME: &str = "ERC20__Token";
@@ -65,6 +80,23 @@ pub const ERC1155_TOKEN_NAME: (&str, &str) = ("erc1155Token", "");
 pub const TOKEN_BALANCE_NAME: (&str, &str) = ("", "tokenBalances");
 pub const TOKEN_TRANSFER_NAME: (&str, &str) = ("", "tokenTransfers");
 
+pub const AGGREGATION_NAMES: (&str, &str) = ("aggregation", "aggregations");
+pub const ACTIVITY_NAMES: (&str, &str) = ("activity", "activities");
+pub const ACTIVITY_ACTION_NAMES: (&str, &str) = ("activityAction", "activityActions");
+pub const ACHIEVEMENT_NAMES: (&str, &str) = ("achievement", "achievements");
+pub const ACHIEVEMENT_TASK_NAMES: (&str, &str) = ("achievementTask", "achievementTasks");
+pub const ACHIEVEMENT_PROGRESSION_NAMES: (&str, &str) =
+    ("achievementProgression", "achievementProgressions");
+pub const PLAYER_ACHIEVEMENT_NAMES: (&str, &str) = ("playerAchievement", "playerAchievements");
+pub const PLAYER_ACHIEVEMENT_STATS_NAMES: (&str, &str) = ("playerAchievementStats", "");
+pub const PLAYER_ACHIEVEMENT_PROGRESS_NAMES: (&str, &str) = ("playerAchievementProgress", "");
+pub const TASK_PROGRESS_NAMES: (&str, &str) = ("taskProgress", "");
+pub const SEARCH_NAMES: (&str, &str) = ("search", "");
+pub const TABLE_SEARCH_RESULT_NAMES: (&str, &str) = ("tableSearchResult", "tableSearchResults");
+pub const SEARCH_MATCH_NAMES: (&str, &str) = ("searchMatch", "searchMatches");
+pub const SEARCH_MATCH_FIELD_NAMES: (&str, &str) = ("searchMatchField", "searchMatchFields");
+pub const CONTRACT_NAMES: (&str, &str) = ("contract", "contracts");
+
 // pub const ERC721_METADATA_NAME: (&str, &str) = ("erc721Metadata", "");
 
 // misc
@@ -4,7 +4,10 @@ pub mod object;
 
 mod constants;
 mod error;
+pub mod limits;
+pub mod loaders;
 mod mapping;
+pub mod persisted_queries;
 pub(crate) mod playground;
 mod query;
 pub mod schema;
@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_graphql::extensions::{Extension, ExtensionContext, ExtensionFactory, NextParseQuery};
+use async_graphql::parser::types::{ExecutableDocument, Field, Selection, SelectionSet};
+use async_graphql::{Name, Positioned, ServerError, ServerResult, Value, Variables};
+
+use crate::constants::DEFAULT_LIMIT;
+
+// Arguments that set the page size of a connection
+const PAGE_SIZE_ARGUMENTS: [&str; 3] = ["first", "last", "limit"];
+
+/// Rejects the queries that are nested too deep or that are too expensive, before they get
+/// executed.
+///
+/// Every field costs 1. The fields of a connection are paid once per item of the page, so a
+/// connection costs its page size (`first`, `last` or `limit`, and [`DEFAULT_LIMIT`] when not
+/// given) times the cost of its selection.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QueryLimits {
+    pub max_depth: Option<usize>,
+    pub max_cost: Option<u64>,
+}
+
+impl ExtensionFactory for QueryLimits {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(*self)
+    }
+}
+
+#[async_trait::async_trait]
+impl Extension for QueryLimits {
+    async fn parse_query(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        query: &str,
+        variables: &Variables,
+        next: NextParseQuery<'_>,
+    ) -> ServerResult<ExecutableDocument> {
+        let document = next.run(ctx, query, variables).await?;
+
+        if let Some(max_depth) = self.max_depth {
+            let depth = query_depth(&document);
+            if depth > max_depth {
+                return Err(ServerError::new(
+                    format!("Query depth of {depth} exceeds the maximum depth of {max_depth}."),
+                    None,
+                ));
+            }
+        }
+
+        if let Some(max_cost) = self.max_cost {
+            let cost = query_cost(&document, variables);
+            if cost > max_cost {
+                return Err(ServerError::new(
+                    format!(
+                        "Query cost of {cost} exceeds the maximum cost of {max_cost}. Request \
+                         smaller pages with `first`/`last`/`limit` or select fewer fields."
+                    ),
+                    None,
+                ));
+            }
+        }
+
+        Ok(document)
+    }
+}
+
+/// Returns the depth of the deepest operation of the document.
+pub fn query_depth(document: &ExecutableDocument) -> usize {
+    let mut fragments = Fragments::default();
+    document
+        .operations
+        .iter()
+        .map(|(_, operation)| {
+            selection_set_depth(document, &operation.node.selection_set, &mut fragments)
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Returns the cost of the most expensive operation of the document.
+pub fn query_cost(document: &ExecutableDocument, variables: &Variables) -> u64 {
+    let mut fragments = Fragments::default();
+    document
+        .operations
+        .iter()
+        .map(|(_, operation)| {
+            selection_set_cost(
+                document,
+                variables,
+                &operation.node.selection_set,
+                &mut fragments,
+            )
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+// Fragments are inlined, and measured once per document as a fragment spread many times would
+// otherwise be measured an exponential number of times. `visiting` holds the ones being
+// measured so that cycles, which are rejected later on by the validation, don't recurse forever
+struct Fragments<T> {
+    visiting: Vec<Name>,
+    measured: HashMap<Name, T>,
+}
+
+impl<T> Default for Fragments<T> {
+    fn default() -> Self {
+        Self {
+            visiting: Vec::new(),
+            measured: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Copy + Default> Fragments<T> {
+    fn measure(
+        &mut self,
+        document: &ExecutableDocument,
+        name: &Name,
+        measure: impl FnOnce(&mut Self, &Positioned<SelectionSet>) -> T,
+    ) -> T {
+        if let Some(measured) = self.measured.get(name) {
+            return *measured;
+        }
+
+        match document.fragments.get(name) {
+            Some(fragment) if !self.visiting.contains(name) => {
+                self.visiting.push(name.clone());
+                let measured = measure(self, &fragment.node.selection_set);
+                self.visiting.pop();
+                self.measured.insert(name.clone(), measured);
+                measured
+            }
+            _ => T::default(),
+        }
+    }
+}
+
+fn selection_set_depth(
+    document: &ExecutableDocument,
+    selection_set: &Positioned<SelectionSet>,
+    fragments: &mut Fragments<usize>,
+) -> usize {
+    selection_set
+        .node
+        .items
+        .iter()
+        .map(|selection| match &selection.node {
+            Selection::Field(field) => {
+                1 + selection_set_depth(document, &field.node.selection_set, fragments)
+            }
+            Selection::InlineFragment(fragment) => {
+                selection_set_depth(document, &fragment.node.selection_set, fragments)
+            }
+            Selection::FragmentSpread(spread) => fragments.measure(
+                document,
+                &spread.node.fragment_name.node,
+                |fragments, selection_set| selection_set_depth(document, selection_set, fragments),
+            ),
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+fn selection_set_cost(
+    document: &ExecutableDocument,
+    variables: &Variables,
+    selection_set: &Positioned<SelectionSet>,
+    fragments: &mut Fragments<u64>,
+) -> u64 {
+    selection_set
+        .node
+        .items
+        .iter()
+        .map(|selection| match &selection.node {
+            Selection::Field(field) => {
+                let children =
+                    selection_set_cost(document, variables, &field.node.selection_set, fragments);
+                let multiplier = page_size(&field.node, variables).unwrap_or(1);
+                1u64.saturating_add(multiplier.saturating_mul(children))
+            }
+            Selection::InlineFragment(fragment) => {
+                selection_set_cost(document, variables, &fragment.node.selection_set, fragments)
+            }
+            Selection::FragmentSpread(spread) => fragments.measure(
+                document,
+                &spread.node.fragment_name.node,
+                |fragments, selection_set| {
+                    selection_set_cost(document, variables, selection_set, fragments)
+                },
+            ),
+        })
+        .fold(0, u64::saturating_add)
+}
+
+// Page size of a connection field, `None` if the field isn't a connection
+fn page_size(field: &Field, variables: &Variables) -> Option<u64> {
+    let argument = field.arguments.iter().find_map(|(name, value)| {
+        PAGE_SIZE_ARGUMENTS
+            .contains(&name.node.as_str())
+            .then_some(&value.node)
+    });
+
+    // Variables are resolved, a missing one is caught by the validation
+    let size = argument
+        .and_then(|value| {
+            value
+                .clone()
+                .into_const_with(|name| variables.get(&name).cloned().ok_or(()))
+                .ok()
+        })
+        .and_then(|value| match value {
+            Value::Number(n) => n.as_u64(),
+            _ => None,
+        });
+    if size.is_some() {
+        return size;
+    }
+
+    // Connections without a page size are paginated with the default limit
+    let is_connection = field.selection_set.node.items.iter().any(|selection| {
+        matches!(&selection.node, Selection::Field(child) if child.node.name.node == "edges")
+    });
+    is_connection.then_some(DEFAULT_LIMIT)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use async_graphql::parser::parse_query;
+    use async_graphql::Variables;
+    use serde_json::json;
+
+    use super::{query_cost, query_depth};
+
+    #[test]
+    fn test_query_depth() {
+        let document = parse_query(
+            r#"
+            query {
+                entities { edges { node { id } } }
+                models { totalCount }
+            }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(query_depth(&document), 4);
+
+        // fragments count as if they were inlined
+        let document = parse_query(
+            r#"
+            query {
+                entities { ...Entities }
+            }
+            fragment Entities on World__EntityConnection { edges { node { id } } }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(query_depth(&document), 4);
+    }
+
+    #[test]
+    fn test_query_cost() {
+        // entities + 5 * (edges + node + id)
+        let document = parse_query("{ entities(first: 5) { edges { node { id } } } }").unwrap();
+        assert_eq!(query_cost(&document, &Variables::default()), 16);
+
+        // the page size defaults to the default limit
+        let document = parse_query("{ entities { edges { node { id } } } }").unwrap();
+        assert_eq!(query_cost(&document, &Variables::default()), 31);
+
+        // nested connections multiply
+        let document = parse_query(
+            "query($first: Int) { entities(first: $first) { edges { node { models(first: 10) { \
+             edges { node { id } } } } } } }",
+        )
+        .unwrap();
+        let variables = Variables::from_json(json!({ "first": 100 }));
+        assert_eq!(
+            query_cost(&document, &variables),
+            1 + 100 * (1 + 1 + (1 + 10 * 3))
+        );
+    }
+
+    #[test]
+    fn test_repeated_fragment_spreads() {
+        // Every fragment spreads the next one twice, 2^30 fields once inlined
+        let mut query = "{ entities { ...F0 } }".to_string();
+        for i in 0..30 {
+            let next = i + 1;
+            query.push_str(&format!(
+                " fragment F{i} on Query {{ ...F{next} ...F{next} }}"
+            ));
+        }
+        query.push_str(" fragment F30 on Query { id }");
+        let document = parse_query(query).unwrap();
+
+        // Each fragment is measured once
+        let start = Instant::now();
+        assert_eq!(query_depth(&document), 2);
+        assert_eq!(query_cost(&document, &Variables::default()), 1 + (1 << 30));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
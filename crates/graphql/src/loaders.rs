@@ -0,0 +1,402 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_graphql::dataloader::{DataLoader, Loader};
+use async_graphql::extensions::{
+    Extension, ExtensionContext, ExtensionFactory, NextPrepareRequest,
+};
+use async_graphql::{Request, ServerResult};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+use torii_sqlite::constants::TOKEN_BALANCE_TABLE;
+
+use crate::constants::ID_COLUMN;
+
+/// Adds fresh data loaders to the data of every request, so that the rows resolved by the
+/// nested fields of a page (the models of the entities, the entity of the model rows...) are
+/// fetched with one query per table instead of one query per item.
+///
+/// The loaders don't cache, a subscription resolving the same rows over time always gets them
+/// up to date.
+#[derive(Debug, Clone)]
+pub struct DataLoaders {
+    pool: SqlitePool,
+}
+
+impl DataLoaders {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+impl ExtensionFactory for DataLoaders {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(self.clone())
+    }
+}
+
+#[async_trait::async_trait]
+impl Extension for DataLoaders {
+    async fn prepare_request(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        request: Request,
+        next: NextPrepareRequest<'_>,
+    ) -> ServerResult<Request> {
+        let request = request
+            .data(DataLoader::new(
+                RowLoader {
+                    pool: self.pool.clone(),
+                },
+                tokio::spawn,
+            ))
+            .data(DataLoader::new(
+                ModelsLoader {
+                    pool: self.pool.clone(),
+                },
+                tokio::spawn,
+            ))
+            .data(DataLoader::new(
+                TokenLoader {
+                    pool: self.pool.clone(),
+                },
+                tokio::spawn,
+            ))
+            .data(DataLoader::new(
+                BalanceLoader {
+                    pool: self.pool.clone(),
+                },
+                tokio::spawn,
+            ));
+        next.run(ctx, request).await
+    }
+}
+
+/// Row of `table` whose `column` is `id`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RowKey {
+    pub table: String,
+    pub column: &'static str,
+    pub id: String,
+}
+
+impl RowKey {
+    pub fn new(table: impl Into<String>, column: &'static str, id: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            column,
+            id: id.into(),
+        }
+    }
+}
+
+/// Loads rows by id, with a single query per table.
+#[derive(Debug)]
+pub struct RowLoader {
+    pool: SqlitePool,
+}
+
+impl Loader<RowKey> for RowLoader {
+    type Value = Arc<SqliteRow>;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(&self, keys: &[RowKey]) -> Result<HashMap<RowKey, Self::Value>, Self::Error> {
+        let mut tables: HashMap<(&str, &'static str), Vec<&str>> = HashMap::new();
+        for key in keys {
+            tables
+                .entry((key.table.as_str(), key.column))
+                .or_default()
+                .push(key.id.as_str());
+        }
+
+        let mut rows = HashMap::new();
+        for ((table, column), ids) in tables {
+            let query = format!(
+                "SELECT * FROM [{table}] WHERE [{column}] IN ({})",
+                placeholders(ids.len())
+            );
+            let mut query = sqlx::query(&query);
+            for id in ids {
+                query = query.bind(id);
+            }
+
+            for row in query.fetch_all(&self.pool).await? {
+                let id = row.try_get::<String, _>(column)?;
+                rows.entry(RowKey::new(table, column, id))
+                    .or_insert_with(|| Arc::new(row));
+            }
+        }
+
+        Ok(rows)
+    }
+}
+
+/// Models of an entity, or of an event message, in the order they were registered.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ModelsKey {
+    Entity(String),
+    EventMessage(String),
+}
+
+/// Namespace, name and schema of a model.
+pub type ModelDefinition = (String, String, String);
+
+/// Loads the models of entities and event messages, with a single query per kind.
+#[derive(Debug)]
+pub struct ModelsLoader {
+    pool: SqlitePool,
+}
+
+impl Loader<ModelsKey> for ModelsLoader {
+    type Value = Vec<ModelDefinition>;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(
+        &self,
+        keys: &[ModelsKey],
+    ) -> Result<HashMap<ModelsKey, Self::Value>, Self::Error> {
+        let (entities, event_messages): (Vec<_>, Vec<_>) = keys
+            .iter()
+            .partition(|key| matches!(key, ModelsKey::Entity(_)));
+
+        let mut models = HashMap::new();
+        for (join_table, keys) in [("entity_model", entities), ("event_model", event_messages)] {
+            if keys.is_empty() {
+                continue;
+            }
+
+            let query = format!(
+                "SELECT em.entity_id, m.namespace, m.name, m.schema
+                FROM {join_table} em
+                JOIN models m ON m.id = em.model_id
+                WHERE em.entity_id IN ({})
+                ORDER BY m.rowid",
+                placeholders(keys.len())
+            );
+            let mut query = sqlx::query_as::<_, (String, String, String, String)>(&query);
+            for key in &keys {
+                let (ModelsKey::Entity(id) | ModelsKey::EventMessage(id)) = key;
+                query = query.bind(id);
+            }
+
+            for (id, namespace, name, schema) in query.fetch_all(&self.pool).await? {
+                let key = if join_table == "entity_model" {
+                    ModelsKey::Entity(id)
+                } else {
+                    ModelsKey::EventMessage(id)
+                };
+                models
+                    .entry(key)
+                    .or_insert_with(Vec::new)
+                    .push((namespace, name, schema));
+            }
+        }
+
+        Ok(models)
+    }
+}
+
+/// Loads tokens along with the type of their contract, with a single query.
+#[derive(Debug)]
+pub struct TokenLoader {
+    pool: SqlitePool,
+}
+
+impl Loader<String> for TokenLoader {
+    type Value = Arc<SqliteRow>;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(&self, keys: &[String]) -> Result<HashMap<String, Self::Value>, Self::Error> {
+        let query = format!(
+            "SELECT t.*, c.contract_type
+            FROM tokens t
+            JOIN contracts c ON t.contract_address = c.contract_address
+            WHERE t.id IN ({})",
+            placeholders(keys.len())
+        );
+        let mut query = sqlx::query(&query);
+        for key in keys {
+            query = query.bind(key);
+        }
+
+        let mut tokens = HashMap::new();
+        for row in query.fetch_all(&self.pool).await? {
+            tokens.insert(row.try_get::<String, _>(ID_COLUMN)?, Arc::new(row));
+        }
+
+        Ok(tokens)
+    }
+}
+
+/// Loads token balances along with their token and the type of its contract, with a single
+/// query.
+#[derive(Debug)]
+pub struct BalanceLoader {
+    pool: SqlitePool,
+}
+
+impl Loader<String> for BalanceLoader {
+    type Value = Arc<SqliteRow>;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(&self, keys: &[String]) -> Result<HashMap<String, Self::Value>, Self::Error> {
+        let query = format!(
+            "SELECT b.id, t.contract_address, t.name, t.symbol, t.decimals, b.balance, b.token_id,
+                t.metadata, c.contract_type
+            FROM {TOKEN_BALANCE_TABLE} b
+            JOIN tokens t ON b.token_id = t.id
+            JOIN contracts c ON t.contract_address = c.contract_address
+            WHERE b.id IN ({})",
+            placeholders(keys.len())
+        );
+        let mut query = sqlx::query(&query);
+        for key in keys {
+            query = query.bind(key);
+        }
+
+        let mut balances = HashMap::new();
+        for row in query.fetch_all(&self.pool).await? {
+            balances.insert(row.try_get::<String, _>(ID_COLUMN)?, Arc::new(row));
+        }
+
+        Ok(balances)
+    }
+}
+
+fn placeholders(count: usize) -> String {
+    vec!["?"; count].join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql::dataloader::Loader;
+    use sqlx::{Row, SqlitePool};
+
+    use super::{BalanceLoader, ModelsKey, ModelsLoader, RowKey, RowLoader, TokenLoader};
+
+    async fn insert_tokens(pool: &SqlitePool) {
+        sqlx::query(
+            "INSERT INTO contracts (id, contract_address, contract_type, head) VALUES
+                ('0xa', '0xa', 'ERC20', 0), ('0xb', '0xb', 'ERC721', 0);
+            INSERT INTO tokens (id, contract_address, token_id, name, symbol, decimals, metadata)
+            VALUES
+                ('0xa', '0xa', NULL, 'Gold', 'GLD', 18, ''),
+                ('0xb:0x1', '0xb', '0x1', 'Heroes', 'HRO', 0, '{}');
+            INSERT INTO token_balances (id, balance, account_address, contract_address, token_id)
+            VALUES
+                ('0x1/0xa', '0x10', '0x1', '0xa', '0xa'),
+                ('0x1/0xb:0x1', '0x1', '0x1', '0xb', '0xb:0x1'),
+                ('0x2/0xa', '0x20', '0x2', '0xa', '0xa');",
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[sqlx::test]
+    async fn test_row_loader(pool: SqlitePool) {
+        sqlx::query(
+            "CREATE TABLE [ns-Position] (internal_entity_id TEXT PRIMARY KEY, x INTEGER);
+            CREATE TABLE [ns-Moves] (internal_entity_id TEXT PRIMARY KEY, remaining INTEGER);
+            INSERT INTO [ns-Position] VALUES ('0x1', 1), ('0x2', 2);
+            INSERT INTO [ns-Moves] VALUES ('0x1', 10);",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let loader = RowLoader { pool };
+        let keys = [
+            RowKey::new("ns-Position", "internal_entity_id", "0x1"),
+            RowKey::new("ns-Position", "internal_entity_id", "0x2"),
+            RowKey::new("ns-Moves", "internal_entity_id", "0x1"),
+            RowKey::new("ns-Moves", "internal_entity_id", "0x2"),
+        ];
+        let rows = loader.load(&keys).await.unwrap();
+
+        // rows are matched back to their keys, missing ones are left out
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[&keys[1]].get::<i64, _>("x"), 2);
+        assert_eq!(rows[&keys[2]].get::<i64, _>("remaining"), 10);
+        assert!(!rows.contains_key(&keys[3]));
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_models_loader(pool: SqlitePool) {
+        sqlx::query(
+            "INSERT INTO entities (id, event_id, executed_at) VALUES ('0x1', '', 0), ('0x2', '', 0);
+            INSERT INTO event_messages (id, event_id, executed_at) VALUES ('0x1', '', 0);
+            INSERT INTO models (id, namespace, name, class_hash, contract_address, \
+             transaction_hash, layout, schema, packed_size, unpacked_size, executed_at) VALUES
+                ('0x10', 'ns', 'Position', '0x0', '0x0', '0x0', '', 'position', 0, 0, 0),
+                ('0x11', 'ns', 'Moves', '0x0', '0x0', '0x0', '', 'moves', 0, 0, 0);
+            INSERT INTO entity_model (entity_id, model_id) VALUES
+                ('0x1', '0x10'), ('0x1', '0x11'), ('0x2', '0x11');
+            INSERT INTO event_model (entity_id, model_id) VALUES ('0x1', '0x11');",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let loader = ModelsLoader { pool };
+        let keys = [
+            ModelsKey::Entity("0x1".to_string()),
+            ModelsKey::Entity("0x2".to_string()),
+            ModelsKey::EventMessage("0x1".to_string()),
+            ModelsKey::EventMessage("0x2".to_string()),
+        ];
+        let models = loader.load(&keys).await.unwrap();
+
+        // the models are in registration order, entities and event messages are kept apart
+        let names = |key: &ModelsKey| {
+            models[key]
+                .iter()
+                .map(|(namespace, name, _)| format!("{namespace}-{name}"))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&keys[0]), ["ns-Position", "ns-Moves"]);
+        assert_eq!(names(&keys[1]), ["ns-Moves"]);
+        assert_eq!(names(&keys[2]), ["ns-Moves"]);
+        assert_eq!(models[&keys[0]][0].2, "position");
+        assert!(!models.contains_key(&keys[3]));
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_token_loader(pool: SqlitePool) {
+        insert_tokens(&pool).await;
+
+        let loader = TokenLoader { pool };
+        let keys = ["0xa".to_string(), "0xb:0x1".to_string(), "0xc".to_string()];
+        let tokens = loader.load(&keys).await.unwrap();
+
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens["0xa"].get::<String, _>("symbol"), "GLD");
+        assert_eq!(
+            tokens["0xb:0x1"].get::<String, _>("contract_type"),
+            "ERC721"
+        );
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_balance_loader(pool: SqlitePool) {
+        insert_tokens(&pool).await;
+
+        let loader = BalanceLoader { pool };
+        let keys = [
+            "0x1/0xa".to_string(),
+            "0x1/0xb:0x1".to_string(),
+            "0x3/0xa".to_string(),
+        ];
+        let balances = loader.load(&keys).await.unwrap();
+
+        // every balance comes with its token and the type of its contract
+        assert_eq!(balances.len(), 2);
+        let gold = &balances["0x1/0xa"];
+        assert_eq!(gold.get::<String, _>("balance"), "0x10");
+        assert_eq!(gold.get::<String, _>("name"), "Gold");
+        assert_eq!(gold.get::<String, _>("contract_type"), "ERC20");
+        let hero = &balances["0x1/0xb:0x1"];
+        assert_eq!(hero.get::<String, _>("token_id"), "0xb:0x1");
+        assert_eq!(hero.get::<String, _>("contract_type"), "ERC721");
+    }
+}
@@ -4,7 +4,12 @@ use async_graphql::Name;
 use dojo_types::primitive::Primitive;
 use std::sync::LazyLock;
 
-use crate::constants::{CONTENT_TYPE_NAME, SOCIAL_TYPE_NAME, TOKEN_UNION_TYPE_NAME};
+use crate::constants::{
+    ACHIEVEMENT_TASK_TYPE_NAME, ACHIEVEMENT_TYPE_NAME, ACTIVITY_ACTION_TYPE_NAME,
+    CONTENT_TYPE_NAME, PLAYER_ACHIEVEMENT_PROGRESS_TYPE_NAME, PLAYER_ACHIEVEMENT_STATS_TYPE_NAME,
+    SEARCH_MATCH_FIELD_TYPE_NAME, SEARCH_MATCH_TYPE_NAME, SOCIAL_TYPE_NAME,
+    TABLE_SEARCH_RESULT_TYPE_NAME, TASK_PROGRESS_TYPE_NAME, TOKEN_UNION_TYPE_NAME,
+};
 use crate::types::{GraphqlType, TypeData, TypeMapping};
 
 pub static ENTITY_TYPE_MAPPING: LazyLock<TypeMapping> = LazyLock::new(|| {
@@ -482,3 +487,446 @@ pub static PUBLISH_MESSAGE_RESPONSE_MAPPING: LazyLock<TypeMapping> = LazyLock::n
         TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
     )])
 });
+
+pub static AGGREGATION_TYPE_MAPPING: LazyLock<TypeMapping> = LazyLock::new(|| {
+    IndexMap::from([
+        (
+            Name::new("id"),
+            TypeData::Simple(TypeRef::named(TypeRef::ID)),
+        ),
+        (
+            Name::new("aggregatorId"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("entityId"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("value"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("displayValue"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("position"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::INT)),
+        ),
+        (
+            Name::new("modelId"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("createdAt"),
+            TypeData::Simple(TypeRef::named_nn(GraphqlType::DateTime.to_string())),
+        ),
+        (
+            Name::new("updatedAt"),
+            TypeData::Simple(TypeRef::named_nn(GraphqlType::DateTime.to_string())),
+        ),
+    ])
+});
+
+pub static ACTIVITY_TYPE_MAPPING: LazyLock<TypeMapping> = LazyLock::new(|| {
+    IndexMap::from([
+        (
+            Name::new("id"),
+            TypeData::Simple(TypeRef::named(TypeRef::ID)),
+        ),
+        (
+            Name::new("worldAddress"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("namespace"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("callerAddress"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("sessionStart"),
+            TypeData::Simple(TypeRef::named_nn(GraphqlType::DateTime.to_string())),
+        ),
+        (
+            Name::new("sessionEnd"),
+            TypeData::Simple(TypeRef::named_nn(GraphqlType::DateTime.to_string())),
+        ),
+        (
+            Name::new("actionCount"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::INT)),
+        ),
+        (
+            Name::new("actions"),
+            TypeData::Simple(TypeRef::named_list(ACTIVITY_ACTION_TYPE_NAME)),
+        ),
+        (
+            Name::new("updatedAt"),
+            TypeData::Simple(TypeRef::named_nn(GraphqlType::DateTime.to_string())),
+        ),
+    ])
+});
+
+pub static ACTIVITY_ACTION_TYPE_MAPPING: LazyLock<TypeMapping> = LazyLock::new(|| {
+    IndexMap::from([
+        (
+            Name::new("name"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("count"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::INT)),
+        ),
+    ])
+});
+
+pub static ACHIEVEMENT_TYPE_MAPPING: LazyLock<TypeMapping> = LazyLock::new(|| {
+    IndexMap::from([
+        (
+            Name::new("id"),
+            TypeData::Simple(TypeRef::named(TypeRef::ID)),
+        ),
+        (
+            Name::new("worldAddress"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("namespace"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("entityId"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("hidden"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::BOOLEAN)),
+        ),
+        (
+            Name::new("index"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::INT)),
+        ),
+        (
+            Name::new("points"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::INT)),
+        ),
+        (
+            Name::new("start"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("end"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("group"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("icon"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("title"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("description"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("tasks"),
+            TypeData::Simple(TypeRef::named_list(ACHIEVEMENT_TASK_TYPE_NAME)),
+        ),
+        (
+            Name::new("data"),
+            TypeData::Simple(TypeRef::named(TypeRef::STRING)),
+        ),
+        (
+            Name::new("totalCompletions"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::INT)),
+        ),
+        (
+            Name::new("completionRate"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::FLOAT)),
+        ),
+        (
+            Name::new("createdAt"),
+            TypeData::Simple(TypeRef::named_nn(GraphqlType::DateTime.to_string())),
+        ),
+        (
+            Name::new("updatedAt"),
+            TypeData::Simple(TypeRef::named_nn(GraphqlType::DateTime.to_string())),
+        ),
+    ])
+});
+
+pub static ACHIEVEMENT_TASK_TYPE_MAPPING: LazyLock<TypeMapping> = LazyLock::new(|| {
+    IndexMap::from([
+        (
+            Name::new("taskId"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("description"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("total"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::INT)),
+        ),
+        (
+            Name::new("totalCompletions"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::INT)),
+        ),
+        (
+            Name::new("completionRate"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::FLOAT)),
+        ),
+        (
+            Name::new("createdAt"),
+            TypeData::Simple(TypeRef::named_nn(GraphqlType::DateTime.to_string())),
+        ),
+    ])
+});
+
+pub static ACHIEVEMENT_PROGRESSION_TYPE_MAPPING: LazyLock<TypeMapping> = LazyLock::new(|| {
+    IndexMap::from([
+        (
+            Name::new("id"),
+            TypeData::Simple(TypeRef::named(TypeRef::ID)),
+        ),
+        (
+            Name::new("achievementId"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("taskId"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("worldAddress"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("namespace"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("playerId"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("count"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::INT)),
+        ),
+        (
+            Name::new("completed"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::BOOLEAN)),
+        ),
+        (
+            Name::new("completedAt"),
+            TypeData::Simple(TypeRef::named(GraphqlType::DateTime.to_string())),
+        ),
+        (
+            Name::new("createdAt"),
+            TypeData::Simple(TypeRef::named_nn(GraphqlType::DateTime.to_string())),
+        ),
+        (
+            Name::new("updatedAt"),
+            TypeData::Simple(TypeRef::named_nn(GraphqlType::DateTime.to_string())),
+        ),
+    ])
+});
+
+pub static PLAYER_ACHIEVEMENT_TYPE_MAPPING: LazyLock<TypeMapping> = LazyLock::new(|| {
+    IndexMap::from([
+        (
+            Name::new("playerAddress"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("stats"),
+            TypeData::Nested((
+                TypeRef::named_nn(PLAYER_ACHIEVEMENT_STATS_TYPE_NAME),
+                IndexMap::new(),
+            )),
+        ),
+        (
+            Name::new("achievements"),
+            TypeData::Simple(TypeRef::named_list(PLAYER_ACHIEVEMENT_PROGRESS_TYPE_NAME)),
+        ),
+    ])
+});
+
+pub static PLAYER_ACHIEVEMENT_STATS_TYPE_MAPPING: LazyLock<TypeMapping> = LazyLock::new(|| {
+    IndexMap::from([
+        (
+            Name::new("totalPoints"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::INT)),
+        ),
+        (
+            Name::new("completedAchievements"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::INT)),
+        ),
+        (
+            Name::new("totalAchievements"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::INT)),
+        ),
+        (
+            Name::new("completionPercentage"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::FLOAT)),
+        ),
+        (
+            Name::new("lastAchievementAt"),
+            TypeData::Simple(TypeRef::named(GraphqlType::DateTime.to_string())),
+        ),
+        (
+            Name::new("createdAt"),
+            TypeData::Simple(TypeRef::named_nn(GraphqlType::DateTime.to_string())),
+        ),
+        (
+            Name::new("updatedAt"),
+            TypeData::Simple(TypeRef::named_nn(GraphqlType::DateTime.to_string())),
+        ),
+    ])
+});
+
+pub static PLAYER_ACHIEVEMENT_PROGRESS_TYPE_MAPPING: LazyLock<TypeMapping> = LazyLock::new(|| {
+    IndexMap::from([
+        (
+            Name::new("achievement"),
+            TypeData::Nested((TypeRef::named_nn(ACHIEVEMENT_TYPE_NAME), IndexMap::new())),
+        ),
+        (
+            Name::new("taskProgress"),
+            TypeData::Simple(TypeRef::named_list(TASK_PROGRESS_TYPE_NAME)),
+        ),
+        (
+            Name::new("completed"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::BOOLEAN)),
+        ),
+        (
+            Name::new("progressPercentage"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::FLOAT)),
+        ),
+    ])
+});
+
+pub static TASK_PROGRESS_TYPE_MAPPING: LazyLock<TypeMapping> = LazyLock::new(|| {
+    IndexMap::from([
+        (
+            Name::new("taskId"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("count"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::INT)),
+        ),
+        (
+            Name::new("completed"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::BOOLEAN)),
+        ),
+    ])
+});
+
+pub static SEARCH_RESULT_TYPE_MAPPING: LazyLock<TypeMapping> = LazyLock::new(|| {
+    IndexMap::from([
+        (
+            Name::new("total"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::INT)),
+        ),
+        (
+            Name::new("results"),
+            TypeData::Simple(TypeRef::named_list(TABLE_SEARCH_RESULT_TYPE_NAME)),
+        ),
+    ])
+});
+
+pub static TABLE_SEARCH_RESULT_TYPE_MAPPING: LazyLock<TypeMapping> = LazyLock::new(|| {
+    IndexMap::from([
+        (
+            Name::new("table"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("count"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::INT)),
+        ),
+        (
+            Name::new("matches"),
+            TypeData::Simple(TypeRef::named_list(SEARCH_MATCH_TYPE_NAME)),
+        ),
+    ])
+});
+
+// Matched fields are a list of name/value pairs, the columns depend on the searched table
+pub static SEARCH_MATCH_TYPE_MAPPING: LazyLock<TypeMapping> = LazyLock::new(|| {
+    IndexMap::from([
+        (
+            Name::new("id"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("fields"),
+            TypeData::Simple(TypeRef::named_list(SEARCH_MATCH_FIELD_TYPE_NAME)),
+        ),
+        (
+            Name::new("score"),
+            TypeData::Simple(TypeRef::named(TypeRef::FLOAT)),
+        ),
+    ])
+});
+
+pub static SEARCH_MATCH_FIELD_TYPE_MAPPING: LazyLock<TypeMapping> = LazyLock::new(|| {
+    IndexMap::from([
+        (
+            Name::new("name"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("value"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+    ])
+});
+
+pub static CONTRACT_TYPE_MAPPING: LazyLock<TypeMapping> = LazyLock::new(|| {
+    IndexMap::from([
+        (
+            Name::new("contractAddress"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("contractType"),
+            TypeData::Simple(TypeRef::named_nn(TypeRef::STRING)),
+        ),
+        (
+            Name::new("head"),
+            TypeData::Simple(TypeRef::named(TypeRef::INT)),
+        ),
+        (
+            Name::new("tps"),
+            TypeData::Simple(TypeRef::named(TypeRef::INT)),
+        ),
+        (
+            Name::new("lastBlockTimestamp"),
+            TypeData::Simple(TypeRef::named(TypeRef::INT)),
+        ),
+        (
+            Name::new("lastPendingBlockTx"),
+            TypeData::Simple(TypeRef::named(TypeRef::STRING)),
+        ),
+        (
+            Name::new("createdAt"),
+            TypeData::Simple(TypeRef::named_nn(GraphqlType::DateTime.to_string())),
+        ),
+        (
+            Name::new("updatedAt"),
+            TypeData::Simple(TypeRef::named_nn(GraphqlType::DateTime.to_string())),
+        ),
+    ])
+});
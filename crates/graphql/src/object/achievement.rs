@@ -0,0 +1,497 @@
+use std::sync::Arc;
+
+use async_graphql::dynamic::{
+    Field, FieldFuture, InputValue, Object, SubscriptionField, SubscriptionFieldFuture, TypeRef,
+};
+use async_graphql::{Name, Value};
+use starknet_crypto::Felt;
+use tokio_stream::StreamExt;
+use torii_broker::types::AchievementProgressionUpdate;
+use torii_broker::MemoryBroker;
+use torii_sqlite::utils::felt_to_sql_string;
+use torii_storage::proto::{
+    Achievement, AchievementProgression, AchievementQuery, AchievementTask, PlayerAchievementEntry,
+    PlayerAchievementProgress, PlayerAchievementQuery, PlayerAchievementStats, TaskProgress,
+};
+use torii_storage::ReadOnlyStorage;
+
+use super::connection::{
+    page_arguments, page_output, paged_connection_objects, parse_page_arguments,
+};
+use super::{BasicObject, ResolvableObject, TypeMapping, ValueMapping};
+use crate::constants::{
+    ACHIEVEMENT_NAMES, ACHIEVEMENT_PROGRESSION_NAMES, ACHIEVEMENT_PROGRESSION_TYPE_NAME,
+    ACHIEVEMENT_TASK_NAMES, ACHIEVEMENT_TASK_TYPE_NAME, ACHIEVEMENT_TYPE_NAME, DATETIME_FORMAT,
+    PLAYER_ACHIEVEMENT_NAMES, PLAYER_ACHIEVEMENT_PROGRESS_NAMES,
+    PLAYER_ACHIEVEMENT_PROGRESS_TYPE_NAME, PLAYER_ACHIEVEMENT_STATS_NAMES,
+    PLAYER_ACHIEVEMENT_STATS_TYPE_NAME, PLAYER_ACHIEVEMENT_TYPE_NAME, TASK_PROGRESS_NAMES,
+    TASK_PROGRESS_TYPE_NAME,
+};
+use crate::mapping::{
+    ACHIEVEMENT_PROGRESSION_TYPE_MAPPING, ACHIEVEMENT_TASK_TYPE_MAPPING, ACHIEVEMENT_TYPE_MAPPING,
+    PLAYER_ACHIEVEMENT_PROGRESS_TYPE_MAPPING, PLAYER_ACHIEVEMENT_STATS_TYPE_MAPPING,
+    PLAYER_ACHIEVEMENT_TYPE_MAPPING, TASK_PROGRESS_TYPE_MAPPING,
+};
+use crate::utils::extract_list;
+
+#[derive(Debug)]
+pub struct AchievementObject;
+
+impl BasicObject for AchievementObject {
+    fn name(&self) -> (&str, &str) {
+        ACHIEVEMENT_NAMES
+    }
+
+    fn type_name(&self) -> &str {
+        ACHIEVEMENT_TYPE_NAME
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &ACHIEVEMENT_TYPE_MAPPING
+    }
+}
+
+impl ResolvableObject for AchievementObject {
+    fn resolvers(&self) -> Vec<Field> {
+        let field = Field::new(
+            self.name().1,
+            TypeRef::named(format!("{}Connection", self.type_name())),
+            |ctx| {
+                FieldFuture::new(async move {
+                    let storage = ctx.data::<Arc<dyn ReadOnlyStorage>>()?;
+                    let pagination = parse_page_arguments(&ctx)?;
+                    let hidden = match ctx.args.get("hidden") {
+                        Some(hidden) if !hidden.is_null() => Some(hidden.boolean()?),
+                        _ => None,
+                    };
+                    let query = AchievementQuery {
+                        world_addresses: extract_list(ctx.args.as_index_map(), "worldAddresses")?,
+                        namespaces: extract_list(ctx.args.as_index_map(), "namespaces")?,
+                        hidden,
+                        pagination: pagination.clone(),
+                    };
+
+                    let page = storage.achievements(&query).await?;
+                    let results = page_output(page, &pagination, AchievementObject::value_mapping);
+
+                    Ok(Some(Value::Object(results)))
+                })
+            },
+        )
+        .argument(InputValue::new(
+            "worldAddresses",
+            TypeRef::named_nn_list(TypeRef::STRING),
+        ))
+        .argument(InputValue::new(
+            "namespaces",
+            TypeRef::named_nn_list(TypeRef::STRING),
+        ))
+        .argument(InputValue::new("hidden", TypeRef::named(TypeRef::BOOLEAN)));
+
+        vec![page_arguments(field)]
+    }
+
+    fn subscriptions(&self) -> Option<Vec<SubscriptionField>> {
+        let subscription = SubscriptionField::new(
+            "achievementProgressionUpdated",
+            TypeRef::named_nn(ACHIEVEMENT_PROGRESSION_TYPE_NAME),
+            |ctx| {
+                SubscriptionFieldFuture::new(async move {
+                    let world_addresses: Vec<Felt> =
+                        extract_list(ctx.args.as_index_map(), "worldAddresses")?;
+                    let namespaces: Vec<String> =
+                        extract_list(ctx.args.as_index_map(), "namespaces")?;
+                    let player_addresses: Vec<Felt> =
+                        extract_list(ctx.args.as_index_map(), "playerAddresses")?;
+                    let achievement_ids: Vec<String> =
+                        extract_list(ctx.args.as_index_map(), "achievementIds")?;
+
+                    // an empty filter matches all the progressions
+                    Ok(
+                        MemoryBroker::<AchievementProgressionUpdate>::subscribe().filter_map(
+                            move |update| {
+                                let progression = update.into_inner();
+                                if (world_addresses.is_empty()
+                                    || world_addresses.contains(&progression.world_address))
+                                    && (namespaces.is_empty()
+                                        || namespaces.contains(&progression.namespace))
+                                    && (player_addresses.is_empty()
+                                        || player_addresses.contains(&progression.player_id))
+                                    && (achievement_ids.is_empty()
+                                        || achievement_ids.contains(&progression.achievement_id))
+                                {
+                                    Some(Ok(Value::Object(progression_value_mapping(progression))))
+                                } else {
+                                    None
+                                }
+                            },
+                        ),
+                    )
+                })
+            },
+        )
+        .argument(InputValue::new(
+            "worldAddresses",
+            TypeRef::named_nn_list(TypeRef::STRING),
+        ))
+        .argument(InputValue::new(
+            "namespaces",
+            TypeRef::named_nn_list(TypeRef::STRING),
+        ))
+        .argument(InputValue::new(
+            "playerAddresses",
+            TypeRef::named_nn_list(TypeRef::STRING),
+        ))
+        .argument(InputValue::new(
+            "achievementIds",
+            TypeRef::named_nn_list(TypeRef::STRING),
+        ));
+
+        Some(vec![subscription])
+    }
+
+    fn connection_objects(&self) -> Option<Vec<Object>> {
+        Some(paged_connection_objects(self.name().0, self.type_name()))
+    }
+}
+
+impl AchievementObject {
+    pub fn value_mapping(achievement: Achievement) -> ValueMapping {
+        let tasks = achievement
+            .tasks
+            .into_iter()
+            .map(|task| Value::Object(task_value_mapping(task)))
+            .collect();
+
+        ValueMapping::from([
+            (Name::new("id"), Value::from(achievement.id)),
+            (
+                Name::new("worldAddress"),
+                Value::from(felt_to_sql_string(&achievement.world_address)),
+            ),
+            (Name::new("namespace"), Value::from(achievement.namespace)),
+            (Name::new("entityId"), Value::from(achievement.entity_id)),
+            (Name::new("hidden"), Value::from(achievement.hidden)),
+            (Name::new("index"), Value::from(achievement.index)),
+            (Name::new("points"), Value::from(achievement.points)),
+            (Name::new("start"), Value::from(achievement.start)),
+            (Name::new("end"), Value::from(achievement.end)),
+            (Name::new("group"), Value::from(achievement.group)),
+            (Name::new("icon"), Value::from(achievement.icon)),
+            (Name::new("title"), Value::from(achievement.title)),
+            (
+                Name::new("description"),
+                Value::from(achievement.description),
+            ),
+            (Name::new("tasks"), Value::List(tasks)),
+            (
+                Name::new("data"),
+                achievement.data.map(Value::from).unwrap_or(Value::Null),
+            ),
+            (
+                Name::new("totalCompletions"),
+                Value::from(achievement.total_completions),
+            ),
+            (
+                Name::new("completionRate"),
+                Value::from(achievement.completion_rate),
+            ),
+            (
+                Name::new("createdAt"),
+                Value::from(achievement.created_at.format(DATETIME_FORMAT).to_string()),
+            ),
+            (
+                Name::new("updatedAt"),
+                Value::from(achievement.updated_at.format(DATETIME_FORMAT).to_string()),
+            ),
+        ])
+    }
+}
+
+fn task_value_mapping(task: AchievementTask) -> ValueMapping {
+    ValueMapping::from([
+        (Name::new("taskId"), Value::from(task.task_id)),
+        (Name::new("description"), Value::from(task.description)),
+        (Name::new("total"), Value::from(task.total)),
+        (
+            Name::new("totalCompletions"),
+            Value::from(task.total_completions),
+        ),
+        (
+            Name::new("completionRate"),
+            Value::from(task.completion_rate),
+        ),
+        (
+            Name::new("createdAt"),
+            Value::from(task.created_at.format(DATETIME_FORMAT).to_string()),
+        ),
+    ])
+}
+
+fn progression_value_mapping(progression: AchievementProgression) -> ValueMapping {
+    ValueMapping::from([
+        (Name::new("id"), Value::from(progression.id)),
+        (
+            Name::new("achievementId"),
+            Value::from(progression.achievement_id),
+        ),
+        (Name::new("taskId"), Value::from(progression.task_id)),
+        (
+            Name::new("worldAddress"),
+            Value::from(felt_to_sql_string(&progression.world_address)),
+        ),
+        (Name::new("namespace"), Value::from(progression.namespace)),
+        (
+            Name::new("playerId"),
+            Value::from(felt_to_sql_string(&progression.player_id)),
+        ),
+        (Name::new("count"), Value::from(progression.count)),
+        (Name::new("completed"), Value::from(progression.completed)),
+        (
+            Name::new("completedAt"),
+            progression
+                .completed_at
+                .map(|t| Value::from(t.format(DATETIME_FORMAT).to_string()))
+                .unwrap_or(Value::Null),
+        ),
+        (
+            Name::new("createdAt"),
+            Value::from(progression.created_at.format(DATETIME_FORMAT).to_string()),
+        ),
+        (
+            Name::new("updatedAt"),
+            Value::from(progression.updated_at.format(DATETIME_FORMAT).to_string()),
+        ),
+    ])
+}
+
+#[derive(Debug)]
+pub struct AchievementTaskObject;
+
+impl BasicObject for AchievementTaskObject {
+    fn name(&self) -> (&str, &str) {
+        ACHIEVEMENT_TASK_NAMES
+    }
+
+    fn type_name(&self) -> &str {
+        ACHIEVEMENT_TASK_TYPE_NAME
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &ACHIEVEMENT_TASK_TYPE_MAPPING
+    }
+}
+
+#[derive(Debug)]
+pub struct AchievementProgressionObject;
+
+impl BasicObject for AchievementProgressionObject {
+    fn name(&self) -> (&str, &str) {
+        ACHIEVEMENT_PROGRESSION_NAMES
+    }
+
+    fn type_name(&self) -> &str {
+        ACHIEVEMENT_PROGRESSION_TYPE_NAME
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &ACHIEVEMENT_PROGRESSION_TYPE_MAPPING
+    }
+}
+
+#[derive(Debug)]
+pub struct PlayerAchievementObject;
+
+impl BasicObject for PlayerAchievementObject {
+    fn name(&self) -> (&str, &str) {
+        PLAYER_ACHIEVEMENT_NAMES
+    }
+
+    fn type_name(&self) -> &str {
+        PLAYER_ACHIEVEMENT_TYPE_NAME
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &PLAYER_ACHIEVEMENT_TYPE_MAPPING
+    }
+}
+
+impl ResolvableObject for PlayerAchievementObject {
+    fn resolvers(&self) -> Vec<Field> {
+        let field = Field::new(
+            self.name().1,
+            TypeRef::named(format!("{}Connection", self.type_name())),
+            |ctx| {
+                FieldFuture::new(async move {
+                    let storage = ctx.data::<Arc<dyn ReadOnlyStorage>>()?;
+                    let pagination = parse_page_arguments(&ctx)?;
+                    let query = PlayerAchievementQuery {
+                        world_addresses: extract_list(ctx.args.as_index_map(), "worldAddresses")?,
+                        namespaces: extract_list(ctx.args.as_index_map(), "namespaces")?,
+                        player_addresses: extract_list(ctx.args.as_index_map(), "playerAddresses")?,
+                        pagination: pagination.clone(),
+                    };
+
+                    let page = storage.player_achievements(&query).await?;
+                    let results =
+                        page_output(page, &pagination, PlayerAchievementObject::value_mapping);
+
+                    Ok(Some(Value::Object(results)))
+                })
+            },
+        )
+        .argument(InputValue::new(
+            "worldAddresses",
+            TypeRef::named_nn_list(TypeRef::STRING),
+        ))
+        .argument(InputValue::new(
+            "namespaces",
+            TypeRef::named_nn_list(TypeRef::STRING),
+        ))
+        .argument(InputValue::new(
+            "playerAddresses",
+            TypeRef::named_nn_list(TypeRef::STRING),
+        ));
+
+        vec![page_arguments(field)]
+    }
+
+    fn connection_objects(&self) -> Option<Vec<Object>> {
+        Some(paged_connection_objects(self.name().0, self.type_name()))
+    }
+}
+
+impl PlayerAchievementObject {
+    pub fn value_mapping(entry: PlayerAchievementEntry) -> ValueMapping {
+        let achievements = entry
+            .achievements
+            .into_iter()
+            .map(|progress| Value::Object(progress_value_mapping(progress)))
+            .collect();
+
+        ValueMapping::from([
+            (
+                Name::new("playerAddress"),
+                Value::from(felt_to_sql_string(&entry.player_address)),
+            ),
+            (
+                Name::new("stats"),
+                Value::Object(stats_value_mapping(entry.stats)),
+            ),
+            (Name::new("achievements"), Value::List(achievements)),
+        ])
+    }
+}
+
+fn stats_value_mapping(stats: PlayerAchievementStats) -> ValueMapping {
+    ValueMapping::from([
+        (Name::new("totalPoints"), Value::from(stats.total_points)),
+        (
+            Name::new("completedAchievements"),
+            Value::from(stats.completed_achievements),
+        ),
+        (
+            Name::new("totalAchievements"),
+            Value::from(stats.total_achievements),
+        ),
+        (
+            Name::new("completionPercentage"),
+            Value::from(stats.completion_percentage),
+        ),
+        (
+            Name::new("lastAchievementAt"),
+            stats
+                .last_achievement_at
+                .map(|t| Value::from(t.format(DATETIME_FORMAT).to_string()))
+                .unwrap_or(Value::Null),
+        ),
+        (
+            Name::new("createdAt"),
+            Value::from(stats.created_at.format(DATETIME_FORMAT).to_string()),
+        ),
+        (
+            Name::new("updatedAt"),
+            Value::from(stats.updated_at.format(DATETIME_FORMAT).to_string()),
+        ),
+    ])
+}
+
+fn progress_value_mapping(progress: PlayerAchievementProgress) -> ValueMapping {
+    let task_progress = progress
+        .task_progress
+        .into_iter()
+        .map(|task| Value::Object(task_progress_value_mapping(task)))
+        .collect();
+
+    ValueMapping::from([
+        (
+            Name::new("achievement"),
+            Value::Object(AchievementObject::value_mapping(progress.achievement)),
+        ),
+        (Name::new("taskProgress"), Value::List(task_progress)),
+        (Name::new("completed"), Value::from(progress.completed)),
+        (
+            Name::new("progressPercentage"),
+            Value::from(progress.progress_percentage),
+        ),
+    ])
+}
+
+fn task_progress_value_mapping(task: TaskProgress) -> ValueMapping {
+    ValueMapping::from([
+        (Name::new("taskId"), Value::from(task.task_id)),
+        (Name::new("count"), Value::from(task.count)),
+        (Name::new("completed"), Value::from(task.completed)),
+    ])
+}
+
+#[derive(Debug)]
+pub struct PlayerAchievementStatsObject;
+
+impl BasicObject for PlayerAchievementStatsObject {
+    fn name(&self) -> (&str, &str) {
+        PLAYER_ACHIEVEMENT_STATS_NAMES
+    }
+
+    fn type_name(&self) -> &str {
+        PLAYER_ACHIEVEMENT_STATS_TYPE_NAME
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &PLAYER_ACHIEVEMENT_STATS_TYPE_MAPPING
+    }
+}
+
+#[derive(Debug)]
+pub struct PlayerAchievementProgressObject;
+
+impl BasicObject for PlayerAchievementProgressObject {
+    fn name(&self) -> (&str, &str) {
+        PLAYER_ACHIEVEMENT_PROGRESS_NAMES
+    }
+
+    fn type_name(&self) -> &str {
+        PLAYER_ACHIEVEMENT_PROGRESS_TYPE_NAME
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &PLAYER_ACHIEVEMENT_PROGRESS_TYPE_MAPPING
+    }
+}
+
+#[derive(Debug)]
+pub struct TaskProgressObject;
+
+impl BasicObject for TaskProgressObject {
+    fn name(&self) -> (&str, &str) {
+        TASK_PROGRESS_NAMES
+    }
+
+    fn type_name(&self) -> &str {
+        TASK_PROGRESS_TYPE_NAME
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &TASK_PROGRESS_TYPE_MAPPING
+    }
+}
@@ -0,0 +1,221 @@
+use std::sync::Arc;
+
+use async_graphql::dynamic::{
+    Field, FieldFuture, InputValue, Object, ResolverContext, SubscriptionField,
+    SubscriptionFieldFuture, TypeRef,
+};
+use async_graphql::{Name, Value};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use starknet_crypto::Felt;
+use tokio_stream::StreamExt;
+use torii_broker::types::ActivityUpdate;
+use torii_broker::MemoryBroker;
+use torii_sqlite::utils::felt_to_sql_string;
+use torii_storage::proto::{Activity, ActivityQuery};
+use torii_storage::ReadOnlyStorage;
+
+use super::connection::{
+    page_arguments, page_output, paged_connection_objects, parse_page_arguments,
+};
+use super::{BasicObject, ResolvableObject, TypeMapping, ValueMapping};
+use crate::constants::{
+    ACTIVITY_ACTION_NAMES, ACTIVITY_ACTION_TYPE_NAME, ACTIVITY_NAMES, ACTIVITY_TYPE_NAME,
+    DATETIME_FORMAT,
+};
+use crate::mapping::{ACTIVITY_ACTION_TYPE_MAPPING, ACTIVITY_TYPE_MAPPING};
+use crate::types::GraphqlType;
+use crate::utils::{extract, extract_list};
+
+#[derive(Debug)]
+pub struct ActivityObject;
+
+impl BasicObject for ActivityObject {
+    fn name(&self) -> (&str, &str) {
+        ACTIVITY_NAMES
+    }
+
+    fn type_name(&self) -> &str {
+        ACTIVITY_TYPE_NAME
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &ACTIVITY_TYPE_MAPPING
+    }
+}
+
+impl ResolvableObject for ActivityObject {
+    fn resolvers(&self) -> Vec<Field> {
+        let field = Field::new(
+            self.name().1,
+            TypeRef::named(format!("{}Connection", self.type_name())),
+            |ctx| {
+                FieldFuture::new(async move {
+                    let storage = ctx.data::<Arc<dyn ReadOnlyStorage>>()?;
+                    let pagination = parse_page_arguments(&ctx)?;
+                    let query = ActivityQuery {
+                        world_addresses: extract_list(ctx.args.as_index_map(), "worldAddresses")?,
+                        namespaces: extract_list(ctx.args.as_index_map(), "namespaces")?,
+                        caller_addresses: extract_list(ctx.args.as_index_map(), "callerAddresses")?,
+                        from_time: parse_datetime_argument(&ctx, "fromTime")?,
+                        to_time: parse_datetime_argument(&ctx, "toTime")?,
+                        pagination: pagination.clone(),
+                    };
+
+                    let page = storage.activities(&query).await?;
+                    let results = page_output(page, &pagination, ActivityObject::value_mapping);
+
+                    Ok(Some(Value::Object(results)))
+                })
+            },
+        )
+        .argument(InputValue::new(
+            "worldAddresses",
+            TypeRef::named_nn_list(TypeRef::STRING),
+        ))
+        .argument(InputValue::new(
+            "namespaces",
+            TypeRef::named_nn_list(TypeRef::STRING),
+        ))
+        .argument(InputValue::new(
+            "callerAddresses",
+            TypeRef::named_nn_list(TypeRef::STRING),
+        ))
+        .argument(InputValue::new(
+            "fromTime",
+            TypeRef::named(GraphqlType::DateTime.to_string()),
+        ))
+        .argument(InputValue::new(
+            "toTime",
+            TypeRef::named(GraphqlType::DateTime.to_string()),
+        ));
+
+        vec![page_arguments(field)]
+    }
+
+    fn subscriptions(&self) -> Option<Vec<SubscriptionField>> {
+        let subscription = SubscriptionField::new(
+            "activityUpdated",
+            TypeRef::named_nn(self.type_name()),
+            |ctx| {
+                SubscriptionFieldFuture::new(async move {
+                    let world_addresses: Vec<Felt> =
+                        extract_list(ctx.args.as_index_map(), "worldAddresses")?;
+                    let namespaces: Vec<String> =
+                        extract_list(ctx.args.as_index_map(), "namespaces")?;
+                    let caller_addresses: Vec<Felt> =
+                        extract_list(ctx.args.as_index_map(), "callerAddresses")?;
+
+                    // an empty filter matches all the activities
+                    Ok(
+                        MemoryBroker::<ActivityUpdate>::subscribe().filter_map(move |update| {
+                            let activity = update.into_inner();
+                            if (world_addresses.is_empty()
+                                || world_addresses.contains(&activity.world_address))
+                                && (namespaces.is_empty()
+                                    || namespaces.contains(&activity.namespace))
+                                && (caller_addresses.is_empty()
+                                    || caller_addresses.contains(&activity.caller_address))
+                            {
+                                Some(Ok(Value::Object(ActivityObject::value_mapping(activity))))
+                            } else {
+                                None
+                            }
+                        }),
+                    )
+                })
+            },
+        )
+        .argument(InputValue::new(
+            "worldAddresses",
+            TypeRef::named_nn_list(TypeRef::STRING),
+        ))
+        .argument(InputValue::new(
+            "namespaces",
+            TypeRef::named_nn_list(TypeRef::STRING),
+        ))
+        .argument(InputValue::new(
+            "callerAddresses",
+            TypeRef::named_nn_list(TypeRef::STRING),
+        ));
+
+        Some(vec![subscription])
+    }
+
+    fn connection_objects(&self) -> Option<Vec<Object>> {
+        Some(paged_connection_objects(self.name().0, self.type_name()))
+    }
+}
+
+impl ActivityObject {
+    pub fn value_mapping(activity: Activity) -> ValueMapping {
+        // actions are listed by name, so that the output is stable
+        let mut actions = activity.actions.into_iter().collect::<Vec<_>>();
+        actions.sort();
+        let actions = actions
+            .into_iter()
+            .map(|(name, count)| {
+                Value::Object(ValueMapping::from([
+                    (Name::new("name"), Value::from(name)),
+                    (Name::new("count"), Value::from(count)),
+                ]))
+            })
+            .collect();
+
+        ValueMapping::from([
+            (Name::new("id"), Value::from(activity.id)),
+            (
+                Name::new("worldAddress"),
+                Value::from(felt_to_sql_string(&activity.world_address)),
+            ),
+            (Name::new("namespace"), Value::from(activity.namespace)),
+            (
+                Name::new("callerAddress"),
+                Value::from(felt_to_sql_string(&activity.caller_address)),
+            ),
+            (
+                Name::new("sessionStart"),
+                Value::from(activity.session_start.format(DATETIME_FORMAT).to_string()),
+            ),
+            (
+                Name::new("sessionEnd"),
+                Value::from(activity.session_end.format(DATETIME_FORMAT).to_string()),
+            ),
+            (Name::new("actionCount"), Value::from(activity.action_count)),
+            (Name::new("actions"), Value::List(actions)),
+            (
+                Name::new("updatedAt"),
+                Value::from(activity.updated_at.format(DATETIME_FORMAT).to_string()),
+            ),
+        ])
+    }
+}
+
+#[derive(Debug)]
+pub struct ActivityActionObject;
+
+impl BasicObject for ActivityActionObject {
+    fn name(&self) -> (&str, &str) {
+        ACTIVITY_ACTION_NAMES
+    }
+
+    fn type_name(&self) -> &str {
+        ACTIVITY_ACTION_TYPE_NAME
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &ACTIVITY_ACTION_TYPE_MAPPING
+    }
+}
+
+fn parse_datetime_argument(
+    ctx: &ResolverContext<'_>,
+    name: &str,
+) -> async_graphql::Result<Option<DateTime<Utc>>> {
+    let Ok(datetime) = extract::<String>(ctx.args.as_index_map(), name) else {
+        return Ok(None);
+    };
+
+    let datetime = NaiveDateTime::parse_from_str(&datetime, DATETIME_FORMAT)
+        .map_err(|e| format!("Invalid `{name}` datetime: {e}"))?;
+    Ok(Some(datetime.and_utc()))
+}
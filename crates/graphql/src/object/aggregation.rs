@@ -0,0 +1,140 @@
+use std::sync::Arc;
+
+use async_graphql::dynamic::{
+    Field, FieldFuture, InputValue, Object, SubscriptionField, SubscriptionFieldFuture, TypeRef,
+};
+use async_graphql::{Name, Value};
+use tokio_stream::StreamExt;
+use torii_broker::types::AggregationUpdate;
+use torii_broker::MemoryBroker;
+use torii_storage::proto::{AggregationEntry, AggregationQuery};
+use torii_storage::ReadOnlyStorage;
+
+use super::connection::{
+    page_arguments, page_output, paged_connection_objects, parse_page_arguments,
+};
+use super::{BasicObject, ResolvableObject, TypeMapping, ValueMapping};
+use crate::constants::{AGGREGATION_NAMES, AGGREGATION_TYPE_NAME, DATETIME_FORMAT};
+use crate::mapping::AGGREGATION_TYPE_MAPPING;
+use crate::utils::extract_list;
+
+#[derive(Debug)]
+pub struct AggregationObject;
+
+impl BasicObject for AggregationObject {
+    fn name(&self) -> (&str, &str) {
+        AGGREGATION_NAMES
+    }
+
+    fn type_name(&self) -> &str {
+        AGGREGATION_TYPE_NAME
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &AGGREGATION_TYPE_MAPPING
+    }
+}
+
+impl ResolvableObject for AggregationObject {
+    fn resolvers(&self) -> Vec<Field> {
+        let field = Field::new(
+            self.name().1,
+            TypeRef::named(format!("{}Connection", self.type_name())),
+            |ctx| {
+                FieldFuture::new(async move {
+                    let storage = ctx.data::<Arc<dyn ReadOnlyStorage>>()?;
+                    let pagination = parse_page_arguments(&ctx)?;
+                    let query = AggregationQuery {
+                        aggregator_ids: extract_list(ctx.args.as_index_map(), "aggregatorIds")?,
+                        entity_ids: extract_list(ctx.args.as_index_map(), "entityIds")?,
+                        pagination: pagination.clone(),
+                    };
+
+                    let page = storage.aggregations(&query).await?;
+                    let results = page_output(page, &pagination, AggregationObject::value_mapping);
+
+                    Ok(Some(Value::Object(results)))
+                })
+            },
+        )
+        .argument(InputValue::new(
+            "aggregatorIds",
+            TypeRef::named_nn_list(TypeRef::STRING),
+        ))
+        .argument(InputValue::new(
+            "entityIds",
+            TypeRef::named_nn_list(TypeRef::STRING),
+        ));
+
+        vec![page_arguments(field)]
+    }
+
+    fn subscriptions(&self) -> Option<Vec<SubscriptionField>> {
+        let subscription = SubscriptionField::new(
+            "aggregationUpdated",
+            TypeRef::named_nn(self.type_name()),
+            |ctx| {
+                SubscriptionFieldFuture::new(async move {
+                    let aggregator_ids: Vec<String> =
+                        extract_list(ctx.args.as_index_map(), "aggregatorIds")?;
+                    let entity_ids: Vec<String> =
+                        extract_list(ctx.args.as_index_map(), "entityIds")?;
+
+                    // an empty filter matches all the entries
+                    Ok(
+                        MemoryBroker::<AggregationUpdate>::subscribe().filter_map(move |update| {
+                            let entry = update.into_inner();
+                            if (aggregator_ids.is_empty()
+                                || aggregator_ids.contains(&entry.aggregator_id))
+                                && (entity_ids.is_empty() || entity_ids.contains(&entry.entity_id))
+                            {
+                                Some(Ok(Value::Object(AggregationObject::value_mapping(entry))))
+                            } else {
+                                None
+                            }
+                        }),
+                    )
+                })
+            },
+        )
+        .argument(InputValue::new(
+            "aggregatorIds",
+            TypeRef::named_nn_list(TypeRef::STRING),
+        ))
+        .argument(InputValue::new(
+            "entityIds",
+            TypeRef::named_nn_list(TypeRef::STRING),
+        ));
+
+        Some(vec![subscription])
+    }
+
+    fn connection_objects(&self) -> Option<Vec<Object>> {
+        Some(paged_connection_objects(self.name().0, self.type_name()))
+    }
+}
+
+impl AggregationObject {
+    pub fn value_mapping(entry: AggregationEntry) -> ValueMapping {
+        ValueMapping::from([
+            (Name::new("id"), Value::from(entry.id)),
+            (Name::new("aggregatorId"), Value::from(entry.aggregator_id)),
+            (Name::new("entityId"), Value::from(entry.entity_id)),
+            (
+                Name::new("value"),
+                Value::from(format!("0x{:x}", entry.value)),
+            ),
+            (Name::new("displayValue"), Value::from(entry.display_value)),
+            (Name::new("position"), Value::from(entry.position)),
+            (Name::new("modelId"), Value::from(entry.model_id)),
+            (
+                Name::new("createdAt"),
+                Value::from(entry.created_at.format(DATETIME_FORMAT).to_string()),
+            ),
+            (
+                Name::new("updatedAt"),
+                Value::from(entry.updated_at.format(DATETIME_FORMAT).to_string()),
+            ),
+        ])
+    }
+}
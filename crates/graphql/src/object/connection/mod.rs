@@ -1,13 +1,15 @@
 use async_graphql::connection::PageInfo;
 use async_graphql::dynamic::indexmap::IndexMap;
-use async_graphql::dynamic::{Field, InputValue, ResolverContext, TypeRef};
+use async_graphql::dynamic::{Field, InputValue, Object, ResolverContext, TypeRef};
 use async_graphql::{Error, Name, Value};
 use sqlx::sqlite::SqliteRow;
 use sqlx::Row;
+use torii_storage::proto::{Page, Pagination, PaginationDirection};
 
+use self::edge::EdgeObject;
 use self::page_info::PageInfoObject;
 use super::BasicObject;
-use crate::constants::PAGE_INFO_TYPE_NAME;
+use crate::constants::{DEFAULT_LIMIT, PAGE_INFO_TYPE_NAME};
 use crate::query::order::Order;
 use crate::query::value_mapping_from_row;
 use crate::types::{GraphqlType, TypeData, TypeMapping, ValueMapping};
@@ -57,6 +59,14 @@ impl ConnectionObject {
             type_mapping,
         }
     }
+
+    // Connection over a storage page. Pages are paginated with an opaque cursor and aren't
+    // counted, so there is no `totalCount`
+    pub fn paged(name: String, type_name: String) -> Self {
+        let mut connection = Self::new(name, type_name);
+        connection.type_mapping.shift_remove("totalCount");
+        connection
+    }
 }
 
 impl BasicObject for ConnectionObject {
@@ -129,6 +139,105 @@ pub fn connection_arguments(field: Field) -> Field {
         .argument(InputValue::new("limit", TypeRef::named(TypeRef::INT)))
 }
 
+// Edge and connection objects of the connections over a storage page
+pub fn paged_connection_objects(name: &str, type_name: &str) -> Vec<Object> {
+    let edge = EdgeObject::new(name.to_string(), type_name.to_string());
+    let connection = ConnectionObject::paged(name.to_string(), type_name.to_string());
+
+    let mut objects = Vec::new();
+    objects.extend(edge.objects());
+    objects.extend(connection.objects());
+    objects
+}
+
+// Arguments of the connections over a storage page, `first`/`after` paginate forward and
+// `last`/`before` backward
+pub fn page_arguments(field: Field) -> Field {
+    field
+        .argument(InputValue::new("first", TypeRef::named(TypeRef::INT)))
+        .argument(InputValue::new("last", TypeRef::named(TypeRef::INT)))
+        .argument(InputValue::new(
+            "before",
+            TypeRef::named(GraphqlType::Cursor.to_string()),
+        ))
+        .argument(InputValue::new(
+            "after",
+            TypeRef::named(GraphqlType::Cursor.to_string()),
+        ))
+}
+
+pub fn parse_page_arguments(ctx: &ResolverContext<'_>) -> Result<Pagination, Error> {
+    let first = extract::<u64>(ctx.args.as_index_map(), "first").ok();
+    let last = extract::<u64>(ctx.args.as_index_map(), "last").ok();
+    let after = extract::<String>(ctx.args.as_index_map(), "after").ok();
+    let before = extract::<String>(ctx.args.as_index_map(), "before").ok();
+
+    if first.is_some() && last.is_some() {
+        return Err(
+            "Passing both `first` and `last` to paginate a connection is not supported.".into(),
+        );
+    }
+
+    if after.is_some() && before.is_some() {
+        return Err(
+            "Passing both `after` and `before` to paginate a connection is not supported.".into(),
+        );
+    }
+
+    let direction = if last.is_some() || before.is_some() {
+        PaginationDirection::Backward
+    } else {
+        PaginationDirection::Forward
+    };
+
+    Ok(Pagination {
+        cursor: after.or(before),
+        limit: Some(first.or(last).unwrap_or(DEFAULT_LIMIT) as u32),
+        direction,
+        order_by: vec![],
+    })
+}
+
+// Storage pages only have a cursor to the following page, which is given in `pageInfo` rather
+// than on the edges
+pub fn page_output<T>(
+    page: Page<T>,
+    pagination: &Pagination,
+    value_mapping: impl Fn(T) -> ValueMapping,
+) -> ValueMapping {
+    let edges = page
+        .items
+        .into_iter()
+        .map(|item| {
+            Value::Object(ValueMapping::from([
+                (Name::new("node"), Value::Object(value_mapping(item))),
+                (Name::new("cursor"), Value::Null),
+            ]))
+        })
+        .collect();
+
+    let has_cursor = pagination.cursor.is_some();
+    let page_info = match pagination.direction {
+        PaginationDirection::Forward => PageInfo {
+            has_previous_page: has_cursor,
+            has_next_page: page.next_cursor.is_some(),
+            start_cursor: None,
+            end_cursor: page.next_cursor,
+        },
+        PaginationDirection::Backward => PageInfo {
+            has_previous_page: page.next_cursor.is_some(),
+            has_next_page: has_cursor,
+            start_cursor: page.next_cursor,
+            end_cursor: None,
+        },
+    };
+
+    ValueMapping::from([
+        (Name::new("edges"), Value::List(edges)),
+        (Name::new("pageInfo"), PageInfoObject::value(page_info)),
+    ])
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn connection_output(
     data: &[SqliteRow],
@@ -0,0 +1,171 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_graphql::dynamic::{
+    Field, FieldFuture, InputValue, Object, ResolverContext, SubscriptionField,
+    SubscriptionFieldFuture, TypeRef,
+};
+use async_graphql::{Name, Value};
+use tokio_stream::StreamExt;
+use torii_broker::types::ContractUpdate;
+use torii_broker::MemoryBroker;
+use torii_sqlite::utils::felt_to_sql_string;
+use torii_storage::proto::{Contract, ContractQuery, ContractType};
+use torii_storage::ReadOnlyStorage;
+
+use super::{BasicObject, ResolvableObject, TypeMapping, ValueMapping};
+use crate::constants::{CONTRACT_NAMES, CONTRACT_TYPE_NAME, DATETIME_FORMAT};
+use crate::mapping::CONTRACT_TYPE_MAPPING;
+use crate::utils::extract_list;
+
+#[derive(Debug)]
+pub struct ContractObject;
+
+impl BasicObject for ContractObject {
+    fn name(&self) -> (&str, &str) {
+        CONTRACT_NAMES
+    }
+
+    fn type_name(&self) -> &str {
+        CONTRACT_TYPE_NAME
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &CONTRACT_TYPE_MAPPING
+    }
+}
+
+impl ResolvableObject for ContractObject {
+    fn resolvers(&self) -> Vec<Field> {
+        let field = Field::new(
+            self.name().1,
+            TypeRef::named_nn_list_nn(self.type_name()),
+            |ctx| {
+                FieldFuture::new(async move {
+                    let storage = ctx.data::<Arc<dyn ReadOnlyStorage>>()?;
+                    let query = parse_contract_query(&ctx)?;
+
+                    let contracts = storage.contracts(&query).await?;
+                    let contracts = contracts
+                        .into_iter()
+                        .map(|contract| Value::Object(ContractObject::value_mapping(contract)))
+                        .collect();
+
+                    Ok(Some(Value::List(contracts)))
+                })
+            },
+        )
+        .argument(InputValue::new(
+            "contractAddresses",
+            TypeRef::named_nn_list(TypeRef::STRING),
+        ))
+        .argument(InputValue::new(
+            "contractTypes",
+            TypeRef::named_nn_list(TypeRef::STRING),
+        ));
+
+        vec![field]
+    }
+
+    fn subscriptions(&self) -> Option<Vec<SubscriptionField>> {
+        let subscription = SubscriptionField::new(
+            "contractUpdated",
+            TypeRef::named_nn(self.type_name()),
+            |ctx| {
+                SubscriptionFieldFuture::new(async move {
+                    let query = parse_contract_query(&ctx)?;
+
+                    // an empty filter matches all the contracts
+                    Ok(
+                        MemoryBroker::<ContractUpdate>::subscribe().filter_map(move |update| {
+                            let contract = update.into_inner();
+                            if (query.contract_addresses.is_empty()
+                                || query
+                                    .contract_addresses
+                                    .contains(&contract.contract_address))
+                                && (query.contract_types.is_empty()
+                                    || query.contract_types.contains(&contract.contract_type))
+                            {
+                                Some(Ok(Value::Object(ContractObject::value_mapping(contract))))
+                            } else {
+                                None
+                            }
+                        }),
+                    )
+                })
+            },
+        )
+        .argument(InputValue::new(
+            "contractAddresses",
+            TypeRef::named_nn_list(TypeRef::STRING),
+        ))
+        .argument(InputValue::new(
+            "contractTypes",
+            TypeRef::named_nn_list(TypeRef::STRING),
+        ));
+
+        Some(vec![subscription])
+    }
+
+    // contracts aren't paginated
+    fn connection_objects(&self) -> Option<Vec<Object>> {
+        None
+    }
+}
+
+impl ContractObject {
+    pub fn value_mapping(contract: Contract) -> ValueMapping {
+        ValueMapping::from([
+            (
+                Name::new("contractAddress"),
+                Value::from(felt_to_sql_string(&contract.contract_address)),
+            ),
+            (
+                Name::new("contractType"),
+                Value::from(contract.contract_type.to_string()),
+            ),
+            (
+                Name::new("head"),
+                contract.head.map(Value::from).unwrap_or(Value::Null),
+            ),
+            (
+                Name::new("tps"),
+                contract.tps.map(Value::from).unwrap_or(Value::Null),
+            ),
+            (
+                Name::new("lastBlockTimestamp"),
+                contract
+                    .last_block_timestamp
+                    .map(Value::from)
+                    .unwrap_or(Value::Null),
+            ),
+            (
+                Name::new("lastPendingBlockTx"),
+                contract
+                    .last_pending_block_tx
+                    .map(|tx| Value::from(felt_to_sql_string(&tx)))
+                    .unwrap_or(Value::Null),
+            ),
+            (
+                Name::new("createdAt"),
+                Value::from(contract.created_at.format(DATETIME_FORMAT).to_string()),
+            ),
+            (
+                Name::new("updatedAt"),
+                Value::from(contract.updated_at.format(DATETIME_FORMAT).to_string()),
+            ),
+        ])
+    }
+}
+
+fn parse_contract_query(ctx: &ResolverContext<'_>) -> async_graphql::Result<ContractQuery> {
+    let contract_types = extract_list::<String>(ctx.args.as_index_map(), "contractTypes")?
+        .iter()
+        .map(|contract_type| ContractType::from_str(contract_type))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(ContractQuery {
+        contract_addresses: extract_list(ctx.args.as_index_map(), "contractAddresses")?,
+        contract_types,
+    })
+}
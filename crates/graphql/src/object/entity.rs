@@ -1,3 +1,4 @@
+use async_graphql::dataloader::DataLoader;
 use async_graphql::dynamic::indexmap::IndexMap;
 use async_graphql::dynamic::{
     Field, FieldFuture, FieldValue, InputValue, SubscriptionField, SubscriptionFieldFuture, TypeRef,
@@ -16,8 +17,10 @@ use torii_storage::utils::format_world_scoped_id;
 use super::inputs::keys_input::keys_argument;
 use super::{BasicObject, ResolvableObject, TypeMapping, ValueMapping};
 use crate::constants::{
-    DATETIME_FORMAT, ENTITY_NAMES, ENTITY_TABLE, ENTITY_TYPE_NAME, EVENT_ID_COLUMN, ID_COLUMN,
+    DATETIME_FORMAT, ENTITY_ID_COLUMN, ENTITY_NAMES, ENTITY_TABLE, ENTITY_TYPE_NAME,
+    EVENT_ID_COLUMN, ID_COLUMN,
 };
+use crate::loaders::{ModelsKey, ModelsLoader, RowKey, RowLoader};
 use crate::mapping::ENTITY_TYPE_MAPPING;
 use crate::object::{resolve_many, resolve_one};
 use crate::query::{build_type_mapping, value_mapping_from_row};
@@ -150,43 +153,38 @@ fn model_union_field() -> Field {
         FieldFuture::new(async move {
             match ctx.parent_value.try_to_value()? {
                 Value::Object(indexmap) => {
-                    let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
-
                     let entity_id = utils::extract::<String>(indexmap, "id")?;
-                    // fetch name from the models table
-                    // using the model id (hashed model name)
-                    let model_ids: Vec<(String, String, String)> = sqlx::query_as(
-                        "SELECT namespace, name, schema
-                        FROM models
-                        WHERE id IN (    
-                            SELECT model_id
-                            FROM entity_model
-                            WHERE entity_id = ?
-                        )",
-                    )
-                    .bind(&entity_id)
-                    .fetch_all(&mut *conn)
-                    .await?;
+
+                    // models and their rows are batched with the ones of the other entities of the page
+                    let models = ctx
+                        .data::<DataLoader<ModelsLoader>>()?
+                        .load_one(ModelsKey::Entity(entity_id.clone()))
+                        .await?
+                        .unwrap_or_default();
+                    let mut rows = ctx
+                        .data::<DataLoader<RowLoader>>()?
+                        .load_many(models.iter().map(|(namespace, name, _)| {
+                            RowKey::new(get_tag(namespace, name), ENTITY_ID_COLUMN, &entity_id)
+                        }))
+                        .await?;
 
                     let mut results: Vec<FieldValue<'_>> = Vec::new();
-                    for (namespace, name, schema) in model_ids {
+                    for (namespace, name, schema) in models {
                         let schema: Ty = serde_json::from_str(&schema).map_err(|e| {
                             anyhow::anyhow!(format!("Failed to parse model schema: {e}"))
                         })?;
                         let type_mapping = build_type_mapping(&namespace, &schema);
 
-                        // Get the table name
                         let table_name = get_tag(&namespace, &name);
-
-                        // Fetch the row data
-                        let query = format!(
-                            "SELECT * FROM [{}] WHERE internal_entity_id = ?",
-                            table_name
-                        );
-                        let row = sqlx::query(&query)
-                            .bind(&entity_id)
-                            .fetch_one(&mut *conn)
-                            .await?;
+                        let row = rows
+                            .remove(&RowKey::new(
+                                table_name.as_str(),
+                                ENTITY_ID_COLUMN,
+                                &entity_id,
+                            ))
+                            .ok_or_else(|| {
+                                format!("No row found in {table_name} for {entity_id}")
+                            })?;
 
                         // Use value_mapping_from_row to handle nested structures
                         let data = value_mapping_from_row(&row, &type_mapping, false, false)?;
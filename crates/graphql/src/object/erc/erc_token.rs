@@ -1,4 +1,5 @@
 use async_graphql::connection::PageInfo;
+use async_graphql::dataloader::DataLoader;
 use async_graphql::dynamic::{
     Field, FieldFuture, FieldValue, InputValue, SubscriptionField, SubscriptionFieldFuture, TypeRef,
 };
@@ -16,6 +17,7 @@ use crate::constants::{
     DEFAULT_LIMIT, ERC1155_TOKEN_NAME, ERC1155_TYPE_NAME, ERC20_TOKEN_NAME, ERC20_TYPE_NAME,
     ERC721_TOKEN_NAME, ERC721_TYPE_NAME, ID_COLUMN,
 };
+use crate::loaders::TokenLoader;
 use crate::mapping::{
     ERC1155_TOKEN_TYPE_MAPPING, ERC20_TOKEN_TYPE_MAPPING, ERC721_TOKEN_TYPE_MAPPING,
     TOKEN_TYPE_MAPPING,
@@ -430,21 +432,16 @@ impl ResolvableObject for TokenObject {
                 TypeRef::named_nn(self.type_name()),
                 move |ctx| {
                     FieldFuture::new(async move {
-                        let pool = ctx.data::<Pool<Sqlite>>()?;
                         let token_id = ctx
                             .args
                             .get("id")
                             .and_then(|v| v.string().ok())
                             .ok_or_else(|| async_graphql::Error::new("Token ID is required"))?;
 
-                        let query = "SELECT t.*, c.contract_type 
-                                   FROM tokens t 
-                                   JOIN contracts c ON t.contract_address = c.contract_address 
-                                   WHERE t.id = ?";
-
-                        let row = sqlx::query(query)
-                            .bind(token_id)
-                            .fetch_optional(pool)
+                        // aliased token queries are fetched together
+                        let row = ctx
+                            .data::<DataLoader<TokenLoader>>()?
+                            .load_one(token_id.to_string())
                             .await?;
 
                         match row {
@@ -1,24 +1,29 @@
 use std::str::FromStr;
 
 use async_graphql::connection::PageInfo;
+use async_graphql::dataloader::DataLoader;
 use async_graphql::dynamic::{
     Field, FieldFuture, FieldValue, InputValue, SubscriptionField, SubscriptionFieldFuture, TypeRef,
 };
 use convert_case::{Case, Casing};
+use futures_util::future::ready;
+use futures_util::stream::iter;
+use futures_util::StreamExt;
 use serde::Deserialize;
 use sqlx::sqlite::SqliteRow;
 use sqlx::{FromRow, Pool, Row, Sqlite, SqliteConnection};
 use starknet_crypto::Felt;
-use tokio_stream::StreamExt;
 use torii_broker::types::TokenBalanceUpdate;
 use torii_broker::MemoryBroker;
 use torii_sqlite::constants::TOKEN_BALANCE_TABLE;
 use torii_sqlite::utils::{felt_and_u256_to_sql_string, felt_to_sql_string};
+use torii_storage::proto::{ComparisonOperator, MemberClause, MemberValue};
 use tracing::warn;
 
 use super::erc_token::{Erc20Token, ErcTokenType};
 use super::{handle_cursor, Connection, ConnectionEdge};
 use crate::constants::{DEFAULT_LIMIT, ID_COLUMN, TOKEN_BALANCE_NAME, TOKEN_BALANCE_TYPE_NAME};
+use crate::loaders::BalanceLoader;
 use crate::mapping::TOKEN_BALANCE_TYPE_MAPPING;
 use crate::object::connection::page_info::PageInfoObject;
 use crate::object::connection::{
@@ -27,11 +32,14 @@ use crate::object::connection::{
 use crate::object::erc::erc_token::{Erc1155Token, Erc721Token};
 use crate::object::{BasicObject, ResolvableObject};
 use crate::query::data::count_rows;
-use crate::query::filter::{Comparator, Filter, FilterValue};
+use crate::query::filter::WhereFilter;
 use crate::query::order::{CursorDirection, Direction};
 use crate::types::TypeMapping;
 use crate::utils::extract;
 
+/// Maximum number of updated balances fetched with a single query by the subscription.
+const MAX_BALANCE_BATCH: usize = 256;
+
 #[derive(Debug)]
 pub struct ErcBalanceObject;
 
@@ -69,11 +77,12 @@ impl ResolvableObject for ErcBalanceObject {
                         &account_address.to_case(Case::Camel),
                     )?;
 
-                    let filter = vec![Filter {
-                        field: "account_address".to_string(),
-                        comparator: Comparator::Eq,
-                        value: FilterValue::String(felt_to_sql_string(&address)),
-                    }];
+                    let filter = WhereFilter::Member(MemberClause {
+                        model: TOKEN_BALANCE_TABLE.to_string(),
+                        member: "account_address".to_string(),
+                        operator: ComparisonOperator::Eq,
+                        value: MemberValue::String(felt_to_sql_string(&address)),
+                    });
 
                     let total_count =
                         count_rows(&mut conn, TOKEN_BALANCE_TABLE, &None, &Some(filter)).await?;
@@ -104,57 +113,47 @@ impl ResolvableObject for ErcBalanceObject {
                         None => None,
                     };
 
-                    let pool = ctx.data::<Pool<Sqlite>>()?;
+                    let loader = ctx.data::<DataLoader<BalanceLoader>>()?;
                     Ok(MemoryBroker::<TokenBalanceUpdate>::subscribe()
-                        .then(move |token_balance| {
-                            let pool = pool.clone();
-                            async move {
-                                // Filter by account address if provided
-                                if let Some(addr) = &address {
-                                    if token_balance.account_address != *addr {
-                                        return None;
-                                    }
+                        // Filter by account address if provided
+                        .filter(move |token_balance| {
+                            ready(address.is_none_or(|addr| token_balance.account_address == addr))
+                        })
+                        .map(|token_balance| {
+                            let token_id = if let Some(token_id) = token_balance.token_id {
+                                felt_and_u256_to_sql_string(
+                                    &token_balance.contract_address,
+                                    &token_id.into(),
+                                )
+                            } else {
+                                felt_to_sql_string(&token_balance.contract_address)
+                            };
+                            format!(
+                                "{}/{}",
+                                felt_to_sql_string(&token_balance.account_address),
+                                token_id
+                            )
+                        })
+                        // the balances updated together are fetched with a single query
+                        .ready_chunks(MAX_BALANCE_BATCH)
+                        .then(move |ids| async move {
+                            let rows = match loader.load_many(ids.clone()).await {
+                                Ok(rows) => rows,
+                                Err(err) => {
+                                    warn!(
+                                        "Failed to fetch token balances in subscription: {}",
+                                        err
+                                    );
+                                    return Vec::new();
                                 }
-
-                                // Fetch associated token data
-                                let query = format!(
-                                    "SELECT b.id, t.contract_address, t.name, t.symbol, \
-                                         t.decimals, b.balance, b.token_id, t.metadata, \
-                                         c.contract_type
-                                        FROM {} b
-                                        JOIN tokens t ON b.token_id = t.id
-                                        JOIN contracts c ON t.contract_address = \
-                                         c.contract_address
-                                        WHERE b.id = ?",
-                                    TOKEN_BALANCE_TABLE
-                                );
-
-                                let token_id = if let Some(token_id) = token_balance.token_id {
-                                    felt_and_u256_to_sql_string(
-                                        &token_balance.contract_address,
-                                        &token_id.into(),
-                                    )
-                                } else {
-                                    felt_to_sql_string(&token_balance.contract_address)
-                                };
-                                let id = format!(
-                                    "{}/{}",
-                                    felt_to_sql_string(&token_balance.account_address),
-                                    token_id
-                                );
-                                let row = match sqlx::query(&query).bind(&id).fetch_one(&pool).await
-                                {
-                                    Ok(row) => row,
-                                    Err(_) => return None,
-                                };
-
-                                let row = match BalanceQueryResultRaw::from_row(&row) {
-                                    Ok(row) => row,
-                                    Err(_) => return None,
-                                };
-
-                                // Use the extracted mapping function
-                                match token_balance_mapping_from_row(&row) {
+                            };
+
+                            ids.iter()
+                                .filter_map(|id| rows.get(id))
+                                .filter_map(|row| {
+                                    BalanceQueryResultRaw::from_row(row.as_ref()).ok()
+                                })
+                                .filter_map(|row| match token_balance_mapping_from_row(&row) {
                                     Ok(balance_value) => {
                                         Some(Ok(FieldValue::owned_any(balance_value)))
                                     }
@@ -166,10 +165,10 @@ impl ResolvableObject for ErcBalanceObject {
                                         );
                                         None
                                     }
-                                }
-                            }
+                                })
+                                .collect::<Vec<_>>()
                         })
-                        .filter_map(|result| result))
+                        .flat_map(iter))
                 })
             },
         )
@@ -1,4 +1,8 @@
+pub mod achievement;
+pub mod activity;
+pub mod aggregation;
 pub mod connection;
+pub mod contract;
 pub mod controller;
 pub mod empty;
 pub mod entity;
@@ -10,6 +14,7 @@ pub mod metadata;
 pub mod model;
 pub mod model_data;
 pub mod publish_message;
+pub mod search;
 pub mod transaction;
 
 use async_graphql::dynamic::{
@@ -1,3 +1,4 @@
+use async_graphql::dataloader::DataLoader;
 use async_graphql::dynamic::{Enum, Field, FieldFuture, InputObject, Object, TypeRef};
 use async_graphql::Value;
 use dojo_types::naming::get_tag;
@@ -13,8 +14,9 @@ use crate::constants::{
     ENTITY_ID_COLUMN, ENTITY_TABLE, ENTITY_TYPE_NAME, EVENT_MESSAGE_TABLE, EVENT_MESSAGE_TYPE_NAME,
     ID_COLUMN, INTERNAL_ENTITY_ID_KEY,
 };
+use crate::loaders::{RowKey, RowLoader};
 use crate::mapping::ENTITY_TYPE_MAPPING;
-use crate::query::data::{count_rows, fetch_multiple_rows, fetch_single_row};
+use crate::query::data::{count_rows, fetch_multiple_rows};
 use crate::query::value_mapping_from_row;
 use crate::types::TypeData;
 use crate::utils;
@@ -109,17 +111,17 @@ impl ResolvableObject for ModelDataObject {
             FieldFuture::new(async move {
                 let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
                 let order = parse_order_argument(&ctx);
-                let filters = parse_where_argument(&ctx, &where_mapping)?;
+                let filter = parse_where_argument(&ctx, &where_mapping, &table_name)?;
                 let connection = parse_connection_arguments(&ctx)?;
 
-                let total_count = count_rows(&mut conn, &table_name, &None, &filters).await?;
+                let total_count = count_rows(&mut conn, &table_name, &None, &filter).await?;
                 let (data, page_info) = fetch_multiple_rows(
                     &mut conn,
                     &table_name,
                     "internal_event_id",
                     &None,
                     &order,
-                    &filters,
+                    &filter,
                     &connection,
                     total_count,
                 )
@@ -199,7 +201,6 @@ pub fn object(type_name: &str, type_mapping: &TypeMapping, path_array: Vec<Strin
                     if let TypeData::Nested((_, nested_mapping)) = type_data {
                         return match ctx.parent_value.try_to_value()? {
                             Value::Object(indexmap) => {
-                                let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
                                 let entity_id =
                                     utils::extract::<String>(indexmap, INTERNAL_ENTITY_ID_KEY)?;
 
@@ -208,14 +209,18 @@ pub fn object(type_name: &str, type_mapping: &TypeMapping, path_array: Vec<Strin
                                     return Ok(Some(data.clone()));
                                 }
 
-                                // TODO: remove subqueries and use JOIN in parent query
-                                let data = fetch_single_row(
-                                    &mut conn,
-                                    &table_name,
-                                    ENTITY_ID_COLUMN,
-                                    &entity_id,
-                                )
-                                .await?;
+                                // batched with the nested rows of the other models of the page
+                                let data = ctx
+                                    .data::<DataLoader<RowLoader>>()?
+                                    .load_one(RowKey::new(
+                                        table_name.as_str(),
+                                        ENTITY_ID_COLUMN,
+                                        &entity_id,
+                                    ))
+                                    .await?
+                                    .ok_or_else(|| {
+                                        format!("No row found in {table_name} for {entity_id}")
+                                    })?;
                                 let result =
                                     value_mapping_from_row(&data, &nested_mapping, false, false)?;
 
@@ -255,10 +260,14 @@ fn entity_field() -> Field {
         FieldFuture::new(async move {
             match ctx.parent_value.try_to_value()? {
                 Value::Object(indexmap) => {
-                    let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
                     let entity_id = utils::extract::<String>(indexmap, INTERNAL_ENTITY_ID_KEY)?;
-                    let data =
-                        fetch_single_row(&mut conn, ENTITY_TABLE, ID_COLUMN, &entity_id).await?;
+                    let Some(data) = ctx
+                        .data::<DataLoader<RowLoader>>()?
+                        .load_one(RowKey::new(ENTITY_TABLE, ID_COLUMN, entity_id))
+                        .await?
+                    else {
+                        return Ok(None);
+                    };
                     let entity = value_mapping_from_row(&data, &ENTITY_TYPE_MAPPING, false, true)?;
 
                     Ok(Some(Value::Object(entity)))
@@ -277,11 +286,14 @@ fn event_message_field() -> Field {
             FieldFuture::new(async move {
                 match ctx.parent_value.try_to_value()? {
                     Value::Object(indexmap) => {
-                        let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
                         let entity_id = utils::extract::<String>(indexmap, INTERNAL_ENTITY_ID_KEY)?;
-                        let data =
-                            fetch_single_row(&mut conn, EVENT_MESSAGE_TABLE, ID_COLUMN, &entity_id)
-                                .await?;
+                        let Some(data) = ctx
+                            .data::<DataLoader<RowLoader>>()?
+                            .load_one(RowKey::new(EVENT_MESSAGE_TABLE, ID_COLUMN, entity_id))
+                            .await?
+                        else {
+                            return Ok(None);
+                        };
                         let event_message =
                             value_mapping_from_row(&data, &ENTITY_TYPE_MAPPING, false, true)?;
 
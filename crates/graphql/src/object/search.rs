@@ -0,0 +1,166 @@
+use std::sync::Arc;
+
+use async_graphql::dynamic::{Field, FieldFuture, InputValue, Object, TypeRef};
+use async_graphql::{Name, Value};
+use torii_storage::proto::{SearchMatch, SearchQuery, SearchResponse, TableSearchResults};
+use torii_storage::ReadOnlyStorage;
+
+use super::{BasicObject, ResolvableObject, TypeMapping, ValueMapping};
+use crate::constants::{
+    DEFAULT_LIMIT, SEARCH_MATCH_FIELD_NAMES, SEARCH_MATCH_FIELD_TYPE_NAME, SEARCH_MATCH_NAMES,
+    SEARCH_MATCH_TYPE_NAME, SEARCH_NAMES, SEARCH_RESULT_TYPE_NAME, TABLE_SEARCH_RESULT_NAMES,
+    TABLE_SEARCH_RESULT_TYPE_NAME,
+};
+use crate::mapping::{
+    SEARCH_MATCH_FIELD_TYPE_MAPPING, SEARCH_MATCH_TYPE_MAPPING, SEARCH_RESULT_TYPE_MAPPING,
+    TABLE_SEARCH_RESULT_TYPE_MAPPING,
+};
+use crate::utils::extract;
+
+#[derive(Debug)]
+pub struct SearchObject;
+
+impl BasicObject for SearchObject {
+    fn name(&self) -> (&str, &str) {
+        SEARCH_NAMES
+    }
+
+    fn type_name(&self) -> &str {
+        SEARCH_RESULT_TYPE_NAME
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &SEARCH_RESULT_TYPE_MAPPING
+    }
+}
+
+impl ResolvableObject for SearchObject {
+    fn resolvers(&self) -> Vec<Field> {
+        let field = Field::new(self.name().0, TypeRef::named_nn(self.type_name()), |ctx| {
+            FieldFuture::new(async move {
+                let storage = ctx.data::<Arc<dyn ReadOnlyStorage>>()?;
+                let query = SearchQuery {
+                    query: extract::<String>(ctx.args.as_index_map(), "query")?,
+                    limit: extract::<u64>(ctx.args.as_index_map(), "limit").unwrap_or(DEFAULT_LIMIT)
+                        as u32,
+                };
+
+                let response = storage.search(&query).await?;
+                Ok(Some(Value::Object(SearchObject::value_mapping(response))))
+            })
+        })
+        .argument(InputValue::new("query", TypeRef::named_nn(TypeRef::STRING)))
+        .argument(InputValue::new("limit", TypeRef::named(TypeRef::INT)));
+
+        vec![field]
+    }
+
+    // search results aren't paginated
+    fn connection_objects(&self) -> Option<Vec<Object>> {
+        None
+    }
+}
+
+impl SearchObject {
+    pub fn value_mapping(response: SearchResponse) -> ValueMapping {
+        let results = response
+            .results
+            .into_iter()
+            .map(|result| Value::Object(table_value_mapping(result)))
+            .collect();
+
+        ValueMapping::from([
+            (Name::new("total"), Value::from(response.total)),
+            (Name::new("results"), Value::List(results)),
+        ])
+    }
+}
+
+fn table_value_mapping(result: TableSearchResults) -> ValueMapping {
+    let matches = result
+        .matches
+        .into_iter()
+        .map(|search_match| Value::Object(match_value_mapping(search_match)))
+        .collect();
+
+    ValueMapping::from([
+        (Name::new("table"), Value::from(result.table)),
+        (Name::new("count"), Value::from(result.count)),
+        (Name::new("matches"), Value::List(matches)),
+    ])
+}
+
+fn match_value_mapping(search_match: SearchMatch) -> ValueMapping {
+    // fields are listed by name, so that the output is stable
+    let mut fields = search_match.fields.into_iter().collect::<Vec<_>>();
+    fields.sort();
+    let fields = fields
+        .into_iter()
+        .map(|(name, value)| {
+            Value::Object(ValueMapping::from([
+                (Name::new("name"), Value::from(name)),
+                (Name::new("value"), Value::from(value)),
+            ]))
+        })
+        .collect();
+
+    ValueMapping::from([
+        (Name::new("id"), Value::from(search_match.id)),
+        (Name::new("fields"), Value::List(fields)),
+        (
+            Name::new("score"),
+            search_match.score.map(Value::from).unwrap_or(Value::Null),
+        ),
+    ])
+}
+
+#[derive(Debug)]
+pub struct TableSearchResultObject;
+
+impl BasicObject for TableSearchResultObject {
+    fn name(&self) -> (&str, &str) {
+        TABLE_SEARCH_RESULT_NAMES
+    }
+
+    fn type_name(&self) -> &str {
+        TABLE_SEARCH_RESULT_TYPE_NAME
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &TABLE_SEARCH_RESULT_TYPE_MAPPING
+    }
+}
+
+#[derive(Debug)]
+pub struct SearchMatchObject;
+
+impl BasicObject for SearchMatchObject {
+    fn name(&self) -> (&str, &str) {
+        SEARCH_MATCH_NAMES
+    }
+
+    fn type_name(&self) -> &str {
+        SEARCH_MATCH_TYPE_NAME
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &SEARCH_MATCH_TYPE_MAPPING
+    }
+}
+
+#[derive(Debug)]
+pub struct SearchMatchFieldObject;
+
+impl BasicObject for SearchMatchFieldObject {
+    fn name(&self) -> (&str, &str) {
+        SEARCH_MATCH_FIELD_NAMES
+    }
+
+    fn type_name(&self) -> &str {
+        SEARCH_MATCH_FIELD_TYPE_NAME
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &SEARCH_MATCH_FIELD_TYPE_MAPPING
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use async_graphql::extensions::{
+    Extension, ExtensionContext, ExtensionFactory, NextPrepareRequest,
+};
+use async_graphql::{Request, ServerError, ServerResult, Value};
+use sha2::{Digest, Sha256};
+
+/// Allowlist of persisted queries, identified by the hex encoded SHA-256 hash of their text.
+///
+/// Clients send the hash of a persisted query in the `persistedQuery` extension of the request,
+/// as in the Apollo persisted queries protocol, and can omit the query text. When
+/// `allowlist_only` is set, queries that aren't persisted are refused.
+#[derive(Debug, Clone, Default)]
+pub struct PersistedQueries {
+    queries: HashMap<String, String>,
+    allowlist_only: bool,
+}
+
+impl PersistedQueries {
+    pub fn new(queries: impl IntoIterator<Item = String>, allowlist_only: bool) -> Self {
+        Self {
+            queries: queries
+                .into_iter()
+                .map(|query| (query_hash(&query), query))
+                .collect(),
+            allowlist_only,
+        }
+    }
+
+    /// Loads the persisted queries from a JSON manifest, either a list of queries or an object
+    /// mapping the query hashes to the queries.
+    pub fn from_file(path: &Path, allowlist_only: bool) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read persisted queries from {}", path.display()))?;
+        let manifest: serde_json::Value = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid persisted queries manifest {}", path.display()))?;
+
+        let queries = match manifest {
+            serde_json::Value::Array(queries) => queries
+                .into_iter()
+                .map(|query| match query {
+                    serde_json::Value::String(query) => Ok(query),
+                    _ => Err(anyhow::anyhow!("Persisted queries must be strings")),
+                })
+                .collect::<Result<Vec<_>>>()?,
+            serde_json::Value::Object(queries) => queries
+                .into_iter()
+                .map(|(hash, query)| match query {
+                    serde_json::Value::String(query) if query_hash(&query) == hash => Ok(query),
+                    serde_json::Value::String(_) => Err(anyhow::anyhow!(
+                        "Persisted query {hash} doesn't match its hash"
+                    )),
+                    _ => Err(anyhow::anyhow!("Persisted queries must be strings")),
+                })
+                .collect::<Result<Vec<_>>>()?,
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "Persisted queries manifest must be a list or an object"
+                ))
+            }
+        };
+
+        Ok(Self::new(queries, allowlist_only))
+    }
+
+    pub fn len(&self) -> usize {
+        self.queries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queries.is_empty()
+    }
+
+    /// Resolves the query of a request, filling in the text of persisted queries sent by hash.
+    pub fn resolve(&self, mut request: Request) -> ServerResult<Request> {
+        match persisted_query_hash(&request) {
+            Some(hash) => {
+                let Some(query) = self.queries.get(&hash) else {
+                    return Err(ServerError::new("PersistedQueryNotFound", None));
+                };
+
+                if request.query.is_empty() {
+                    request.query = query.clone();
+                } else if request.query != *query {
+                    return Err(ServerError::new(
+                        "The query doesn't match the persisted query hash.",
+                        None,
+                    ));
+                }
+            }
+            None if self.allowlist_only
+                && !self.queries.contains_key(&query_hash(&request.query)) =>
+            {
+                return Err(ServerError::new(
+                    "Only persisted queries are allowed, the query isn't in the allowlist.",
+                    None,
+                ));
+            }
+            None => {}
+        }
+
+        Ok(request)
+    }
+}
+
+impl ExtensionFactory for PersistedQueries {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(PersistedQueriesExtension(Arc::new(self.clone())))
+    }
+}
+
+struct PersistedQueriesExtension(Arc<PersistedQueries>);
+
+#[async_trait::async_trait]
+impl Extension for PersistedQueriesExtension {
+    async fn prepare_request(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        request: Request,
+        next: NextPrepareRequest<'_>,
+    ) -> ServerResult<Request> {
+        let request = self.0.resolve(request)?;
+        next.run(ctx, request).await
+    }
+}
+
+pub fn query_hash(query: &str) -> String {
+    format!("{:x}", Sha256::digest(query.as_bytes()))
+}
+
+// Hash of the `persistedQuery` extension, `{"version": 1, "sha256Hash": "<hash>"}`
+fn persisted_query_hash(request: &Request) -> Option<String> {
+    match request.extensions.get("persistedQuery") {
+        Some(Value::Object(persisted_query)) => match persisted_query.get("sha256Hash") {
+            Some(Value::String(hash)) => Some(hash.to_lowercase()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql::{Request, Value};
+
+    use super::{query_hash, PersistedQueries};
+
+    const QUERY: &str = "{ models { totalCount } }";
+
+    fn persisted_request(query: &str, hash: &str) -> Request {
+        let mut request = Request::new(query);
+        request.extensions.insert(
+            "persistedQuery".to_string(),
+            Value::from_json(serde_json::json!({ "version": 1, "sha256Hash": hash })).unwrap(),
+        );
+        request
+    }
+
+    #[test]
+    fn test_resolve_persisted_query() {
+        let persisted = PersistedQueries::new([QUERY.to_string()], false);
+
+        // the query text is filled in from the hash
+        let request = persisted
+            .resolve(persisted_request("", &query_hash(QUERY)))
+            .unwrap();
+        assert_eq!(request.query, QUERY);
+
+        assert!(persisted
+            .resolve(persisted_request(
+                "",
+                &query_hash("{ entities { totalCount } }")
+            ))
+            .is_err());
+        assert!(persisted
+            .resolve(persisted_request(
+                "{ entities { totalCount } }",
+                &query_hash(QUERY)
+            ))
+            .is_err());
+
+        // arbitrary queries are allowed
+        assert!(persisted
+            .resolve(Request::new("{ entities { totalCount } }"))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_allowlist_only() {
+        let persisted = PersistedQueries::new([QUERY.to_string()], true);
+
+        assert!(persisted.resolve(Request::new(QUERY)).is_ok());
+        assert!(persisted
+            .resolve(Request::new("{ entities { totalCount } }"))
+            .is_err());
+    }
+}
@@ -3,7 +3,7 @@ use sqlx::sqlite::SqliteRow;
 use sqlx::{Result, Row, SqliteConnection};
 use torii_sqlite::constants::WORLD_CONTRACT_TYPE;
 
-use super::filter::{Filter, FilterValue};
+use super::filter::WhereFilter;
 use super::order::{CursorDirection, Direction, Order};
 use crate::constants::DEFAULT_LIMIT;
 use crate::object::connection::{cursor, ConnectionArguments};
@@ -12,16 +12,21 @@ pub async fn count_rows(
     conn: &mut SqliteConnection,
     table_name: &str,
     keys: &Option<Vec<String>>,
-    filters: &Option<Vec<Filter>>,
+    filter: &Option<WhereFilter>,
 ) -> Result<i64> {
     let mut query = format!("SELECT COUNT(*) FROM [{}]", table_name);
-    let conditions = build_conditions(keys, filters);
+    let (conditions, bind_values) = build_conditions(keys, filter)?;
 
     if !conditions.is_empty() {
         query.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
     }
 
-    let result: (i64,) = sqlx::query_as(&query).fetch_one(conn).await?;
+    let mut query = sqlx::query_as::<_, (i64,)>(&query);
+    for value in bind_values {
+        query = query.bind(value);
+    }
+
+    let result = query.fetch_one(conn).await?;
     Ok(result.0)
 }
 
@@ -123,11 +128,11 @@ pub async fn fetch_multiple_rows(
     id_column: &str,
     keys: &Option<Vec<String>>,
     order: &Option<Order>,
-    filters: &Option<Vec<Filter>>,
+    filter: &Option<WhereFilter>,
     connection: &ConnectionArguments,
     total_count: i64,
 ) -> Result<(Vec<SqliteRow>, PageInfo)> {
-    let mut conditions = build_conditions(keys, filters);
+    let (mut conditions, bind_values) = build_conditions(keys, filter)?;
 
     let mut cursor_param = &connection.after;
     if let Some(after_cursor) = &connection.after {
@@ -199,7 +204,12 @@ pub async fn fetch_multiple_rows(
         query.push_str(&format!(" OFFSET {}", offset));
     }
 
-    let mut data = sqlx::query(&query).fetch_all(conn).await?;
+    let mut query = sqlx::query(&query);
+    for value in bind_values {
+        query = query.bind(value);
+    }
+
+    let mut data = query.fetch_all(conn).await?;
     let mut page_info = PageInfo {
         has_previous_page: false,
         has_next_page: false,
@@ -297,8 +307,13 @@ fn handle_cursor(
     }
 }
 
-fn build_conditions(keys: &Option<Vec<String>>, filters: &Option<Vec<Filter>>) -> Vec<String> {
+// Conditions on the keys and the filter, along with the values bound to the filter placeholders
+fn build_conditions(
+    keys: &Option<Vec<String>>,
+    filter: &Option<WhereFilter>,
+) -> Result<(Vec<String>, Vec<String>)> {
     let mut conditions = Vec::new();
+    let mut bind_values = Vec::new();
 
     if let Some(keys) = keys {
         if !keys.is_empty() {
@@ -312,26 +327,15 @@ fn build_conditions(keys: &Option<Vec<String>>, filters: &Option<Vec<Filter>>) -
         }
     }
 
-    if let Some(filters) = filters {
-        conditions.extend(filters.iter().map(|filter| match &filter.value {
-            FilterValue::Int(i) => format!("[{}] {} {}", filter.field, filter.comparator, i),
-            FilterValue::String(s) => format!("[{}] {} '{}'", filter.field, filter.comparator, s),
-            FilterValue::List(list) => {
-                let values = list
-                    .iter()
-                    .map(|value| match value {
-                        FilterValue::Int(i) => i.to_string(),
-                        FilterValue::String(s) => format!("'{}'", s),
-                        FilterValue::List(_) => unreachable!(),
-                    })
-                    .collect::<Vec<_>>()
-                    .join(", ");
-                format!("[{}] {} ({})", filter.field, filter.comparator, values)
-            }
-        }));
+    if let Some(filter) = filter {
+        let (condition, values) = filter.to_sql().map_err(|e| sqlx::Error::Encode(e.into()))?;
+        if !condition.is_empty() {
+            conditions.push(format!("({condition})"));
+            bind_values = values;
+        }
     }
 
-    conditions
+    Ok((conditions, bind_values))
 }
 
 fn keys_to_pattern(keys: &[String], use_regex: bool) -> String {
@@ -1,12 +1,22 @@
-use core::fmt;
-
-use async_graphql::Name;
 use strum::IntoEnumIterator;
-use strum_macros::{AsRefStr, Display, EnumIter};
+use strum_macros::{AsRefStr, EnumIter};
+use torii_sqlite::error::Error;
+use torii_sqlite::model::build_composite_clause;
+use torii_storage::proto::{
+    Clause, ComparisonOperator, CompositeClause, LogicalOperator, MemberClause,
+};
 
 #[derive(AsRefStr, Debug, Clone, PartialEq, EnumIter)]
 #[strum(serialize_all = "UPPERCASE")]
 pub enum Comparator {
+    // Array comparators end like the scalar ones ("ARRAYLENGTHGT" ends with "GT"), so they are
+    // checked first
+    ContainsAll,
+    ContainsAny,
+    Contains,
+    ArrayLengthEq,
+    ArrayLengthGt,
+    ArrayLengthLt,
     Gt,
     Gte,
     Lt,
@@ -22,52 +32,170 @@ pub enum Comparator {
     Like,
 }
 
-impl fmt::Display for Comparator {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl Comparator {
+    pub fn is_array(&self) -> bool {
+        matches!(
+            self,
+            Comparator::Contains
+                | Comparator::ContainsAll
+                | Comparator::ContainsAny
+                | Comparator::ArrayLengthEq
+                | Comparator::ArrayLengthGt
+                | Comparator::ArrayLengthLt
+        )
+    }
+
+    /// Operator of the member clause. `NOTLIKE` is a negated `LIKE` clause.
+    pub fn operator(&self) -> ComparisonOperator {
         match self {
-            Comparator::Gt => write!(f, ">"),
-            Comparator::Gte => write!(f, ">="),
-            Comparator::Lt => write!(f, "<"),
-            Comparator::Lte => write!(f, "<="),
-            Comparator::Neq => write!(f, "!="),
-            Comparator::Eq => write!(f, "="),
-            Comparator::In => write!(f, "IN"),
-            Comparator::NotIn => write!(f, "NOT IN"),
-            Comparator::Like => write!(f, "LIKE"),
-            Comparator::NotLike => write!(f, "NOT LIKE"),
+            Comparator::Contains => ComparisonOperator::Contains,
+            Comparator::ContainsAll => ComparisonOperator::ContainsAll,
+            Comparator::ContainsAny => ComparisonOperator::ContainsAny,
+            Comparator::ArrayLengthEq => ComparisonOperator::ArrayLengthEq,
+            Comparator::ArrayLengthGt => ComparisonOperator::ArrayLengthGt,
+            Comparator::ArrayLengthLt => ComparisonOperator::ArrayLengthLt,
+            Comparator::Gt => ComparisonOperator::Gt,
+            Comparator::Gte => ComparisonOperator::Gte,
+            Comparator::Lt => ComparisonOperator::Lt,
+            Comparator::Lte => ComparisonOperator::Lte,
+            Comparator::Neq => ComparisonOperator::Neq,
+            Comparator::Eq => ComparisonOperator::Eq,
+            Comparator::NotIn => ComparisonOperator::NotIn,
+            Comparator::In => ComparisonOperator::In,
+            Comparator::NotLike | Comparator::Like => ComparisonOperator::Like,
         }
     }
 }
 
-#[derive(Debug, Display)]
-pub enum FilterValue {
-    Int(i64),
-    String(String),
-    List(Vec<FilterValue>),
+/// Splits a where input field name into the member it filters and its comparator, e.g. `xGTE`
+/// into `x` and [`Comparator::Gte`]. Fields without a comparator suffix are equality checks.
+pub fn parse_comparator(input: &str) -> (&str, Comparator) {
+    for comparator in Comparator::iter() {
+        if let Some(member) = input.strip_suffix(comparator.as_ref()) {
+            return (member, comparator);
+        }
+    }
+
+    (input, Comparator::Eq)
 }
 
-#[derive(Debug)]
-pub struct Filter {
-    pub field: String,
-    pub comparator: Comparator,
-    pub value: FilterValue,
+/// Filter on the rows of a table, comparisons of its columns grouped with `and`, `or` and
+/// `not`.
+#[derive(Debug, Clone)]
+pub enum WhereFilter {
+    /// Comparison of a column, the member being the column of the clause model table
+    Member(MemberClause),
+    And(Vec<WhereFilter>),
+    Or(Vec<WhereFilter>),
+    Not(Box<WhereFilter>),
 }
 
-pub fn parse_filter(input: &Name, value: FilterValue) -> Filter {
-    for comparator in Comparator::iter() {
-        if let Some(field) = input.strip_suffix(comparator.as_ref()) {
-            return Filter {
-                field: field.to_string(),
-                comparator: comparator.clone(),
-                value,
-            };
+impl WhereFilter {
+    /// Builds the SQL condition of the filter, along with the values to bind to its placeholders.
+    /// Member comparisons are built like the clauses of the entity queries. The condition is
+    /// empty when the filter matches all the rows.
+    pub fn to_sql(&self) -> Result<(String, Vec<String>), Error> {
+        match self {
+            WhereFilter::Member(member) => build_composite_clause(
+                &member.model,
+                "",
+                &CompositeClause {
+                    operator: LogicalOperator::And,
+                    clauses: vec![Clause::Member(member.clone())],
+                },
+                false,
+            ),
+            WhereFilter::And(filters) | WhereFilter::Or(filters) => {
+                let mut conditions = Vec::new();
+                let mut bind_values = Vec::new();
+                for filter in filters {
+                    let (condition, values) = filter.to_sql()?;
+                    if !condition.is_empty() {
+                        conditions.push(format!("({condition})"));
+                        bind_values.extend(values);
+                    }
+                }
+
+                let separator = match self {
+                    WhereFilter::Or(_) => " OR ",
+                    _ => " AND ",
+                };
+                Ok((conditions.join(separator), bind_values))
+            }
+            WhereFilter::Not(filter) => {
+                let (condition, bind_values) = filter.to_sql()?;
+                // negating a filter that matches all the rows matches none
+                let condition = if condition.is_empty() {
+                    "1".to_string()
+                } else {
+                    condition
+                };
+                Ok((format!("NOT ({condition})"), bind_values))
+            }
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use dojo_types::primitive::Primitive;
+    use torii_storage::proto::{ComparisonOperator, MemberClause, MemberValue};
+
+    use super::{parse_comparator, Comparator, WhereFilter};
+
+    fn member(member: &str, operator: ComparisonOperator, value: MemberValue) -> WhereFilter {
+        WhereFilter::Member(MemberClause {
+            model: "ns-Position".to_string(),
+            member: member.to_string(),
+            operator,
+            value,
+        })
+    }
+
+    #[test]
+    fn test_parse_comparator() {
+        assert_eq!(parse_comparator("xGTE"), ("x", Comparator::Gte));
+        assert_eq!(parse_comparator("nameNOTIN"), ("name", Comparator::NotIn));
+        assert_eq!(
+            parse_comparator("movesARRAYLENGTHGT"),
+            ("moves", Comparator::ArrayLengthGt)
+        );
+        assert_eq!(
+            parse_comparator("movesCONTAINSALL"),
+            ("moves", Comparator::ContainsAll)
+        );
+        assert_eq!(parse_comparator("vec.x"), ("vec.x", Comparator::Eq));
+    }
+
+    #[test]
+    fn test_where_filter_sql() {
+        let filter = WhereFilter::And(vec![
+            member(
+                "vec.x",
+                ComparisonOperator::Gt,
+                MemberValue::Primitive(Primitive::U32(Some(1))),
+            ),
+            WhereFilter::Or(vec![
+                WhereFilter::Not(Box::new(member(
+                    "player",
+                    ComparisonOperator::Eq,
+                    MemberValue::String("0x1".to_string()),
+                ))),
+                member(
+                    "name",
+                    ComparisonOperator::Like,
+                    MemberValue::String("bob%".to_string()),
+                ),
+            ]),
+            WhereFilter::And(vec![]),
+        ]);
 
-    // If no suffix found assume equality comparison
-    Filter {
-        field: input.to_string(),
-        comparator: Comparator::Eq,
-        value,
+        let (condition, bind_values) = filter.to_sql().unwrap();
+        assert_eq!(
+            condition,
+            "(([ns-Position].[vec.x] > ?)) AND ((NOT (([ns-Position].[player] = ?))) OR \
+             (([ns-Position].[name] LIKE ?)))"
+        );
+        assert_eq!(bind_values, vec!["1", "0x1", "bob%"]);
     }
 }
@@ -3,6 +3,7 @@ use async_graphql::dynamic::{Object, Scalar, Schema, Subscription, Union};
 use dojo_types::schema::Ty;
 use sqlx::SqlitePool;
 use starknet::providers::Provider;
+use std::collections::HashMap;
 use std::sync::Arc;
 use torii_messaging::{Messaging, MessagingTrait};
 use torii_sqlite::types::Model;
@@ -12,12 +13,22 @@ use super::object::connection::page_info::PageInfoObject;
 use super::object::entity::EntityObject;
 use super::object::event::EventObject;
 use super::object::model_data::ModelDataObject;
-use super::types::ScalarType;
+use super::types::{ScalarType, TypeMapping};
 use super::utils;
 use crate::constants::{
     EMPTY_TYPE_NAME, ERC1155_TYPE_NAME, ERC20_TYPE_NAME, ERC721_TYPE_NAME, MUTATION_TYPE_NAME,
     QUERY_TYPE_NAME, SUBSCRIPTION_TYPE_NAME, TOKEN_UNION_TYPE_NAME,
 };
+use crate::limits::QueryLimits;
+use crate::loaders::DataLoaders;
+use crate::object::achievement::{
+    AchievementObject, AchievementProgressionObject, AchievementTaskObject,
+    PlayerAchievementObject, PlayerAchievementProgressObject, PlayerAchievementStatsObject,
+    TaskProgressObject,
+};
+use crate::object::activity::{ActivityActionObject, ActivityObject};
+use crate::object::aggregation::AggregationObject;
+use crate::object::contract::ContractObject;
 use crate::object::controller::ControllerObject;
 use crate::object::empty::EmptyObject;
 use crate::object::erc::erc_token::{
@@ -31,8 +42,12 @@ use crate::object::metadata::social::SocialObject;
 use crate::object::metadata::MetadataObject;
 use crate::object::model::ModelObject;
 use crate::object::publish_message::PublishMessageObject;
+use crate::object::search::{
+    SearchMatchFieldObject, SearchMatchObject, SearchObject, TableSearchResultObject,
+};
 use crate::object::transaction::{CallObject, TransactionObject};
 use crate::object::{BasicObject, ObjectVariant};
+use crate::persisted_queries::PersistedQueries;
 use crate::query::build_type_mapping;
 
 // The graphql schema is built dynamically at runtime, this is because we won't know the schema of
@@ -44,9 +59,171 @@ pub async fn build_schema<P: Provider + Sync + Send + 'static>(
     messaging: Arc<Messaging<P>>,
     storage: Arc<dyn ReadOnlyStorage>,
 ) -> Result<Schema> {
-    // build world gql objects
-    let (objects, unions) = build_objects(pool).await?;
+    SchemaBuilder::default()
+        .build(pool, messaging, storage)
+        .await
+}
+
+/// Limits and allowlist enforced on the requests run against the schema.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaConfig {
+    pub limits: QueryLimits,
+    pub persisted_queries: Option<PersistedQueries>,
+}
+
+/// Builds the schema repeatedly as models get registered and upgraded. The type mappings of the
+/// models are kept between builds, so a rebuild only parses the new or changed models.
+#[derive(Debug, Default)]
+pub struct SchemaBuilder {
+    config: SchemaConfig,
+    models: HashMap<String, ModelTypes>,
+}
+
+#[derive(Debug, Clone)]
+struct ModelTypes {
+    // Raw model schema, to detect upgrades
+    raw_schema: String,
+    field_name: String,
+    type_name: String,
+    type_mapping: TypeMapping,
+    schema: Ty,
+}
+
+impl SchemaBuilder {
+    pub fn new(config: SchemaConfig) -> Self {
+        Self {
+            config,
+            models: HashMap::new(),
+        }
+    }
+
+    pub async fn build<P: Provider + Sync + Send + 'static>(
+        &mut self,
+        pool: &SqlitePool,
+        messaging: Arc<Messaging<P>>,
+        storage: Arc<dyn ReadOnlyStorage>,
+    ) -> Result<Schema> {
+        // build world gql objects
+        let (objects, unions) = self.build_objects(pool).await?;
+        build_schema_from_objects(&self.config, objects, unions, pool, messaging, storage)
+    }
+
+    async fn build_objects(
+        &mut self,
+        pool: &SqlitePool,
+    ) -> Result<(Vec<ObjectVariant>, Vec<Union>)> {
+        let mut conn = pool.acquire().await?;
+        let models: Vec<Model> = sqlx::query_as("SELECT * FROM models")
+            .fetch_all(&mut *conn)
+            .await?;
+
+        // predefined objects
+        let mut objects: Vec<ObjectVariant> = vec![
+            ObjectVariant::Resolvable(Box::new(EntityObject)),
+            ObjectVariant::Resolvable(Box::new(EventMessageObject)),
+            ObjectVariant::Resolvable(Box::new(EventObject)),
+            ObjectVariant::Resolvable(Box::new(MetadataObject)),
+            ObjectVariant::Resolvable(Box::new(ModelObject)),
+            ObjectVariant::Resolvable(Box::new(TransactionObject)),
+            ObjectVariant::Resolvable(Box::new(ErcBalanceObject)),
+            ObjectVariant::Resolvable(Box::new(ErcTransferObject)),
+            ObjectVariant::Resolvable(Box::new(ControllerObject)),
+            ObjectVariant::Resolvable(Box::new(TokenObject)),
+            ObjectVariant::Resolvable(Box::new(AggregationObject)),
+            ObjectVariant::Resolvable(Box::new(ActivityObject)),
+            ObjectVariant::Resolvable(Box::new(AchievementObject)),
+            ObjectVariant::Resolvable(Box::new(PlayerAchievementObject)),
+            ObjectVariant::Resolvable(Box::new(SearchObject)),
+            ObjectVariant::Resolvable(Box::new(ContractObject)),
+            ObjectVariant::Basic(Box::new(SocialObject)),
+            ObjectVariant::Basic(Box::new(ContentObject)),
+            ObjectVariant::Basic(Box::new(PageInfoObject)),
+            ObjectVariant::Basic(Box::new(Erc721TokenObject)),
+            ObjectVariant::Basic(Box::new(Erc20TokenObject)),
+            ObjectVariant::Basic(Box::new(Erc1155TokenObject)),
+            ObjectVariant::Basic(Box::new(EmptyObject)),
+            ObjectVariant::Basic(Box::new(CallObject)),
+            ObjectVariant::Basic(Box::new(ActivityActionObject)),
+            ObjectVariant::Basic(Box::new(AchievementTaskObject)),
+            ObjectVariant::Basic(Box::new(AchievementProgressionObject)),
+            ObjectVariant::Basic(Box::new(PlayerAchievementStatsObject)),
+            ObjectVariant::Basic(Box::new(PlayerAchievementProgressObject)),
+            ObjectVariant::Basic(Box::new(TaskProgressObject)),
+            ObjectVariant::Basic(Box::new(TableSearchResultObject)),
+            ObjectVariant::Basic(Box::new(SearchMatchObject)),
+            ObjectVariant::Basic(Box::new(SearchMatchFieldObject)),
+        ];
+
+        // model union object
+        let mut unions: Vec<Union> = Vec::new();
+        let mut model_union = Union::new("ModelUnion");
+
+        // erc_token union object
+        let erc_token_union = Union::new(TOKEN_UNION_TYPE_NAME)
+            .possible_type(ERC20_TYPE_NAME)
+            .possible_type(ERC721_TYPE_NAME)
+            .possible_type(ERC1155_TYPE_NAME);
+
+        unions.push(erc_token_union);
+
+        // Forget the models that are gone, e.g. after a reindex
+        self.models
+            .retain(|id, _| models.iter().any(|model| &model.id == id));
+
+        // model data objects
+        for model in &models {
+            // Only parse the models that are new or were upgraded since the last build
+            let up_to_date = self
+                .models
+                .get(&model.id)
+                .is_some_and(|model_types| model_types.raw_schema == model.schema);
+            if !up_to_date {
+                let schema: Ty = serde_json::from_str(&model.schema)
+                    .map_err(|e| anyhow::anyhow!(format!("Failed to parse model schema: {e}")))?;
+                let model_types = ModelTypes {
+                    raw_schema: model.schema.clone(),
+                    field_name: utils::field_name_from_names(&model.namespace, &model.name),
+                    type_name: utils::type_name_from_names(&model.namespace, &model.name),
+                    type_mapping: build_type_mapping(&model.namespace, &schema),
+                    schema,
+                };
+                self.models.insert(model.id.clone(), model_types);
+            }
+            let model_types = &self.models[&model.id];
+
+            if !model_types.type_mapping.is_empty() {
+                // add models objects & unions
+                model_union = model_union.possible_type(&model_types.type_name);
 
+                objects.push(ObjectVariant::Resolvable(Box::new(ModelDataObject::new(
+                    model_types.field_name.clone(),
+                    model_types.type_name.clone(),
+                    model_types.type_mapping.clone(),
+                    model_types.schema.clone(),
+                ))));
+            }
+        }
+
+        // When creating an empty union, add the empty type (this is required otherwise the schema
+        // will be invalid)
+        if models.is_empty() {
+            model_union = model_union.possible_type(EMPTY_TYPE_NAME);
+        }
+
+        unions.push(model_union);
+
+        Ok((objects, unions))
+    }
+}
+
+fn build_schema_from_objects<P: Provider + Sync + Send + 'static>(
+    config: &SchemaConfig,
+    objects: Vec<ObjectVariant>,
+    unions: Vec<Union>,
+    pool: &SqlitePool,
+    messaging: Arc<Messaging<P>>,
+    storage: Arc<dyn ReadOnlyStorage>,
+) -> Result<Schema> {
     let mut schema_builder = Schema::build(
         QUERY_TYPE_NAME,
         Some(MUTATION_TYPE_NAME),
@@ -127,6 +304,14 @@ pub async fn build_schema<P: Provider + Sync + Send + 'static>(
         schema_builder = schema_builder.register(object);
     }
 
+    // persisted queries are resolved before the limits are checked on the query
+    if let Some(persisted_queries) = &config.persisted_queries {
+        schema_builder = schema_builder.extension(persisted_queries.clone());
+    }
+    schema_builder = schema_builder
+        .extension(config.limits)
+        .extension(DataLoaders::new(pool.clone()));
+
     schema_builder
         .register(query_root)
         .register(mutation_root)
@@ -137,77 +322,3 @@ pub async fn build_schema<P: Provider + Sync + Send + 'static>(
         .finish()
         .map_err(|e| e.into())
 }
-
-async fn build_objects(pool: &SqlitePool) -> Result<(Vec<ObjectVariant>, Vec<Union>)> {
-    let mut conn = pool.acquire().await?;
-    let models: Vec<Model> = sqlx::query_as("SELECT * FROM models")
-        .fetch_all(&mut *conn)
-        .await?;
-
-    // predefined objects
-    let mut objects: Vec<ObjectVariant> = vec![
-        ObjectVariant::Resolvable(Box::new(EntityObject)),
-        ObjectVariant::Resolvable(Box::new(EventMessageObject)),
-        ObjectVariant::Resolvable(Box::new(EventObject)),
-        ObjectVariant::Resolvable(Box::new(MetadataObject)),
-        ObjectVariant::Resolvable(Box::new(ModelObject)),
-        ObjectVariant::Resolvable(Box::new(TransactionObject)),
-        ObjectVariant::Resolvable(Box::new(ErcBalanceObject)),
-        ObjectVariant::Resolvable(Box::new(ErcTransferObject)),
-        ObjectVariant::Resolvable(Box::new(ControllerObject)),
-        ObjectVariant::Resolvable(Box::new(TokenObject)),
-        ObjectVariant::Basic(Box::new(SocialObject)),
-        ObjectVariant::Basic(Box::new(ContentObject)),
-        ObjectVariant::Basic(Box::new(PageInfoObject)),
-        ObjectVariant::Basic(Box::new(Erc721TokenObject)),
-        ObjectVariant::Basic(Box::new(Erc20TokenObject)),
-        ObjectVariant::Basic(Box::new(Erc1155TokenObject)),
-        ObjectVariant::Basic(Box::new(EmptyObject)),
-        ObjectVariant::Basic(Box::new(CallObject)),
-    ];
-
-    // model union object
-    let mut unions: Vec<Union> = Vec::new();
-    let mut model_union = Union::new("ModelUnion");
-
-    // erc_token union object
-    let erc_token_union = Union::new(TOKEN_UNION_TYPE_NAME)
-        .possible_type(ERC20_TYPE_NAME)
-        .possible_type(ERC721_TYPE_NAME)
-        .possible_type(ERC1155_TYPE_NAME);
-
-    unions.push(erc_token_union);
-
-    // model data objects
-    for model in &models {
-        let schema: Ty = serde_json::from_str(&model.schema)
-            .map_err(|e| anyhow::anyhow!(format!("Failed to parse model schema: {e}")))?;
-        let type_mapping = build_type_mapping(&model.namespace, &schema);
-
-        if !type_mapping.is_empty() {
-            // add models objects & unions
-            let field_name = utils::field_name_from_names(&model.namespace, &model.name);
-
-            let type_name = utils::type_name_from_names(&model.namespace, &model.name);
-
-            model_union = model_union.possible_type(&type_name);
-
-            objects.push(ObjectVariant::Resolvable(Box::new(ModelDataObject::new(
-                field_name,
-                type_name,
-                type_mapping.clone(),
-                schema,
-            ))));
-        }
-    }
-
-    // When creating an empty union, add the empty type (this is required otherwise the schema will
-    // be invalid)
-    if models.is_empty() {
-        model_union = model_union.possible_type(EMPTY_TYPE_NAME);
-    }
-
-    unions.push(model_union);
-
-    Ok((objects, unions))
-}
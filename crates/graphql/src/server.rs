@@ -1,37 +1,180 @@
 use std::future::Future;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_graphql::dynamic::Schema;
-use async_graphql::Request;
+use async_graphql::{Data, Executor, Request, Response};
 use async_graphql_warp::graphql_subscription;
+use futures_util::stream::{BoxStream, Stream, StreamExt};
 use sqlx::{Pool, Sqlite};
 use starknet::providers::Provider;
 use tokio::sync::broadcast::Receiver;
+use tokio::sync::watch;
+use tokio::time::Instant;
+use torii_broker::types::ModelUpdate;
+use torii_broker::MemoryBroker;
 use torii_messaging::Messaging;
 use torii_storage::ReadOnlyStorage;
+use tracing::{debug, error, info};
 use warp::{Filter, Rejection, Reply};
 
 use crate::playground::{graphiql::GraphiQLSource, graphiql_plugin::GraphiQLPlugin};
 
-use super::schema::build_schema;
+use super::schema::{SchemaBuilder, SchemaConfig};
+
+pub(crate) const LOG_TARGET: &str = "torii::graphql::server";
+
+/// Quiet period after a model registration before the schema is rebuilt, so that a burst of
+/// registrations (e.g. a world migration) results in a single rebuild.
+const REBUILD_DEBOUNCE: Duration = Duration::from_millis(500);
+/// Longest a rebuild can be deferred while model registrations keep coming.
+const MAX_REBUILD_DELAY: Duration = Duration::from_secs(5);
+
+/// Handle to the live GraphQL schema, swapped atomically when models are registered.
+///
+/// Queries run against the latest schema. Subscriptions are bound to the schema they started
+/// with, and are completed once it gets swapped so that clients resubscribe against the new one.
+#[derive(Clone)]
+pub struct SchemaHandle {
+    schema: Arc<watch::Sender<Schema>>,
+}
+
+impl std::fmt::Debug for SchemaHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SchemaHandle").finish_non_exhaustive()
+    }
+}
+
+impl SchemaHandle {
+    pub fn new(schema: Schema) -> Self {
+        Self {
+            schema: Arc::new(watch::Sender::new(schema)),
+        }
+    }
+
+    /// Returns the current schema.
+    pub fn current(&self) -> Schema {
+        self.schema.borrow().clone()
+    }
+
+    /// Replaces the schema, completing the subscriptions running against the previous one.
+    pub fn swap(&self, schema: Schema) {
+        self.schema.send_replace(schema);
+    }
+}
+
+impl Executor for SchemaHandle {
+    async fn execute(&self, request: Request) -> Response {
+        self.current().execute(request).await
+    }
+
+    fn execute_stream(
+        &self,
+        request: Request,
+        session_data: Option<Arc<Data>>,
+    ) -> BoxStream<'static, Response> {
+        let mut swapped = self.schema.subscribe();
+        let schema = swapped.borrow_and_update().clone();
+
+        schema
+            .execute_stream_with_session_data(request, session_data.unwrap_or_default())
+            .take_until(async move {
+                let _ = swapped.changed().await;
+            })
+            .boxed()
+    }
+}
 
 pub async fn new<P: Provider + Sync + Send + Clone + 'static>(
     mut shutdown_rx: Receiver<()>,
     pool: &Pool<Sqlite>,
     messaging: Arc<Messaging<P>>,
     storage: Arc<dyn ReadOnlyStorage>,
+    config: SchemaConfig,
 ) -> (SocketAddr, impl Future<Output = ()> + 'static) {
-    let schema = build_schema(pool, messaging, storage).await.unwrap();
-    let routes = graphql_filter(schema);
-    warp::serve(routes).bind_with_graceful_shutdown(([127, 0, 0, 1], 0), async move {
-        shutdown_rx.recv().await.ok();
+    // Subscribed before the schema is built, so that no model registered meanwhile is missed
+    let updates = MemoryBroker::<ModelUpdate>::subscribe();
+    let mut builder = SchemaBuilder::new(config);
+    let schema = builder
+        .build(pool, messaging.clone(), storage.clone())
+        .await
+        .unwrap();
+    let handle = SchemaHandle::new(schema);
+
+    let rebuilder = rebuild_on_model_updates(
+        handle.clone(),
+        updates,
+        builder,
+        pool.clone(),
+        messaging,
+        storage,
+        shutdown_rx.resubscribe(),
+    );
+
+    let routes = graphql_filter(handle);
+    let (addr, server) =
+        warp::serve(routes).bind_with_graceful_shutdown(([127, 0, 0, 1], 0), async move {
+            shutdown_rx.recv().await.ok();
+        });
+
+    (addr, async move {
+        tokio::join!(server, rebuilder);
     })
 }
 
-fn graphql_filter(schema: Schema) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+/// Rebuilds the schema when models are registered or upgraded, and swaps it in place.
+async fn rebuild_on_model_updates<P: Provider + Sync + Send + Clone + 'static>(
+    handle: SchemaHandle,
+    mut updates: impl Stream<Item = ModelUpdate> + Unpin,
+    mut builder: SchemaBuilder,
+    pool: Pool<Sqlite>,
+    messaging: Arc<Messaging<P>>,
+    storage: Arc<dyn ReadOnlyStorage>,
+    mut shutdown_rx: Receiver<()>,
+) {
+    loop {
+        tokio::select! {
+            _ = shutdown_rx.recv() => break,
+            update = updates.next() => {
+                if update.is_none() {
+                    break;
+                }
+            }
+        }
+
+        // Wait for the registrations to settle down
+        let deadline = Instant::now() + MAX_REBUILD_DELAY;
+        let mut pending = 1;
+        loop {
+            let wait_until = (Instant::now() + REBUILD_DEBOUNCE).min(deadline);
+            match tokio::time::timeout_at(wait_until, updates.next()).await {
+                Ok(Some(_)) => pending += 1,
+                Ok(None) | Err(_) => break,
+            }
+        }
+
+        debug!(target: LOG_TARGET, models = pending, "Rebuilding GraphQL schema.");
+        match builder
+            .build(&pool, messaging.clone(), storage.clone())
+            .await
+        {
+            Ok(schema) => {
+                handle.swap(schema);
+                info!(target: LOG_TARGET, models = pending, "GraphQL schema updated.");
+            }
+            Err(e) => {
+                error!(target: LOG_TARGET, error = ?e, "Failed to rebuild GraphQL schema.");
+            }
+        }
+    }
+}
+
+pub(crate) fn graphql_filter(
+    schema: SchemaHandle,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     let graphql_post = async_graphql_warp::graphql(schema.clone()).and_then(
-        move |(schema, request): (Schema, Request)| async move {
+        move |(schema, request): (SchemaHandle, Request)| async move {
             // Execute query
             let response = schema.execute(request).await;
             // Return result
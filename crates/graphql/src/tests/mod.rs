@@ -44,6 +44,8 @@ mod metadata_test;
 mod models_ordering_test;
 mod models_test;
 mod publish_message_test;
+mod server_test;
+mod storage_test;
 mod subscription_test;
 
 use crate::schema::build_schema;
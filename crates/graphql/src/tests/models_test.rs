@@ -279,6 +279,17 @@ mod tests {
         let first_record = connection.edges.first().unwrap();
         assert_eq!(first_record.node.type_u16, 3);
 
+        // where filter with OR and NOT groups
+        let records = records_model_query(
+            &schema,
+            "(where: { or: [{ type_u8LTE: 1 }, { type_u16GTE: 8 }], not: { type_u32: 9 } }, \
+             order: { direction: DESC, field: RECORD_ID })",
+        )
+        .await;
+        let connection: Connection<Record> = serde_json::from_value(records).unwrap();
+        assert_eq!(connection.total_count, 3);
+        assert_eq!(connection.edges.first().unwrap().node.type_u8, 8);
+
         // NOTE: Server side is gonna parse "0x5" and "5" to hexadecimal format
         let felt_str_0x5 = "0x5";
         let felt_int_5 = "5";
@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use async_graphql::dynamic::{
+        Field, FieldFuture, Object, Schema, Subscription, SubscriptionField,
+        SubscriptionFieldFuture, TypeRef,
+    };
+    use async_graphql::{Executor, Request, Value};
+    use futures_util::StreamExt;
+    use serde_json::json;
+
+    use crate::server::{graphql_filter, SchemaHandle};
+
+    // Schema whose `version` query and `ticks` subscription return its version
+    fn versioned_schema(version: i32) -> Schema {
+        let query = Object::new("Query").field(Field::new(
+            "version",
+            TypeRef::named_nn(TypeRef::INT),
+            move |_| FieldFuture::new(async move { Ok(Some(Value::from(version))) }),
+        ));
+        let subscription = Subscription::new("Subscription").field(SubscriptionField::new(
+            "ticks",
+            TypeRef::named_nn(TypeRef::INT),
+            move |_| {
+                SubscriptionFieldFuture::new(async move {
+                    Ok(futures_util::stream::once(async move {
+                        Ok::<_, async_graphql::Error>(Value::from(version))
+                    })
+                    .chain(futures_util::stream::pending()))
+                })
+            },
+        ));
+
+        Schema::build("Query", None, Some("Subscription"))
+            .register(query)
+            .register(subscription)
+            .finish()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_queries_run_against_swapped_schema() {
+        let handle = SchemaHandle::new(versioned_schema(1));
+        let response = handle.execute(Request::new("{ version }")).await;
+        assert_eq!(response.data.into_json().unwrap(), json!({ "version": 1 }));
+
+        handle.swap(versioned_schema(2));
+        let response = handle.execute(Request::new("{ version }")).await;
+        assert_eq!(response.data.into_json().unwrap(), json!({ "version": 2 }));
+
+        // Over HTTP as well
+        let filter = graphql_filter(handle.clone());
+        let response = warp::test::request()
+            .method("POST")
+            .path("/graphql")
+            .json(&json!({ "query": "{ version }" }))
+            .reply(&filter)
+            .await;
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["data"], json!({ "version": 2 }));
+    }
+
+    #[tokio::test]
+    async fn test_subscriptions_complete_on_swap() {
+        let handle = SchemaHandle::new(versioned_schema(1));
+        let mut stream = handle.execute_stream(Request::new("subscription { ticks }"), None);
+        let response = stream.next().await.unwrap();
+        assert_eq!(response.data.into_json().unwrap(), json!({ "ticks": 1 }));
+
+        handle.swap(versioned_schema(2));
+        let next = tokio::time::timeout(Duration::from_secs(1), stream.next())
+            .await
+            .unwrap();
+        assert!(next.is_none());
+
+        // New subscriptions run against the new schema
+        let mut stream = handle.execute_stream(Request::new("subscription { ticks }"), None);
+        let response = stream.next().await.unwrap();
+        assert_eq!(response.data.into_json().unwrap(), json!({ "ticks": 2 }));
+    }
+}
@@ -0,0 +1,241 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use async_graphql::dynamic::Schema;
+    use serde_json::{json, Value};
+    use sqlx::SqlitePool;
+    use starknet::core::types::Felt;
+    use starknet::providers::jsonrpc::HttpTransport;
+    use starknet::providers::JsonRpcClient;
+    use tokio::sync::broadcast;
+    use torii_messaging::{Messaging, MessagingConfig};
+    use torii_sqlite::executor::Executor;
+    use torii_sqlite::utils::felt_to_sql_string;
+    use torii_sqlite::Sql;
+    use torii_storage::proto::{ContractDefinition, ContractType};
+    use url::Url;
+
+    use crate::schema::build_schema;
+    use crate::tests::run_graphql_query;
+
+    const WORLD_ADDRESS: Felt = Felt::from_hex_unchecked("0x1234");
+    const ERC20_ADDRESS: Felt = Felt::from_hex_unchecked("0x5678");
+
+    async fn setup(pool: &SqlitePool) -> Schema {
+        let (shutdown_tx, _) = broadcast::channel(1);
+        let url: Url = "https://www.example.com".parse().unwrap();
+        let provider = Arc::new(JsonRpcClient::new(HttpTransport::new(url)));
+        let (mut executor, sender) =
+            Executor::new(pool.clone(), shutdown_tx.clone(), Arc::clone(&provider))
+                .await
+                .unwrap();
+        tokio::spawn(async move {
+            executor.run().await.unwrap();
+        });
+        let db = Sql::new(
+            pool.clone(),
+            sender,
+            &[
+                ContractDefinition {
+                    address: WORLD_ADDRESS,
+                    r#type: ContractType::WORLD,
+                    starting_block: None,
+                },
+                ContractDefinition {
+                    address: ERC20_ADDRESS,
+                    r#type: ContractType::ERC20,
+                    starting_block: None,
+                },
+            ],
+        )
+        .await
+        .unwrap();
+
+        let messaging = Arc::new(Messaging::new(
+            MessagingConfig::default(),
+            Arc::new(db.clone()),
+            provider.clone(),
+        ));
+
+        build_schema(pool, messaging, Arc::new(db.clone()))
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_storage_queries(pool: SqlitePool) {
+        let schema = setup(&pool).await;
+
+        // contracts are filtered by type
+        let result = run_graphql_query(
+            &schema,
+            r#"
+              {
+                contracts(contractTypes: ["ERC20"]) {
+                  contractAddress
+                  contractType
+                }
+              }
+            "#,
+        )
+        .await;
+        let contracts = result.get("contracts").unwrap().as_array().unwrap();
+        assert_eq!(contracts.len(), 1);
+        assert_eq!(
+            contracts[0]["contractAddress"],
+            Value::from(felt_to_sql_string(&ERC20_ADDRESS))
+        );
+        assert_eq!(contracts[0]["contractType"], Value::from("ERC20"));
+
+        // nothing was aggregated yet, so the page is empty
+        let result = run_graphql_query(
+            &schema,
+            r#"
+              {
+                aggregations(aggregatorIds: ["top_players"], first: 5) {
+                  edges {
+                    node {
+                      entityId
+                      position
+                    }
+                  }
+                  pageInfo {
+                    hasNextPage
+                    endCursor
+                  }
+                }
+              }
+            "#,
+        )
+        .await;
+        let aggregations = result.get("aggregations").unwrap();
+        assert_eq!(aggregations["edges"], Value::Array(vec![]));
+        assert_eq!(aggregations["pageInfo"]["hasNextPage"], Value::Bool(false));
+        assert_eq!(aggregations["pageInfo"]["endCursor"], Value::Null);
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_aggregations_and_activities(pool: SqlitePool) {
+        let schema = setup(&pool).await;
+
+        for (entity_id, value) in [("0x1", 10), ("0x2", 30), ("0x3", 20)] {
+            sqlx::query(
+                "INSERT INTO aggregations (id, aggregator_id, entity_id, value, display_value, \
+                 model_id) VALUES (?, 'top_players', ?, ?, ?, '0x99')",
+            )
+            .bind(format!("top_players:{entity_id}"))
+            .bind(entity_id)
+            .bind(format!("0x{value:064x}"))
+            .bind(value.to_string())
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        // entries are ranked by value, and paged
+        let query = |after: &str| {
+            format!(
+                r#"
+                  {{
+                    aggregations(aggregatorIds: ["top_players"], first: 2{after}) {{
+                      edges {{
+                        node {{
+                          entityId
+                          displayValue
+                          position
+                        }}
+                      }}
+                      pageInfo {{
+                        hasNextPage
+                        endCursor
+                      }}
+                    }}
+                  }}
+                "#
+            )
+        };
+        let result = run_graphql_query(&schema, &query("")).await;
+        let aggregations = &result["aggregations"];
+        let nodes = aggregations["edges"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|edge| edge["node"].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            nodes,
+            vec![
+                json!({ "entityId": "0x2", "displayValue": "30", "position": 1 }),
+                json!({ "entityId": "0x3", "displayValue": "20", "position": 2 }),
+            ]
+        );
+        assert_eq!(aggregations["pageInfo"]["hasNextPage"], Value::Bool(true));
+
+        let end_cursor = aggregations["pageInfo"]["endCursor"].as_str().unwrap();
+        let result =
+            run_graphql_query(&schema, &query(&format!(", after: \"{end_cursor}\""))).await;
+        let edges = result["aggregations"]["edges"].as_array().unwrap();
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0]["node"]["entityId"], Value::from("0x1"));
+        assert_eq!(edges[0]["node"]["position"], Value::from(3));
+
+        for (caller, actions) in [
+            ("0xa", r#"{"move": 2, "attack": 1}"#),
+            ("0xb", r#"{"move": 1}"#),
+        ] {
+            let caller = felt_to_sql_string(&Felt::from_hex_unchecked(caller));
+            sqlx::query(
+                "INSERT INTO activities (id, world_address, namespace, caller_address, \
+                 session_start, session_end, action_count, actions) VALUES (?, ?, 'ns', ?, \
+                 '2025-01-01 00:00:00', '2025-01-01 00:10:00', ?, ?)",
+            )
+            .bind(format!("activity:{caller}"))
+            .bind(felt_to_sql_string(&WORLD_ADDRESS))
+            .bind(&caller)
+            .bind(if actions.contains("attack") { 3 } else { 1 })
+            .bind(actions)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        // activities are filtered by caller, with their actions listed by name
+        let result = run_graphql_query(
+            &schema,
+            &format!(
+                r#"
+                  {{
+                    activities(callerAddresses: ["{}"]) {{
+                      edges {{
+                        node {{
+                          namespace
+                          actionCount
+                          actions {{
+                            name
+                            count
+                          }}
+                        }}
+                      }}
+                    }}
+                  }}
+                "#,
+                felt_to_sql_string(&Felt::from_hex_unchecked("0xa"))
+            ),
+        )
+        .await;
+        let edges = result["activities"]["edges"].as_array().unwrap();
+        assert_eq!(edges.len(), 1);
+        assert_eq!(
+            edges[0]["node"],
+            json!({
+                "namespace": "ns",
+                "actionCount": 3,
+                "actions": [
+                    { "name": "attack", "count": 1 },
+                    { "name": "move", "count": 2 },
+                ],
+            })
+        );
+    }
+}
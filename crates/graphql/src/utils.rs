@@ -64,6 +64,26 @@ impl ExtractFromIndexMap for Vec<String> {
     }
 }
 
+impl ExtractFromIndexMap for Vec<Felt> {
+    fn extract(indexmap: &ValueMapping, input: &str) -> Result<Self, ExtractError> {
+        let value = indexmap
+            .get(input)
+            .ok_or_else(|| ExtractError::NotFound(input.to_string()))?;
+        match value {
+            Value::List(list) => list
+                .iter()
+                .map(|value| match value {
+                    Value::String(s) => {
+                        Felt::from_str(s).map_err(|_| ExtractError::NotFelt(input.to_string()))
+                    }
+                    _ => Err(ExtractError::NotString(input.to_string())),
+                })
+                .collect(),
+            _ => Err(ExtractError::NotList(input.to_string())),
+        }
+    }
+}
+
 pub fn extract<T: ExtractFromIndexMap>(
     values: &ValueMapping,
     key: &str,
@@ -71,6 +91,17 @@ pub fn extract<T: ExtractFromIndexMap>(
     T::extract(values, key)
 }
 
+// Extracts an optional list argument, a missing or null argument being an empty list
+pub fn extract_list<T>(values: &ValueMapping, key: &str) -> Result<Vec<T>, ExtractError>
+where
+    Vec<T>: ExtractFromIndexMap,
+{
+    match values.get(key) {
+        None | Some(Value::Null) => Ok(Vec::new()),
+        Some(_) => Vec::<T>::extract(values, key),
+    }
+}
+
 pub fn field_name_from_names(namespace: &str, model_name: &str) -> String {
     format!(
         "{}{}",
@@ -18,23 +18,25 @@ use tonic::transport::Endpoint;
 
 use torii_proto::error::ProtoError;
 use torii_proto::proto::world::{
-    world_client, PublishMessageBatchRequest, PublishMessageRequest, RetrieveAchievementsRequest,
-    RetrieveAchievementsResponse, RetrieveActivitiesRequest, RetrieveActivitiesResponse,
-    RetrieveAggregationsRequest, RetrieveAggregationsResponse, RetrieveContractsRequest,
-    RetrieveContractsResponse, RetrieveControllersRequest, RetrieveControllersResponse,
-    RetrieveEntitiesRequest, RetrieveEntitiesResponse, RetrieveEventsRequest,
-    RetrieveEventsResponse, RetrievePlayerAchievementsRequest, RetrievePlayerAchievementsResponse,
+    world_client, AggregateEntitiesRequest, AggregateEntitiesResponse, PublishMessageBatchRequest,
+    PublishMessageRequest, RetrieveAchievementsRequest, RetrieveAchievementsResponse,
+    RetrieveActivitiesRequest, RetrieveActivitiesResponse, RetrieveAggregationsRequest,
+    RetrieveAggregationsResponse, RetrieveContractsRequest, RetrieveContractsResponse,
+    RetrieveControllersRequest, RetrieveControllersResponse, RetrieveEntitiesRequest,
+    RetrieveEntitiesResponse, RetrieveEventsRequest, RetrieveEventsResponse,
+    RetrievePlayerAchievementsRequest, RetrievePlayerAchievementsResponse,
     RetrieveTokenBalancesRequest, RetrieveTokenBalancesResponse, RetrieveTokenContractsRequest,
-    RetrieveTokenContractsResponse, RetrieveTokenTransfersRequest, RetrieveTokenTransfersResponse,
-    RetrieveTokensRequest, RetrieveTokensResponse, RetrieveTransactionsRequest,
-    RetrieveTransactionsResponse, SearchRequest, SubscribeAchievementProgressionsRequest,
-    SubscribeAchievementProgressionsResponse, SubscribeActivitiesRequest,
-    SubscribeActivitiesResponse, SubscribeAggregationsRequest, SubscribeAggregationsResponse,
-    SubscribeContractsRequest, SubscribeContractsResponse, SubscribeEntitiesRequest,
-    SubscribeEntityResponse, SubscribeEventsRequest, SubscribeEventsResponse,
-    SubscribeTokenBalancesRequest, SubscribeTokenBalancesResponse, SubscribeTokenTransfersRequest,
-    SubscribeTokenTransfersResponse, SubscribeTokensRequest, SubscribeTokensResponse,
-    SubscribeTransactionsRequest, SubscribeTransactionsResponse,
+    RetrieveTokenContractsResponse, RetrieveTokenHolderStatsRequest,
+    RetrieveTokenHolderStatsResponse, RetrieveTokenTransfersRequest,
+    RetrieveTokenTransfersResponse, RetrieveTokensRequest, RetrieveTokensResponse,
+    RetrieveTransactionsRequest, RetrieveTransactionsResponse, SearchRequest,
+    SubscribeAchievementProgressionsRequest, SubscribeAchievementProgressionsResponse,
+    SubscribeActivitiesRequest, SubscribeActivitiesResponse, SubscribeAggregationsRequest,
+    SubscribeAggregationsResponse, SubscribeContractsRequest, SubscribeContractsResponse,
+    SubscribeEntitiesRequest, SubscribeEntityResponse, SubscribeEventsRequest,
+    SubscribeEventsResponse, SubscribeTokenBalancesRequest, SubscribeTokenBalancesResponse,
+    SubscribeTokenTransfersRequest, SubscribeTokenTransfersResponse, SubscribeTokensRequest,
+    SubscribeTokensResponse, SubscribeTransactionsRequest, SubscribeTransactionsResponse,
     UpdateAchievementProgressionsSubscriptionRequest, UpdateActivitiesSubscriptionRequest,
     UpdateAggregationsSubscriptionRequest, UpdateEntitiesSubscriptionRequest,
     UpdateTokenBalancesSubscriptionRequest, UpdateTokenSubscriptionRequest,
@@ -43,9 +45,10 @@ use torii_proto::proto::world::{
 use torii_proto::schema::Entity;
 use torii_proto::{
     AchievementQuery, ActivityQuery, AggregationQuery, Clause, Contract, ContractQuery,
-    ControllerQuery, Event, EventQuery, KeysClause, Message, PlayerAchievementQuery, Query,
-    SearchQuery, SqlRow, Token, TokenBalance, TokenBalanceQuery, TokenContractQuery, TokenQuery,
-    TokenTransfer, TokenTransferQuery, Transaction, TransactionFilter, TransactionQuery,
+    ControllerQuery, EntityAggregateQuery, Event, EventQuery, KeysClause, Message,
+    PlayerAchievementQuery, Query, SearchQuery, SqlRow, Token, TokenBalance, TokenBalanceQuery,
+    TokenContractQuery, TokenHolderStatsQuery, TokenQuery, TokenTransfer, TokenTransferQuery,
+    Transaction, TransactionFilter, TransactionQuery,
 };
 
 pub use torii_proto as types;
@@ -506,6 +509,19 @@ impl WorldClient {
             .map(|res| res.into_inner())
     }
 
+    pub async fn retrieve_token_holder_stats(
+        &mut self,
+        query: TokenHolderStatsQuery,
+    ) -> Result<RetrieveTokenHolderStatsResponse, Error> {
+        self.inner
+            .retrieve_token_holder_stats(RetrieveTokenHolderStatsRequest {
+                query: Some(query.into()),
+            })
+            .await
+            .map_err(Error::Grpc)
+            .map(|res| res.into_inner())
+    }
+
     pub async fn retrieve_token_contracts(
         &mut self,
         query: TokenContractQuery,
@@ -611,6 +627,19 @@ impl WorldClient {
             .map(|res| res.into_inner())
     }
 
+    pub async fn aggregate_entities(
+        &mut self,
+        query: EntityAggregateQuery,
+    ) -> Result<AggregateEntitiesResponse, Error> {
+        self.inner
+            .aggregate_entities(AggregateEntitiesRequest {
+                query: Some(query.into()),
+            })
+            .await
+            .map_err(Error::Grpc)
+            .map(|res| res.into_inner())
+    }
+
     pub async fn retrieve_event_messages(
         &mut self,
         query: Query,
@@ -910,6 +939,19 @@ impl WorldClient {
             })
     }
 
+    /// Execute a SQL query and stream its rows, in chunks, as the server reads them.
+    pub async fn execute_sql_stream(&mut self, query: String) -> Result<SqlRowStreaming, Error> {
+        let stream = self
+            .inner
+            .execute_sql_stream(torii_proto::proto::types::SqlQueryRequest { query })
+            .await
+            .map_err(Error::Grpc)
+            .map(|res| res.into_inner())?;
+        Ok(SqlRowStreaming(stream.map_ok(Box::new(|res| {
+            res.rows.into_iter().map(|r| r.into()).collect()
+        }))))
+    }
+
     /// Perform a full-text search across indexed entities.
     /// Returns search results grouped by table with relevance scores.
     pub async fn search(
@@ -1124,3 +1166,21 @@ impl Stream for AchievementProgressionUpdateStreaming {
         self.0.poll_next_unpin(cx)
     }
 }
+
+type SqlRowMappedStream = MapOk<
+    tonic::Streaming<torii_proto::proto::types::SqlQueryResponse>,
+    Box<dyn Fn(torii_proto::proto::types::SqlQueryResponse) -> Vec<SqlRow> + Send>,
+>;
+
+#[derive(Debug)]
+pub struct SqlRowStreaming(SqlRowMappedStream);
+
+impl Stream for SqlRowStreaming {
+    type Item = <SqlRowMappedStream as Stream>::Item;
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        self.0.poll_next_unpin(cx)
+    }
+}
@@ -0,0 +1,160 @@
+use std::sync::Arc;
+
+use starknet::core::types::Felt;
+use tonic::{Request, Response, Status};
+use torii_admin::{Admin, AdminError, EngineCommand};
+use torii_proto::proto::world::admin_server::Admin as AdminRpc;
+use torii_proto::proto::world::{
+    AddContractRequest, KillSubscriptionRequest, ListSubscriptionsResponse, RefreshMetadataRequest,
+    ReindexRangeRequest, RemoveContractRequest, SetLogFilterRequest, SubscriptionInfo,
+};
+use torii_proto::{ContractDefinition, ContractType};
+
+/// The gRPC admin API, operating the indexer and the subscriptions at runtime.
+#[derive(Debug, Clone)]
+pub struct AdminService {
+    admin: Arc<Admin>,
+}
+
+impl AdminService {
+    pub fn new(admin: Arc<Admin>) -> Self {
+        Self { admin }
+    }
+
+    async fn execute(&self, command: EngineCommand) -> Result<Response<()>, Status> {
+        self.admin.execute(command).await.map_err(admin_status)?;
+        Ok(Response::new(()))
+    }
+}
+
+fn admin_status(error: AdminError) -> Status {
+    match error {
+        AdminError::EngineStopped => Status::unavailable(error.to_string()),
+        AdminError::Busy => Status::resource_exhausted(error.to_string()),
+        AdminError::NoLogFilter => Status::unimplemented(error.to_string()),
+        AdminError::UnknownSubscription(..) => Status::not_found(error.to_string()),
+        AdminError::Command(_) | AdminError::InvalidLogFilter(_) => {
+            Status::failed_precondition(error.to_string())
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl AdminRpc for AdminService {
+    async fn add_contract(
+        &self,
+        request: Request<AddContractRequest>,
+    ) -> Result<Response<()>, Status> {
+        let AddContractRequest {
+            contract_address,
+            contract_type,
+            starting_block,
+        } = request.into_inner();
+        let r#type = ContractType::try_from(contract_type)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        self.execute(EngineCommand::AddContract(ContractDefinition {
+            address: Felt::from_bytes_be_slice(&contract_address),
+            r#type,
+            starting_block,
+        }))
+        .await
+    }
+
+    async fn remove_contract(
+        &self,
+        request: Request<RemoveContractRequest>,
+    ) -> Result<Response<()>, Status> {
+        let RemoveContractRequest { contract_address } = request.into_inner();
+        self.execute(EngineCommand::RemoveContract(Felt::from_bytes_be_slice(
+            &contract_address,
+        )))
+        .await
+    }
+
+    async fn refresh_metadata(
+        &self,
+        request: Request<RefreshMetadataRequest>,
+    ) -> Result<Response<()>, Status> {
+        let RefreshMetadataRequest { contract_address } = request.into_inner();
+        self.execute(EngineCommand::RefreshMetadata(Felt::from_bytes_be_slice(
+            &contract_address,
+        )))
+        .await
+    }
+
+    async fn rebuild_search_index(&self, _request: Request<()>) -> Result<Response<()>, Status> {
+        self.execute(EngineCommand::RebuildSearchIndex).await
+    }
+
+    async fn reindex_range(
+        &self,
+        request: Request<ReindexRangeRequest>,
+    ) -> Result<Response<()>, Status> {
+        let ReindexRangeRequest {
+            from_block,
+            to_block,
+            contract_addresses,
+        } = request.into_inner();
+
+        self.execute(EngineCommand::ReindexRange {
+            from_block,
+            to_block,
+            contracts: contract_addresses
+                .iter()
+                .map(|address| Felt::from_bytes_be_slice(address))
+                .collect(),
+        })
+        .await
+    }
+
+    async fn pause_engine(&self, _request: Request<()>) -> Result<Response<()>, Status> {
+        self.execute(EngineCommand::Pause).await
+    }
+
+    async fn resume_engine(&self, _request: Request<()>) -> Result<Response<()>, Status> {
+        self.execute(EngineCommand::Resume).await
+    }
+
+    async fn set_log_filter(
+        &self,
+        request: Request<SetLogFilterRequest>,
+    ) -> Result<Response<()>, Status> {
+        let SetLogFilterRequest { directives } = request.into_inner();
+        self.admin
+            .set_log_filter(&directives)
+            .map_err(admin_status)?;
+        Ok(Response::new(()))
+    }
+
+    async fn list_subscriptions(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<ListSubscriptionsResponse>, Status> {
+        let subscriptions = self
+            .admin
+            .subscriptions()
+            .into_iter()
+            .map(|subscription| SubscriptionInfo {
+                id: subscription.id,
+                kind: subscription.kind,
+            })
+            .collect();
+
+        Ok(Response::new(ListSubscriptionsResponse { subscriptions }))
+    }
+
+    async fn kill_subscription(
+        &self,
+        request: Request<KillSubscriptionRequest>,
+    ) -> Result<Response<()>, Status> {
+        let KillSubscriptionRequest {
+            subscription_id,
+            kind,
+        } = request.into_inner();
+        self.admin
+            .kill_subscription(&kind, subscription_id)
+            .map_err(admin_status)?;
+        Ok(Response::new(()))
+    }
+}
@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use http::header::AUTHORIZATION;
+use tonic::Status;
+use torii_auth::{credential, AuthError, Authenticator, Capability, API_KEY_HEADER};
+use tower::filter::Predicate;
+use tower::BoxError;
+
+/// Authorizes the requests of every service of the server by their method, the principal is
+/// added to the request extensions.
+///
+/// A tonic interceptor only sees the metadata of a request, not which method is called, so
+/// this filters the http requests instead.
+#[derive(Debug, Clone)]
+pub struct AuthPredicate {
+    authenticator: Arc<Authenticator>,
+}
+
+impl AuthPredicate {
+    pub fn new(authenticator: Arc<Authenticator>) -> Self {
+        Self { authenticator }
+    }
+}
+
+impl<B> Predicate<http::Request<B>> for AuthPredicate {
+    type Request = http::Request<B>;
+
+    fn check(&mut self, mut request: http::Request<B>) -> Result<Self::Request, BoxError> {
+        let principal = {
+            let header = |name| {
+                request
+                    .headers()
+                    .get(name)
+                    .and_then(|value| value.to_str().ok())
+            };
+            let credential = credential(header(AUTHORIZATION.as_str()), header(API_KEY_HEADER));
+            self.authenticator
+                .authorize(credential, method_capability(request.uri().path()))
+                .map_err(|e| Box::new(auth_status(e)) as BoxError)?
+        };
+
+        request.extensions_mut().insert(principal);
+        Ok(request)
+    }
+}
+
+/// The capability required by a method, from its `/<package>.<service>/<method>` path.
+pub fn method_capability(path: &str) -> Capability {
+    if path.starts_with("/world.Admin/") {
+        return Capability::Admin;
+    }
+
+    let method = path.rsplit('/').next().unwrap_or_default();
+    if method.starts_with("Subscribe")
+        || (method.starts_with("Update") && method.ends_with("Subscription"))
+    {
+        Capability::Subscribe
+    } else if method.starts_with("PublishMessage") {
+        Capability::PublishMessage
+    } else if method.starts_with("ExecuteSql") {
+        Capability::Sql
+    } else {
+        Capability::Read
+    }
+}
+
+fn auth_status(error: AuthError) -> Status {
+    match error {
+        AuthError::Unauthenticated => Status::unauthenticated(error.to_string()),
+        AuthError::Forbidden(_) => Status::permission_denied(error.to_string()),
+        AuthError::RateLimited { .. } => Status::resource_exhausted(error.to_string()),
+    }
+}
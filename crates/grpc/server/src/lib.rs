@@ -1,3 +1,5 @@
+pub mod admin;
+pub mod auth;
 pub mod subscriptions;
 
 #[cfg(test)]
@@ -5,7 +7,7 @@ mod tests;
 
 use std::collections::HashMap;
 use std::future::Future;
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::pin::Pin;
 use std::str;
 use std::sync::Arc;
@@ -29,6 +31,7 @@ use subscriptions::event::EventManager;
 use subscriptions::token::TokenManager;
 use subscriptions::token_balance::TokenBalanceManager;
 use subscriptions::token_transfer::TokenTransferManager;
+use subscriptions::SubscriptionRegistry;
 use tokio::net::TcpListener;
 use tokio::sync::mpsc::UnboundedSender;
 use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
@@ -36,25 +39,34 @@ use tonic::codec::CompressionEncoding;
 use tonic::transport::Server;
 use tonic::{Request, Response, Status};
 use tonic_web::GrpcWebLayer;
+use torii_admin::Admin;
+use torii_auth::Authenticator;
 use torii_messaging::Messaging;
 use torii_proto::error::ProtoError;
+use torii_sqlite::sandbox::{SandboxConfig, SandboxError, SqlSandbox};
 use torii_storage::ReadOnlyStorage;
+use tower::filter::FilterLayer;
 use tower_http::cors::{AllowOrigin, CorsLayer};
 
+use crate::admin::AdminService;
+use crate::auth::AuthPredicate;
 use crate::subscriptions::transaction::TransactionManager;
 
 use self::subscriptions::entity::EntityManager;
 use self::subscriptions::event_message::EventMessageManager;
 use sqlx::SqlitePool;
+use torii_proto::proto::world::admin_server::AdminServer;
 use torii_proto::proto::world::world_server::WorldServer;
 use torii_proto::proto::world::{
-    PublishMessageBatchRequest, PublishMessageBatchResponse, PublishMessageRequest,
-    PublishMessageResponse, RetrieveAchievementsRequest, RetrieveAchievementsResponse,
-    RetrieveActivitiesRequest, RetrieveActivitiesResponse, RetrieveAggregationsRequest,
-    RetrieveAggregationsResponse, RetrieveContractsRequest, RetrieveContractsResponse,
-    RetrieveControllersRequest, RetrieveControllersResponse, RetrievePlayerAchievementsRequest,
+    AggregateEntitiesRequest, AggregateEntitiesResponse, PublishMessageBatchRequest,
+    PublishMessageBatchResponse, PublishMessageRequest, PublishMessageResponse,
+    RetrieveAchievementsRequest, RetrieveAchievementsResponse, RetrieveActivitiesRequest,
+    RetrieveActivitiesResponse, RetrieveAggregationsRequest, RetrieveAggregationsResponse,
+    RetrieveContractsRequest, RetrieveContractsResponse, RetrieveControllersRequest,
+    RetrieveControllersResponse, RetrievePlayerAchievementsRequest,
     RetrievePlayerAchievementsResponse, RetrieveTokenBalancesRequest,
     RetrieveTokenBalancesResponse, RetrieveTokenContractsRequest, RetrieveTokenContractsResponse,
+    RetrieveTokenHolderStatsRequest, RetrieveTokenHolderStatsResponse,
     RetrieveTokenTransfersRequest, RetrieveTokenTransfersResponse, RetrieveTokensRequest,
     RetrieveTokensResponse, RetrieveTransactionsRequest, RetrieveTransactionsResponse,
     SearchRequest, SearchResponse, SubscribeAchievementProgressionsRequest,
@@ -94,10 +106,33 @@ pub struct DojoWorld<P: Provider + Sync> {
     aggregation_manager: Arc<AggregationManager>,
     activity_manager: Arc<ActivityManager>,
     achievement_progression_manager: Arc<AchievementProgressionManager>,
-    pool: SqlitePool,
+    sql_sandbox: SqlSandbox,
+    trusted_proxies: Vec<IpAddr>,
     _config: GrpcConfig,
 }
 
+impl<P: Provider + Sync> DojoWorld<P> {
+    /// The open subscriptions of every kind, for the admin API.
+    pub fn subscriptions(&self) -> SubscriptionRegistry {
+        SubscriptionRegistry::new(vec![
+            ("entities", self.entity_manager.clone()),
+            ("event_messages", self.event_message_manager.clone()),
+            ("events", self.event_manager.clone()),
+            ("contracts", self.contract_manager.clone()),
+            ("token_balances", self.token_balance_manager.clone()),
+            ("tokens", self.token_manager.clone()),
+            ("token_transfers", self.token_transfer_manager.clone()),
+            ("transactions", self.transaction_manager.clone()),
+            ("aggregations", self.aggregation_manager.clone()),
+            ("activities", self.activity_manager.clone()),
+            (
+                "achievement_progressions",
+                self.achievement_progression_manager.clone(),
+            ),
+        ])
+    }
+}
+
 impl<P: Provider + Sync> DojoWorld<P> {
     pub fn new(
         storage: Arc<dyn ReadOnlyStorage>,
@@ -180,7 +215,8 @@ impl<P: Provider + Sync> DojoWorld<P> {
             aggregation_manager,
             activity_manager,
             achievement_progression_manager,
-            pool,
+            sql_sandbox: SqlSandbox::new(pool, config.sql_sandbox.clone()),
+            trusted_proxies: config.trusted_proxies.clone(),
             _config: config,
         }
     }
@@ -234,6 +270,8 @@ impl<P: Provider + Sync> DojoWorld<P> {
 }
 
 type ServiceResult<T> = Result<Response<T>, Status>;
+type ExecuteSqlStreamResponseStream =
+    Pin<Box<dyn Stream<Item = Result<proto::types::SqlQueryResponse, Status>> + Send>>;
 type SubscribeEntitiesResponseStream =
     Pin<Box<dyn Stream<Item = Result<SubscribeEntityResponse, Status>> + Send>>;
 type SubscribeEventsResponseStream =
@@ -268,6 +306,7 @@ impl<P: Provider + Sync + Send + 'static> proto::world::world_server::World for
     type SubscribeAggregationsStream = SubscribeAggregationsResponseStream;
     type SubscribeActivitiesStream = SubscribeActivitiesResponseStream;
     type SubscribeAchievementProgressionsStream = SubscribeAchievementProgressionsResponseStream;
+    type ExecuteSqlStreamStream = ExecuteSqlStreamResponseStream;
 
     async fn worlds(
         &self,
@@ -348,6 +387,38 @@ impl<P: Provider + Sync + Send + 'static> proto::world::world_server::World for
         }))
     }
 
+    async fn aggregate_entities(
+        &self,
+        request: Request<AggregateEntitiesRequest>,
+    ) -> Result<Response<AggregateEntitiesResponse>, Status> {
+        let AggregateEntitiesRequest { query } = request.into_inner();
+        let query: torii_proto::EntityAggregateQuery = query
+            .ok_or_else(|| Status::invalid_argument("Missing query argument"))?
+            .try_into()
+            .map_err(|e: ProtoError| Status::invalid_argument(e.to_string()))?;
+
+        if query.model.is_empty() {
+            return Err(Status::invalid_argument("A model is required"));
+        }
+        if query.member.is_none()
+            && !matches!(query.function, torii_proto::AggregateFunction::Count)
+        {
+            return Err(Status::invalid_argument(
+                "A member is required to aggregate",
+            ));
+        }
+
+        let aggregates = self
+            .storage
+            .aggregate_entities(&query)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(AggregateEntitiesResponse {
+            aggregates: aggregates.into_iter().map(Into::into).collect(),
+        }))
+    }
+
     async fn retrieve_event_messages(
         &self,
         request: Request<RetrieveEntitiesRequest>,
@@ -921,6 +992,33 @@ impl<P: Provider + Sync + Send + 'static> proto::world::world_server::World for
         }))
     }
 
+    async fn retrieve_token_holder_stats(
+        &self,
+        request: Request<RetrieveTokenHolderStatsRequest>,
+    ) -> Result<Response<RetrieveTokenHolderStatsResponse>, Status> {
+        let RetrieveTokenHolderStatsRequest { query } = request.into_inner();
+        let query: torii_proto::TokenHolderStatsQuery = query
+            .ok_or_else(|| Status::invalid_argument("Missing query argument"))?
+            .try_into()
+            .map_err(|e: ProtoError| Status::invalid_argument(e.to_string()))?;
+
+        if query.contract_addresses.is_empty() {
+            return Err(Status::invalid_argument(
+                "At least one contract address is required",
+            ));
+        }
+
+        let stats = self
+            .storage
+            .token_holder_stats(&query)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(RetrieveTokenHolderStatsResponse {
+            stats: stats.into_iter().map(Into::into).collect(),
+        }))
+    }
+
     async fn subscribe_contracts(
         &self,
         request: Request<SubscribeContractsRequest>,
@@ -1194,13 +1292,14 @@ impl<P: Provider + Sync + Send + 'static> proto::world::world_server::World for
         &self,
         request: Request<proto::types::SqlQueryRequest>,
     ) -> Result<Response<proto::types::SqlQueryResponse>, Status> {
+        let client = sql_client(&request, &self.trusted_proxies);
         let proto::types::SqlQueryRequest { query } = request.into_inner();
 
-        // Execute the query
-        let rows = sqlx::query(&query)
-            .fetch_all(&self.pool)
+        let rows = self
+            .sql_sandbox
+            .execute(&client, &query)
             .await
-            .map_err(|e| Status::invalid_argument(format!("Query error: {:?}", e)))?;
+            .map_err(sandbox_status)?;
 
         // Map rows to proto types
         let proto_rows: Vec<proto::types::SqlRow> = rows
@@ -1212,6 +1311,89 @@ impl<P: Provider + Sync + Send + 'static> proto::world::world_server::World for
             rows: proto_rows,
         }))
     }
+
+    async fn execute_sql_stream(
+        &self,
+        request: Request<proto::types::SqlQueryRequest>,
+    ) -> ServiceResult<Self::ExecuteSqlStreamStream> {
+        let client = sql_client(&request, &self.trusted_proxies);
+        let proto::types::SqlQueryRequest { query } = request.into_inner();
+
+        let mut rows = self
+            .sql_sandbox
+            .execute_stream(&client, &query)
+            .map_err(sandbox_status)?;
+
+        let (tx, rx) = tokio::sync::mpsc::channel(SQL_STREAM_BUFFER_SIZE);
+        tokio::spawn(async move {
+            while let Some(row) = rows.recv().await {
+                // Send the rows already read along, in a single message
+                let mut chunk = vec![row];
+                while chunk.len() < SQL_STREAM_CHUNK_SIZE {
+                    match rows.try_recv() {
+                        Ok(row) => chunk.push(row),
+                        Err(_) => break,
+                    }
+                }
+
+                let response = chunk
+                    .into_iter()
+                    .map(|row| row.map(|row| torii_sqlite::utils::map_row_to_proto(&row)))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(|rows| proto::types::SqlQueryResponse { rows })
+                    .map_err(sandbox_status);
+                let failed = response.is_err();
+                // The client is gone, dropping the rows stops the query
+                if tx.send(response).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        Ok(Response::new(
+            Box::pin(ReceiverStream::new(rx)) as Self::ExecuteSqlStreamStream
+        ))
+    }
+}
+
+/// Maximum number of rows sent in a single message of a SQL stream.
+const SQL_STREAM_CHUNK_SIZE: usize = 1000;
+/// Number of messages of a SQL stream buffered ahead of the client.
+const SQL_STREAM_BUFFER_SIZE: usize = 4;
+
+/// Identifies the client of a raw SQL query by the address of its peer. When the peer is a
+/// trusted proxy, the client is the last address of the forwarded chain that is not a trusted
+/// proxy itself, as the addresses before it can be set by anyone.
+fn sql_client<T>(request: &Request<T>, trusted_proxies: &[IpAddr]) -> String {
+    let Some(peer) = request.remote_addr().map(|addr| addr.ip()) else {
+        return String::new();
+    };
+    if !trusted_proxies.contains(&peer) {
+        return peer.to_string();
+    }
+
+    request
+        .metadata()
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| {
+            value
+                .rsplit(',')
+                .filter_map(|hop| hop.trim().parse::<IpAddr>().ok())
+                .find(|hop| !trusted_proxies.contains(hop))
+        })
+        .unwrap_or(peer)
+        .to_string()
+}
+
+fn sandbox_status(e: SandboxError) -> Status {
+    match e {
+        SandboxError::TooManyQueries(_)
+        | SandboxError::RowLimit(_)
+        | SandboxError::ByteLimit(_) => Status::resource_exhausted(e.to_string()),
+        SandboxError::Timeout(_) => Status::deadline_exceeded(e.to_string()),
+        _ => Status::invalid_argument(e.to_string()),
+    }
 }
 
 const DEFAULT_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
@@ -1221,8 +1403,10 @@ const DEFAULT_EXPOSED_HEADERS: [&str; 4] = [
     "grpc-status-details-bin",
     "grpc-encoding",
 ];
-const DEFAULT_ALLOW_HEADERS: [&str; 6] = [
+const DEFAULT_ALLOW_HEADERS: [&str; 8] = [
     "x-grpc-web",
+    "authorization",
+    "x-api-key",
     "content-type",
     "x-user-agent",
     "grpc-timeout",
@@ -1238,6 +1422,9 @@ pub struct GrpcConfig {
     pub http2_keepalive_interval: Duration,
     pub http2_keepalive_timeout: Duration,
     pub max_message_size: usize,
+    pub sql_sandbox: SandboxConfig,
+    /// Proxies whose `x-forwarded-for` header identifies the client of a raw SQL query.
+    pub trusted_proxies: Vec<IpAddr>,
 }
 
 impl Default for GrpcConfig {
@@ -1249,6 +1436,8 @@ impl Default for GrpcConfig {
             http2_keepalive_interval: Duration::from_secs(30),
             http2_keepalive_timeout: Duration::from_secs(10),
             max_message_size: 16 * 1024 * 1024,
+            sql_sandbox: SandboxConfig::default(),
+            trusted_proxies: vec![],
         }
     }
 }
@@ -1261,6 +1450,8 @@ pub async fn new<P: Provider + Sync + Send + 'static>(
     cross_messaging_tx: UnboundedSender<Message>,
     pool: SqlitePool,
     config: GrpcConfig,
+    authenticator: Arc<Authenticator>,
+    admin: Arc<Admin>,
     bind_addr: Option<SocketAddr>,
 ) -> Result<
     (
@@ -1285,6 +1476,7 @@ pub async fn new<P: Provider + Sync + Send + 'static>(
     let max_message_size = config.max_message_size;
 
     let world = DojoWorld::new(storage, messaging, Some(cross_messaging_tx), pool, config);
+    admin.register_subscriptions(Arc::new(world.subscriptions()));
     let server = WorldServer::new(world)
         .accept_compressed(CompressionEncoding::Gzip)
         .send_compressed(CompressionEncoding::Gzip)
@@ -1323,8 +1515,10 @@ pub async fn new<P: Provider + Sync + Send + 'static>(
                 ),
         )
         .layer(GrpcWebLayer::new())
+        .layer(FilterLayer::new(AuthPredicate::new(authenticator)))
         .add_service(reflection)
         .add_service(server)
+        .add_service(AdminServer::new(AdminService::new(admin)))
         .serve_with_incoming_shutdown(TcpListenerStream::new(listener), async move {
             shutdown_rx.recv().await.map_or((), |_| ())
         });
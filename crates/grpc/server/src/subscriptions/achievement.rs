@@ -16,6 +16,7 @@ use torii_broker::MemoryBroker;
 use torii_proto::AchievementProgression;
 use tracing::{error, trace};
 
+use super::SubscriptionManager;
 use crate::GrpcConfig;
 use torii_proto::proto::world::SubscribeAchievementProgressionsResponse;
 
@@ -117,6 +118,16 @@ impl AchievementProgressionManager {
     }
 }
 
+impl SubscriptionManager for AchievementProgressionManager {
+    fn subscription_ids(&self) -> Vec<u64> {
+        self.subscribers.iter().map(|sub| *sub.key()).collect()
+    }
+
+    fn close_subscriber(&self, id: u64) -> bool {
+        self.subscribers.remove(&id).is_some()
+    }
+}
+
 #[must_use = "Service does nothing unless polled"]
 #[allow(missing_debug_implementations)]
 pub struct Service {
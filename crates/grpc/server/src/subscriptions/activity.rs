@@ -16,6 +16,7 @@ use torii_broker::MemoryBroker;
 use torii_proto::Activity;
 use tracing::{error, trace};
 
+use super::SubscriptionManager;
 use crate::GrpcConfig;
 use torii_proto::proto::world::SubscribeActivitiesResponse;
 
@@ -106,6 +107,16 @@ impl ActivityManager {
     }
 }
 
+impl SubscriptionManager for ActivityManager {
+    fn subscription_ids(&self) -> Vec<u64> {
+        self.subscribers.iter().map(|sub| *sub.key()).collect()
+    }
+
+    fn close_subscriber(&self, id: u64) -> bool {
+        self.subscribers.remove(&id).is_some()
+    }
+}
+
 #[must_use = "Service does nothing unless polled"]
 #[allow(missing_debug_implementations)]
 pub struct Service {
@@ -16,6 +16,7 @@ use tracing::{error, trace};
 
 use crate::GrpcConfig;
 
+use super::SubscriptionManager;
 use torii_proto::proto::world::SubscribeAggregationsResponse;
 
 pub(crate) const LOG_TARGET: &str = "torii::grpc::server::subscriptions::aggregation";
@@ -102,6 +103,16 @@ impl AggregationManager {
     }
 }
 
+impl SubscriptionManager for AggregationManager {
+    fn subscription_ids(&self) -> Vec<u64> {
+        self.subscribers.iter().map(|sub| *sub.key()).collect()
+    }
+
+    fn close_subscriber(&self, id: u64) -> bool {
+        self.subscribers.remove(&id).is_some()
+    }
+}
+
 #[must_use = "Service does nothing unless polled"]
 #[allow(missing_debug_implementations)]
 pub struct Service {
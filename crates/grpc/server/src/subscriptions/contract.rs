@@ -18,6 +18,7 @@ use tracing::{error, trace};
 
 use torii_proto::proto::world::SubscribeContractsResponse;
 
+use super::SubscriptionManager;
 use crate::GrpcConfig;
 
 pub(crate) const LOG_TARGET: &str = "torii::grpc::server::subscriptions::contracts";
@@ -71,6 +72,19 @@ impl ContractManager {
     }
 }
 
+impl SubscriptionManager for ContractManager {
+    fn subscription_ids(&self) -> Vec<u64> {
+        self.subscribers
+            .iter()
+            .map(|sub| *sub.key() as u64)
+            .collect()
+    }
+
+    fn close_subscriber(&self, id: u64) -> bool {
+        self.subscribers.remove(&(id as usize)).is_some()
+    }
+}
+
 #[must_use = "Service does nothing unless polled"]
 #[allow(missing_debug_implementations)]
 pub struct Service {
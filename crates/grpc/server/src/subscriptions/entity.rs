@@ -12,13 +12,15 @@ use starknet_crypto::Felt;
 use tokio::sync::mpsc::{
     channel, unbounded_channel, Receiver, Sender, UnboundedReceiver, UnboundedSender,
 };
-use torii_broker::{types::EntityUpdate, MemoryBroker};
+use torii_broker::types::{EntityUpdate, Update};
+use torii_broker::MemoryBroker;
 use torii_proto::schema::EntityWithMetadata;
-use tracing::{error, trace};
+use tracing::{error, field, info_span, trace, Instrument, Span};
 
 use crate::GrpcConfig;
 
 use super::match_entity;
+use super::SubscriptionManager;
 use torii_proto::proto::world::SubscribeEntityResponse;
 use torii_proto::Clause;
 
@@ -94,22 +96,30 @@ impl EntityManager {
     }
 }
 
+impl SubscriptionManager for EntityManager {
+    fn subscription_ids(&self) -> Vec<u64> {
+        self.subscribers.iter().map(|sub| *sub.key()).collect()
+    }
+
+    fn close_subscriber(&self, id: u64) -> bool {
+        self.subscribers.remove(&id).is_some()
+    }
+}
+
 #[must_use = "Service does nothing unless polled"]
 #[allow(missing_debug_implementations)]
 pub struct Service {
-    simple_broker: Pin<Box<dyn Stream<Item = EntityWithMetadata> + Send>>,
-    entity_sender: UnboundedSender<EntityWithMetadata>,
+    simple_broker: Pin<Box<dyn Stream<Item = Update<EntityWithMetadata>> + Send>>,
+    entity_sender: UnboundedSender<Update<EntityWithMetadata>>,
 }
 
 impl Service {
     pub fn new(subs_manager: Arc<EntityManager>) -> Self {
         let (entity_sender, entity_receiver) = unbounded_channel();
         let service = Self {
-            simple_broker: if subs_manager.config.optimistic {
-                Box::pin(MemoryBroker::<EntityUpdate>::subscribe_optimistic())
-            } else {
-                Box::pin(MemoryBroker::<EntityUpdate>::subscribe())
-            },
+            simple_broker: Box::pin(MemoryBroker::<EntityUpdate>::subscribe_traced(
+                subs_manager.config.optimistic,
+            )),
             entity_sender,
         };
 
@@ -120,15 +130,19 @@ impl Service {
 
     async fn publish_updates(
         subs: Arc<EntityManager>,
-        mut entity_receiver: UnboundedReceiver<EntityWithMetadata>,
+        mut entity_receiver: UnboundedReceiver<Update<EntityWithMetadata>>,
     ) {
         while let Some(update) = entity_receiver.recv().await {
-            Self::process_entity_update(&subs, &update).await;
+            let span = info_span!(target: LOG_TARGET, parent: &update.span, "grpc_send", sent = field::Empty);
+            Self::process_entity_update(&subs, &update.inner)
+                .instrument(span)
+                .await;
         }
     }
 
     async fn process_entity_update(subs: &Arc<EntityManager>, entity: &EntityWithMetadata) {
         let mut closed_stream = Vec::new();
+        let mut sent = 0;
 
         for sub in subs.subscribers.iter() {
             let idx = sub.key();
@@ -165,7 +179,7 @@ impl Service {
             // Use try_send to avoid blocking on slow subscribers
             match sub.sender.try_send(Ok(resp)) {
                 Ok(_) => {
-                    // Message sent successfully
+                    sent += 1;
                 }
                 Err(tokio::sync::mpsc::error::TrySendError::Full(_)) => {
                     // Channel is full, subscriber is too slow - disconnect them
@@ -179,6 +193,8 @@ impl Service {
             }
         }
 
+        Span::current().record("sent", sent);
+
         for id in closed_stream {
             trace!(target = LOG_TARGET, id = %id, "Closing entity stream.");
             subs.remove_subscriber(id).await
@@ -19,6 +19,7 @@ use tracing::{error, trace};
 use crate::GrpcConfig;
 
 use super::match_keys;
+use super::SubscriptionManager;
 use torii_proto::proto::types::Event as ProtoEvent;
 use torii_proto::proto::world::SubscribeEventsResponse;
 
@@ -71,6 +72,19 @@ impl EventManager {
     }
 }
 
+impl SubscriptionManager for EventManager {
+    fn subscription_ids(&self) -> Vec<u64> {
+        self.subscribers
+            .iter()
+            .map(|sub| *sub.key() as u64)
+            .collect()
+    }
+
+    fn close_subscriber(&self, id: u64) -> bool {
+        self.subscribers.remove(&(id as usize)).is_some()
+    }
+}
+
 #[must_use = "Service does nothing unless polled"]
 #[allow(missing_debug_implementations)]
 pub struct Service {
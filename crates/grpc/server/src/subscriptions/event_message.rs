@@ -12,17 +12,18 @@ use starknet_crypto::Felt;
 use tokio::sync::mpsc::{
     channel, unbounded_channel, Receiver, Sender, UnboundedReceiver, UnboundedSender,
 };
-use torii_broker::types::EventMessageUpdate;
+use torii_broker::types::{EventMessageUpdate, Update};
 use torii_broker::MemoryBroker;
 use torii_proto::schema::EntityWithMetadata;
 use torii_proto::Clause;
-use tracing::{error, trace};
+use tracing::{error, field, info_span, trace, Instrument, Span};
 
 use torii_proto::proto::world::SubscribeEntityResponse;
 
 use crate::GrpcConfig;
 
 use super::match_entity;
+use super::SubscriptionManager;
 
 pub(crate) const LOG_TARGET: &str = "torii::grpc::server::subscriptions::event_message";
 
@@ -97,22 +98,30 @@ impl EventMessageManager {
     }
 }
 
+impl SubscriptionManager for EventMessageManager {
+    fn subscription_ids(&self) -> Vec<u64> {
+        self.subscribers.iter().map(|sub| *sub.key()).collect()
+    }
+
+    fn close_subscriber(&self, id: u64) -> bool {
+        self.subscribers.remove(&id).is_some()
+    }
+}
+
 #[must_use = "Service does nothing unless polled"]
 #[allow(missing_debug_implementations)]
 pub struct Service {
-    simple_broker: Pin<Box<dyn Stream<Item = EntityWithMetadata<true>> + Send>>,
-    event_sender: UnboundedSender<EntityWithMetadata<true>>,
+    simple_broker: Pin<Box<dyn Stream<Item = Update<EntityWithMetadata<true>>> + Send>>,
+    event_sender: UnboundedSender<Update<EntityWithMetadata<true>>>,
 }
 
 impl Service {
     pub fn new(subs_manager: Arc<EventMessageManager>) -> Self {
         let (event_sender, event_receiver) = unbounded_channel();
         let service = Self {
-            simple_broker: if subs_manager.config.optimistic {
-                Box::pin(MemoryBroker::<EventMessageUpdate>::subscribe_optimistic())
-            } else {
-                Box::pin(MemoryBroker::<EventMessageUpdate>::subscribe())
-            },
+            simple_broker: Box::pin(MemoryBroker::<EventMessageUpdate>::subscribe_traced(
+                subs_manager.config.optimistic,
+            )),
             event_sender,
         };
 
@@ -123,10 +132,13 @@ impl Service {
 
     async fn publish_updates(
         subs: Arc<EventMessageManager>,
-        mut event_receiver: UnboundedReceiver<EntityWithMetadata<true>>,
+        mut event_receiver: UnboundedReceiver<Update<EntityWithMetadata<true>>>,
     ) {
-        while let Some(event) = event_receiver.recv().await {
-            Self::process_event_update(&subs, &event).await;
+        while let Some(update) = event_receiver.recv().await {
+            let span = info_span!(target: LOG_TARGET, parent: &update.span, "grpc_send", sent = field::Empty);
+            Self::process_event_update(&subs, &update.inner)
+                .instrument(span)
+                .await;
         }
     }
 
@@ -135,6 +147,7 @@ impl Service {
         event: &EntityWithMetadata<true>,
     ) {
         let mut closed_stream = Vec::new();
+        let mut sent = 0;
 
         for sub in subs.subscribers.iter() {
             let idx = sub.key();
@@ -171,7 +184,7 @@ impl Service {
             // Use try_send to avoid blocking on slow subscribers
             match sub.sender.try_send(Ok(resp)) {
                 Ok(_) => {
-                    // Message sent successfully
+                    sent += 1;
                 }
                 Err(tokio::sync::mpsc::error::TrySendError::Full(_)) => {
                     // Channel is full, subscriber is too slow - disconnect them
@@ -185,6 +198,8 @@ impl Service {
             }
         }
 
+        Span::current().record("sent", sent);
+
         for id in closed_stream {
             trace!(target = LOG_TARGET, id = %id, "Closing entity stream.");
             subs.remove_subscriber(id).await
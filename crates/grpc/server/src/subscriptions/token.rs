@@ -19,6 +19,7 @@ use tracing::{error, trace};
 use torii_proto::proto::world::SubscribeTokensResponse;
 use torii_proto::Token;
 
+use super::SubscriptionManager;
 use crate::GrpcConfig;
 
 pub(crate) const LOG_TARGET: &str = "torii::grpc::server::subscriptions::token";
@@ -94,6 +95,16 @@ impl TokenManager {
     }
 }
 
+impl SubscriptionManager for TokenManager {
+    fn subscription_ids(&self) -> Vec<u64> {
+        self.subscribers.iter().map(|sub| *sub.key()).collect()
+    }
+
+    fn close_subscriber(&self, id: u64) -> bool {
+        self.subscribers.remove(&id).is_some()
+    }
+}
+
 #[must_use = "Service does nothing unless polled"]
 #[allow(missing_debug_implementations)]
 pub struct Service {
@@ -19,6 +19,7 @@ use tracing::{error, trace};
 
 use torii_proto::proto::world::SubscribeTokenBalancesResponse;
 
+use super::SubscriptionManager;
 use crate::GrpcConfig;
 
 pub(crate) const LOG_TARGET: &str = "torii::grpc::server::subscriptions::balance";
@@ -101,6 +102,16 @@ impl TokenBalanceManager {
     }
 }
 
+impl SubscriptionManager for TokenBalanceManager {
+    fn subscription_ids(&self) -> Vec<u64> {
+        self.subscribers.iter().map(|sub| *sub.key()).collect()
+    }
+
+    fn close_subscriber(&self, id: u64) -> bool {
+        self.subscribers.remove(&id).is_some()
+    }
+}
+
 #[must_use = "Service does nothing unless polled"]
 #[allow(missing_debug_implementations)]
 pub struct Service {
@@ -19,6 +19,7 @@ use tracing::{error, trace};
 use torii_proto::proto::world::SubscribeTokenTransfersResponse;
 use torii_proto::TokenTransfer;
 
+use super::SubscriptionManager;
 use crate::GrpcConfig;
 
 pub(crate) const LOG_TARGET: &str = "torii::grpc::server::subscriptions::token_transfer";
@@ -101,6 +102,16 @@ impl TokenTransferManager {
     }
 }
 
+impl SubscriptionManager for TokenTransferManager {
+    fn subscription_ids(&self) -> Vec<u64> {
+        self.subscribers.iter().map(|sub| *sub.key()).collect()
+    }
+
+    fn close_subscriber(&self, id: u64) -> bool {
+        self.subscribers.remove(&id).is_some()
+    }
+}
+
 #[must_use = "Service does nothing unless polled"]
 #[allow(missing_debug_implementations)]
 pub struct Service {
@@ -0,0 +1,177 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use dojo_types::naming::compute_selector_from_names;
+use dojo_types::primitive::Primitive;
+use dojo_types::schema::{Member, Struct, Ty};
+use dojo_world::contracts::abigen::model::Layout;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use starknet::providers::jsonrpc::HttpTransport;
+use starknet::providers::{JsonRpcClient, Url};
+use starknet_crypto::Felt;
+use tempfile::NamedTempFile;
+use tokio::sync::broadcast;
+use tonic::{Code, Request};
+use torii_messaging::{Messaging, MessagingConfig};
+use torii_proto::proto::world::world_server::World;
+use torii_proto::proto::world::{AggregateEntitiesRequest, AggregateEntitiesResponse};
+use torii_proto::{AggregateFunction, EntityAggregate, EntityAggregateQuery};
+use torii_sqlite::executor::Executor;
+use torii_sqlite::Sql;
+use torii_storage::proto::{ContractDefinition, ContractType};
+use torii_storage::Storage;
+
+use crate::{DojoWorld, GrpcConfig};
+
+fn unit(id: u32, faction: u32) -> Ty {
+    Ty::Struct(Struct {
+        name: "ns-Unit".to_string(),
+        children: vec![
+            Member {
+                name: "id".to_string(),
+                ty: Ty::Primitive(Primitive::U32(Some(id))),
+                key: true,
+            },
+            Member {
+                name: "faction".to_string(),
+                ty: Ty::Primitive(Primitive::U32(Some(faction))),
+                key: false,
+            },
+            Member {
+                name: "power".to_string(),
+                ty: Ty::Primitive(Primitive::U32(Some(id * 10))),
+                key: false,
+            },
+        ],
+    })
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_aggregate_entities() {
+    let tempfile = NamedTempFile::new().unwrap();
+    let path = tempfile.path().to_string_lossy();
+    let options = SqliteConnectOptions::from_str(&path)
+        .unwrap()
+        .create_if_missing(true)
+        .with_regexp();
+    let pool = SqlitePoolOptions::new()
+        .min_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect_with(options)
+        .await
+        .unwrap();
+    sqlx::migrate!("../../migrations").run(&pool).await.unwrap();
+
+    let url: Url = "https://www.example.com".parse().unwrap();
+    let provider = Arc::new(JsonRpcClient::new(HttpTransport::new(url)));
+    let (shutdown_tx, _) = broadcast::channel(1);
+    let (mut executor, sender) =
+        Executor::new(pool.clone(), shutdown_tx.clone(), Arc::clone(&provider))
+            .await
+            .unwrap();
+    tokio::spawn(async move {
+        executor.run().await.unwrap();
+    });
+
+    let db = Arc::new(
+        Sql::new(
+            pool.clone(),
+            sender,
+            &[ContractDefinition {
+                address: Felt::ZERO,
+                r#type: ContractType::WORLD,
+                starting_block: None,
+            }],
+        )
+        .await
+        .unwrap(),
+    );
+
+    let selector = compute_selector_from_names("ns", "Unit");
+    db.register_model(
+        Felt::ZERO,
+        selector,
+        &unit(0, 0),
+        &Layout::Fixed(vec![]),
+        Felt::ZERO,
+        Felt::ZERO,
+        0,
+        0,
+        0,
+        None,
+        None,
+        true,
+    )
+    .await
+    .unwrap();
+    // units 1, 2 and 3 are of faction 1, unit 4 of faction 2
+    for id in 1..=4u32 {
+        db.set_entity(
+            Felt::ZERO,
+            unit(id, if id < 4 { 1 } else { 2 }),
+            &format!("0x0:0x{id}:0x0"),
+            0,
+            Felt::from(id),
+            selector,
+            Some(vec![Felt::from(id)]),
+        )
+        .await
+        .unwrap();
+    }
+    db.execute().await.unwrap();
+
+    let messaging = Arc::new(Messaging::new(
+        MessagingConfig::default(),
+        db.clone(),
+        provider.clone(),
+    ));
+    let grpc = DojoWorld::new(db, messaging, None, pool, GrpcConfig::default());
+
+    let aggregate = |function, member: &str| {
+        let query = EntityAggregateQuery {
+            model: "ns-Unit".to_string(),
+            clause: None,
+            group_by: vec!["faction".to_string()],
+            function,
+            member: (!member.is_empty()).then(|| member.to_string()),
+            world_addresses: vec![],
+        };
+        Request::new(AggregateEntitiesRequest {
+            query: Some(query.into()),
+        })
+    };
+    let aggregates = |response: AggregateEntitiesResponse| {
+        response
+            .aggregates
+            .into_iter()
+            .map(EntityAggregate::from)
+            .map(|aggregate| (aggregate.group, aggregate.count, aggregate.value))
+            .collect::<Vec<_>>()
+    };
+
+    let response = grpc
+        .aggregate_entities(aggregate(AggregateFunction::Sum, "power"))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(
+        aggregates(response),
+        vec![
+            (vec!["1".to_string()], 3, Some("60".to_string())),
+            (vec!["2".to_string()], 1, Some("40".to_string())),
+        ]
+    );
+
+    // the query is checked before reaching the storage
+    let error = grpc
+        .aggregate_entities(aggregate(AggregateFunction::Sum, ""))
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), Code::InvalidArgument);
+    let error = grpc
+        .aggregate_entities(Request::new(AggregateEntitiesRequest { query: None }))
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), Code::InvalidArgument);
+}
@@ -0,0 +1,126 @@
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use tonic::Code;
+use torii_auth::{AuthConfig, Authenticator, Capability, Principal};
+use tower::filter::{FilterLayer, Predicate};
+use tower::{service_fn, ServiceBuilder, ServiceExt};
+
+use crate::auth::{method_capability, AuthPredicate};
+
+#[test]
+fn test_method_capability() {
+    assert_eq!(
+        method_capability("/world.World/RetrieveEntities"),
+        Capability::Read
+    );
+    assert_eq!(
+        method_capability("/world.World/SubscribeEntities"),
+        Capability::Subscribe
+    );
+    assert_eq!(
+        method_capability("/world.World/UpdateEntitiesSubscription"),
+        Capability::Subscribe
+    );
+    assert_eq!(
+        method_capability("/world.World/PublishMessageBatch"),
+        Capability::PublishMessage
+    );
+    assert_eq!(
+        method_capability("/world.World/ExecuteSqlStream"),
+        Capability::Sql
+    );
+    assert_eq!(
+        method_capability("/world.Admin/ListSubscriptions"),
+        Capability::Admin
+    );
+    assert_eq!(
+        method_capability("/grpc.reflection.v1alpha.ServerReflection/ServerReflectionInfo"),
+        Capability::Read
+    );
+}
+
+#[test]
+fn test_auth_predicate() {
+    let authenticator = Authenticator::new(AuthConfig {
+        api_keys: vec!["partner:read".parse().unwrap()],
+        ..Default::default()
+    })
+    .unwrap();
+    let mut predicate = AuthPredicate::new(Arc::new(authenticator));
+
+    let build = |path: &str, key: Option<&str>| {
+        let mut builder = http::Request::builder().uri(path);
+        if let Some(key) = key {
+            builder = builder.header("authorization", format!("Bearer {}", key));
+        }
+        builder.body(()).unwrap()
+    };
+    let code = |error: tower::BoxError| error.downcast::<tonic::Status>().unwrap().code();
+
+    let request = predicate
+        .check(build("/world.World/RetrieveEntities", Some("partner")))
+        .unwrap();
+    assert!(request.extensions().get::<Principal>().is_some());
+
+    let error = predicate
+        .check(build("/world.World/RetrieveEntities", None))
+        .unwrap_err();
+    assert_eq!(code(error), Code::Unauthenticated);
+
+    let error = predicate
+        .check(build("/world.World/ExecuteSql", Some("partner")))
+        .unwrap_err();
+    assert_eq!(code(error), Code::PermissionDenied);
+
+    let error = predicate
+        .check(build("/world.Admin/PauseEngine", Some("partner")))
+        .unwrap_err();
+    assert_eq!(code(error), Code::PermissionDenied);
+}
+
+#[tokio::test]
+async fn test_auth_layer() {
+    let authenticator = Authenticator::new(AuthConfig {
+        api_keys: vec!["partner:read:1".parse().unwrap()],
+        ..Default::default()
+    })
+    .unwrap();
+    // The layer of the server, in front of a service answering with the id of the principal
+    let service = ServiceBuilder::new()
+        .layer(FilterLayer::new(AuthPredicate::new(Arc::new(
+            authenticator,
+        ))))
+        .service(service_fn(|request: http::Request<()>| async move {
+            Ok::<_, Infallible>(request.extensions().get::<Principal>().unwrap().id.clone())
+        }));
+
+    let call = |path: &str, key: Option<&str>| {
+        let mut builder = http::Request::builder().uri(path);
+        if let Some(key) = key {
+            builder = builder.header("x-api-key", key);
+        }
+        service.clone().oneshot(builder.body(()).unwrap())
+    };
+    let code = |error: tower::BoxError| error.downcast::<tonic::Status>().unwrap().code();
+
+    // The principal of a key is identified without its key
+    let id = call("/world.World/RetrieveEntities", Some("partner"))
+        .await
+        .unwrap();
+    assert!(id.starts_with("key:") && !id.contains("partner"));
+
+    let error = call("/world.World/SubscribeEntities", Some("partner"))
+        .await
+        .unwrap_err();
+    assert_eq!(code(error), Code::PermissionDenied);
+    let error = call("/world.World/RetrieveEntities", Some("other"))
+        .await
+        .unwrap_err();
+    assert_eq!(code(error), Code::Unauthenticated);
+    // The quota of the key is 1 request per minute
+    let error = call("/world.World/RetrieveEntities", Some("partner"))
+        .await
+        .unwrap_err();
+    assert_eq!(code(error), Code::ResourceExhausted);
+}
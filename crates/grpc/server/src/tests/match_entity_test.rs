@@ -0,0 +1,144 @@
+use dojo_types::primitive::Primitive;
+use dojo_types::schema::{Enum, EnumOption, Member, Struct, Ty};
+use starknet_crypto::Felt;
+use torii_proto::matching::{like_match, match_entity};
+use torii_proto::{
+    Clause, ComparisonOperator, CompositeClause, LogicalOperator, MemberClause, MemberValue,
+};
+
+fn player(name: &str, level: u32, mount: Option<u32>) -> Ty {
+    Ty::Struct(Struct {
+        name: "ns-Player".to_string(),
+        children: vec![
+            Member {
+                name: "name".to_string(),
+                ty: Ty::ByteArray(name.to_string()),
+                key: false,
+            },
+            Member {
+                name: "level".to_string(),
+                ty: Ty::Primitive(Primitive::U32(Some(level))),
+                key: false,
+            },
+            Member {
+                name: "mount".to_string(),
+                ty: Ty::Enum(Enum {
+                    name: "Option<u32>".to_string(),
+                    option: Some(if mount.is_some() { 0 } else { 1 }),
+                    options: vec![
+                        EnumOption {
+                            name: "Some".to_string(),
+                            ty: Ty::Primitive(Primitive::U32(mount)),
+                        },
+                        EnumOption {
+                            name: "None".to_string(),
+                            ty: Ty::Tuple(vec![]),
+                        },
+                    ],
+                }),
+                key: false,
+            },
+        ],
+    })
+}
+
+fn member(member: &str, operator: ComparisonOperator, value: MemberValue) -> Clause {
+    Clause::Member(MemberClause {
+        model: "ns-Player".to_string(),
+        member: member.to_string(),
+        operator,
+        value,
+    })
+}
+
+fn matches(model: &Ty, clause: &Clause) -> bool {
+    match_entity(Felt::ONE, &[], &Some(model.clone()), clause)
+}
+
+#[test]
+fn test_match_not_clause() {
+    let clause = Clause::Composite(CompositeClause {
+        operator: LogicalOperator::Not,
+        clauses: vec![
+            member(
+                "level",
+                ComparisonOperator::Gt,
+                MemberValue::Primitive(Primitive::U32(Some(5))),
+            ),
+            member(
+                "name",
+                ComparisonOperator::Eq,
+                MemberValue::String("bob".to_string()),
+            ),
+        ],
+    });
+
+    assert!(!matches(&player("bob", 10, None), &clause));
+    assert!(matches(&player("alice", 10, None), &clause));
+    assert!(matches(&player("bob", 1, None), &clause));
+
+    // the update of another model doesn't tell whether the player matches
+    let position = Ty::Struct(Struct {
+        name: "ns-Position".to_string(),
+        children: vec![Member {
+            name: "x".to_string(),
+            ty: Ty::Primitive(Primitive::U32(Some(1))),
+            key: false,
+        }],
+    });
+    assert!(!matches(&position, &clause));
+    assert!(!match_entity(Felt::ONE, &[], &None, &clause));
+}
+
+#[test]
+fn test_match_null_checks() {
+    let is_null = member(
+        "mount.Some",
+        ComparisonOperator::IsNull,
+        MemberValue::List(vec![]),
+    );
+    let is_not_null = member(
+        "mount.Some",
+        ComparisonOperator::IsNotNull,
+        MemberValue::List(vec![]),
+    );
+
+    assert!(matches(&player("bob", 1, None), &is_null));
+    assert!(!matches(&player("bob", 1, None), &is_not_null));
+    assert!(!matches(&player("bob", 1, Some(3)), &is_null));
+    assert!(matches(&player("bob", 1, Some(3)), &is_not_null));
+}
+
+#[test]
+fn test_match_like_and_between() {
+    let like = member(
+        "name",
+        ComparisonOperator::Like,
+        MemberValue::String("B_b%".to_string()),
+    );
+    assert!(matches(&player("bobby", 1, None), &like));
+    assert!(!matches(&player("alice", 1, None), &like));
+
+    let between = member(
+        "level",
+        ComparisonOperator::Between,
+        MemberValue::List(vec![
+            MemberValue::Primitive(Primitive::U32(Some(5))),
+            MemberValue::Primitive(Primitive::U32(Some(10))),
+        ]),
+    );
+    assert!(matches(&player("bob", 5, None), &between));
+    assert!(matches(&player("bob", 10, None), &between));
+    assert!(!matches(&player("bob", 11, None), &between));
+}
+
+#[test]
+fn test_like_match() {
+    assert!(like_match("bob", "bob"));
+    assert!(like_match("Bob", "bOB"));
+    assert!(like_match("bobby", "%by"));
+    assert!(like_match("bobby", "b%b%y"));
+    assert!(like_match("", "%"));
+    assert!(!like_match("bob", "b_"));
+    assert!(!like_match("bobby", "%bo"));
+}
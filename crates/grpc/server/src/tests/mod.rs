@@ -1,2 +1,7 @@
+mod aggregate_test;
+mod auth_test;
 mod entities_test;
+mod match_entity_test;
 mod messaging;
+mod sql_test;
+mod tracing_test;
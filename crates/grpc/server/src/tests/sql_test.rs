@@ -0,0 +1,64 @@
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use tonic::transport::server::TcpConnectInfo;
+use tonic::Request;
+
+use crate::sql_client;
+
+fn request(peer: [u8; 4], forwarded_for: Option<&str>) -> Request<()> {
+    let mut request = Request::new(());
+    request.extensions_mut().insert(TcpConnectInfo {
+        local_addr: None,
+        remote_addr: Some(SocketAddr::from((peer, 8080))),
+    });
+    if let Some(forwarded_for) = forwarded_for {
+        request
+            .metadata_mut()
+            .insert("x-forwarded-for", forwarded_for.parse().unwrap());
+    }
+    request
+}
+
+#[test]
+fn test_sql_client() {
+    let proxies = [
+        IpAddr::V4(Ipv4Addr::LOCALHOST),
+        IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)),
+    ];
+
+    // without a trusted proxy, the forwarded chain is ignored
+    assert_eq!(
+        sql_client(&request([1, 2, 3, 4], None), &proxies),
+        "1.2.3.4"
+    );
+    assert_eq!(
+        sql_client(&request([1, 2, 3, 4], Some("5.6.7.8")), &proxies),
+        "1.2.3.4"
+    );
+    assert_eq!(
+        sql_client(&request([127, 0, 0, 1], Some("5.6.7.8")), &[]),
+        "127.0.0.1"
+    );
+
+    // the client can't pick its address by forwarding one itself
+    assert_eq!(
+        sql_client(&request([127, 0, 0, 1], Some("5.6.7.8, 1.2.3.4")), &proxies),
+        "1.2.3.4"
+    );
+    assert_eq!(
+        sql_client(
+            &request([127, 0, 0, 1], Some("5.6.7.8, 1.2.3.4, 10.0.0.2")),
+            &proxies
+        ),
+        "1.2.3.4"
+    );
+    // a chain of trusted proxies only, or an invalid one, is the client of the peer
+    assert_eq!(
+        sql_client(&request([127, 0, 0, 1], Some("10.0.0.2")), &proxies),
+        "127.0.0.1"
+    );
+    assert_eq!(
+        sql_client(&request([127, 0, 0, 1], Some("unknown")), &proxies),
+        "127.0.0.1"
+    );
+}
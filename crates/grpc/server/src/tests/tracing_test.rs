@@ -0,0 +1,215 @@
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use cainome::cairo_serde::ContractAddress;
+use dojo_test_utils::migration::copy_spawn_and_move_db;
+use dojo_test_utils::setup::TestSetup;
+use dojo_types::naming::compute_selector_from_names;
+use dojo_utils::{TransactionExt, TransactionWaiter, TxnConfig};
+use dojo_world::contracts::naming::compute_bytearray_hash;
+use dojo_world::contracts::WorldContract;
+use katana_runner::RunnerCtx;
+use scarb_interop::Profile;
+use scarb_metadata_ext::MetadataDojoExt;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use starknet::accounts::Account;
+use starknet::core::types::Call;
+use starknet::core::utils::get_selector_from_name;
+use starknet::providers::jsonrpc::HttpTransport;
+use starknet::providers::JsonRpcClient;
+use tempfile::NamedTempFile;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc::Receiver;
+use torii_cache::InMemoryCache;
+use torii_indexer::engine::{Engine, EngineConfig};
+use torii_processors::processors::Processors;
+use torii_proto::proto::world::SubscribeEntityResponse;
+use torii_sqlite::executor::Executor;
+use torii_sqlite::Sql;
+use torii_storage::proto::{ContractDefinition, ContractType};
+use tracing::span::{Attributes, Id};
+use tracing::Subscriber;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+use crate::subscriptions::entity::{EntityManager, Service};
+use crate::GrpcConfig;
+
+/// Records the names of the spans created, from each span to the root of its trace.
+#[derive(Clone, Default)]
+struct Collector(Arc<Mutex<Vec<Vec<&'static str>>>>);
+
+impl<S: Subscriber + for<'span> LookupSpan<'span>> Layer<S> for Collector {
+    fn on_new_span(&self, _attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        // The parents of a span live as long as the span, its whole scope is known here
+        let scope = ctx
+            .span_scope(id)
+            .unwrap()
+            .map(|span| span.name())
+            .collect();
+        self.0.lock().unwrap().push(scope);
+    }
+}
+
+/// The first entity delivered to a subscriber, after the message opening its stream.
+async fn first_entity(receiver: &mut Receiver<Result<SubscribeEntityResponse, tonic::Status>>) {
+    while let Some(response) = receiver.recv().await {
+        if response.unwrap().entity.is_some() {
+            return;
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[katana_runner::test(accounts = 10, db_dir = copy_spawn_and_move_db().as_str())]
+async fn test_trace_of_a_range(sequencer: &RunnerCtx) {
+    // The spans are filtered like the logs of torii, the queries aren't spans at this level
+    let collector = Collector::default();
+    tracing::subscriber::set_global_default(
+        tracing_subscriber::registry()
+            .with(LevelFilter::INFO)
+            .with(collector.clone()),
+    )
+    .unwrap();
+
+    let tempfile = NamedTempFile::new().unwrap();
+    let path = tempfile.path().to_string_lossy();
+    let options = SqliteConnectOptions::from_str(&path)
+        .unwrap()
+        .create_if_missing(true)
+        .with_regexp();
+    let pool = SqlitePoolOptions::new()
+        .min_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect_with(options)
+        .await
+        .unwrap();
+    sqlx::migrate!("../../migrations").run(&pool).await.unwrap();
+
+    let setup = TestSetup::from_examples("/tmp", "../../../examples/");
+    let metadata = setup.load_metadata("spawn-and-move", Profile::DEV);
+
+    let account = sequencer.account(0);
+
+    let world_local = metadata.load_dojo_world_local().unwrap();
+    let world_address = world_local.deterministic_world_address().unwrap();
+
+    let actions_address = world_local
+        .get_contract_address_local(compute_selector_from_names("ns", "actions"))
+        .unwrap();
+
+    let provider = Arc::new(JsonRpcClient::new(HttpTransport::new(sequencer.url())));
+
+    let world = WorldContract::new(world_address, &account);
+
+    world
+        .grant_writer(
+            &compute_bytearray_hash("ns"),
+            &ContractAddress(actions_address),
+        )
+        .send_with_cfg(&TxnConfig::init_wait())
+        .await
+        .unwrap();
+    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+
+    // spawn
+    let tx = account
+        .execute_v3(vec![Call {
+            to: actions_address,
+            selector: get_selector_from_name("spawn").unwrap(),
+            calldata: vec![],
+        }])
+        .send()
+        .await
+        .unwrap();
+
+    TransactionWaiter::new(tx.transaction_hash, &provider)
+        .await
+        .unwrap();
+
+    let (shutdown_tx, _) = broadcast::channel(1);
+
+    let (mut executor, sender) =
+        Executor::new(pool.clone(), shutdown_tx.clone(), Arc::clone(&provider))
+            .await
+            .unwrap();
+    tokio::spawn(async move {
+        executor.run().await.unwrap();
+    });
+
+    let db = Sql::new(
+        pool.clone(),
+        sender,
+        &[ContractDefinition {
+            address: world_address,
+            r#type: ContractType::WORLD,
+            starting_block: None,
+        }],
+    )
+    .await
+    .unwrap();
+
+    let cache = Arc::new(InMemoryCache::new(Arc::new(db.clone())).await.unwrap());
+    let db = db.with_cache(cache.clone());
+
+    // The subscribers of the committed updates and of the optimistic ones
+    let committed = Arc::new(EntityManager::new(GrpcConfig::default()));
+    let optimistic = Arc::new(EntityManager::new(GrpcConfig {
+        optimistic: true,
+        ..Default::default()
+    }));
+    tokio::spawn(Service::new(Arc::clone(&committed)));
+    tokio::spawn(Service::new(Arc::clone(&optimistic)));
+    let mut committed_rx = committed.add_subscriber(None, vec![]).await;
+    let mut optimistic_rx = optimistic.add_subscriber(None, vec![]).await;
+
+    let mut engine = Engine::new(
+        Arc::new(db.clone()),
+        cache.clone(),
+        Arc::clone(&provider),
+        Arc::new(Processors::default()),
+        EngineConfig::default(),
+        shutdown_tx.clone(),
+    );
+    let engine = tokio::spawn(async move { engine.start().await });
+
+    tokio::time::timeout(Duration::from_secs(30), async {
+        first_entity(&mut committed_rx).await;
+        first_entity(&mut optimistic_rx).await;
+    })
+    .await
+    .unwrap();
+    shutdown_tx.send(()).unwrap();
+    engine.await.unwrap().unwrap();
+
+    let scopes = collector.0.lock().unwrap().clone();
+    // The committed updates are delivered in the span of the commit of their range
+    assert!(
+        scopes.contains(&vec![
+            "grpc_send",
+            "publish",
+            "commit",
+            "index_range",
+            "fetch"
+        ]),
+        "{:?}",
+        scopes
+    );
+    // The optimistic ones in the span of the task their query was sent from
+    assert!(
+        scopes.contains(&vec![
+            "grpc_send",
+            "publish",
+            "task",
+            "process",
+            "index_range",
+            "fetch"
+        ]),
+        "{:?}",
+        scopes
+    );
+}
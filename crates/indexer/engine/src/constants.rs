@@ -1 +1,5 @@
 pub(crate) const LOG_TARGET: &str = "torii::indexer::engine";
+
+/// Largest block range processed again by a single reindex command, as it holds the engine
+/// until done.
+pub const MAX_REINDEX_BLOCKS: u64 = 10_000;
@@ -4,7 +4,9 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use metrics::{counter, gauge};
-use starknet::core::types::{Event, TransactionContent};
+use starknet::core::types::{
+    BlockHashAndNumber, BlockId, Event, MaybePreConfirmedBlockWithTxHashes, TransactionContent,
+};
 use starknet::macros::selector;
 use starknet::providers::Provider;
 use starknet_crypto::Felt;
@@ -12,23 +14,27 @@ use std::sync::LazyLock;
 use tokio::sync::broadcast::Sender;
 use tokio::sync::Semaphore;
 use tokio::time::{sleep, Instant};
+use torii_admin::{CommandReceiver, CommandReply, EngineCommand};
 use torii_cache::{Cache, ContractClassCache};
 use torii_controllers::sync::ControllersSync;
 use torii_processors::{
     BlockProcessorContext, EventProcessorConfig, EventProcessorContext, Processors,
     TransactionProcessorContext,
 };
-use torii_storage::proto::{Contract, ContractCursor, ContractQuery, ContractType};
+use torii_storage::proto::{
+    Contract, ContractCursor, ContractDefinition, ContractQuery, ContractType,
+};
 use torii_storage::utils::format_event_id;
 use torii_storage::Storage;
-use tracing::{debug, error, info, trace};
+use tracing::{debug, error, field, info, info_span, trace, Instrument};
 
-use crate::constants::LOG_TARGET;
+use crate::constants::{LOG_TARGET, MAX_REINDEX_BLOCKS};
 use crate::error::{Error, ProcessError};
 use crate::IndexingFlags;
 use torii_indexer_fetcher::{
     FetchPreconfirmedBlockResult, FetchRangeResult, FetchResult, Fetcher, FetcherConfig,
 };
+use torii_processors::status::IndexerStatus;
 use torii_processors::task_manager::{ParallelizedEvent, TaskManager};
 
 static DOJO_RELATED_EVENTS: LazyLock<HashSet<Felt>> = LazyLock::new(|| {
@@ -67,6 +73,8 @@ pub struct Engine<P: Provider + Send + Sync + Clone + std::fmt::Debug + 'static>
     // The last fetch result & cursors, in case the processing fails, but not fetching.
     // Thus we can retry the processing with the same data instead of fetching again.
     cached_fetch: Option<(Box<FetchResult>, HashMap<Felt, ContractType>)>,
+    status: Arc<IndexerStatus>,
+    commands: Option<CommandReceiver>,
 }
 
 impl Default for EngineConfig {
@@ -144,9 +152,23 @@ impl<P: Provider + Send + Sync + Clone + std::fmt::Debug + 'static> Engine<P> {
             fetcher: Fetcher::new(provider.clone(), fetcher_config),
             nft_metadata_semaphore,
             cached_fetch: None,
+            status: Arc::new(IndexerStatus::default()),
+            commands: None,
         }
     }
 
+    /// Shares the progress of the engine through `status`.
+    pub fn with_status(mut self, status: Arc<IndexerStatus>) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Applies the runtime commands of the admin API, between two fetches.
+    pub fn with_commands(mut self, commands: CommandReceiver) -> Self {
+        self.commands = Some(commands);
+        self
+    }
+
     async fn get_contracts(&self) -> Result<HashMap<Felt, Contract>, Error> {
         let query = ContractQuery {
             contract_addresses: vec![],
@@ -171,6 +193,34 @@ impl<P: Provider + Send + Sync + Clone + std::fmt::Debug + 'static> Engine<P> {
         let mut processing_erroring_out = false;
 
         loop {
+            // Commands are applied between two fetches, out of the transaction of a range
+            while let Some((command, reply)) = self
+                .commands
+                .as_mut()
+                .and_then(|commands| commands.try_recv().ok())
+            {
+                self.apply_command(command, reply).await;
+            }
+
+            if self.status.paused() {
+                tokio::select! {
+                    _ = shutdown_rx.recv() => {
+                        break Ok(());
+                    }
+                    command = next_command(&mut self.commands) => {
+                        match command {
+                            Some((command, reply)) => self.apply_command(command, reply).await,
+                            // Nothing can resume the engine anymore
+                            None => self.status.set_paused(false),
+                        }
+                    }
+                }
+                continue;
+            }
+
+            // Root of the spans of an iteration, from the fetch to the commit of its queries
+            let fetch_span = info_span!(target: LOG_TARGET, "fetch", head = field::Empty);
+
             tokio::select! {
                 _ = shutdown_rx.recv() => {
                     break Ok(());
@@ -189,7 +239,7 @@ impl<P: Provider + Send + Sync + Clone + std::fmt::Debug + 'static> Engine<P> {
                     };
 
                     Result::<_, Error>::Ok((result, controller_sync_handle))
-                } => {
+                }.instrument(fetch_span.clone()) => {
                     match res {
                         Ok((fetch_result, controller_sync_handle)) => {
                             match fetch_result {
@@ -208,8 +258,19 @@ impl<P: Provider + Send + Sync + Clone + std::fmt::Debug + 'static> Engine<P> {
                                         self.cached_fetch = Some((fetch_result.clone(), contracts.clone()));
                                     }
 
+                                    // Only the iterations that fetched blocks index a range
+                                    let range_span = if !fetch_result.range.blocks.is_empty() || fetch_result.preconfirmed_block.is_some() {
+                                        info_span!(target: LOG_TARGET, parent: &fetch_span, "index_range")
+                                    } else {
+                                        fetch_span.clone()
+                                    };
+
                                     let process_start = Instant::now();
-                                    match self.process(&fetch_result, &contracts).await {
+                                    match self
+                                        .process(&fetch_result, &contracts)
+                                        .instrument(info_span!(target: LOG_TARGET, parent: &range_span, "process"))
+                                        .await
+                                    {
                                         Ok(_) => {
 
                                             // Only reset backoff delay after successful processing
@@ -224,14 +285,14 @@ impl<P: Provider + Send + Sync + Clone + std::fmt::Debug + 'static> Engine<P> {
 
                                             // Wait for controller sync to complete before executing
                                             self.join_controllers_sync(controller_sync_handle).await?;
-                                            self.storage.execute().await?;
+                                            self.storage.execute().instrument(range_span.clone()).await?;
                                         },
                                         Err(e) => {
                                             self.abort_controllers_sync(controller_sync_handle).await;
                                             counter!("torii_indexer_errors_total", "operation" => "process").increment(1);
                                             error!(target: LOG_TARGET, error = ?e, "Processing fetched data.");
                                             processing_erroring_out = true;
-                                            self.storage.rollback().await?;
+                                            self.storage.rollback().instrument(range_span.clone()).await?;
                                             self.task_manager.clear_tasks();
                                             gauge!("torii_indexer_backoff_delay_seconds", "operation" => "process").set(processing_backoff_delay.as_secs_f64());
                                             sleep(processing_backoff_delay).await;
@@ -275,6 +336,159 @@ impl<P: Provider + Send + Sync + Clone + std::fmt::Debug + 'static> Engine<P> {
         }
     }
 
+    pub(crate) async fn apply_command(&mut self, command: EngineCommand, reply: CommandReply) {
+        info!(target: LOG_TARGET, command = ?command, "Applying admin command.");
+
+        let result = match command {
+            EngineCommand::AddContract(contract) => self.add_contract(contract).await,
+            EngineCommand::RemoveContract(address) => self.remove_contract(address).await,
+            EngineCommand::RefreshMetadata(address) => self
+                .storage
+                .enqueue_contract_metadata_jobs(address)
+                .await
+                .map_err(Error::from),
+            EngineCommand::RebuildSearchIndex => self
+                .storage
+                .rebuild_search_index()
+                .await
+                .map_err(Error::from),
+            EngineCommand::ReindexRange {
+                from_block,
+                to_block,
+                contracts,
+            } => self.reindex_range(from_block, to_block, &contracts).await,
+            EngineCommand::Pause => {
+                self.status.set_paused(true);
+                Ok(())
+            }
+            EngineCommand::Resume => {
+                self.status.set_paused(false);
+                Ok(())
+            }
+        };
+
+        let result = match result {
+            Ok(()) => self.storage.execute().await.map_err(Error::from),
+            Err(e) => {
+                self.task_manager.clear_tasks();
+                if let Err(e) = self.storage.rollback().await {
+                    error!(target: LOG_TARGET, error = ?e, "Rolling back admin command.");
+                }
+                Err(e)
+            }
+        };
+
+        if let Err(e) = &result {
+            error!(target: LOG_TARGET, error = ?e, "Applying admin command.");
+        }
+        let _ = reply.send(result.map_err(|e| e.to_string()));
+    }
+
+    async fn add_contract(&self, contract: ContractDefinition) -> Result<(), Error> {
+        if self.get_contracts().await?.contains_key(&contract.address) {
+            return Err(Error::ContractAlreadyIndexed(contract.address));
+        }
+
+        self.storage
+            .register_contract(
+                contract.address,
+                contract.r#type,
+                contract
+                    .starting_block
+                    .map_or(0, |block| block.saturating_sub(1)),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn remove_contract(&mut self, address: Felt) -> Result<(), Error> {
+        if !self.get_contracts().await?.contains_key(&address) {
+            return Err(Error::ContractNotIndexed(address));
+        }
+
+        self.storage.unregister_contract(address).await?;
+        // A failed fetch of the contract must not be retried
+        self.cached_fetch = None;
+
+        Ok(())
+    }
+
+    /// Processes the events of the world contracts in a block range again, without moving
+    /// their cursors, up to `MAX_REINDEX_BLOCKS` at once. ERC contracts are left out, see
+    /// `torii_sqlite::maintenance::rewind`.
+    async fn reindex_range(
+        &mut self,
+        from_block: u64,
+        to_block: u64,
+        contracts: &[Felt],
+    ) -> Result<(), Error> {
+        if from_block > to_block {
+            return Err(Error::InvalidBlockRange(from_block, to_block));
+        }
+        if to_block - from_block >= MAX_REINDEX_BLOCKS {
+            return Err(Error::ReindexRangeTooLarge(from_block, to_block));
+        }
+
+        let contract_types = self
+            .get_contracts()
+            .await?
+            .into_values()
+            .filter(|contract| contract.contract_type == ContractType::WORLD)
+            .filter(|contract| {
+                contracts.is_empty() || contracts.contains(&contract.contract_address)
+            })
+            .map(|contract| (contract.contract_address, contract.contract_type))
+            .collect::<HashMap<_, _>>();
+        if contract_types.is_empty() {
+            return Err(Error::NothingToReindex);
+        }
+
+        let to = match self
+            .provider
+            .get_block_with_tx_hashes(BlockId::Number(to_block))
+            .await?
+        {
+            MaybePreConfirmedBlockWithTxHashes::Block(block) => BlockHashAndNumber {
+                block_hash: block.block_hash,
+                block_number: block.block_number,
+            },
+            _ => return Err(Error::InvalidBlockRange(from_block, to_block)),
+        };
+
+        let mut cursors = contract_types
+            .keys()
+            .map(|address| {
+                (
+                    *address,
+                    ContractCursor {
+                        contract_address: *address,
+                        head: Some(from_block.saturating_sub(1)),
+                        last_block_timestamp: None,
+                        last_pending_block_tx: None,
+                    },
+                )
+            })
+            .collect::<HashMap<_, _>>();
+
+        while cursors.values().any(|cursor| cursor.head < Some(to_block)) {
+            let (range, next) = self.fetcher.fetch_range(&cursors, to.clone()).await?;
+            self.process_range(&range, &contract_types).await?;
+            self.task_manager
+                .process_tasks()
+                .await
+                .map_err(ProcessError::from)?;
+
+            if next.cursors == cursors {
+                break;
+            }
+            cursors = next.cursors;
+        }
+
+        info!(target: LOG_TARGET, from_block, to_block, contracts = contract_types.len(), "Reindexed block range.");
+        Ok(())
+    }
+
     pub async fn process(
         &mut self,
         fetch_result: &FetchResult,
@@ -294,7 +508,11 @@ impl<P: Provider + Send + Sync + Clone + std::fmt::Debug + 'static> Engine<P> {
         // Process parallelized events
         debug!(target: LOG_TARGET, "Processing parallelized events.");
         let instant = Instant::now();
-        self.task_manager.process_tasks().await?;
+        self.status
+            .set_pending_tasks(self.task_manager.pending_tasks_count());
+        let result = self.task_manager.process_tasks().await;
+        self.status.set_pending_tasks(0);
+        result?;
         debug!(target: LOG_TARGET, duration = ?instant.elapsed(), "Processed parallelized events.");
 
         // Apply ERC balances cache diff
@@ -660,3 +878,12 @@ impl<P: Provider + Send + Sync + Clone + std::fmt::Debug + 'static> Engine<P> {
         }
     }
 }
+
+async fn next_command(
+    commands: &mut Option<CommandReceiver>,
+) -> Option<(EngineCommand, CommandReply)> {
+    match commands {
+        Some(commands) => commands.recv().await,
+        None => None,
+    }
+}
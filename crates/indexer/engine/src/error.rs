@@ -1,5 +1,8 @@
+use starknet_crypto::Felt;
 use thiserror::Error;
 
+use crate::constants::MAX_REINDEX_BLOCKS;
+
 #[derive(Error, Debug)]
 pub enum Error {
     #[error(transparent)]
@@ -14,6 +17,19 @@ pub enum Error {
     Provider(#[from] starknet::providers::ProviderError),
     #[error(transparent)]
     ControllerSync(#[from] torii_controllers::error::Error),
+    #[error("Contract {0:#x} is already indexed")]
+    ContractAlreadyIndexed(Felt),
+    #[error("Contract {0:#x} is not indexed")]
+    ContractNotIndexed(Felt),
+    #[error("Invalid block range {0}..={1}")]
+    InvalidBlockRange(u64, u64),
+    #[error(
+        "Block range {0}..={1} is larger than the {max} blocks reindexed at once",
+        max = MAX_REINDEX_BLOCKS
+    )]
+    ReindexRangeTooLarge(u64, u64),
+    #[error("No indexed world contract to reindex")]
+    NothingToReindex,
 }
 
 #[derive(Error, Debug)]
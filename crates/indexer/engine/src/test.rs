@@ -19,19 +19,23 @@ use starknet::providers::jsonrpc::HttpTransport;
 use starknet::providers::{JsonRpcClient, Provider};
 use starknet_crypto::poseidon_hash_many;
 use tempfile::NamedTempFile;
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, oneshot};
+use torii_admin::EngineCommand;
 use torii_cache::{Cache, InMemoryCache};
 use torii_sqlite::executor::Executor;
 use torii_sqlite::types::Token;
 use torii_sqlite::utils::{felt_and_u256_to_sql_string, felt_to_sql_string, u256_to_sql_string};
 use torii_sqlite::Sql;
-use torii_storage::proto::{ContractDefinition, ContractType};
+use torii_storage::proto::{ContractDefinition, ContractQuery, ContractType};
 use torii_storage::utils::format_world_scoped_id;
 use torii_storage::Storage;
 
+use crate::constants::MAX_REINDEX_BLOCKS;
 use crate::engine::{Engine, EngineConfig};
+use crate::error::Error;
 use torii_indexer_fetcher::{Fetcher, FetcherConfig};
 use torii_processors::processors::Processors;
+use torii_processors::status::IndexerStatus;
 
 pub async fn bootstrap_engine<P>(
     db: Sql,
@@ -1952,3 +1956,131 @@ async fn test_erc1155_total_supply_tracking(sequencer: &RunnerCtx) {
         u256_to_sql_string(&expected_token2_supply)
     );
 }
+
+async fn apply<P>(engine: &mut Engine<P>, command: EngineCommand) -> Result<(), String>
+where
+    P: Provider + Send + Sync + core::fmt::Debug + Clone + 'static,
+{
+    let (reply, outcome) = oneshot::channel();
+    engine.apply_command(command, reply).await;
+    outcome.await.unwrap()
+}
+
+async fn indexed_contracts(db: &Sql) -> Vec<Felt> {
+    db.contracts(&ContractQuery {
+        contract_addresses: vec![],
+        contract_types: vec![],
+    })
+    .await
+    .unwrap()
+    .into_iter()
+    .map(|contract| contract.contract_address)
+    .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_apply_command() {
+    // The commands below are applied without reaching the provider.
+    let provider = Arc::new(JsonRpcClient::new(HttpTransport::new(
+        url::Url::parse("http://127.0.0.1:1").unwrap(),
+    )));
+
+    let tempfile = NamedTempFile::new().unwrap();
+    let path = tempfile.path().to_string_lossy();
+    let options = SqliteConnectOptions::from_str(&path)
+        .unwrap()
+        .create_if_missing(true);
+    let pool = SqlitePoolOptions::new()
+        .connect_with(options)
+        .await
+        .unwrap();
+    sqlx::migrate!("../../migrations").run(&pool).await.unwrap();
+
+    let (shutdown_tx, _) = broadcast::channel(1);
+    let (mut executor, sender) =
+        Executor::new(pool.clone(), shutdown_tx.clone(), Arc::clone(&provider))
+            .await
+            .unwrap();
+    tokio::spawn(async move {
+        executor.run().await.unwrap();
+    });
+
+    let world_address = Felt::from_hex_unchecked("0x1234");
+    let erc20_address = Felt::from_hex_unchecked("0x5678");
+    let db = Sql::new(
+        pool.clone(),
+        sender,
+        &[ContractDefinition {
+            address: world_address,
+            r#type: ContractType::WORLD,
+            starting_block: None,
+        }],
+    )
+    .await
+    .unwrap();
+    let cache = Arc::new(InMemoryCache::new(Arc::new(db.clone())).await.unwrap());
+    let status = Arc::new(IndexerStatus::default());
+    let mut engine = Engine::new(
+        Arc::new(db.clone()),
+        cache,
+        provider,
+        Arc::new(Processors::default()),
+        EngineConfig::default(),
+        shutdown_tx,
+    )
+    .with_status(status.clone());
+
+    let erc20 = ContractDefinition {
+        address: erc20_address,
+        r#type: ContractType::ERC20,
+        starting_block: Some(10),
+    };
+    assert!(
+        apply(&mut engine, EngineCommand::AddContract(erc20.clone()))
+            .await
+            .is_ok()
+    );
+    assert!(indexed_contracts(&db).await.contains(&erc20_address));
+    assert!(apply(&mut engine, EngineCommand::AddContract(erc20))
+        .await
+        .is_err());
+
+    assert!(
+        apply(&mut engine, EngineCommand::RemoveContract(erc20_address))
+            .await
+            .is_ok()
+    );
+    assert!(!indexed_contracts(&db).await.contains(&erc20_address));
+    assert!(
+        apply(&mut engine, EngineCommand::RemoveContract(erc20_address))
+            .await
+            .is_err()
+    );
+
+    let reindex = |from_block, to_block, contracts| EngineCommand::ReindexRange {
+        from_block,
+        to_block,
+        contracts,
+    };
+    let error = apply(&mut engine, reindex(10, 5, vec![]))
+        .await
+        .unwrap_err();
+    assert_eq!(error, Error::InvalidBlockRange(10, 5).to_string());
+    let error = apply(&mut engine, reindex(0, MAX_REINDEX_BLOCKS, vec![]))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error,
+        Error::ReindexRangeTooLarge(0, MAX_REINDEX_BLOCKS).to_string()
+    );
+    // Only the world contracts are reindexed
+    let error = apply(&mut engine, reindex(0, 5, vec![erc20_address]))
+        .await
+        .unwrap_err();
+    assert_eq!(error, Error::NothingToReindex.to_string());
+
+    assert!(apply(&mut engine, EngineCommand::Pause).await.is_ok());
+    assert!(status.paused());
+    assert!(apply(&mut engine, EngineCommand::Resume).await.is_ok());
+    assert!(!status.paused());
+}
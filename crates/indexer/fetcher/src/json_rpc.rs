@@ -17,7 +17,7 @@ use starknet::providers::{Provider, ProviderRequestData, ProviderResponseData};
 use starknet_crypto::Felt;
 use tokio::time::{sleep, Instant};
 use torii_storage::proto::ContractCursor;
-use tracing::{debug, error, trace, warn};
+use tracing::{debug, error, info_span, trace, warn, Instrument, Span};
 
 use crate::error::Error;
 use crate::{
@@ -53,13 +53,18 @@ impl<P: Provider + Send + Sync + Clone + std::fmt::Debug + 'static> Fetcher<P> {
         let fetch_start = Instant::now();
 
         let latest_block = self.provider.block_hash_and_number().await?;
+        // Recorded on the span of the fetch, if the caller declared the field
+        Span::current().record("head", latest_block.block_number);
 
         // Track chain head for lag monitoring
         counter!("torii_fetcher_chain_head_block_number").absolute(latest_block.block_number);
 
         let range_start = Instant::now();
         // Fetch all events from 'from' to our blocks chunk size
-        let (range, cursors) = self.fetch_range(cursors, latest_block.clone()).await?;
+        let (range, cursors) = self
+            .fetch_range(cursors, latest_block.clone())
+            .instrument(info_span!(target: LOG_TARGET, "fetch_range"))
+            .await?;
         histogram!("torii_fetcher_range_duration_seconds")
             .record(range_start.elapsed().as_secs_f64());
         debug!(target: LOG_TARGET, duration = ?range_start.elapsed(), cursors = ?cursors, "Fetched data for range.");
@@ -76,6 +81,7 @@ impl<P: Provider + Send + Sync + Clone + std::fmt::Debug + 'static> Fetcher<P> {
             let preconfirmed_start = Instant::now();
             let preconfirmed_result = self
                 .fetch_preconfirmed_block(latest_block.block_number, &cursors)
+                .instrument(info_span!(target: LOG_TARGET, "fetch_preconfirmed_block"))
                 .await?;
             histogram!("torii_fetcher_preconfirmed_duration_seconds")
                 .record(preconfirmed_start.elapsed().as_secs_f64());
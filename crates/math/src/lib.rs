@@ -1,8 +1,11 @@
 use std::{
     cmp::Ordering,
+    fmt,
     ops::{Add, AddAssign, Sub, SubAssign},
+    str::FromStr,
 };
 
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use starknet::core::types::U256;
 
 #[derive(Debug, Clone, Copy)]
@@ -110,10 +113,109 @@ impl SubAssign for I256 {
     }
 }
 
+impl PartialEq for I256 {
+    fn eq(&self, other: &I256) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for I256 {}
+
+impl PartialOrd for I256 {
+    fn partial_cmp(&self, other: &I256) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for I256 {
+    fn cmp(&self, other: &I256) -> Ordering {
+        let zero = U256::from(0u8);
+        // Negative zero is zero
+        let self_negative = self.is_negative && self.value != zero;
+        let other_negative = other.is_negative && other.value != zero;
+
+        match (self_negative, other_negative) {
+            (false, false) => self.value.cmp(&other.value),
+            (true, true) => other.value.cmp(&self.value),
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+        }
+    }
+}
+
+impl fmt::Display for I256 {
+    /// Hexadecimal, with a leading `-` if negative.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{:#x}", self.value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid I256: {0}")]
+pub struct ParseI256Error(String);
+
+impl FromStr for I256 {
+    type Err = ParseI256Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (is_negative, hex) = match s.strip_prefix('-') {
+            Some(hex) => (true, hex),
+            None => (false, s),
+        };
+        let hex = hex.strip_prefix("0x").unwrap_or(hex);
+        if hex.is_empty() || hex.len() > 64 {
+            return Err(ParseI256Error(s.to_string()));
+        }
+
+        let hex = format!("{:0>64}", hex);
+        let word =
+            |hex: &str| u128::from_str_radix(hex, 16).map_err(|_| ParseI256Error(s.to_string()));
+        Ok(I256 {
+            value: U256::from_words(word(&hex[32..])?, word(&hex[..32])?),
+            is_negative,
+        })
+    }
+}
+
+impl Serialize for I256 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for I256 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_roundtrip() {
+        for value in [
+            I256::default(),
+            I256::from(U256::from(255u8)),
+            I256 {
+                value: U256::from_words(1, u128::MAX),
+                is_negative: true,
+            },
+        ] {
+            let parsed: I256 = value.to_string().parse().unwrap();
+            assert_eq!(parsed.value, value.value);
+            assert_eq!(parsed.is_negative, value.is_negative);
+        }
+
+        assert!("".parse::<I256>().is_err());
+        assert!("-0xzz".parse::<I256>().is_err());
+    }
+
     #[test]
     fn test_add_zero_false_and_zero_false() {
         // 0,false + 0,false == 0,false
@@ -414,4 +516,21 @@ mod tests {
         assert_eq!(result.value, U256::from(15u8));
         assert!(!result.is_negative);
     }
+
+    #[test]
+    fn test_ordering() {
+        let negative_five = I256 {
+            value: U256::from(5u8),
+            is_negative: true,
+        };
+        let negative_zero = I256 {
+            value: U256::from(0u8),
+            is_negative: true,
+        };
+
+        assert!(negative_five < I256::default());
+        assert!(I256::from(U256::from(10u8)) > I256::from(U256::from(5u8)));
+        assert!(negative_five > negative_five - I256::from(1u8));
+        assert_eq!(negative_zero, I256::default());
+    }
 }
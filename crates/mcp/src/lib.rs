@@ -1,3 +1,7 @@
 pub mod resources;
+pub mod subscriptions;
 pub mod tools;
 pub mod types;
+
+#[cfg(test)]
+mod test;
@@ -1,8 +1,268 @@
+use serde_json::{json, Value};
+use sqlx::{Row, SqlitePool};
+use starknet::core::types::Felt;
+use torii_proto::{Clause, Query};
+use torii_storage::{ReadOnlyStorage, StorageError};
+
+use crate::tools::entities::entity_to_json;
+use crate::tools::models::model_to_json;
+use crate::tools::{pagination, storage_error};
+use crate::types::{JsonRpcRequest, JsonRpcResponse};
+
+/// URI of the metadata of the worlds.
+pub const WORLD_METADATA_URI: &str = "torii://worlds/metadata";
+/// Prefix of the URIs of the models, `torii://models/<world address>/<tag>`.
+pub const MODELS_URI_PREFIX: &str = "torii://models/";
+/// URI of the entities matching a query, `torii://entities?world_address=<address>&clause=<clause>`
+/// with the clause JSON of `get_entities` percent-encoded. Both parameters are optional.
+pub const ENTITIES_URI: &str = "torii://entities";
+
 #[derive(Clone, Debug)]
 pub struct Resource {
+    pub uri: String,
     pub name: String,
+    pub description: String,
+    pub mime_type: String,
+}
+
+/// A resource, as identified by its URI.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ResourceUri {
+    WorldMetadata,
+    Model {
+        world_address: Felt,
+        tag: String,
+    },
+    Entities {
+        world_address: Option<Felt>,
+        clause: Option<Clause>,
+    },
+}
+
+impl ResourceUri {
+    /// Parses the URI of a resource, none if there is no such resource.
+    pub fn parse(uri: &str) -> Option<Self> {
+        if uri == WORLD_METADATA_URI {
+            return Some(Self::WorldMetadata);
+        }
+
+        if let Some(path) = uri.strip_prefix(MODELS_URI_PREFIX) {
+            let (world_address, tag) = path.split_once('/')?;
+            return Some(Self::Model {
+                world_address: Felt::from_hex(world_address).ok()?,
+                tag: tag.to_string(),
+            });
+        }
+
+        let params = match uri.strip_prefix(ENTITIES_URI)? {
+            "" => "",
+            path => path.strip_prefix('?')?,
+        };
+        let (mut world_address, mut clause) = (None, None);
+        for (key, value) in form_urlencoded::parse(params.as_bytes()) {
+            match key.as_ref() {
+                "world_address" => world_address = Some(Felt::from_hex(&value).ok()?),
+                "clause" => clause = Some(serde_json::from_str(&value).ok()?),
+                _ => return None,
+            }
+        }
+
+        Some(Self::Entities {
+            world_address,
+            clause,
+        })
+    }
+}
+
+/// The metadata of the worlds and the schema of each of their models.
+pub async fn get_resources(storage: &dyn ReadOnlyStorage) -> Result<Vec<Resource>, StorageError> {
+    let mut resources = vec![Resource {
+        uri: WORLD_METADATA_URI.to_string(),
+        name: "World metadata".to_string(),
+        description: "Name, description, socials and images of the indexed worlds".to_string(),
+        mime_type: "application/json".to_string(),
+    }];
+
+    for model in storage.models(&[], &[]).await? {
+        let tag = format!("{}-{}", model.namespace, model.name);
+        resources.push(Resource {
+            uri: format!("{}{:#x}/{}", MODELS_URI_PREFIX, model.world_address, tag),
+            description: format!(
+                "Schema of the model {} of the world {:#x}",
+                tag, model.world_address
+            ),
+            name: tag,
+            mime_type: "application/json".to_string(),
+        });
+    }
+
+    Ok(resources)
+}
+
+/// Reads the resource at `uri`, none if there is no such resource.
+pub async fn read(
+    storage: &dyn ReadOnlyStorage,
+    pool: &SqlitePool,
+    uri: &str,
+) -> Result<Option<Value>, StorageError> {
+    match ResourceUri::parse(uri) {
+        Some(ResourceUri::WorldMetadata) => Ok(Some(world_metadata(pool).await?)),
+        Some(ResourceUri::Model { world_address, tag }) => Ok(storage
+            .models(&[world_address], &[])
+            .await?
+            .iter()
+            .find(|model| format!("{}-{}", model.namespace, model.name) == tag)
+            .map(model_to_json)),
+        Some(ResourceUri::Entities {
+            world_address,
+            clause,
+        }) => {
+            let query = Query {
+                clause,
+                pagination: pagination(None, None),
+                world_addresses: world_address.into_iter().collect(),
+                ..Default::default()
+            };
+            let entities = storage
+                .entities(&query)
+                .await?
+                .items
+                .into_iter()
+                .map(entity_to_json)
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Some(json!(entities)))
+        }
+        None => Ok(None),
+    }
 }
 
-pub fn get_resources() -> Vec<Resource> {
-    vec![]
+/// The response to `resources/list`.
+pub async fn handle_list(storage: &dyn ReadOnlyStorage, id: Value) -> JsonRpcResponse {
+    let resources = match get_resources(storage).await {
+        Ok(resources) => resources,
+        Err(e) => return storage_error(id, e),
+    };
+
+    let resources_json: Vec<Value> = resources
+        .iter()
+        .map(|resource| {
+            json!({
+                "uri": resource.uri,
+                "name": resource.name,
+                "description": resource.description,
+                "mimeType": resource.mime_type,
+            })
+        })
+        .collect();
+
+    JsonRpcResponse::ok(id, json!({ "resources": resources_json }))
+}
+
+/// The response to `resources/read`.
+pub async fn handle_read(
+    storage: &dyn ReadOnlyStorage,
+    pool: &SqlitePool,
+    request: JsonRpcRequest,
+) -> JsonRpcResponse {
+    let Some(params) = &request.params else {
+        return JsonRpcResponse::invalid_params(request.id, "Missing params");
+    };
+
+    let Some(uri) = params.get("uri").and_then(Value::as_str) else {
+        return JsonRpcResponse::invalid_params(request.id, "Missing resource uri");
+    };
+
+    match read(storage, pool, uri).await {
+        Ok(Some(contents)) => JsonRpcResponse::ok(
+            request.id,
+            json!({
+                "contents": [{
+                    "uri": uri,
+                    "mimeType": "application/json",
+                    "text": serde_json::to_string_pretty(&contents).unwrap()
+                }]
+            }),
+        ),
+        Ok(None) => {
+            JsonRpcResponse::invalid_params(request.id, &format!("Resource not found: {}", uri))
+        }
+        Err(e) => storage_error(request.id, e),
+    }
+}
+
+async fn world_metadata(pool: &SqlitePool) -> Result<Value, sqlx::Error> {
+    let rows = sqlx::query("SELECT id, uri, json, icon_img, cover_img FROM metadata ORDER BY id")
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let metadata = row
+                .get::<Option<String>, _>("json")
+                .and_then(|json| serde_json::from_str::<Value>(&json).ok());
+            json!({
+                "world_address": row.get::<String, _>("id"),
+                "uri": row.get::<Option<String>, _>("uri"),
+                "metadata": metadata,
+                "icon_img": row.get::<Option<String>, _>("icon_img"),
+                "cover_img": row.get::<Option<String>, _>("cover_img"),
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use torii_proto::{KeysClause, PatternMatching};
+
+    use super::*;
+
+    #[test]
+    fn test_parse_uri() {
+        assert_eq!(
+            ResourceUri::parse(WORLD_METADATA_URI),
+            Some(ResourceUri::WorldMetadata)
+        );
+        assert_eq!(
+            ResourceUri::parse("torii://models/0x1/ns-Position"),
+            Some(ResourceUri::Model {
+                world_address: Felt::ONE,
+                tag: "ns-Position".to_string(),
+            })
+        );
+        assert_eq!(
+            ResourceUri::parse(ENTITIES_URI),
+            Some(ResourceUri::Entities {
+                world_address: None,
+                clause: None,
+            })
+        );
+
+        let clause = Clause::Keys(KeysClause {
+            keys: vec![Some(Felt::TWO), None],
+            pattern_matching: PatternMatching::FixedLen,
+            models: vec!["ns-Position".to_string()],
+        });
+        let uri = format!(
+            "{}?world_address=0x1&clause={}",
+            ENTITIES_URI,
+            form_urlencoded::byte_serialize(serde_json::to_string(&clause).unwrap().as_bytes())
+                .collect::<String>()
+        );
+        assert_eq!(
+            ResourceUri::parse(&uri),
+            Some(ResourceUri::Entities {
+                world_address: Some(Felt::ONE),
+                clause: Some(clause),
+            })
+        );
+
+        assert_eq!(ResourceUri::parse("torii://models/ns-Position"), None);
+        assert_eq!(
+            ResourceUri::parse(&format!("{}?clause=invalid", ENTITIES_URI)),
+            None
+        );
+        assert_eq!(ResourceUri::parse("torii://entitiesx"), None);
+    }
 }
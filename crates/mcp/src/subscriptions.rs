@@ -0,0 +1,187 @@
+//! Subscriptions of the clients to the resources, notified of their updates with
+//! `notifications/resources/updated`.
+//!
+//! An entity query is updated when one of the entities matching its clause is, and a model, being
+//! its schema, when it's upgraded.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+
+use dojo_types::schema::Ty;
+use futures_util::StreamExt;
+use tokio::sync::broadcast;
+use torii_broker::types::{EntityUpdate, ModelUpdate};
+use torii_broker::MemoryBroker;
+use torii_proto::matching::match_entity;
+use torii_proto::schema::EntityWithMetadata;
+use torii_proto::Model;
+
+use crate::resources::ResourceUri;
+use crate::types::{JsonRpcServerNotification, ServerMessage};
+
+/// The resources a session subscribed to, by URI.
+#[derive(Debug, Default)]
+pub struct ResourceSubscriptions {
+    resources: RwLock<HashMap<String, ResourceUri>>,
+    // Whether the updates are being notified to the session
+    notifying: AtomicBool,
+}
+
+impl ResourceSubscriptions {
+    /// Subscribes to the resource at `uri`. Returns false if it isn't a resource that's updated.
+    pub fn subscribe(&self, uri: &str) -> bool {
+        match ResourceUri::parse(uri) {
+            Some(resource @ (ResourceUri::Model { .. } | ResourceUri::Entities { .. })) => {
+                self.resources
+                    .write()
+                    .unwrap()
+                    .insert(uri.to_string(), resource);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Unsubscribes from the resource at `uri`. Returns whether it was subscribed to.
+    pub fn unsubscribe(&self, uri: &str) -> bool {
+        self.resources.write().unwrap().remove(uri).is_some()
+    }
+
+    /// The URIs of the subscribed entity queries updated by an update of `entity`.
+    pub fn updated_by_entity(&self, entity: &EntityWithMetadata) -> Vec<String> {
+        let updated_model = entity.entity.models.first();
+
+        self.resources
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(_, resource)| match resource {
+                ResourceUri::Entities {
+                    world_address,
+                    clause,
+                } => {
+                    world_address.is_none_or(|address| address == entity.entity.world_address)
+                        && clause.as_ref().is_none_or(|clause| {
+                            match_entity(
+                                entity.entity.hashed_keys,
+                                &entity.keys,
+                                &updated_model.map(|model| Ty::Struct(model.clone())),
+                                clause,
+                            )
+                        })
+                }
+                ResourceUri::Model { .. } | ResourceUri::WorldMetadata => false,
+            })
+            .map(|(uri, _)| uri.clone())
+            .collect()
+    }
+
+    /// The URIs of the subscribed resources updated by a registration or upgrade of `model`.
+    pub fn updated_by_model(&self, model: &Model) -> Vec<String> {
+        let model_tag = format!("{}-{}", model.namespace, model.name);
+
+        self.resources
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(_, resource)| {
+                matches!(resource, ResourceUri::Model { world_address, tag }
+                    if *world_address == model.world_address && *tag == model_tag)
+            })
+            .map(|(uri, _)| uri.clone())
+            .collect()
+    }
+
+    /// Notifies `tx` of the updates of the subscribed resources, until it has no receivers left,
+    /// the session having ended. Only the first call starts notifying, the subscriptions made
+    /// later are notified as well.
+    pub fn notify(self: &Arc<Self>, tx: broadcast::Sender<ServerMessage>) {
+        if self.notifying.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let subscriptions = self.clone();
+        tokio::spawn(async move {
+            let mut entities = Box::pin(MemoryBroker::<EntityUpdate>::subscribe());
+            let mut models = Box::pin(MemoryBroker::<ModelUpdate>::subscribe());
+
+            loop {
+                let uris = tokio::select! {
+                    _ = tx.closed() => break,
+                    Some(entity) = entities.next() => subscriptions.updated_by_entity(&entity),
+                    Some(model) = models.next() => subscriptions.updated_by_model(&model),
+                    else => break,
+                };
+
+                for uri in uris {
+                    let notification = JsonRpcServerNotification::resource_updated(&uri);
+                    if tx.send(ServerMessage::Notification(notification)).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dojo_types::primitive::Primitive;
+    use dojo_types::schema::{Member, Struct};
+    use starknet::core::types::Felt;
+    use torii_proto::schema::Entity;
+
+    use super::*;
+
+    fn position_update(world_address: Felt, x: u32) -> EntityWithMetadata {
+        EntityWithMetadata {
+            entity: Entity {
+                world_address,
+                hashed_keys: Felt::THREE,
+                models: vec![Struct {
+                    name: "ns-Position".to_string(),
+                    children: vec![Member {
+                        name: "x".to_string(),
+                        ty: Ty::Primitive(Primitive::U32(Some(x))),
+                        key: false,
+                    }],
+                }],
+                ..Default::default()
+            },
+            event_id: String::new(),
+            keys: vec![Felt::TWO],
+        }
+    }
+
+    #[test]
+    fn test_updated_by_entity() {
+        let subscriptions = ResourceSubscriptions::default();
+        let model_uri = "torii://models/0x1/ns-Position";
+        let clause_uri = "torii://entities?clause=%7B%22Member%22%3A%7B%22model%22%3A%22ns-\
+                          Position%22%2C%22member%22%3A%22x%22%2C%22operator%22%3A%22Gt%22%2C%\
+                          22value%22%3A%7B%22Primitive%22%3A%7B%22U32%22%3A10%7D%7D%7D%7D";
+        assert!(subscriptions.subscribe(model_uri));
+        assert!(subscriptions.subscribe(clause_uri));
+        assert!(!subscriptions.subscribe("torii://worlds/metadata"));
+
+        // The schema of the model is left unchanged by its entities
+        assert!(subscriptions
+            .updated_by_entity(&position_update(Felt::ONE, 5))
+            .is_empty());
+        assert_eq!(
+            subscriptions.updated_by_entity(&position_update(Felt::ONE, 20)),
+            vec![clause_uri.to_string()]
+        );
+        assert_eq!(
+            subscriptions.updated_by_entity(&position_update(Felt::TWO, 20)),
+            vec![clause_uri.to_string()]
+        );
+
+        assert!(subscriptions.unsubscribe(clause_uri));
+        assert!(!subscriptions.unsubscribe(clause_uri));
+        assert!(subscriptions
+            .updated_by_entity(&position_update(Felt::TWO, 20))
+            .is_empty());
+    }
+}
@@ -0,0 +1,338 @@
+//! Tests of the tools and the resources over the storage of an indexer.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use dojo_types::naming::compute_selector_from_names;
+use dojo_types::primitive::Primitive;
+use dojo_types::schema::{Member, Struct, Ty};
+use dojo_world::contracts::abigen::model::Layout;
+use serde_json::{json, Value};
+use sqlx::SqlitePool;
+use starknet::core::types::Felt;
+use starknet::providers::jsonrpc::HttpTransport;
+use starknet::providers::{JsonRpcClient, Url};
+use tokio::sync::broadcast;
+use torii_math::I256;
+use torii_proto::{BalanceId, ContractCursor, ContractDefinition, ContractType, TokenId};
+use torii_sqlite::executor::Executor;
+use torii_sqlite::{Sql, SqlConfig};
+use torii_storage::Storage;
+
+use crate::resources::{self, ENTITIES_URI, WORLD_METADATA_URI};
+use crate::tools::{entities, leaderboard, models, search, token_balances, transactions};
+use crate::types::{JsonRpcRequest, JsonRpcResponse};
+
+const WORLD: Felt = Felt::ONE;
+const GOLD: Felt = Felt::TWO;
+const ALICE: Felt = Felt::THREE;
+
+fn position(id: u32, x: u32) -> Ty {
+    Ty::Struct(Struct {
+        name: "ns-Position".to_string(),
+        children: vec![
+            Member {
+                name: "id".to_string(),
+                ty: Ty::Primitive(Primitive::U32(Some(id))),
+                key: true,
+            },
+            Member {
+                name: "x".to_string(),
+                ty: Ty::Primitive(Primitive::U32(Some(x))),
+                key: false,
+            },
+        ],
+    })
+}
+
+/// A storage with the model ns-Position of the entities 0x1 and 0x2, at x 5 and 20, and the
+/// balance of 5 GLD of Alice.
+async fn storage(pool: &SqlitePool) -> Sql {
+    let url: Url = "http://127.0.0.1:1".parse().unwrap();
+    let provider = Arc::new(JsonRpcClient::new(HttpTransport::new(url)));
+    let (shutdown_tx, _) = broadcast::channel(1);
+    let (mut executor, sender) = Executor::new(pool.clone(), shutdown_tx, provider)
+        .await
+        .unwrap();
+    tokio::spawn(async move {
+        executor.run().await.unwrap();
+    });
+
+    let db = Sql::new_with_config(
+        pool.clone(),
+        sender,
+        &[
+            ContractDefinition {
+                address: WORLD,
+                r#type: ContractType::WORLD,
+                starting_block: None,
+            },
+            ContractDefinition {
+                address: GOLD,
+                r#type: ContractType::ERC20,
+                starting_block: None,
+            },
+        ],
+        SqlConfig {
+            search_max_results: 10,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    let selector = compute_selector_from_names("ns", "Position");
+    db.register_model(
+        WORLD,
+        selector,
+        &position(0, 0),
+        &Layout::Fixed(vec![]),
+        Felt::ZERO,
+        Felt::ZERO,
+        0,
+        0,
+        0,
+        None,
+        None,
+        true,
+    )
+    .await
+    .unwrap();
+    for (id, x) in [(1u32, 5), (2, 20)] {
+        db.set_entity(
+            WORLD,
+            position(id, x),
+            &format!("0x0:{:#x}:0x0", id),
+            0,
+            Felt::from(id),
+            selector,
+            Some(vec![Felt::from(id)]),
+        )
+        .await
+        .unwrap();
+    }
+
+    db.register_token_contract(GOLD, "Gold".to_string(), "GLD".to_string(), 18, None)
+        .await
+        .unwrap();
+    db.apply_balances_diff(
+        HashMap::from([(
+            BalanceId {
+                account_address: ALICE,
+                token_id: TokenId::Contract(GOLD),
+            },
+            I256::from(5u8),
+        )]),
+        HashMap::from([(TokenId::Contract(GOLD), I256::from(5u8))]),
+        HashMap::from([(
+            GOLD,
+            ContractCursor {
+                contract_address: GOLD,
+                head: Some(1),
+                last_block_timestamp: Some(100),
+                last_pending_block_tx: None,
+            },
+        )]),
+    )
+    .await
+    .unwrap();
+    db.execute().await.unwrap();
+
+    db
+}
+
+fn request(method: &str, params: Value) -> JsonRpcRequest {
+    JsonRpcRequest {
+        jsonrpc: "2.0".to_string(),
+        id: json!(1),
+        method: method.to_string(),
+        params: Some(params),
+    }
+}
+
+/// A call of a tool with `arguments`.
+fn call(arguments: Value) -> JsonRpcRequest {
+    request("tools/call", json!({ "arguments": arguments }))
+}
+
+/// The JSON of the text result of a tool.
+fn result(response: JsonRpcResponse) -> Value {
+    assert!(response.error.is_none(), "{:?}", response.error);
+    let text = response.result.unwrap()["content"][0]["text"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    serde_json::from_str(&text).unwrap()
+}
+
+fn error_code(response: JsonRpcResponse) -> i32 {
+    response.error.unwrap().code
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_list_models(pool: SqlitePool) {
+    let db = storage(&pool).await;
+
+    let listed = result(models::handle(&db, request("tools/call", json!({}))).await);
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+    assert_eq!(listed[0]["tag"], "ns-Position");
+    assert_eq!(listed[0]["world_address"], "0x1");
+
+    let listed = result(models::handle(&db, call(json!({ "namespace": "other" }))).await);
+    assert_eq!(listed, json!([]));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_get_entities(pool: SqlitePool) {
+    let db = storage(&pool).await;
+
+    let first = result(entities::handle(&db, call(json!({ "limit": 1 }))).await);
+    assert_eq!(first["items"].as_array().unwrap().len(), 1);
+    let cursor = first["next_cursor"].as_str().unwrap();
+    let second = result(entities::handle(&db, call(json!({ "cursor": cursor }))).await);
+    assert_eq!(second["items"].as_array().unwrap().len(), 1);
+    assert_ne!(
+        first["items"][0]["hashed_keys"],
+        second["items"][0]["hashed_keys"]
+    );
+
+    let clause = json!({
+        "Member": {
+            "model": "ns-Position",
+            "member": "x",
+            "operator": "Gt",
+            "value": { "Primitive": { "U32": 10 } }
+        }
+    });
+    let matched = result(entities::handle(&db, call(json!({ "clause": clause }))).await);
+    assert_eq!(matched["items"].as_array().unwrap().len(), 1);
+    assert_eq!(matched["items"][0]["hashed_keys"], "0x2");
+    assert!(matched["items"][0]["models"].get("ns-Position").is_some());
+
+    let invalid = entities::handle(&db, call(json!({ "limit": "ten" }))).await;
+    assert_eq!(error_code(invalid), -32602);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_get_token_balances(pool: SqlitePool) {
+    let db = storage(&pool).await;
+
+    let balances =
+        result(token_balances::handle(&db, call(json!({ "account_addresses": ["0x3"] }))).await);
+    assert_eq!(balances["items"].as_array().unwrap().len(), 1);
+    assert_eq!(balances["items"][0]["contract_address"], "0x2");
+
+    let balances =
+        result(token_balances::handle(&db, call(json!({ "account_addresses": ["0x4"] }))).await);
+    assert_eq!(balances["items"], json!([]));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_get_leaderboard(pool: SqlitePool) {
+    let db = storage(&pool).await;
+
+    let missing = leaderboard::handle(&db, call(json!({}))).await;
+    assert_eq!(error_code(missing), -32602);
+
+    let entries =
+        result(leaderboard::handle(&db, call(json!({ "aggregator_id": "unknown" }))).await);
+    assert_eq!(entries["items"], json!([]));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_search(pool: SqlitePool) {
+    let db = storage(&pool).await;
+
+    let missing = search::handle(&db, call(json!({}))).await;
+    assert_eq!(error_code(missing), -32602);
+
+    let found = result(search::handle(&db, call(json!({ "query": "Gold" }))).await);
+    assert_eq!(found["total"], 1);
+    assert_eq!(
+        found["results"][0]["matches"][0]["fields"]["primary_text"],
+        "Gold GLD"
+    );
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_get_transactions(pool: SqlitePool) {
+    let db = storage(&pool).await;
+
+    let page = result(transactions::handle(&db, call(json!({ "from_block": 1 }))).await);
+    assert_eq!(page["items"], json!([]));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_get_resources(pool: SqlitePool) {
+    let db = storage(&pool).await;
+
+    let uris = resources::get_resources(&db)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|resource| resource.uri)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        uris,
+        vec![
+            WORLD_METADATA_URI.to_string(),
+            "torii://models/0x1/ns-Position".to_string()
+        ]
+    );
+
+    let listed = resources::handle_list(&db, json!(1)).await.result.unwrap();
+    assert_eq!(listed["resources"][1]["name"], "ns-Position");
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_read(pool: SqlitePool) {
+    let db = storage(&pool).await;
+    sqlx::query(
+        "INSERT INTO metadata (id, uri, executed_at, json) VALUES ('0x1', 'ipfs://world', \
+         CURRENT_TIMESTAMP, '{\"name\":\"World\"}')",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let read = |uri| resources::read(&db, &pool, uri);
+
+    let worlds = read(WORLD_METADATA_URI).await.unwrap().unwrap();
+    assert_eq!(worlds[0]["world_address"], "0x1");
+    assert_eq!(worlds[0]["metadata"]["name"], "World");
+
+    let model = read("torii://models/0x1/ns-Position")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(model["name"], "Position");
+    assert!(read("torii://models/0x1/ns-Unknown")
+        .await
+        .unwrap()
+        .is_none());
+
+    let all = read(ENTITIES_URI).await.unwrap().unwrap();
+    assert_eq!(all.as_array().unwrap().len(), 2);
+    let uri = format!("{}?world_address=0x5", ENTITIES_URI);
+    assert_eq!(read(&uri).await.unwrap().unwrap(), json!([]));
+
+    assert!(read("torii://unknown").await.unwrap().is_none());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_handle_read(pool: SqlitePool) {
+    let db = storage(&pool).await;
+    let read = |params| resources::handle_read(&db, &pool, request("resources/read", params));
+
+    let response = read(json!({ "uri": "torii://models/0x1/ns-Position" })).await;
+    let contents = &response.result.unwrap()["contents"][0];
+    assert_eq!(contents["uri"], "torii://models/0x1/ns-Position");
+    let model: Value = serde_json::from_str(contents["text"].as_str().unwrap()).unwrap();
+    assert_eq!(model["tag"], "ns-Position");
+
+    assert_eq!(error_code(read(json!({})).await), -32602);
+    assert_eq!(
+        error_code(read(json!({ "uri": "torii://unknown" })).await),
+        -32602
+    );
+}
@@ -0,0 +1,157 @@
+use dojo_types::primitive::PrimitiveError;
+use dojo_types::schema::Ty;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use starknet::core::types::Felt;
+use torii_proto::schema::Entity;
+use torii_proto::{Clause, Query};
+use torii_storage::ReadOnlyStorage;
+
+use super::{arguments, pagination, storage_error, text_response, Tool};
+use crate::types::{JsonRpcRequest, JsonRpcResponse};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Arguments {
+    clause: Option<Clause>,
+    models: Vec<String>,
+    world_addresses: Vec<Felt>,
+    historical: bool,
+    limit: Option<u32>,
+    cursor: Option<String>,
+}
+
+pub fn get_tool() -> Tool {
+    Tool {
+        name: "get_entities".to_string(),
+        description: "Retrieve entities and the values of their models, filtered by a clause. \
+                      Prefer it to SQL, the model tables having mangled names. Clauses are \
+                      externally tagged: {\"Keys\": {\"keys\": [\"0x1\", null], \
+                      \"pattern_matching\": \"FixedLen\", \"models\": [\"ns-Position\"]}}, \
+                      {\"Member\": {\"model\": \"ns-Position\", \"member\": \"x\", \"operator\": \
+                      \"Gt\", \"value\": {\"Primitive\": {\"U32\": 10}}}}, {\"HashedKeys\": \
+                      [\"0x...\"]} or {\"Composite\": {\"operator\": \"And\", \"clauses\": \
+                      [...]}}. Use list_models for the models and their members."
+            .to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "clause": {
+                    "type": "object",
+                    "description": "Optional clause the entities match. If omitted, returns all the entities."
+                },
+                "models": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Optional tags (namespace-name) of the models to retrieve, the entities without any of them are excluded."
+                },
+                "world_addresses": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Optional addresses of the worlds of the entities."
+                },
+                "historical": {
+                    "type": "boolean",
+                    "description": "Whether to retrieve the past states of the entities."
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum number of entities, 100 by default."
+                },
+                "cursor": {
+                    "type": "string",
+                    "description": "The next_cursor of a previous call, to retrieve the next page."
+                }
+            }
+        }),
+    }
+}
+
+pub async fn handle(storage: &dyn ReadOnlyStorage, request: JsonRpcRequest) -> JsonRpcResponse {
+    let args = match arguments::<Arguments>(&request) {
+        Ok(args) => args,
+        Err(response) => return response,
+    };
+
+    let query = Query {
+        clause: args.clause,
+        pagination: pagination(args.limit, args.cursor),
+        no_hashed_keys: false,
+        models: args.models,
+        historical: args.historical,
+        world_addresses: args.world_addresses,
+    };
+
+    let page = match storage.entities(&query).await {
+        Ok(page) => page,
+        Err(e) => return storage_error(request.id, e),
+    };
+
+    let items = match page
+        .items
+        .into_iter()
+        .map(entity_to_json)
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(items) => items,
+        Err(e) => return storage_error(request.id, e.into()),
+    };
+
+    text_response(
+        request.id,
+        &json!({ "items": items, "next_cursor": page.next_cursor }),
+    )
+}
+
+/// An entity with the values of its models by tag, as they are stored.
+pub(crate) fn entity_to_json(entity: Entity) -> Result<Value, PrimitiveError> {
+    let mut models = Map::new();
+    for model in entity.models {
+        let name = model.name.clone();
+        models.insert(name, Ty::Struct(model).to_json_value()?);
+    }
+
+    Ok(json!({
+        "world_address": format!("{:#x}", entity.world_address),
+        "hashed_keys": format!("{:#x}", entity.hashed_keys),
+        "models": models,
+        "updated_at": entity.updated_at.to_rfc3339(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use torii_proto::{ComparisonOperator, MemberClause, MemberValue};
+
+    use super::*;
+
+    #[test]
+    fn test_arguments_with_clause() {
+        let args: Arguments = serde_json::from_value(json!({
+            "clause": {
+                "Member": {
+                    "model": "ns-Position",
+                    "member": "x",
+                    "operator": "Gt",
+                    "value": { "String": "10" }
+                }
+            },
+            "world_addresses": ["0x1"],
+            "limit": 5
+        }))
+        .unwrap();
+
+        assert_eq!(
+            args.clause,
+            Some(Clause::Member(MemberClause {
+                model: "ns-Position".to_string(),
+                member: "x".to_string(),
+                operator: ComparisonOperator::Gt,
+                value: MemberValue::String("10".to_string()),
+            }))
+        );
+        assert_eq!(args.world_addresses, vec![Felt::ONE]);
+        assert_eq!(pagination(args.limit, args.cursor).limit, Some(5));
+        assert!(args.models.is_empty());
+    }
+}
@@ -0,0 +1,70 @@
+use serde::Deserialize;
+use serde_json::json;
+use torii_proto::AggregationQuery;
+use torii_storage::ReadOnlyStorage;
+
+use super::{arguments, pagination, storage_error, text_response, Tool};
+use crate::types::{JsonRpcRequest, JsonRpcResponse};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Arguments {
+    aggregator_id: Option<String>,
+    entity_ids: Vec<String>,
+    limit: Option<u32>,
+    cursor: Option<String>,
+}
+
+pub fn get_tool() -> Tool {
+    Tool {
+        name: "get_leaderboard".to_string(),
+        description: "Retrieve the entries of a leaderboard (aggregation), ranked by value with \
+                      their position"
+            .to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "aggregator_id": {
+                    "type": "string",
+                    "description": "Id of the aggregator of the leaderboard."
+                },
+                "entity_ids": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Optional ids of the ranked entities, e.g. players, to retrieve their positions."
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum number of entries, 100 by default."
+                },
+                "cursor": {
+                    "type": "string",
+                    "description": "The next_cursor of a previous call, to retrieve the next page."
+                }
+            },
+            "required": ["aggregator_id"]
+        }),
+    }
+}
+
+pub async fn handle(storage: &dyn ReadOnlyStorage, request: JsonRpcRequest) -> JsonRpcResponse {
+    let args = match arguments::<Arguments>(&request) {
+        Ok(args) => args,
+        Err(response) => return response,
+    };
+
+    let Some(aggregator_id) = args.aggregator_id else {
+        return JsonRpcResponse::invalid_params(request.id, "Missing aggregator_id parameter");
+    };
+
+    let query = AggregationQuery {
+        aggregator_ids: vec![aggregator_id],
+        entity_ids: args.entity_ids,
+        pagination: pagination(args.limit, args.cursor),
+    };
+
+    match storage.aggregations(&query).await {
+        Ok(page) => text_response(request.id, &json!(page)),
+        Err(e) => storage_error(request.id, e),
+    }
+}
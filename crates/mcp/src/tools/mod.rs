@@ -1,7 +1,23 @@
-use serde_json::Value;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use torii_proto::Pagination;
+use torii_storage::StorageError;
 
+use crate::types::{JsonRpcRequest, JsonRpcResponse};
+
+pub mod entities;
+pub mod leaderboard;
+pub mod models;
 pub mod query;
 pub mod schema;
+pub mod search;
+pub mod token_balances;
+pub mod transactions;
+
+/// Items returned by the paginated tools when no limit is given.
+pub const DEFAULT_LIMIT: u32 = 100;
+/// Maximum items returned by the paginated tools.
+pub const MAX_LIMIT: u32 = 1000;
 
 #[derive(Clone, Debug)]
 pub struct Tool {
@@ -11,5 +27,60 @@ pub struct Tool {
 }
 
 pub fn get_tools() -> Vec<Tool> {
-    vec![query::get_tool(), schema::get_tool()]
+    vec![
+        query::get_tool(),
+        schema::get_tool(),
+        entities::get_tool(),
+        models::get_tool(),
+        token_balances::get_tool(),
+        leaderboard::get_tool(),
+        search::get_tool(),
+        transactions::get_tool(),
+    ]
+}
+
+/// Deserializes the arguments of a tool call, none being the default arguments.
+pub(crate) fn arguments<T: DeserializeOwned + Default>(
+    request: &JsonRpcRequest,
+) -> Result<T, JsonRpcResponse> {
+    match request
+        .params
+        .as_ref()
+        .and_then(|params| params.get("arguments"))
+    {
+        Some(arguments) => serde_json::from_value(arguments.clone())
+            .map_err(|e| JsonRpcResponse::invalid_params(request.id.clone(), &e.to_string())),
+        None => Ok(T::default()),
+    }
+}
+
+/// Forward pagination of the tools, `limit` being capped to [`MAX_LIMIT`].
+pub(crate) fn pagination(limit: Option<u32>, cursor: Option<String>) -> Pagination {
+    Pagination {
+        cursor,
+        limit: Some(limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)),
+        ..Default::default()
+    }
+}
+
+/// The result of a tool call, as JSON text.
+pub(crate) fn text_response(id: Value, result: &Value) -> JsonRpcResponse {
+    JsonRpcResponse::ok(
+        id,
+        json!({
+            "content": [{
+                "type": "text",
+                "text": serde_json::to_string(result).unwrap()
+            }]
+        }),
+    )
+}
+
+pub(crate) fn storage_error(id: Value, error: StorageError) -> JsonRpcResponse {
+    JsonRpcResponse::error(
+        id,
+        -32603,
+        "Storage error",
+        Some(json!({ "details": error.to_string() })),
+    )
 }
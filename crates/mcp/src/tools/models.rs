@@ -0,0 +1,74 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+use starknet::core::types::Felt;
+use torii_proto::Model;
+use torii_storage::ReadOnlyStorage;
+
+use super::{arguments, storage_error, text_response, Tool};
+use crate::types::{JsonRpcRequest, JsonRpcResponse};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Arguments {
+    world_addresses: Vec<Felt>,
+    namespace: Option<String>,
+}
+
+pub fn get_tool() -> Tool {
+    Tool {
+        name: "list_models".to_string(),
+        description: "List the models of the worlds with their schemas, the members of a model \
+                      being the ones get_entities filters on"
+            .to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "world_addresses": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Optional addresses of the worlds. If omitted, lists the models of all the worlds."
+                },
+                "namespace": {
+                    "type": "string",
+                    "description": "Optional namespace of the models."
+                }
+            }
+        }),
+    }
+}
+
+pub async fn handle(storage: &dyn ReadOnlyStorage, request: JsonRpcRequest) -> JsonRpcResponse {
+    let args = match arguments::<Arguments>(&request) {
+        Ok(args) => args,
+        Err(response) => return response,
+    };
+
+    let models = match storage.models(&args.world_addresses, &[]).await {
+        Ok(models) => models,
+        Err(e) => return storage_error(request.id, e),
+    };
+
+    let models = models
+        .iter()
+        .filter(|model| {
+            args.namespace
+                .as_ref()
+                .is_none_or(|namespace| &model.namespace == namespace)
+        })
+        .map(model_to_json)
+        .collect::<Vec<_>>();
+
+    text_response(request.id, &json!(models))
+}
+
+/// A model with its schema, as listed by the tool and read from its resource.
+pub fn model_to_json(model: &Model) -> Value {
+    json!({
+        "tag": format!("{}-{}", model.namespace, model.name),
+        "namespace": model.namespace,
+        "name": model.name,
+        "world_address": format!("{:#x}", model.world_address),
+        "selector": format!("{:#x}", model.selector),
+        "schema": model.schema,
+    })
+}
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use serde_json::{json, Value};
-use sqlx::SqlitePool;
+use torii_sqlite::sandbox::SqlSandbox;
 use torii_sqlite::utils::map_row_to_json;
 
 use super::Tool;
@@ -10,7 +10,7 @@ use crate::types::{JsonRpcError, JsonRpcRequest, JsonRpcResponse, JSONRPC_VERSIO
 pub fn get_tool() -> Tool {
     Tool {
         name: "query".to_string(),
-        description: "Execute a SQL query on the database".to_string(),
+        description: "Execute a read-only SQL query on the database".to_string(),
         input_schema: json!({
             "type": "object",
             "properties": {
@@ -24,14 +24,18 @@ pub fn get_tool() -> Tool {
     }
 }
 
-pub async fn handle(pool: Arc<SqlitePool>, request: JsonRpcRequest) -> JsonRpcResponse {
+pub async fn handle(
+    sandbox: Arc<SqlSandbox>,
+    client: &str,
+    request: JsonRpcRequest,
+) -> JsonRpcResponse {
     let Some(params) = request.params else {
         return JsonRpcResponse::invalid_params(request.id, "Missing params");
     };
 
     let args = params.get("arguments").and_then(Value::as_object);
     if let Some(query) = args.and_then(|args| args.get("query").and_then(Value::as_str)) {
-        match sqlx::query(query).fetch_all(&*pool).await {
+        match sandbox.execute(client, query).await {
             Ok(rows) => {
                 // Convert rows to JSON using shared mapping function
                 let result = rows.iter().map(map_row_to_json).collect::<Vec<_>>();
@@ -55,7 +59,7 @@ pub async fn handle(pool: Arc<SqlitePool>, request: JsonRpcRequest) -> JsonRpcRe
                 error: Some(JsonRpcError {
                     code: -32603,
                     message: "Database error".to_string(),
-                    data: Some(json!({ "details": e.to_string() })),
+                    data: Some(json!({ "code": e.code(), "details": e.to_string() })),
                 }),
             },
         }
@@ -0,0 +1,64 @@
+use serde::Deserialize;
+use serde_json::json;
+use torii_proto::SearchQuery;
+use torii_storage::ReadOnlyStorage;
+
+use super::{arguments, storage_error, text_response, Tool, MAX_LIMIT};
+use crate::types::{JsonRpcRequest, JsonRpcResponse};
+
+/// Matches returned per table when no limit is given.
+const DEFAULT_SEARCH_LIMIT: u32 = 10;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Arguments {
+    query: Option<String>,
+    limit: Option<u32>,
+}
+
+pub fn get_tool() -> Tool {
+    Tool {
+        name: "search".to_string(),
+        description: "Full-text search across achievements, controllers, token attributes, \
+                      entities and the other indexed tables"
+            .to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "Text to search for."
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum number of matches per table, 10 by default."
+                }
+            },
+            "required": ["query"]
+        }),
+    }
+}
+
+pub async fn handle(storage: &dyn ReadOnlyStorage, request: JsonRpcRequest) -> JsonRpcResponse {
+    let args = match arguments::<Arguments>(&request) {
+        Ok(args) => args,
+        Err(response) => return response,
+    };
+
+    let Some(query) = args.query else {
+        return JsonRpcResponse::invalid_params(request.id, "Missing query parameter");
+    };
+
+    let query = SearchQuery {
+        query,
+        limit: args
+            .limit
+            .unwrap_or(DEFAULT_SEARCH_LIMIT)
+            .clamp(1, MAX_LIMIT),
+    };
+
+    match storage.search(&query).await {
+        Ok(response) => text_response(request.id, &json!(response)),
+        Err(e) => storage_error(request.id, e),
+    }
+}
@@ -0,0 +1,72 @@
+use serde::Deserialize;
+use serde_json::json;
+use starknet::core::types::{Felt, U256};
+use torii_proto::TokenBalanceQuery;
+use torii_storage::ReadOnlyStorage;
+
+use super::{arguments, pagination, storage_error, text_response, Tool};
+use crate::types::{JsonRpcRequest, JsonRpcResponse};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Arguments {
+    account_addresses: Vec<Felt>,
+    contract_addresses: Vec<Felt>,
+    token_ids: Vec<Felt>,
+    limit: Option<u32>,
+    cursor: Option<String>,
+}
+
+pub fn get_tool() -> Tool {
+    Tool {
+        name: "get_token_balances".to_string(),
+        description: "Retrieve the ERC20, ERC721 and ERC1155 balances of accounts".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "account_addresses": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Optional addresses of the accounts holding the tokens."
+                },
+                "contract_addresses": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Optional addresses of the token contracts."
+                },
+                "token_ids": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Optional ids of the NFTs, as hex strings."
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum number of balances, 100 by default."
+                },
+                "cursor": {
+                    "type": "string",
+                    "description": "The next_cursor of a previous call, to retrieve the next page."
+                }
+            }
+        }),
+    }
+}
+
+pub async fn handle(storage: &dyn ReadOnlyStorage, request: JsonRpcRequest) -> JsonRpcResponse {
+    let args = match arguments::<Arguments>(&request) {
+        Ok(args) => args,
+        Err(response) => return response,
+    };
+
+    let query = TokenBalanceQuery {
+        account_addresses: args.account_addresses,
+        contract_addresses: args.contract_addresses,
+        token_ids: args.token_ids.into_iter().map(U256::from).collect(),
+        pagination: pagination(args.limit, args.cursor),
+    };
+
+    match storage.token_balances(&query).await {
+        Ok(page) => text_response(request.id, &json!(page)),
+        Err(e) => storage_error(request.id, e),
+    }
+}
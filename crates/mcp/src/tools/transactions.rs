@@ -0,0 +1,100 @@
+use serde::Deserialize;
+use serde_json::json;
+use starknet::core::types::Felt;
+use torii_proto::{TransactionFilter, TransactionQuery};
+use torii_storage::ReadOnlyStorage;
+
+use super::{arguments, pagination, storage_error, text_response, Tool};
+use crate::types::{JsonRpcRequest, JsonRpcResponse};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Arguments {
+    transaction_hashes: Vec<Felt>,
+    caller_addresses: Vec<Felt>,
+    contract_addresses: Vec<Felt>,
+    entrypoints: Vec<String>,
+    model_selectors: Vec<Felt>,
+    from_block: Option<u64>,
+    to_block: Option<u64>,
+    limit: Option<u32>,
+    cursor: Option<String>,
+}
+
+pub fn get_tool() -> Tool {
+    Tool {
+        name: "get_transactions".to_string(),
+        description: "Retrieve the indexed transactions with their calls, latest first".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "transaction_hashes": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Optional hashes of the transactions."
+                },
+                "caller_addresses": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Optional addresses of the callers of the calls."
+                },
+                "contract_addresses": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Optional addresses of the called contracts."
+                },
+                "entrypoints": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Optional names of the called functions."
+                },
+                "model_selectors": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Optional selectors of the models the transactions updated."
+                },
+                "from_block": {
+                    "type": "integer",
+                    "description": "Optional first block of the transactions."
+                },
+                "to_block": {
+                    "type": "integer",
+                    "description": "Optional last block of the transactions."
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum number of transactions, 100 by default."
+                },
+                "cursor": {
+                    "type": "string",
+                    "description": "The next_cursor of a previous call, to retrieve the next page."
+                }
+            }
+        }),
+    }
+}
+
+pub async fn handle(storage: &dyn ReadOnlyStorage, request: JsonRpcRequest) -> JsonRpcResponse {
+    let args = match arguments::<Arguments>(&request) {
+        Ok(args) => args,
+        Err(response) => return response,
+    };
+
+    let query = TransactionQuery {
+        filter: Some(TransactionFilter {
+            transaction_hashes: args.transaction_hashes,
+            caller_addresses: args.caller_addresses,
+            contract_addresses: args.contract_addresses,
+            entrypoints: args.entrypoints,
+            model_selectors: args.model_selectors,
+            from_block: args.from_block,
+            to_block: args.to_block,
+        }),
+        pagination: pagination(args.limit, args.cursor),
+    };
+
+    match storage.transactions(&query).await {
+        Ok(page) => text_response(request.id, &json!(page)),
+        Err(e) => storage_error(request.id, e),
+    }
+}
@@ -1,7 +1,11 @@
+use std::sync::Arc;
+
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use tokio::sync::broadcast;
 
+use crate::subscriptions::ResourceSubscriptions;
+
 // Constants
 pub const JSONRPC_VERSION: &str = "2.0";
 pub const MCP_VERSION: &str = "2024-11-05";
@@ -47,6 +51,33 @@ pub struct JsonRpcError {
     pub data: Option<Value>,
 }
 
+/// A notification sent by the server, e.g. of an update of a subscribed resource.
+#[derive(Debug, Serialize, Clone)]
+pub struct JsonRpcServerNotification {
+    pub jsonrpc: String,
+    pub method: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+}
+
+impl JsonRpcServerNotification {
+    pub fn resource_updated(uri: &str) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            method: "notifications/resources/updated".to_string(),
+            params: Some(json!({ "uri": uri })),
+        }
+    }
+}
+
+/// A message sent by the server to a client.
+#[derive(Debug, Serialize, Clone)]
+#[serde(untagged)]
+pub enum ServerMessage {
+    Response(JsonRpcResponse),
+    Notification(JsonRpcServerNotification),
+}
+
 #[derive(Debug, Serialize)]
 pub struct Implementation {
     pub name: String,
@@ -70,11 +101,12 @@ pub struct ResourceCapabilities {
     pub list_changed: bool,
 }
 
-// Structure to hold SSE session information
+// Structure to hold the information of a session, over SSE or WebSocket
 #[derive(Clone, Debug)]
-pub struct SseSession {
-    pub tx: broadcast::Sender<JsonRpcResponse>,
+pub struct Session {
+    pub tx: broadcast::Sender<ServerMessage>,
     pub _session_id: String,
+    pub subscriptions: Arc<ResourceSubscriptions>,
 }
 
 impl JsonRpcResponse {
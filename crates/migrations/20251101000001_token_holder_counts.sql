@@ -0,0 +1,106 @@
+-- Holder counts per token contract, maintained incrementally when balance diffs are applied.
+-- An account is a holder if it has a non-zero balance for any token ID of the contract.
+CREATE TABLE IF NOT EXISTS token_holder_counts (
+    contract_address TEXT NOT NULL PRIMARY KEY,
+    holder_count INTEGER NOT NULL DEFAULT 0,
+    -- Sum of the holdings, as a zero-padded hex like the balances
+    total_held TEXT NOT NULL DEFAULT '0x0000000000000000000000000000000000000000000000000000000000000000',
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Holdings of the holders of a token contract, their balances summed across its token IDs
+CREATE TABLE IF NOT EXISTS token_holders (
+    contract_address TEXT NOT NULL,
+    account_address TEXT NOT NULL,
+    balance TEXT NOT NULL,
+    -- Lossy balance, for the distribution statistics
+    amount REAL NOT NULL,
+    -- Number of token IDs with a non-zero balance
+    token_count INTEGER NOT NULL,
+    PRIMARY KEY (contract_address, account_address)
+);
+
+-- Balances are zero-padded hex, so lexicographic ordering = numerical ordering
+CREATE INDEX IF NOT EXISTS idx_token_holders_contract_balance
+ON token_holders (contract_address, balance DESC);
+
+-- Holder count snapshots, one per contract and block timestamp
+CREATE TABLE IF NOT EXISTS token_holder_count_history (
+    contract_address TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    holder_count INTEGER NOT NULL,
+    PRIMARY KEY (contract_address, timestamp)
+);
+
+-- Backfill the holdings from the existing balances. The hex balances are summed exactly by
+-- splitting them into 32-bit chunks, summed per holder then carried from the lowest one.
+CREATE TEMP TABLE held_chunks AS
+WITH RECURSIVE positions(i) AS (
+    SELECT 1 UNION ALL SELECT i + 1 FROM positions WHERE i < 64
+),
+balances AS (
+    SELECT contract_address, account_address,
+        SUBSTR('0000000000000000000000000000000000000000000000000000000000000000' || SUBSTR(balance, 3), -64) AS digits
+    FROM token_balances
+    WHERE LTRIM(SUBSTR(balance, 3), '0') != ''
+)
+SELECT contract_address, account_address, (i - 1) / 8 AS chunk,
+    SUM((INSTR('0123456789abcdef', LOWER(SUBSTR(digits, i, 1))) - 1) << (4 * (7 - (i - 1) % 8))) AS value,
+    COUNT(*) / 8 AS token_count
+FROM balances, positions
+GROUP BY contract_address, account_address, chunk;
+
+INSERT INTO token_holders (contract_address, account_address, balance, amount, token_count)
+WITH RECURSIVE carried(contract_address, account_address, chunk, carry, digits, amount, weight) AS (
+    SELECT DISTINCT contract_address, account_address, 8, 0, '', 0.0, 1.0 FROM held_chunks
+    UNION ALL
+    SELECT c.contract_address, c.account_address, c.chunk - 1,
+        (h.value + c.carry) >> 32,
+        printf('%08x', (h.value + c.carry) & 4294967295) || c.digits,
+        c.amount + ((h.value + c.carry) & 4294967295) * c.weight,
+        c.weight * 4294967296.0
+    FROM carried c
+    JOIN held_chunks h ON h.contract_address = c.contract_address
+        AND h.account_address = c.account_address AND h.chunk = c.chunk - 1
+    WHERE c.chunk > 0
+)
+SELECT c.contract_address, c.account_address, '0x' || c.digits, c.amount, h.token_count
+FROM carried c
+JOIN held_chunks h ON h.contract_address = c.contract_address
+    AND h.account_address = c.account_address AND h.chunk = 0
+WHERE c.chunk = 0;
+
+DROP TABLE held_chunks;
+
+-- Then the totals of the contracts from their holdings, the same way
+CREATE TEMP TABLE held_chunks AS
+WITH RECURSIVE positions(i) AS (
+    SELECT 1 UNION ALL SELECT i + 1 FROM positions WHERE i < 64
+)
+SELECT contract_address, (i - 1) / 8 AS chunk,
+    SUM((INSTR('0123456789abcdef', SUBSTR(balance, i + 2, 1)) - 1) << (4 * (7 - (i - 1) % 8))) AS value,
+    COUNT(*) / 8 AS holder_count
+FROM token_holders, positions
+GROUP BY contract_address, chunk;
+
+INSERT INTO token_holder_counts (contract_address, holder_count, total_held)
+WITH RECURSIVE carried(contract_address, chunk, carry, digits) AS (
+    SELECT DISTINCT contract_address, 8, 0, '' FROM held_chunks
+    UNION ALL
+    SELECT c.contract_address, c.chunk - 1,
+        (h.value + c.carry) >> 32,
+        printf('%08x', (h.value + c.carry) & 4294967295) || c.digits
+    FROM carried c
+    JOIN held_chunks h ON h.contract_address = c.contract_address AND h.chunk = c.chunk - 1
+    WHERE c.chunk > 0
+)
+SELECT c.contract_address, h.holder_count, '0x' || c.digits
+FROM carried c
+JOIN held_chunks h ON h.contract_address = c.contract_address AND h.chunk = 0
+WHERE c.chunk = 0;
+
+DROP TABLE held_chunks;
+
+INSERT INTO token_holder_count_history (contract_address, timestamp, holder_count)
+SELECT contract_address, CAST(strftime('%s', 'now') AS INTEGER), holder_count
+FROM token_holder_counts;
@@ -0,0 +1,22 @@
+-- Metadata refresh jobs, one per token or token contract.
+-- `id` matches the id of the token in the tokens table.
+CREATE TABLE IF NOT EXISTS token_metadata_jobs (
+    id TEXT NOT NULL PRIMARY KEY,
+    contract_address TEXT NOT NULL,
+    token_id TEXT,
+    -- pending, ok or failed
+    status TEXT NOT NULL DEFAULT 'pending',
+    -- Number of consecutive failed attempts
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    -- Unix timestamp at which a failed job is retried, NULL once it gave up
+    retry_at INTEGER,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_token_metadata_jobs_status_retry_at
+ON token_metadata_jobs (status, retry_at);
+
+CREATE INDEX IF NOT EXISTS idx_token_metadata_jobs_contract_address
+ON token_metadata_jobs (contract_address, status);
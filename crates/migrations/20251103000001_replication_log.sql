@@ -0,0 +1,14 @@
+-- Change log of a primary, one row per committed batch of executor queries, for followers to
+-- replay. Only written with `--replication.log`.
+CREATE TABLE IF NOT EXISTS replication_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    -- JSON array of the queries of the batch, in order
+    queries TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Last batch of the primary applied by a follower.
+CREATE TABLE IF NOT EXISTS replication_state (
+    id INTEGER PRIMARY KEY CHECK (id = 0),
+    applied_id INTEGER NOT NULL
+);
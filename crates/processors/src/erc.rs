@@ -1,7 +1,8 @@
-use std::{str::FromStr, sync::Arc, time::Duration};
+use std::{sync::Arc, time::Duration};
 
+use base64::{engine::general_purpose, Engine as _};
 use cainome_cairo_serde::{ByteArray, CairoSerde};
-use data_url::{mime::Mime, DataUrl};
+use chrono::{DateTime, Utc};
 use starknet::{
     core::{
         types::{requests::CallRequest, BlockId, BlockTag, FunctionCall, StarknetError, U256},
@@ -17,15 +18,20 @@ use torii_storage::Storage;
 use tracing::{debug, warn};
 
 use crate::{
-    error::{Error, ParseError, TokenMetadataError},
-    fetch::{fetch_content_from_http, fetch_content_from_ipfs},
+    error::{Error, ParseError, TokenMetadataError, UriError},
+    fetch::{resolve_uri, uri_from_felts},
 };
-use torii_proto::TokenId;
+use torii_proto::{MetadataJobStatus, TokenId};
 
 // Retry configuration constants
 const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
 const PROVIDER_MAX_RETRIES: u32 = 5;
 
+// Metadata job retry configuration constants
+const METADATA_RETRY_BACKOFF: Duration = Duration::from_secs(60);
+const METADATA_MAX_RETRY_BACKOFF: Duration = Duration::from_secs(24 * 60 * 60);
+const METADATA_MAX_ATTEMPTS: u32 = 8;
+
 /// Determines if a provider error is permanent (should not be retried) or transient (can be retried)
 fn is_permanent_error(error: &ProviderError) -> bool {
     match error {
@@ -401,6 +407,7 @@ async fn try_fetch_token_uri_sequence<P: Provider + Sync>(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn try_register_nft_token_metadata<P: Provider + Sync>(
     id: TokenId,
     contract_address: Felt,
@@ -409,6 +416,7 @@ pub async fn try_register_nft_token_metadata<P: Provider + Sync>(
     cache: Arc<dyn Cache + Send + Sync>,
     storage: Arc<dyn Storage>,
     nft_metadata_semaphore: Arc<Semaphore>,
+    metadata_queue: bool,
 ) -> Result<(), Error> {
     let _lock = match cache.get_token_registration_lock(id.clone()).await {
         Some(lock) => lock,
@@ -419,16 +427,38 @@ pub async fn try_register_nft_token_metadata<P: Provider + Sync>(
         return Ok(());
     }
 
-    let _permit = nft_metadata_semaphore
-        .acquire()
-        .await
-        .map_err(|e| Error::TokenMetadataError(TokenMetadataError::AcquireError(e)))?;
-    let metadata = fetch_token_metadata(contract_address, actual_token_id, provider).await?;
+    // With the metadata queue enabled, the token is registered with empty metadata and the
+    // metadata workers fetch it in the background.
+    let metadata = if metadata_queue {
+        String::new()
+    } else {
+        let _permit = nft_metadata_semaphore
+            .acquire()
+            .await
+            .map_err(|e| Error::TokenMetadataError(TokenMetadataError::AcquireError(e)))?;
+        match try_fetch_token_metadata(contract_address, actual_token_id, provider).await {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                warn!(
+                    contract_address = format!("{:#x}", contract_address),
+                    token_id = %actual_token_id,
+                    error = ?e,
+                    "Error fetching metadata, empty metadata will be used instead.",
+                );
+                record_metadata_job_failure(storage.as_ref(), id.clone(), e.to_string(), 0).await?;
+                String::new()
+            }
+        }
+    };
 
     storage
         .register_nft_token(contract_address, actual_token_id, metadata)
         .await?;
 
+    if metadata_queue {
+        storage.enqueue_metadata_jobs(vec![id.clone()]).await?;
+    }
+
     cache.mark_token_registered(id).await;
 
     // For ERC-1155, we need to track unique token count at contract level
@@ -443,6 +473,39 @@ pub async fn try_register_nft_token_metadata<P: Provider + Sync>(
     Ok(())
 }
 
+/// Returns when a failed metadata job should be retried, backing off exponentially with the
+/// number of failed attempts. Returns `None` once the job ran out of attempts.
+pub fn metadata_retry_at(attempts: u32) -> Option<DateTime<Utc>> {
+    if attempts >= METADATA_MAX_ATTEMPTS {
+        return None;
+    }
+
+    let backoff = METADATA_RETRY_BACKOFF
+        .saturating_mul(2u32.saturating_pow(attempts))
+        .min(METADATA_MAX_RETRY_BACKOFF);
+    Some(Utc::now() + backoff)
+}
+
+/// Marks the metadata job of a token as failed, scheduling a retry if it has attempts left.
+/// `attempts` is the number of failed attempts before this one.
+pub async fn record_metadata_job_failure(
+    storage: &dyn Storage,
+    token_id: TokenId,
+    error: String,
+    attempts: u32,
+) -> Result<(), Error> {
+    storage
+        .set_metadata_job_status(
+            token_id,
+            MetadataJobStatus::Failed,
+            Some(error),
+            metadata_retry_at(attempts),
+        )
+        .await?;
+
+    Ok(())
+}
+
 pub(crate) async fn try_register_token_contract<P: Provider + Sync>(
     contract_address: Felt,
     provider: &P,
@@ -528,24 +591,17 @@ pub async fn fetch_contract_uri<P: Provider + Sync>(
             Err(_) => return Ok(None),
         };
 
-    let contract_uri = if let Ok(byte_array) = ByteArray::cairo_deserialize(&contract_uri, 0) {
-        byte_array
-            .to_string()
-            .map_err(|e| TokenMetadataError::Parse(ParseError::FromUtf8(e)))?
-    } else if let Ok(felt_array) = Vec::<Felt>::cairo_deserialize(&contract_uri, 0) {
-        felt_array
-            .iter()
-            .map(parse_cairo_short_string)
-            .collect::<Result<Vec<String>, _>>()
-            .map(|strings| strings.join(""))
-            .map_err(|e| TokenMetadataError::Parse(ParseError::ParseCairoShortString(e)))?
-    } else {
-        debug!(
-            contract_address = format!("{:#x}", contract_address),
-            contract_uri = %contract_uri.iter().map(|f| format!("{:#x}", f)).collect::<Vec<String>>().join(", "),
-            "contract_uri is neither ByteArray nor Array<Felt>"
-        );
-        return Ok(None);
+    let contract_uri = match uri_from_felts(&contract_uri) {
+        Ok(uri) => uri,
+        Err(UriError::InvalidFelts) => {
+            debug!(
+                contract_address = format!("{:#x}", contract_address),
+                contract_uri = %contract_uri.iter().map(|f| format!("{:#x}", f)).collect::<Vec<String>>().join(", "),
+                "contract_uri is neither ByteArray nor Array<Felt>"
+            );
+            return Ok(None);
+        }
+        Err(e) => return Err(TokenMetadataError::Uri(e)),
     };
 
     Ok(Some(contract_uri))
@@ -579,25 +635,18 @@ pub async fn fetch_token_uri<P: Provider + Sync>(
             }
         };
 
-    let mut token_uri = if let Ok(byte_array) = ByteArray::cairo_deserialize(&token_uri, 0) {
-        byte_array
-            .to_string()
-            .map_err(|e| TokenMetadataError::Parse(ParseError::FromUtf8(e)))?
-    } else if let Ok(felt_array) = Vec::<Felt>::cairo_deserialize(&token_uri, 0) {
-        felt_array
-            .iter()
-            .map(parse_cairo_short_string)
-            .collect::<Result<Vec<String>, _>>()
-            .map(|strings| strings.join(""))
-            .map_err(|e| TokenMetadataError::Parse(ParseError::ParseCairoShortString(e)))?
-    } else {
-        debug!(
-            contract_address = format!("{:#x}", contract_address),
-            token_id = %token_id,
-            token_uri = %token_uri.iter().map(|f| format!("{:#x}", f)).collect::<Vec<String>>().join(", "),
-            "token_uri is neither ByteArray nor Array<Felt>"
-        );
-        "".to_string()
+    let mut token_uri = match uri_from_felts(&token_uri) {
+        Ok(uri) => uri,
+        Err(UriError::InvalidFelts) => {
+            debug!(
+                contract_address = format!("{:#x}", contract_address),
+                token_id = %token_id,
+                token_uri = %token_uri.iter().map(|f| format!("{:#x}", f)).collect::<Vec<String>>().join(", "),
+                "token_uri is neither ByteArray nor Array<Felt>"
+            );
+            "".to_string()
+        }
+        Err(e) => return Err(TokenMetadataError::Uri(e)),
     };
 
     // Handle ERC1155 {id} replacement
@@ -612,21 +661,12 @@ pub async fn fetch_token_metadata<P: Provider + Sync>(
     token_id: U256,
     provider: &P,
 ) -> Result<String, TokenMetadataError> {
-    let token_uri = fetch_token_uri(provider, contract_address, token_id).await?;
-
-    if token_uri.is_empty() {
-        return Ok("".to_string());
-    }
-
-    let metadata = fetch_metadata(&token_uri).await;
-    match metadata {
-        Ok(metadata) => serde_json::to_string(&metadata)
-            .map_err(|e| TokenMetadataError::Parse(ParseError::FromJsonStr(e))),
+    match try_fetch_token_metadata(contract_address, token_id, provider).await {
+        Ok(metadata) => Ok(metadata),
         Err(e) => {
             warn!(
                 contract_address = format!("{:#x}", contract_address),
                 token_id = %token_id,
-                token_uri = %token_uri,
                 error = ?e,
                 "Error fetching metadata, empty metadata will be used instead.",
             );
@@ -635,77 +675,75 @@ pub async fn fetch_token_metadata<P: Provider + Sync>(
     }
 }
 
-// given a uri which can be either http/https url or data uri, fetch the metadata erc721
-// metadata json schema
-pub async fn fetch_metadata(token_uri: &str) -> Result<serde_json::Value, TokenMetadataError> {
-    // Parse the token_uri
+/// Same as [`fetch_token_metadata`], but surfaces errors fetching the metadata from the token URI
+/// instead of falling back to empty metadata. A token without a URI still has empty metadata.
+pub async fn try_fetch_token_metadata<P: Provider + Sync>(
+    contract_address: Felt,
+    token_id: U256,
+    provider: &P,
+) -> Result<String, TokenMetadataError> {
+    let token_uri = fetch_token_uri(provider, contract_address, token_id).await?;
 
-    match token_uri {
-        uri if uri.starts_with("http") || uri.starts_with("https") => {
-            // Fetch metadata from HTTP/HTTPS URL
-            debug!(token_uri = %token_uri, "Fetching metadata from http/https URL");
-            let response = fetch_content_from_http(token_uri)
-                .await
-                .map_err(TokenMetadataError::Http)?;
+    if token_uri.is_empty() {
+        return Ok("".to_string());
+    }
+
+    let metadata = fetch_metadata(&token_uri).await?;
+    serde_json::to_string(&metadata)
+        .map_err(|e| TokenMetadataError::Parse(ParseError::FromJsonStr(e)))
+}
 
-            let json: serde_json::Value = serde_json::from_slice(&response)
-                .map_err(|e| TokenMetadataError::Parse(ParseError::FromJsonStr(e)))?;
+// given a uri which can be an ipfs, arweave, http/https or data uri, fetch the metadata erc721
+// metadata json schema
+pub async fn fetch_metadata(token_uri: &str) -> Result<serde_json::Value, TokenMetadataError> {
+    debug!(token_uri = %token_uri, "Resolving metadata URI");
 
-            Ok(json)
+    let resolved = match resolve_uri(token_uri).await {
+        Ok(resolved) => resolved,
+        Err(UriError::UnsupportedScheme(uri)) => {
+            // Fallback: try to parse the URI content as raw JSON
+            debug!(uri = %uri, "Attempting to parse URI content as raw JSON");
+            return serde_json::from_str::<serde_json::Value>(&uri)
+                .map_err(|_| TokenMetadataError::UnsupportedUriScheme(uri));
         }
-        uri if uri.starts_with("ipfs") => {
-            let cid = uri.strip_prefix("ipfs://").unwrap();
-            debug!(cid = %cid, "Fetching metadata from IPFS");
-            let response = fetch_content_from_ipfs(cid)
-                .await
-                .map_err(TokenMetadataError::Ipfs)?;
+        Err(e) => return Err(TokenMetadataError::Uri(e)),
+    };
 
-            let json: serde_json::Value = serde_json::from_slice(&response)
-                .map_err(|e| TokenMetadataError::Parse(ParseError::FromJsonStr(e)))?;
+    // On-chain SVG token URIs are the token image itself, the image pipeline renders and
+    // caches it from the URI.
+    if resolved.is_svg() {
+        let image = if token_uri.starts_with("data:") {
+            token_uri.to_string()
+        } else {
+            format!(
+                "data:image/svg+xml;base64,{}",
+                general_purpose::STANDARD.encode(&resolved.content)
+            )
+        };
+        return Ok(serde_json::json!({ "image": image }));
+    }
 
-            Ok(json)
+    // Ensure data URIs declare a JSON payload
+    if let Some(mime_type) = &resolved.mime_type {
+        if mime_type != "application/json" && mime_type != "text/plain" {
+            return Err(TokenMetadataError::InvalidMimeType(mime_type.clone()));
         }
-        uri if uri.starts_with("data") => {
-            // Parse and decode data URI
-            debug!(data_uri = %token_uri, "Parsing metadata from data URI");
-
-            // HACK: https://github.com/servo/rust-url/issues/908
-            let uri = token_uri.replace("#", "%23");
+    }
 
-            let data_url = DataUrl::process(&uri).map_err(TokenMetadataError::DataUrl)?;
+    if let Ok(json) = serde_json::from_slice::<serde_json::Value>(&resolved.content) {
+        return Ok(json);
+    }
 
-            // Ensure the MIME type is JSON
-            if data_url.mime_type() != &Mime::from_str("application/json").unwrap() {
-                return Err(TokenMetadataError::InvalidMimeType(
-                    data_url.mime_type().to_string(),
-                ));
-            }
+    // HACK: Loot Survior NFT metadata contains control characters which makes the json
+    // DATA invalid so filter them out
+    let decoded_str = String::from_utf8_lossy(&resolved.content)
+        .chars()
+        .filter(|c| !c.is_ascii_control())
+        .collect::<String>();
+    let sanitized_json = sanitize_json_string(&decoded_str);
 
-            let decoded = data_url
-                .decode_to_vec()
-                .map_err(TokenMetadataError::InvalidBase64)?;
-            // HACK: Loot Survior NFT metadata contains control characters which makes the json
-            // DATA invalid so filter them out
-            let decoded_str = String::from_utf8_lossy(&decoded.0)
-                .chars()
-                .filter(|c| !c.is_ascii_control())
-                .collect::<String>();
-            let sanitized_json = sanitize_json_string(&decoded_str);
-
-            let json: serde_json::Value = serde_json::from_str(&sanitized_json)
-                .map_err(|e| TokenMetadataError::Parse(ParseError::FromJsonStr(e)))?;
-
-            Ok(json)
-        }
-        uri => {
-            // Fallback: try to parse the URI content as raw JSON
-            debug!(uri = %uri, "Attempting to parse URI content as raw JSON");
-            match serde_json::from_str::<serde_json::Value>(uri) {
-                Ok(json) => Ok(json),
-                Err(_) => Err(TokenMetadataError::UnsupportedUriScheme(uri.to_string())),
-            }
-        }
-    }
+    serde_json::from_str(&sanitized_json)
+        .map_err(|e| TokenMetadataError::Parse(ParseError::FromJsonStr(e)))
 }
 
 /// Sanitizes a JSON string by escaping unescaped double quotes within string values.
@@ -772,6 +810,25 @@ pub fn sanitize_json_string(s: &str) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_metadata_retry_at_backs_off() {
+        let now = Utc::now();
+
+        let first = metadata_retry_at(0).unwrap();
+        assert!(first >= now + METADATA_RETRY_BACKOFF);
+        assert!(first < now + METADATA_RETRY_BACKOFF * 2);
+
+        let third = metadata_retry_at(2).unwrap();
+        assert!(third >= now + METADATA_RETRY_BACKOFF * 4);
+
+        // The backoff is capped
+        let last = metadata_retry_at(METADATA_MAX_ATTEMPTS - 1).unwrap();
+        assert!(last <= Utc::now() + METADATA_MAX_RETRY_BACKOFF);
+
+        // No more retries once the job ran out of attempts
+        assert!(metadata_retry_at(METADATA_MAX_ATTEMPTS).is_none());
+    }
+
     #[test]
     fn test_sanitize_json_string() {
         let input = r#"{"name":""Rage Shout" DireWolf"}"#;
@@ -784,4 +841,33 @@ mod tests {
         let sanitized_escaped = sanitize_json_string(input_escaped);
         assert_eq!(sanitized_escaped, expected_escaped);
     }
+
+    #[tokio::test]
+    async fn test_fetch_metadata_from_data_uris() {
+        let metadata = fetch_metadata("data:application/json;base64,eyJuYW1lIjoiYmFzZTY0In0=")
+            .await
+            .unwrap();
+        assert_eq!(metadata, serde_json::json!({ "name": "base64" }));
+
+        let metadata = fetch_metadata(r#"data:application/json,{"name":"Loot #1"}"#)
+            .await
+            .unwrap();
+        assert_eq!(metadata, serde_json::json!({ "name": "Loot #1" }));
+
+        let svg_uri = "data:image/svg+xml;utf8,<svg xmlns='http://www.w3.org/2000/svg'></svg>";
+        let metadata = fetch_metadata(svg_uri).await.unwrap();
+        assert_eq!(metadata, serde_json::json!({ "image": svg_uri }));
+
+        let metadata = fetch_metadata(r#"{"name":"raw"}"#).await.unwrap();
+        assert_eq!(metadata, serde_json::json!({ "name": "raw" }));
+
+        assert!(matches!(
+            fetch_metadata("data:image/png;base64,AAAA").await,
+            Err(TokenMetadataError::InvalidMimeType(mime)) if mime == "image/png"
+        ));
+        assert!(matches!(
+            fetch_metadata("ftp://example.com/1.json").await,
+            Err(TokenMetadataError::UnsupportedUriScheme(_))
+        ));
+    }
 }
@@ -31,6 +31,8 @@ pub enum Error {
     TokenMetadataError(#[from] TokenMetadataError),
     #[error(transparent)]
     CacheError(#[from] torii_cache::error::Error),
+    #[error(transparent)]
+    FetchError(#[from] FetchError),
 }
 
 #[derive(Error, Debug)]
@@ -59,6 +61,10 @@ pub enum TokenMetadataError {
     ProviderError(#[from] starknet::providers::ProviderError),
     #[error(transparent)]
     Http(#[from] HttpError),
+    #[error(transparent)]
+    Fetch(#[from] FetchError),
+    #[error(transparent)]
+    Uri(#[from] UriError),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -69,6 +75,38 @@ pub enum HttpError {
     Reqwest(#[from] reqwest::Error),
 }
 
+#[derive(Debug, thiserror::Error)]
+pub enum FetchError {
+    #[error(transparent)]
+    Http(#[from] HttpError),
+    #[error(transparent)]
+    Ipfs(#[from] ipfs_api_backend_hyper::Error),
+    #[error("No gateway could serve {0}")]
+    GatewaysExhausted(String),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UriError {
+    #[error("Unsupported URI scheme: {0}")]
+    UnsupportedScheme(String),
+    #[error("Malformed URI: {0}")]
+    Malformed(String),
+    #[error(transparent)]
+    Http(#[from] HttpError),
+    #[error(transparent)]
+    Fetch(#[from] FetchError),
+    #[error(transparent)]
+    DataUrl(#[from] data_url::DataUrlError),
+    #[error(transparent)]
+    InvalidBase64(#[from] data_url::forgiving_base64::InvalidBase64),
+    #[error(transparent)]
+    InvalidUtf8(#[from] std::string::FromUtf8Error),
+    #[error(transparent)]
+    InvalidShortString(#[from] ParseCairoShortStringError),
+    #[error("URI felts are neither a ByteArray nor an array of short strings")]
+    InvalidFelts,
+}
+
 #[derive(Error, Debug)]
 pub enum ParseError {
     #[error(transparent)]
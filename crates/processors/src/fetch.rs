@@ -1,17 +1,33 @@
-use std::sync::LazyLock;
-use std::time::Duration;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, OnceLock};
+use std::time::{Duration, SystemTime};
 
+use cainome_cairo_serde::{ByteArray, CairoSerde};
+use data_url::DataUrl;
 use futures_util::TryStreamExt;
 use ipfs_api_backend_hyper::{IpfsApi, IpfsClient, TryFromUri};
 use reqwest::Client;
+use sha2::{Digest, Sha256};
+use starknet::core::utils::parse_cairo_short_string;
+use starknet_crypto::Felt;
 use tokio_util::bytes::Bytes;
-use tracing::debug;
+use tracing::{debug, warn};
 
 use crate::{
     constants::{IPFS_CLIENT_PASSWORD, IPFS_CLIENT_URL, IPFS_CLIENT_USERNAME},
-    error::HttpError,
+    error::{FetchError, HttpError, UriError},
 };
 
+pub const DEFAULT_IPFS_GATEWAYS: &[&str] = &["https://ipfs.io/ipfs/", "https://dweb.link/ipfs/"];
+pub const DEFAULT_ARWEAVE_GATEWAYS: &[&str] = &["https://arweave.net/"];
+/// Default timeout of a single gateway request.
+pub const DEFAULT_GATEWAY_TIMEOUT: Duration = Duration::from_millis(5000);
+
+const IPFS_CACHE_NAMESPACE: &str = "ipfs";
+const ARWEAVE_CACHE_NAMESPACE: &str = "ar";
+const HTTP_CACHE_NAMESPACE: &str = "http";
+
 // Global clients
 static HTTP_CLIENT: LazyLock<Client> = LazyLock::new(|| {
     Client::builder()
@@ -27,11 +43,365 @@ static IPFS_CLIENT: LazyLock<IpfsClient> = LazyLock::new(|| {
         .with_credentials(IPFS_CLIENT_USERNAME, IPFS_CLIENT_PASSWORD)
 });
 
+static FETCHER: OnceLock<ContentFetcher> = OnceLock::new();
+
 const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
 const REQ_MAX_RETRIES: u32 = 5;
 
+/// Configuration of the gateways and of the on-disk cache used to fetch off-chain content.
+#[derive(Debug, Clone)]
+pub struct FetchConfig {
+    /// IPFS HTTP gateways, tried in order. The CID is appended to the gateway URL.
+    pub ipfs_gateways: Vec<String>,
+    /// Arweave gateways, tried in order. The transaction id is appended to the gateway URL.
+    pub arweave_gateways: Vec<String>,
+    /// Timeout of a single gateway request before failing over to the next gateway.
+    pub gateway_timeout: Duration,
+    /// Whether to fall back to the IPFS API client once every IPFS gateway failed.
+    pub ipfs_api_fallback: bool,
+    /// Directory of the content-addressed cache. Nothing is cached when `None`.
+    pub cache_dir: Option<PathBuf>,
+    /// How long HTTP(S) content is served from the cache. IPFS and Arweave content is
+    /// immutable and never expires. HTTP(S) content is not cached when zero.
+    pub http_cache_ttl: Duration,
+}
+
+impl Default for FetchConfig {
+    fn default() -> Self {
+        Self {
+            ipfs_gateways: DEFAULT_IPFS_GATEWAYS
+                .iter()
+                .map(|g| g.to_string())
+                .collect(),
+            arweave_gateways: DEFAULT_ARWEAVE_GATEWAYS
+                .iter()
+                .map(|g| g.to_string())
+                .collect(),
+            gateway_timeout: DEFAULT_GATEWAY_TIMEOUT,
+            ipfs_api_fallback: true,
+            cache_dir: None,
+            http_cache_ttl: Duration::ZERO,
+        }
+    }
+}
+
+/// On-disk cache of fetched content, keyed by CID, Arweave transaction id or URL hash.
+///
+/// Entries are laid out as `<dir>/<namespace>/<key>`. Keys that are not plain identifiers
+/// (URLs, CIDs with a path) are stored under their SHA-256 hash.
+#[derive(Debug, Clone)]
+pub struct ContentCache {
+    dir: PathBuf,
+}
+
+impl ContentCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn path(&self, namespace: &str, key: &str) -> PathBuf {
+        let is_identifier =
+            !key.is_empty() && key.len() <= 128 && key.chars().all(|c| c.is_ascii_alphanumeric());
+        let file_name = if is_identifier {
+            key.to_string()
+        } else {
+            format!("{:x}", Sha256::digest(key.as_bytes()))
+        };
+
+        self.dir.join(namespace).join(file_name)
+    }
+
+    /// Returns the cached content, unless it is older than `max_age`.
+    pub async fn get(
+        &self,
+        namespace: &str,
+        key: &str,
+        max_age: Option<Duration>,
+    ) -> Option<Bytes> {
+        let path = self.path(namespace, key);
+
+        if let Some(max_age) = max_age {
+            let modified = tokio::fs::metadata(&path).await.ok()?.modified().ok()?;
+            let age = SystemTime::now()
+                .duration_since(modified)
+                .unwrap_or_default();
+            if age > max_age {
+                return None;
+            }
+        }
+
+        tokio::fs::read(&path).await.ok().map(Bytes::from)
+    }
+
+    /// Writes the content to the cache. The entry is written to a temporary file first and
+    /// renamed, so concurrent readers never observe a partial entry.
+    pub async fn put(&self, namespace: &str, key: &str, content: &[u8]) -> std::io::Result<()> {
+        static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        let path = self.path(namespace, key);
+        let dir = path
+            .parent()
+            .expect("cache entries always have a parent directory");
+        tokio::fs::create_dir_all(dir).await?;
+
+        let tmp_path = path.with_extension(format!(
+            "{}.{}.tmp",
+            std::process::id(),
+            TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        tokio::fs::write(&tmp_path, content).await?;
+        tokio::fs::rename(&tmp_path, &path).await
+    }
+}
+
+/// Content a URI resolved to.
+#[derive(Debug, Clone)]
+pub struct ResolvedUri {
+    pub content: Bytes,
+    /// MIME type, without parameters, declared by a `data:` URI. `None` for fetched content.
+    pub mime_type: Option<String>,
+}
+
+impl ResolvedUri {
+    fn fetched(content: Bytes) -> Self {
+        Self {
+            content,
+            mime_type: None,
+        }
+    }
+
+    pub fn is_svg(&self) -> bool {
+        if self.mime_type.as_deref() == Some("image/svg+xml") {
+            return true;
+        }
+
+        // svg files typically start with <svg or <?xml
+        let content = self.content.trim_ascii_start();
+        content.starts_with(b"<svg") || content.starts_with(b"<?xml")
+    }
+}
+
+/// Fetches IPFS, Arweave and HTTP(S) content through the configured gateways, caching the
+/// results on disk.
+#[derive(Debug)]
+pub struct ContentFetcher {
+    config: FetchConfig,
+    cache: Option<ContentCache>,
+}
+
+impl ContentFetcher {
+    pub fn new(config: FetchConfig) -> Self {
+        let cache = config.cache_dir.clone().map(ContentCache::new);
+        Self { config, cache }
+    }
+
+    pub fn config(&self) -> &FetchConfig {
+        &self.config
+    }
+
+    pub fn cache(&self) -> Option<&ContentCache> {
+        self.cache.as_ref()
+    }
+
+    /// Fetch content from HTTP URL with retries
+    pub async fn fetch_http(&self, url: &str) -> Result<Bytes, HttpError> {
+        let use_cache = !self.config.http_cache_ttl.is_zero();
+        if use_cache {
+            if let Some(content) = self
+                .cached(HTTP_CACHE_NAMESPACE, url, Some(self.config.http_cache_ttl))
+                .await
+            {
+                return Ok(content);
+            }
+        }
+
+        let content = fetch_http_with_retries(url).await?;
+        if use_cache {
+            self.store(HTTP_CACHE_NAMESPACE, url, &content).await;
+        }
+
+        Ok(content)
+    }
+
+    /// Fetch content from IPFS, trying each gateway in order before falling back to the IPFS
+    /// API client.
+    pub async fn fetch_ipfs(&self, cid: &str) -> Result<Bytes, FetchError> {
+        if let Some(content) = self.cached(IPFS_CACHE_NAMESPACE, cid, None).await {
+            return Ok(content);
+        }
+
+        let content = match self
+            .fetch_from_gateways(&self.config.ipfs_gateways, cid)
+            .await
+        {
+            Some(content) => content,
+            None if self.config.ipfs_api_fallback => {
+                debug!(cid = %cid, "IPFS gateways failed, falling back to the IPFS API");
+                fetch_ipfs_api_with_retries(cid).await?
+            }
+            None => return Err(FetchError::GatewaysExhausted(format!("ipfs://{cid}"))),
+        };
+
+        self.store(IPFS_CACHE_NAMESPACE, cid, &content).await;
+        Ok(content)
+    }
+
+    /// Fetch content from Arweave, trying each gateway in order.
+    pub async fn fetch_arweave(&self, tx_id: &str) -> Result<Bytes, FetchError> {
+        if let Some(content) = self.cached(ARWEAVE_CACHE_NAMESPACE, tx_id, None).await {
+            return Ok(content);
+        }
+
+        let content = self
+            .fetch_from_gateways(&self.config.arweave_gateways, tx_id)
+            .await
+            .ok_or_else(|| FetchError::GatewaysExhausted(format!("ar://{tx_id}")))?;
+
+        self.store(ARWEAVE_CACHE_NAMESPACE, tx_id, &content).await;
+        Ok(content)
+    }
+
+    /// Resolves an `ipfs://`, `ar://`, `http(s)://` or `data:` URI to its content.
+    pub async fn resolve(&self, uri: &str) -> Result<ResolvedUri, UriError> {
+        let uri = uri.trim();
+        let Some((scheme, rest)) = uri.split_once(':') else {
+            return Err(UriError::UnsupportedScheme(uri.to_string()));
+        };
+
+        match scheme.to_ascii_lowercase().as_str() {
+            "http" | "https" => Ok(ResolvedUri::fetched(self.fetch_http(uri).await?)),
+            "ipfs" => {
+                // Accept ipfs://<cid>, ipfs:/<cid> and ipfs://ipfs/<cid>
+                let path = rest.trim_start_matches('/');
+                let path = path.strip_prefix("ipfs/").unwrap_or(path);
+                if path.is_empty() {
+                    return Err(UriError::Malformed(uri.to_string()));
+                }
+                Ok(ResolvedUri::fetched(self.fetch_ipfs(path).await?))
+            }
+            "ar" => {
+                let tx_id = rest.trim_start_matches('/');
+                if tx_id.is_empty() {
+                    return Err(UriError::Malformed(uri.to_string()));
+                }
+                Ok(ResolvedUri::fetched(self.fetch_arweave(tx_id).await?))
+            }
+            "data" => decode_data_uri(uri),
+            _ => Err(UriError::UnsupportedScheme(uri.to_string())),
+        }
+    }
+
+    async fn fetch_from_gateways(&self, gateways: &[String], path: &str) -> Option<Bytes> {
+        for gateway in gateways {
+            let url = format!("{}/{}", gateway.trim_end_matches('/'), path);
+            let result = HTTP_CLIENT
+                .get(&url)
+                .timeout(self.config.gateway_timeout)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+
+            match result {
+                Ok(response) => match response.bytes().await {
+                    Ok(content) => return Some(content),
+                    Err(e) => debug!(url = %url, error = ?e, "Failed to read gateway response"),
+                },
+                Err(e) => debug!(url = %url, error = ?e, "Gateway request failed, trying next"),
+            }
+        }
+
+        None
+    }
+
+    async fn cached(&self, namespace: &str, key: &str, max_age: Option<Duration>) -> Option<Bytes> {
+        let content = self.cache.as_ref()?.get(namespace, key, max_age).await?;
+        debug!(namespace = %namespace, key = %key, "Serving content from cache");
+        Some(content)
+    }
+
+    async fn store(&self, namespace: &str, key: &str, content: &[u8]) {
+        if let Some(cache) = &self.cache {
+            if let Err(e) = cache.put(namespace, key, content).await {
+                warn!(namespace = %namespace, key = %key, error = ?e, "Failed to cache content");
+            }
+        }
+    }
+}
+
+/// Installs the process-wide fetcher used by the `fetch_content_from_*` functions.
+///
+/// Returns `false` if a fetcher was already installed or used, in which case the config is
+/// ignored.
+pub fn init_fetcher(config: FetchConfig) -> bool {
+    FETCHER.set(ContentFetcher::new(config)).is_ok()
+}
+
+/// The process-wide fetcher, using the default config if none was installed.
+pub fn fetcher() -> &'static ContentFetcher {
+    FETCHER.get_or_init(|| ContentFetcher::new(FetchConfig::default()))
+}
+
+/// Resolves an `ipfs://`, `ar://`, `http(s)://` or `data:` URI through the process-wide
+/// fetcher.
+pub async fn resolve_uri(uri: &str) -> Result<ResolvedUri, UriError> {
+    fetcher().resolve(uri).await
+}
+
+/// Decodes a base64 or percent-encoded (utf8) `data:` URI.
+pub fn decode_data_uri(uri: &str) -> Result<ResolvedUri, UriError> {
+    // HACK: https://github.com/servo/rust-url/issues/908
+    let uri = uri.replace('#', "%23");
+
+    let data_url = DataUrl::process(&uri)?;
+    let mime = data_url.mime_type();
+    let mime_type = format!("{}/{}", mime.type_, mime.subtype);
+    let (content, _) = data_url.decode_to_vec()?;
+
+    Ok(ResolvedUri {
+        content: Bytes::from(content),
+        mime_type: Some(mime_type),
+    })
+}
+
+/// Decodes a URI returned by a contract as felts. Supports `ByteArray`, `Array<felt252>` of
+/// short strings and bare short strings split across several felts.
+pub fn uri_from_felts(felts: &[Felt]) -> Result<String, UriError> {
+    if let Ok(byte_array) = ByteArray::cairo_deserialize(felts, 0) {
+        return Ok(byte_array.to_string()?);
+    }
+
+    if let Ok(felt_array) = Vec::<Felt>::cairo_deserialize(felts, 0) {
+        let strings = felt_array
+            .iter()
+            .map(parse_cairo_short_string)
+            .collect::<Result<Vec<String>, _>>()?;
+        return Ok(strings.join(""));
+    }
+
+    felts
+        .iter()
+        .map(parse_cairo_short_string)
+        .collect::<Result<Vec<String>, _>>()
+        .map(|strings| strings.join(""))
+        .map_err(|_| UriError::InvalidFelts)
+}
+
 /// Fetch content from HTTP URL with retries
 pub async fn fetch_content_from_http(url: &str) -> Result<Bytes, HttpError> {
+    fetcher().fetch_http(url).await
+}
+
+/// Fetch content from IPFS through the configured gateways
+pub async fn fetch_content_from_ipfs(cid: &str) -> Result<Bytes, FetchError> {
+    fetcher().fetch_ipfs(cid).await
+}
+
+/// Fetch content from Arweave through the configured gateways
+pub async fn fetch_content_from_arweave(tx_id: &str) -> Result<Bytes, FetchError> {
+    fetcher().fetch_arweave(tx_id).await
+}
+
+async fn fetch_http_with_retries(url: &str) -> Result<Bytes, HttpError> {
     let mut retries = 0;
     let mut backoff = INITIAL_BACKOFF;
 
@@ -59,8 +429,7 @@ pub async fn fetch_content_from_http(url: &str) -> Result<Bytes, HttpError> {
     }
 }
 
-/// Fetch content from IPFS with retries
-pub async fn fetch_content_from_ipfs(cid: &str) -> Result<Bytes, ipfs_api_backend_hyper::Error> {
+async fn fetch_ipfs_api_with_retries(cid: &str) -> Result<Bytes, ipfs_api_backend_hyper::Error> {
     let mut retries = 0;
     let mut backoff = INITIAL_BACKOFF;
 
@@ -84,3 +453,260 @@ pub async fn fetch_content_from_ipfs(cid: &str) -> Result<Bytes, ipfs_api_backen
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    /// Minimal HTTP server standing in for a gateway. Serves `routes` by exact path, answers
+    /// 404 otherwise, and counts the requests it received.
+    async fn spawn_gateway(
+        routes: Vec<(&'static str, &'static [u8])>,
+    ) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let hits = Arc::new(AtomicUsize::new(0));
+
+        let counter = hits.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                counter.fetch_add(1, Ordering::SeqCst);
+
+                let mut buf = vec![0; 4096];
+                let n = socket.read(&mut buf).await.unwrap();
+                let request = String::from_utf8_lossy(&buf[..n]);
+                let path = request.split_whitespace().nth(1).unwrap_or_default();
+
+                let (status, body) = routes
+                    .iter()
+                    .find(|(route, _)| *route == path)
+                    .map(|(_, body)| ("200 OK", *body))
+                    .unwrap_or(("404 Not Found", b"".as_slice()));
+
+                let head = format!(
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                socket.write_all(head.as_bytes()).await.unwrap();
+                socket.write_all(body).await.unwrap();
+            }
+        });
+
+        (format!("http://{addr}"), hits)
+    }
+
+    fn config(ipfs_gateways: Vec<String>, cache_dir: Option<PathBuf>) -> FetchConfig {
+        FetchConfig {
+            ipfs_gateways,
+            arweave_gateways: vec![],
+            gateway_timeout: Duration::from_secs(1),
+            ipfs_api_fallback: false,
+            cache_dir,
+            http_cache_ttl: Duration::ZERO,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ipfs_gateway_failover() {
+        let (broken, broken_hits) = spawn_gateway(vec![]).await;
+        let (healthy, healthy_hits) =
+            spawn_gateway(vec![("/ipfs/QmToken", b"{\"name\":\"x\"}".as_slice())]).await;
+
+        let fetcher = ContentFetcher::new(config(
+            vec![format!("{broken}/ipfs/"), format!("{healthy}/ipfs")],
+            None,
+        ));
+
+        let content = fetcher.fetch_ipfs("QmToken").await.unwrap();
+        assert_eq!(content.as_ref(), b"{\"name\":\"x\"}".as_slice());
+        assert_eq!(broken_hits.load(Ordering::SeqCst), 1);
+        assert_eq!(healthy_hits.load(Ordering::SeqCst), 1);
+
+        let err = fetcher.fetch_ipfs("QmMissing").await.unwrap_err();
+        assert!(matches!(err, FetchError::GatewaysExhausted(uri) if uri == "ipfs://QmMissing"));
+    }
+
+    #[tokio::test]
+    async fn test_content_cache_is_shared() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let (gateway, hits) = spawn_gateway(vec![
+            ("/ipfs/QmImage/1.png", b"png".as_slice()),
+            ("/arweave/tx1", b"arweave".as_slice()),
+        ])
+        .await;
+
+        let mut fetch_config = config(
+            vec![format!("{gateway}/ipfs/")],
+            Some(cache_dir.path().to_path_buf()),
+        );
+        fetch_config.arweave_gateways = vec![format!("{gateway}/arweave/")];
+        let fetcher = ContentFetcher::new(fetch_config);
+
+        assert_eq!(
+            fetcher.fetch_ipfs("QmImage/1.png").await.unwrap().as_ref(),
+            b"png".as_slice()
+        );
+        assert_eq!(
+            fetcher.fetch_ipfs("QmImage/1.png").await.unwrap().as_ref(),
+            b"png".as_slice()
+        );
+        assert_eq!(
+            fetcher.fetch_arweave("tx1").await.unwrap().as_ref(),
+            b"arweave".as_slice()
+        );
+        assert_eq!(
+            fetcher.fetch_arweave("tx1").await.unwrap().as_ref(),
+            b"arweave".as_slice()
+        );
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+        // A second fetcher without any reachable gateway is served from the same cache.
+        let offline = ContentFetcher::new(config(vec![], Some(cache_dir.path().to_path_buf())));
+        assert_eq!(
+            offline.fetch_ipfs("QmImage/1.png").await.unwrap().as_ref(),
+            b"png".as_slice()
+        );
+        assert!(offline.fetch_ipfs("QmOther").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_http_cache_ttl() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let (server, hits) = spawn_gateway(vec![("/metadata/1", b"{}".as_slice())]).await;
+        let url = format!("{server}/metadata/1");
+
+        let uncached = ContentFetcher::new(config(vec![], Some(cache_dir.path().to_path_buf())));
+        uncached.fetch_http(&url).await.unwrap();
+        uncached.fetch_http(&url).await.unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+        let mut fetch_config = config(vec![], Some(cache_dir.path().to_path_buf()));
+        fetch_config.http_cache_ttl = Duration::from_secs(60);
+        let cached = ContentFetcher::new(fetch_config);
+        cached.fetch_http(&url).await.unwrap();
+        cached.fetch_http(&url).await.unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_uri() {
+        let (gateway, _) = spawn_gateway(vec![
+            ("/ipfs/QmMeta", b"{\"name\":\"ipfs\"}".as_slice()),
+            ("/arweave/tx1", b"<svg></svg>".as_slice()),
+            ("/token/1", b"{\"name\":\"http\"}".as_slice()),
+        ])
+        .await;
+        let mut fetch_config = config(vec![format!("{gateway}/ipfs/")], None);
+        fetch_config.arweave_gateways = vec![format!("{gateway}/arweave/")];
+        let fetcher = ContentFetcher::new(fetch_config);
+
+        for uri in ["ipfs://QmMeta", "ipfs://ipfs/QmMeta", "ipfs:/QmMeta"] {
+            let resolved = fetcher.resolve(uri).await.unwrap();
+            assert_eq!(resolved.content.as_ref(), b"{\"name\":\"ipfs\"}".as_slice());
+            assert_eq!(resolved.mime_type, None);
+        }
+
+        let resolved = fetcher.resolve("ar://tx1").await.unwrap();
+        assert!(resolved.is_svg());
+
+        let resolved = fetcher
+            .resolve(&format!("{gateway}/token/1"))
+            .await
+            .unwrap();
+        assert_eq!(resolved.content.as_ref(), b"{\"name\":\"http\"}".as_slice());
+
+        assert!(matches!(
+            fetcher.resolve("ipfs://").await,
+            Err(UriError::Malformed(_))
+        ));
+        assert!(matches!(
+            fetcher.resolve("ftp://example.com/1.json").await,
+            Err(UriError::UnsupportedScheme(_))
+        ));
+        assert!(matches!(
+            fetcher.resolve("ar://missing").await,
+            Err(UriError::Fetch(FetchError::GatewaysExhausted(_)))
+        ));
+    }
+
+    #[test]
+    fn test_decode_data_uri() {
+        let resolved =
+            decode_data_uri("data:application/json;base64,eyJuYW1lIjoiYmFzZTY0In0=").unwrap();
+        assert_eq!(resolved.mime_type.as_deref(), Some("application/json"));
+        assert_eq!(
+            resolved.content.as_ref(),
+            b"{\"name\":\"base64\"}".as_slice()
+        );
+
+        let resolved =
+            decode_data_uri(r#"data:application/json;charset=utf-8,{"name":"utf8 #1"}"#).unwrap();
+        assert_eq!(resolved.mime_type.as_deref(), Some("application/json"));
+        assert_eq!(
+            resolved.content.as_ref(),
+            br#"{"name":"utf8 #1"}"#.as_slice()
+        );
+
+        let resolved = decode_data_uri(
+            "data:image/svg+xml;utf8,<svg xmlns='http://www.w3.org/2000/svg'><rect fill='%23fff'/></svg>",
+        )
+        .unwrap();
+        assert!(resolved.is_svg());
+        assert!(resolved.content.ends_with(b"<rect fill='#fff'/></svg>"));
+
+        assert!(matches!(
+            decode_data_uri("data:application/json;base64,!!!"),
+            Err(UriError::InvalidBase64(_))
+        ));
+    }
+
+    #[test]
+    fn test_uri_from_felts() {
+        let uri = "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi/1.json";
+
+        let byte_array = ByteArray::from_string(uri).unwrap();
+        let felts = ByteArray::cairo_serialize(&byte_array);
+        assert_eq!(uri_from_felts(&felts).unwrap(), uri);
+
+        let short_strings = vec![
+            Felt::from_bytes_be_slice(b"https://example.com/"),
+            Felt::from_bytes_be_slice(b"token/1.json"),
+        ];
+        let felts = Vec::<Felt>::cairo_serialize(&short_strings);
+        assert_eq!(
+            uri_from_felts(&felts).unwrap(),
+            "https://example.com/token/1.json"
+        );
+
+        // Bare short strings without a length prefix
+        assert_eq!(
+            uri_from_felts(&short_strings).unwrap(),
+            "https://example.com/token/1.json"
+        );
+
+        assert!(matches!(
+            uri_from_felts(&[Felt::MAX]),
+            Err(UriError::InvalidFelts)
+        ));
+    }
+
+    #[test]
+    fn test_cache_keys() {
+        let cache = ContentCache::new("/cache");
+        assert_eq!(
+            cache.path("ipfs", "QmToken"),
+            PathBuf::from("/cache/ipfs/QmToken")
+        );
+
+        let hashed = cache.path("http", "https://example.com/1.json");
+        assert_eq!(hashed.parent().unwrap(), PathBuf::from("/cache/http"));
+        assert_eq!(hashed.file_name().unwrap().len(), 64);
+    }
+}
@@ -12,7 +12,9 @@ mod constants;
 pub mod erc;
 pub mod error;
 pub mod fetch;
+pub mod metadata_queue;
 pub mod processors;
+pub mod status;
 pub mod task_manager;
 
 use crate::error::Error;
@@ -48,6 +50,8 @@ pub struct EventProcessorConfig {
     pub metadata_updates: bool,
     pub metadata_update_whitelist: HashSet<Felt>,
     pub metadata_update_blacklist: HashSet<Felt>,
+    /// Defer token metadata fetching to the metadata queue workers.
+    pub metadata_queue: bool,
 }
 
 impl Default for EventProcessorConfig {
@@ -63,6 +67,7 @@ impl Default for EventProcessorConfig {
             metadata_updates: true,
             metadata_update_whitelist: HashSet::new(),
             metadata_update_blacklist: HashSet::new(),
+            metadata_queue: false,
         }
     }
 }
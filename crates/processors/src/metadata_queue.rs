@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use metrics::counter;
+use starknet::providers::Provider;
+use tokio::sync::broadcast::Receiver;
+use tokio::sync::Semaphore;
+use torii_proto::{MetadataJob, MetadataJobStatus, TokenId};
+use torii_storage::Storage;
+use tracing::{debug, error, info, warn};
+
+use crate::erc::{record_metadata_job_failure, try_fetch_token_metadata, update_contract_metadata};
+use crate::error::Error;
+
+const LOG_TARGET: &str = "torii::indexer::metadata_queue";
+
+#[derive(Debug, Clone)]
+pub struct MetadataQueueConfig {
+    /// Maximum number of metadata jobs processed concurrently.
+    pub workers: usize,
+    /// Interval at which due jobs are polled from the storage.
+    pub poll_interval: Duration,
+    /// Maximum number of due jobs fetched per poll.
+    pub batch_size: u32,
+}
+
+impl Default for MetadataQueueConfig {
+    fn default() -> Self {
+        Self {
+            workers: 10,
+            poll_interval: Duration::from_secs(1),
+            batch_size: 100,
+        }
+    }
+}
+
+/// Processes the persistent token metadata jobs in the background, so that slow metadata hosts
+/// don't hold back indexing.
+#[allow(missing_debug_implementations)]
+pub struct MetadataQueue<P: Provider + Send + Sync + Clone + 'static> {
+    storage: Arc<dyn Storage>,
+    provider: P,
+    config: MetadataQueueConfig,
+    semaphore: Arc<Semaphore>,
+    dispatched: Arc<Mutex<DispatchedJobs>>,
+}
+
+impl<P: Provider + Send + Sync + Clone + 'static> MetadataQueue<P> {
+    pub fn new(storage: Arc<dyn Storage>, provider: P, config: MetadataQueueConfig) -> Self {
+        Self {
+            storage,
+            provider,
+            semaphore: Arc::new(Semaphore::new(config.workers.max(1))),
+            config,
+            dispatched: Arc::new(Mutex::new(DispatchedJobs::default())),
+        }
+    }
+
+    pub async fn run(&self, mut shutdown_rx: Receiver<()>) -> Result<(), Error> {
+        info!(target: LOG_TARGET, workers = self.config.workers, "Starting metadata workers.");
+        let mut interval = tokio::time::interval(self.config.poll_interval);
+
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.recv() => {
+                    debug!(target: LOG_TARGET, "Shutting down metadata workers.");
+                    break Ok(());
+                }
+                _ = interval.tick() => {
+                    if let Err(e) = self.dispatch().await {
+                        error!(target: LOG_TARGET, error = ?e, "Dispatching metadata jobs.");
+                    }
+                }
+            }
+        }
+    }
+
+    async fn dispatch(&self) -> Result<(), Error> {
+        let jobs = self
+            .storage
+            .due_metadata_jobs(self.config.batch_size)
+            .await?;
+        self.dispatched
+            .lock()
+            .unwrap()
+            .prune(&jobs, jobs.len() < self.config.batch_size as usize);
+
+        for job in jobs {
+            if self.dispatched.lock().unwrap().contains(&job) {
+                continue;
+            }
+
+            // All workers are busy, the remaining jobs are picked up on the next poll
+            let Ok(permit) = self.semaphore.clone().try_acquire_owned() else {
+                break;
+            };
+
+            self.dispatched.lock().unwrap().start(&job);
+
+            let storage = self.storage.clone();
+            let provider = self.provider.clone();
+            let dispatched = self.dispatched.clone();
+            tokio::spawn(async move {
+                let _permit = permit;
+                if let Err(e) = process_job(storage, &provider, &job).await {
+                    error!(target: LOG_TARGET, error = ?e, token_id = %job.token_id, "Recording metadata job status.");
+                }
+                dispatched.lock().unwrap().finish(&job);
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Jobs dispatched to the workers, along with the state they were dispatched in.
+///
+/// The outcome of a job is committed along with the indexed events, so a finished job keeps
+/// looking due until the next commit. It is held back as long as the storage returns it in the
+/// state it was dispatched in, and released once that state changed or it's no longer due.
+#[derive(Debug, Default)]
+struct DispatchedJobs {
+    // Job id to the job as dispatched, and whether it finished
+    jobs: HashMap<String, (MetadataJob, bool)>,
+}
+
+impl DispatchedJobs {
+    fn contains(&self, job: &MetadataJob) -> bool {
+        self.jobs.contains_key(&job.token_id.to_string())
+    }
+
+    fn start(&mut self, job: &MetadataJob) {
+        self.jobs
+            .insert(job.token_id.to_string(), (job.clone(), false));
+    }
+
+    fn finish(&mut self, job: &MetadataJob) {
+        if let Some((_, finished)) = self.jobs.get_mut(&job.token_id.to_string()) {
+            *finished = true;
+        }
+    }
+
+    /// Releases the finished jobs whose outcome is committed, given the due jobs and whether
+    /// they are all of them.
+    fn prune(&mut self, due: &[MetadataJob], complete: bool) {
+        let due = due
+            .iter()
+            .map(|job| (job.token_id.to_string(), job))
+            .collect::<HashMap<_, _>>();
+        self.jobs.retain(|id, (dispatched, finished)| {
+            if !*finished {
+                return true;
+            }
+            match due.get(id) {
+                Some(job) => **job == *dispatched,
+                // A job past the due jobs fetched can still be uncommitted
+                None => !complete,
+            }
+        });
+    }
+}
+
+/// Refreshes the metadata of the job's token and records the outcome.
+async fn process_job<P: Provider + Sync>(
+    storage: Arc<dyn Storage>,
+    provider: &P,
+    job: &MetadataJob,
+) -> Result<(), Error> {
+    let result = match job.token_id {
+        TokenId::Nft(contract_address, token_id) => {
+            match try_fetch_token_metadata(contract_address, token_id, provider).await {
+                Ok(metadata) => storage
+                    .update_token_metadata(job.token_id.clone(), metadata)
+                    .await
+                    .map_err(Error::from),
+                Err(e) => Err(e.into()),
+            }
+        }
+        TokenId::Contract(contract_address) => {
+            update_contract_metadata(contract_address, provider, storage.clone()).await
+        }
+    };
+
+    match result {
+        Ok(()) => {
+            counter!("torii_metadata_jobs_total", "status" => "ok").increment(1);
+            debug!(target: LOG_TARGET, token_id = %job.token_id, "Refreshed token metadata.");
+            storage
+                .set_metadata_job_status(job.token_id.clone(), MetadataJobStatus::Ok, None, None)
+                .await?;
+        }
+        Err(e) => {
+            counter!("torii_metadata_jobs_total", "status" => "failed").increment(1);
+            warn!(
+                target: LOG_TARGET,
+                token_id = %job.token_id,
+                attempts = job.attempts + 1,
+                error = ?e,
+                "Failed to refresh token metadata."
+            );
+            record_metadata_job_failure(
+                storage.as_ref(),
+                job.token_id.clone(),
+                e.to_string(),
+                job.attempts,
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use starknet_crypto::Felt;
+
+    use super::*;
+
+    fn job(contract_address: u64, attempts: u32) -> MetadataJob {
+        MetadataJob {
+            token_id: TokenId::Contract(Felt::from(contract_address)),
+            status: MetadataJobStatus::Pending,
+            attempts,
+            last_error: None,
+            retry_at: None,
+            updated_at: Utc.timestamp_opt(1_700_000_000, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_finished_job_held_until_committed() {
+        let mut dispatched = DispatchedJobs::default();
+        let (a, b) = (job(1, 0), job(2, 0));
+        dispatched.start(&a);
+        dispatched.start(&b);
+
+        // In flight jobs are never released
+        dispatched.prune(&[], true);
+        assert!(dispatched.contains(&a) && dispatched.contains(&b));
+
+        // Finished but not committed yet, the storage still returns it as dispatched
+        dispatched.finish(&a);
+        dispatched.prune(&[a.clone(), b.clone()], true);
+        assert!(dispatched.contains(&a));
+
+        // The failure is committed and the job is due again for a retry
+        let retried = job(1, 1);
+        dispatched.prune(&[retried.clone(), b.clone()], true);
+        assert!(!dispatched.contains(&retried));
+        assert!(dispatched.contains(&b));
+    }
+
+    #[test]
+    fn test_finished_job_released_when_no_longer_due() {
+        let mut dispatched = DispatchedJobs::default();
+        let a = job(1, 0);
+        dispatched.start(&a);
+        dispatched.finish(&a);
+
+        // The due jobs were truncated, the job may still be uncommitted
+        dispatched.prune(&[job(2, 0)], false);
+        assert!(dispatched.contains(&a));
+
+        dispatched.prune(&[job(2, 0)], true);
+        assert!(!dispatched.contains(&a));
+    }
+}
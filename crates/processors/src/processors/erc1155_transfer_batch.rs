@@ -90,6 +90,7 @@ where
             let cache = ctx.cache.clone();
             let provider = ctx.provider.clone();
             let nft_metadata_semaphore = ctx.nft_metadata_semaphore.clone();
+            let metadata_queue = ctx.config.metadata_queue;
             let from_clone = from;
             let to_clone = to;
             let token_id_clone = *token_id;
@@ -111,6 +112,7 @@ where
                     cache.clone(),
                     storage.clone(),
                     nft_metadata_semaphore,
+                    metadata_queue,
                 )
                 .await?;
 
@@ -70,6 +70,7 @@ where
             ctx.cache.clone(),
             ctx.storage.clone(),
             ctx.nft_metadata_semaphore.clone(),
+            ctx.config.metadata_queue,
         )
         .await?;
 
@@ -5,10 +5,10 @@ use cainome::cairo_serde::{CairoSerde, U256 as U256Cainome};
 use futures_util::future::try_join_all;
 use starknet::core::types::{Event, U256};
 use starknet::providers::Provider;
-use torii_proto::TokenId;
-use tracing::debug;
+use torii_proto::{MetadataJobStatus, TokenId};
+use tracing::{debug, warn};
 
-use crate::erc::fetch_token_metadata;
+use crate::erc::{record_metadata_job_failure, try_fetch_token_metadata};
 use crate::error::{Error, TokenMetadataError};
 use crate::task_manager::TaskId;
 use crate::{EventProcessor, EventProcessorContext};
@@ -71,6 +71,22 @@ where
         let to_token_id = U256Cainome::cairo_deserialize(&ctx.event.keys, 3)?;
         let to_token_id = U256::from_words(to_token_id.low, to_token_id.high);
 
+        // Leave the refresh of the registered tokens to the metadata workers
+        if ctx.config.metadata_queue {
+            let mut ids = Vec::new();
+            let mut token_id = from_token_id;
+            while token_id <= to_token_id {
+                let id = TokenId::Nft(token_address, token_id);
+                if ctx.cache.is_token_registered(&id).await {
+                    ids.push(id);
+                }
+                token_id += U256::from(1u8);
+            }
+
+            ctx.storage.enqueue_metadata_jobs(ids).await?;
+            return Ok(());
+        }
+
         let mut tasks = Vec::new();
         let mut token_id = from_token_id;
 
@@ -87,10 +103,29 @@ where
                     .await
                     .map_err(TokenMetadataError::AcquireError)?;
 
-                let metadata =
-                    fetch_token_metadata(token_address_clone, current_token_id, &provider).await?;
                 let id = TokenId::Nft(token_address_clone, current_token_id);
-                storage.update_token_metadata(id, metadata).await?;
+                // Keep the current metadata if the new one can't be fetched, the job is retried
+                // later
+                match try_fetch_token_metadata(token_address_clone, current_token_id, &provider)
+                    .await
+                {
+                    Ok(metadata) => {
+                        storage.update_token_metadata(id.clone(), metadata).await?;
+                        storage
+                            .set_metadata_job_status(id, MetadataJobStatus::Ok, None, None)
+                            .await?;
+                    }
+                    Err(e) => {
+                        warn!(
+                            target: LOG_TARGET,
+                            token_address = ?token_address_clone,
+                            token_id = ?current_token_id,
+                            error = ?e,
+                            "Failed to fetch updated NFT metadata"
+                        );
+                        record_metadata_job_failure(storage.as_ref(), id, e.to_string(), 0).await?;
+                    }
+                }
                 Result::<_, Error>::Ok(())
             }));
 
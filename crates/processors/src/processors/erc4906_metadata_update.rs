@@ -4,13 +4,13 @@ use async_trait::async_trait;
 use cainome::cairo_serde::{CairoSerde, U256 as U256Cainome};
 use starknet::core::types::{Event, U256};
 use starknet::providers::Provider;
-use tracing::debug;
+use tracing::{debug, warn};
 
-use crate::erc::fetch_token_metadata;
+use crate::erc::{record_metadata_job_failure, try_fetch_token_metadata};
 use crate::error::{Error, TokenMetadataError};
 use crate::task_manager::TaskId;
 use crate::{EventProcessor, EventProcessorConfig, EventProcessorContext, IndexingMode};
-use torii_proto::TokenId;
+use torii_proto::{MetadataJobStatus, TokenId};
 
 pub(crate) const LOG_TARGET: &str = "torii::indexer::processors::erc4906_metadata_update";
 #[derive(Default, Debug)]
@@ -66,15 +66,41 @@ where
             return Ok(());
         }
 
+        // Leave the refresh to the metadata workers
+        if ctx.config.metadata_queue {
+            ctx.storage.enqueue_metadata_jobs(vec![id]).await?;
+            return Ok(());
+        }
+
         let _permit = ctx
             .nft_metadata_semaphore
             .acquire()
             .await
             .map_err(TokenMetadataError::AcquireError)?;
 
-        let metadata = fetch_token_metadata(token_address, token_id, &ctx.provider).await?;
-
-        ctx.storage.update_token_metadata(id, metadata).await?;
+        // Keep the current metadata if the new one can't be fetched, the job is retried later
+        let metadata = match try_fetch_token_metadata(token_address, token_id, &ctx.provider).await
+        {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                warn!(
+                    target: LOG_TARGET,
+                    token_address = ?token_address,
+                    token_id = ?token_id,
+                    error = ?e,
+                    "Failed to fetch updated NFT metadata"
+                );
+                return record_metadata_job_failure(ctx.storage.as_ref(), id, e.to_string(), 0)
+                    .await;
+            }
+        };
+
+        ctx.storage
+            .update_token_metadata(id.clone(), metadata)
+            .await?;
+        ctx.storage
+            .set_metadata_job_status(id, MetadataJobStatus::Ok, None, None)
+            .await?;
 
         debug!(
             target: LOG_TARGET,
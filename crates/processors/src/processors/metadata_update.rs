@@ -114,7 +114,7 @@ async fn metadata(
 
     let bytes = fetch_content_from_ipfs(cid)
         .await
-        .map_err(Error::IpfsError)?;
+        .map_err(Error::FetchError)?;
     let metadata: WorldMetadata =
         serde_json::from_str(std::str::from_utf8(&bytes).map_err(ParseError::Utf8Error)?)
             .map_err(ParseError::FromJsonStr)?;
@@ -0,0 +1,38 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Live state of the indexer, shared with the HTTP server to report its health and progress.
+#[derive(Debug, Default)]
+pub struct IndexerStatus {
+    pending_tasks: AtomicUsize,
+    executor_stopped: AtomicBool,
+    paused: AtomicBool,
+}
+
+impl IndexerStatus {
+    /// Number of parallelized tasks of the range being processed.
+    pub fn pending_tasks(&self) -> usize {
+        self.pending_tasks.load(Ordering::Relaxed)
+    }
+
+    pub fn set_pending_tasks(&self, count: usize) {
+        self.pending_tasks.store(count, Ordering::Relaxed);
+    }
+
+    /// Whether the executor still applies the writes of the indexer.
+    pub fn executor_alive(&self) -> bool {
+        !self.executor_stopped.load(Ordering::Relaxed)
+    }
+
+    pub fn set_executor_stopped(&self) {
+        self.executor_stopped.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the engine was paused and doesn't fetch new blocks.
+    pub fn paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+}
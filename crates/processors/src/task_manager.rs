@@ -9,7 +9,7 @@ use torii_cache::Cache;
 use torii_proto::ContractType;
 use torii_storage::Storage;
 use torii_task_network::TaskNetwork;
-use tracing::{debug, error};
+use tracing::{debug, error, info_span, Instrument};
 
 use crate::error::Error;
 use crate::processors::Processors;
@@ -152,6 +152,12 @@ impl<P: Provider + Send + Sync + Clone + std::fmt::Debug + 'static> TaskManager<
                 let event_processor_config = event_processor_config.clone();
                 let cache = cache.clone();
                 let nft_metadata_semaphore = nft_metadata_semaphore.clone();
+                let task_span = info_span!(
+                    target: LOG_TARGET,
+                    "task",
+                    task_id = %task_id,
+                    events = task_data.events.len() + task_data.latest_only_events.len()
+                );
 
                 async move {
                     // Process all events for this task sequentially
@@ -255,6 +261,7 @@ impl<P: Provider + Send + Sync + Clone + std::fmt::Debug + 'static> TaskManager<
 
                     Ok::<_, Error>(())
                 }
+                .instrument(task_span)
             })
             .await
             .map_err(Error::TaskNetworkError)?;
@@ -23,6 +23,12 @@ pub enum ProtoError {
     InvalidCallType(String),
     #[error("Invalid contract type: {0}")]
     InvalidContractType(String),
+    #[error("Invalid token id: {0}")]
+    InvalidTokenId(String),
+    #[error("Invalid balance id: {0}")]
+    InvalidBalanceId(String),
+    #[error("Invalid metadata job status: {0}")]
+    InvalidMetadataJobStatus(String),
     #[error("Failed to parse timestamp '{0}': {1}")]
     ParseTimestamp(String, ChronoParseError),
 }
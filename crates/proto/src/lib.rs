@@ -18,6 +18,7 @@ pub mod proto {
 }
 
 pub mod error;
+pub mod matching;
 pub mod schema;
 
 use core::fmt;
@@ -69,6 +70,45 @@ impl std::fmt::Display for TokenId {
     }
 }
 
+impl FromStr for TokenId {
+    type Err = ProtoError;
+
+    /// Parses `<contract_address>` or `<contract_address>:<token_id>`, as displayed.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None => Ok(TokenId::Contract(Felt::from_str(s)?)),
+            Some((contract_address, token_id)) => {
+                let token_id = token_id.trim_start_matches("0x");
+                if token_id.is_empty()
+                    || token_id.len() > 64
+                    || !token_id.chars().all(|c| c.is_ascii_hexdigit())
+                {
+                    return Err(ProtoError::InvalidTokenId(s.to_string()));
+                }
+
+                Ok(TokenId::Nft(
+                    Felt::from_str(contract_address)?,
+                    U256::from_be_hex(&format!("{:0>64}", token_id)).into(),
+                ))
+            }
+        }
+    }
+}
+
+// Serialized as displayed, so that they can be keys of JSON maps
+impl Serialize for TokenId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for TokenId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct BalanceId {
     pub account_address: Felt,
@@ -81,6 +121,35 @@ impl std::fmt::Display for BalanceId {
     }
 }
 
+impl FromStr for BalanceId {
+    type Err = ProtoError;
+
+    /// Parses `<account_address>/<token_id>`, as displayed.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (account_address, token_id) = s
+            .split_once('/')
+            .ok_or_else(|| ProtoError::InvalidBalanceId(s.to_string()))?;
+
+        Ok(BalanceId {
+            account_address: Felt::from_str(account_address)?,
+            token_id: token_id.parse()?,
+        })
+    }
+}
+
+impl Serialize for BalanceId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for BalanceId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 /// SQL query value types
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum SqlValue {
@@ -1025,6 +1094,7 @@ pub enum Clause {
     Keys(KeysClause),
     Member(MemberClause),
     Composite(CompositeClause),
+    Join(JoinClause),
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Hash, Eq, Clone)]
@@ -1061,6 +1131,26 @@ pub struct CompositeClause {
     pub clauses: Vec<Clause>,
 }
 
+/// Matches the entities whose `model.member` references an entity of `joined_model`, through its
+/// `joined_member`, that matches all the `clauses`. e.g. the positions whose `player` is a player
+/// of a given guild:
+///
+/// ```text
+/// JoinClause {
+///     model: "ns-Position", member: "player",
+///     joined_model: "ns-Player", joined_member: "player",
+///     clauses: [Member { model: "ns-Player", member: "guild", operator: Eq, .. }],
+/// }
+/// ```
+#[derive(Debug, Serialize, Deserialize, PartialEq, Hash, Eq, Clone)]
+pub struct JoinClause {
+    pub model: String,
+    pub member: String,
+    pub joined_model: String,
+    pub joined_member: String,
+    pub clauses: Vec<Clause>,
+}
+
 #[derive(
     Debug, AsRefStr, Serialize, Deserialize, EnumIter, FromRepr, PartialEq, Hash, Eq, Clone,
 )]
@@ -1068,6 +1158,8 @@ pub struct CompositeClause {
 pub enum LogicalOperator {
     And,
     Or,
+    // Negates the clauses, ANDed together
+    Not,
 }
 
 impl From<LogicalOperator> for proto::types::LogicalOperator {
@@ -1075,6 +1167,7 @@ impl From<LogicalOperator> for proto::types::LogicalOperator {
         match value {
             LogicalOperator::And => proto::types::LogicalOperator::And,
             LogicalOperator::Or => proto::types::LogicalOperator::Or,
+            LogicalOperator::Not => proto::types::LogicalOperator::Not,
         }
     }
 }
@@ -1084,6 +1177,7 @@ impl From<proto::types::LogicalOperator> for LogicalOperator {
         match value {
             proto::types::LogicalOperator::And => LogicalOperator::And,
             proto::types::LogicalOperator::Or => LogicalOperator::Or,
+            proto::types::LogicalOperator::Not => LogicalOperator::Not,
         }
     }
 }
@@ -1107,6 +1201,10 @@ pub enum ComparisonOperator {
     ArrayLengthEq, // Array length equals
     ArrayLengthGt, // Array length greater than
     ArrayLengthLt, // Array length less than
+    IsNull,        // Member is null
+    IsNotNull,     // Member is not null
+    Like,          // Case-insensitive pattern
+    Between,       // Member is within two values, inclusive
 }
 
 impl fmt::Display for ComparisonOperator {
@@ -1128,6 +1226,10 @@ impl fmt::Display for ComparisonOperator {
             ComparisonOperator::ArrayLengthEq => write!(f, "ARRAY_LENGTH_EQ"),
             ComparisonOperator::ArrayLengthGt => write!(f, "ARRAY_LENGTH_GT"),
             ComparisonOperator::ArrayLengthLt => write!(f, "ARRAY_LENGTH_LT"),
+            ComparisonOperator::IsNull => write!(f, "IS NULL"),
+            ComparisonOperator::IsNotNull => write!(f, "IS NOT NULL"),
+            ComparisonOperator::Like => write!(f, "LIKE"),
+            ComparisonOperator::Between => write!(f, "BETWEEN"),
         }
     }
 }
@@ -1149,6 +1251,10 @@ impl From<ComparisonOperator> for proto::types::ComparisonOperator {
             ComparisonOperator::ArrayLengthEq => proto::types::ComparisonOperator::ArrayLengthEq,
             ComparisonOperator::ArrayLengthGt => proto::types::ComparisonOperator::ArrayLengthGt,
             ComparisonOperator::ArrayLengthLt => proto::types::ComparisonOperator::ArrayLengthLt,
+            ComparisonOperator::IsNull => proto::types::ComparisonOperator::IsNull,
+            ComparisonOperator::IsNotNull => proto::types::ComparisonOperator::IsNotNull,
+            ComparisonOperator::Like => proto::types::ComparisonOperator::Like,
+            ComparisonOperator::Between => proto::types::ComparisonOperator::Between,
         }
     }
 }
@@ -1170,6 +1276,10 @@ impl From<proto::types::ComparisonOperator> for ComparisonOperator {
             proto::types::ComparisonOperator::ArrayLengthEq => ComparisonOperator::ArrayLengthEq,
             proto::types::ComparisonOperator::ArrayLengthGt => ComparisonOperator::ArrayLengthGt,
             proto::types::ComparisonOperator::ArrayLengthLt => ComparisonOperator::ArrayLengthLt,
+            proto::types::ComparisonOperator::IsNull => ComparisonOperator::IsNull,
+            proto::types::ComparisonOperator::IsNotNull => ComparisonOperator::IsNotNull,
+            proto::types::ComparisonOperator::Like => ComparisonOperator::Like,
+            proto::types::ComparisonOperator::Between => ComparisonOperator::Between,
         }
     }
 }
@@ -1376,6 +1486,9 @@ impl From<Clause> for proto::types::Clause {
             Clause::Composite(clause) => Self {
                 clause_type: Some(proto::types::clause::ClauseType::Composite(clause.into())),
             },
+            Clause::Join(clause) => Self {
+                clause_type: Some(proto::types::clause::ClauseType::Join(clause.into())),
+            },
         }
     }
 }
@@ -1402,6 +1515,7 @@ impl TryFrom<proto::types::Clause> for Clause {
             proto::types::clause::ClauseType::Composite(clause) => {
                 Ok(Clause::Composite(clause.try_into()?))
             }
+            proto::types::clause::ClauseType::Join(clause) => Ok(Clause::Join(clause.try_into()?)),
         }
     }
 }
@@ -1488,6 +1602,41 @@ impl TryFrom<proto::types::CompositeClause> for CompositeClause {
     }
 }
 
+impl From<JoinClause> for proto::types::JoinClause {
+    fn from(value: JoinClause) -> Self {
+        Self {
+            model: value.model,
+            member: value.member,
+            joined_model: value.joined_model,
+            joined_member: value.joined_member,
+            clauses: value
+                .clauses
+                .into_iter()
+                .map(|clause| clause.into())
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<proto::types::JoinClause> for JoinClause {
+    type Error = ProtoError;
+    fn try_from(value: proto::types::JoinClause) -> Result<Self, Self::Error> {
+        let clauses = value
+            .clauses
+            .into_iter()
+            .map(|clause| clause.try_into())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            model: value.model,
+            member: value.member,
+            joined_model: value.joined_model,
+            joined_member: value.joined_member,
+            clauses,
+        })
+    }
+}
+
 impl From<MemberValue> for proto::types::member_value::ValueType {
     fn from(value: MemberValue) -> Self {
         match value {
@@ -2460,3 +2609,329 @@ impl From<proto::types::SearchResponse> for SearchResponse {
         }
     }
 }
+
+// ===== Token Holder Types =====
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+pub struct TokenHolderStatsQuery {
+    pub contract_addresses: Vec<Felt>,
+    pub top_holders_limit: u32,
+    pub history_from: Option<u64>,
+    pub history_to: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct TokenHolder {
+    pub account_address: Felt,
+    pub balance: U256,
+    pub token_count: u32,
+    pub share: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+pub struct TokenHolderCountPoint {
+    pub timestamp: u64,
+    pub holder_count: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct TokenHolderStats {
+    pub contract_address: Felt,
+    pub holder_count: u64,
+    pub total_supply: Option<U256>,
+    pub total_held: U256,
+    pub top_holders: Vec<TokenHolder>,
+    pub top_holders_share: f64,
+    pub gini: f64,
+    pub holder_count_history: Vec<TokenHolderCountPoint>,
+}
+
+// ===== Token Holder Conversions =====
+
+impl From<TokenHolderStatsQuery> for proto::types::TokenHolderStatsQuery {
+    fn from(value: TokenHolderStatsQuery) -> Self {
+        Self {
+            contract_addresses: value
+                .contract_addresses
+                .into_iter()
+                .map(|a| a.to_bytes_be().into())
+                .collect(),
+            top_holders_limit: value.top_holders_limit,
+            history_from: value.history_from,
+            history_to: value.history_to,
+        }
+    }
+}
+
+impl TryFrom<proto::types::TokenHolderStatsQuery> for TokenHolderStatsQuery {
+    type Error = ProtoError;
+    fn try_from(value: proto::types::TokenHolderStatsQuery) -> Result<Self, Self::Error> {
+        Ok(Self {
+            contract_addresses: value
+                .contract_addresses
+                .into_iter()
+                .map(|a| Felt::from_bytes_be_slice(&a))
+                .collect(),
+            top_holders_limit: value.top_holders_limit,
+            history_from: value.history_from,
+            history_to: value.history_to,
+        })
+    }
+}
+
+impl From<TokenHolder> for proto::types::TokenHolder {
+    fn from(value: TokenHolder) -> Self {
+        Self {
+            account_address: value.account_address.to_bytes_be().into(),
+            balance: value.balance.to_be_bytes().to_vec(),
+            token_count: value.token_count,
+            share: value.share,
+        }
+    }
+}
+
+impl From<proto::types::TokenHolder> for TokenHolder {
+    fn from(value: proto::types::TokenHolder) -> Self {
+        Self {
+            account_address: Felt::from_bytes_be_slice(&value.account_address),
+            balance: U256::from_be_slice(&value.balance),
+            token_count: value.token_count,
+            share: value.share,
+        }
+    }
+}
+
+impl From<TokenHolderCountPoint> for proto::types::TokenHolderCountPoint {
+    fn from(value: TokenHolderCountPoint) -> Self {
+        Self {
+            timestamp: value.timestamp,
+            holder_count: value.holder_count,
+        }
+    }
+}
+
+impl From<proto::types::TokenHolderCountPoint> for TokenHolderCountPoint {
+    fn from(value: proto::types::TokenHolderCountPoint) -> Self {
+        Self {
+            timestamp: value.timestamp,
+            holder_count: value.holder_count,
+        }
+    }
+}
+
+impl From<TokenHolderStats> for proto::types::TokenHolderStats {
+    fn from(value: TokenHolderStats) -> Self {
+        Self {
+            contract_address: value.contract_address.to_bytes_be().into(),
+            holder_count: value.holder_count,
+            total_supply: value.total_supply.map(|s| s.to_be_bytes().to_vec()),
+            total_held: value.total_held.to_be_bytes().to_vec(),
+            top_holders: value.top_holders.into_iter().map(Into::into).collect(),
+            top_holders_share: value.top_holders_share,
+            gini: value.gini,
+            holder_count_history: value
+                .holder_count_history
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
+impl From<proto::types::TokenHolderStats> for TokenHolderStats {
+    fn from(value: proto::types::TokenHolderStats) -> Self {
+        Self {
+            contract_address: Felt::from_bytes_be_slice(&value.contract_address),
+            holder_count: value.holder_count,
+            total_supply: value.total_supply.map(|s| U256::from_be_slice(&s)),
+            total_held: U256::from_be_slice(&value.total_held),
+            top_holders: value.top_holders.into_iter().map(Into::into).collect(),
+            top_holders_share: value.top_holders_share,
+            gini: value.gini,
+            holder_count_history: value
+                .holder_count_history
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
+// ===== Entity Aggregate Types =====
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub enum AggregateFunction {
+    #[default]
+    Count,
+    Sum,
+    Min,
+    Max,
+    Avg,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Default)]
+pub struct EntityAggregateQuery {
+    /// The model whose entities are aggregated.
+    pub model: String,
+    /// Only the entities matching the clause are aggregated.
+    pub clause: Option<Clause>,
+    /// Members of the model the entities are grouped by.
+    pub group_by: Vec<String>,
+    pub function: AggregateFunction,
+    /// The member aggregated by the function, not needed to count the entities.
+    pub member: Option<String>,
+    pub world_addresses: Vec<Felt>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct EntityAggregate {
+    /// Values of the group by members, formatted like the member values.
+    pub group: Vec<String>,
+    /// Number of entities in the group.
+    pub count: u64,
+    /// Result of the function, formatted like the member values (hex for felts and large
+    /// integers), and in decimal for averages. `None` when counting.
+    pub value: Option<String>,
+}
+
+// ===== Entity Aggregate Conversions =====
+
+impl From<AggregateFunction> for proto::types::AggregateFunction {
+    fn from(value: AggregateFunction) -> Self {
+        match value {
+            AggregateFunction::Count => proto::types::AggregateFunction::Count,
+            AggregateFunction::Sum => proto::types::AggregateFunction::Sum,
+            AggregateFunction::Min => proto::types::AggregateFunction::Min,
+            AggregateFunction::Max => proto::types::AggregateFunction::Max,
+            AggregateFunction::Avg => proto::types::AggregateFunction::Avg,
+        }
+    }
+}
+
+impl From<proto::types::AggregateFunction> for AggregateFunction {
+    fn from(value: proto::types::AggregateFunction) -> Self {
+        match value {
+            proto::types::AggregateFunction::Count => AggregateFunction::Count,
+            proto::types::AggregateFunction::Sum => AggregateFunction::Sum,
+            proto::types::AggregateFunction::Min => AggregateFunction::Min,
+            proto::types::AggregateFunction::Max => AggregateFunction::Max,
+            proto::types::AggregateFunction::Avg => AggregateFunction::Avg,
+        }
+    }
+}
+
+impl From<EntityAggregateQuery> for proto::types::EntityAggregateQuery {
+    fn from(value: EntityAggregateQuery) -> Self {
+        Self {
+            model: value.model,
+            clause: value.clause.map(Into::into),
+            group_by: value.group_by,
+            function: proto::types::AggregateFunction::from(value.function) as i32,
+            member: value.member.unwrap_or_default(),
+            world_addresses: value
+                .world_addresses
+                .into_iter()
+                .map(|a| a.to_bytes_be().into())
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<proto::types::EntityAggregateQuery> for EntityAggregateQuery {
+    type Error = ProtoError;
+    fn try_from(value: proto::types::EntityAggregateQuery) -> Result<Self, Self::Error> {
+        let function = value.function().into();
+        Ok(Self {
+            model: value.model,
+            clause: value.clause.map(TryInto::try_into).transpose()?,
+            group_by: value.group_by,
+            function,
+            member: (!value.member.is_empty()).then_some(value.member),
+            world_addresses: value
+                .world_addresses
+                .into_iter()
+                .map(|a| Felt::from_bytes_be_slice(&a))
+                .collect(),
+        })
+    }
+}
+
+impl From<EntityAggregate> for proto::types::EntityAggregate {
+    fn from(value: EntityAggregate) -> Self {
+        Self {
+            group: value.group,
+            count: value.count,
+            value: value.value,
+        }
+    }
+}
+
+impl From<proto::types::EntityAggregate> for EntityAggregate {
+    fn from(value: proto::types::EntityAggregate) -> Self {
+        Self {
+            group: value.group,
+            count: value.count,
+            value: value.value,
+        }
+    }
+}
+
+// ===== Metadata Job Types =====
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MetadataJobStatus {
+    Pending,
+    Ok,
+    Failed,
+}
+
+impl MetadataJobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MetadataJobStatus::Pending => "pending",
+            MetadataJobStatus::Ok => "ok",
+            MetadataJobStatus::Failed => "failed",
+        }
+    }
+}
+
+impl fmt::Display for MetadataJobStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for MetadataJobStatus {
+    type Err = ProtoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(MetadataJobStatus::Pending),
+            "ok" => Ok(MetadataJobStatus::Ok),
+            "failed" => Ok(MetadataJobStatus::Failed),
+            _ => Err(ProtoError::InvalidMetadataJobStatus(s.to_string())),
+        }
+    }
+}
+
+/// Metadata refresh state of a token or token contract.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetadataJob {
+    pub token_id: TokenId,
+    pub status: MetadataJobStatus,
+    /// Number of consecutive failed attempts.
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    /// When a failed job is retried. `None` once the job gave up retrying.
+    pub retry_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MetadataJobQuery {
+    pub contract_addresses: Vec<Felt>,
+    pub statuses: Vec<MetadataJobStatus>,
+    /// Token id (as stored in the tokens table) to start after.
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+}
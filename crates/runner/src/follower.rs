@@ -0,0 +1,142 @@
+//! Follower mode, where Torii applies the change log of a primary instead of indexing the chain.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use tokio::sync::broadcast::Receiver;
+use tokio::sync::mpsc::UnboundedSender;
+use torii_cache::Cache;
+use torii_sqlite::executor::{QueryMessage, QueryType};
+use torii_sqlite::replication::{self, ReplicationBatch};
+use torii_storage::ReadOnlyStorage;
+use tracing::{debug, info, warn};
+
+use crate::constants::LOG_TARGET;
+
+#[derive(Debug, Deserialize)]
+struct Changes {
+    head: i64,
+    batches: Vec<ReplicationBatch>,
+}
+
+#[derive(Debug)]
+pub struct Follower {
+    client: reqwest::Client,
+    changes_url: String,
+    api_key: Option<String>,
+    pool: SqlitePool,
+    executor: UnboundedSender<QueryMessage>,
+    // Without cache, to reload the models registered by the primary into the cache
+    storage: Arc<dyn ReadOnlyStorage>,
+    cache: Arc<dyn Cache>,
+    poll_interval: Duration,
+    batch_size: u32,
+}
+
+impl Follower {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        primary: &str,
+        api_key: Option<String>,
+        pool: SqlitePool,
+        executor: UnboundedSender<QueryMessage>,
+        storage: Arc<dyn ReadOnlyStorage>,
+        cache: Arc<dyn Cache>,
+        poll_interval: Duration,
+        batch_size: u32,
+    ) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            changes_url: format!("{}/replication/changes", primary.trim_end_matches('/')),
+            api_key,
+            pool,
+            executor,
+            storage,
+            cache,
+            poll_interval,
+            batch_size,
+        }
+    }
+
+    /// Applies the changes of the primary until shutdown. Returns an error if the changes the
+    /// follower needs were pruned from the log of the primary, or if a batch fails to apply, as
+    /// it can't catch up anymore.
+    pub async fn run(self, mut shutdown_rx: Receiver<()>) -> anyhow::Result<()> {
+        let mut applied = replication::applied_id(&self.pool).await?;
+        info!(target: LOG_TARGET, primary = %self.changes_url, applied = applied, "Following primary.");
+
+        loop {
+            let caught_up = match self.fetch(applied).await {
+                Ok(Some(changes)) => {
+                    let caught_up = changes.batches.len() < self.batch_size as usize;
+                    for batch in changes.batches {
+                        applied = self.apply(batch).await?;
+                    }
+                    debug!(target: LOG_TARGET, applied = applied, head = changes.head, "Applied changes.");
+                    caught_up
+                }
+                Ok(None) => {
+                    return Err(anyhow::anyhow!(
+                        "The primary pruned the changes after batch {}, bootstrap this follower \
+                         again from a snapshot",
+                        applied
+                    ));
+                }
+                Err(e) => {
+                    warn!(target: LOG_TARGET, error = ?e, "Fetching changes from primary.");
+                    true
+                }
+            };
+
+            if caught_up {
+                tokio::select! {
+                    _ = shutdown_rx.recv() => return Ok(()),
+                    _ = tokio::time::sleep(self.poll_interval) => {}
+                }
+            }
+        }
+    }
+
+    /// The batches after `after`, none if they were pruned.
+    async fn fetch(&self, after: i64) -> anyhow::Result<Option<Changes>> {
+        let mut request = self.client.get(&self.changes_url).query(&[
+            ("after", after.to_string()),
+            ("limit", self.batch_size.to_string()),
+        ]);
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+
+        let response = request.send().await?;
+        if response.status() == reqwest::StatusCode::GONE {
+            return Ok(None);
+        }
+
+        Ok(Some(response.error_for_status()?.json().await?))
+    }
+
+    async fn apply(&self, batch: ReplicationBatch) -> anyhow::Result<i64> {
+        let id = batch.id;
+        let registers_model = batch
+            .queries
+            .iter()
+            .any(|query| matches!(query.query_type, QueryType::RegisterModel));
+
+        replication::apply(&self.executor, batch)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to apply batch {} of the primary: {}", id, e))?;
+
+        // The models are served from the cache, which the indexer fills as it registers them
+        if registers_model {
+            for model in self.storage.models(&[], &[]).await? {
+                self.cache
+                    .register_model(model.world_address, model.selector, model)
+                    .await;
+            }
+        }
+
+        Ok(id)
+    }
+}
@@ -12,8 +12,7 @@
 
 use std::cmp;
 use std::collections::HashSet;
-use std::fmt::Debug;
-use std::net::SocketAddr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 use std::path::Path;
 use std::str::FromStr;
 use std::sync::Arc;
@@ -22,12 +21,11 @@ use std::time::Duration;
 use camino::Utf8PathBuf;
 use dojo_metrics::exporters::prometheus::PrometheusRecorder;
 use dojo_types::naming::try_compute_selector_from_tag;
-use futures::future::join_all;
+use futures::future::{join_all, select_all, OptionFuture};
 use sqlx::sqlite::{
     SqliteAutoVacuum, SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous,
 };
 use sqlx::Executor as SqlxExecutor;
-use sqlx::SqlitePool;
 use starknet::core::types::{BlockId, BlockTag};
 use starknet::providers::jsonrpc::HttpTransport;
 use starknet::providers::{JsonRpcClient, Provider};
@@ -37,31 +35,44 @@ use terminal_size::{terminal_size, Height, Width};
 use tokio::fs::File;
 use tokio::io::AsyncWriteExt;
 use tokio::sync::broadcast;
-use tokio::sync::broadcast::Sender;
 use tokio_stream::StreamExt;
+use torii_admin::{Admin, LogFilter};
+use torii_auth::{AuthConfig, Authenticator};
+use torii_broker::bridge;
 use torii_broker::types::ModelUpdate;
-use torii_broker::MemoryBroker;
-use torii_cache::InMemoryCache;
+use torii_broker::{MemoryBroker, NatsBroker};
+use torii_cache::{Cache, InMemoryCache};
 use torii_cli::ToriiArgs;
 use torii_controllers::sync::ControllersSync;
+use torii_graphql::limits::QueryLimits;
+use torii_graphql::persisted_queries::PersistedQueries;
+use torii_graphql::schema::SchemaConfig;
 use torii_grpc_server::GrpcConfig;
 use torii_indexer::engine::{Engine, EngineConfig};
 use torii_indexer::{FetcherConfig, FetchingFlags, IndexingFlags};
 use torii_libp2p_relay::Relay;
 use torii_messaging::{Messaging, MessagingConfig};
+use torii_processors::fetch::{init_fetcher, FetchConfig};
+use torii_processors::metadata_queue::{MetadataQueue, MetadataQueueConfig};
+use torii_processors::status::IndexerStatus;
 use torii_processors::{EventProcessorConfig, Processors};
 use torii_server::proxy::{Proxy, ProxySettings};
+use torii_server::StaticConfig;
+use torii_sqlite::constants::DATABASE_FILE;
 use torii_sqlite::executor::Executor;
+use torii_sqlite::sandbox::{SandboxConfig, SqlSandbox};
+use torii_sqlite::snapshot::{SnapshotManifest, MANIFEST_SUFFIX};
 use torii_sqlite::{Sql, SqlConfig};
 use torii_storage::proto::{ContractDefinition, ContractType};
-use torii_storage::ReadOnlyStorage;
 use tracing::{debug, error, info, info_span, warn, Instrument, Span};
 use tracing_indicatif::span_ext::IndicatifSpanExt;
 use url::form_urlencoded;
 
 mod constants;
+mod follower;
 
 use crate::constants::LOG_TARGET;
+use crate::follower::Follower;
 const MIN_THREADS: usize = 1;
 
 #[derive(Debug, Clone)]
@@ -248,11 +259,22 @@ fn create_progress_bar_template() -> String {
 pub struct Runner {
     args: ToriiArgs,
     version_spec: String,
+    log_filter: Option<LogFilter>,
 }
 
 impl Runner {
     pub fn new(args: ToriiArgs, version_spec: String) -> Self {
-        Self { args, version_spec }
+        Self {
+            args,
+            version_spec,
+            log_filter: None,
+        }
+    }
+
+    /// Lets the admin API change the log filter of the process at runtime.
+    pub fn with_log_filter(mut self, log_filter: LogFilter) -> Self {
+        self.log_filter = Some(log_filter);
+        self
     }
 
     pub async fn run(mut self) -> anyhow::Result<()> {
@@ -291,18 +313,37 @@ impl Runner {
         );
         let provider: Arc<_> = JsonRpcClient::new(transport).into();
 
-        // Check provider spec version. We only support v0.9.
-        let supported_spec = "0.9";
-        let spec_version = provider.spec_version().await?;
-        if !spec_version.starts_with(supported_spec) {
+        // A follower applies the changes of its primary and a relay serves the database of an
+        // indexer running elsewhere, neither of them index and they barely need the RPC
+        let primary = self.args.replication.primary.clone();
+        if primary.is_some() && self.args.broker.relay {
             return Err(anyhow::anyhow!(
-                "Provider spec version is not supported. Please use a provider that supports v{supported_spec}. Got: {spec_version}. You might need to add a `rpc/v{}` to the end of the URL.",
-                supported_spec.replace('.', "_")
+                "--replication.primary and --broker.relay are exclusive, a follower publishes \
+                 the updates of its primary itself"
             ));
         }
+        let relay = self.args.broker.relay;
+        if relay && self.args.db_dir.is_none() {
+            return Err(anyhow::anyhow!(
+                "--broker.relay requires --db-dir, the database of the indexer it serves"
+            ));
+        }
+        let indexing = primary.is_none() && !relay;
+
+        // Check provider spec version. We only support v0.9.
+        let supported_spec = "0.9";
+        if indexing {
+            let spec_version = provider.spec_version().await?;
+            if !spec_version.starts_with(supported_spec) {
+                return Err(anyhow::anyhow!(
+                    "Provider spec version is not supported. Please use a provider that supports v{supported_spec}. Got: {spec_version}. You might need to add a `rpc/v{}` to the end of the URL.",
+                    supported_spec.replace('.', "_")
+                ));
+            }
+        }
 
         // Verify contracts are deployed
-        if self.args.runner.check_contracts {
+        if self.args.runner.check_contracts && indexing {
             let undeployed =
                 verify_contracts_deployed(&provider, &self.args.indexing.contracts).await?;
             if !undeployed.is_empty() {
@@ -318,7 +359,7 @@ impl Runner {
             // Create the directory if it doesn't exist
             std::fs::create_dir_all(db_dir)?;
             // Set the database file path inside the directory
-            db_dir.join("torii.db")
+            db_dir.join(DATABASE_FILE)
         } else {
             tempfile.path().to_path_buf()
         };
@@ -330,8 +371,27 @@ impl Runner {
             if self.args.db_dir.is_none() || !database_path.exists() {
                 info!(target: LOG_TARGET, url = %snapshot_url, path = %database_path.display(), "Downloading snapshot...");
 
+                let client = reqwest::Client::new();
+                let manifest_url = self
+                    .args
+                    .snapshot
+                    .manifest
+                    .clone()
+                    .unwrap_or_else(|| format!("{}{}", snapshot_url, MANIFEST_SUFFIX));
+                // A manifest given explicitly must be there, the default one may be missing
+                let manifest = fetch_snapshot_manifest(
+                    &manifest_url,
+                    &client,
+                    self.args.snapshot.manifest.is_some(),
+                )
+                .await?;
+
                 // Check for version mismatch
-                if let Some(snapshot_version) = self.args.snapshot.version {
+                if let Some(snapshot_version) = manifest
+                    .as_ref()
+                    .map(|manifest| manifest.version.clone())
+                    .or(self.args.snapshot.version)
+                {
                     if snapshot_version != self.version_spec {
                         warn!(
                             target: LOG_TARGET,
@@ -342,15 +402,36 @@ impl Runner {
                     }
                 }
 
-                let client = reqwest::Client::new();
+                let compressed = manifest
+                    .as_ref()
+                    .map_or(snapshot_url.ends_with(".gz"), |manifest| {
+                        manifest.compression == "gzip"
+                    });
+                let download_path = if compressed {
+                    database_path.with_extension("db.gz")
+                } else {
+                    database_path.clone()
+                };
+
                 if let Err(e) =
-                    stream_snapshot_into_file(&snapshot_url, &database_path, &client).await
+                    stream_snapshot_into_file(&snapshot_url, &download_path, &client).await
                 {
                     error!(target: LOG_TARGET, error = ?e, "Failed to download snapshot.");
                     // Decide if we should exit or continue with a fresh DB
                     // For now, let's exit as the user explicitly requested a snapshot.
                     return Err(e);
                 }
+
+                if manifest.is_none() {
+                    warn!(target: LOG_TARGET, url = %manifest_url, "Snapshot has no manifest, it is not verified.");
+                }
+                install_snapshot(
+                    &download_path,
+                    &database_path,
+                    manifest.as_ref(),
+                    compressed,
+                )
+                .await?;
                 info!(target: LOG_TARGET, "Snapshot downloaded successfully.");
             } else {
                 error!(target: LOG_TARGET, "A database already exists at the given path. If you want to download a new snapshot, please delete the existing database file or provide a different path.");
@@ -371,7 +452,7 @@ impl Runner {
         );
 
         let mut options = SqliteConnectOptions::from_str(&database_path.to_string_lossy())?
-            .create_if_missing(true)
+            .create_if_missing(!relay)
             .with_regexp();
 
         // Optimize SQLite threading for parallelizable operations
@@ -389,7 +470,8 @@ impl Runner {
         // Use NORMAL for better performance during heavy indexing
         // FULL would be safer but much slower for writes
         options = options.synchronous(SqliteSynchronous::Normal);
-        options = options.optimize_on_close(true, None);
+        // The statistics of the database of a relay are gathered by the indexer writing it
+        options = options.optimize_on_close(!relay, None);
 
         // Performance tuning based on workload
         options = options.pragma("cache_size", self.args.sql.cache_size.to_string());
@@ -424,19 +506,26 @@ impl Runner {
             self.args.sql.journal_size_limit.to_string(),
         );
 
-        // Write pool: NO memory limits - critical for indexing performance
-        let write_pool = SqlitePoolOptions::new()
-            .min_connections(1)
-            .max_connections(1)
-            .acquire_timeout(Duration::from_millis(self.args.sql.acquire_timeout))
-            .idle_timeout(Some(Duration::from_millis(self.args.sql.idle_timeout)))
-            .connect_with(options.clone())
-            .await?;
+        // Write pool: NO memory limits - critical for indexing performance. A relay only reads
+        // the database, the indexer it serves migrates and writes it.
+        let write_pool = if relay {
+            None
+        } else {
+            let write_pool = SqlitePoolOptions::new()
+                .min_connections(1)
+                .max_connections(1)
+                .acquire_timeout(Duration::from_millis(self.args.sql.acquire_timeout))
+                .idle_timeout(Some(Duration::from_millis(self.args.sql.idle_timeout)))
+                .connect_with(options.clone())
+                .await?;
 
-        // Aggressive WAL cleanup
-        write_pool
-            .execute("PRAGMA wal_checkpoint(TRUNCATE);")
-            .await?;
+            // Aggressive WAL cleanup
+            write_pool
+                .execute("PRAGMA wal_checkpoint(TRUNCATE);")
+                .await?;
+
+            Some(write_pool)
+        };
 
         // Readonly pool
         let readonly_options = options.read_only(true);
@@ -455,42 +544,44 @@ impl Runner {
             .connect_with(readonly_options)
             .await?;
 
-        let mut migrate_handle = write_pool.acquire().await?;
-        if let Some(migrations) = self.args.sql.migrations {
-            // Create a temporary directory to combine migrations
-            let temp_migrations = TempDir::new()?;
-
-            // Copy default migrations first
-            let default_migrations_dir =
-                std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../migrations");
-            for entry in std::fs::read_dir(default_migrations_dir)? {
-                let entry = entry?;
-                let target = temp_migrations.path().join(entry.file_name());
-                std::fs::copy(entry.path(), target)?;
-            }
+        if let Some(write_pool) = &write_pool {
+            let mut migrate_handle = write_pool.acquire().await?;
+            if let Some(migrations) = self.args.sql.migrations {
+                // Create a temporary directory to combine migrations
+                let temp_migrations = TempDir::new()?;
+
+                // Copy default migrations first
+                let default_migrations_dir =
+                    std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../migrations");
+                for entry in std::fs::read_dir(default_migrations_dir)? {
+                    let entry = entry?;
+                    let target = temp_migrations.path().join(entry.file_name());
+                    std::fs::copy(entry.path(), target)?;
+                }
 
-            // Copy custom migrations
-            for entry in std::fs::read_dir(&migrations)? {
-                let entry = entry?;
-                let target = temp_migrations.path().join(entry.file_name());
-                std::fs::copy(entry.path(), target)?;
+                // Copy custom migrations
+                for entry in std::fs::read_dir(&migrations)? {
+                    let entry = entry?;
+                    let target = temp_migrations.path().join(entry.file_name());
+                    std::fs::copy(entry.path(), target)?;
+                }
+
+                // Run combined migrations
+                let migrator = sqlx::migrate::Migrator::new(temp_migrations.path()).await?;
+                migrator.run(&mut migrate_handle).await?;
+            } else {
+                sqlx::migrate!("../migrations")
+                    .run(&mut migrate_handle)
+                    .await?;
             }
 
-            // Run combined migrations
-            let migrator = sqlx::migrate::Migrator::new(temp_migrations.path()).await?;
-            migrator.run(&mut migrate_handle).await?;
-        } else {
-            sqlx::migrate!("../migrations")
-                .run(&mut migrate_handle)
+            // Optimize database after schema changes (migrations/indexes)
+            sqlx::query("PRAGMA optimize")
+                .execute(&mut *migrate_handle)
                 .await?;
-        }
 
-        // Optimize database after schema changes (migrations/indexes)
-        sqlx::query("PRAGMA optimize")
-            .execute(&mut *migrate_handle)
-            .await?;
-
-        drop(migrate_handle);
+            drop(migrate_handle);
+        }
 
         if self.args.sql.all_model_indices && !self.args.sql.model_indices.is_empty() {
             warn!(
@@ -568,28 +659,61 @@ impl Runner {
             search_prefix_matching: self.args.search.prefix_matching,
             search_return_snippets: self.args.search.return_snippets,
             search_snippet_length: self.args.search.snippet_length,
+            replication_log: self.args.replication.log,
+            replication_log_retention: self.args.replication.retention,
         };
 
-        let (mut executor, sender) = Executor::new_with_config(
-            write_pool.clone(),
-            shutdown_tx.clone(),
-            provider.clone(),
-            sql_config.clone(),
-            database_path.clone(),
-        )
-        .await?;
-        let executor_handle = tokio::spawn(async move { executor.run().await });
-
-        let db = Sql::new_with_config(
-            readonly_pool.clone(),
-            sender.clone(),
-            &self.args.indexing.contracts,
-            sql_config.clone(),
-        )
-        .await?;
-        let cache = Arc::new(InMemoryCache::new(Arc::new(db.clone())).await.unwrap());
+        let indexer_status = Arc::new(IndexerStatus::default());
+        let (db, executor_handle) = match write_pool {
+            Some(write_pool) => {
+                let (mut executor, sender) = Executor::new_with_config(
+                    write_pool,
+                    shutdown_tx.clone(),
+                    provider.clone(),
+                    sql_config.clone(),
+                    database_path.clone(),
+                )
+                .await?;
+                let executor_status = indexer_status.clone();
+                let executor_handle = tokio::spawn(async move {
+                    let result = executor.run().await;
+                    executor_status.set_executor_stopped();
+                    result
+                });
+
+                let db = Sql::new_with_config(
+                    readonly_pool.clone(),
+                    sender,
+                    &self.args.indexing.contracts,
+                    sql_config.clone(),
+                )
+                .await?;
+                (db, Some(executor_handle))
+            }
+            // The contracts are registered by the indexer
+            None => (
+                Sql::new_read_only(readonly_pool.clone(), sql_config.clone()),
+                None,
+            ),
+        };
+        let uncached_storage = Arc::new(db.clone());
+        let cache = Arc::new(InMemoryCache::new(uncached_storage.clone()).await.unwrap());
         let db = db.with_cache(cache.clone());
 
+        let follower = primary.as_ref().map(|primary| {
+            info!(target: LOG_TARGET, primary = %primary, "Follower mode, the engine is not started.");
+            Follower::new(
+                primary,
+                self.args.replication.api_key.clone(),
+                readonly_pool.clone(),
+                db.executor.clone(),
+                uncached_storage,
+                cache.clone(),
+                Duration::from_millis(self.args.replication.poll_interval),
+                self.args.replication.batch_size,
+            )
+        });
+
         let processors = Arc::new(Processors::default());
 
         let mut indexing_flags = IndexingFlags::empty();
@@ -605,7 +729,7 @@ impl Runner {
         }
 
         let storage = Arc::new(db.clone());
-        let controllers = if self.args.indexing.controllers {
+        let controllers = if self.args.indexing.controllers && indexing {
             Some(Arc::new(
                 ControllersSync::new(storage.clone()).await.unwrap(),
             ))
@@ -631,57 +755,66 @@ impl Runner {
             "Runtime allocation calculated"
         );
 
-        let mut engine: Engine<Arc<JsonRpcClient<HttpTransport>>> = Engine::new_with_controllers(
-            storage.clone(),
-            cache.clone(),
-            provider.clone(),
-            processors.clone(),
-            EngineConfig {
-                max_concurrent_tasks: optimal_concurrent_tasks,
-                fetcher_config: FetcherConfig {
-                    batch_chunk_size: self.args.indexing.batch_chunk_size,
-                    blocks_chunk_size: self.args.indexing.blocks_chunk_size,
-                    events_chunk_size: self.args.indexing.events_chunk_size,
+        // Without an engine to apply them, the commands fail as soon as they are sent
+        let (admin, commands) = Admin::new(self.log_filter.take());
+        let admin = Arc::new(admin);
+
+        let engine = indexing.then(|| {
+            Engine::<Arc<JsonRpcClient<HttpTransport>>>::new_with_controllers(
+                storage.clone(),
+                cache.clone(),
+                provider.clone(),
+                processors.clone(),
+                EngineConfig {
+                    max_concurrent_tasks: optimal_concurrent_tasks,
+                    fetcher_config: FetcherConfig {
+                        batch_chunk_size: self.args.indexing.batch_chunk_size,
+                        blocks_chunk_size: self.args.indexing.blocks_chunk_size,
+                        events_chunk_size: self.args.indexing.events_chunk_size,
+                        world_block: self.args.indexing.world_block,
+                        flags: fetching_flags,
+                    },
+                    polling_interval: Duration::from_millis(self.args.indexing.polling_interval),
+                    flags: indexing_flags,
+                    event_processor_config: EventProcessorConfig {
+                        strict_model_reader: self.args.indexing.strict_model_reader,
+                        namespaces: self.args.indexing.namespaces.into_iter().collect(),
+                        historical_models,
+                        max_metadata_tasks: self.args.erc.max_metadata_tasks,
+                        models: self.args.indexing.models.clone().into_iter().collect(),
+                        external_contracts: self.args.indexing.external_contracts,
+                        external_contract_whitelist: self
+                            .args
+                            .indexing
+                            .external_contract_whitelist
+                            .clone()
+                            .into_iter()
+                            .collect(),
+                        metadata_updates: self.args.erc.metadata_updates,
+                        metadata_update_whitelist: self
+                            .args
+                            .erc
+                            .metadata_update_whitelist
+                            .iter()
+                            .filter_map(|s| Felt::from_hex(s.trim()).ok())
+                            .collect(),
+                        metadata_update_blacklist: self
+                            .args
+                            .erc
+                            .metadata_update_blacklist
+                            .iter()
+                            .filter_map(|s| Felt::from_hex(s.trim()).ok())
+                            .collect(),
+                        metadata_queue: self.args.erc.metadata_queue,
+                    },
                     world_block: self.args.indexing.world_block,
-                    flags: fetching_flags,
-                },
-                polling_interval: Duration::from_millis(self.args.indexing.polling_interval),
-                flags: indexing_flags,
-                event_processor_config: EventProcessorConfig {
-                    strict_model_reader: self.args.indexing.strict_model_reader,
-                    namespaces: self.args.indexing.namespaces.into_iter().collect(),
-                    historical_models,
-                    max_metadata_tasks: self.args.erc.max_metadata_tasks,
-                    models: self.args.indexing.models.clone().into_iter().collect(),
-                    external_contracts: self.args.indexing.external_contracts,
-                    external_contract_whitelist: self
-                        .args
-                        .indexing
-                        .external_contract_whitelist
-                        .clone()
-                        .into_iter()
-                        .collect(),
-                    metadata_updates: self.args.erc.metadata_updates,
-                    metadata_update_whitelist: self
-                        .args
-                        .erc
-                        .metadata_update_whitelist
-                        .iter()
-                        .filter_map(|s| Felt::from_hex(s.trim()).ok())
-                        .collect(),
-                    metadata_update_blacklist: self
-                        .args
-                        .erc
-                        .metadata_update_blacklist
-                        .iter()
-                        .filter_map(|s| Felt::from_hex(s.trim()).ok())
-                        .collect(),
                 },
-                world_block: self.args.indexing.world_block,
-            },
-            shutdown_tx.clone(),
-            controllers,
-        );
+                shutdown_tx.clone(),
+                controllers,
+            )
+            .with_status(indexer_status.clone())
+            .with_commands(commands)
+        });
 
         let shutdown_rx = shutdown_tx.subscribe();
         let temp_dir = TempDir::new()?;
@@ -694,6 +827,43 @@ impl Runner {
         tokio::fs::create_dir_all(&artifacts_path).await?;
         let absolute_path = artifacts_path.canonicalize_utf8()?;
 
+        // Metadata and images share the same gateways and content-addressed cache.
+        let cache_path = self
+            .args
+            .erc
+            .cache_path
+            .clone()
+            .unwrap_or_else(|| absolute_path.join("cache"));
+        let default_fetch_config = FetchConfig::default();
+        init_fetcher(FetchConfig {
+            ipfs_gateways: if self.args.erc.ipfs_gateways.is_empty() {
+                default_fetch_config.ipfs_gateways
+            } else {
+                self.args.erc.ipfs_gateways.clone()
+            },
+            arweave_gateways: if self.args.erc.arweave_gateways.is_empty() {
+                default_fetch_config.arweave_gateways
+            } else {
+                self.args.erc.arweave_gateways.clone()
+            },
+            gateway_timeout: Duration::from_millis(self.args.erc.gateway_timeout),
+            ipfs_api_fallback: self.args.erc.ipfs_api_fallback,
+            cache_dir: Some(cache_path.into_std_path_buf()),
+            http_cache_ttl: Duration::from_secs(self.args.erc.http_cache_ttl),
+        });
+
+        // Only an indexer fetches the metadata, a follower replicates the one of its primary
+        let metadata_queue = indexing.then(|| {
+            MetadataQueue::new(
+                storage.clone(),
+                provider.clone(),
+                MetadataQueueConfig {
+                    workers: self.args.erc.metadata_workers,
+                    ..Default::default()
+                },
+            )
+        });
+
         // Create messaging instance with configuration
         let messaging_config = MessagingConfig {
             max_age: self.args.messaging.max_age,
@@ -717,6 +887,27 @@ impl Runner {
         )
         .expect("Failed to start libp2p relay server");
 
+        let sql_sandbox_config = SandboxConfig {
+            max_rows: self.args.sql.query_max_rows,
+            max_bytes: self.args.sql.query_max_bytes,
+            timeout: Duration::from_millis(self.args.sql.query_timeout),
+            stream_max_rows: self.args.sql.stream_max_rows,
+            stream_timeout: Duration::from_millis(self.args.sql.stream_timeout),
+            max_concurrent_queries: self.args.sql.query_max_concurrent,
+        };
+
+        let authenticator = Arc::new(Authenticator::new(AuthConfig {
+            api_keys: self.args.auth.api_keys.clone(),
+            jwks_path: self.args.auth.jwks.clone(),
+            jwt_issuer: self.args.auth.jwt_issuer.clone(),
+            jwt_audience: self.args.auth.jwt_audience.clone(),
+            default_rate_limit: self.args.auth.default_rate_limit,
+            anonymous: self.args.auth.anonymous.clone(),
+        })?);
+        if authenticator.is_enabled() {
+            info!(target: LOG_TARGET, api_keys = self.args.auth.api_keys.len(), jwt = self.args.auth.jwks.is_some(), "Authentication enabled.");
+        }
+
         let grpc_bind_addr = SocketAddr::new(self.args.grpc.grpc_addr, self.args.grpc.grpc_port);
         let (grpc_addr, grpc_server) = torii_grpc_server::new(
             shutdown_rx,
@@ -735,11 +926,49 @@ impl Runner {
                     self.args.grpc.http2_keepalive_timeout,
                 ),
                 max_message_size: self.args.grpc.max_message_size,
+                sql_sandbox: sql_sandbox_config.clone(),
+                // The HTTP server proxies the gRPC requests from the loopback interface
+                trusted_proxies: [
+                    IpAddr::V4(Ipv4Addr::LOCALHOST),
+                    IpAddr::V6(Ipv6Addr::LOCALHOST),
+                ]
+                .into_iter()
+                .chain(self.args.grpc.trusted_proxies.iter().copied())
+                .collect(),
             },
+            authenticator.clone(),
+            admin.clone(),
             Some(grpc_bind_addr),
         )
         .await?;
 
+        let persisted_queries = match &self.args.graphql.persisted_queries {
+            Some(path) => {
+                let persisted_queries =
+                    PersistedQueries::from_file(path, self.args.graphql.persisted_queries_only)?;
+                info!(target: LOG_TARGET, queries = persisted_queries.len(), "Loaded persisted GraphQL queries.");
+                Some(persisted_queries)
+            }
+            None => None,
+        };
+        let graphql_config = SchemaConfig {
+            limits: QueryLimits {
+                max_depth: Some(self.args.graphql.max_depth).filter(|depth| *depth > 0),
+                max_cost: Some(self.args.graphql.max_cost).filter(|cost| *cost > 0),
+            },
+            persisted_queries,
+        };
+
+        // A single GraphQL server, its schema is swapped in place as models get registered
+        let (graphql_addr, graphql_server) = torii_graphql::server::new(
+            shutdown_tx.subscribe(),
+            &readonly_pool,
+            messaging.clone(),
+            storage.clone(),
+            graphql_config,
+        )
+        .await;
+
         let addr = SocketAddr::new(self.args.server.http_addr, self.args.server.http_port);
 
         let mut proxy_server = Proxy::new(
@@ -749,17 +978,28 @@ impl Runner {
                 .http_cors_origins
                 .filter(|cors_origins| !cors_origins.is_empty()),
             Some(grpc_addr),
-            None,
+            Some(graphql_addr),
             absolute_path.clone(),
             Arc::new(readonly_pool.clone()),
+            Arc::new(SqlSandbox::new(readonly_pool.clone(), sql_sandbox_config)),
             storage.clone(),
             provider.clone(),
+            indexer_status,
+            authenticator,
+            admin,
             self.version_spec.clone(),
             ProxySettings {
                 tcp_keepalive_interval: self.args.grpc.tcp_keepalive_interval,
                 http2_keepalive_interval: self.args.grpc.http2_keepalive_interval,
                 http2_keepalive_timeout: self.args.grpc.http2_keepalive_timeout,
             },
+            StaticConfig {
+                max_artifacts_size: self.args.erc.artifacts_max_size * 1024 * 1024,
+                default_quality: self.args.erc.image_quality,
+                cache_max_age: self.args.erc.image_cache_max_age,
+            },
+            self.args.snapshot.dir.clone(),
+            self.args.replication.log,
         );
 
         // Handle mkcert certificate generation
@@ -806,14 +1046,6 @@ impl Runner {
 
         let proxy_server = Arc::new(proxy_server);
 
-        let graphql_server = spawn_rebuilding_graphql_server(
-            shutdown_tx.clone(),
-            readonly_pool.into(),
-            proxy_server.clone(),
-            messaging.clone(),
-            storage.clone(),
-        );
-
         let protocol = if final_cert_path.is_some() && final_key_path.is_some() {
             "https"
         } else {
@@ -854,14 +1086,68 @@ impl Runner {
             tokio::spawn(server.start(addr));
         }
 
+        // Share the updates with other processes through the broker, or relay the ones of the
+        // indexer writing the database
+        let broker_handles = match &self.args.broker.url {
+            Some(url) => {
+                let broker =
+                    Arc::new(NatsBroker::connect(url, self.args.broker.prefix.clone()).await?);
+                info!(target: LOG_TARGET, url = %url, relay = self.args.broker.relay, "Connected to broker.");
+                if self.args.broker.relay {
+                    // The models are served from the cache, which the indexer fills as it
+                    // registers them
+                    let cache = cache.clone();
+                    tokio::spawn(async move {
+                        let mut models = MemoryBroker::<ModelUpdate>::subscribe();
+                        while let Some(model) = models.next().await {
+                            cache
+                                .register_model(model.world_address, model.selector, model)
+                                .await;
+                        }
+                    });
+                    bridge::relay_all(broker)
+                } else {
+                    bridge::forward_all(broker)
+                }
+            }
+            None => Vec::new(),
+        };
+        let broker_handle = (!broker_handles.is_empty()).then(|| {
+            tokio::spawn(async move {
+                let (result, _, _) = select_all(broker_handles).await;
+                result?.map_err(anyhow::Error::from)
+            })
+        });
+
         // Create dedicated runtimes
         let query_runtime = create_query_runtime(allocation.query_threads);
         let indexer_runtime = create_indexer_runtime(allocation.indexer_threads);
 
-        // Move engine to dedicated indexer runtime for CPU isolation
-        let engine_handle = indexer_runtime
-            .handle()
-            .spawn(async move { engine.start().await });
+        // Move engine to dedicated indexer runtime for CPU isolation. A follower applies the
+        // changes of its primary in its place, and a relay has neither.
+        let engine_handle = match (engine, follower) {
+            (Some(mut engine), _) => Some(
+                indexer_runtime
+                    .handle()
+                    .spawn(async move { engine.start().await.map_err(anyhow::Error::from) }),
+            ),
+            (None, Some(follower)) => {
+                let follower_shutdown_rx = shutdown_tx.subscribe();
+                Some(
+                    indexer_runtime
+                        .handle()
+                        .spawn(async move { follower.run(follower_shutdown_rx).await }),
+                )
+            }
+            (None, None) => None,
+        };
+
+        let metadata_queue_handle = metadata_queue.map(|metadata_queue| {
+            let metadata_queue_shutdown_rx = shutdown_tx.subscribe();
+            indexer_runtime
+                .handle()
+                .spawn(async move { metadata_queue.run(metadata_queue_shutdown_rx).await })
+        });
 
         let proxy_server_handle =
             tokio::spawn(async move { proxy_server.start(shutdown_tx.subscribe()).await });
@@ -891,14 +1177,17 @@ impl Runner {
             };
         }
 
-        // Wait for shutdown signal or any task completion
+        // Wait for shutdown signal or any task completion. The branches of the tasks which
+        // weren't spawned are disabled.
         let result = tokio::select! {
-            res = engine_handle => handle_task!(res, "Engine"),
-            res = executor_handle => handle_task!(res, "Executor"),
+            Some(res) = OptionFuture::from(engine_handle) => handle_task!(res, "Engine"),
+            Some(res) = OptionFuture::from(executor_handle) => handle_task!(res, "Executor"),
+            Some(res) = OptionFuture::from(metadata_queue_handle) => handle_task!(res, "Metadata queue"),
             res = proxy_server_handle => handle_task!(res, "Proxy server"),
             res = graphql_server_handle => handle_task!(res, "GraphQL server", void),
             res = grpc_server_handle => handle_task!(res, "gRPC server"),
             res = libp2p_relay_server_handle => handle_task!(res, "LibP2P relay", void),
+            Some(res) = OptionFuture::from(broker_handle) => handle_task!(res, "Broker"),
             _ = dojo_utils::signal::wait_signals() => {
                 info!(target: LOG_TARGET, "Shutdown signal received, cleaning up...");
                 Ok(())
@@ -914,34 +1203,6 @@ impl Runner {
     }
 }
 
-async fn spawn_rebuilding_graphql_server<P: Provider + Sync + Send + Clone + Debug + 'static>(
-    shutdown_tx: Sender<()>,
-    pool: Arc<SqlitePool>,
-    proxy_server: Arc<Proxy<P>>,
-    messaging: Arc<Messaging<P>>,
-    storage: Arc<dyn ReadOnlyStorage>,
-) {
-    let mut broker = MemoryBroker::<ModelUpdate>::subscribe();
-
-    loop {
-        let shutdown_rx = shutdown_tx.subscribe();
-        let (new_addr, new_server) =
-            torii_graphql::server::new(shutdown_rx, &pool, messaging.clone(), storage.clone())
-                .await;
-
-        tokio::spawn(new_server);
-
-        proxy_server.set_graphql_addr(new_addr).await;
-
-        // Break the loop if there are no more events
-        if broker.next().await.is_none() {
-            break;
-        } else {
-            tokio::time::sleep(Duration::from_secs(1)).await;
-        }
-    }
-}
-
 async fn verify_contracts_deployed(
     provider: &JsonRpcClient<HttpTransport>,
     contracts: &[ContractDefinition],
@@ -972,6 +1233,65 @@ async fn verify_contracts_deployed(
     Ok(undeployed)
 }
 
+/// The manifest of the snapshot to download. A missing manifest is an error only if `required`,
+/// otherwise the snapshot is downloaded without being verified.
+async fn fetch_snapshot_manifest(
+    url: &str,
+    client: &reqwest::Client,
+    required: bool,
+) -> anyhow::Result<Option<SnapshotManifest>> {
+    let response = match client.get(url).send().await {
+        Ok(response) => response.error_for_status(),
+        Err(e) => Err(e),
+    };
+    let manifest = match response {
+        Ok(response) => response.json().await,
+        Err(e) => Err(e),
+    };
+
+    match manifest {
+        Ok(manifest) => Ok(Some(manifest)),
+        Err(e) if required => {
+            Err(anyhow::Error::new(e).context(format!("Failed to fetch snapshot manifest {url}")))
+        }
+        Err(e) => {
+            warn!(target: LOG_TARGET, error = ?e, url = %url, "Fetching snapshot manifest.");
+            Ok(None)
+        }
+    }
+}
+
+/// Checks a downloaded snapshot against its manifest and writes its database to
+/// `database_path`. A snapshot which doesn't match is removed.
+async fn install_snapshot(
+    download_path: &Path,
+    database_path: &Path,
+    manifest: Option<&SnapshotManifest>,
+    compressed: bool,
+) -> anyhow::Result<()> {
+    if let Some(manifest) = manifest {
+        if let Err(e) = manifest.verify(download_path) {
+            error!(target: LOG_TARGET, error = %e, "Snapshot doesn't match its manifest.");
+            let _ = std::fs::remove_file(download_path);
+            return Err(e.into());
+        }
+        for cursor in &manifest.cursors {
+            info!(target: LOG_TARGET, contract = %cursor.contract_address, head = ?cursor.head, "Snapshot cursor.");
+        }
+    }
+
+    if compressed {
+        let (source, destination) = (download_path.to_path_buf(), database_path.to_path_buf());
+        tokio::task::spawn_blocking(move || {
+            torii_sqlite::snapshot::decompress(&source, &destination)
+        })
+        .await??;
+        std::fs::remove_file(download_path)?;
+    }
+
+    Ok(())
+}
+
 /// Streams a snapshot into a file, displaying progress and handling potential errors.
 ///
 /// # Arguments
@@ -1079,3 +1399,74 @@ async fn generate_mkcert_certificates() -> anyhow::Result<(String, String)> {
         key_path.to_string_lossy().to_string(),
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use sqlx::SqlitePool;
+    use torii_sqlite::snapshot;
+
+    use super::*;
+
+    #[test]
+    fn test_gateway_timeout_default() {
+        // The CLI mirrors the default of the fetcher, it doesn't depend on the processors
+        assert_eq!(
+            Duration::from_millis(torii_cli::options::DEFAULT_ERC_GATEWAY_TIMEOUT),
+            torii_processors::fetch::DEFAULT_GATEWAY_TIMEOUT
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_snapshot_manifest() {
+        // Nothing listens there
+        let url = "http://127.0.0.1:1/torii.db.gz.manifest.json";
+        let client = reqwest::Client::new();
+
+        assert!(fetch_snapshot_manifest(url, &client, false)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(fetch_snapshot_manifest(url, &client, true).await.is_err());
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_install_snapshot(pool: SqlitePool) {
+        sqlx::query(
+            "INSERT INTO contracts (id, contract_address, contract_type, head) VALUES ('0x1', \
+             '0x1', 'WORLD', 42)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let (path, manifest) = snapshot::create(&pool, dir.path(), "1.0.0").await.unwrap();
+        let database_path = dir.path().join(DATABASE_FILE);
+
+        // A snapshot which doesn't match its manifest is removed
+        let download_path = database_path.with_extension("db.gz");
+        std::fs::write(&download_path, b"tampered").unwrap();
+        assert!(
+            install_snapshot(&download_path, &database_path, Some(&manifest), true)
+                .await
+                .is_err()
+        );
+        assert!(!download_path.exists());
+        assert!(!database_path.exists());
+
+        std::fs::copy(&path, &download_path).unwrap();
+        install_snapshot(&download_path, &database_path, Some(&manifest), true)
+            .await
+            .unwrap();
+        assert!(!download_path.exists());
+
+        let restored = SqlitePool::connect(&database_path.to_string_lossy())
+            .await
+            .unwrap();
+        let head: i64 = sqlx::query_scalar("SELECT head FROM contracts")
+            .fetch_one(&restored)
+            .await
+            .unwrap();
+        assert_eq!(head, 42);
+    }
+}
@@ -0,0 +1,212 @@
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use hashlink::LinkedHashMap;
+use tracing::{debug, warn};
+
+pub(crate) const LOG_TARGET: &str = "torii::server::artifacts";
+
+/// Keeps the artifacts directory under a size budget, evicting the images of the least recently
+/// served tokens first.
+///
+/// Entries are image directories: `contract_address` for contract images and
+/// `contract_address/token_id` for token images. Only the files directly inside an entry count
+/// towards its size, so evicting a contract image leaves its token images in place. Anything else
+/// in the artifacts directory, like the fetch cache, is left to its owner.
+#[derive(Debug)]
+pub struct ArtifactsLru {
+    max_size: u64,
+    state: Mutex<LruState>,
+}
+
+#[derive(Debug, Default)]
+struct LruState {
+    // Least recently used first
+    entries: LinkedHashMap<Utf8PathBuf, u64>,
+    total_size: u64,
+}
+
+impl ArtifactsLru {
+    /// Indexes the images already in the artifacts directory, ordered by their last access.
+    pub fn new(root: &Utf8Path, max_size: u64) -> Self {
+        let mut dirs = Vec::new();
+        // Image directories are named after the hex addresses and token IDs
+        for contract_dir in read_dirs(root)
+            .into_iter()
+            .filter(|dir| dir.file_name().is_some_and(|name| name.starts_with("0x")))
+        {
+            for token_dir in read_dirs(&contract_dir) {
+                let (size, accessed) = dir_usage(&token_dir);
+                dirs.push((token_dir, size, accessed));
+            }
+            let (size, accessed) = dir_usage(&contract_dir);
+            dirs.push((contract_dir, size, accessed));
+        }
+        dirs.sort_by_key(|(_, _, accessed)| *accessed);
+
+        let mut state = LruState::default();
+        for (dir, size, _) in dirs.into_iter().filter(|(_, size, _)| *size > 0) {
+            state.total_size += size;
+            state.entries.insert(dir, size);
+        }
+        debug!(target: LOG_TARGET, entries = state.entries.len(), total_size = state.total_size, max_size, "Indexed artifacts.");
+
+        Self {
+            max_size,
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Marks the images of a directory as recently served.
+    pub fn touch(&self, dir: &Utf8Path) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(size) = state.entries.remove(dir) {
+            state.entries.insert(dir.to_path_buf(), size);
+        }
+    }
+
+    /// Records the new size of a directory after images were written to it, and evicts the least
+    /// recently served directories if the artifacts went over budget.
+    pub async fn record(&self, dir: &Utf8Path) {
+        let (size, _) = dir_usage(dir);
+
+        let evicted = {
+            let mut state = self.state.lock().unwrap();
+            if let Some(previous) = state.entries.remove(dir) {
+                state.total_size -= previous;
+            }
+            state.total_size += size;
+            state.entries.insert(dir.to_path_buf(), size);
+
+            let mut evicted = Vec::new();
+            // Never evict the directory that was just written
+            while state.total_size > self.max_size && state.entries.len() > 1 {
+                let (victim, victim_size) = state.entries.pop_front().unwrap();
+                state.total_size -= victim_size;
+                evicted.push(victim);
+            }
+            evicted
+        };
+
+        for victim in evicted {
+            debug!(target: LOG_TARGET, dir = %victim, "Evicting artifacts.");
+            if let Err(e) = remove_dir_files(&victim).await {
+                warn!(target: LOG_TARGET, error = ?e, dir = %victim, "Failed to evict artifacts.");
+            }
+        }
+    }
+}
+
+/// Removes the files directly inside a directory, and the directory itself if it's left empty.
+pub(crate) async fn remove_dir_files(dir: &Utf8Path) -> std::io::Result<()> {
+    let mut entries = match tokio::fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+
+    while let Some(entry) = entries.next_entry().await? {
+        if entry.file_type().await?.is_file() {
+            tokio::fs::remove_file(entry.path()).await?;
+        }
+    }
+
+    // Fails if the directory still holds token images, which is fine
+    let _ = tokio::fs::remove_dir(dir).await;
+    Ok(())
+}
+
+fn read_dirs(dir: &Utf8Path) -> Vec<Utf8PathBuf> {
+    dir.read_dir_utf8()
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_type().map(|t| t.is_dir()).unwrap_or(false))
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+/// Returns the total size of the files directly inside a directory and their last access time.
+fn dir_usage(dir: &Utf8Path) -> (u64, SystemTime) {
+    let mut size = 0;
+    let mut accessed = SystemTime::UNIX_EPOCH;
+
+    for entry in dir.read_dir_utf8().into_iter().flatten().flatten() {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if !metadata.is_file() {
+            continue;
+        }
+
+        size += metadata.len();
+        if let Ok(time) = metadata.accessed().or_else(|_| metadata.modified()) {
+            accessed = accessed.max(time);
+        }
+    }
+
+    (size, accessed)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn write_image(dir: &Utf8Path, size: usize) {
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(dir.join("image.png"), vec![0u8; size]).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_evicts_least_recently_served() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = Utf8Path::from_path(temp_dir.path()).unwrap();
+        let (a, b, c) = (
+            root.join("0xa"),
+            root.join("0xb").join("0x1"),
+            root.join("0xc"),
+        );
+        // The fetch cache shares the artifacts directory by default
+        let cache = root.join("cache").join("ipfs");
+        std::fs::create_dir_all(&cache).unwrap();
+        std::fs::write(cache.join("Qm"), vec![0u8; 100]).unwrap();
+
+        let lru = ArtifactsLru::new(root, 250);
+        write_image(&a, 100);
+        lru.record(&a).await;
+        write_image(&b, 100);
+        lru.record(&b).await;
+        lru.touch(&a);
+
+        // Over budget, the least recently served image goes
+        write_image(&c, 100);
+        lru.record(&c).await;
+        assert!(a.join("image.png").exists());
+        assert!(!b.exists());
+        assert!(c.join("image.png").exists());
+        assert!(cache.join("Qm").exists());
+    }
+
+    #[tokio::test]
+    async fn test_indexes_existing_images_only() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = Utf8Path::from_path(temp_dir.path()).unwrap();
+        let cache = root.join("cache").join("ipfs");
+        std::fs::create_dir_all(&cache).unwrap();
+        std::fs::write(cache.join("Qm"), vec![0u8; 1000]).unwrap();
+        write_image(&root.join("0xa"), 100);
+
+        let lru = ArtifactsLru::new(root, 150);
+        assert_eq!(lru.state.lock().unwrap().total_size, 100);
+
+        // Evicting the existing image never touches the cache
+        let b = root.join("0xb");
+        write_image(&b, 100);
+        lru.record(&b).await;
+        assert!(!root.join("0xa").exists());
+        assert!(cache.join("Qm").exists());
+    }
+}
@@ -0,0 +1,418 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use std::{fmt::Debug, net::IpAddr};
+
+use http::{Method, Request, Response, StatusCode};
+use hyper::Body;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use starknet_crypto::Felt;
+use torii_admin::{Admin, AdminError, EngineCommand};
+use torii_auth::Capability;
+use torii_storage::proto::{ContractDefinition, ContractType};
+use tracing::info;
+
+use super::Handler;
+
+const LOG_TARGET: &str = "torii::server::handlers::admin";
+
+const ADMIN_PREFIX: &str = "/admin/";
+
+#[derive(Debug, Deserialize)]
+struct AddContractBody {
+    address: Felt,
+    r#type: String,
+    #[serde(default)]
+    starting_block: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReindexBody {
+    from_block: u64,
+    to_block: u64,
+    #[serde(default)]
+    contracts: Vec<Felt>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LogFilterBody {
+    directives: String,
+}
+
+/// Runtime operations of the indexer and of the subscriptions, under `/admin/`. Requires the
+/// `admin` capability.
+#[derive(Debug)]
+pub struct AdminHandler {
+    admin: Arc<Admin>,
+}
+
+impl AdminHandler {
+    pub fn new(admin: Arc<Admin>) -> Self {
+        Self { admin }
+    }
+
+    async fn execute(&self, command: EngineCommand) -> Response<Body> {
+        info!(target: LOG_TARGET, command = ?command, "Executing admin command.");
+        match self.admin.execute(command).await {
+            Ok(()) => json_response(StatusCode::OK, json!({ "success": true })),
+            Err(e) => admin_error(e),
+        }
+    }
+
+    async fn add_contract(&self, req: Request<Body>) -> Response<Body> {
+        let body: AddContractBody = match json_body(req).await {
+            Ok(body) => body,
+            Err(response) => return response,
+        };
+        let r#type = match ContractType::from_str(&body.r#type) {
+            Ok(r#type) => r#type,
+            Err(e) => return json_error(StatusCode::BAD_REQUEST, &e.to_string()),
+        };
+
+        self.execute(EngineCommand::AddContract(ContractDefinition {
+            address: body.address,
+            r#type,
+            starting_block: body.starting_block,
+        }))
+        .await
+    }
+
+    async fn reindex(&self, req: Request<Body>) -> Response<Body> {
+        let body: ReindexBody = match json_body(req).await {
+            Ok(body) => body,
+            Err(response) => return response,
+        };
+
+        self.execute(EngineCommand::ReindexRange {
+            from_block: body.from_block,
+            to_block: body.to_block,
+            contracts: body.contracts,
+        })
+        .await
+    }
+
+    fn log_filter(&self) -> Response<Body> {
+        json_response(
+            StatusCode::OK,
+            json!({ "directives": self.admin.log_filter() }),
+        )
+    }
+
+    async fn set_log_filter(&self, req: Request<Body>) -> Response<Body> {
+        let body: LogFilterBody = match json_body(req).await {
+            Ok(body) => body,
+            Err(response) => return response,
+        };
+
+        info!(target: LOG_TARGET, directives = %body.directives, "Setting log filter.");
+        match self.admin.set_log_filter(&body.directives) {
+            Ok(()) => self.log_filter(),
+            Err(e) => admin_error(e),
+        }
+    }
+
+    fn subscriptions(&self) -> Response<Body> {
+        json_response(
+            StatusCode::OK,
+            json!({ "subscriptions": self.admin.subscriptions() }),
+        )
+    }
+
+    fn kill_subscription(&self, kind: &str, id: &str) -> Response<Body> {
+        let id = match id.parse() {
+            Ok(id) => id,
+            Err(_) => return json_error(StatusCode::BAD_REQUEST, "Invalid subscription id"),
+        };
+
+        info!(target: LOG_TARGET, kind = %kind, id = %id, "Killing subscription.");
+        match self.admin.kill_subscription(kind, id) {
+            Ok(()) => json_response(StatusCode::OK, json!({ "success": true })),
+            Err(e) => admin_error(e),
+        }
+    }
+}
+
+async fn json_body<T: for<'de> Deserialize<'de>>(req: Request<Body>) -> Result<T, Response<Body>> {
+    let bytes = hyper::body::to_bytes(req.into_body())
+        .await
+        .map_err(|_| json_error(StatusCode::BAD_REQUEST, "Failed to read request body"))?;
+    serde_json::from_slice(&bytes)
+        .map_err(|e| json_error(StatusCode::BAD_REQUEST, &format!("Invalid body: {}", e)))
+}
+
+fn parse_address(address: &str) -> Result<Felt, Response<Body>> {
+    Felt::from_hex(address).map_err(|e| {
+        json_error(
+            StatusCode::BAD_REQUEST,
+            &format!("Invalid contract address: {}", e),
+        )
+    })
+}
+
+fn admin_error(error: AdminError) -> Response<Body> {
+    let status = match error {
+        AdminError::EngineStopped => StatusCode::SERVICE_UNAVAILABLE,
+        AdminError::Busy => StatusCode::TOO_MANY_REQUESTS,
+        AdminError::NoLogFilter => StatusCode::NOT_IMPLEMENTED,
+        AdminError::UnknownSubscription(..) => StatusCode::NOT_FOUND,
+        AdminError::Command(_) | AdminError::InvalidLogFilter(_) => StatusCode::CONFLICT,
+    };
+    json_error(status, &error.to_string())
+}
+
+fn json_response(status: StatusCode, body: Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn json_error(status: StatusCode, message: &str) -> Response<Body> {
+    json_response(status, json!({ "error": message }))
+}
+
+#[async_trait::async_trait]
+impl Handler for AdminHandler {
+    fn should_handle(&self, req: &Request<Body>) -> bool {
+        req.uri().path().starts_with(ADMIN_PREFIX)
+    }
+
+    fn capability(&self, _req: &Request<Body>) -> Option<Capability> {
+        Some(Capability::Admin)
+    }
+
+    async fn handle(&self, req: Request<Body>, _client_addr: IpAddr) -> Response<Body> {
+        let path = req.uri().path().trim_end_matches('/').to_string();
+        let segments = path
+            .strip_prefix(ADMIN_PREFIX)
+            .unwrap_or_default()
+            .split('/')
+            .collect::<Vec<_>>();
+
+        match (req.method().clone(), segments.as_slice()) {
+            (Method::POST, ["contracts"]) => self.add_contract(req).await,
+            (Method::DELETE, ["contracts", address]) => match parse_address(address) {
+                Ok(address) => self.execute(EngineCommand::RemoveContract(address)).await,
+                Err(response) => response,
+            },
+            (Method::POST, ["metadata", "refresh", address]) => match parse_address(address) {
+                Ok(address) => self.execute(EngineCommand::RefreshMetadata(address)).await,
+                Err(response) => response,
+            },
+            (Method::POST, ["search", "rebuild"]) => {
+                self.execute(EngineCommand::RebuildSearchIndex).await
+            }
+            (Method::POST, ["reindex"]) => self.reindex(req).await,
+            (Method::POST, ["pause"]) => self.execute(EngineCommand::Pause).await,
+            (Method::POST, ["resume"]) => self.execute(EngineCommand::Resume).await,
+            (Method::GET, ["log"]) => self.log_filter(),
+            (Method::PUT, ["log"]) => self.set_log_filter(req).await,
+            (Method::GET, ["subscriptions"]) => self.subscriptions(),
+            (Method::DELETE, ["subscriptions", kind, id]) => self.kill_subscription(kind, id),
+            _ => json_error(StatusCode::NOT_FOUND, "Unknown admin endpoint"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+    use std::sync::Mutex;
+
+    use torii_admin::{SubscriptionInfo, Subscriptions};
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct TestSubscriptions;
+
+    impl Subscriptions for TestSubscriptions {
+        fn list(&self) -> Vec<SubscriptionInfo> {
+            vec![SubscriptionInfo {
+                id: 1,
+                kind: "entities".to_string(),
+            }]
+        }
+
+        fn kill(&self, kind: &str, id: u64) -> bool {
+            kind == "entities" && id == 1
+        }
+    }
+
+    /// A handler whose engine records the commands, and refuses to remove contracts.
+    fn handler() -> (AdminHandler, Arc<Mutex<Vec<EngineCommand>>>) {
+        let (admin, mut receiver) = Admin::new(None);
+        admin.register_subscriptions(Arc::new(TestSubscriptions));
+
+        let commands = Arc::new(Mutex::new(vec![]));
+        let applied = commands.clone();
+        tokio::spawn(async move {
+            while let Some((command, reply)) = receiver.recv().await {
+                let outcome = match command {
+                    EngineCommand::RemoveContract(_) => Err("Contract is not indexed".to_string()),
+                    _ => Ok(()),
+                };
+                applied.lock().unwrap().push(command);
+                reply.send(outcome).unwrap();
+            }
+        });
+
+        (AdminHandler::new(Arc::new(admin)), commands)
+    }
+
+    async fn request(
+        handler: &AdminHandler,
+        method: Method,
+        path: &str,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let body = body.map_or_else(Body::empty, |body| Body::from(body.to_string()));
+        let req = Request::builder()
+            .method(method)
+            .uri(path)
+            .body(body)
+            .unwrap();
+        assert!(handler.should_handle(&req));
+        assert_eq!(handler.capability(&req), Some(Capability::Admin));
+
+        let response = handler.handle(req, IpAddr::V4(Ipv4Addr::LOCALHOST)).await;
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_commands() {
+        let (handler, commands) = handler();
+
+        let (status, _) = request(
+            &handler,
+            Method::POST,
+            "/admin/contracts",
+            Some(json!({ "address": "0x1", "type": "ERC20", "starting_block": 10 })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, _) = request(
+            &handler,
+            Method::POST,
+            "/admin/contracts",
+            Some(json!({ "address": "0x1", "type": "UNKNOWN" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, body) = request(&handler, Method::DELETE, "/admin/contracts/0x1", None).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["error"], "Contract is not indexed");
+
+        let (status, _) = request(&handler, Method::DELETE, "/admin/contracts/zz", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        for path in [
+            "/admin/metadata/refresh/0x2",
+            "/admin/search/rebuild",
+            "/admin/pause",
+            "/admin/resume/",
+        ] {
+            let (status, body) = request(&handler, Method::POST, path, None).await;
+            assert_eq!(status, StatusCode::OK, "{path}");
+            assert_eq!(body["success"], true);
+        }
+
+        let (status, _) = request(
+            &handler,
+            Method::POST,
+            "/admin/reindex",
+            Some(json!({ "from_block": 1, "to_block": 2, "contracts": ["0x3"] })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, _) = request(&handler, Method::POST, "/admin/unknown", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        assert_eq!(
+            *commands.lock().unwrap(),
+            vec![
+                EngineCommand::AddContract(ContractDefinition {
+                    address: Felt::ONE,
+                    r#type: ContractType::ERC20,
+                    starting_block: Some(10),
+                }),
+                EngineCommand::RemoveContract(Felt::ONE),
+                EngineCommand::RefreshMetadata(Felt::TWO),
+                EngineCommand::RebuildSearchIndex,
+                EngineCommand::Pause,
+                EngineCommand::Resume,
+                EngineCommand::ReindexRange {
+                    from_block: 1,
+                    to_block: 2,
+                    contracts: vec![Felt::THREE],
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_engine_stopped() {
+        let (admin, receiver) = Admin::new(None);
+        drop(receiver);
+        let handler = AdminHandler::new(Arc::new(admin));
+
+        let (status, _) = request(&handler, Method::POST, "/admin/pause", None).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_log_and_subscriptions() {
+        let (handler, _) = handler();
+
+        // The log filter can't be changed without a reload handle
+        let (status, body) = request(&handler, Method::GET, "/admin/log", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["directives"].is_null());
+        let (status, _) = request(
+            &handler,
+            Method::PUT,
+            "/admin/log",
+            Some(json!({ "directives": "torii=debug" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_IMPLEMENTED);
+
+        let (status, body) = request(&handler, Method::GET, "/admin/subscriptions", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            body["subscriptions"],
+            json!([{ "id": 1, "kind": "entities" }])
+        );
+
+        let (status, _) = request(
+            &handler,
+            Method::DELETE,
+            "/admin/subscriptions/entities/1",
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = request(
+            &handler,
+            Method::DELETE,
+            "/admin/subscriptions/tokens/1",
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = request(
+            &handler,
+            Method::DELETE,
+            "/admin/subscriptions/entities/x",
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
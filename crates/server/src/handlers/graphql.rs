@@ -8,6 +8,7 @@ use hyper::client::HttpConnector;
 use hyper::{Body, Request, Response};
 use hyper_reverse_proxy::ReverseProxy;
 use tokio::time::timeout;
+use torii_auth::Capability;
 use tracing::error;
 
 use super::Handler;
@@ -51,6 +52,15 @@ impl Handler for GraphQLHandler {
         req.uri().path().starts_with("/graphql")
     }
 
+    fn capability(&self, req: &Request<Body>) -> Option<Capability> {
+        // Subscriptions are served over websockets
+        if crate::proxy::is_websocket_upgrade(req) {
+            Some(Capability::Subscribe)
+        } else {
+            Some(Capability::Read)
+        }
+    }
+
     async fn handle(&self, req: Request<Body>, client_addr: IpAddr) -> Response<Body> {
         if let Some(addr) = self.graphql_addr {
             let graphql_addr = format!("http://{}", addr);
@@ -8,6 +8,7 @@ use hyper::client::HttpConnector;
 use hyper::{Body, Request, Response, StatusCode};
 use hyper_reverse_proxy::ReverseProxy;
 use tokio::time::timeout;
+use torii_auth::Capability;
 use tracing::error;
 
 use super::Handler;
@@ -52,6 +53,11 @@ impl Handler for GrpcHandler {
             .unwrap_or(false)
     }
 
+    // The gRPC server authorizes each method itself
+    fn capability(&self, _req: &Request<Body>) -> Option<Capability> {
+        None
+    }
+
     async fn handle(&self, req: Request<Body>, client_addr: IpAddr) -> Response<Body> {
         if let Some(grpc_addr) = self.grpc_addr {
             let grpc_addr = format!("http://{}", grpc_addr);
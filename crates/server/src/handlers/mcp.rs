@@ -7,12 +7,15 @@ use serde_json::{json, Value};
 use sqlx::SqlitePool;
 use tokio::sync::{broadcast, RwLock};
 use tokio_tungstenite::tungstenite::Message;
-use torii_mcp::resources::{self, Resource};
+use torii_auth::Capability;
+use torii_mcp::resources;
 use torii_mcp::tools::{self, Tool};
 use torii_mcp::types::{
-    JsonRpcMessage, JsonRpcRequest, JsonRpcResponse, SseSession, JSONRPC_VERSION, MCP_VERSION,
-    SSE_CHANNEL_CAPACITY,
+    JsonRpcMessage, JsonRpcRequest, JsonRpcResponse, ServerMessage, Session, JSONRPC_VERSION,
+    MCP_VERSION, SSE_CHANNEL_CAPACITY,
 };
+use torii_sqlite::sandbox::SqlSandbox;
+use torii_storage::ReadOnlyStorage;
 use tracing::warn;
 use uuid::Uuid;
 
@@ -21,22 +24,33 @@ use super::Handler;
 #[derive(Clone, Debug)]
 pub struct McpHandler {
     pool: Arc<SqlitePool>,
-    sse_sessions: Arc<RwLock<std::collections::HashMap<String, SseSession>>>,
+    sandbox: Arc<SqlSandbox>,
+    storage: Arc<dyn ReadOnlyStorage>,
+    sse_sessions: Arc<RwLock<std::collections::HashMap<String, Session>>>,
     tools: Vec<Tool>,
-    resources: Vec<Resource>,
 }
 
 impl McpHandler {
-    pub fn new(pool: Arc<SqlitePool>) -> Self {
+    pub fn new(
+        pool: Arc<SqlitePool>,
+        sandbox: Arc<SqlSandbox>,
+        storage: Arc<dyn ReadOnlyStorage>,
+    ) -> Self {
         Self {
             pool,
+            sandbox,
+            storage,
             sse_sessions: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
             tools: tools::get_tools(),
-            resources: resources::get_resources(),
         }
     }
 
-    async fn handle_request(&self, request: JsonRpcRequest) -> JsonRpcResponse {
+    async fn handle_request(
+        &self,
+        request: JsonRpcRequest,
+        client_addr: IpAddr,
+        session: &Session,
+    ) -> JsonRpcResponse {
         if request.jsonrpc != JSONRPC_VERSION {
             return JsonRpcResponse::invalid_request(request.id);
         }
@@ -44,9 +58,11 @@ impl McpHandler {
         match request.method.as_str() {
             "initialize" => self.handle_initialize(request.id),
             "tools/list" => self.handle_tools_list(request.id),
-            "tools/call" => self.handle_tools_call(request).await,
-            "resources/list" => self.handle_resources_list(request.id),
+            "tools/call" => self.handle_tools_call(request, client_addr).await,
+            "resources/list" => self.handle_resources_list(request.id).await,
             "resources/read" => self.handle_resources_read(request).await,
+            "resources/subscribe" => self.handle_resources_subscribe(request, session),
+            "resources/unsubscribe" => self.handle_resources_unsubscribe(request, session),
             _ => JsonRpcResponse::method_not_found(request.id),
         }
     }
@@ -90,7 +106,11 @@ impl McpHandler {
         JsonRpcResponse::ok(id, json!({ "tools": tools_json }))
     }
 
-    async fn handle_tools_call(&self, request: JsonRpcRequest) -> JsonRpcResponse {
+    async fn handle_tools_call(
+        &self,
+        request: JsonRpcRequest,
+        client_addr: IpAddr,
+    ) -> JsonRpcResponse {
         let Some(params) = &request.params else {
             return JsonRpcResponse::invalid_params(request.id, "Missing params");
         };
@@ -100,8 +120,18 @@ impl McpHandler {
         };
 
         match tool_name {
-            "query" => tools::query::handle(self.pool.clone(), request).await,
+            "query" => {
+                tools::query::handle(self.sandbox.clone(), &client_addr.to_string(), request).await
+            }
             "schema" => tools::schema::handle(self.pool.clone(), request).await,
+            "get_entities" => tools::entities::handle(self.storage.as_ref(), request).await,
+            "list_models" => tools::models::handle(self.storage.as_ref(), request).await,
+            "get_token_balances" => {
+                tools::token_balances::handle(self.storage.as_ref(), request).await
+            }
+            "get_leaderboard" => tools::leaderboard::handle(self.storage.as_ref(), request).await,
+            "search" => tools::search::handle(self.storage.as_ref(), request).await,
+            "get_transactions" => tools::transactions::handle(self.storage.as_ref(), request).await,
             _ => JsonRpcResponse::method_not_found(request.id),
         }
     }
@@ -109,27 +139,56 @@ impl McpHandler {
     async fn handle_websocket_connection(
         &self,
         ws_stream: tokio_tungstenite::WebSocketStream<hyper::upgrade::Upgraded>,
+        client_addr: IpAddr,
     ) {
         let (mut write, mut read) = ws_stream.split();
 
-        while let Some(msg) = read.next().await {
-            if let Ok(Message::Text(text)) = msg {
-                let response = match serde_json::from_str::<JsonRpcMessage>(&text) {
-                    Ok(JsonRpcMessage::Request(request)) => self.handle_request(request).await,
-                    Ok(JsonRpcMessage::Notification(_notification)) => {
-                        // Handle notifications if needed
+        // The responses are sent as they are handled, the channel carries the notifications
+        let (tx, mut rx) = broadcast::channel::<ServerMessage>(SSE_CHANNEL_CAPACITY);
+        let session = Session {
+            tx,
+            _session_id: Uuid::new_v4().to_string(),
+            subscriptions: Default::default(),
+        };
+
+        loop {
+            let message = tokio::select! {
+                msg = read.next() => {
+                    let Some(msg) = msg else {
+                        break;
+                    };
+                    let Ok(Message::Text(text)) = msg else {
                         continue;
-                    }
-                    Err(e) => JsonRpcResponse::parse_error(Value::Null, &e.to_string()),
-                };
-
-                if let Err(e) = write
-                    .send(Message::Text(serde_json::to_string(&response).unwrap()))
-                    .await
-                {
-                    warn!("Error sending message: {}", e);
-                    break;
+                    };
+
+                    let response = match serde_json::from_str::<JsonRpcMessage>(&text) {
+                        Ok(JsonRpcMessage::Request(request)) => {
+                            self.handle_request(request, client_addr, &session).await
+                        }
+                        Ok(JsonRpcMessage::Notification(_notification)) => {
+                            // Handle notifications if needed
+                            continue;
+                        }
+                        Err(e) => JsonRpcResponse::parse_error(Value::Null, &e.to_string()),
+                    };
+                    ServerMessage::Response(response)
                 }
+                notification = rx.recv() => match notification {
+                    Ok(notification) => notification,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Skipped {} resource notifications of a slow client", skipped);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+            };
+
+            if let Err(e) = write
+                .send(Message::Text(serde_json::to_string(&message).unwrap()))
+                .await
+            {
+                warn!("Error sending message: {}", e);
+                break;
             }
         }
     }
@@ -140,20 +199,29 @@ impl McpHandler {
         let session_id = Uuid::new_v4().to_string();
 
         // Create a broadcast channel for SSE messages
-        let (tx, rx) = broadcast::channel::<JsonRpcResponse>(SSE_CHANNEL_CAPACITY);
+        let (tx, rx) = broadcast::channel::<ServerMessage>(SSE_CHANNEL_CAPACITY);
 
         // Store the session
         {
             let mut sessions = self.sse_sessions.write().await;
             sessions.insert(
                 session_id.clone(),
-                SseSession {
+                Session {
                     tx: tx.clone(),
                     _session_id: session_id.clone(),
+                    subscriptions: Default::default(),
                 },
             );
         }
 
+        // The stream, and its receiver, are dropped when the client disconnects
+        let sessions = self.sse_sessions.clone();
+        let closed_session_id = session_id.clone();
+        tokio::spawn(async move {
+            tx.closed().await;
+            sessions.write().await.remove(&closed_session_id);
+        });
+
         // Create the message endpoint path
         let message_endpoint = format!("/mcp/message?sessionId={}", session_id);
 
@@ -215,7 +283,11 @@ impl McpHandler {
             .unwrap()
     }
 
-    async fn handle_message_request(&self, req: Request<Body>) -> Response<Body> {
+    async fn handle_message_request(
+        &self,
+        req: Request<Body>,
+        client_addr: IpAddr,
+    ) -> Response<Body> {
         // Extract session ID from query parameters
         let session_id = req.uri().query().and_then(|q| {
             q.split('&').find_map(|p| {
@@ -238,19 +310,19 @@ impl McpHandler {
         let session_id = session_id.unwrap();
 
         // Check if the session exists
-        let tx = {
+        let session = {
             let sessions = self.sse_sessions.read().await;
-            sessions.get(&session_id).map(|s| s.tx.clone())
+            sessions.get(&session_id).cloned()
         };
 
-        if tx.is_none() {
+        if session.is_none() {
             return Response::builder()
                 .status(StatusCode::NOT_FOUND)
                 .body(Body::from("Session not found"))
                 .unwrap();
         }
 
-        let tx = tx.unwrap();
+        let session = session.unwrap();
 
         // Read the request body
         let body_bytes = hyper::body::to_bytes(req.into_body()).await.unwrap();
@@ -259,9 +331,9 @@ impl McpHandler {
         // Parse the JSON-RPC request
         let response = match serde_json::from_str::<JsonRpcMessage>(&body_str) {
             Ok(JsonRpcMessage::Request(request)) => {
-                let response = self.handle_request(request).await;
+                let response = self.handle_request(request, client_addr, &session).await;
                 // Send the response to the SSE channel
-                if let Err(e) = tx.send(response.clone()) {
+                if let Err(e) = session.tx.send(ServerMessage::Response(response.clone())) {
                     warn!("Error sending message to SSE channel: {}", e);
                 }
                 response
@@ -281,28 +353,55 @@ impl McpHandler {
             .unwrap()
     }
 
-    fn handle_resources_list(&self, id: Value) -> JsonRpcResponse {
-        let resources_json: Vec<Value> = self
-            .resources
-            .iter()
-            .map(|resource| json!({ "name": resource.name }))
-            .collect();
-
-        JsonRpcResponse::ok(id, json!({ "resources": resources_json }))
+    async fn handle_resources_list(&self, id: Value) -> JsonRpcResponse {
+        resources::handle_list(self.storage.as_ref(), id).await
     }
 
-    async fn handle_resources_read(&self, request: JsonRpcRequest) -> JsonRpcResponse {
-        let Some(params) = &request.params else {
-            return JsonRpcResponse::invalid_params(request.id, "Missing params");
+    fn handle_resources_subscribe(
+        &self,
+        request: JsonRpcRequest,
+        session: &Session,
+    ) -> JsonRpcResponse {
+        let Some(uri) = request
+            .params
+            .as_ref()
+            .and_then(|params| params.get("uri"))
+            .and_then(Value::as_str)
+        else {
+            return JsonRpcResponse::invalid_params(request.id, "Missing resource uri");
         };
 
-        let Some(_resource_name) = params.get("name").and_then(Value::as_str) else {
-            return JsonRpcResponse::invalid_params(request.id, "Missing resource name");
+        if !session.subscriptions.subscribe(uri) {
+            return JsonRpcResponse::invalid_params(
+                request.id,
+                &format!("Resource can't be subscribed to: {}", uri),
+            );
+        }
+        session.subscriptions.notify(session.tx.clone());
+
+        JsonRpcResponse::ok(request.id, json!({}))
+    }
+
+    fn handle_resources_unsubscribe(
+        &self,
+        request: JsonRpcRequest,
+        session: &Session,
+    ) -> JsonRpcResponse {
+        let Some(uri) = request
+            .params
+            .as_ref()
+            .and_then(|params| params.get("uri"))
+            .and_then(Value::as_str)
+        else {
+            return JsonRpcResponse::invalid_params(request.id, "Missing resource uri");
         };
 
-        // Implement resource reading logic here
-        // For now, return method not found
-        JsonRpcResponse::method_not_found(request.id)
+        session.subscriptions.unsubscribe(uri);
+        JsonRpcResponse::ok(request.id, json!({}))
+    }
+
+    async fn handle_resources_read(&self, request: JsonRpcRequest) -> JsonRpcResponse {
+        resources::handle_read(self.storage.as_ref(), &self.pool, request).await
     }
 }
 
@@ -312,7 +411,11 @@ impl Handler for McpHandler {
         req.uri().path().starts_with("/mcp")
     }
 
-    async fn handle(&self, req: Request<Body>, _: IpAddr) -> Response<Body> {
+    fn capability(&self, _req: &Request<Body>) -> Option<Capability> {
+        Some(Capability::Mcp)
+    }
+
+    async fn handle(&self, req: Request<Body>, client_addr: IpAddr) -> Response<Body> {
         // Handle WebSocket upgrade requests
         if hyper_tungstenite::is_upgrade_request(&req) {
             let (response, websocket) = hyper_tungstenite::upgrade(req, None).unwrap();
@@ -321,7 +424,9 @@ impl Handler for McpHandler {
             // Spawn a task to handle the WebSocket connection
             tokio::spawn(async move {
                 if let Ok(ws_stream) = websocket.await {
-                    self_clone.handle_websocket_connection(ws_stream).await;
+                    self_clone
+                        .handle_websocket_connection(ws_stream, client_addr)
+                        .await;
                 }
             });
 
@@ -330,7 +435,7 @@ impl Handler for McpHandler {
 
         // Handle message requests for SSE
         if req.uri().path() == "/mcp/message" {
-            return self.handle_message_request(req).await;
+            return self.handle_message_request(req, client_addr).await;
         }
 
         match req.method() {
@@ -347,3 +452,110 @@ impl Handler for McpHandler {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+    use std::time::Duration;
+
+    use starknet::core::types::Felt;
+    use starknet::providers::jsonrpc::HttpTransport;
+    use starknet::providers::{JsonRpcClient, Url};
+    use tokio::time::timeout;
+    use torii_broker::types::EntityUpdate;
+    use torii_broker::MemoryBroker;
+    use torii_sqlite::executor::Executor;
+    use torii_sqlite::sandbox::SandboxConfig;
+    use torii_sqlite::Sql;
+    use torii_storage::proto::schema::{Entity, EntityWithMetadata};
+
+    use super::*;
+
+    const CLIENT_ADDR: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+    async fn handler(pool: SqlitePool) -> McpHandler {
+        // Nothing listens there, the chain is unreachable
+        let url: Url = "http://127.0.0.1:1".parse().unwrap();
+        let provider = Arc::new(JsonRpcClient::new(HttpTransport::new(url)));
+        let (shutdown_tx, _) = broadcast::channel(1);
+        let (mut executor, sender) = Executor::new(pool.clone(), shutdown_tx, provider)
+            .await
+            .unwrap();
+        tokio::spawn(async move {
+            executor.run().await.unwrap();
+        });
+        let db = Sql::new(pool.clone(), sender, &[]).await.unwrap();
+
+        McpHandler::new(
+            Arc::new(pool.clone()),
+            Arc::new(SqlSandbox::new(pool, SandboxConfig::default())),
+            Arc::new(db),
+        )
+    }
+
+    /// The next event of an SSE stream.
+    async fn next_event(events: &mut Body) -> String {
+        let chunk = timeout(Duration::from_secs(5), events.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        String::from_utf8(chunk.to_vec()).unwrap()
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_resource_subscription(pool: SqlitePool) {
+        let handler = handler(pool).await;
+
+        let req = Request::get("/mcp/sse").body(Body::empty()).unwrap();
+        let mut events = handler.handle(req, CLIENT_ADDR).await.into_body();
+        let endpoint = next_event(&mut events).await;
+        let path = endpoint
+            .strip_prefix("event: endpoint\ndata: ")
+            .unwrap()
+            .trim_end()
+            .to_string();
+
+        let subscribe = json!({
+            "jsonrpc": JSONRPC_VERSION,
+            "id": 1,
+            "method": "resources/subscribe",
+            "params": { "uri": resources::ENTITIES_URI }
+        });
+        let req = Request::post(path)
+            .body(Body::from(subscribe.to_string()))
+            .unwrap();
+        let response = handler.handle(req, CLIENT_ADDR).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(next_event(&mut events).await.contains(r#""id":1"#));
+
+        // The updates are notified once the session subscribed to them
+        let update = EntityWithMetadata {
+            entity: Entity {
+                world_address: Felt::ONE,
+                hashed_keys: Felt::TWO,
+                ..Default::default()
+            },
+            event_id: String::new(),
+            keys: vec![],
+        };
+        let notification = loop {
+            MemoryBroker::publish(EntityUpdate::new(update.clone(), false));
+            if let Ok(Some(Ok(chunk))) = timeout(Duration::from_millis(100), events.next()).await {
+                break String::from_utf8(chunk.to_vec()).unwrap();
+            }
+        };
+        assert!(notification.contains("notifications/resources/updated"));
+        assert!(notification.contains(resources::ENTITIES_URI));
+
+        // The session ends with its stream
+        drop(events);
+        timeout(Duration::from_secs(5), async {
+            while !handler.sse_sessions.read().await.is_empty() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+}
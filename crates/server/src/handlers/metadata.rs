@@ -8,7 +8,7 @@ use hyper::Body;
 use starknet::providers::Provider;
 use starknet_crypto::Felt;
 use torii_processors::erc::fetch_token_metadata;
-use torii_storage::proto::TokenId;
+use torii_storage::proto::{MetadataJob, MetadataJobQuery, MetadataJobStatus, TokenId};
 use torii_storage::Storage;
 use tracing::{debug, error};
 
@@ -16,6 +16,10 @@ use super::Handler;
 
 pub(crate) const LOG_TARGET: &str = "torii::server::handlers::metadata";
 
+const REINDEX_PREFIX: &str = "/metadata/reindex/";
+const REFRESH_PREFIX: &str = "/metadata/refresh/";
+const JOBS_PATH: &str = "/metadata/jobs";
+
 #[derive(Debug)]
 pub struct MetadataHandler<P: Provider + Sync + Send + Debug, S: Storage> {
     storage: Arc<S>,
@@ -26,19 +30,179 @@ impl<P: Provider + Sync + Send + Debug, S: Storage> MetadataHandler<P, S> {
     pub fn new(storage: Arc<S>, provider: P) -> Self {
         Self { storage, provider }
     }
+
+    /// Queues a metadata refresh for a contract and all its tokens, or for a single token.
+    async fn refresh(&self, path: &str) -> Response<Body> {
+        let parts: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+        if parts.len() > 2 {
+            return json_error(
+                StatusCode::BAD_REQUEST,
+                "Invalid path format. Expected: /metadata/refresh/{contract_address}[/{token_id}]",
+            );
+        }
+
+        let contract_address = match Felt::from_hex(parts[0]) {
+            Ok(addr) => addr,
+            Err(e) => {
+                return json_error(
+                    StatusCode::BAD_REQUEST,
+                    &format!("Invalid contract address: {}", e),
+                )
+            }
+        };
+
+        let (token_id, result) = match parts.get(1) {
+            Some(token_id) => {
+                let token_id = match Felt::from_hex(token_id) {
+                    Ok(token_id) => TokenId::Nft(
+                        contract_address,
+                        starknet::core::types::U256::from(token_id),
+                    ),
+                    Err(e) => {
+                        return json_error(
+                            StatusCode::BAD_REQUEST,
+                            &format!("Invalid token ID: {}", e),
+                        )
+                    }
+                };
+                let result = self
+                    .storage
+                    .enqueue_metadata_jobs(vec![token_id.clone()])
+                    .await;
+                (token_id, result)
+            }
+            None => (
+                TokenId::Contract(contract_address),
+                self.storage
+                    .enqueue_contract_metadata_jobs(contract_address)
+                    .await,
+            ),
+        };
+
+        // Commit right away so the workers pick the jobs up on their next poll
+        let result = match result {
+            Ok(()) => self.storage.execute().await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            error!(target: LOG_TARGET, error = ?e, token_id = %token_id, "Failed to queue metadata refresh");
+            return json_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                &format!("Failed to queue metadata refresh: {}", e),
+            );
+        }
+
+        debug!(target: LOG_TARGET, token_id = %token_id, "Queued metadata refresh");
+        json_response(
+            StatusCode::ACCEPTED,
+            serde_json::json!({
+                "success": true,
+                "message": "Metadata refresh queued",
+                "token_id": token_id.to_string(),
+            }),
+        )
+    }
+
+    /// Lists metadata jobs, filtered by the `contract` and `status` query parameters, which can
+    /// be repeated or comma-separated.
+    async fn jobs(&self, query: &str) -> Response<Body> {
+        let mut jobs_query = MetadataJobQuery::default();
+
+        for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+            match key.as_ref() {
+                "contract" => {
+                    for contract in value.split(',').filter(|s| !s.is_empty()) {
+                        match Felt::from_hex(contract) {
+                            Ok(addr) => jobs_query.contract_addresses.push(addr),
+                            Err(e) => {
+                                return json_error(
+                                    StatusCode::BAD_REQUEST,
+                                    &format!("Invalid contract address: {}", e),
+                                )
+                            }
+                        }
+                    }
+                }
+                "status" => {
+                    for status in value.split(',').filter(|s| !s.is_empty()) {
+                        match MetadataJobStatus::from_str(status) {
+                            Ok(status) => jobs_query.statuses.push(status),
+                            Err(e) => return json_error(StatusCode::BAD_REQUEST, &e.to_string()),
+                        }
+                    }
+                }
+                "cursor" => jobs_query.cursor = Some(value.to_string()),
+                "limit" => match value.parse() {
+                    Ok(limit) => jobs_query.limit = Some(limit),
+                    Err(_) => return json_error(StatusCode::BAD_REQUEST, "Invalid limit"),
+                },
+                _ => {}
+            }
+        }
+
+        match self.storage.metadata_jobs(&jobs_query).await {
+            Ok(page) => json_response(
+                StatusCode::OK,
+                serde_json::json!({
+                    "items": page.items.iter().map(job_to_json).collect::<Vec<_>>(),
+                    "next_cursor": page.next_cursor,
+                }),
+            ),
+            Err(e) => {
+                error!(target: LOG_TARGET, error = ?e, "Failed to list metadata jobs");
+                json_error(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    &format!("Failed to list metadata jobs: {}", e),
+                )
+            }
+        }
+    }
+}
+
+fn job_to_json(job: &MetadataJob) -> serde_json::Value {
+    serde_json::json!({
+        "token_id": job.token_id.to_string(),
+        "contract_address": format!("{:#x}", job.token_id.contract_address()),
+        "status": job.status.as_str(),
+        "attempts": job.attempts,
+        "last_error": job.last_error,
+        "retry_at": job.retry_at.map(|t| t.to_rfc3339()),
+        "updated_at": job.updated_at.to_rfc3339(),
+    })
+}
+
+fn json_response(status: StatusCode, body: serde_json::Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn json_error(status: StatusCode, message: &str) -> Response<Body> {
+    json_response(status, serde_json::json!({ "error": message }))
 }
 
 #[async_trait::async_trait]
 impl<P: Provider + Sync + Send + Debug, S: Storage> Handler for MetadataHandler<P, S> {
     fn should_handle(&self, req: &Request<Body>) -> bool {
-        req.uri().path().starts_with("/metadata/reindex/")
+        let path = req.uri().path();
+        path.starts_with(REINDEX_PREFIX) || path.starts_with(REFRESH_PREFIX) || path == JOBS_PATH
     }
 
     async fn handle(&self, req: Request<Body>, _client_addr: IpAddr) -> Response<Body> {
         let path = req.uri().path();
 
+        if let Some(path) = path.strip_prefix(REFRESH_PREFIX) {
+            return self.refresh(path).await;
+        }
+
+        if path == JOBS_PATH {
+            return self.jobs(req.uri().query().unwrap_or_default()).await;
+        }
+
         // Remove "/metadata/reindex/" prefix
-        let path = match path.strip_prefix("/metadata/reindex/") {
+        let path = match path.strip_prefix(REINDEX_PREFIX) {
             Some(p) => p,
             None => {
                 return Response::builder()
@@ -1,20 +1,31 @@
+pub mod admin;
 pub mod graphql;
 pub mod grpc;
 pub mod mcp;
 pub mod metadata;
+pub mod replication;
+pub mod snapshot;
 pub mod sql;
 pub mod r#static;
+pub mod status;
 
 use std::{fmt::Debug, net::IpAddr};
 
 use http::{Method, StatusCode};
 use hyper::{Body, Request, Response};
+use torii_auth::Capability;
 
 #[async_trait::async_trait]
 pub trait Handler: Send + Sync + Debug {
     // Check if this handler should handle the given request
     fn should_handle(&self, req: &Request<Body>) -> bool;
 
+    // The capability required to be handled, none for requests which are always allowed or
+    // authorized further down, like gRPC
+    fn capability(&self, _req: &Request<Body>) -> Option<Capability> {
+        Some(Capability::Read)
+    }
+
     // Handle the request
     async fn handle(&self, req: Request<Body>, client_addr: IpAddr) -> Response<Body>;
 
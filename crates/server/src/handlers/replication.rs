@@ -0,0 +1,104 @@
+use std::sync::Arc;
+use std::{fmt::Debug, net::IpAddr};
+
+use http::{Method, Request, Response, StatusCode};
+use hyper::Body;
+use serde_json::{json, Value};
+use sqlx::SqlitePool;
+use torii_auth::Capability;
+use torii_sqlite::replication::{self, ReplicationError};
+use tracing::error;
+
+use super::Handler;
+
+const LOG_TARGET: &str = "torii::server::handlers::replication";
+
+const CHANGES_PATH: &str = "/replication/changes";
+const DEFAULT_LIMIT: u32 = 100;
+const MAX_LIMIT: u32 = 1000;
+
+/// Serves the change log of the database to followers, with `GET
+/// /replication/changes?after=<id>&limit=<n>`. Requires the `replicate` capability.
+#[derive(Debug)]
+pub struct ReplicationHandler {
+    pool: Arc<SqlitePool>,
+}
+
+impl ReplicationHandler {
+    pub fn new(pool: Arc<SqlitePool>) -> Self {
+        Self { pool }
+    }
+
+    async fn changes(&self, after: i64, limit: u32) -> Response<Body> {
+        let head = match replication::head(&self.pool).await {
+            Ok(head) => head,
+            Err(e) => return internal_error(e.into()),
+        };
+
+        match replication::changes(&self.pool, after, limit).await {
+            Ok(batches) => {
+                json_response(StatusCode::OK, json!({ "head": head, "batches": batches }))
+            }
+            Err(e @ ReplicationError::Pruned { .. }) => {
+                json_error(StatusCode::GONE, &e.to_string())
+            }
+            Err(e) => internal_error(e),
+        }
+    }
+}
+
+fn internal_error(error: ReplicationError) -> Response<Body> {
+    error!(target: LOG_TARGET, error = ?error, "Reading replication log.");
+    json_error(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        &format!("Failed to read replication log: {}", error),
+    )
+}
+
+fn json_response(status: StatusCode, body: Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn json_error(status: StatusCode, message: &str) -> Response<Body> {
+    json_response(status, json!({ "error": message }))
+}
+
+#[async_trait::async_trait]
+impl Handler for ReplicationHandler {
+    fn should_handle(&self, req: &Request<Body>) -> bool {
+        req.uri().path().trim_end_matches('/') == CHANGES_PATH
+    }
+
+    fn capability(&self, _req: &Request<Body>) -> Option<Capability> {
+        Some(Capability::Replicate)
+    }
+
+    async fn handle(&self, req: Request<Body>, _client_addr: IpAddr) -> Response<Body> {
+        if req.method() != Method::GET {
+            return json_error(StatusCode::METHOD_NOT_ALLOWED, "Method not allowed");
+        }
+
+        let mut after = 0;
+        let mut limit = DEFAULT_LIMIT;
+        let params = req.uri().query().unwrap_or_default();
+        for (key, value) in form_urlencoded::parse(params.as_bytes()) {
+            let parsed = match key.as_ref() {
+                "after" => value.parse().map(|value| after = value),
+                "limit" => value.parse().map(|value| limit = value),
+                _ => continue,
+            };
+            if parsed.is_err() {
+                return json_error(
+                    StatusCode::BAD_REQUEST,
+                    &format!("Invalid {}: {}", key, value),
+                );
+            }
+        }
+
+        self.changes(after, limit.clamp(1, MAX_LIMIT)).await
+    }
+}
@@ -0,0 +1,232 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::{fmt::Debug, net::IpAddr};
+
+use http::{Method, Request, Response, StatusCode};
+use hyper::Body;
+use serde_json::{json, Value};
+use sqlx::SqlitePool;
+use tokio::sync::Mutex;
+use tokio_util::io::ReaderStream;
+use torii_auth::Capability;
+use torii_sqlite::snapshot;
+use tracing::{error, info};
+
+use super::Handler;
+
+const LOG_TARGET: &str = "torii::server::handlers::snapshot";
+
+const SNAPSHOTS_PATH: &str = "/snapshots";
+
+/// Takes snapshots of the database into a directory and serves them, for replicas to bootstrap
+/// from. Taking one requires the `admin` capability.
+#[derive(Debug)]
+pub struct SnapshotHandler {
+    pool: Arc<SqlitePool>,
+    dir: PathBuf,
+    version_spec: String,
+    // A snapshot at a time, they are heavy on disk and CPU
+    lock: Mutex<()>,
+}
+
+impl SnapshotHandler {
+    pub fn new(pool: Arc<SqlitePool>, dir: PathBuf, version_spec: String) -> Self {
+        Self {
+            pool,
+            dir,
+            version_spec,
+            lock: Mutex::new(()),
+        }
+    }
+
+    async fn create(&self) -> Response<Body> {
+        let Ok(_guard) = self.lock.try_lock() else {
+            return json_error(StatusCode::CONFLICT, "A snapshot is already being taken");
+        };
+
+        info!(target: LOG_TARGET, dir = %self.dir.display(), "Taking snapshot.");
+        match snapshot::create(&self.pool, &self.dir, &self.version_spec).await {
+            Ok((path, manifest)) => {
+                info!(target: LOG_TARGET, path = %path.display(), size = manifest.size_bytes, "Took snapshot.");
+                json_response(StatusCode::CREATED, json!(manifest))
+            }
+            Err(e) => {
+                error!(target: LOG_TARGET, error = ?e, "Taking snapshot.");
+                json_error(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    &format!("Failed to take snapshot: {}", e),
+                )
+            }
+        }
+    }
+
+    fn list(&self) -> Response<Body> {
+        if !self.dir.exists() {
+            return json_response(StatusCode::OK, json!({ "snapshots": [] }));
+        }
+
+        match snapshot::list(&self.dir) {
+            Ok(manifests) => json_response(StatusCode::OK, json!({ "snapshots": manifests })),
+            Err(e) => json_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                &format!("Failed to list snapshots: {}", e),
+            ),
+        }
+    }
+
+    /// Serves a snapshot file or a manifest.
+    async fn file(&self, name: &str) -> Response<Body> {
+        if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+            return json_error(StatusCode::BAD_REQUEST, "Invalid snapshot name");
+        }
+
+        let file = match tokio::fs::File::open(self.dir.join(name)).await {
+            Ok(file) => file,
+            Err(_) => return json_error(StatusCode::NOT_FOUND, "Snapshot not found"),
+        };
+        let content_type = if name.ends_with(snapshot::MANIFEST_SUFFIX) {
+            "application/json"
+        } else {
+            "application/gzip"
+        };
+
+        let mut response = Response::builder()
+            .status(StatusCode::OK)
+            .header("content-type", content_type);
+        if let Ok(metadata) = file.metadata().await {
+            response = response.header("content-length", metadata.len());
+        }
+        response
+            .body(Body::wrap_stream(ReaderStream::new(file)))
+            .unwrap()
+    }
+}
+
+fn json_response(status: StatusCode, body: Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn json_error(status: StatusCode, message: &str) -> Response<Body> {
+    json_response(status, json!({ "error": message }))
+}
+
+#[async_trait::async_trait]
+impl Handler for SnapshotHandler {
+    fn should_handle(&self, req: &Request<Body>) -> bool {
+        let path = req.uri().path();
+        path == SNAPSHOTS_PATH || path.starts_with(&format!("{}/", SNAPSHOTS_PATH))
+    }
+
+    fn capability(&self, req: &Request<Body>) -> Option<Capability> {
+        match *req.method() {
+            Method::GET => Some(Capability::Read),
+            _ => Some(Capability::Admin),
+        }
+    }
+
+    async fn handle(&self, req: Request<Body>, _client_addr: IpAddr) -> Response<Body> {
+        let path = req.uri().path().trim_end_matches('/');
+        match (req.method(), path.strip_prefix(SNAPSHOTS_PATH)) {
+            (&Method::POST, Some("")) => self.create().await,
+            (&Method::GET, Some("")) => self.list(),
+            (&Method::GET, Some(name)) => self.file(name.trim_start_matches('/')).await,
+            _ => json_error(StatusCode::METHOD_NOT_ALLOWED, "Method not allowed"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+
+    async fn request(handler: &SnapshotHandler, method: Method, path: &str) -> Response<Body> {
+        let req = Request::builder()
+            .method(method)
+            .uri(path)
+            .body(Body::empty())
+            .unwrap();
+        assert!(handler.should_handle(&req));
+        handler.handle(req, IpAddr::V4(Ipv4Addr::LOCALHOST)).await
+    }
+
+    async fn json_body(response: Response<Body>) -> Value {
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_snapshots(pool: SqlitePool) {
+        let dir = tempfile::tempdir().unwrap();
+        let handler = SnapshotHandler::new(
+            Arc::new(pool),
+            dir.path().join("snapshots"),
+            "1.0.0".to_string(),
+        );
+
+        let get = Request::get(SNAPSHOTS_PATH).body(Body::empty()).unwrap();
+        assert_eq!(handler.capability(&get), Some(Capability::Read));
+        let post = Request::post(SNAPSHOTS_PATH).body(Body::empty()).unwrap();
+        assert_eq!(handler.capability(&post), Some(Capability::Admin));
+        assert!(!handler.should_handle(&Request::get("/snapshotsx").body(Body::empty()).unwrap()));
+
+        // The directory is created by the first snapshot
+        let response = request(&handler, Method::GET, SNAPSHOTS_PATH).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(json_body(response).await, json!({ "snapshots": [] }));
+
+        let response = request(&handler, Method::POST, SNAPSHOTS_PATH).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let manifest = json_body(response).await;
+        assert_eq!(manifest["version"], "1.0.0");
+
+        let response = request(&handler, Method::GET, "/snapshots/").await;
+        assert_eq!(
+            json_body(response).await,
+            json!({ "snapshots": [manifest.clone()] })
+        );
+
+        let file = manifest["file"].as_str().unwrap();
+        let response = request(&handler, Method::GET, &format!("/snapshots/{file}")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "application/gzip");
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(body.len() as u64, manifest["size_bytes"].as_u64().unwrap());
+
+        let response = request(
+            &handler,
+            Method::GET,
+            &format!("/snapshots/{file}{}", snapshot::MANIFEST_SUFFIX),
+        )
+        .await;
+        assert_eq!(response.headers()["content-type"], "application/json");
+        assert_eq!(json_body(response).await, manifest);
+
+        let response = request(&handler, Method::GET, "/snapshots/missing.db.gz").await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        // The copies being compressed are hidden
+        let response = request(&handler, Method::GET, "/snapshots/.torii.db").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = request(&handler, Method::DELETE, SNAPSHOTS_PATH).await;
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_snapshot_in_progress(pool: SqlitePool) {
+        let dir = tempfile::tempdir().unwrap();
+        let handler = SnapshotHandler::new(
+            Arc::new(pool),
+            dir.path().to_path_buf(),
+            "1.0.0".to_string(),
+        );
+
+        let _guard = handler.lock.lock().await;
+        let response = request(&handler, Method::POST, SNAPSHOTS_PATH).await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+}
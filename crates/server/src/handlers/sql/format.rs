@@ -0,0 +1,400 @@
+use std::sync::Arc;
+
+use anyhow::bail;
+use arrow::array::{ArrayRef, BinaryArray, Float64Array, Int64Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::ipc::writer::StreamWriter;
+use http::header::ACCEPT;
+use hyper::{Body, Request};
+use serde_json::Value;
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Column, Row, TypeInfo, ValueRef};
+use torii_sqlite::utils::map_row_to_json;
+
+pub const JSON_CONTENT_TYPE: &str = "application/json";
+pub const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+pub const CSV_CONTENT_TYPE: &str = "text/csv";
+pub const ARROW_CONTENT_TYPE: &str = "application/vnd.apache.arrow.stream";
+
+/// Number of rows in a record batch of an Arrow stream.
+const ARROW_BATCH_SIZE: usize = 4096;
+
+/// Output format of the SQL endpoint. Every format but JSON streams the rows as they are read.
+/// Parquet is not supported, its metadata follows all the rows so it can't be streamed, Arrow
+/// streams the same columns and converts to Parquet on the client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlFormat {
+    Json,
+    NdJson,
+    Csv,
+    Arrow,
+}
+
+impl SqlFormat {
+    /// The format of the `format` query parameter or else of the Accept header, JSON by default.
+    /// Returns `None` for an unknown `format` parameter.
+    pub fn from_request(req: &Request<Body>) -> Option<Self> {
+        let param = req.uri().query().and_then(|query| {
+            form_urlencoded::parse(query.as_bytes())
+                .find(|(key, _)| key == "format")
+                .map(|(_, value)| value.to_lowercase())
+        });
+        if let Some(param) = param {
+            return match param.as_str() {
+                "json" => Some(SqlFormat::Json),
+                "ndjson" | "jsonl" => Some(SqlFormat::NdJson),
+                "csv" => Some(SqlFormat::Csv),
+                "arrow" => Some(SqlFormat::Arrow),
+                _ => None,
+            };
+        }
+
+        let accept = req
+            .headers()
+            .get(ACCEPT)
+            .and_then(|accept| accept.to_str().ok())
+            .unwrap_or_default();
+        let format = [SqlFormat::NdJson, SqlFormat::Csv, SqlFormat::Arrow]
+            .into_iter()
+            .find(|format| accept.contains(format.content_type()))
+            .unwrap_or(SqlFormat::Json);
+
+        Some(format)
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            SqlFormat::Json => JSON_CONTENT_TYPE,
+            SqlFormat::NdJson => NDJSON_CONTENT_TYPE,
+            SqlFormat::Csv => CSV_CONTENT_TYPE,
+            SqlFormat::Arrow => ARROW_CONTENT_TYPE,
+        }
+    }
+
+    /// The encoder of a streamed format, `None` for JSON which is not streamed.
+    pub fn encoder(&self) -> Option<Box<dyn RowEncoder>> {
+        match self {
+            SqlFormat::Json => None,
+            SqlFormat::NdJson => Some(Box::new(NdJsonEncoder)),
+            SqlFormat::Csv => Some(Box::new(CsvEncoder::default())),
+            SqlFormat::Arrow => Some(Box::<ArrowEncoder>::default()),
+        }
+    }
+}
+
+/// Encodes streamed rows, returning the bytes that are ready to be sent.
+pub trait RowEncoder: Send {
+    fn encode(&mut self, row: SqliteRow) -> anyhow::Result<Vec<u8>>;
+
+    /// Ends the output, once all the rows have been encoded.
+    fn finish(&mut self) -> anyhow::Result<Vec<u8>>;
+}
+
+/// One JSON object per line.
+#[derive(Debug)]
+pub struct NdJsonEncoder;
+
+impl RowEncoder for NdJsonEncoder {
+    fn encode(&mut self, row: SqliteRow) -> anyhow::Result<Vec<u8>> {
+        let mut bytes = serde_json::to_vec(&map_row_to_json(&row))?;
+        bytes.push(b'\n');
+        Ok(bytes)
+    }
+
+    fn finish(&mut self) -> anyhow::Result<Vec<u8>> {
+        Ok(vec![])
+    }
+}
+
+/// RFC 4180 CSV, with a header of the column names. Blobs are base64 encoded, like in JSON.
+#[derive(Debug, Default)]
+pub struct CsvEncoder {
+    header_written: bool,
+}
+
+impl RowEncoder for CsvEncoder {
+    fn encode(&mut self, row: SqliteRow) -> anyhow::Result<Vec<u8>> {
+        let mut output = String::new();
+        if !self.header_written {
+            let header = row.columns().iter().map(|column| csv_field(column.name()));
+            output.push_str(&header.collect::<Vec<_>>().join(","));
+            output.push_str("\r\n");
+            self.header_written = true;
+        }
+
+        let object = map_row_to_json(&row);
+        let fields = row
+            .columns()
+            .iter()
+            .map(|column| match &object[column.name()] {
+                Value::Null => String::new(),
+                Value::String(value) => csv_field(value),
+                value => value.to_string(),
+            });
+        output.push_str(&fields.collect::<Vec<_>>().join(","));
+        output.push_str("\r\n");
+
+        Ok(output.into_bytes())
+    }
+
+    fn finish(&mut self) -> anyhow::Result<Vec<u8>> {
+        Ok(vec![])
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Arrow IPC stream, in record batches of [`ARROW_BATCH_SIZE`] rows. The type of a column is
+/// its declared type, or for an expression, the type of its values in the first batch. A value
+/// of another type fails the stream, as SQLite doesn't enforce the column types.
+#[derive(Default)]
+pub struct ArrowEncoder {
+    writer: Option<(SchemaRef, StreamWriter<Vec<u8>>)>,
+    rows: Vec<SqliteRow>,
+}
+
+impl ArrowEncoder {
+    fn flush(&mut self) -> anyhow::Result<Vec<u8>> {
+        if self.writer.is_none() {
+            // Without rows, there are no columns either
+            let schema = Arc::new(match self.rows.first() {
+                Some(row) => arrow_schema(row, &self.rows),
+                None => Schema::empty(),
+            });
+            let writer = StreamWriter::try_new(Vec::new(), &schema)?;
+            self.writer = Some((schema, writer));
+        }
+
+        let (schema, writer) = self.writer.as_mut().expect("writer created above");
+        if !self.rows.is_empty() {
+            let columns = schema
+                .fields()
+                .iter()
+                .enumerate()
+                .map(|(i, field)| arrow_column(&self.rows, i, field))
+                .collect::<anyhow::Result<_>>()?;
+            writer.write(&RecordBatch::try_new(schema.clone(), columns)?)?;
+            self.rows.clear();
+        }
+
+        Ok(std::mem::take(writer.get_mut()))
+    }
+}
+
+impl RowEncoder for ArrowEncoder {
+    fn encode(&mut self, row: SqliteRow) -> anyhow::Result<Vec<u8>> {
+        self.rows.push(row);
+        if self.rows.len() < ARROW_BATCH_SIZE {
+            // The schema is sent with the first batch
+            return Ok(vec![]);
+        }
+
+        self.flush()
+    }
+
+    fn finish(&mut self) -> anyhow::Result<Vec<u8>> {
+        let mut bytes = self.flush()?;
+        if let Some((_, writer)) = &mut self.writer {
+            writer.finish()?;
+            bytes.append(writer.get_mut());
+        }
+
+        Ok(bytes)
+    }
+}
+
+/// Arrow type of a SQLite type, `None` for the columns without a declared type.
+fn arrow_type(name: &str) -> Option<DataType> {
+    match name {
+        "INTEGER" | "BOOLEAN" => Some(DataType::Int64),
+        "REAL" => Some(DataType::Float64),
+        "BLOB" => Some(DataType::Binary),
+        "TEXT" | "DATE" | "TIME" | "DATETIME" => Some(DataType::Utf8),
+        _ => None,
+    }
+}
+
+fn arrow_schema(row: &SqliteRow, rows: &[SqliteRow]) -> Schema {
+    let fields = row
+        .columns()
+        .iter()
+        .map(|column| {
+            let data_type = arrow_type(column.type_info().name())
+                .or_else(|| {
+                    rows.iter()
+                        .filter_map(|row| row.try_get_raw(column.ordinal()).ok())
+                        .find(|value| !value.is_null())
+                        .and_then(|value| arrow_type(value.type_info().name()))
+                })
+                .unwrap_or(DataType::Utf8);
+            Field::new(column.name(), data_type, true)
+        })
+        .collect::<Vec<_>>();
+
+    Schema::new(fields)
+}
+
+fn arrow_column(rows: &[SqliteRow], i: usize, field: &Field) -> anyhow::Result<ArrayRef> {
+    // Checks the type of the values, NULL fits any column and integers fit REAL columns
+    for row in rows {
+        let value = row.try_get_raw(i)?;
+        if value.is_null() {
+            continue;
+        }
+        let value_type = value.type_info().name().to_string();
+        match (field.data_type(), value_type.as_str()) {
+            (DataType::Int64, "INTEGER")
+            | (DataType::Float64, "REAL" | "INTEGER")
+            | (DataType::Binary, "BLOB")
+            | (DataType::Utf8, "TEXT") => {}
+            (data_type, _) => bail!(
+                "Column `{}` of type {data_type} has a value of type {value_type}",
+                field.name()
+            ),
+        }
+    }
+
+    let column: ArrayRef = match field.data_type() {
+        DataType::Int64 => Arc::new(
+            rows.iter()
+                .map(|row| row.try_get::<Option<i64>, _>(i))
+                .collect::<Result<Int64Array, _>>()?,
+        ),
+        DataType::Float64 => Arc::new(
+            rows.iter()
+                // SQLite converts the integers, which sqlx doesn't decode as floats when checked
+                .map(|row| row.try_get_unchecked::<Option<f64>, _>(i))
+                .collect::<Result<Float64Array, _>>()?,
+        ),
+        DataType::Binary => Arc::new(
+            rows.iter()
+                .map(|row| row.try_get::<Option<Vec<u8>>, _>(i))
+                .collect::<Result<BinaryArray, _>>()?,
+        ),
+        _ => Arc::new(
+            rows.iter()
+                .map(|row| row.try_get::<Option<String>, _>(i))
+                .collect::<Result<StringArray, _>>()?,
+        ),
+    };
+
+    Ok(column)
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::AsArray;
+    use arrow::datatypes::{Float64Type, Int64Type};
+    use arrow::ipc::reader::StreamReader;
+    use sqlx::SqlitePool;
+
+    use super::*;
+
+    async fn setup(pool: &SqlitePool) {
+        sqlx::query("CREATE TABLE t (i INTEGER, r REAL, s TEXT, b BLOB)")
+            .execute(pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO t VALUES (1, 1.5, 'a,\"b\"', x'0102'), (NULL, 2, NULL, NULL)")
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    fn encode(encoder: &mut dyn RowEncoder, rows: Vec<SqliteRow>) -> anyhow::Result<Vec<u8>> {
+        let mut bytes = vec![];
+        for row in rows {
+            bytes.extend(encoder.encode(row)?);
+        }
+        bytes.extend(encoder.finish()?);
+        Ok(bytes)
+    }
+
+    #[sqlx::test]
+    async fn test_text_encoders(pool: SqlitePool) {
+        setup(&pool).await;
+        let query = "SELECT i, s FROM t";
+
+        let rows = sqlx::query(query).fetch_all(&pool).await.unwrap();
+        let ndjson = encode(&mut NdJsonEncoder, rows).unwrap();
+        let lines = String::from_utf8(ndjson).unwrap();
+        let lines = lines.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            serde_json::from_str::<Value>(lines[0]).unwrap(),
+            serde_json::json!({ "i": 1, "s": "a,\"b\"" })
+        );
+
+        let rows = sqlx::query(query).fetch_all(&pool).await.unwrap();
+        let csv = encode(&mut CsvEncoder::default(), rows).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "i,s\r\n1,\"a,\"\"b\"\"\"\r\n,\r\n"
+        );
+    }
+
+    #[sqlx::test]
+    async fn test_arrow_encoder(pool: SqlitePool) {
+        setup(&pool).await;
+
+        // the declared types, or the type of the values of an expression
+        let rows = sqlx::query("SELECT i, r, s, b, i * 2 AS e FROM t")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        let bytes = encode(&mut ArrowEncoder::default(), rows).unwrap();
+        let reader = StreamReader::try_new(bytes.as_slice(), None).unwrap();
+        let schema = reader.schema();
+        let types = schema
+            .fields()
+            .iter()
+            .map(|field| field.data_type().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            vec![
+                DataType::Int64,
+                DataType::Float64,
+                DataType::Utf8,
+                DataType::Binary,
+                DataType::Int64
+            ]
+        );
+
+        let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+        assert_eq!(batch.num_rows(), 2);
+        let integers = batch.column(0).as_primitive::<Int64Type>();
+        assert_eq!(integers.value(0), 1);
+        assert!(integers.is_null(1));
+        // SQLite stores the integer of a REAL column as a float
+        let floats = batch.column(1).as_primitive::<Float64Type>();
+        assert_eq!((floats.value(0), floats.value(1)), (1.5, 2.0));
+        assert_eq!(batch.column(2).as_string::<i32>().value(0), "a,\"b\"");
+        assert_eq!(batch.column(3).as_binary::<i32>().value(0), [1, 2]);
+        assert_eq!(batch.column(4).as_primitive::<Int64Type>().value(0), 2);
+
+        // no rows, no columns
+        let bytes = encode(&mut ArrowEncoder::default(), vec![]).unwrap();
+        let reader = StreamReader::try_new(bytes.as_slice(), None).unwrap();
+        assert!(reader.schema().fields().is_empty());
+
+        // SQLite doesn't enforce the declared types, the values that don't fit fail the stream
+        sqlx::query("INSERT INTO t (i) VALUES ('x')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let rows = sqlx::query("SELECT i FROM t")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert!(encode(&mut ArrowEncoder::default(), rows).is_err());
+    }
+}
@@ -0,0 +1,263 @@
+pub mod format;
+
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use format::{RowEncoder, SqlFormat};
+use http::header::CONTENT_TYPE;
+use hyper::body::Bytes;
+use hyper::{Body, Method, Request, Response, StatusCode};
+use include_str;
+use serde_json::json;
+use sqlx::sqlite::SqliteRow;
+use tokio::sync::mpsc::Receiver;
+use torii_auth::Capability;
+use torii_sqlite::sandbox::{SandboxError, SqlSandbox};
+use torii_sqlite::utils::map_row_to_json;
+use tracing::warn;
+
+use super::Handler;
+
+const LOG_TARGET: &str = "torii::server::handlers::sql";
+
+#[derive(Debug)]
+pub struct SqlHandler {
+    sandbox: Arc<SqlSandbox>,
+}
+
+impl SqlHandler {
+    pub fn new(sandbox: Arc<SqlSandbox>) -> Self {
+        Self { sandbox }
+    }
+
+    pub async fn execute_query(&self, query: String, client_addr: IpAddr) -> Response<Body> {
+        match self.sandbox.execute(&client_addr.to_string(), &query).await {
+            Ok(rows) => {
+                let result: Vec<_> = rows.iter().map(map_row_to_json).collect();
+                let json = match serde_json::to_string(&result) {
+                    Ok(json) => json,
+                    Err(e) => {
+                        return Response::builder()
+                            .status(StatusCode::INTERNAL_SERVER_ERROR)
+                            .body(Body::from(format!("Failed to serialize result: {:?}", e)))
+                            .unwrap();
+                    }
+                };
+
+                Response::builder()
+                    .status(StatusCode::OK)
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(Body::from(json))
+                    .unwrap()
+            }
+            Err(e) => error_response(&e),
+        }
+    }
+
+    /// Streams the rows of the query in `format`, as they are read. JSON is not streamed.
+    pub async fn stream_query(
+        &self,
+        query: String,
+        format: SqlFormat,
+        client_addr: IpAddr,
+    ) -> Response<Body> {
+        let Some(mut encoder) = format.encoder() else {
+            return self.execute_query(query, client_addr).await;
+        };
+        let mut rows = match self
+            .sandbox
+            .execute_stream(&client_addr.to_string(), &query)
+        {
+            Ok(rows) => rows,
+            Err(e) => return error_response(&e),
+        };
+
+        // An invalid query fails before its first row, which can still be reported with a status
+        let first = match rows.recv().await {
+            Some(Ok(row)) => Some(row),
+            Some(Err(e)) => return error_response(&e),
+            None => None,
+        };
+
+        let (mut sender, body) = Body::channel();
+        tokio::spawn(async move {
+            if let Err(e) = send_rows(&mut sender, encoder.as_mut(), first, &mut rows).await {
+                // The status is already sent, the client only sees the truncated output
+                warn!(target: LOG_TARGET, error = %e, "Streaming SQL query results.");
+                sender.abort();
+            }
+        });
+
+        Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, format.content_type())
+            .body(body)
+            .unwrap()
+    }
+
+    async fn serve_playground(&self) -> Response<Body> {
+        let html = include_str!("../../../static/sql-playground.html");
+
+        Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, "text/html")
+            .header("Access-Control-Allow-Origin", "*")
+            .body(Body::from(html))
+            .unwrap()
+    }
+
+    async fn handle_request(&self, req: Request<Body>, client_addr: IpAddr) -> Response<Body> {
+        if req.method() == Method::GET && req.uri().query().unwrap_or_default().is_empty() {
+            self.serve_playground().await
+        } else {
+            let Some(format) = SqlFormat::from_request(&req) else {
+                return Response::builder()
+                    .status(StatusCode::BAD_REQUEST)
+                    .body(Body::from(
+                        "Unsupported format, expected one of json, ndjson, csv or arrow.",
+                    ))
+                    .unwrap();
+            };
+
+            match self.extract_query(req).await {
+                Ok(query) => self.stream_query(query, format, client_addr).await,
+                Err(_) => self.serve_playground().await,
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Handler for SqlHandler {
+    fn should_handle(&self, req: &Request<Body>) -> bool {
+        req.uri().path().starts_with("/sql")
+    }
+
+    fn capability(&self, _req: &Request<Body>) -> Option<Capability> {
+        Some(Capability::Sql)
+    }
+
+    async fn handle(&self, req: Request<Body>, client_addr: IpAddr) -> Response<Body> {
+        self.handle_request(req, client_addr).await
+    }
+}
+
+async fn send_rows(
+    sender: &mut hyper::body::Sender,
+    encoder: &mut dyn RowEncoder,
+    first: Option<SqliteRow>,
+    rows: &mut Receiver<Result<SqliteRow, SandboxError>>,
+) -> anyhow::Result<()> {
+    let Some(first) = first else {
+        sender.send_data(Bytes::from(encoder.finish()?)).await?;
+        return Ok(());
+    };
+
+    let bytes = encoder.encode(first)?;
+    if !bytes.is_empty() {
+        sender.send_data(Bytes::from(bytes)).await?;
+    }
+    while let Some(row) = rows.recv().await {
+        let bytes = encoder.encode(row?)?;
+        if !bytes.is_empty() {
+            // Fails once the client is gone, dropping the rows stops the query
+            sender.send_data(Bytes::from(bytes)).await?;
+        }
+    }
+    sender.send_data(Bytes::from(encoder.finish()?)).await?;
+
+    Ok(())
+}
+
+fn error_response(e: &SandboxError) -> Response<Body> {
+    let status = match e {
+        SandboxError::TooManyQueries(_) => StatusCode::TOO_MANY_REQUESTS,
+        SandboxError::RowLimit(_) | SandboxError::ByteLimit(_) => StatusCode::PAYLOAD_TOO_LARGE,
+        SandboxError::Timeout(_) => StatusCode::REQUEST_TIMEOUT,
+        _ => StatusCode::BAD_REQUEST,
+    };
+    let body = json!({ "error": { "code": e.code(), "message": e.to_string() } });
+
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+    use std::time::Duration;
+
+    use sqlx::SqlitePool;
+    use torii_sqlite::sandbox::SandboxConfig;
+
+    use super::*;
+
+    const COUNTER: &str = "WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c";
+
+    #[sqlx::test]
+    async fn test_stream_query(pool: SqlitePool) {
+        let handler = SqlHandler::new(Arc::new(SqlSandbox::new(
+            pool,
+            SandboxConfig {
+                max_rows: 1,
+                max_bytes: 1024,
+                timeout: Duration::from_secs(5),
+                stream_max_rows: 3,
+                stream_timeout: Duration::from_secs(5),
+                max_concurrent_queries: 1,
+            },
+        )));
+        let client = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+        let response = handler
+            .stream_query(
+                format!("{COUNTER} LIMIT 3) SELECT x FROM c"),
+                SqlFormat::NdJson,
+                client,
+            )
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[CONTENT_TYPE],
+            SqlFormat::NdJson.content_type()
+        );
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(body, "{\"x\":1}\n{\"x\":2}\n{\"x\":3}\n");
+
+        // an error before the first row has a status
+        let response = handler
+            .stream_query("SELECT * FROM missing".to_string(), SqlFormat::Csv, client)
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // the streaming limits apply, after the first rows the stream is cut
+        let response = handler
+            .stream_query(
+                format!("{COUNTER} LIMIT 4) SELECT x FROM c"),
+                SqlFormat::Csv,
+                client,
+            )
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(hyper::body::to_bytes(response.into_body()).await.is_err());
+
+        let response = handler
+            .stream_query("SELECT 1 AS x".to_string(), SqlFormat::Csv, client)
+            .await;
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(body, "x\r\n1\r\n");
+
+        // JSON isn't streamed, the limits of the buffered queries apply
+        let response = handler
+            .stream_query(
+                format!("{COUNTER} LIMIT 3) SELECT x FROM c"),
+                SqlFormat::Json,
+                client,
+            )
+            .await;
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
@@ -1,16 +1,17 @@
 use std::io::Cursor;
 use std::net::IpAddr;
-use std::str::FromStr;
+use std::sync::Arc;
 use std::time::SystemTime;
 
 use anyhow::{Context, Result};
 use base64::{engine::general_purpose, Engine as _};
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 use chrono;
-use data_url::mime::Mime;
-use data_url::DataUrl;
 use hyper::{Body, Request, Response, StatusCode};
-use image::{DynamicImage, ImageFormat};
+use image::codecs::avif::AvifEncoder;
+use image::codecs::gif::GifDecoder;
+use image::codecs::jpeg::JpegEncoder;
+use image::{AnimationDecoder, DynamicImage, ImageFormat};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -18,60 +19,238 @@ use sqlx::{Pool, Sqlite};
 use tokio::fs;
 use tokio::fs::File;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use torii_processors::fetch::{fetch_content_from_http, fetch_content_from_ipfs};
+use tokio::sync::Semaphore;
+use torii_processors::fetch::resolve_uri;
 use torii_sqlite::constants::TOKENS_TABLE;
 use tracing::{debug, error, trace};
 
 use super::Handler;
+use crate::artifacts::{remove_dir_files, ArtifactsLru};
 
 pub(crate) const LOG_TARGET: &str = "torii::server::handlers::static";
 
+/// Largest width or height an image can be resized to.
+const MAX_IMAGE_DIMENSION: u32 = 2048;
+/// Widths and heights the variants are generated at, requested ones are rounded up to the next.
+const VARIANT_DIMENSIONS: &[u32] = &[32, 64, 100, 128, 250, 256, 512, 1024, MAX_IMAGE_DIMENSION];
+/// Qualities the variants are encoded at, requested ones are rounded up to the next.
+const VARIANT_QUALITIES: &[u8] = &[10, 20, 30, 40, 50, 60, 70, 80, 90, 100];
+/// Number of variants generated per image, past it the preset images are served.
+const MAX_IMAGE_VARIANTS: usize = 32;
+/// Number of variants encoded at once.
+const MAX_CONCURRENT_ENCODES: usize = 4;
+/// AVIF encoder speed, from 1 (slowest, smallest) to 10 (fastest).
+const AVIF_SPEED: u8 = 8;
+
 fn parse_image_query(query_str: &str) -> ImageQuery {
-    let mut height = None;
-    let mut width = None;
+    let mut query = ImageQuery::default();
 
     for pair in query_str.split('&') {
         if let Some((key, value)) = pair.split_once('=') {
             match key {
                 "h" | "height" => {
                     if let Ok(h) = value.parse::<u32>() {
-                        height = Some(h);
+                        query.height = Some(allowed_value(VARIANT_DIMENSIONS, h));
                     }
                 }
                 "w" | "width" => {
                     if let Ok(w) = value.parse::<u32>() {
-                        width = Some(w);
+                        query.width = Some(allowed_value(VARIANT_DIMENSIONS, w));
                     }
                 }
+                "f" | "format" => {
+                    // `auto` (or anything unknown) negotiates the format from the Accept header
+                    query.format = OutputFormat::from_name(value);
+                }
+                "q" | "quality" => {
+                    if let Ok(q) = value.parse::<u8>() {
+                        query.quality = Some(allowed_value(VARIANT_QUALITIES, q));
+                    }
+                }
+                "fit" => {
+                    query.fit = FitMode::from_name(value);
+                }
                 _ => {}
             }
         }
     }
 
-    ImageQuery { height, width }
+    query
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Rounds a requested value up to the next allowed one, or down to the largest.
+fn allowed_value<T: Copy + PartialOrd>(allowed: &[T], value: T) -> T {
+    allowed
+        .iter()
+        .copied()
+        .find(|allowed| *allowed >= value)
+        .unwrap_or(allowed[allowed.len() - 1])
+}
+
+/// Picks the smallest image format the client accepts, based on its Accept header.
+fn negotiate_format(accept: Option<&str>) -> OutputFormat {
+    let accepted = |mime: &str| {
+        accept.is_some_and(|accept| {
+            accept.split(',').any(|media_range| {
+                let mut params = media_range.split(';').map(str::trim);
+                params.next() == Some(mime)
+                    && !params.any(|param| {
+                        param
+                            .strip_prefix("q=")
+                            .and_then(|q| q.parse::<f32>().ok())
+                            .is_some_and(|q| q == 0.0)
+                    })
+            })
+        })
+    };
+
+    if accepted("image/avif") {
+        OutputFormat::Avif
+    } else if accepted("image/webp") {
+        OutputFormat::WebP
+    } else {
+        OutputFormat::Original
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct ImageQuery {
     #[serde(alias = "h")]
     height: Option<u32>,
     #[serde(alias = "w")]
     width: Option<u32>,
+    /// Output format, negotiated from the Accept header if not set.
+    #[serde(alias = "f")]
+    format: Option<OutputFormat>,
+    /// Encoding quality from 1 to 100, for the lossy formats (JPEG, AVIF and WebP).
+    #[serde(alias = "q")]
+    quality: Option<u8>,
+    /// How the image is fitted into the requested dimensions.
+    fit: Option<FitMode>,
+}
+
+impl ImageQuery {
+    /// Whether the query can be served from the images generated when the image was fetched.
+    fn is_preset(&self, format: OutputFormat) -> bool {
+        format == OutputFormat::Original && self.quality.is_none() && self.fit.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// The format of the fetched image
+    Original,
+    Avif,
+    WebP,
+    Png,
+    Jpeg,
+}
+
+impl OutputFormat {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "original" => Some(Self::Original),
+            "avif" => Some(Self::Avif),
+            "webp" => Some(Self::WebP),
+            "png" => Some(Self::Png),
+            "jpeg" | "jpg" => Some(Self::Jpeg),
+            _ => None,
+        }
+    }
+
+    fn image_format(&self, original: ImageFormat) -> ImageFormat {
+        match self {
+            Self::Original => original,
+            Self::Avif => ImageFormat::Avif,
+            Self::WebP => ImageFormat::WebP,
+            Self::Png => ImageFormat::Png,
+            Self::Jpeg => ImageFormat::Jpeg,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FitMode {
+    /// Fill the dimensions, cropping what overflows around the center
+    #[default]
+    Cover,
+    /// Fit within the dimensions, preserving the aspect ratio
+    Contain,
+    /// Stretch to the dimensions
+    Fill,
+}
+
+impl FitMode {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "cover" | "crop" => Some(Self::Cover),
+            "contain" | "inside" => Some(Self::Contain),
+            "fill" => Some(Self::Fill),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Cover => "cover",
+            Self::Contain => "contain",
+            Self::Fill => "fill",
+        }
+    }
+}
+
+/// Configuration of the image pipeline serving `/static`.
+#[derive(Debug, Clone)]
+pub struct StaticConfig {
+    /// Size budget in bytes of the artifacts directory, 0 for unbounded.
+    pub max_artifacts_size: u64,
+    /// Encoding quality of the lossy formats when the query doesn't set one.
+    pub default_quality: u8,
+    /// `max-age` in seconds of the Cache-Control header.
+    pub cache_max_age: u64,
+}
+
+impl Default for StaticConfig {
+    fn default() -> Self {
+        Self {
+            max_artifacts_size: 0,
+            default_quality: 80,
+            cache_max_age: 3600,
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct StaticHandler {
     artifacts_dir: Utf8PathBuf,
     pool: Pool<Sqlite>,
+    config: StaticConfig,
+    lru: Option<ArtifactsLru>,
+    encode_permits: Arc<Semaphore>,
 }
 
 impl StaticHandler {
-    pub fn new(artifacts_dir: Utf8PathBuf, pool: Pool<Sqlite>) -> Self {
+    pub fn new(artifacts_dir: Utf8PathBuf, pool: Pool<Sqlite>, config: StaticConfig) -> Self {
+        let lru = (config.max_artifacts_size > 0)
+            .then(|| ArtifactsLru::new(&artifacts_dir, config.max_artifacts_size));
+
         Self {
             artifacts_dir,
             pool,
+            config,
+            lru,
+            encode_permits: Arc::new(Semaphore::new(MAX_CONCURRENT_ENCODES)),
         }
     }
+
+    fn cache_control(&self) -> String {
+        format!(
+            "public, max-age={}, stale-while-revalidate=86400",
+            self.config.cache_max_age
+        )
+    }
 }
 
 #[async_trait::async_trait]
@@ -203,7 +382,11 @@ impl StaticHandler {
                 .fetch_and_process_image(&token_id, db_timestamp.as_deref())
                 .await
             {
-                Ok(_) => {}
+                Ok(_) => {
+                    if let Some(lru) = &self.lru {
+                        lru.record(&token_image_dir).await;
+                    }
+                }
                 Err(e) => {
                     error!(target: LOG_TARGET, error = ?e, "Failed to fetch and process image for token_id: {}", token_id);
                     return Ok(Response::builder()
@@ -214,7 +397,16 @@ impl StaticHandler {
             };
         }
 
-        let file_name = match self.file_name_from_dir_and_query(token_image_dir, &query) {
+        let format = query.format.unwrap_or_else(|| {
+            negotiate_format(req.headers().get("accept").and_then(|h| h.to_str().ok()))
+        });
+        // Unless the format is explicit, the response depends on the Accept header
+        let vary_accept = query.format.is_none();
+
+        let file_name = match self
+            .file_name_from_dir_and_query(&token_image_dir, &query, format)
+            .await
+        {
             Ok(file_name) => file_name,
             Err(e) => {
                 error!(target: LOG_TARGET, error = ?e, "Failed to get file name from directory and query");
@@ -229,6 +421,10 @@ impl StaticHandler {
             Ok(mut file) => {
                 let mut contents = vec![];
                 if file.read_to_end(&mut contents).await.is_ok() {
+                    if let Some(lru) = &self.lru {
+                        lru.touch(&token_image_dir);
+                    }
+
                     let mime = mime_guess::from_path(&file_name)
                         .first_or_octet_stream()
                         .to_string();
@@ -247,14 +443,10 @@ impl StaticHandler {
 
                     // Check conditional requests now that we have the content ETag
                     if let Some(ref client_etag_str) = client_etag {
-                        if client_etag_str == &etag {
-                            return Ok(Response::builder()
+                        if etag_matches(client_etag_str, &etag) {
+                            return Ok(self
+                                .cache_headers(Response::builder(), &etag, vary_accept)
                                 .status(StatusCode::NOT_MODIFIED)
-                                .header("etag", etag)
-                                .header(
-                                    "cache-control",
-                                    "public, max-age=3600, stale-while-revalidate=86400",
-                                )
                                 .body(Body::empty())
                                 .unwrap());
                         }
@@ -281,27 +473,19 @@ impl StaticHandler {
                             .as_secs();
 
                         if server_time_secs <= client_time_secs {
-                            return Ok(Response::builder()
+                            return Ok(self
+                                .cache_headers(Response::builder(), &etag, vary_accept)
                                 .status(StatusCode::NOT_MODIFIED)
-                                .header("etag", etag)
                                 .header("last-modified", httpdate::fmt_http_date(file_mod_time))
-                                .header(
-                                    "cache-control",
-                                    "public, max-age=3600, stale-while-revalidate=86400",
-                                )
                                 .body(Body::empty())
                                 .unwrap());
                         }
                     }
 
                     // Build response with content-based ETag and file-based Last-Modified
-                    let mut response_builder = Response::builder()
-                        .header("content-type", mime)
-                        .header("etag", etag)
-                        .header(
-                            "cache-control",
-                            "public, max-age=3600, stale-while-revalidate=86400",
-                        );
+                    let mut response_builder = self
+                        .cache_headers(Response::builder(), &etag, vary_accept)
+                        .header("content-type", mime);
 
                     // Add Last-Modified header from file modification time
                     if let Some(file_mod_time) = file_last_modified {
@@ -324,31 +508,41 @@ impl StaticHandler {
         }
     }
 
-    fn file_name_from_dir_and_query(
+    fn cache_headers(
+        &self,
+        builder: http::response::Builder,
+        etag: &str,
+        vary_accept: bool,
+    ) -> http::response::Builder {
+        let builder = builder
+            .header("etag", etag)
+            .header("cache-control", self.cache_control());
+        if vary_accept {
+            builder.header("vary", "accept")
+        } else {
+            builder
+        }
+    }
+
+    async fn file_name_from_dir_and_query(
         &self,
-        token_image_dir: Utf8PathBuf,
+        token_image_dir: &Utf8Path,
         query: &ImageQuery,
+        format: OutputFormat,
     ) -> Result<Utf8PathBuf> {
-        let mut entries = std::fs::read_dir(&token_image_dir)
-            .ok()
-            .into_iter()
-            .flatten()
-            .flatten();
-
-        // Find the base image (without @medium or @small)
-        let base_image = entries
-            .find(|entry| {
-                entry
-                    .file_name()
-                    .to_str()
-                    .map(|name| name.starts_with("image") && !name.contains('@'))
-                    .unwrap_or(false)
-            })
-            .with_context(|| "Failed to find base image")?;
+        let base_image = find_base_image(token_image_dir)?;
+        let base_ext = base_image.extension().unwrap_or_default();
 
-        let base_filename = base_image.file_name();
-        let base_filename = base_filename.to_str().unwrap();
-        let base_ext = base_filename.split('.').next_back().unwrap();
+        // SVGs scale on their own
+        if base_ext == "svg" {
+            return Ok(base_image);
+        }
+
+        if !query.is_preset(format) {
+            if let Some(variant) = self.image_variant(&base_image, query, format).await? {
+                return Ok(variant);
+            }
+        }
 
         let suffix = match (query.width, query.height) {
             // If either dimension is <= 100px, use small version
@@ -365,6 +559,71 @@ impl StaticHandler {
         Ok(token_image_dir.join(target_filename))
     }
 
+    /// Returns the path of the image transformed as requested, generating it from the base image
+    /// on first request. None if the image already has as many variants as it can.
+    async fn image_variant(
+        &self,
+        base_image: &Utf8Path,
+        query: &ImageQuery,
+        format: OutputFormat,
+    ) -> Result<Option<Utf8PathBuf>> {
+        let dir = base_image.parent().context("Base image has no directory")?;
+        let base_format = ImageFormat::from_path(base_image)
+            .with_context(|| format!("Unknown image format: {}", base_image))?;
+        let output_format = format.image_format(base_format);
+        let quality = query.quality.unwrap_or(self.config.default_quality);
+        let fit = query.fit.unwrap_or_default();
+
+        let file_name = format!(
+            "image@{}x{}-{}-q{}.{}",
+            query.width.unwrap_or(0),
+            query.height.unwrap_or(0),
+            fit.as_str(),
+            quality,
+            output_format.extensions_str()[0]
+        );
+        let file_path = dir.join(file_name);
+        if fs::try_exists(&file_path).await.unwrap_or(false) {
+            return Ok(Some(file_path));
+        }
+
+        let _permit = self.encode_permits.acquire().await?;
+        // Another request may have generated it while waiting for the permit
+        if fs::try_exists(&file_path).await.unwrap_or(false) {
+            return Ok(Some(file_path));
+        }
+        if count_variants(dir) >= MAX_IMAGE_VARIANTS {
+            debug!(target: LOG_TARGET, dir = %dir, "Image variants limit reached, serving a preset image.");
+            return Ok(None);
+        }
+
+        let bytes = fs::read(base_image)
+            .await
+            .with_context(|| format!("Failed to read image: {}", base_image))?;
+        let (width, height) = (query.width, query.height);
+        let encoded = tokio::task::spawn_blocking(move || {
+            let image = decode_first_frame(&bytes, base_format)?;
+            let image = transform_image(image, width, height, fit);
+            encode_image(&image, output_format, quality)
+        })
+        .await??;
+
+        // Write to a temporary file first so concurrent requests never serve a partial image
+        let temp_path = dir.join(format!(".{}.tmp", file_path.file_name().unwrap()));
+        fs::write(&temp_path, &encoded)
+            .await
+            .with_context(|| format!("Failed to write image variant: {}", temp_path))?;
+        fs::rename(&temp_path, &file_path)
+            .await
+            .with_context(|| format!("Failed to write image variant: {}", file_path))?;
+
+        if let Some(lru) = &self.lru {
+            lru.record(dir).await;
+        }
+
+        Ok(Some(file_path))
+    }
+
     async fn get_token_updated_at(&self, token_id: &str) -> Result<String> {
         // For both tokens and contracts, we can use the same query since contract address is the ID for contracts
         let query_str = format!("SELECT updated_at FROM {TOKENS_TABLE} WHERE id = ?");
@@ -456,12 +715,13 @@ impl StaticHandler {
             if href.starts_with("data:") {
                 patched_svg.push_str(m.as_str());
             } else {
-                // Fetch the image bytes using your fetchers
-                let image_bytes = if href.starts_with("http://") || href.starts_with("https://") {
-                    fetch_content_from_http(href).await?
-                } else if href.starts_with("ipfs://") {
-                    let cid = href.strip_prefix("ipfs://").unwrap();
-                    fetch_content_from_ipfs(cid).await?
+                // Fetch the image bytes using the shared resolver
+                let image_bytes = if href.starts_with("http://")
+                    || href.starts_with("https://")
+                    || href.starts_with("ipfs://")
+                    || href.starts_with("ar://")
+                {
+                    resolve_uri(href).await?.content
                 } else {
                     // fallback: leave as is
                     patched_svg.push_str(m.as_str());
@@ -516,89 +776,41 @@ impl StaticHandler {
 
         let metadata: serde_json::Value =
             serde_json::from_str(&query_result.0).context("Failed to parse metadata")?;
-        let image_uri = metadata
-            .get("image")
-            .context("Image URL not found in metadata")?
-            .as_str()
-            .context("Image field not a string")?
-            .to_string();
-
-        let image_type = match &image_uri {
-            uri if uri.starts_with("http") || uri.starts_with("https") => {
-                debug!(image_uri = %uri, "Fetching image from http/https URL");
-                // Fetch image from HTTP/HTTPS URL
-                let response = fetch_content_from_http(uri)
-                    .await
-                    .context("Failed to fetch image from URL")?;
+        // `image_data` holds raw SVG markup for fully on-chain collections
+        let image_uri = match (metadata.get("image"), metadata.get("image_data")) {
+            (Some(image), _) => image.as_str().context("Image field not a string")?,
+            (None, Some(image_data)) => image_data
+                .as_str()
+                .context("Image data field not a string")?,
+            (None, None) => anyhow::bail!("Image URL not found in metadata"),
+        }
+        .trim()
+        .to_string();
 
-                // svg files typically start with <svg or <?xml
-                if response.starts_with(b"<svg") || response.starts_with(b"<?xml") {
-                    ErcImageType::Svg(response.to_vec())
-                } else {
-                    let format = image::guess_format(&response).with_context(|| {
-                        format!(
-                            "Unknown file format for token_id: {}, data: {:?}",
-                            token_id, &response
-                        )
-                    })?;
-                    ErcImageType::DynamicImage((
-                        image::load_from_memory_with_format(&response, format)
-                            .context("Failed to load image from bytes")?,
-                        format,
-                    ))
-                }
-            }
-            uri if uri.starts_with("ipfs") => {
-                debug!(image_uri = %uri, "Fetching image from IPFS");
-                let cid = uri.strip_prefix("ipfs://").unwrap();
-                let response = fetch_content_from_ipfs(cid)
-                    .await
-                    .context("Failed to read image bytes from IPFS response")?;
+        let image_type = if image_uri.starts_with("<svg") || image_uri.starts_with("<?xml") {
+            debug!("Using inline SVG image markup");
+            ErcImageType::Svg(image_uri.into_bytes())
+        } else {
+            trace!(image_uri = %image_uri, "Resolving image URI");
+            let resolved = resolve_uri(&image_uri).await.with_context(|| {
+                format!("Failed to resolve image URI for token_id: {}", token_id)
+            })?;
 
-                if response.starts_with(b"<svg") || response.starts_with(b"<?xml") {
-                    ErcImageType::Svg(response.to_vec())
-                } else {
-                    let format = image::guess_format(&response).with_context(|| {
-                        format!(
-                            "Unknown file format for token_id: {}, cid: {}, data: {:?}",
-                            token_id, cid, &response
-                        )
-                    })?;
-                    ErcImageType::DynamicImage((
-                        image::load_from_memory_with_format(&response, format)
-                            .context("Failed to load image from bytes")?,
-                        format,
-                    ))
-                }
-            }
-            uri if uri.starts_with("data") => {
-                debug!("Parsing image from data URI");
-                trace!(data_uri = %uri);
-                // Parse and decode data URI
-                let data_url = DataUrl::process(uri).context("Failed to parse data URI")?;
-
-                // Check if it's an SVG
-                if data_url.mime_type() == &Mime::from_str("image/svg+xml").unwrap() {
-                    let decoded = data_url
-                        .decode_to_vec()
-                        .context("Failed to decode data URI")?;
-                    ErcImageType::Svg(decoded.0)
-                } else {
-                    let decoded = data_url
-                        .decode_to_vec()
-                        .context("Failed to decode data URI")?;
-                    let format = image::guess_format(&decoded.0).with_context(|| {
-                        format!("Unknown file format for token_id: {}", token_id)
-                    })?;
-                    ErcImageType::DynamicImage((
-                        image::load_from_memory_with_format(&decoded.0, format)
-                            .context("Failed to load image from bytes")?,
-                        format,
-                    ))
-                }
-            }
-            uri => {
-                return Err(anyhow::anyhow!("Unsupported URI scheme: {}", uri));
+            if resolved.is_svg() {
+                ErcImageType::Svg(resolved.content.to_vec())
+            } else {
+                let format = image::guess_format(&resolved.content).with_context(|| {
+                    format!(
+                        "Unknown file format for token_id: {}, image_uri: {}",
+                        token_id, image_uri
+                    )
+                })?;
+                ErcImageType::DynamicImage((
+                    decode_first_frame(&resolved.content, format)
+                        .context("Failed to load image from bytes")?,
+                    format,
+                    resolved.content.to_vec(),
+                ))
             }
         };
 
@@ -626,6 +838,11 @@ impl StaticHandler {
                 .join(token_id_part)
         };
 
+        // Drop the previous image and its variants, the new image may have another format
+        remove_dir_files(&dir_path)
+            .await
+            .context("Failed to remove previous images")?;
+
         // Create directories if they don't exist
         fs::create_dir_all(&dir_path)
             .await
@@ -645,7 +862,7 @@ impl StaticHandler {
         };
 
         match image_type {
-            ErcImageType::DynamicImage((img, format)) => {
+            ErcImageType::DynamicImage((img, format, original)) => {
                 let format_ext = format.extensions_str()[0];
 
                 let target_sizes = [("medium", 250, 250), ("small", 100, 100)];
@@ -656,9 +873,14 @@ impl StaticHandler {
                 let mut file = fs::File::create(&original_file_path)
                     .await
                     .with_context(|| format!("Failed to create file: {:?}", original_file_path))?;
-                let encoded_image = self
-                    .encode_image_to_vec(&img, format)
-                    .with_context(|| format!("Failed to encode image: {:?}", original_file_path))?;
+                // Animated GIFs are kept as is, re-encoding would only keep the first frame
+                let encoded_image = if format == ImageFormat::Gif {
+                    original
+                } else {
+                    self.encode_image_to_vec(&img, format).with_context(|| {
+                        format!("Failed to encode image: {:?}", original_file_path)
+                    })?
+                };
                 file.write_all(&encoded_image).await.with_context(|| {
                     format!("Failed to write image to file: {:?}", original_file_path)
                 })?;
@@ -744,6 +966,198 @@ impl StaticHandler {
 
 #[derive(Debug)]
 pub enum ErcImageType {
-    DynamicImage((DynamicImage, ImageFormat)),
+    /// Decoded image (first frame if animated), its format and the fetched bytes
+    DynamicImage((DynamicImage, ImageFormat, Vec<u8>)),
     Svg(Vec<u8>),
 }
+
+/// Finds the image fetched for a token, as opposed to its resized variants.
+fn find_base_image(token_image_dir: &Utf8Path) -> Result<Utf8PathBuf> {
+    token_image_dir
+        .read_dir_utf8()
+        .ok()
+        .into_iter()
+        .flatten()
+        .flatten()
+        .find(|entry| {
+            let name = entry.file_name();
+            name.starts_with("image") && !name.contains('@')
+        })
+        .map(|entry| entry.into_path())
+        .with_context(|| "Failed to find base image")
+}
+
+/// Counts the variants generated for an image, as opposed to its base and preset images.
+fn count_variants(token_image_dir: &Utf8Path) -> usize {
+    token_image_dir
+        .read_dir_utf8()
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| {
+            // Variants are named `image@<width>x<height>-<fit>-q<quality>.<ext>`
+            let name = entry.file_name();
+            name.starts_with("image@") && name.contains("-q")
+        })
+        .count()
+}
+
+/// Decodes an image, keeping only the first frame of animated GIFs.
+fn decode_first_frame(bytes: &[u8], format: ImageFormat) -> Result<DynamicImage> {
+    if format == ImageFormat::Gif {
+        let frame = GifDecoder::new(Cursor::new(bytes))?
+            .into_frames()
+            .next()
+            .context("GIF has no frames")??;
+        return Ok(DynamicImage::ImageRgba8(frame.into_buffer()));
+    }
+
+    Ok(image::load_from_memory_with_format(bytes, format)?)
+}
+
+/// Resizes an image to the requested dimensions. A missing dimension follows the aspect ratio.
+fn transform_image(
+    image: DynamicImage,
+    width: Option<u32>,
+    height: Option<u32>,
+    fit: FitMode,
+) -> DynamicImage {
+    let scale = |length: u32, target: u32, reference: u32| {
+        ((length as u64 * target as u64) / reference.max(1) as u64)
+            .clamp(1, MAX_IMAGE_DIMENSION as u64) as u32
+    };
+    let (width, height) = match (width, height) {
+        (None, None) => return image,
+        (Some(w), Some(h)) => (w, h),
+        (Some(w), None) => (w, scale(image.height(), w, image.width())),
+        (None, Some(h)) => (scale(image.width(), h, image.height()), h),
+    };
+
+    let filter = image::imageops::FilterType::Lanczos3;
+    match fit {
+        FitMode::Cover => image.resize_to_fill(width, height, filter),
+        FitMode::Contain => image.resize(width, height, filter),
+        FitMode::Fill => image.resize_exact(width, height, filter),
+    }
+}
+
+/// Encodes an image, applying the quality to the lossy formats.
+fn encode_image(image: &DynamicImage, format: ImageFormat, quality: u8) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    match format {
+        ImageFormat::Jpeg => {
+            // JPEG has no alpha channel
+            DynamicImage::ImageRgb8(image.to_rgb8())
+                .write_with_encoder(JpegEncoder::new_with_quality(&mut buf, quality))?
+        }
+        ImageFormat::Avif => DynamicImage::ImageRgba8(image.to_rgba8()).write_with_encoder(
+            AvifEncoder::new_with_speed_quality(&mut buf, AVIF_SPEED, quality),
+        )?,
+        ImageFormat::WebP => {
+            let image = image.to_rgba8();
+            let encoded = webp::Encoder::from_rgba(image.as_raw(), image.width(), image.height())
+                .encode_simple(false, quality as f32)
+                .map_err(|e| anyhow::anyhow!("Failed to encode WebP image: {:?}", e))?;
+            buf.extend_from_slice(&encoded);
+        }
+        format => image.write_to(&mut Cursor::new(&mut buf), format)?,
+    }
+    Ok(buf)
+}
+
+/// Checks an If-None-Match header value against the ETag of the served content.
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(|tag| tag.trim().trim_start_matches("W/"))
+        .any(|tag| tag == "*" || tag == etag)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgba, RgbaImage};
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn noise(width: u32, height: u32) -> DynamicImage {
+        DynamicImage::ImageRgba8(RgbaImage::from_fn(width, height, |x, y| {
+            let v = (x.wrapping_mul(7919) ^ y.wrapping_mul(104729)) as u8;
+            Rgba([v, v.wrapping_mul(3), v.wrapping_add(x as u8), 255])
+        }))
+    }
+
+    #[test]
+    fn test_encode_webp_quality() {
+        let image = noise(64, 64);
+        let low = encode_image(&image, ImageFormat::WebP, 10).unwrap();
+        let high = encode_image(&image, ImageFormat::WebP, 100).unwrap();
+        assert!(low.len() < high.len());
+
+        let decoded = image::load_from_memory_with_format(&low, ImageFormat::WebP).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (64, 64));
+    }
+
+    #[test]
+    fn test_encode_jpeg_drops_alpha() {
+        let encoded = encode_image(&noise(16, 8), ImageFormat::Jpeg, 80).unwrap();
+        let decoded = image::load_from_memory_with_format(&encoded, ImageFormat::Jpeg).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (16, 8));
+    }
+
+    #[test]
+    fn test_query_rounds_to_allowed_variants() {
+        let query = parse_image_query("w=90&h=100000&q=73&fit=contain");
+        assert_eq!(query.width, Some(100));
+        assert_eq!(query.height, Some(MAX_IMAGE_DIMENSION));
+        assert_eq!(query.quality, Some(80));
+        assert_eq!(query.fit, Some(FitMode::Contain));
+
+        let query = parse_image_query("w=0&q=0");
+        assert_eq!(query.width, Some(32));
+        assert_eq!(query.quality, Some(10));
+    }
+
+    #[tokio::test]
+    async fn test_image_variants_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = Utf8Path::from_path(temp_dir.path()).unwrap();
+        let dir = root.join("0x1");
+        std::fs::create_dir_all(&dir).unwrap();
+        let base_image = dir.join("image.png");
+        noise(8, 8).save(&base_image).unwrap();
+
+        let pool = sqlx::SqlitePool::connect_lazy("sqlite::memory:").unwrap();
+        let handler = StaticHandler::new(root.to_path_buf(), pool, StaticConfig::default());
+
+        let query = parse_image_query("w=32&h=32&f=webp");
+        let variant = handler
+            .image_variant(&base_image, &query, OutputFormat::WebP)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(variant.file_name(), Some("image@32x32-cover-q80.webp"));
+        let decoded = image::open(&variant).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (32, 32));
+
+        for quality in 1..MAX_IMAGE_VARIANTS {
+            std::fs::write(dir.join(format!("image@1x1-cover-q{quality}.png")), []).unwrap();
+        }
+        assert_eq!(count_variants(&dir), MAX_IMAGE_VARIANTS);
+
+        // Past the limit, existing variants are served and no new ones are generated
+        assert_eq!(
+            handler
+                .image_variant(&base_image, &query, OutputFormat::WebP)
+                .await
+                .unwrap(),
+            Some(variant)
+        );
+        let query = parse_image_query("w=64&h=64&f=webp");
+        assert!(handler
+            .image_variant(&base_image, &query, OutputFormat::WebP)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,379 @@
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Instant;
+use std::{fmt::Debug, time::Duration};
+
+use http::{Request, Response, StatusCode};
+use hyper::Body;
+use serde_json::{json, Value};
+use sqlx::SqlitePool;
+use starknet::core::types::{BlockId, BlockTag, MaybePreConfirmedBlockWithTxHashes};
+use starknet::providers::Provider;
+use tokio::sync::{Mutex, RwLock};
+use torii_auth::Capability;
+use torii_broker::types::{
+    AchievementProgressionUpdate, ActivityUpdate, AggregationUpdate, ContractUpdate, EntityUpdate,
+    EventMessageUpdate, EventUpdate, ModelUpdate, TokenBalanceUpdate, TokenTransferUpdate,
+    TokenUpdate, TransactionUpdate,
+};
+use torii_broker::MemoryBroker;
+use torii_processors::status::IndexerStatus;
+use torii_storage::proto::ContractQuery;
+use torii_storage::Storage;
+use tracing::warn;
+
+use super::Handler;
+
+const LOG_TARGET: &str = "torii::server::handlers::status";
+
+const HEALTH_PATH: &str = "/health";
+const READY_PATH: &str = "/ready";
+const STATUS_PATH: &str = "/status";
+
+/// Time after which a readiness check of the database fails.
+const DATABASE_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Time during which the chain head is reused, so that the status requests don't each reach the
+/// provider.
+const CHAIN_HEAD_TTL: Duration = Duration::from_secs(5);
+
+/// Number and timestamp of the latest block of the chain.
+type ChainHead = Option<(u64, u64)>;
+
+/// Liveness, readiness and indexing progress of Torii, for probes, load balancers and operators.
+#[derive(Debug)]
+pub struct StatusHandler<P: Provider + Sync + Send + Debug, S: Storage> {
+    pool: Arc<SqlitePool>,
+    storage: Arc<S>,
+    provider: P,
+    indexer_status: Arc<IndexerStatus>,
+    graphql_addr: Arc<RwLock<Option<SocketAddr>>>,
+    version_spec: String,
+    chain_head: Mutex<Option<(Instant, ChainHead)>>,
+}
+
+impl<P: Provider + Sync + Send + Debug, S: Storage> StatusHandler<P, S> {
+    pub fn new(
+        pool: Arc<SqlitePool>,
+        storage: Arc<S>,
+        provider: P,
+        indexer_status: Arc<IndexerStatus>,
+        graphql_addr: Arc<RwLock<Option<SocketAddr>>>,
+        version_spec: String,
+    ) -> Self {
+        Self {
+            pool,
+            storage,
+            provider,
+            indexer_status,
+            graphql_addr,
+            version_spec,
+            chain_head: Mutex::new(None),
+        }
+    }
+
+    /// The process is up and serving requests.
+    fn health(&self) -> Response<Body> {
+        json_response(
+            StatusCode::OK,
+            json!({ "status": "ok", "version": self.version_spec }),
+        )
+    }
+
+    /// The database answers, the executor applies the writes and GraphQL is served.
+    async fn ready(&self) -> Response<Body> {
+        let database = matches!(
+            tokio::time::timeout(
+                DATABASE_CHECK_TIMEOUT,
+                sqlx::query("SELECT 1").execute(&*self.pool)
+            )
+            .await,
+            Ok(Ok(_))
+        );
+        let executor = self.indexer_status.executor_alive();
+        let graphql = self.graphql_addr.read().await.is_some();
+
+        let ready = database && executor && graphql;
+        json_response(
+            if ready {
+                StatusCode::OK
+            } else {
+                StatusCode::SERVICE_UNAVAILABLE
+            },
+            json!({
+                "status": if ready { "ready" } else { "unavailable" },
+                "checks": {
+                    "database": database,
+                    "executor": executor,
+                    "graphql": graphql,
+                },
+            }),
+        )
+    }
+
+    /// The head of the chain, fetched at most once per [`CHAIN_HEAD_TTL`]. The lock is held while
+    /// fetching, so that concurrent requests wait for the same head.
+    async fn chain_head(&self) -> ChainHead {
+        let mut cached = self.chain_head.lock().await;
+        if let Some((fetched_at, head)) = *cached {
+            if fetched_at.elapsed() < CHAIN_HEAD_TTL {
+                return head;
+            }
+        }
+
+        // The chain can be unreachable, the rest of the status is still useful then
+        let head = match self
+            .provider
+            .get_block_with_tx_hashes(BlockId::Tag(BlockTag::Latest))
+            .await
+        {
+            Ok(MaybePreConfirmedBlockWithTxHashes::Block(block)) => {
+                Some((block.block_number, block.timestamp))
+            }
+            Ok(_) => None,
+            Err(e) => {
+                warn!(target: LOG_TARGET, error = ?e, "Fetching chain head.");
+                None
+            }
+        };
+        *cached = Some((Instant::now(), head));
+
+        head
+    }
+
+    /// The head of every indexed contract against the chain head, and the load of the indexer.
+    async fn status(&self) -> Response<Body> {
+        let contracts = match self
+            .storage
+            .contracts(&ContractQuery {
+                contract_addresses: vec![],
+                contract_types: vec![],
+            })
+            .await
+        {
+            Ok(contracts) => contracts,
+            Err(e) => {
+                return json_response(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    json!({ "error": format!("Failed to read contract cursors: {}", e) }),
+                )
+            }
+        };
+
+        let chain_head = self.chain_head().await;
+
+        let contracts = contracts
+            .iter()
+            .map(|contract| {
+                let (lag_blocks, lag_seconds) = match (chain_head, contract.head) {
+                    (Some((block_number, timestamp)), Some(head)) => (
+                        Some(block_number.saturating_sub(head)),
+                        contract
+                            .last_block_timestamp
+                            .map(|last| timestamp.saturating_sub(last)),
+                    ),
+                    _ => (None, None),
+                };
+
+                json!({
+                    "contract_address": format!("{:#x}", contract.contract_address),
+                    "contract_type": contract.contract_type.to_string(),
+                    "head": contract.head,
+                    "last_block_timestamp": contract.last_block_timestamp,
+                    "tps": contract.tps,
+                    "lag_blocks": lag_blocks,
+                    "lag_seconds": lag_seconds,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        json_response(
+            StatusCode::OK,
+            json!({
+                "version": self.version_spec,
+                "chain_head": chain_head.map(|(block_number, timestamp)| json!({
+                    "block_number": block_number,
+                    "timestamp": timestamp,
+                })),
+                "contracts": contracts,
+                "paused": self.indexer_status.paused(),
+                "pending_tasks": self.indexer_status.pending_tasks(),
+                "subscribers": subscriber_counts(),
+            }),
+        )
+    }
+}
+
+/// Open subscription streams per kind of update, from GraphQL, gRPC and MCP alike.
+fn subscriber_counts() -> Value {
+    json!({
+        "entities": MemoryBroker::<EntityUpdate>::subscriber_count(),
+        "event_messages": MemoryBroker::<EventMessageUpdate>::subscriber_count(),
+        "events": MemoryBroker::<EventUpdate>::subscriber_count(),
+        "models": MemoryBroker::<ModelUpdate>::subscriber_count(),
+        "contracts": MemoryBroker::<ContractUpdate>::subscriber_count(),
+        "tokens": MemoryBroker::<TokenUpdate>::subscriber_count(),
+        "token_balances": MemoryBroker::<TokenBalanceUpdate>::subscriber_count(),
+        "token_transfers": MemoryBroker::<TokenTransferUpdate>::subscriber_count(),
+        "transactions": MemoryBroker::<TransactionUpdate>::subscriber_count(),
+        "aggregations": MemoryBroker::<AggregationUpdate>::subscriber_count(),
+        "activities": MemoryBroker::<ActivityUpdate>::subscriber_count(),
+        "achievement_progressions": MemoryBroker::<AchievementProgressionUpdate>::subscriber_count(),
+    })
+}
+
+fn json_response(status: StatusCode, body: Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+#[async_trait::async_trait]
+impl<P: Provider + Sync + Send + Debug, S: Storage> Handler for StatusHandler<P, S> {
+    fn should_handle(&self, req: &Request<Body>) -> bool {
+        matches!(req.uri().path(), HEALTH_PATH | READY_PATH | STATUS_PATH)
+    }
+
+    fn capability(&self, req: &Request<Body>) -> Option<Capability> {
+        // Probes don't carry credentials
+        match req.uri().path() {
+            HEALTH_PATH | READY_PATH => None,
+            _ => Some(Capability::Read),
+        }
+    }
+
+    async fn handle(&self, req: Request<Body>, _client_addr: IpAddr) -> Response<Body> {
+        match req.uri().path() {
+            HEALTH_PATH => self.health(),
+            READY_PATH => self.ready().await,
+            _ => self.status().await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use starknet::core::types::Felt;
+    use starknet::providers::jsonrpc::HttpTransport;
+    use starknet::providers::{JsonRpcClient, Url};
+    use tokio::sync::broadcast;
+    use torii_sqlite::executor::Executor;
+    use torii_sqlite::Sql;
+    use torii_storage::proto::{ContractDefinition, ContractType};
+
+    use super::*;
+
+    type TestHandler = StatusHandler<JsonRpcClient<HttpTransport>, Sql>;
+
+    async fn handler(pool: SqlitePool) -> TestHandler {
+        // Nothing listens there, the chain is unreachable
+        let url: Url = "http://127.0.0.1:1".parse().unwrap();
+        let provider = JsonRpcClient::new(HttpTransport::new(url));
+        let (shutdown_tx, _) = broadcast::channel(1);
+        let (mut executor, sender) =
+            Executor::new(pool.clone(), shutdown_tx, Arc::new(provider.clone()))
+                .await
+                .unwrap();
+        tokio::spawn(async move {
+            executor.run().await.unwrap();
+        });
+
+        let db = Sql::new(
+            pool.clone(),
+            sender,
+            &[ContractDefinition {
+                address: Felt::ONE,
+                r#type: ContractType::WORLD,
+                starting_block: None,
+            }],
+        )
+        .await
+        .unwrap();
+        sqlx::query("UPDATE contracts SET head = 90, last_block_timestamp = 900")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        StatusHandler::new(
+            Arc::new(pool),
+            Arc::new(db),
+            provider,
+            Arc::new(IndexerStatus::default()),
+            Arc::new(RwLock::new(None)),
+            "1.0.0".to_string(),
+        )
+    }
+
+    async fn get(handler: &TestHandler, path: &str) -> (StatusCode, Value) {
+        let req = Request::get(path).body(Body::empty()).unwrap();
+        assert!(handler.should_handle(&req));
+        let response = handler.handle(req, IpAddr::V4(Ipv4Addr::LOCALHOST)).await;
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_health_and_ready(pool: SqlitePool) {
+        let handler = handler(pool).await;
+
+        let (status, body) = get(&handler, HEALTH_PATH).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["version"], "1.0.0");
+
+        // GraphQL isn't served yet
+        let (status, body) = get(&handler, READY_PATH).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["checks"]["database"], true);
+        assert_eq!(body["checks"]["executor"], true);
+        assert_eq!(body["checks"]["graphql"], false);
+
+        *handler.graphql_addr.write().await = Some(SocketAddr::from(([127, 0, 0, 1], 8080)));
+        let (status, _) = get(&handler, READY_PATH).await;
+        assert_eq!(status, StatusCode::OK);
+
+        handler.indexer_status.set_executor_stopped();
+        let (status, body) = get(&handler, READY_PATH).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["checks"]["executor"], false);
+
+        // probes don't need credentials, the status does
+        let req = Request::get(HEALTH_PATH).body(Body::empty()).unwrap();
+        assert_eq!(handler.capability(&req), None);
+        let req = Request::get(STATUS_PATH).body(Body::empty()).unwrap();
+        assert_eq!(handler.capability(&req), Some(Capability::Read));
+        let req = Request::get("/graphql").body(Body::empty()).unwrap();
+        assert!(!handler.should_handle(&req));
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_status(pool: SqlitePool) {
+        let handler = handler(pool).await;
+
+        // without the chain head, the lag is unknown
+        let (status, body) = get(&handler, STATUS_PATH).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["chain_head"], Value::Null);
+        assert_eq!(body["paused"], false);
+        let contract = &body["contracts"][0];
+        assert_eq!(contract["contract_address"], "0x1");
+        assert_eq!(contract["contract_type"], "WORLD");
+        assert_eq!(contract["head"], 90);
+        assert_eq!(contract["lag_blocks"], Value::Null);
+
+        // the failed fetch is cached too, a fresh head is used until it expires
+        assert!(handler.chain_head.lock().await.is_some());
+        *handler.chain_head.lock().await = Some((Instant::now(), Some((100, 1000))));
+        handler.indexer_status.set_paused(true);
+        let (_, body) = get(&handler, STATUS_PATH).await;
+        assert_eq!(body["chain_head"]["block_number"], 100);
+        assert_eq!(body["paused"], true);
+        let contract = &body["contracts"][0];
+        assert_eq!(contract["lag_blocks"], 10);
+        assert_eq!(contract["lag_seconds"], 100);
+    }
+}
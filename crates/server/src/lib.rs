@@ -1,4 +1,6 @@
+mod artifacts;
 pub(crate) mod handlers;
 pub mod proxy;
 
+pub use handlers::r#static::StaticConfig;
 pub use proxy::{ProxySettings, TlsConfig};
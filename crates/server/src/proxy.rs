@@ -3,12 +3,13 @@ use std::fmt::Debug;
 use std::fs::File;
 use std::io::BufReader;
 use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow;
 use camino::Utf8PathBuf;
-use http::header::CONTENT_TYPE;
+use http::header::{AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER, WWW_AUTHENTICATE};
 use http::{HeaderName, Method};
 use hyper::client::connect::dns::GaiResolver;
 use hyper::client::HttpConnector;
@@ -22,17 +23,25 @@ use sqlx::SqlitePool;
 use starknet::providers::Provider;
 use tokio::sync::RwLock;
 use tokio_rustls::TlsAcceptor;
+use torii_admin::Admin;
+use torii_auth::{credential, AuthError, Authenticator, API_KEY_HEADER, API_KEY_PARAM};
+use torii_processors::status::IndexerStatus;
+use torii_sqlite::sandbox::SqlSandbox;
 use torii_storage::Storage;
 use tower::ServiceBuilder;
 use tower_http::cors::{AllowOrigin, CorsLayer};
 use tracing::{debug, warn};
 
+use crate::handlers::admin::AdminHandler;
 use crate::handlers::graphql::GraphQLHandler;
 use crate::handlers::grpc::GrpcHandler;
 use crate::handlers::mcp::McpHandler;
 use crate::handlers::metadata::MetadataHandler;
-use crate::handlers::r#static::StaticHandler;
+use crate::handlers::r#static::{StaticConfig, StaticHandler};
+use crate::handlers::replication::ReplicationHandler;
+use crate::handlers::snapshot::SnapshotHandler;
 use crate::handlers::sql::SqlHandler;
+use crate::handlers::status::StatusHandler;
 use crate::handlers::Handler;
 
 pub const LOG_TARGET: &str = "torii::server::proxy";
@@ -48,8 +57,10 @@ pub struct ProxySettings {
     pub http2_keepalive_timeout: u64,
 }
 
-const DEFAULT_ALLOW_HEADERS: [&str; 13] = [
+const DEFAULT_ALLOW_HEADERS: [&str; 15] = [
     "accept",
+    "authorization",
+    "x-api-key",
     "origin",
     "content-type",
     "access-control-allow-origin",
@@ -131,8 +142,7 @@ pub struct Proxy<P: Provider + Sync + Send + Debug + 'static> {
     handlers: Arc<RwLock<Vec<Box<dyn Handler>>>>,
     version_spec: String,
     tls_config: Option<Arc<ServerConfig>>,
-    grpc_proxy_client: Arc<ReverseProxy<HttpConnector<GaiResolver>>>,
-    websocket_proxy_client: Arc<ReverseProxy<HttpConnector<GaiResolver>>>,
+    authenticator: Arc<Authenticator>,
     _provider: std::marker::PhantomData<P>,
 }
 
@@ -152,7 +162,7 @@ pub struct TlsConfig {
     pub key_path: String,
 }
 
-impl<P: Provider + Sync + Send + Debug + 'static> Proxy<P> {
+impl<P: Provider + Sync + Send + Clone + Debug + 'static> Proxy<P> {
     #[allow(clippy::too_many_arguments)]
     pub fn new<S: Storage + 'static>(
         addr: SocketAddr,
@@ -161,27 +171,62 @@ impl<P: Provider + Sync + Send + Debug + 'static> Proxy<P> {
         graphql_addr: Option<SocketAddr>,
         artifacts_dir: Utf8PathBuf,
         pool: Arc<SqlitePool>,
+        sql_sandbox: Arc<SqlSandbox>,
         storage: Arc<S>,
         provider: P,
+        indexer_status: Arc<IndexerStatus>,
+        authenticator: Arc<Authenticator>,
+        admin: Arc<Admin>,
         version_spec: String,
         proxy_settings: ProxySettings,
+        static_config: StaticConfig,
+        snapshot_dir: Option<PathBuf>,
+        replication_log: bool,
     ) -> Self {
         // Create proxy clients with configured settings
         let grpc_proxy_client = Arc::new(create_grpc_proxy_client(&proxy_settings));
         let websocket_proxy_client = Arc::new(create_websocket_proxy_client());
 
-        let handlers: Arc<RwLock<Vec<Box<dyn Handler>>>> = Arc::new(RwLock::new(vec![
+        let mut handlers: Vec<Box<dyn Handler>> = vec![
             Box::new(GraphQLHandler::new(
                 graphql_addr,
                 grpc_proxy_client.clone(),
                 websocket_proxy_client.clone(),
             )),
             Box::new(GrpcHandler::new(grpc_addr, grpc_proxy_client.clone())),
-            Box::new(McpHandler::new(pool.clone())),
-            Box::new(MetadataHandler::new(storage.clone(), provider)),
-            Box::new(SqlHandler::new(pool.clone())),
-            Box::new(StaticHandler::new(artifacts_dir, (*pool).clone())),
-        ]));
+            Box::new(AdminHandler::new(admin)),
+            Box::new(McpHandler::new(
+                pool.clone(),
+                sql_sandbox.clone(),
+                storage.clone(),
+            )),
+            Box::new(MetadataHandler::new(storage.clone(), provider.clone())),
+            Box::new(SqlHandler::new(sql_sandbox)),
+            Box::new(StatusHandler::new(
+                pool.clone(),
+                storage.clone(),
+                provider,
+                indexer_status,
+                Arc::new(RwLock::new(graphql_addr)),
+                version_spec.clone(),
+            )),
+            Box::new(StaticHandler::new(
+                artifacts_dir,
+                (*pool).clone(),
+                static_config,
+            )),
+        ];
+        if let Some(snapshot_dir) = snapshot_dir {
+            handlers.push(Box::new(SnapshotHandler::new(
+                pool.clone(),
+                snapshot_dir,
+                version_spec.clone(),
+            )));
+        }
+        if replication_log {
+            handlers.push(Box::new(ReplicationHandler::new(pool.clone())));
+        }
+        let handlers = Arc::new(RwLock::new(handlers));
 
         Self {
             addr,
@@ -189,8 +234,7 @@ impl<P: Provider + Sync + Send + Debug + 'static> Proxy<P> {
             handlers,
             version_spec,
             tls_config: None,
-            grpc_proxy_client,
-            websocket_proxy_client,
+            authenticator,
             _provider: std::marker::PhantomData,
         }
     }
@@ -239,19 +283,10 @@ impl<P: Provider + Sync + Send + Debug + 'static> Proxy<P> {
         Ok(server_config)
     }
 
-    pub async fn set_graphql_addr(&self, addr: SocketAddr) {
-        let mut handlers = self.handlers.write().await;
-        handlers[0] = Box::new(GraphQLHandler::new(
-            Some(addr),
-            self.grpc_proxy_client.clone(),
-            self.websocket_proxy_client.clone(),
-        ));
-    }
-
     fn create_cors_layer(&self) -> Option<CorsLayer> {
         let cors = CorsLayer::new()
             .max_age(DEFAULT_MAX_AGE)
-            .allow_methods([Method::GET, Method::POST])
+            .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
             .allow_headers(
                 DEFAULT_ALLOW_HEADERS
                     .iter()
@@ -306,6 +341,7 @@ impl<P: Provider + Sync + Send + Debug + 'static> Proxy<P> {
                                 let tls_acceptor = tls_acceptor.clone();
                                 let handlers = self.handlers.clone();
                                 let version_spec = self.version_spec.clone();
+                                let authenticator = self.authenticator.clone();
                                 let cors_layer = cors_layer.clone();
 
                                 tokio::spawn(async move {
@@ -316,9 +352,10 @@ impl<P: Provider + Sync + Send + Debug + 'static> Proxy<P> {
                                                 .service_fn(move |req| {
                                                     let handlers = handlers.clone();
                                                     let version_spec = version_spec.clone();
+                                                    let authenticator = authenticator.clone();
                                                     async move {
                                                         let handlers = handlers.read().await;
-                                                        handle(remote_addr.ip(), req, &handlers, &version_spec).await
+                                                        handle(remote_addr.ip(), req, &handlers, &authenticator, &version_spec).await
                                                     }
                                                 });
 
@@ -360,6 +397,7 @@ impl<P: Provider + Sync + Send + Debug + 'static> Proxy<P> {
                 let remote_addr = conn.remote_addr().ip();
                 let handlers = self.handlers.clone();
                 let version_spec = self.version_spec.clone();
+                let authenticator = self.authenticator.clone();
                 let cors_layer = cors_layer.clone();
 
                 let service =
@@ -368,9 +406,11 @@ impl<P: Provider + Sync + Send + Debug + 'static> Proxy<P> {
                         .service_fn(move |req| {
                             let handlers = handlers.clone();
                             let version_spec = version_spec.clone();
+                            let authenticator = authenticator.clone();
                             async move {
                                 let handlers = handlers.read().await;
-                                handle(remote_addr, req, &handlers, &version_spec).await
+                                handle(remote_addr, req, &handlers, &authenticator, &version_spec)
+                                    .await
                             }
                         });
 
@@ -393,10 +433,18 @@ async fn handle(
     client_ip: IpAddr,
     req: Request<Body>,
     handlers: &[Box<dyn Handler>],
+    authenticator: &Authenticator,
     version_spec: &str,
 ) -> Result<Response<Body>, Infallible> {
     for handler in handlers.iter() {
         if handler.should_handle(&req) {
+            if let Some(capability) = handler.capability(&req) {
+                if let Err(e) =
+                    authenticator.authorize(request_credential(&req).as_deref(), capability)
+                {
+                    return Ok(auth_error_response(e));
+                }
+            }
             return Ok(handler.handle(req, client_ip).await);
         }
     }
@@ -415,3 +463,124 @@ async fn handle(
         .body(Body::from(json.to_string()))
         .unwrap())
 }
+
+/// The credential of a request, from its headers or else its `api_key` query parameter.
+fn request_credential(req: &Request<Body>) -> Option<String> {
+    let header = |name| {
+        req.headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+    };
+    credential(header(AUTHORIZATION.as_str()), header(API_KEY_HEADER))
+        .map(str::to_string)
+        .or_else(|| {
+            form_urlencoded::parse(req.uri().query().unwrap_or_default().as_bytes())
+                .find(|(key, _)| key == API_KEY_PARAM)
+                .map(|(_, value)| value.to_string())
+        })
+}
+
+fn auth_error_response(error: AuthError) -> Response<Body> {
+    let (status, code) = match &error {
+        AuthError::Unauthenticated => (StatusCode::UNAUTHORIZED, "unauthenticated"),
+        AuthError::Forbidden(_) => (StatusCode::FORBIDDEN, "forbidden"),
+        AuthError::RateLimited { .. } => (StatusCode::TOO_MANY_REQUESTS, "rate_limited"),
+    };
+
+    let mut response = Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json");
+    match &error {
+        AuthError::Unauthenticated => response = response.header(WWW_AUTHENTICATE, "Bearer"),
+        AuthError::RateLimited { retry_after } => {
+            response = response.header(RETRY_AFTER, retry_after.as_secs().max(1))
+        }
+        AuthError::Forbidden(_) => {}
+    }
+
+    let json = json!({ "error": { "code": code, "message": error.to_string() } });
+    response.body(Body::from(json.to_string())).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use torii_auth::{AuthConfig, Capability};
+
+    use super::*;
+
+    /// Serves `/sql`, which requires the SQL capability.
+    #[derive(Debug)]
+    struct SqlOnlyHandler;
+
+    #[async_trait::async_trait]
+    impl Handler for SqlOnlyHandler {
+        fn should_handle(&self, req: &Request<Body>) -> bool {
+            req.uri().path() == "/sql"
+        }
+
+        fn capability(&self, _req: &Request<Body>) -> Option<Capability> {
+            Some(Capability::Sql)
+        }
+
+        async fn handle(&self, _req: Request<Body>, _client_addr: IpAddr) -> Response<Body> {
+            Response::new(Body::empty())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handle_authorization() {
+        let authenticator = Authenticator::new(AuthConfig {
+            api_keys: vec![
+                "analyst:read,sql:2".parse().unwrap(),
+                "reader:read".parse().unwrap(),
+            ],
+            ..Default::default()
+        })
+        .unwrap();
+        let handlers: Vec<Box<dyn Handler>> = vec![Box::new(SqlOnlyHandler)];
+        let call = |uri: &str, api_key: Option<&str>| {
+            let mut builder = Request::get(uri);
+            if let Some(api_key) = api_key {
+                builder = builder.header(API_KEY_HEADER, api_key);
+            }
+            let req = builder.body(Body::empty()).unwrap();
+            let handlers = &handlers;
+            let authenticator = &authenticator;
+            async move {
+                handle(
+                    IpAddr::V4(Ipv4Addr::LOCALHOST),
+                    req,
+                    handlers,
+                    authenticator,
+                    "1.0.0",
+                )
+                .await
+                .unwrap()
+            }
+        };
+
+        let response = call("/sql", None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(response.headers()[WWW_AUTHENTICATE], "Bearer");
+
+        let response = call("/sql", Some("reader")).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = call("/sql", Some("analyst")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        // the key of browser websockets is a query parameter
+        let response = call("/sql?api_key=analyst", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // 2 requests per minute
+        let response = call("/sql", Some("analyst")).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key(RETRY_AFTER));
+
+        // no handler, the version of the service is always served
+        let response = call("/", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -1,13 +1,25 @@
+/// Name of the database file in the database directory.
+pub const DATABASE_FILE: &str = "torii.db";
+
 pub const QUERY_QUEUE_BATCH_SIZE: usize = 1000;
 pub const TOKEN_BALANCE_TABLE: &str = "token_balances";
 pub const TOKEN_TRANSFER_TABLE: &str = "token_transfers";
 pub const TOKENS_TABLE: &str = "tokens";
+pub const TOKEN_HOLDER_COUNTS_TABLE: &str = "token_holder_counts";
+pub const TOKEN_HOLDERS_TABLE: &str = "token_holders";
+pub const TOKEN_HOLDER_COUNT_HISTORY_TABLE: &str = "token_holder_count_history";
+pub const TOKEN_METADATA_JOBS_TABLE: &str = "token_metadata_jobs";
 pub const WORLD_CONTRACT_TYPE: &str = "WORLD";
 pub const SQL_FELT_DELIMITER: &str = "/";
 pub const REQ_MAX_RETRIES: u8 = 3;
 
 pub const SQL_DEFAULT_LIMIT: u64 = 10000;
 pub const SQL_MAX_JOINS: usize = 64;
+// Maximum number of join clauses nested in one another, each one is a correlated subquery
+pub const SQL_MAX_JOIN_DEPTH: usize = 4;
+
+pub const CONTRACTS_TABLE: &str = "contracts";
+pub const MODELS_TABLE: &str = "models";
 
 pub const ENTITIES_TABLE: &str = "entities";
 pub const ENTITIES_MODEL_RELATION_TABLE: &str = "entity_model";
@@ -22,3 +34,27 @@ pub const EVENT_MESSAGES_ENTITY_RELATION_COLUMN: &str = "internal_event_message_
 pub const EVENT_MESSAGES_HISTORICAL_TABLE: &str = "event_messages_historical";
 
 pub const EVENTS_TABLE: &str = "events";
+
+pub const SEARCH_INDEX_TABLE: &str = "search_index";
+
+/// The statements populating the search index from the searchable tables, the initial
+/// population of its migration, run again when the index is rebuilt.
+pub const SEARCH_INDEX_POPULATE_QUERIES: [&str; 4] = [
+    "INSERT INTO search_index(entity_type, entity_id, primary_text, secondary_text, metadata)
+     SELECT 'achievement', id, title || ' ' || COALESCE(group_name, ''), description,
+         json_object('world_address', world_address, 'namespace', namespace, 'group_name',
+         group_name)
+     FROM achievements",
+    "INSERT INTO search_index(entity_type, entity_id, primary_text, secondary_text, metadata)
+     SELECT 'controller', id, username, '', json_object('address', address)
+     FROM controllers",
+    "INSERT INTO search_index(entity_type, entity_id, primary_text, secondary_text, metadata)
+     SELECT 'token_attribute', id, trait_name, trait_value, json_object('token_id', token_id)
+     FROM token_attributes",
+    "INSERT INTO search_index(entity_type, entity_id, primary_text, secondary_text, metadata)
+     SELECT 'token', id, name || ' ' || symbol, COALESCE(metadata, ''),
+         json_object('contract_address', contract_address, 'symbol', symbol, 'decimals',
+         decimals)
+     FROM tokens
+     WHERE token_id IS NULL",
+];
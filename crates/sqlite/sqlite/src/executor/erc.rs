@@ -1,4 +1,8 @@
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use cainome::cairo_serde::CairoSerde;
+use serde::{Deserialize, Serialize};
 use serde_json;
 use starknet::core::types::{BlockId, BlockTag, FunctionCall, U256};
 use starknet::macros::selector;
@@ -8,27 +12,35 @@ use torii_proto::{BalanceId, TokenId};
 use tracing::{debug, warn};
 
 use super::{ApplyBalanceDiffQuery, BrokerMessage, Executor};
-use crate::constants::TOKEN_BALANCE_TABLE;
+use crate::constants::{
+    TOKEN_BALANCE_TABLE, TOKEN_HOLDERS_TABLE, TOKEN_HOLDER_COUNTS_TABLE,
+    TOKEN_HOLDER_COUNT_HISTORY_TABLE,
+};
 use crate::error::Error;
 use crate::executor::LOG_TARGET;
 use crate::types::TokenBalance;
-use crate::utils::{felt_to_sql_string, sql_string_to_u256, u256_to_sql_string};
+use crate::utils::{felt_to_sql_string, sql_string_to_u256, u256_to_f64, u256_to_sql_string};
 use torii_math::I256;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RegisterNftTokenQuery {
     pub contract_address: Felt,
+    #[serde(with = "u256_hex")]
     pub token_id: U256,
     pub metadata: String,
+    /// Name and symbol of the contract, as resolved by the executor of the primary. Followers
+    /// replay them instead of calling the chain.
+    #[serde(default)]
+    pub name_and_symbol: Option<(String, String)>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateTokenMetadataQuery {
     pub token_id: TokenId,
     pub metadata: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RegisterTokenContractQuery {
     pub contract_address: Felt,
     pub name: String,
@@ -37,6 +49,27 @@ pub struct RegisterTokenContractQuery {
     pub metadata: Option<String>,
 }
 
+/// (De)serializes a `U256` as its SQL string, for the replication log.
+mod u256_hex {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use starknet::core::types::U256;
+
+    use crate::utils::{sql_string_to_u256, u256_to_sql_string};
+
+    pub fn serialize<S: Serializer>(value: &U256, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&u256_to_sql_string(value))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<U256, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        let hex = s.strip_prefix("0x").unwrap_or(&s);
+        if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(serde::de::Error::custom(format!("Invalid U256: {}", s)));
+        }
+        Ok(sql_string_to_u256(&s))
+    }
+}
+
 /// Represents a trait extracted from NFT metadata
 #[derive(Debug, Clone)]
 pub struct TokenTrait {
@@ -304,12 +337,153 @@ pub async fn update_contract_traits_on_metadata_change(
     Ok(())
 }
 
+/// The changes of the holdings of a token contract, applied to its totals once its balances are.
+#[derive(Debug)]
+pub struct HoldersDiff {
+    pub holder_count: i64,
+    pub added: U256,
+    pub removed: U256,
+}
+
+impl Default for HoldersDiff {
+    fn default() -> Self {
+        Self {
+            holder_count: 0,
+            added: U256::from(0u8),
+            removed: U256::from(0u8),
+        }
+    }
+}
+
+/// Applies the change of a balance to the holding of its account, the sum of its balances across
+/// the token IDs of the contract. Returns the change in the number of holders (-1, 0 or 1).
+pub async fn update_holder(
+    id: &BalanceId,
+    previous: U256,
+    balance: U256,
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+) -> Result<i64, sqlx::Error> {
+    let zero = U256::from(0u8);
+    let contract_id = felt_to_sql_string(&id.token_id.contract_address());
+    let account_id = felt_to_sql_string(&id.account_address);
+
+    let holder: Option<(String, i64)> = sqlx::query_as(&format!(
+        "SELECT balance, token_count FROM {TOKEN_HOLDERS_TABLE} WHERE contract_address = ? AND \
+         account_address = ?"
+    ))
+    .bind(&contract_id)
+    .bind(&account_id)
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    let (mut held, token_count) = holder.as_ref().map_or((zero, 0), |(held, token_count)| {
+        (sql_string_to_u256(held), *token_count)
+    });
+    held += balance;
+    held = if held >= previous {
+        held - previous
+    } else {
+        zero
+    };
+    let token_count = token_count + i64::from(balance != zero) - i64::from(previous != zero);
+
+    // An account is a holder as long as it has a non-zero balance for any token ID
+    if token_count <= 0 {
+        sqlx::query(&format!(
+            "DELETE FROM {TOKEN_HOLDERS_TABLE} WHERE contract_address = ? AND account_address = ?"
+        ))
+        .bind(&contract_id)
+        .bind(&account_id)
+        .execute(&mut **tx)
+        .await?;
+
+        return Ok(-i64::from(holder.is_some()));
+    }
+
+    sqlx::query(&format!(
+        "INSERT INTO {TOKEN_HOLDERS_TABLE} (contract_address, account_address, balance, amount, \
+         token_count) VALUES (?, ?, ?, ?, ?) ON CONFLICT(contract_address, account_address) DO \
+         UPDATE SET balance = EXCLUDED.balance, amount = EXCLUDED.amount, token_count = \
+         EXCLUDED.token_count"
+    ))
+    .bind(&contract_id)
+    .bind(&account_id)
+    .bind(u256_to_sql_string(&held))
+    .bind(u256_to_f64(&held))
+    .bind(token_count)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(i64::from(holder.is_none()))
+}
+
+/// Applies the changes of the holdings of a token contract to its holder count and total held,
+/// and records a snapshot of the count in its history at the given block timestamp if it changed.
+pub async fn update_holder_totals(
+    contract_address: &Felt,
+    diff: &HoldersDiff,
+    timestamp: u64,
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+) -> Result<i64, sqlx::Error> {
+    let zero = U256::from(0u8);
+    let contract_id = felt_to_sql_string(contract_address);
+    let current: Option<(i64, String)> = sqlx::query_as(&format!(
+        "SELECT holder_count, total_held FROM {TOKEN_HOLDER_COUNTS_TABLE} WHERE \
+         contract_address = ?"
+    ))
+    .bind(&contract_id)
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    let (holder_count, mut total_held) = current.map_or((0, zero), |(holder_count, total_held)| {
+        (holder_count, sql_string_to_u256(&total_held))
+    });
+    // Counts can't go below zero, even if we missed some transfers
+    let holder_count = (holder_count + diff.holder_count).max(0);
+    total_held += diff.added;
+    total_held = if total_held >= diff.removed {
+        total_held - diff.removed
+    } else {
+        zero
+    };
+
+    sqlx::query(&format!(
+        "INSERT INTO {TOKEN_HOLDER_COUNTS_TABLE} (contract_address, holder_count, total_held, \
+         updated_at) VALUES (?, ?, ?, CURRENT_TIMESTAMP) ON CONFLICT(contract_address) DO UPDATE \
+         SET holder_count = EXCLUDED.holder_count, total_held = EXCLUDED.total_held, updated_at = \
+         CURRENT_TIMESTAMP"
+    ))
+    .bind(&contract_id)
+    .bind(holder_count)
+    .bind(u256_to_sql_string(&total_held))
+    .execute(&mut **tx)
+    .await?;
+
+    if diff.holder_count != 0 {
+        sqlx::query(&format!(
+            "INSERT INTO {TOKEN_HOLDER_COUNT_HISTORY_TABLE} (contract_address, timestamp, \
+             holder_count) VALUES (?, ?, ?) ON CONFLICT(contract_address, timestamp) DO UPDATE \
+             SET holder_count = EXCLUDED.holder_count"
+        ))
+        .bind(&contract_id)
+        .bind(timestamp as i64)
+        .bind(holder_count)
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    debug!(target: LOG_TARGET, contract_address = %contract_id, holder_count = holder_count, "Updated token holder totals");
+    Ok(holder_count)
+}
+
 impl<P: Provider + Sync + Send + Clone + 'static> Executor<'_, P> {
+    /// Applies the balance and total supply diffs. Returns the balance diffs as applied, which
+    /// differ from the given ones where a balance was read from the chain.
     pub async fn apply_balance_diff(
         &mut self,
         apply_balance_diff: ApplyBalanceDiffQuery,
         provider: P,
-    ) -> Result<(), Error> {
+    ) -> Result<HashMap<BalanceId, I256>, Error> {
         // Update total supply for all token types
         let tx = self.transaction.as_mut().unwrap();
         for (token_id, supply_diff) in apply_balance_diff.total_supply_diff.iter() {
@@ -358,8 +532,10 @@ impl<P: Provider + Sync + Send + Clone + 'static> Executor<'_, P> {
             }
         }
 
-        // Then, update individual balances
+        // Then, update individual balances, tracking the holdings they change
         let balances_diff = apply_balance_diff.balances_diff;
+        let mut holders_diff: HashMap<Felt, HoldersDiff> = HashMap::new();
+        let mut applied_diff = HashMap::with_capacity(balances_diff.len());
         for (balance_id, balance) in balances_diff.iter() {
             let cursor = apply_balance_diff
                 .cursors
@@ -371,13 +547,46 @@ impl<P: Provider + Sync + Send + Clone + 'static> Executor<'_, P> {
                 BlockId::Number(cursor.head.unwrap())
             };
 
-            self.apply_balance_diff_helper(balance_id, balance, block_id, provider.clone())
+            let (previous, new_balance, holder_delta) = self
+                .apply_balance_diff_helper(balance_id, balance, block_id, provider.clone())
                 .await?;
+            applied_diff.insert(
+                balance_id.clone(),
+                I256::from(new_balance) - I256::from(previous),
+            );
+            if previous != new_balance {
+                let diff = holders_diff
+                    .entry(balance_id.token_id.contract_address())
+                    .or_default();
+                diff.holder_count += holder_delta;
+                diff.added += new_balance;
+                diff.removed += previous;
+            }
         }
 
-        Ok(())
+        // Finally, update the totals of the contracts whose holdings changed
+        let tx = self.transaction.as_mut().unwrap();
+        for (contract_address, diff) in holders_diff {
+            let timestamp = apply_balance_diff
+                .cursors
+                .get(&contract_address)
+                .and_then(|cursor| cursor.last_block_timestamp)
+                .unwrap_or_else(|| {
+                    SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .unwrap()
+                        .as_secs()
+                });
+
+            update_holder_totals(&contract_address, &diff, timestamp, tx).await?;
+        }
+
+        Ok(applied_diff)
     }
 
+    /// Applies a balance diff to a single balance and to the holding of its account.
+    /// Returns the previous and new balances, and the change in the number of holders of the
+    /// token contract (-1, 0 or 1).
     #[allow(clippy::too_many_arguments)]
     pub async fn apply_balance_diff_helper(
         &mut self,
@@ -385,7 +594,7 @@ impl<P: Provider + Sync + Send + Clone + 'static> Executor<'_, P> {
         balance_diff: &I256,
         block_id: BlockId,
         provider: P,
-    ) -> Result<(), Error> {
+    ) -> Result<(U256, U256, i64), Error> {
         let tx = self.transaction.as_mut().unwrap();
         let balance: Option<String> = sqlx::query_scalar(&format!(
             "SELECT balance FROM {TOKEN_BALANCE_TABLE} WHERE id = ?"
@@ -399,6 +608,7 @@ impl<P: Provider + Sync + Send + Clone + 'static> Executor<'_, P> {
         } else {
             U256::from(0u8)
         };
+        let previous = balance;
 
         if balance_diff.is_negative {
             if balance < balance_diff.value {
@@ -464,13 +674,29 @@ impl<P: Provider + Sync + Send + Clone + 'static> Executor<'_, P> {
         debug!(target: LOG_TARGET, token_balance = ?token_balance, "Applied balance diff");
         self.publish_optimistic_and_queue(BrokerMessage::TokenBalanceUpdated(token_balance.into()));
 
-        Ok(())
+        let tx = self.transaction.as_mut().unwrap();
+        let holder_delta = if previous != balance {
+            update_holder(id, previous, balance, tx).await?
+        } else {
+            0
+        };
+
+        Ok((previous, balance, holder_delta))
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
+    use starknet::providers::jsonrpc::HttpTransport;
+    use starknet::providers::{JsonRpcClient, Url};
+    use torii_proto::{ContractCursor, ContractDefinition, ContractType, TokenHolderStatsQuery};
+    use torii_storage::{ReadOnlyStorage, Storage};
+
     use super::*;
+    use crate::executor::Executor;
+    use crate::Sql;
     use serde_json::json;
 
     #[test]
@@ -694,4 +920,130 @@ mod tests {
 
         assert_eq!(result, expected.to_string());
     }
+
+    fn diff(value: u64, is_negative: bool) -> I256 {
+        I256 {
+            value: U256::from(value),
+            is_negative,
+        }
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn test_apply_balance_diff_holders(pool: sqlx::SqlitePool) {
+        let (shutdown_tx, _) = tokio::sync::broadcast::channel(1);
+        let url: Url = "https://www.example.com".parse().unwrap();
+        let provider = Arc::new(JsonRpcClient::new(HttpTransport::new(url)));
+        let (mut executor, sender) = Executor::new(pool.clone(), shutdown_tx, provider)
+            .await
+            .unwrap();
+        tokio::spawn(async move {
+            executor.run().await.unwrap();
+        });
+
+        let contract = Felt::from(0x1155u64);
+        let db = Sql::new(
+            pool.clone(),
+            sender,
+            &[ContractDefinition {
+                address: contract,
+                r#type: ContractType::ERC1155,
+                starting_block: None,
+            }],
+        )
+        .await
+        .unwrap();
+
+        let (alice, bob) = (Felt::from(0xa11ceu64), Felt::from(0xb0bu64));
+        let balance_id = |account_address, token_id: u64| BalanceId {
+            account_address,
+            token_id: TokenId::Nft(contract, U256::from(token_id)),
+        };
+        let cursors = HashMap::from([(
+            contract,
+            ContractCursor {
+                contract_address: contract,
+                head: Some(1),
+                last_block_timestamp: Some(100),
+                last_pending_block_tx: None,
+            },
+        )]);
+        let stats_query = TokenHolderStatsQuery {
+            contract_addresses: vec![contract],
+            ..Default::default()
+        };
+
+        db.apply_balances_diff(
+            HashMap::from([
+                (balance_id(alice, 1), diff(5, false)),
+                (balance_id(alice, 2), diff(3, false)),
+                (balance_id(bob, 1), diff(10, false)),
+            ]),
+            HashMap::new(),
+            cursors.clone(),
+        )
+        .await
+        .unwrap();
+        db.execute().await.unwrap();
+
+        let stats = db.token_holder_stats(&stats_query).await.unwrap().remove(0);
+        assert_eq!(stats.holder_count, 2);
+        assert_eq!(stats.total_held, crypto_bigint::U256::from(18u64));
+        let top_holders = stats
+            .top_holders
+            .iter()
+            .map(|holder| (holder.account_address, holder.balance, holder.token_count))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            top_holders,
+            vec![
+                (bob, crypto_bigint::U256::from(10u64), 1),
+                (alice, crypto_bigint::U256::from(8u64), 2),
+            ]
+        );
+        assert!((stats.top_holders_share - 1.0).abs() < 1e-9);
+        // [8, 10]: (2 * (8 + 2 * 10)) / (2 * 18) - 3 / 2
+        assert!((stats.gini - (56.0 / 36.0 - 1.5)).abs() < 1e-9);
+
+        // Alice stays a holder until her last token is gone
+        let mut cursors = cursors;
+        cursors.get_mut(&contract).unwrap().last_block_timestamp = Some(200);
+        db.apply_balances_diff(
+            HashMap::from([(balance_id(alice, 1), diff(5, true))]),
+            HashMap::new(),
+            cursors.clone(),
+        )
+        .await
+        .unwrap();
+        db.execute().await.unwrap();
+
+        let stats = db.token_holder_stats(&stats_query).await.unwrap().remove(0);
+        assert_eq!(stats.holder_count, 2);
+        assert_eq!(stats.total_held, crypto_bigint::U256::from(13u64));
+        assert_eq!(stats.top_holders[1].token_count, 1);
+
+        cursors.get_mut(&contract).unwrap().last_block_timestamp = Some(300);
+        db.apply_balances_diff(
+            HashMap::from([(balance_id(alice, 2), diff(3, true))]),
+            HashMap::new(),
+            cursors,
+        )
+        .await
+        .unwrap();
+        db.execute().await.unwrap();
+
+        let stats = db.token_holder_stats(&stats_query).await.unwrap().remove(0);
+        assert_eq!(stats.holder_count, 1);
+        assert_eq!(stats.total_held, crypto_bigint::U256::from(10u64));
+        assert_eq!(stats.top_holders.len(), 1);
+        assert_eq!(stats.gini, 0.0);
+        // The count only changed at the first and last diffs
+        assert_eq!(
+            stats
+                .holder_count_history
+                .iter()
+                .map(|point| (point.timestamp, point.holder_count))
+                .collect::<Vec<_>>(),
+            vec![(100, 2), (300, 1)]
+        );
+    }
 }
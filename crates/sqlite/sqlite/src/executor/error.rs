@@ -6,6 +6,8 @@ pub enum ExecutorError {
     Provider(#[from] starknet::providers::ProviderError),
     #[error(transparent)]
     Sqlx(#[from] sqlx::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
 }
 
 #[derive(Error, Debug)]
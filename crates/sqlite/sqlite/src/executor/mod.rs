@@ -10,6 +10,7 @@ use erc::{
     update_contract_traits_on_metadata_change, UpdateTokenMetadataQuery,
 };
 use metrics::{counter, histogram};
+use serde::{Deserialize, Serialize};
 use serde_json;
 use sqlx::{FromRow, Pool, Sqlite, Transaction as SqlxTransaction};
 use starknet::core::types::requests::CallRequest;
@@ -29,11 +30,12 @@ use torii_broker::types::{
 use torii_math::I256;
 use torii_proto::{BalanceId, ContractCursor, TokenId, TransactionCall};
 use torii_sqlite_types::TokenTransfer as SQLTokenTransfer;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, debug_span, error, info, info_span, warn, Instrument, Span};
 
 use crate::constants::TOKENS_TABLE;
 use crate::error::ParseError;
 use crate::executor::error::{ExecutorError, ExecutorQueryError};
+use crate::replication::{self, ReplicatedQuery};
 use crate::utils::{
     felt_and_u256_to_sql_string, felt_to_sql_string, felts_to_sql_string, u256_to_sql_string,
 };
@@ -53,7 +55,7 @@ pub(crate) const LOG_TARGET: &str = "torii::sqlite::executor";
 pub type Result<T> = std::result::Result<T, ExecutorError>;
 pub type QueryResult<T> = std::result::Result<T, ExecutorQueryError>;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Argument {
     Null,
     Int(i64),
@@ -77,7 +79,7 @@ pub enum BrokerMessage {
     ActivityUpdated(<ActivityUpdate as InnerType>::Inner),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeleteEntityQuery {
     pub entity_id: String,
     pub model_id: String,
@@ -86,14 +88,14 @@ pub struct DeleteEntityQuery {
     pub ty: Ty,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApplyBalanceDiffQuery {
     pub balances_diff: HashMap<BalanceId, I256>,
     pub total_supply_diff: HashMap<TokenId, I256>,
     pub cursors: HashMap<Felt, ContractCursor>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EventMessageQuery {
     pub world_address: String,
     pub entity_id: String,
@@ -105,14 +107,14 @@ pub struct EventMessageQuery {
     pub ty: Ty,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StoreTransactionQuery {
     pub contract_addresses: HashSet<Felt>,
     pub calls: Vec<TransactionCall>,
     pub unique_models: HashSet<Felt>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EntityQuery {
     pub entity_id: String,
     pub model_id: String,
@@ -123,13 +125,13 @@ pub struct EntityQuery {
     pub ty: Ty,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateCursorsQuery {
     pub cursors: HashMap<Felt, ContractCursor>,
     pub cursor_transactions: HashMap<Felt, HashSet<Felt>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum QueryType {
     StoreTransaction(StoreTransactionQuery),
     UpdateCursors(UpdateCursorsQuery),
@@ -183,6 +185,10 @@ pub struct Executor<'c, P: Provider + Sync + Send + Clone + 'static> {
     pool: Pool<Sqlite>,
     transaction: Option<SqlxTransaction<'c, Sqlite>>,
     publish_queue: Vec<BrokerMessage>,
+    // Span of the query being handled, the parent of its optimistic updates
+    span: Span,
+    // Queries of the transaction, written to the replication log on commit
+    replication_queue: Vec<ReplicatedQuery>,
     rx: UnboundedReceiver<QueryMessage>,
     shutdown_rx: Receiver<()>,
     // It is used to make RPC calls to fetch erc contracts
@@ -200,6 +206,8 @@ pub struct QueryMessage {
     pub arguments: Vec<Argument>,
     pub query_type: QueryType,
     tx: Option<oneshot::Sender<QueryResult<()>>>,
+    // Span the query was sent in, the parent of the span of its execution
+    span: Span,
 }
 
 impl QueryMessage {
@@ -209,6 +217,7 @@ impl QueryMessage {
             arguments,
             query_type,
             tx: None,
+            span: Span::current(),
         }
     }
 
@@ -224,6 +233,7 @@ impl QueryMessage {
                 arguments,
                 query_type,
                 tx: Some(tx),
+                span: Span::current(),
             },
             rx,
         )
@@ -256,6 +266,7 @@ impl QueryMessage {
                 arguments: vec![],
                 query_type: QueryType::Rollback,
                 tx: Some(tx),
+                span: Span::current(),
             },
             rx,
         )
@@ -295,6 +306,8 @@ impl<P: Provider + Sync + Send + Clone + 'static> Executor<'_, P> {
                 pool,
                 transaction: Some(transaction),
                 publish_queue,
+                span: Span::none(),
+                replication_queue: Vec::new(),
                 rx,
                 shutdown_rx,
                 provider,
@@ -318,7 +331,21 @@ impl<P: Provider + Sync + Send + Clone + 'static> Executor<'_, P> {
                     let statement = msg.statement.clone();
                     let arguments = msg.arguments.clone();
                     let tx = msg.tx.take();
-                    let res = self.handle_query_message(msg).await;
+                    // The commits are linked to the range they index, the queries are detailed
+                    // at the debug level only
+                    let span = match query_type {
+                        QueryType::Execute => info_span!(target: LOG_TARGET, parent: &msg.span, "commit"),
+                        QueryType::Rollback => info_span!(target: LOG_TARGET, parent: &msg.span, "rollback"),
+                        _ => debug_span!(target: LOG_TARGET, parent: &msg.span, "query", r#type = %query_type),
+                    };
+                    // The span of the query isn't entered when it's disabled, the updates are
+                    // then published in the span the query was sent in
+                    self.span = if span.is_disabled() {
+                        msg.span.clone()
+                    } else {
+                        span.clone()
+                    };
+                    let res = self.handle_query_message(msg).instrument(span).await;
 
                     if let Err(e) = &res {
                         error!(target: LOG_TARGET, r#type = %query_type, error = ?e, "Failed to execute query.");
@@ -339,6 +366,19 @@ impl<P: Provider + Sync + Send + Clone + 'static> Executor<'_, P> {
         let start_time = Instant::now();
         let query_type_str = format!("{}", query_message.query_type);
 
+        // The values resolved from the chain are replaced in the replicated query, for the
+        // followers to replay it without calling the chain
+        let mut replicated = (self.config.replication_log
+            && !matches!(
+                query_message.query_type,
+                QueryType::Execute | QueryType::Rollback
+            ))
+        .then(|| ReplicatedQuery {
+            statement: query_message.statement.clone(),
+            arguments: query_message.arguments.clone(),
+            query_type: query_message.query_type.clone(),
+        });
+
         let tx = self.transaction.as_mut().unwrap();
 
         let mut query = sqlx::query(&query_message.statement);
@@ -953,19 +993,32 @@ impl<P: Provider + Sync + Send + Clone + 'static> Executor<'_, P> {
             QueryType::ApplyBalanceDiff(apply_balance_diff) => {
                 debug!(target: LOG_TARGET, "Applying balance diff.");
                 let instant = Instant::now();
-                self.apply_balance_diff(apply_balance_diff, self.provider.clone())
+                let applied_diff = self
+                    .apply_balance_diff(apply_balance_diff, self.provider.clone())
                     .await
                     .map_err(Box::new)?;
+                if let Some(QueryType::ApplyBalanceDiff(replicated)) = replicated
+                    .as_mut()
+                    .map(|replicated| &mut replicated.query_type)
+                {
+                    replicated.balances_diff = applied_diff;
+                }
                 debug!(target: LOG_TARGET, duration = ?instant.elapsed(), "Applied balance diff.");
             }
             QueryType::RegisterNftToken(register_nft_token) => {
                 // Check if we already have the metadata for this contract
-                let res = sqlx::query_as::<_, (String, String)>(&format!(
-                    "SELECT name, symbol FROM {TOKENS_TABLE} WHERE contract_address = ? LIMIT 1"
-                ))
-                .bind(felt_to_sql_string(&register_nft_token.contract_address))
-                .fetch_one(&mut **tx)
-                .await;
+                let res = match &register_nft_token.name_and_symbol {
+                    Some(name_and_symbol) => Ok(name_and_symbol.clone()),
+                    None => {
+                        sqlx::query_as::<_, (String, String)>(&format!(
+                            "SELECT name, symbol FROM {TOKENS_TABLE} WHERE contract_address = ? \
+                             LIMIT 1"
+                        ))
+                        .bind(felt_to_sql_string(&register_nft_token.contract_address))
+                        .fetch_one(&mut **tx)
+                        .await
+                    }
+                };
 
                 // If we find a token already registered for this contract_address we dont need to
                 // refetch the data since its same for all tokens of this contract
@@ -1057,6 +1110,12 @@ impl<P: Provider + Sync + Send + Clone + 'static> Executor<'_, P> {
                         }
                     }
                 };
+                if let Some(QueryType::RegisterNftToken(replicated)) = replicated
+                    .as_mut()
+                    .map(|replicated| &mut replicated.query_type)
+                {
+                    replicated.name_and_symbol = Some((name.clone(), symbol.clone()));
+                }
 
                 let query = sqlx::query_as::<_, torii_sqlite_types::Token>(
                     "INSERT INTO tokens (id, contract_address, token_id, name, symbol, decimals, \
@@ -1173,6 +1232,10 @@ impl<P: Provider + Sync + Send + Clone + 'static> Executor<'_, P> {
             }
         }
 
+        if let Some(replicated) = replicated {
+            self.replication_queue.push(replicated);
+        }
+
         // Record metrics
         let duration = start_time.elapsed();
         histogram!(
@@ -1191,9 +1254,19 @@ impl<P: Provider + Sync + Send + Clone + 'static> Executor<'_, P> {
     }
 
     async fn execute(&mut self) -> Result<()> {
-        if let Some(transaction) = self.transaction.take() {
+        if let Some(mut transaction) = self.transaction.take() {
+            // In the same transaction, a batch is in the log if and only if it is committed
+            if !self.replication_queue.is_empty() {
+                replication::write(
+                    &mut transaction,
+                    &self.replication_queue,
+                    self.config.replication_log_retention,
+                )
+                .await?;
+            }
             transaction.commit().await?;
         }
+        self.replication_queue.clear();
 
         // Run PRAGMA optimize after committing transaction if interval has elapsed
         // This is the optimal time since the transaction is closed and tables may have changed
@@ -1221,7 +1294,7 @@ impl<P: Provider + Sync + Send + Clone + 'static> Executor<'_, P> {
         self.transaction = Some(self.pool.begin().await?);
 
         for message in self.publish_queue.drain(..) {
-            send_broker_message(message, false);
+            send_broker_message(message, false, &Span::current());
         }
 
         // Record metrics
@@ -1239,6 +1312,7 @@ impl<P: Provider + Sync + Send + Clone + 'static> Executor<'_, P> {
         self.transaction = Some(self.pool.begin().await?);
 
         self.publish_queue.clear();
+        self.replication_queue.clear();
 
         // Record metrics
         counter!("torii_executor_transaction_operations_total", "operation" => "rollback", "status" => "success")
@@ -1277,43 +1351,166 @@ impl<P: Provider + Sync + Send + Clone + 'static> Executor<'_, P> {
     }
 
     fn publish_optimistic_and_queue(&mut self, message: BrokerMessage) {
-        send_broker_message(message.clone(), true);
+        send_broker_message(message.clone(), true, &self.span);
         self.publish_queue.push(message);
     }
 }
 
-fn send_broker_message(message: BrokerMessage, optimistic: bool) {
+fn send_broker_message(message: BrokerMessage, optimistic: bool, span: &Span) {
     match message {
         BrokerMessage::ContractUpdate(contract) => {
-            MemoryBroker::publish(Update::new(contract, optimistic))
+            MemoryBroker::publish(Update::with_span(contract, optimistic, span.clone()))
         }
         BrokerMessage::ModelRegistered(model) => {
-            MemoryBroker::publish(Update::new(model, optimistic))
+            MemoryBroker::publish(Update::with_span(model, optimistic, span.clone()))
         }
         BrokerMessage::EntityUpdate(entity) => {
-            MemoryBroker::publish(Update::new(entity, optimistic))
+            MemoryBroker::publish(Update::with_span(entity, optimistic, span.clone()))
         }
         BrokerMessage::EventMessageUpdate(event) => {
-            MemoryBroker::publish(Update::new(event, optimistic))
+            MemoryBroker::publish(Update::with_span(event, optimistic, span.clone()))
+        }
+        BrokerMessage::EventEmitted(event) => {
+            MemoryBroker::publish(Update::with_span(event, optimistic, span.clone()))
         }
-        BrokerMessage::EventEmitted(event) => MemoryBroker::publish(Update::new(event, optimistic)),
         BrokerMessage::TokenRegistered(token) => {
-            MemoryBroker::publish(Update::new(token, optimistic))
+            MemoryBroker::publish(Update::with_span(token, optimistic, span.clone()))
         }
         BrokerMessage::TokenBalanceUpdated(token_balance) => {
-            MemoryBroker::publish(Update::new(token_balance, optimistic))
+            MemoryBroker::publish(Update::with_span(token_balance, optimistic, span.clone()))
         }
         BrokerMessage::TokenTransfer(token_transfer) => {
-            MemoryBroker::publish(Update::new(token_transfer, optimistic))
+            MemoryBroker::publish(Update::with_span(token_transfer, optimistic, span.clone()))
         }
         BrokerMessage::Transaction(transaction) => {
-            MemoryBroker::publish(Update::new(transaction, optimistic))
+            MemoryBroker::publish(Update::with_span(transaction, optimistic, span.clone()))
         }
         BrokerMessage::AggregationUpdated(aggregation) => {
-            MemoryBroker::publish(Update::new(aggregation, optimistic))
+            MemoryBroker::publish(Update::with_span(aggregation, optimistic, span.clone()))
         }
         BrokerMessage::ActivityUpdated(activity) => {
-            MemoryBroker::publish(Update::new(activity, optimistic))
+            MemoryBroker::publish(Update::with_span(activity, optimistic, span.clone()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dojo_types::primitive::Primitive;
+    use torii_proto::CallType;
+
+    use super::*;
+
+    fn cursors() -> HashMap<Felt, ContractCursor> {
+        HashMap::from([(
+            Felt::ONE,
+            ContractCursor {
+                contract_address: Felt::ONE,
+                head: Some(10),
+                last_block_timestamp: Some(100),
+                last_pending_block_tx: Some(Felt::TWO),
+            },
+        )])
+    }
+
+    #[test]
+    fn test_query_type_serde() {
+        let ty = Ty::Primitive(Primitive::U32(Some(42)));
+        let token_id = TokenId::Nft(Felt::ONE, U256::from(7u8));
+        let query_types = vec![
+            QueryType::StoreTransaction(StoreTransactionQuery {
+                contract_addresses: HashSet::from([Felt::ONE]),
+                calls: vec![TransactionCall {
+                    contract_address: Felt::ONE,
+                    entrypoint: "move".to_string(),
+                    calldata: vec![Felt::TWO],
+                    call_type: CallType::Execute,
+                    caller_address: Felt::THREE,
+                }],
+                unique_models: HashSet::from([Felt::TWO]),
+            }),
+            QueryType::UpdateCursors(UpdateCursorsQuery {
+                cursors: cursors(),
+                cursor_transactions: HashMap::from([(Felt::ONE, HashSet::from([Felt::TWO]))]),
+            }),
+            QueryType::SetEntity(EntityQuery {
+                entity_id: "0x1".to_string(),
+                model_id: "0x2".to_string(),
+                keys_str: Some("0x3/".to_string()),
+                event_id: "0x4".to_string(),
+                block_timestamp: "100".to_string(),
+                is_historical: true,
+                ty: ty.clone(),
+            }),
+            QueryType::DeleteEntity(DeleteEntityQuery {
+                entity_id: "0x1".to_string(),
+                model_id: "0x2".to_string(),
+                event_id: "0x4".to_string(),
+                block_timestamp: "100".to_string(),
+                ty: ty.clone(),
+            }),
+            QueryType::EventMessage(EventMessageQuery {
+                world_address: "0x5".to_string(),
+                entity_id: "0x1".to_string(),
+                model_id: "0x2".to_string(),
+                keys_str: "0x3/".to_string(),
+                event_id: "0x4".to_string(),
+                block_timestamp: "100".to_string(),
+                is_historical: false,
+                ty,
+            }),
+            QueryType::ApplyBalanceDiff(ApplyBalanceDiffQuery {
+                balances_diff: HashMap::from([(
+                    BalanceId {
+                        account_address: Felt::TWO,
+                        token_id: token_id.clone(),
+                    },
+                    I256 {
+                        value: U256::from(3u8),
+                        is_negative: true,
+                    },
+                )]),
+                total_supply_diff: HashMap::from([(
+                    TokenId::Contract(Felt::ONE),
+                    I256::from(U256::from(u128::MAX)),
+                )]),
+                cursors: cursors(),
+            }),
+            QueryType::RegisterNftToken(RegisterNftTokenQuery {
+                contract_address: Felt::ONE,
+                token_id: U256::from_words(1, 2),
+                metadata: "{}".to_string(),
+                name_and_symbol: Some(("Name".to_string(), "SYM".to_string())),
+            }),
+            QueryType::RegisterTokenContract(RegisterTokenContractQuery {
+                contract_address: Felt::ONE,
+                name: "Name".to_string(),
+                symbol: "SYM".to_string(),
+                decimals: 18,
+                metadata: None,
+            }),
+            QueryType::RegisterModel,
+            QueryType::RegisterContract,
+            QueryType::StoreEvent,
+            QueryType::StoreTokenTransfer,
+            QueryType::UpdateTokenMetadata(UpdateTokenMetadataQuery {
+                token_id,
+                metadata: "{}".to_string(),
+            }),
+            QueryType::Execute,
+            QueryType::Rollback,
+            QueryType::Other,
+        ];
+
+        for query_type in query_types {
+            let serialized = serde_json::to_value(&query_type).unwrap();
+            let deserialized: QueryType = serde_json::from_value(serialized.clone()).unwrap();
+            assert_eq!(query_type.to_string(), deserialized.to_string());
+            assert_eq!(
+                serde_json::to_value(&deserialized).unwrap(),
+                serialized,
+                "{query_type}"
+            );
         }
     }
 }
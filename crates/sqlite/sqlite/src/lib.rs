@@ -5,7 +5,7 @@ use dojo_types::primitive::SqlType;
 use dojo_types::schema::Ty;
 use sqlx::{Pool, Sqlite};
 use starknet::core::types::Felt;
-use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
 use torii_cache::Cache;
 use torii_proto::ContractDefinition;
 use torii_storage::Storage;
@@ -20,8 +20,12 @@ pub mod constants;
 pub mod cursor;
 pub mod error;
 pub mod executor;
+pub mod maintenance;
 pub mod model;
 pub mod query;
+pub mod replication;
+pub mod sandbox;
+pub mod snapshot;
 pub mod storage;
 pub mod utils;
 
@@ -55,6 +59,9 @@ pub struct SqlConfig {
     pub search_prefix_matching: bool,
     pub search_return_snippets: bool,
     pub search_snippet_length: usize,
+    // Replication configuration
+    pub replication_log: bool,
+    pub replication_log_retention: u64,
 }
 
 impl SqlConfig {
@@ -127,6 +134,18 @@ impl Sql {
         Ok(db)
     }
 
+    /// A storage reading a database written by another process. Without an executor, its
+    /// writes fail.
+    pub fn new_read_only(pool: Pool<Sqlite>, config: SqlConfig) -> Self {
+        let (executor, _) = unbounded_channel();
+        Self {
+            pool,
+            executor,
+            config,
+            cache: None,
+        }
+    }
+
     pub fn with_cache(self, cache: Arc<dyn Cache>) -> Self {
         Self {
             cache: Some(cache),
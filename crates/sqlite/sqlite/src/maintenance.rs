@@ -0,0 +1,352 @@
+//! Offline maintenance and inspection of a Torii database, for the `torii db` commands.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use starknet::core::types::Felt;
+
+use crate::constants::{
+    CONTRACTS_TABLE, ENTITIES_ENTITY_RELATION_COLUMN, ENTITIES_MODEL_RELATION_TABLE,
+    ENTITIES_TABLE, EVENT_MESSAGES_ENTITY_RELATION_COLUMN, EVENT_MESSAGES_MODEL_RELATION_TABLE,
+    EVENT_MESSAGES_TABLE, MODELS_TABLE, WORLD_CONTRACT_TYPE,
+};
+use crate::utils::felt_to_sql_string;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelInfo {
+    /// `<namespace>-<name>`, which is also the name of its table.
+    pub tag: String,
+    pub world_address: String,
+    /// Rows of its table, none if the table is missing.
+    pub rows: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractCursor {
+    pub contract_address: String,
+    pub contract_type: String,
+    pub head: Option<i64>,
+    pub last_block_timestamp: Option<i64>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DatabaseInfo {
+    /// Size of the database file, without its WAL.
+    pub size_bytes: i64,
+    pub free_bytes: i64,
+    pub models: Vec<ModelInfo>,
+    /// Rows of the tables which aren't model tables.
+    pub tables: Vec<(String, i64)>,
+    pub contracts: Vec<ContractCursor>,
+}
+
+/// An inconsistency between the registered models and the tables, or rows referencing missing
+/// entities.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Issue {
+    /// A registered model without its table.
+    MissingModelTable { tag: String },
+    /// Rows of a model table whose entity or event message doesn't exist.
+    OrphanModelRows { tag: String, rows: i64 },
+    /// Rows of a relation table whose entity or model doesn't exist.
+    OrphanRelations { table: String, rows: i64 },
+}
+
+impl std::fmt::Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Issue::MissingModelTable { tag } => write!(f, "Model {} has no table", tag),
+            Issue::OrphanModelRows { tag, rows } => {
+                write!(f, "Model {} has {} rows without entity", tag, rows)
+            }
+            Issue::OrphanRelations { table, rows } => write!(
+                f,
+                "Table {} has {} rows without entity or model",
+                table, rows
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Checkpoint {
+    /// Whether a reader or writer prevented a full checkpoint.
+    pub busy: bool,
+    /// Frames in the WAL, and how many were copied into the database.
+    pub log_frames: i64,
+    pub checkpointed_frames: i64,
+}
+
+/// Tables of the database, without the ones of SQLite and of the migrations.
+async fn tables(pool: &SqlitePool) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND \
+         name NOT LIKE '_sqlx_%' ORDER BY name",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+async fn count(pool: &SqlitePool, table: &str) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(&format!("SELECT COUNT(*) FROM [{table}]"))
+        .fetch_one(pool)
+        .await
+}
+
+/// Registered models with their world, ordered by tag.
+async fn models(pool: &SqlitePool) -> Result<Vec<(String, String)>, sqlx::Error> {
+    let rows = sqlx::query(&format!(
+        "SELECT namespace || '-' || name AS tag, world_address FROM {MODELS_TABLE} ORDER BY tag, \
+         world_address"
+    ))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.get("tag"), row.get("world_address")))
+        .collect())
+}
+
+/// Models, row counts, contract cursors and size of the database.
+pub async fn info(pool: &SqlitePool) -> Result<DatabaseInfo, sqlx::Error> {
+    let tables = tables(pool).await?;
+
+    let mut models_info = Vec::new();
+    for (tag, world_address) in models(pool).await? {
+        let rows = if tables.contains(&tag) {
+            Some(count(pool, &tag).await?)
+        } else {
+            None
+        };
+        models_info.push(ModelInfo {
+            tag,
+            world_address,
+            rows,
+        });
+    }
+
+    let mut tables_info = Vec::new();
+    for table in tables
+        .iter()
+        .filter(|table| !models_info.iter().any(|model| &model.tag == *table))
+    {
+        tables_info.push((table.clone(), count(pool, table).await?));
+    }
+
+    let contracts = sqlx::query(&format!(
+        "SELECT contract_address, contract_type, head, last_block_timestamp, updated_at FROM \
+         {CONTRACTS_TABLE} ORDER BY contract_type, contract_address"
+    ))
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|row| ContractCursor {
+        contract_address: row.get("contract_address"),
+        contract_type: row.get("contract_type"),
+        head: row.get("head"),
+        last_block_timestamp: row.get("last_block_timestamp"),
+        updated_at: row.get("updated_at"),
+    })
+    .collect();
+
+    let page_size: i64 = sqlx::query_scalar("PRAGMA page_size")
+        .fetch_one(pool)
+        .await?;
+    let page_count: i64 = sqlx::query_scalar("PRAGMA page_count")
+        .fetch_one(pool)
+        .await?;
+    let freelist_count: i64 = sqlx::query_scalar("PRAGMA freelist_count")
+        .fetch_one(pool)
+        .await?;
+
+    Ok(DatabaseInfo {
+        size_bytes: page_size * page_count,
+        free_bytes: page_size * freelist_count,
+        models: models_info,
+        tables: tables_info,
+        contracts,
+    })
+}
+
+/// Checks that every registered model has its table and that no row references a missing entity,
+/// event message or model.
+pub async fn verify(pool: &SqlitePool) -> Result<Vec<Issue>, sqlx::Error> {
+    let tables = tables(pool).await?;
+    let mut issues = Vec::new();
+
+    let mut tags = models(pool)
+        .await?
+        .into_iter()
+        .map(|(tag, _)| tag)
+        .collect::<Vec<_>>();
+    // Models of several worlds can share a table
+    tags.dedup();
+
+    for tag in tags {
+        if !tables.contains(&tag) {
+            issues.push(Issue::MissingModelTable { tag });
+            continue;
+        }
+
+        let rows: i64 = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM [{tag}] WHERE ({ENTITIES_ENTITY_RELATION_COLUMN} IS NOT NULL AND \
+             {ENTITIES_ENTITY_RELATION_COLUMN} NOT IN (SELECT id FROM {ENTITIES_TABLE})) OR \
+             ({EVENT_MESSAGES_ENTITY_RELATION_COLUMN} IS NOT NULL AND \
+             {EVENT_MESSAGES_ENTITY_RELATION_COLUMN} NOT IN (SELECT id FROM \
+             {EVENT_MESSAGES_TABLE}))"
+        ))
+        .fetch_one(pool)
+        .await?;
+        if rows > 0 {
+            issues.push(Issue::OrphanModelRows { tag, rows });
+        }
+    }
+
+    for (table, entities) in [
+        (ENTITIES_MODEL_RELATION_TABLE, ENTITIES_TABLE),
+        (EVENT_MESSAGES_MODEL_RELATION_TABLE, EVENT_MESSAGES_TABLE),
+    ] {
+        let rows: i64 = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM {table} WHERE entity_id NOT IN (SELECT id FROM {entities}) OR \
+             model_id NOT IN (SELECT id FROM {MODELS_TABLE})"
+        ))
+        .fetch_one(pool)
+        .await?;
+        if rows > 0 {
+            issues.push(Issue::OrphanRelations {
+                table: table.to_string(),
+                rows,
+            });
+        }
+    }
+
+    Ok(issues)
+}
+
+/// Rebuilds the database file, reclaiming the space of deleted rows.
+pub async fn vacuum(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query("VACUUM").execute(pool).await?;
+    Ok(())
+}
+
+/// Copies the WAL into the database and truncates it.
+pub async fn checkpoint(pool: &SqlitePool) -> Result<Checkpoint, sqlx::Error> {
+    let row = sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+        .fetch_one(pool)
+        .await?;
+
+    Ok(Checkpoint {
+        busy: row.get::<i64, _>(0) != 0,
+        log_frames: row.get(1),
+        checkpointed_frames: row.get(2),
+    })
+}
+
+/// Writes a compacted and consistent copy of the database to `path`, which must not exist.
+pub async fn export(pool: &SqlitePool, path: &Path) -> Result<(), sqlx::Error> {
+    sqlx::query("VACUUM INTO ?")
+        .bind(path.to_string_lossy().to_string())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Moves the cursors of the world contracts back so that the next run indexes again from
+/// `from_block`, for the given contracts or all of them if empty.
+///
+/// Token contracts are left untouched: their balances are accumulated from the transfers, so
+/// processing the transfers of a block again would count them twice. The same holds for the
+/// ranges reindexed at runtime.
+///
+/// Returns the addresses of the contracts rewound.
+pub async fn rewind(
+    pool: &SqlitePool,
+    from_block: u64,
+    contracts: &[Felt],
+) -> Result<Vec<String>, sqlx::Error> {
+    let head = from_block.saturating_sub(1) as i64;
+    let mut query = format!(
+        "UPDATE {CONTRACTS_TABLE} SET head = ?, last_pending_block_tx = NULL, updated_at = \
+         CURRENT_TIMESTAMP WHERE contract_type = ? AND head > ?"
+    );
+    if !contracts.is_empty() {
+        query.push_str(&format!(
+            " AND id IN ({})",
+            vec!["?"; contracts.len()].join(", ")
+        ));
+    }
+    query.push_str(" RETURNING contract_address");
+
+    let mut query = sqlx::query_scalar(&query)
+        .bind(head)
+        .bind(WORLD_CONTRACT_TYPE)
+        .bind(head);
+    for contract in contracts {
+        query = query.bind(felt_to_sql_string(contract));
+    }
+
+    query.fetch_all(pool).await
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::SqlitePool;
+    use starknet::core::types::Felt;
+
+    use super::*;
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn test_verify_and_rewind(pool: SqlitePool) {
+        let world = felt_to_sql_string(&Felt::ONE);
+        let erc20 = felt_to_sql_string(&Felt::TWO);
+        for (address, contract_type) in [(&world, "WORLD"), (&erc20, "ERC20")] {
+            sqlx::query(
+                "INSERT INTO contracts (id, contract_address, contract_type, head) VALUES (?, ?, \
+                 ?, 100)",
+            )
+            .bind(address)
+            .bind(address)
+            .bind(contract_type)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        assert!(verify(&pool).await.unwrap().is_empty());
+
+        // Orphans are left behind by databases written without foreign keys
+        let mut conn = pool.acquire().await.unwrap();
+        sqlx::query("PRAGMA foreign_keys = OFF")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO entity_model (entity_id, model_id) VALUES ('0x1', '0x2')")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        drop(conn);
+        assert_eq!(
+            verify(&pool).await.unwrap(),
+            vec![Issue::OrphanRelations {
+                table: "entity_model".to_string(),
+                rows: 1
+            }]
+        );
+
+        // Only the world is rewound, and never forward
+        assert_eq!(rewind(&pool, 50, &[]).await.unwrap(), vec![world.clone()]);
+        assert!(rewind(&pool, 80, &[Felt::ONE]).await.unwrap().is_empty());
+
+        let info = info(&pool).await.unwrap();
+        let heads = info
+            .contracts
+            .iter()
+            .map(|contract| (contract.contract_type.as_str(), contract.head))
+            .collect::<Vec<_>>();
+        assert_eq!(heads, vec![("ERC20", Some(100)), ("WORLD", Some(49))]);
+    }
+}
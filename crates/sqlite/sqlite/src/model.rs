@@ -5,7 +5,8 @@ use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 use torii_proto::schema::Entity;
 use torii_proto::{
-    Clause, ComparisonOperator, CompositeClause, LogicalOperator, MemberValue, OrderBy,
+    AggregateFunction, Clause, ComparisonOperator, CompositeClause, EntityAggregate,
+    EntityAggregateQuery, JoinClause, LogicalOperator, MemberClause, MemberValue, OrderBy,
     OrderDirection, Page, Pagination,
 };
 use torii_storage::utils::format_world_scoped_id;
@@ -13,19 +14,27 @@ use torii_storage::ReadOnlyStorage;
 
 use async_trait::async_trait;
 use crypto_bigint::U256;
-use dojo_types::primitive::{Primitive, PrimitiveError};
+use dojo_types::primitive::{Primitive, PrimitiveError, SqlType};
 use dojo_types::schema::Ty;
 use dojo_world::contracts::abigen::model::Layout;
 use dojo_world::contracts::model::ModelReader;
+use futures::TryStreamExt;
 use serde_json::Value as JsonValue;
 use sqlx::sqlite::SqliteRow;
 use sqlx::{Pool, Row, Sqlite};
-use starknet::core::types::Felt;
+use starknet::core::types::{Felt, U256 as StarknetU256};
+use torii_math::I256;
 
 use super::error::{self, Error};
-use crate::constants::SQL_MAX_JOINS;
+use crate::constants::{
+    ENTITIES_ENTITY_RELATION_COLUMN, ENTITIES_MODEL_RELATION_TABLE, ENTITIES_TABLE,
+    EVENT_MESSAGES_ENTITY_RELATION_COLUMN, EVENT_MESSAGES_MODEL_RELATION_TABLE,
+    EVENT_MESSAGES_TABLE, SQL_MAX_JOINS, SQL_MAX_JOIN_DEPTH,
+};
 use crate::error::{ParseError, QueryError};
-use crate::utils::{build_keys_pattern, felt_to_sql_string};
+use crate::utils::{
+    build_keys_pattern, felt_to_sql_string, sql_string_to_u256, u256_to_f64, u256_to_sql_string,
+};
 use crate::Sql;
 
 /// Helper function to parse array index from field name like "field[0]"
@@ -59,34 +68,40 @@ fn build_array_query(
         format!("[{model}].[{field}]")
     };
 
+    // The values are bound as text, so the elements are compared as text and the lengths as
+    // integers, for the arrays of numbers to match too
     match operator {
         ComparisonOperator::Contains => Ok(format!(
-            "EXISTS (SELECT 1 FROM json_each({column_access}) WHERE value = {value})"
+            "EXISTS (SELECT 1 FROM json_each({column_access}) WHERE CAST(value AS TEXT) = {value})"
         )),
         ComparisonOperator::ContainsAll => {
             // For CONTAINS_ALL, we need to check that every value in the input list exists in the array
             // We can't use COUNT(DISTINCT) because the input might have duplicates
             // Instead, we'll use NOT EXISTS to check if any input value is missing from the array
             //
-            // Create a subquery that checks if all input values exist in the array
-            // We use a VALUES clause to create a temporary table of the input values
-            // and check that none of them are missing from the array
+            // The input values are made a JSON array to iterate over them, and we check that
+            // none of them are missing from the array
+            let values = if value.starts_with('(') {
+                format!("json_array{value}")
+            } else {
+                format!("json_array({value})")
+            };
             Ok(format!(
-                "NOT EXISTS (SELECT 1 FROM (VALUES {value}) AS input_vals(val) WHERE NOT EXISTS (SELECT 1 FROM json_each({column_access}) WHERE value = input_vals.val))"
+                "NOT EXISTS (SELECT 1 FROM json_each({values}) AS input_vals WHERE NOT EXISTS (SELECT 1 FROM json_each({column_access}) WHERE CAST(value AS TEXT) = input_vals.value))"
             ))
         }
         ComparisonOperator::ContainsAny => Ok(format!(
-            "EXISTS (SELECT 1 FROM json_each({column_access}) WHERE value IN {value})"
+            "EXISTS (SELECT 1 FROM json_each({column_access}) WHERE CAST(value AS TEXT) IN {value})"
+        )),
+        ComparisonOperator::ArrayLengthEq => Ok(format!(
+            "json_array_length({column_access}) = CAST({value} AS INTEGER)"
+        )),
+        ComparisonOperator::ArrayLengthGt => Ok(format!(
+            "json_array_length({column_access}) > CAST({value} AS INTEGER)"
+        )),
+        ComparisonOperator::ArrayLengthLt => Ok(format!(
+            "json_array_length({column_access}) < CAST({value} AS INTEGER)"
         )),
-        ComparisonOperator::ArrayLengthEq => {
-            Ok(format!("json_array_length({column_access}) = {value}"))
-        }
-        ComparisonOperator::ArrayLengthGt => {
-            Ok(format!("json_array_length({column_access}) > {value}"))
-        }
-        ComparisonOperator::ArrayLengthLt => {
-            Ok(format!("json_array_length({column_access}) < {value}"))
-        }
         _ => {
             // For non-array operations, fallback to the original behavior
             if historical {
@@ -459,14 +474,17 @@ fn map_row_to_entity(
     })
 }
 
-// builds a composite clause for a query
-fn build_composite_clause(
+/// Builds the SQL condition of a composite clause, along with the values to bind to its
+/// placeholders.
+///
+/// Member clauses compare the `[model].[member]` columns, so the model tables must be part of the
+/// query, or the data of `table` is used when `historical`.
+pub fn build_composite_clause(
     table: &str,
     model_relation_table: &str,
     composite: &torii_proto::CompositeClause,
     historical: bool,
 ) -> Result<(String, Vec<String>), Error> {
-    let is_or = composite.operator == LogicalOperator::Or;
     let mut where_clauses = Vec::new();
     let mut bind_values = Vec::new();
 
@@ -575,8 +593,31 @@ fn build_composite_clause(
                         | ComparisonOperator::ArrayLengthLt
                 ) || array_index.is_some(); // Array indexing also needs JSON formatting
 
-                let value =
-                    prepare_comparison(&member.value, &mut bind_values, is_array_operation)?;
+                let value = match operator {
+                    // null checks have no value to compare with
+                    ComparisonOperator::IsNull | ComparisonOperator::IsNotNull => String::new(),
+                    ComparisonOperator::Between => match &member.value {
+                        MemberValue::List(bounds) if bounds.len() == 2 => format!(
+                            "{} AND {}",
+                            prepare_comparison(&bounds[0], &mut bind_values, is_array_operation)?,
+                            prepare_comparison(&bounds[1], &mut bind_values, is_array_operation)?
+                        ),
+                        _ => {
+                            return Err(QueryError::UnsupportedQuery(format!(
+                                "BETWEEN on {} requires a list of two values",
+                                member.member
+                            ))
+                            .into())
+                        }
+                    },
+                    _ => prepare_comparison(&member.value, &mut bind_values, is_array_operation)?,
+                };
+                // comparison of the member, e.g. `= ?`, `BETWEEN ? AND ?` or `IS NULL`
+                let comparison = if value.is_empty() {
+                    operator.to_string()
+                } else {
+                    format!("{operator} {value}")
+                };
 
                 // Check if this field has array indexing syntax like "field[0]"
                 if let Some((field_name, index)) = array_index {
@@ -587,8 +628,7 @@ fn build_composite_clause(
                         format!("[{model}].[{field_name}]")
                     };
 
-                    let query =
-                        format!("json_extract({column_access}, '$[{index}]') {operator} {value}");
+                    let query = format!("json_extract({column_access}, '$[{index}]') {comparison}");
                     where_clauses.push(query);
                 } else if matches!(
                     operator,
@@ -613,14 +653,11 @@ fn build_composite_clause(
                     // Regular field handling
                     if historical {
                         where_clauses.push(format!(
-                            "CAST(JSON_EXTRACT({table}.data, '$.{}') AS TEXT) {operator} {value}",
+                            "CAST(JSON_EXTRACT({table}.data, '$.{}') AS TEXT) {comparison}",
                             member.member
                         ));
                     } else {
-                        where_clauses.push(format!(
-                            "([{model}].[{}] {operator} {value})",
-                            member.member
-                        ));
+                        where_clauses.push(format!("([{model}].[{}] {comparison})", member.member));
                     }
                 }
             }
@@ -630,22 +667,247 @@ fn build_composite_clause(
                     build_composite_clause(table, model_relation_table, nested, historical)?;
 
                 if !nested_where.is_empty() {
-                    where_clauses.push(nested_where);
+                    where_clauses.push(format!("({nested_where})"));
                 }
                 bind_values.extend(nested_values);
             }
+            Clause::Join(join) => {
+                if historical {
+                    return Err(QueryError::UnsupportedQuery(
+                        "Joins are not supported on historical entities".to_string(),
+                    )
+                    .into());
+                }
+
+                if join_depth(clause) > SQL_MAX_JOIN_DEPTH {
+                    return Err(QueryError::UnsupportedQuery(format!(
+                        "Joins can't be nested more than {SQL_MAX_JOIN_DEPTH} deep"
+                    ))
+                    .into());
+                }
+
+                // The joined entities are of the same kind as the queried ones, entities or
+                // event messages
+                let (joined_table, relation_column) =
+                    if model_relation_table == EVENT_MESSAGES_MODEL_RELATION_TABLE {
+                        (EVENT_MESSAGES_TABLE, EVENT_MESSAGES_ENTITY_RELATION_COLUMN)
+                    } else {
+                        (ENTITIES_TABLE, ENTITIES_ENTITY_RELATION_COLUMN)
+                    };
+
+                // The joined model table is aliased after the member referencing it, so that a
+                // model can be joined with itself, e.g. `[ns-Player.leader]`
+                let alias = format!("{}.{}", join.model, join.member);
+                let entities = format!("{table}_joined");
+                let (joined_where, joined_values) = build_composite_clause(
+                    &entities,
+                    model_relation_table,
+                    &CompositeClause {
+                        operator: LogicalOperator::And,
+                        clauses: join
+                            .clauses
+                            .iter()
+                            .map(|clause| alias_model(clause, &join.joined_model, &alias))
+                            .collect(),
+                    },
+                    false,
+                )?;
+
+                let mut conditions = vec![
+                    format!("{entities}.world_address = {table}.world_address"),
+                    format!(
+                        "[{alias}].[{}] = [{}].[{}]",
+                        join.joined_member, join.model, join.member
+                    ),
+                ];
+                if !joined_where.is_empty() {
+                    conditions.push(format!("({joined_where})"));
+                }
+                where_clauses.push(format!(
+                    "EXISTS (SELECT 1 FROM {joined_table} {entities} \
+                     JOIN [{}] [{alias}] ON [{alias}].{relation_column} = {entities}.id \
+                     WHERE {})",
+                    join.joined_model,
+                    conditions.join(" AND ")
+                ));
+                bind_values.extend(joined_values);
+            }
         }
     }
 
-    let where_clause = if !where_clauses.is_empty() {
-        where_clauses.join(if is_or { " OR " } else { " AND " })
-    } else {
+    let where_clause = if where_clauses.is_empty() {
         String::new()
+    } else {
+        match composite.operator {
+            LogicalOperator::And => where_clauses.join(" AND "),
+            LogicalOperator::Or => where_clauses.join(" OR "),
+            LogicalOperator::Not => format!("NOT ({})", where_clauses.join(" AND ")),
+        }
     };
 
     Ok((where_clause, bind_values))
 }
 
+/// Type of a member of a model, e.g. `position.x` or `mount.Some`.
+fn member_ty<'a>(ty: &'a Ty, member: &str) -> Option<&'a Ty> {
+    member.split('.').try_fold(ty, |ty, part| match ty {
+        Ty::Struct(s) => s.children.iter().find(|c| c.name == part).map(|c| &c.ty),
+        Ty::Enum(e) => e.options.iter().find(|o| o.name == part).map(|o| &o.ty),
+        Ty::Tuple(t) => part.parse::<usize>().ok().and_then(|i| t.get(i)),
+        _ => None,
+    })
+}
+
+/// Type of a member of the schemas of `model` in the queried worlds. The worlds registering a
+/// model share its table, but each one has its own schema of it, so the member has to be of the
+/// same type in all of them.
+fn resolve_member<'a>(schemas: &'a [Ty], model: &str, member: &str) -> Result<&'a Ty, Error> {
+    let mut tys = schemas.iter().map(|schema| member_ty(schema, member));
+    let ty = tys.next().flatten().ok_or_else(|| {
+        QueryError::UnsupportedQuery(format!("Unknown member {member} of {model}"))
+    })?;
+    if !tys.all(|other| other == Some(ty)) {
+        return Err(QueryError::UnsupportedQuery(format!(
+            "{member} of {model} isn't of the same type in all the worlds"
+        ))
+        .into());
+    }
+
+    Ok(ty)
+}
+
+/// Parses a member value stored as text, either hex or decimal.
+fn parse_aggregated_value(value: &str) -> Option<AggregatedValue> {
+    if let Some(hex) = value.strip_prefix("0x") {
+        if hex.is_empty() || hex.len() > 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        return Some(AggregatedValue {
+            value: I256::from(sql_string_to_u256(&format!("{hex:0>64}"))),
+            is_hex: true,
+        });
+    }
+
+    let value = value.parse::<i128>().ok()?;
+    Some(AggregatedValue {
+        value: I256 {
+            value: StarknetU256::from_words(value.unsigned_abs(), 0),
+            is_negative: value < 0,
+        },
+        is_hex: false,
+    })
+}
+
+#[derive(Debug, Clone, Copy)]
+struct AggregatedValue {
+    value: I256,
+    /// Whether the member values are hex, to format the aggregate alike
+    is_hex: bool,
+}
+
+impl AggregatedValue {
+    fn format(&self) -> String {
+        let sign = if self.value.is_negative && self.value.value != StarknetU256::from(0u8) {
+            "-"
+        } else {
+            ""
+        };
+        if !self.is_hex && self.value.value.high() == 0 {
+            format!("{sign}{}", self.value.value.low())
+        } else {
+            format!("{sign}{}", u256_to_sql_string(&self.value.value))
+        }
+    }
+
+    fn to_f64(self) -> f64 {
+        let value = u256_to_f64(&self.value.value);
+        if self.value.is_negative {
+            -value
+        } else {
+            value
+        }
+    }
+}
+
+/// Aggregate of the text values of a group of entities.
+#[derive(Debug, Default)]
+struct Accumulator {
+    count: u64,
+    values: u64,
+    sum: Option<AggregatedValue>,
+    min: Option<AggregatedValue>,
+    max: Option<AggregatedValue>,
+}
+
+impl Accumulator {
+    fn add(&mut self, value: Option<AggregatedValue>) {
+        self.count += 1;
+        let Some(value) = value else {
+            return;
+        };
+
+        self.values += 1;
+        self.sum = Some(match self.sum {
+            Some(sum) => AggregatedValue {
+                value: sum.value + value.value,
+                ..sum
+            },
+            None => value,
+        });
+        if self.min.is_none_or(|min| value.value < min.value) {
+            self.min = Some(value);
+        }
+        if self.max.is_none_or(|max| value.value > max.value) {
+            self.max = Some(value);
+        }
+    }
+
+    fn value(&self, function: AggregateFunction) -> Option<String> {
+        match function {
+            AggregateFunction::Count => None,
+            AggregateFunction::Sum => self.sum.map(|sum| sum.format()),
+            AggregateFunction::Min => self.min.map(|min| min.format()),
+            AggregateFunction::Max => self.max.map(|max| max.format()),
+            AggregateFunction::Avg => self
+                .sum
+                .map(|sum| (sum.to_f64() / self.values as f64).to_string()),
+        }
+    }
+}
+
+/// Number of join clauses nested in one another in `clause`.
+fn join_depth(clause: &Clause) -> usize {
+    match clause {
+        Clause::Join(join) => 1 + join.clauses.iter().map(join_depth).max().unwrap_or(0),
+        Clause::Composite(composite) => composite.clauses.iter().map(join_depth).max().unwrap_or(0),
+        Clause::HashedKeys(_) | Clause::Keys(_) | Clause::Member(_) => 0,
+    }
+}
+
+/// Makes the clauses on `model` refer to it through its `alias`. The clauses of nested joins are
+/// left as is, they refer to the models of their own join.
+fn alias_model(clause: &Clause, model: &str, alias: &str) -> Clause {
+    match clause {
+        Clause::Member(member) if member.model == model => Clause::Member(MemberClause {
+            model: alias.to_string(),
+            ..member.clone()
+        }),
+        Clause::Composite(composite) => Clause::Composite(CompositeClause {
+            operator: composite.operator.clone(),
+            clauses: composite
+                .clauses
+                .iter()
+                .map(|clause| alias_model(clause, model, alias))
+                .collect(),
+        }),
+        Clause::Join(join) if join.model == model => Clause::Join(JoinClause {
+            model: alias.to_string(),
+            ..join.clone()
+        }),
+        _ => clause.clone(),
+    }
+}
+
 impl Sql {
     #[allow(clippy::too_many_arguments)]
     pub(crate) async fn query_by_composite(
@@ -758,6 +1020,181 @@ impl Sql {
         Ok(page)
     }
 
+    /// Aggregates the entities of a model matching the clause of the query, for each group of
+    /// their `group_by` members.
+    ///
+    /// Integer members are aggregated by SQLite. Felts and integers of more than 64 bits are
+    /// stored as hex strings, so they are aggregated here as 256 bits integers.
+    pub(crate) async fn aggregate_entities_by_composite(
+        &self,
+        query: &EntityAggregateQuery,
+    ) -> Result<Vec<EntityAggregate>, Error> {
+        let selector = try_compute_selector_from_tag(&query.model)
+            .map_err(|_| QueryError::InvalidNamespacedModel(query.model.clone()))?;
+        let schemas = self
+            .models(&query.world_addresses, &[selector])
+            .await?
+            .into_iter()
+            .map(|model| model.schema)
+            .collect::<Vec<_>>();
+        if schemas.is_empty() {
+            return Err(QueryError::ModelNotFound(query.model.clone()).into());
+        }
+
+        for member in &query.group_by {
+            resolve_member(&schemas, &query.model, member)?;
+        }
+
+        // The aggregated member, and whether SQLite can aggregate it
+        let member = match (query.function, &query.member) {
+            (AggregateFunction::Count, _) => None,
+            (_, Some(member)) => match resolve_member(&schemas, &query.model, member)? {
+                Ty::Primitive(primitive) => {
+                    Some((member, primitive.to_sql_type() == SqlType::Integer))
+                }
+                _ => {
+                    return Err(QueryError::UnsupportedQuery(format!(
+                        "{member} of {} is not a primitive member",
+                        query.model
+                    ))
+                    .into())
+                }
+            },
+            (_, None) => return Err(QueryError::MissingParam("member".to_string()).into()),
+        };
+
+        let table = &query.model;
+        let groups = query
+            .group_by
+            .iter()
+            .map(|member| format!("CAST([{table}].[{member}] AS TEXT)"))
+            .collect::<Vec<_>>();
+
+        let mut conditions = Vec::new();
+        let mut bind_values = Vec::new();
+        if let Some(clause) = &query.clause {
+            let (where_clause, values) = build_composite_clause(
+                ENTITIES_TABLE,
+                ENTITIES_MODEL_RELATION_TABLE,
+                &CompositeClause {
+                    operator: LogicalOperator::And,
+                    clauses: vec![clause.clone()],
+                },
+                false,
+            )?;
+            if !where_clause.is_empty() {
+                conditions.push(format!("({where_clause})"));
+                bind_values.extend(values);
+            }
+        }
+        if !query.world_addresses.is_empty() {
+            let placeholders = vec!["?"; query.world_addresses.len()].join(", ");
+            conditions.push(format!(
+                "{ENTITIES_TABLE}.world_address IN ({placeholders})"
+            ));
+            bind_values.extend(query.world_addresses.iter().map(felt_to_sql_string));
+        }
+
+        let mut sql = format!(
+            "FROM [{table}] JOIN {ENTITIES_TABLE} ON {ENTITIES_TABLE}.id = \
+             [{table}].{ENTITIES_ENTITY_RELATION_COLUMN}"
+        );
+        if !conditions.is_empty() {
+            sql = format!("{sql} WHERE {}", conditions.join(" AND "));
+        }
+
+        let aggregated_by_sqlite = member.is_none_or(|(_, is_integer)| is_integer);
+        let mut selections = groups.clone();
+        if aggregated_by_sqlite {
+            selections.push("COUNT(*)".to_string());
+            if let Some((member, _)) = member {
+                let function = match query.function {
+                    AggregateFunction::Sum => "SUM",
+                    AggregateFunction::Min => "MIN",
+                    AggregateFunction::Max => "MAX",
+                    AggregateFunction::Avg => "AVG",
+                    AggregateFunction::Count => unreachable!("counts have no member"),
+                };
+                selections.push(format!("CAST({function}([{table}].[{member}]) AS TEXT)"));
+            }
+            if !groups.is_empty() {
+                sql = format!("{sql} GROUP BY {}", groups.join(", "));
+            }
+        } else if let Some((member, _)) = member {
+            selections.push(format!("[{table}].[{member}]"));
+        }
+        if !groups.is_empty() {
+            sql = format!("{sql} ORDER BY {}", groups.join(", "));
+        }
+
+        let sql = format!("SELECT {} {sql}", selections.join(", "));
+        let mut statement = sqlx::query(&sql);
+        for value in &bind_values {
+            statement = statement.bind(value);
+        }
+
+        let group_of = |row: &SqliteRow| {
+            (0..groups.len())
+                .map(|i| {
+                    row.try_get::<Option<String>, _>(i)
+                        .map(Option::unwrap_or_default)
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        if aggregated_by_sqlite {
+            return statement
+                .fetch_all(&self.pool)
+                .await?
+                .iter()
+                .map(|row| {
+                    Ok::<_, Error>(EntityAggregate {
+                        group: group_of(row)?,
+                        count: row.try_get::<i64, _>(groups.len())? as u64,
+                        value: if member.is_some() {
+                            row.try_get::<Option<String>, _>(groups.len() + 1)?
+                        } else {
+                            None
+                        },
+                    })
+                })
+                .collect();
+        }
+
+        // Rows are ordered by group, so they are streamed and the groups folded one after the
+        // other, only their accumulators are kept in memory
+        let mut aggregates: Vec<(Vec<String>, Accumulator)> = Vec::new();
+        let mut rows = statement.fetch(&self.pool);
+        while let Some(row) = rows.try_next().await? {
+            let group = group_of(&row)?;
+            let value = row
+                .try_get::<Option<String>, _>(groups.len())?
+                .and_then(|value| parse_aggregated_value(&value));
+
+            match aggregates.last_mut() {
+                Some((last, accumulator)) if *last == group => accumulator.add(value),
+                _ => {
+                    let mut accumulator = Accumulator::default();
+                    accumulator.add(value);
+                    aggregates.push((group, accumulator));
+                }
+            }
+        }
+        // Like SQLite, aggregating no rows without groups gives an empty aggregate
+        if aggregates.is_empty() && groups.is_empty() {
+            aggregates.push((vec![], Accumulator::default()));
+        }
+
+        Ok(aggregates
+            .into_iter()
+            .map(|(group, accumulator)| EntityAggregate {
+                group,
+                count: accumulator.count,
+                value: accumulator.value(query.function),
+            })
+            .collect())
+    }
+
     async fn fetch_historical_entities(
         &self,
         table: &str,
@@ -1005,12 +1442,22 @@ impl Sql {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use super::*;
+    use dojo_types::naming::compute_selector_from_names;
+    use dojo_types::schema::{Member, Struct};
     use starknet::core::types::Felt;
+    use starknet::providers::jsonrpc::HttpTransport;
+    use starknet::providers::{JsonRpcClient, Url};
+    use starknet_crypto::poseidon_hash_many;
     use torii_proto::{
-        Clause, ComparisonOperator, CompositeClause, KeysClause, LogicalOperator, MemberClause,
-        MemberValue,
+        Clause, ComparisonOperator, CompositeClause, ContractDefinition, ContractType, JoinClause,
+        KeysClause, LogicalOperator, MemberClause, MemberValue, PaginationDirection, Query,
     };
+    use torii_storage::Storage;
+
+    use crate::executor::Executor;
 
     #[test]
     fn test_build_composite_clause_hashed_keys() {
@@ -1356,7 +1803,7 @@ mod tests {
 
         assert_eq!(
             where_clause,
-            "EXISTS (SELECT 1 FROM json_each([Player].[scores]) WHERE value = ?)"
+            "EXISTS (SELECT 1 FROM json_each([Player].[scores]) WHERE CAST(value AS TEXT) = ?)"
         );
         assert_eq!(bind_values.len(), 1);
     }
@@ -1381,7 +1828,7 @@ mod tests {
         let (where_clause, bind_values) =
             build_composite_clause("entities", "entity_model", &composite, false).unwrap();
 
-        assert_eq!(where_clause, "NOT EXISTS (SELECT 1 FROM (VALUES (?, ?, ?)) AS input_vals(val) WHERE NOT EXISTS (SELECT 1 FROM json_each([Player].[scores]) WHERE value = input_vals.val))");
+        assert_eq!(where_clause, "NOT EXISTS (SELECT 1 FROM json_each(json_array(?, ?, ?)) AS input_vals WHERE NOT EXISTS (SELECT 1 FROM json_each([Player].[scores]) WHERE CAST(value AS TEXT) = input_vals.value))");
         assert_eq!(bind_values.len(), 3); // The list values are used once
     }
 
@@ -1407,7 +1854,7 @@ mod tests {
             build_composite_clause("entities", "entity_model", &composite, false).unwrap();
 
         // Should still work correctly even with duplicate values in the search criteria
-        assert_eq!(where_clause, "NOT EXISTS (SELECT 1 FROM (VALUES (?, ?, ?)) AS input_vals(val) WHERE NOT EXISTS (SELECT 1 FROM json_each([Player].[scores]) WHERE value = input_vals.val))");
+        assert_eq!(where_clause, "NOT EXISTS (SELECT 1 FROM json_each(json_array(?, ?, ?)) AS input_vals WHERE NOT EXISTS (SELECT 1 FROM json_each([Player].[scores]) WHERE CAST(value AS TEXT) = input_vals.value))");
         assert_eq!(bind_values.len(), 3); // Still 3 bind values even with duplicates
     }
 
@@ -1432,7 +1879,7 @@ mod tests {
 
         assert_eq!(
             where_clause,
-            "EXISTS (SELECT 1 FROM json_each([Player].[inventory]) WHERE value IN (?, ?))"
+            "EXISTS (SELECT 1 FROM json_each([Player].[inventory]) WHERE CAST(value AS TEXT) IN (?, ?))"
         );
         assert_eq!(bind_values.len(), 2);
     }
@@ -1453,7 +1900,10 @@ mod tests {
         let (where_clause, bind_values) =
             build_composite_clause("entities", "entity_model", &composite, false).unwrap();
 
-        assert_eq!(where_clause, "json_array_length([Player].[skills]) = ?");
+        assert_eq!(
+            where_clause,
+            "json_array_length([Player].[skills]) = CAST(? AS INTEGER)"
+        );
         assert_eq!(bind_values.len(), 1);
     }
 
@@ -1475,7 +1925,7 @@ mod tests {
 
         assert_eq!(
             where_clause,
-            "json_array_length([Player].[achievements]) > ?"
+            "json_array_length([Player].[achievements]) > CAST(? AS INTEGER)"
         );
         assert_eq!(bind_values.len(), 1);
     }
@@ -1496,7 +1946,10 @@ mod tests {
         let (where_clause, bind_values) =
             build_composite_clause("entities", "entity_model", &composite, false).unwrap();
 
-        assert_eq!(where_clause, "json_array_length([Player].[buffs]) < ?");
+        assert_eq!(
+            where_clause,
+            "json_array_length([Player].[buffs]) < CAST(? AS INTEGER)"
+        );
         assert_eq!(bind_values.len(), 1);
     }
 
@@ -1516,7 +1969,7 @@ mod tests {
         let (where_clause, bind_values) =
             build_composite_clause("entities", "entity_model", &composite, true).unwrap();
 
-        assert_eq!(where_clause, "EXISTS (SELECT 1 FROM json_each(JSON_EXTRACT(entities.data, '$.scores')) WHERE value = ?)");
+        assert_eq!(where_clause, "EXISTS (SELECT 1 FROM json_each(JSON_EXTRACT(entities.data, '$.scores')) WHERE CAST(value AS TEXT) = ?)");
         assert_eq!(bind_values.len(), 1);
     }
 
@@ -1542,8 +1995,9 @@ mod tests {
         let (where_clause, bind_values) =
             build_composite_clause("entities", "entity_model", &composite, false).unwrap();
 
-        assert!(where_clause
-            .contains("EXISTS (SELECT 1 FROM json_each([Player].[scores]) WHERE value = ?)"));
+        assert!(where_clause.contains(
+            "EXISTS (SELECT 1 FROM json_each([Player].[scores]) WHERE CAST(value AS TEXT) = ?)"
+        ));
         assert!(where_clause.contains("([Player].[name] = ?)"));
         assert!(where_clause.contains(" AND "));
         assert_eq!(bind_values.len(), 2);
@@ -1629,4 +2083,496 @@ mod tests {
         assert!(where_clause.contains(" AND "));
         assert_eq!(bind_values.len(), 3);
     }
+
+    #[test]
+    fn test_build_composite_clause_not_operator() {
+        let composite = CompositeClause {
+            operator: LogicalOperator::Not,
+            clauses: vec![
+                Clause::Member(MemberClause {
+                    model: "Player".to_string(),
+                    member: "score".to_string(),
+                    operator: ComparisonOperator::Gt,
+                    value: MemberValue::Primitive(Primitive::U32(Some(100))),
+                }),
+                Clause::Composite(CompositeClause {
+                    operator: LogicalOperator::Or,
+                    clauses: vec![
+                        Clause::Member(MemberClause {
+                            model: "Player".to_string(),
+                            member: "level".to_string(),
+                            operator: ComparisonOperator::Lt,
+                            value: MemberValue::Primitive(Primitive::U32(Some(5))),
+                        }),
+                        Clause::Member(MemberClause {
+                            model: "Player".to_string(),
+                            member: "level".to_string(),
+                            operator: ComparisonOperator::Gt,
+                            value: MemberValue::Primitive(Primitive::U32(Some(50))),
+                        }),
+                    ],
+                }),
+            ],
+        };
+
+        let (where_clause, bind_values) =
+            build_composite_clause("entities", "entity_model", &composite, false).unwrap();
+
+        assert_eq!(
+            where_clause,
+            "NOT (([Player].[score] > ?) AND (([Player].[level] < ?) OR ([Player].[level] > ?)))"
+        );
+        assert_eq!(bind_values.len(), 3);
+    }
+
+    #[test]
+    fn test_build_composite_clause_null_like_between() {
+        let composite = CompositeClause {
+            operator: LogicalOperator::And,
+            clauses: vec![
+                Clause::Member(MemberClause {
+                    model: "Player".to_string(),
+                    member: "mount.Some".to_string(),
+                    operator: ComparisonOperator::IsNull,
+                    value: MemberValue::List(vec![]),
+                }),
+                Clause::Member(MemberClause {
+                    model: "Player".to_string(),
+                    member: "name".to_string(),
+                    operator: ComparisonOperator::Like,
+                    value: MemberValue::String("bob%".to_string()),
+                }),
+                Clause::Member(MemberClause {
+                    model: "Player".to_string(),
+                    member: "level".to_string(),
+                    operator: ComparisonOperator::Between,
+                    value: MemberValue::List(vec![
+                        MemberValue::Primitive(Primitive::U32(Some(5))),
+                        MemberValue::Primitive(Primitive::U32(Some(10))),
+                    ]),
+                }),
+            ],
+        };
+
+        let (where_clause, bind_values) =
+            build_composite_clause("entities", "entity_model", &composite, false).unwrap();
+
+        assert_eq!(
+            where_clause,
+            "([Player].[mount.Some] IS NULL) AND ([Player].[name] LIKE ?) AND ([Player].[level] \
+             BETWEEN ? AND ?)"
+        );
+        assert_eq!(bind_values, vec!["bob%", "5", "10"]);
+
+        // BETWEEN requires both bounds
+        let composite = CompositeClause {
+            operator: LogicalOperator::And,
+            clauses: vec![Clause::Member(MemberClause {
+                model: "Player".to_string(),
+                member: "level".to_string(),
+                operator: ComparisonOperator::Between,
+                value: MemberValue::Primitive(Primitive::U32(Some(5))),
+            })],
+        };
+        assert!(build_composite_clause("entities", "entity_model", &composite, false).is_err());
+    }
+
+    #[test]
+    fn test_accumulate_text_values() {
+        // felts and u256 values are hex, i128 values decimal
+        let mut felts = Accumulator::default();
+        for value in [Some("0x0a"), None, Some("0x05"), Some("not a number")] {
+            felts.add(value.and_then(parse_aggregated_value));
+        }
+        assert_eq!(felts.count, 4);
+        assert_eq!(
+            felts.value(AggregateFunction::Sum),
+            Some(format!("0x{:0>62}", "f"))
+        );
+        assert_eq!(
+            felts.value(AggregateFunction::Min),
+            Some(format!("0x{:0>62}", "5"))
+        );
+        assert_eq!(felts.value(AggregateFunction::Avg), Some("7.5".to_string()));
+
+        let mut signed = Accumulator::default();
+        for value in ["-20", "5", "3"] {
+            signed.add(parse_aggregated_value(value));
+        }
+        assert_eq!(
+            signed.value(AggregateFunction::Sum),
+            Some("-12".to_string())
+        );
+        assert_eq!(
+            signed.value(AggregateFunction::Min),
+            Some("-20".to_string())
+        );
+        assert_eq!(signed.value(AggregateFunction::Max), Some("5".to_string()));
+        assert_eq!(signed.value(AggregateFunction::Count), None);
+
+        assert_eq!(Accumulator::default().value(AggregateFunction::Sum), None);
+    }
+
+    #[test]
+    fn test_build_composite_clause_join() {
+        // positions of the players of guild 1, whose leader is in guild 2
+        let composite = CompositeClause {
+            operator: LogicalOperator::And,
+            clauses: vec![Clause::Join(JoinClause {
+                model: "ns-Position".to_string(),
+                member: "player".to_string(),
+                joined_model: "ns-Player".to_string(),
+                joined_member: "player".to_string(),
+                clauses: vec![
+                    Clause::Member(MemberClause {
+                        model: "ns-Player".to_string(),
+                        member: "guild".to_string(),
+                        operator: ComparisonOperator::Eq,
+                        value: MemberValue::Primitive(Primitive::U32(Some(1))),
+                    }),
+                    Clause::Join(JoinClause {
+                        model: "ns-Player".to_string(),
+                        member: "leader".to_string(),
+                        joined_model: "ns-Player".to_string(),
+                        joined_member: "player".to_string(),
+                        clauses: vec![Clause::Member(MemberClause {
+                            model: "ns-Player".to_string(),
+                            member: "guild".to_string(),
+                            operator: ComparisonOperator::Eq,
+                            value: MemberValue::Primitive(Primitive::U32(Some(2))),
+                        })],
+                    }),
+                ],
+            })],
+        };
+
+        let (where_clause, bind_values) =
+            build_composite_clause("entities", "entity_model", &composite, false).unwrap();
+
+        assert_eq!(
+            where_clause,
+            "EXISTS (SELECT 1 FROM entities entities_joined JOIN [ns-Player] \
+             [ns-Position.player] ON [ns-Position.player].internal_entity_id = entities_joined.id \
+             WHERE entities_joined.world_address = entities.world_address AND \
+             [ns-Position.player].[player] = [ns-Position].[player] AND \
+             (([ns-Position.player].[guild] = ?) AND EXISTS (SELECT 1 FROM entities \
+             entities_joined_joined JOIN [ns-Player] [ns-Position.player.leader] ON \
+             [ns-Position.player.leader].internal_entity_id = entities_joined_joined.id WHERE \
+             entities_joined_joined.world_address = entities_joined.world_address AND \
+             [ns-Position.player.leader].[player] = [ns-Position.player].[leader] AND \
+             (([ns-Position.player.leader].[guild] = ?)))))"
+        );
+        assert_eq!(bind_values, vec!["1", "2"]);
+
+        // joins need the current state of the joined entities
+        assert!(build_composite_clause("entities_historical", "", &composite, true).is_err());
+    }
+
+    fn model(name: &str, members: Vec<(&str, Primitive, bool)>) -> Ty {
+        Ty::Struct(Struct {
+            name: name.to_string(),
+            children: members
+                .into_iter()
+                .map(|(name, primitive, key)| Member {
+                    name: name.to_string(),
+                    ty: Ty::Primitive(primitive),
+                    key,
+                })
+                .collect(),
+        })
+    }
+
+    fn player(player: u64, guild: u32) -> Ty {
+        model(
+            "ns-Player",
+            vec![
+                (
+                    "player",
+                    Primitive::ContractAddress(Some(Felt::from(player))),
+                    true,
+                ),
+                ("guild", Primitive::U32(Some(guild)), false),
+                ("gold", Primitive::U128(Some(player as u128 * 10)), false),
+            ],
+        )
+    }
+
+    fn position(id: u32, player: u64) -> Ty {
+        model(
+            "ns-Position",
+            vec![
+                ("id", Primitive::U32(Some(id)), true),
+                (
+                    "player",
+                    Primitive::ContractAddress(Some(Felt::from(player))),
+                    false,
+                ),
+            ],
+        )
+    }
+
+    // Players 1 and 3 are in guild 1, players 2 and 4 in guild 0, and each player has a position,
+    // both as entities and as event messages
+    async fn setup_players(pool: sqlx::SqlitePool) -> Sql {
+        let (shutdown_tx, _) = tokio::sync::broadcast::channel(1);
+        let url: Url = "https://www.example.com".parse().unwrap();
+        let provider = Arc::new(JsonRpcClient::new(HttpTransport::new(url)));
+        let (mut executor, sender) = Executor::new(pool.clone(), shutdown_tx, provider)
+            .await
+            .unwrap();
+        tokio::spawn(async move {
+            executor.run().await.unwrap();
+        });
+        let db = Sql::new(
+            pool.clone(),
+            sender,
+            &[ContractDefinition {
+                address: Felt::ZERO,
+                r#type: ContractType::WORLD,
+                starting_block: None,
+            }],
+        )
+        .await
+        .unwrap();
+
+        for ty in [player(0, 0), position(0, 0)] {
+            let name = ty.name();
+            let (namespace, name) = name.split_once('-').unwrap();
+            db.register_model(
+                Felt::ZERO,
+                compute_selector_from_names(namespace, name),
+                &ty,
+                &Layout::Fixed(vec![]),
+                Felt::ZERO,
+                Felt::ZERO,
+                0,
+                0,
+                0,
+                None,
+                None,
+                true,
+            )
+            .await
+            .unwrap();
+        }
+
+        for id in 1..=4u32 {
+            let player_address = Felt::from(id);
+            db.set_entity(
+                Felt::ZERO,
+                player(id.into(), id % 2),
+                &format!("0x0:0x{id}:0x0"),
+                0,
+                player_address,
+                compute_selector_from_names("ns", "Player"),
+                Some(vec![player_address]),
+            )
+            .await
+            .unwrap();
+            db.set_entity(
+                Felt::ZERO,
+                position(id, id.into()),
+                &format!("0x0:0x{id}:0x1"),
+                0,
+                Felt::from(100 + id),
+                compute_selector_from_names("ns", "Position"),
+                Some(vec![Felt::from(id)]),
+            )
+            .await
+            .unwrap();
+            db.set_event_message(
+                Felt::ZERO,
+                player(id.into(), id % 2),
+                &format!("0x0:0x{id}:0x2"),
+                0,
+                vec![player_address],
+            )
+            .await
+            .unwrap();
+            db.set_event_message(
+                Felt::ZERO,
+                position(id, id.into()),
+                &format!("0x0:0x{id}:0x3"),
+                0,
+                vec![Felt::from(100 + id)],
+            )
+            .await
+            .unwrap();
+        }
+        db.execute().await.unwrap();
+
+        db
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn test_query_join(pool: sqlx::SqlitePool) {
+        let db = setup_players(pool).await;
+
+        // the positions of the players of guild 1
+        let query = |cursor| Query {
+            clause: Some(Clause::Join(JoinClause {
+                model: "ns-Position".to_string(),
+                member: "player".to_string(),
+                joined_model: "ns-Player".to_string(),
+                joined_member: "player".to_string(),
+                clauses: vec![Clause::Member(MemberClause {
+                    model: "ns-Player".to_string(),
+                    member: "guild".to_string(),
+                    operator: ComparisonOperator::Eq,
+                    value: MemberValue::Primitive(Primitive::U32(Some(1))),
+                })],
+            })),
+            pagination: Pagination {
+                cursor,
+                limit: Some(1),
+                direction: PaginationDirection::Forward,
+                order_by: vec![],
+            },
+            no_hashed_keys: false,
+            models: vec!["ns-Position".to_string()],
+            historical: false,
+            world_addresses: vec![],
+        };
+        let ids = |page: &Page<Entity>| {
+            page.items
+                .iter()
+                .map(|entity| entity.hashed_keys)
+                .collect::<Vec<_>>()
+        };
+
+        // the joined positions are paginated like the other queries
+        let first = db.entities(&query(None)).await.unwrap();
+        assert_eq!(first.items.len(), 1);
+        let second = db
+            .entities(&query(first.next_cursor.clone()))
+            .await
+            .unwrap();
+        assert_eq!(second.items.len(), 1);
+        let mut positions = [ids(&first), ids(&second)].concat();
+        positions.sort();
+        assert_eq!(positions, vec![Felt::from(101), Felt::from(103)]);
+        if let Some(cursor) = second.next_cursor {
+            assert!(db
+                .entities(&query(Some(cursor)))
+                .await
+                .unwrap()
+                .items
+                .is_empty());
+        }
+
+        // event messages are joined with the other event messages
+        let mut query = query(None);
+        query.pagination.limit = None;
+        let mut positions = ids(&db.event_messages(&query).await.unwrap());
+        positions.sort();
+        let mut expected = vec![
+            poseidon_hash_many(&[Felt::from(101)]),
+            poseidon_hash_many(&[Felt::from(103)]),
+        ];
+        expected.sort();
+        assert_eq!(positions, expected);
+
+        // joins are nested up to a maximum depth
+        let mut clause = query.clause.clone().unwrap();
+        for _ in 0..SQL_MAX_JOIN_DEPTH {
+            let Clause::Join(join) = &clause else {
+                unreachable!()
+            };
+            clause = Clause::Join(JoinClause {
+                clauses: vec![clause.clone()],
+                ..join.clone()
+            });
+        }
+        query.clause = Some(clause);
+        assert!(db.entities(&query).await.is_err());
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn test_aggregate_entities(pool: sqlx::SqlitePool) {
+        let db = setup_players(pool.clone()).await;
+
+        let query = |function, member: Option<&str>| EntityAggregateQuery {
+            model: "ns-Player".to_string(),
+            clause: None,
+            group_by: vec!["guild".to_string()],
+            function,
+            member: member.map(str::to_string),
+            world_addresses: vec![],
+        };
+        let aggregates = |aggregates: Vec<EntityAggregate>| {
+            aggregates
+                .into_iter()
+                .map(|aggregate| (aggregate.group, aggregate.count, aggregate.value))
+                .collect::<Vec<_>>()
+        };
+        let group = |guild: &str| vec![guild.to_string()];
+
+        let counts = db
+            .aggregate_entities(&query(AggregateFunction::Count, None))
+            .await
+            .unwrap();
+        assert_eq!(
+            aggregates(counts),
+            vec![(group("0"), 2, None), (group("1"), 2, None)]
+        );
+
+        // u128 are stored as hex, their rows are streamed and summed here
+        let sums = db
+            .aggregate_entities(&query(AggregateFunction::Sum, Some("gold")))
+            .await
+            .unwrap();
+        assert_eq!(
+            aggregates(sums),
+            vec![
+                (group("0"), 2, Some(format!("{:#064x}", 60))),
+                (group("1"), 2, Some(format!("{:#064x}", 40))),
+            ]
+        );
+
+        // integers are aggregated by SQLite, here the filtered entities without groups
+        let mut max = query(AggregateFunction::Max, Some("guild"));
+        max.group_by = vec![];
+        max.clause = Some(Clause::Member(MemberClause {
+            model: "ns-Player".to_string(),
+            member: "gold".to_string(),
+            operator: ComparisonOperator::Lt,
+            value: MemberValue::Primitive(Primitive::U128(Some(30))),
+        }));
+        assert_eq!(
+            aggregates(db.aggregate_entities(&max).await.unwrap()),
+            vec![(vec![], 2, Some("1".to_string()))]
+        );
+
+        // another world registers the model with a felt guild
+        let selector = compute_selector_from_names("ns", "Player");
+        let other_schema = model(
+            "ns-Player",
+            vec![
+                ("player", Primitive::ContractAddress(None), true),
+                ("guild", Primitive::Felt252(None), false),
+            ],
+        );
+        sqlx::query(
+            "INSERT INTO models (id, world_address, model_selector, namespace, name, class_hash, \
+             contract_address, layout, legacy_store, schema, packed_size, unpacked_size, \
+             executed_at) SELECT ?, ?, model_selector, namespace, name, class_hash, \
+             contract_address, layout, legacy_store, ?, packed_size, unpacked_size, executed_at \
+             FROM models WHERE name = 'Player'",
+        )
+        .bind(format_world_scoped_id(&Felt::ONE, &selector))
+        .bind(felt_to_sql_string(&Felt::ONE))
+        .bind(serde_json::to_string(&other_schema).unwrap())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // the member types are resolved in each of the queried worlds
+        assert!(db
+            .aggregate_entities(&query(AggregateFunction::Count, None))
+            .await
+            .is_err());
+        let mut counts = query(AggregateFunction::Count, None);
+        counts.world_addresses = vec![Felt::ZERO];
+        assert_eq!(db.aggregate_entities(&counts).await.unwrap().len(), 2);
+    }
 }
@@ -0,0 +1,394 @@
+//! Replication of a primary database to followers.
+//!
+//! With `replication_log` set, the executor of the primary writes the queries of every committed
+//! transaction to the `replication_log` table, as a batch in the same transaction. Followers fetch
+//! the batches after the last one they applied and replay them through their own executor, which
+//! writes the same rows and publishes the same updates to the subscribers without fetching the
+//! chain.
+//!
+//! A follower bootstrapped from a snapshot of the primary resumes from the last batch of the log
+//! in the snapshot.
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, Sqlite, SqlitePool, Transaction};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::executor::error::ExecutorQueryError;
+use crate::executor::{Argument, QueryMessage, QueryType};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReplicationError {
+    #[error(transparent)]
+    Sql(#[from] sqlx::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Query(#[from] ExecutorQueryError),
+    #[error("Executor stopped")]
+    ExecutorStopped,
+    #[error(
+        "Batches after {after} were pruned from the log, the oldest one is {oldest}. Bootstrap \
+         from a more recent snapshot"
+    )]
+    Pruned { after: i64, oldest: i64 },
+}
+
+/// A query as sent to the executor of the primary.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicatedQuery {
+    pub statement: String,
+    pub arguments: Vec<Argument>,
+    pub query_type: QueryType,
+}
+
+/// The queries of a committed transaction of the primary.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicationBatch {
+    pub id: i64,
+    pub queries: Vec<ReplicatedQuery>,
+}
+
+/// Appends a batch to the log, keeping the last `retention` batches if not zero.
+pub(crate) async fn write(
+    transaction: &mut Transaction<'_, Sqlite>,
+    queries: &[ReplicatedQuery],
+    retention: u64,
+) -> Result<(), crate::executor::error::ExecutorError> {
+    let id: i64 =
+        sqlx::query_scalar("INSERT INTO replication_log (queries) VALUES (?) RETURNING id")
+            .bind(serde_json::to_string(queries)?)
+            .fetch_one(&mut **transaction)
+            .await?;
+
+    if retention > 0 {
+        sqlx::query("DELETE FROM replication_log WHERE id <= ?")
+            .bind(id - retention as i64)
+            .execute(&mut **transaction)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Up to `limit` batches of the log after the batch `after`, in order.
+pub async fn changes(
+    pool: &SqlitePool,
+    after: i64,
+    limit: u32,
+) -> Result<Vec<ReplicationBatch>, ReplicationError> {
+    let oldest: Option<i64> = sqlx::query_scalar("SELECT MIN(id) FROM replication_log")
+        .fetch_one(pool)
+        .await?;
+    if let Some(oldest) = oldest {
+        if after + 1 < oldest {
+            return Err(ReplicationError::Pruned { after, oldest });
+        }
+    }
+
+    let rows =
+        sqlx::query("SELECT id, queries FROM replication_log WHERE id > ? ORDER BY id LIMIT ?")
+            .bind(after)
+            .bind(limit)
+            .fetch_all(pool)
+            .await?;
+
+    rows.into_iter()
+        .map(|row| {
+            Ok::<_, ReplicationError>(ReplicationBatch {
+                id: row.get("id"),
+                queries: serde_json::from_str(row.get::<&str, _>("queries"))?,
+            })
+        })
+        .collect()
+}
+
+/// Last batch of the log, zero if it's empty.
+pub async fn head(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar("SELECT COALESCE(MAX(id), 0) FROM replication_log")
+        .fetch_one(pool)
+        .await
+}
+
+/// Last batch of the primary applied by this follower. Until it applies one, the last batch of its
+/// own log, which is the one of the snapshot it was bootstrapped from.
+pub async fn applied_id(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT COALESCE((SELECT applied_id FROM replication_state WHERE id = 0), (SELECT \
+         MAX(id) FROM replication_log), 0)",
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Applies a batch through the executor and commits it, along with its id, in one transaction.
+/// The transaction is rolled back if a query fails.
+pub async fn apply(
+    executor: &UnboundedSender<QueryMessage>,
+    batch: ReplicationBatch,
+) -> Result<(), ReplicationError> {
+    let result = async {
+        for query in batch.queries {
+            let (message, rx) =
+                QueryMessage::new_recv(query.statement, query.arguments, query.query_type);
+            executor
+                .send(message)
+                .map_err(|_| ReplicationError::ExecutorStopped)?;
+            rx.await.map_err(|_| ReplicationError::ExecutorStopped)??;
+        }
+
+        let (message, rx) = QueryMessage::other_recv(
+            "INSERT INTO replication_state (id, applied_id) VALUES (0, ?) ON CONFLICT(id) DO \
+             UPDATE SET applied_id = excluded.applied_id"
+                .to_string(),
+            vec![Argument::Int(batch.id)],
+        );
+        executor
+            .send(message)
+            .map_err(|_| ReplicationError::ExecutorStopped)?;
+        rx.await.map_err(|_| ReplicationError::ExecutorStopped)??;
+
+        Ok::<_, ReplicationError>(())
+    }
+    .await;
+
+    let (message, rx) = match result {
+        Ok(()) => QueryMessage::execute_recv(),
+        Err(_) => QueryMessage::rollback_recv(),
+    };
+    executor
+        .send(message)
+        .map_err(|_| ReplicationError::ExecutorStopped)?;
+    rx.await.map_err(|_| ReplicationError::ExecutorStopped)??;
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+    use sqlx::SqlitePool;
+    use starknet::core::types::{Felt, U256};
+    use starknet::providers::jsonrpc::HttpTransport;
+    use starknet::providers::{JsonRpcClient, Url};
+    use tempfile::NamedTempFile;
+    use tokio::sync::broadcast;
+    use torii_math::I256;
+    use torii_proto::{BalanceId, ContractCursor, ContractDefinition, ContractType, TokenId};
+    use torii_storage::Storage;
+
+    use super::*;
+    use crate::executor::{Executor, RegisterNftTokenQuery};
+    use crate::{Sql, SqlConfig};
+
+    /// Runs an executor on `pool`, whose provider is unreachable.
+    async fn executor(pool: &SqlitePool, config: SqlConfig) -> UnboundedSender<QueryMessage> {
+        let url: Url = "http://127.0.0.1:1".parse().unwrap();
+        let provider = Arc::new(JsonRpcClient::new(HttpTransport::new(url)));
+        let (shutdown_tx, _) = broadcast::channel(1);
+        let (mut executor, sender) =
+            Executor::new_with_config(pool.clone(), shutdown_tx, provider, config, PathBuf::new())
+                .await
+                .unwrap();
+        tokio::spawn(async move {
+            executor.run().await.unwrap();
+        });
+        sender
+    }
+
+    /// A migrated database, next to the one of the test.
+    async fn follower_pool(file: &NamedTempFile) -> SqlitePool {
+        let options = SqliteConnectOptions::from_str(&file.path().to_string_lossy())
+            .unwrap()
+            .create_if_missing(true);
+        let pool = SqlitePoolOptions::new()
+            .connect_with(options)
+            .await
+            .unwrap();
+        sqlx::migrate!("../../migrations").run(&pool).await.unwrap();
+        pool
+    }
+
+    async fn tokens(pool: &SqlitePool) -> Vec<(String, String, String, Option<String>)> {
+        sqlx::query_as("SELECT id, name, symbol, total_supply FROM tokens ORDER BY id")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    fn query(statement: &str) -> ReplicatedQuery {
+        ReplicatedQuery {
+            statement: statement.to_string(),
+            arguments: vec![],
+            query_type: QueryType::Other,
+        }
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn test_log_retention(pool: SqlitePool) {
+        for i in 0..3 {
+            let mut transaction = pool.begin().await.unwrap();
+            write(&mut transaction, &[query(&format!("SELECT {i}"))], 2)
+                .await
+                .unwrap();
+            transaction.commit().await.unwrap();
+        }
+
+        assert_eq!(head(&pool).await.unwrap(), 3);
+        assert_eq!(applied_id(&pool).await.unwrap(), 3);
+
+        let batches = changes(&pool, 1, 10).await.unwrap();
+        assert_eq!(
+            batches.iter().map(|batch| batch.id).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+        assert_eq!(batches[1].queries[0].statement, "SELECT 2");
+        assert!(changes(&pool, 3, 10).await.unwrap().is_empty());
+        assert!(matches!(
+            changes(&pool, 0, 10).await,
+            Err(ReplicationError::Pruned {
+                after: 0,
+                oldest: 2
+            })
+        ));
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn test_primary_to_follower(pool: SqlitePool) {
+        let config = SqlConfig {
+            replication_log: true,
+            ..Default::default()
+        };
+        let contract = Felt::from(0x721u64);
+        let db = Sql::new_with_config(
+            pool.clone(),
+            executor(&pool, config.clone()).await,
+            &[ContractDefinition {
+                address: contract,
+                r#type: ContractType::ERC721,
+                starting_block: None,
+            }],
+            config,
+        )
+        .await
+        .unwrap();
+
+        let token_id = TokenId::Nft(contract, U256::from(1u8));
+        db.register_token_contract(contract, "Test".to_string(), "TST".to_string(), 0, None)
+            .await
+            .unwrap();
+        db.register_nft_token(contract, U256::from(1u8), "{}".to_string())
+            .await
+            .unwrap();
+        db.apply_balances_diff(
+            HashMap::from([(
+                BalanceId {
+                    account_address: Felt::from(0xa11ceu64),
+                    token_id: token_id.clone(),
+                },
+                I256::from(1u8),
+            )]),
+            HashMap::from([(token_id, I256::from(1u8))]),
+            HashMap::from([(
+                contract,
+                ContractCursor {
+                    contract_address: contract,
+                    head: Some(1),
+                    last_block_timestamp: Some(100),
+                    last_pending_block_tx: None,
+                },
+            )]),
+        )
+        .await
+        .unwrap();
+        db.execute().await.unwrap();
+
+        // The name and symbol resolved by the primary are replicated
+        let batches = changes(&pool, 0, 100).await.unwrap();
+        let name_and_symbol = batches
+            .iter()
+            .flat_map(|batch| &batch.queries)
+            .find_map(|query| match &query.query_type {
+                QueryType::RegisterNftToken(query) => query.name_and_symbol.clone(),
+                _ => None,
+            });
+        assert_eq!(
+            name_and_symbol,
+            Some(("Test".to_string(), "TST".to_string()))
+        );
+
+        let file = NamedTempFile::new().unwrap();
+        let follower = follower_pool(&file).await;
+        let sender = executor(&follower, SqlConfig::default()).await;
+        assert_eq!(applied_id(&follower).await.unwrap(), 0);
+        for batch in changes(&pool, applied_id(&follower).await.unwrap(), 100)
+            .await
+            .unwrap()
+        {
+            apply(&sender, batch).await.unwrap();
+        }
+
+        assert_eq!(
+            applied_id(&follower).await.unwrap(),
+            head(&pool).await.unwrap()
+        );
+        assert_eq!(tokens(&follower).await, tokens(&pool).await);
+        let balances = "SELECT id, balance FROM token_balances ORDER BY id";
+        let primary_balances: Vec<(String, String)> =
+            sqlx::query_as(balances).fetch_all(&pool).await.unwrap();
+        let follower_balances: Vec<(String, String)> =
+            sqlx::query_as(balances).fetch_all(&follower).await.unwrap();
+        assert_eq!(primary_balances.len(), 1);
+        assert_eq!(follower_balances, primary_balances);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn test_replay_resolved_values(pool: SqlitePool) {
+        // Without a token of the contract registered, the name and symbol would be read from the
+        // unreachable chain
+        let contract = Felt::from(0x721u64);
+        let contract_statement =
+            "INSERT INTO contracts (id, contract_address, contract_type) VALUES (?, ?, 'ERC721')";
+        let batch = ReplicationBatch {
+            id: 1,
+            queries: vec![
+                ReplicatedQuery {
+                    statement: contract_statement.to_string(),
+                    arguments: vec![
+                        Argument::FieldElement(contract),
+                        Argument::FieldElement(contract),
+                    ],
+                    query_type: QueryType::Other,
+                },
+                ReplicatedQuery {
+                    statement: String::new(),
+                    arguments: vec![],
+                    query_type: QueryType::RegisterNftToken(RegisterNftTokenQuery {
+                        contract_address: contract,
+                        token_id: U256::from(1u8),
+                        metadata: "{}".to_string(),
+                        name_and_symbol: Some(("Test".to_string(), "TST".to_string())),
+                    }),
+                },
+            ],
+        };
+        // As read back from the log of the primary
+        let batch: ReplicationBatch =
+            serde_json::from_str(&serde_json::to_string(&batch).unwrap()).unwrap();
+
+        let sender = executor(&pool, SqlConfig::default()).await;
+        apply(&sender, batch).await.unwrap();
+
+        let tokens = tokens(&pool).await;
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(
+            (tokens[0].1.as_str(), tokens[0].2.as_str()),
+            ("Test", "TST")
+        );
+        assert_eq!(applied_id(&pool).await.unwrap(), 1);
+    }
+}
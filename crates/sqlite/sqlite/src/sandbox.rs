@@ -0,0 +1,538 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use dashmap::DashMap;
+use futures::TryStreamExt;
+use sqlx::pool::PoolConnection;
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Decode, Row, Sqlite, SqlitePool, ValueRef};
+use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Statements that can change the database or its connection. The first keyword of a query
+/// must also be one of [`READ_KEYWORDS`], this catches the writes of CTEs, e.g.
+/// `WITH x AS (...) DELETE FROM ...`.
+const WRITE_KEYWORDS: [&str; 13] = [
+    "INSERT",
+    "UPDATE",
+    "DELETE",
+    "DROP",
+    "ALTER",
+    "CREATE",
+    "ATTACH",
+    "DETACH",
+    "PRAGMA",
+    "VACUUM",
+    "REINDEX",
+    "ANALYZE",
+    "LOAD_EXTENSION",
+];
+
+/// Keywords a read-only statement starts with.
+const READ_KEYWORDS: [&str; 3] = ["SELECT", "WITH", "VALUES"];
+
+/// Number of SQLite virtual machine instructions between two checks of the query deadline.
+const PROGRESS_HANDLER_OPS: i32 = 1000;
+
+/// Number of rows read ahead of the consumer of a query.
+const ROW_BUFFER_SIZE: usize = 256;
+
+#[derive(Debug, Clone)]
+pub struct SandboxConfig {
+    /// Maximum number of rows a query can return.
+    pub max_rows: usize,
+    /// Maximum size in bytes of the values a query can return.
+    pub max_bytes: usize,
+    /// Time after which a query is interrupted.
+    pub timeout: Duration,
+    /// Maximum number of rows a streamed query can return. The rows in memory are bounded by
+    /// the buffer of the stream rather than by a size, the consumer reading them as they come.
+    pub stream_max_rows: usize,
+    /// Time after which a streamed query is interrupted.
+    pub stream_timeout: Duration,
+    /// Maximum number of queries a client can run at the same time.
+    pub max_concurrent_queries: usize,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            max_rows: 10_000,
+            max_bytes: 16 * 1024 * 1024,
+            timeout: Duration::from_secs(5),
+            stream_max_rows: 10_000_000,
+            stream_timeout: Duration::from_secs(300),
+            max_concurrent_queries: 2,
+        }
+    }
+}
+
+impl SandboxConfig {
+    fn limits(&self) -> Limits {
+        Limits {
+            max_rows: self.max_rows,
+            max_bytes: Some(self.max_bytes),
+            timeout: self.timeout,
+        }
+    }
+
+    fn stream_limits(&self) -> Limits {
+        Limits {
+            max_rows: self.stream_max_rows,
+            max_bytes: None,
+            timeout: self.stream_timeout,
+        }
+    }
+}
+
+/// Limits of a single query.
+#[derive(Debug, Clone, Copy)]
+struct Limits {
+    max_rows: usize,
+    max_bytes: Option<usize>,
+    timeout: Duration,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SandboxError {
+    #[error("Only SELECT statements are allowed: {0}")]
+    NotReadOnly(String),
+    #[error("Only a single statement can be executed")]
+    MultipleStatements,
+    #[error("Empty query")]
+    EmptyQuery,
+    #[error("Query exceeded the {0:?} timeout")]
+    Timeout(Duration),
+    #[error("Query returned more than {0} rows, add a LIMIT")]
+    RowLimit(usize),
+    #[error("Query returned more than {0} bytes, select fewer columns or rows")]
+    ByteLimit(usize),
+    #[error("Too many concurrent queries, at most {0} per client")]
+    TooManyQueries(usize),
+    #[error("Query error: {0}")]
+    Query(#[from] sqlx::Error),
+}
+
+impl SandboxError {
+    /// Stable code of the error, for the clients to handle it.
+    pub fn code(&self) -> &'static str {
+        match self {
+            SandboxError::NotReadOnly(_) => "not_read_only",
+            SandboxError::MultipleStatements => "multiple_statements",
+            SandboxError::EmptyQuery => "empty_query",
+            SandboxError::Timeout(_) => "timeout",
+            SandboxError::RowLimit(_) => "row_limit",
+            SandboxError::ByteLimit(_) => "byte_limit",
+            SandboxError::TooManyQueries(_) => "too_many_queries",
+            SandboxError::Query(_) => "query_error",
+        }
+    }
+}
+
+/// Runs the raw SQL queries of the clients against the read-only pool, restricted to a single
+/// SELECT statement, and bounded in time, rows, bytes and concurrent queries per client.
+#[derive(Debug)]
+pub struct SqlSandbox {
+    pool: SqlitePool,
+    config: SandboxConfig,
+    clients: Arc<DashMap<String, Arc<Semaphore>>>,
+}
+
+impl SqlSandbox {
+    pub fn new(pool: SqlitePool, config: SandboxConfig) -> Self {
+        Self {
+            pool,
+            config,
+            clients: Arc::new(DashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &SandboxConfig {
+        &self.config
+    }
+
+    /// Executes the query of `client`, an identifier of whoever sends it, like its IP address.
+    pub async fn execute(&self, client: &str, query: &str) -> Result<Vec<SqliteRow>, SandboxError> {
+        let mut receiver = self.spawn_query(client, query, self.config.limits())?;
+
+        let mut rows = Vec::new();
+        while let Some(row) = receiver.recv().await {
+            rows.push(row?);
+        }
+
+        Ok(rows)
+    }
+
+    /// Executes the query of `client` and streams its rows as they are read, under the
+    /// streaming limits of the config: its rows and its timeout, not the size of its result. A
+    /// limit ends the stream with its error, after the rows sent so far. Dropping the receiver
+    /// stops the query.
+    pub fn execute_stream(
+        &self,
+        client: &str,
+        query: &str,
+    ) -> Result<Receiver<Result<SqliteRow, SandboxError>>, SandboxError> {
+        self.spawn_query(client, query, self.config.stream_limits())
+    }
+
+    fn spawn_query(
+        &self,
+        client: &str,
+        query: &str,
+        limits: Limits,
+    ) -> Result<Receiver<Result<SqliteRow, SandboxError>>, SandboxError> {
+        validate_statement(query)?;
+
+        let permit = self.acquire(client)?;
+        let (sender, receiver) = mpsc::channel(ROW_BUFFER_SIZE);
+
+        let pool = self.pool.clone();
+        let query = query.to_string();
+        let max_concurrent_queries = self.config.max_concurrent_queries;
+        let clients = self.clients.clone();
+        let client = client.to_string();
+        // The query runs in its own task, holding the permit of the client, so that it cleans its
+        // connection up even if the client goes away. The deadline bounds it anyway
+        tokio::spawn(async move {
+            let result = run_query(pool, &query, limits, &sender).await;
+
+            // The permit is released before the end of the stream is seen, so that the client
+            // can run its next query right away
+            drop(permit);
+            clients.remove_if(&client, |_, semaphore| {
+                Arc::strong_count(semaphore) == 1
+                    && semaphore.available_permits() == max_concurrent_queries
+            });
+
+            if let Err(e) = result {
+                let _ = sender.send(Err(e)).await;
+            }
+        });
+
+        Ok(receiver)
+    }
+
+    fn acquire(&self, client: &str) -> Result<OwnedSemaphorePermit, SandboxError> {
+        let semaphore = self
+            .clients
+            .entry(client.to_string())
+            .or_insert_with(|| Arc::new(Semaphore::new(self.config.max_concurrent_queries)))
+            .clone();
+
+        semaphore
+            .try_acquire_owned()
+            .map_err(|_| SandboxError::TooManyQueries(self.config.max_concurrent_queries))
+    }
+}
+
+async fn run_query(
+    pool: SqlitePool,
+    query: &str,
+    limits: Limits,
+    sender: &Sender<Result<SqliteRow, SandboxError>>,
+) -> Result<(), SandboxError> {
+    let deadline = Instant::now() + limits.timeout;
+    let mut conn = ProgressHandlerGuard::install(pool.acquire().await?, deadline).await?;
+
+    let result = async {
+        let mut stream = sqlx::query(query).fetch(&mut *conn.conn);
+        let mut count = 0;
+        let mut bytes = 0;
+        while let Some(row) = stream.try_next().await? {
+            count += 1;
+            if count > limits.max_rows {
+                return Err(SandboxError::RowLimit(limits.max_rows));
+            }
+            if let Some(max_bytes) = limits.max_bytes {
+                bytes += row_size(&row);
+                if bytes > max_bytes {
+                    return Err(SandboxError::ByteLimit(max_bytes));
+                }
+            }
+
+            // The receiver is gone, nobody wants the remaining rows
+            if sender.send(Ok(row)).await.is_err() {
+                break;
+            }
+        }
+        Ok(())
+    }
+    .await;
+
+    // The connection goes back to the pool without the deadline of this query, or is closed
+    conn.remove().await;
+
+    match result {
+        // SQLite reports the interruption of the progress handler as a database error
+        Err(SandboxError::Query(sqlx::Error::Database(_))) if Instant::now() >= deadline => {
+            Err(SandboxError::Timeout(limits.timeout))
+        }
+        result => result,
+    }
+}
+
+/// Pool connection with a progress handler interrupting its queries after a deadline. The
+/// connection must not go back to the pool with the handler, so it is closed on drop unless the
+/// handler could be removed.
+struct ProgressHandlerGuard {
+    conn: PoolConnection<Sqlite>,
+    installed: bool,
+}
+
+impl ProgressHandlerGuard {
+    async fn install(
+        mut conn: PoolConnection<Sqlite>,
+        deadline: Instant,
+    ) -> Result<Self, sqlx::Error> {
+        conn.lock_handle()
+            .await?
+            .set_progress_handler(PROGRESS_HANDLER_OPS, move || Instant::now() < deadline);
+
+        Ok(Self {
+            conn,
+            installed: true,
+        })
+    }
+
+    async fn remove(&mut self) {
+        if let Ok(mut handle) = self.conn.lock_handle().await {
+            handle.remove_progress_handler();
+            self.installed = false;
+        }
+    }
+}
+
+impl Drop for ProgressHandlerGuard {
+    fn drop(&mut self) {
+        if self.installed {
+            self.conn.close_on_drop();
+        }
+    }
+}
+
+/// Size of the values of a row, as stored by SQLite.
+fn row_size(row: &SqliteRow) -> usize {
+    (0..row.len())
+        .filter_map(|i| row.try_get_raw(i).ok())
+        .filter(|value| !value.is_null())
+        .map(|value| <&[u8] as Decode<Sqlite>>::decode(value).map_or(0, |bytes| bytes.len()))
+        .sum()
+}
+
+/// Checks that the query is a single read-only statement. Comments, strings and quoted
+/// identifiers are skipped, so that they can contain any keyword.
+pub fn validate_statement(query: &str) -> Result<(), SandboxError> {
+    let mut keywords = Vec::new();
+    let mut statement_ended = false;
+
+    let mut chars = query.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '-' if chars.peek() == Some(&'-') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = None;
+                for c in chars.by_ref() {
+                    if previous == Some('*') && c == '/' {
+                        break;
+                    }
+                    previous = Some(c);
+                }
+            }
+            // an escaped quote ends the quoted text and starts a new one
+            '\'' | '"' | '`' | '[' => {
+                let end = if c == '[' { ']' } else { c };
+                for c in chars.by_ref() {
+                    if c == end {
+                        break;
+                    }
+                }
+            }
+            ';' => {
+                statement_ended = true;
+                continue;
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut word = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_' || c == '$') {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                keywords.push(word.to_ascii_uppercase());
+            }
+            _ => {}
+        }
+
+        if statement_ended {
+            return Err(SandboxError::MultipleStatements);
+        }
+    }
+
+    let Some(first) = keywords.first() else {
+        return Err(SandboxError::EmptyQuery);
+    };
+    if !READ_KEYWORDS.contains(&first.as_str()) {
+        return Err(SandboxError::NotReadOnly(first.clone()));
+    }
+    if let Some(keyword) = keywords
+        .iter()
+        .find(|keyword| WRITE_KEYWORDS.contains(&keyword.as_str()))
+    {
+        return Err(SandboxError::NotReadOnly(keyword.clone()));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use sqlx::SqlitePool;
+
+    use super::{validate_statement, SandboxConfig, SandboxError, SqlSandbox};
+
+    #[test]
+    fn test_validate_statement() {
+        assert!(validate_statement("SELECT * FROM entities").is_ok());
+        assert!(validate_statement("  select 1; ").is_ok());
+        assert!(validate_statement("WITH t AS (SELECT 1) SELECT * FROM t").is_ok());
+        // keywords in strings, identifiers and comments are fine
+        assert!(validate_statement("SELECT 'DROP TABLE x;' AS [delete] -- DELETE").is_ok());
+        assert!(validate_statement("SELECT /* ; UPDATE */ \"insert\" FROM t").is_ok());
+
+        assert!(matches!(
+            validate_statement("DELETE FROM entities"),
+            Err(SandboxError::NotReadOnly(_))
+        ));
+        assert!(matches!(
+            validate_statement("WITH t AS (SELECT 1) DELETE FROM entities"),
+            Err(SandboxError::NotReadOnly(_))
+        ));
+        assert!(matches!(
+            validate_statement("SELECT load_extension('evil')"),
+            Err(SandboxError::NotReadOnly(_))
+        ));
+        assert!(matches!(
+            validate_statement("SELECT 1; DROP TABLE entities"),
+            Err(SandboxError::MultipleStatements)
+        ));
+        assert!(matches!(
+            validate_statement(" -- nothing"),
+            Err(SandboxError::EmptyQuery)
+        ));
+    }
+
+    #[sqlx::test]
+    async fn test_sandbox_limits(pool: SqlitePool) {
+        let sandbox = SqlSandbox::new(
+            pool,
+            SandboxConfig {
+                max_rows: 10,
+                max_bytes: 100,
+                timeout: Duration::from_millis(100),
+                stream_max_rows: 20,
+                stream_timeout: Duration::from_millis(100),
+                max_concurrent_queries: 1,
+            },
+        );
+
+        let rows = sandbox
+            .execute("client", "SELECT 1 UNION SELECT 2")
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 2);
+
+        let counter = "WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c";
+        assert!(matches!(
+            sandbox
+                .execute("client", &format!("{counter} LIMIT 11) SELECT x FROM c"))
+                .await,
+            Err(SandboxError::RowLimit(10))
+        ));
+        assert!(matches!(
+            sandbox
+                .execute("client", "SELECT printf('%.200c', 'x')")
+                .await,
+            Err(SandboxError::ByteLimit(100))
+        ));
+        // counting forever is interrupted
+        assert!(matches!(
+            sandbox
+                .execute("client", &format!("{counter}) SELECT count(*) FROM c"))
+                .await,
+            Err(SandboxError::Timeout(_))
+        ));
+    }
+
+    #[sqlx::test]
+    async fn test_sandbox_stream_limits(pool: SqlitePool) {
+        let sandbox = SqlSandbox::new(
+            pool,
+            SandboxConfig {
+                max_rows: 10,
+                max_bytes: 100,
+                timeout: Duration::from_millis(100),
+                stream_max_rows: 20,
+                stream_timeout: Duration::from_millis(100),
+                max_concurrent_queries: 1,
+            },
+        );
+        let counter = "WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c";
+
+        // the rows before the limit are streamed, then its error
+        let mut receiver = sandbox
+            .execute_stream("client", &format!("{counter} LIMIT 21) SELECT x FROM c"))
+            .unwrap();
+        let mut count = 0;
+        let error = loop {
+            match receiver.recv().await {
+                Some(Ok(_)) => count += 1,
+                Some(Err(e)) => break Some(e),
+                None => break None,
+            }
+        };
+        assert_eq!(count, 20);
+        assert!(matches!(error, Some(SandboxError::RowLimit(20))));
+
+        // the limits of the buffered queries don't apply, nor does any size
+        let mut receiver = sandbox
+            .execute_stream(
+                "client",
+                &format!("{counter} LIMIT 15) SELECT printf('%.200c', 'x') FROM c"),
+            )
+            .unwrap();
+        let mut count = 0;
+        while let Some(row) = receiver.recv().await {
+            row.unwrap();
+            count += 1;
+        }
+        assert_eq!(count, 15);
+
+        // the query holds the permit of its client until its deadline
+        let mut receiver = sandbox
+            .execute_stream("client", &format!("{counter}) SELECT count(*) FROM c"))
+            .unwrap();
+        assert!(matches!(
+            sandbox.execute("client", "SELECT 1").await,
+            Err(SandboxError::TooManyQueries(1))
+        ));
+        assert!(matches!(
+            receiver.recv().await,
+            Some(Err(SandboxError::Timeout(_)))
+        ));
+        // and releases it before its end is seen
+        let rows = sandbox.execute("client", "SELECT 1").await.unwrap();
+        assert_eq!(rows.len(), 1);
+    }
+}
@@ -0,0 +1,244 @@
+//! Snapshots of a database, to bootstrap replicas from.
+//!
+//! A snapshot is a gzipped copy of the database taken with `VACUUM INTO`, which is consistent and
+//! doesn't block the writer, along with a manifest describing it.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use chrono::Utc;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::SqlitePool;
+
+use crate::maintenance::{self, ContractCursor};
+
+/// Suffix of the manifest of a snapshot file, `<file>.manifest.json`.
+pub const MANIFEST_SUFFIX: &str = ".manifest.json";
+
+#[derive(Debug, thiserror::Error)]
+pub enum SnapshotError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Sql(#[from] sqlx::Error),
+    #[error(transparent)]
+    Manifest(#[from] serde_json::Error),
+    #[error("Checksum mismatch, expected {expected} got {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+    #[error("Size mismatch, expected {expected} bytes got {actual}")]
+    SizeMismatch { expected: u64, actual: u64 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    /// Version spec of the Torii which took the snapshot.
+    pub version: String,
+    pub created_at: String,
+    /// Name of the snapshot file, next to the manifest.
+    pub file: String,
+    pub compression: String,
+    /// Size and SHA-256 of the snapshot file, as downloaded.
+    pub size_bytes: u64,
+    pub sha256: String,
+    /// Cursors of the contracts in the snapshot, where a replica resumes indexing from.
+    pub cursors: Vec<ContractCursor>,
+}
+
+impl SnapshotManifest {
+    /// Path of the manifest of a snapshot file.
+    pub fn path(snapshot: &Path) -> PathBuf {
+        let mut path = snapshot.as_os_str().to_owned();
+        path.push(MANIFEST_SUFFIX);
+        PathBuf::from(path)
+    }
+
+    pub fn read(path: &Path) -> Result<Self, SnapshotError> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+
+    /// Checks the size and checksum of a downloaded snapshot file.
+    pub fn verify(&self, snapshot: &Path) -> Result<(), SnapshotError> {
+        let (size_bytes, sha256) = digest(snapshot)?;
+        if size_bytes != self.size_bytes {
+            return Err(SnapshotError::SizeMismatch {
+                expected: self.size_bytes,
+                actual: size_bytes,
+            });
+        }
+        if sha256 != self.sha256 {
+            return Err(SnapshotError::ChecksumMismatch {
+                expected: self.sha256.clone(),
+                actual: sha256,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Takes a snapshot of the database of `pool` into `output_dir`, as `torii-<timestamp>.db.gz`
+/// with its manifest. Safe to run while Torii indexes.
+pub async fn create(
+    pool: &SqlitePool,
+    output_dir: &Path,
+    version: &str,
+) -> Result<(PathBuf, SnapshotManifest), SnapshotError> {
+    tokio::fs::create_dir_all(output_dir).await?;
+
+    let created_at = Utc::now();
+    let name = format!("torii-{}.db", created_at.format("%Y%m%dT%H%M%SZ"));
+    let copy = output_dir.join(format!(".{}", name));
+    let snapshot = output_dir.join(format!("{}.gz", name));
+
+    maintenance::export(pool, &copy).await?;
+
+    let result = async {
+        // The cursors of the copy are the ones matching its data
+        let copy_pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(SqliteConnectOptions::from_str(&copy.to_string_lossy())?.read_only(true))
+            .await?;
+        let cursors = maintenance::info(&copy_pool).await?.contracts;
+        copy_pool.close().await;
+
+        let (copy_path, snapshot_path) = (copy.clone(), snapshot.clone());
+        tokio::task::spawn_blocking(move || compress(&copy_path, &snapshot_path))
+            .await
+            .map_err(std::io::Error::other)??;
+
+        let snapshot_path = snapshot.clone();
+        let (size_bytes, sha256) = tokio::task::spawn_blocking(move || digest(&snapshot_path))
+            .await
+            .map_err(std::io::Error::other)??;
+
+        Ok::<_, SnapshotError>(SnapshotManifest {
+            version: version.to_string(),
+            created_at: created_at.to_rfc3339(),
+            file: format!("{}.gz", name),
+            compression: "gzip".to_string(),
+            size_bytes,
+            sha256,
+            cursors,
+        })
+    }
+    .await;
+    let _ = tokio::fs::remove_file(&copy).await;
+    let manifest = result?;
+
+    // Written last, a manifest is only ever next to a complete snapshot
+    tokio::fs::write(
+        SnapshotManifest::path(&snapshot),
+        serde_json::to_vec_pretty(&manifest)?,
+    )
+    .await?;
+
+    Ok((snapshot, manifest))
+}
+
+/// Manifests of the snapshots of a directory, the most recent first.
+pub fn list(dir: &Path) -> Result<Vec<SnapshotManifest>, SnapshotError> {
+    let mut manifests = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.to_string_lossy().ends_with(MANIFEST_SUFFIX) {
+            manifests.push(SnapshotManifest::read(&path)?);
+        }
+    }
+    manifests.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+
+    Ok(manifests)
+}
+
+/// Writes the database of a gzipped snapshot to `destination`.
+pub fn decompress(snapshot: &Path, destination: &Path) -> Result<(), SnapshotError> {
+    let mut decoder = GzDecoder::new(BufReader::new(File::open(snapshot)?));
+    let mut output = BufWriter::new(File::create(destination)?);
+    std::io::copy(&mut decoder, &mut output)?;
+    Ok(())
+}
+
+fn compress(source: &Path, destination: &Path) -> Result<(), SnapshotError> {
+    let mut input = BufReader::new(File::open(source)?);
+    let mut encoder = GzEncoder::new(
+        BufWriter::new(File::create(destination)?),
+        Compression::default(),
+    );
+    std::io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?.into_inner().map_err(|e| e.into_error())?;
+    Ok(())
+}
+
+fn digest(path: &Path) -> Result<(u64, String), SnapshotError> {
+    let mut file = BufReader::new(File::open(path)?);
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    let mut size = 0u64;
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        size += read as u64;
+    }
+
+    let sha256 = hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    Ok((size, sha256))
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::SqlitePool;
+
+    use super::*;
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn test_snapshot_roundtrip(pool: SqlitePool) {
+        sqlx::query(
+            "INSERT INTO contracts (id, contract_address, contract_type, head) VALUES ('0x1', \
+             '0x1', 'WORLD', 42)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let (snapshot, manifest) = create(&pool, dir.path(), "1.0.0").await.unwrap();
+        assert_eq!(manifest.cursors.len(), 1);
+        assert_eq!(manifest.cursors[0].head, Some(42));
+        assert_eq!(
+            SnapshotManifest::read(&SnapshotManifest::path(&snapshot)).unwrap(),
+            manifest
+        );
+        assert_eq!(list(dir.path()).unwrap(), vec![manifest.clone()]);
+        manifest.verify(&snapshot).unwrap();
+
+        let database = dir.path().join("restored.db");
+        decompress(&snapshot, &database).unwrap();
+        let restored = SqlitePool::connect(&database.to_string_lossy())
+            .await
+            .unwrap();
+        let head: i64 = sqlx::query_scalar("SELECT head FROM contracts")
+            .fetch_one(&restored)
+            .await
+            .unwrap();
+        assert_eq!(head, 42);
+
+        std::fs::write(&snapshot, b"tampered").unwrap();
+        assert!(matches!(
+            manifest.verify(&snapshot),
+            Err(SnapshotError::SizeMismatch { .. })
+        ));
+    }
+}
@@ -14,9 +14,11 @@ use torii_math::I256;
 use torii_proto::{
     schema::Entity, Activity, ActivityQuery, AggregationEntry, AggregationQuery, BalanceId,
     CallType, Clause, CompositeClause, Contract, ContractCursor, ContractQuery, Controller,
-    ControllerQuery, Event, EventQuery, LogicalOperator, Model, OrderBy, OrderDirection, Page,
-    Query, SearchMatch, SearchQuery, SearchResponse, TableSearchResults, Token, TokenBalance,
-    TokenBalanceQuery, TokenContract, TokenContractQuery, TokenId, TokenQuery, TokenTransfer,
+    ControllerQuery, EntityAggregate, EntityAggregateQuery, Event, EventQuery, LogicalOperator,
+    MetadataJob, MetadataJobQuery, MetadataJobStatus, Model, OrderBy, OrderDirection, Page, Query,
+    SearchMatch, SearchQuery, SearchResponse, TableSearchResults, Token, TokenBalance,
+    TokenBalanceQuery, TokenContract, TokenContractQuery, TokenHolder, TokenHolderCountPoint,
+    TokenHolderStats, TokenHolderStatsQuery, TokenId, TokenQuery, TokenTransfer,
     TokenTransferQuery, Transaction, TransactionCall, TransactionQuery,
 };
 use torii_sqlite_types::{HookEvent, Model as SQLModel};
@@ -25,17 +27,23 @@ use tracing::warn;
 
 use crate::{
     constants::{
-        ENTITIES_ENTITY_RELATION_COLUMN, ENTITIES_HISTORICAL_TABLE, ENTITIES_MODEL_RELATION_TABLE,
-        ENTITIES_TABLE, EVENT_MESSAGES_ENTITY_RELATION_COLUMN, EVENT_MESSAGES_HISTORICAL_TABLE,
-        EVENT_MESSAGES_MODEL_RELATION_TABLE, EVENT_MESSAGES_TABLE, TOKEN_TRANSFER_TABLE,
+        CONTRACTS_TABLE, ENTITIES_ENTITY_RELATION_COLUMN, ENTITIES_HISTORICAL_TABLE,
+        ENTITIES_MODEL_RELATION_TABLE, ENTITIES_TABLE, EVENT_MESSAGES_ENTITY_RELATION_COLUMN,
+        EVENT_MESSAGES_HISTORICAL_TABLE, EVENT_MESSAGES_MODEL_RELATION_TABLE, EVENT_MESSAGES_TABLE,
+        SEARCH_INDEX_POPULATE_QUERIES, SEARCH_INDEX_TABLE, TOKENS_TABLE, TOKEN_HOLDERS_TABLE,
+        TOKEN_HOLDER_COUNTS_TABLE, TOKEN_HOLDER_COUNT_HISTORY_TABLE, TOKEN_METADATA_JOBS_TABLE,
+        TOKEN_TRANSFER_TABLE,
     },
     executor::{erc::UpdateTokenMetadataQuery, RegisterNftTokenQuery, RegisterTokenContractQuery},
     model::map_row_to_ty,
     query::{PaginationExecutor, QueryBuilder},
-    utils::{build_keys_pattern, u256_to_sql_string},
+    utils::{
+        build_keys_pattern, gini_coefficient, sql_string_to_u256, u256_to_crypto_bigint,
+        u256_to_f64, u256_to_sql_string,
+    },
 };
 use crate::{
-    error::{Error, ParseError},
+    error::{Error, ParseError, QueryError},
     executor::{
         error::ExecutorQueryError, ApplyBalanceDiffQuery, Argument, DeleteEntityQuery, EntityQuery,
         EventMessageQuery, QueryMessage, QueryType, StoreTransactionQuery, UpdateCursorsQuery,
@@ -46,6 +54,15 @@ use crate::{
 
 pub const LOG_TARGET: &str = "torii::sqlite::storage";
 
+/// Number of top holders returned per contract when the query doesn't specify a limit.
+const DEFAULT_TOP_HOLDERS_LIMIT: u32 = 10;
+/// Maximum number of top holders that can be returned per contract.
+const MAX_TOP_HOLDERS_LIMIT: u32 = 1000;
+/// Number of metadata jobs returned when the query doesn't specify a limit.
+const DEFAULT_METADATA_JOBS_LIMIT: u32 = 100;
+/// Maximum number of metadata jobs that can be returned at once.
+const MAX_METADATA_JOBS_LIMIT: u32 = 1000;
+
 #[async_trait]
 impl ReadOnlyStorage for Sql {
     fn as_read_only(&self) -> &dyn ReadOnlyStorage {
@@ -398,6 +415,203 @@ impl ReadOnlyStorage for Sql {
         })
     }
 
+    /// Returns holder statistics for each of the queried token contracts.
+    async fn token_holder_stats(
+        &self,
+        query: &TokenHolderStatsQuery,
+    ) -> Result<Vec<TokenHolderStats>, StorageError> {
+        if query.contract_addresses.is_empty() {
+            return Err(
+                Error::Query(QueryError::MissingParam("contract_addresses".to_string())).into(),
+            );
+        }
+
+        let top_holders_limit = match query.top_holders_limit {
+            0 => DEFAULT_TOP_HOLDERS_LIMIT,
+            limit => limit.min(MAX_TOP_HOLDERS_LIMIT),
+        } as usize;
+
+        let mut stats = Vec::with_capacity(query.contract_addresses.len());
+        for contract_address in &query.contract_addresses {
+            let contract_id = felt_to_sql_string(contract_address);
+
+            // The holdings and their totals are maintained by the executor as balances change
+            let totals: Option<(i64, String)> = sqlx::query_as(&format!(
+                "SELECT holder_count, total_held FROM {TOKEN_HOLDER_COUNTS_TABLE} WHERE \
+                 contract_address = ?"
+            ))
+            .bind(&contract_id)
+            .fetch_optional(&self.pool)
+            .await?;
+            let (holder_count, total_held) = totals
+                .map_or((0, U256::from(0u8)), |(holder_count, total_held)| {
+                    (holder_count as u64, sql_string_to_u256(&total_held))
+                });
+            let total_held_f64 = u256_to_f64(&total_held);
+            let share_of = |balance: &U256| {
+                if total_held_f64 > 0.0 {
+                    u256_to_f64(balance) / total_held_f64
+                } else {
+                    0.0
+                }
+            };
+
+            let top_holders = sqlx::query_as::<_, (String, String, i64)>(&format!(
+                "SELECT account_address, balance, token_count FROM {TOKEN_HOLDERS_TABLE} WHERE \
+                 contract_address = ? ORDER BY balance DESC, account_address ASC LIMIT ?"
+            ))
+            .bind(&contract_id)
+            .bind(top_holders_limit as i64)
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|(account_address, balance, token_count)| {
+                let balance = sql_string_to_u256(&balance);
+                Ok(TokenHolder {
+                    account_address: Felt::from_str(&account_address)
+                        .map_err(ParseError::FromStr)?,
+                    balance: u256_to_crypto_bigint(&balance),
+                    token_count: token_count as u32,
+                    share: share_of(&balance),
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+            let top_holders_share = top_holders.iter().map(|h| h.share).sum();
+
+            // Rank-weighted sum of the holdings in ascending order, for the gini coefficient
+            let (count, total, weighted_sum): (i64, f64, f64) = sqlx::query_as(&format!(
+                "SELECT COUNT(*), COALESCE(SUM(amount), 0.0), COALESCE(SUM(rank * amount), 0.0) \
+                 FROM (SELECT amount, ROW_NUMBER() OVER (ORDER BY balance ASC) AS rank FROM \
+                 {TOKEN_HOLDERS_TABLE} WHERE contract_address = ?)"
+            ))
+            .bind(&contract_id)
+            .fetch_one(&self.pool)
+            .await?;
+            let gini = gini_coefficient(count as u64, total, weighted_sum);
+
+            let total_supply: Option<Option<String>> = sqlx::query_scalar(&format!(
+                "SELECT total_supply FROM {TOKENS_TABLE} WHERE id = ?"
+            ))
+            .bind(&contract_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+            let holder_count_history_sql = format!(
+                "SELECT timestamp, holder_count FROM {TOKEN_HOLDER_COUNT_HISTORY_TABLE} WHERE \
+                 contract_address = ? AND timestamp >= ? AND timestamp <= ? ORDER BY timestamp ASC"
+            );
+            let holder_count_history = sqlx::query_as::<_, (i64, i64)>(&holder_count_history_sql)
+                .bind(&contract_id)
+                .bind(query.history_from.map_or(0, |from| from as i64))
+                .bind(query.history_to.map_or(i64::MAX, |to| to as i64))
+                .fetch_all(&self.pool)
+                .await?
+                .into_iter()
+                .map(|(timestamp, holder_count)| TokenHolderCountPoint {
+                    timestamp: timestamp as u64,
+                    holder_count: holder_count as u64,
+                })
+                .collect();
+
+            stats.push(TokenHolderStats {
+                contract_address: *contract_address,
+                holder_count,
+                total_supply: total_supply
+                    .flatten()
+                    .filter(|supply| !supply.is_empty())
+                    .map(|supply| u256_to_crypto_bigint(&sql_string_to_u256(&supply))),
+                total_held: u256_to_crypto_bigint(&total_held),
+                top_holders,
+                top_holders_share,
+                gini,
+                holder_count_history,
+            });
+        }
+
+        Ok(stats)
+    }
+
+    async fn metadata_jobs(
+        &self,
+        query: &MetadataJobQuery,
+    ) -> Result<Page<MetadataJob>, StorageError> {
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_METADATA_JOBS_LIMIT)
+            .clamp(1, MAX_METADATA_JOBS_LIMIT);
+
+        let mut conditions = Vec::new();
+        let mut bind_values = Vec::new();
+
+        if !query.contract_addresses.is_empty() {
+            conditions.push(format!(
+                "contract_address IN ({})",
+                vec!["?"; query.contract_addresses.len()].join(", ")
+            ));
+            bind_values.extend(query.contract_addresses.iter().map(felt_to_sql_string));
+        }
+
+        if !query.statuses.is_empty() {
+            conditions.push(format!(
+                "status IN ({})",
+                vec!["?"; query.statuses.len()].join(", ")
+            ));
+            bind_values.extend(query.statuses.iter().map(|s| s.as_str().to_string()));
+        }
+
+        if let Some(cursor) = &query.cursor {
+            conditions.push("id > ?".to_string());
+            bind_values.push(cursor.clone());
+        }
+
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+
+        // Fetch one extra row to know whether there is a next page
+        let sql = format!(
+            "SELECT id, contract_address, token_id, status, attempts, last_error, retry_at, \
+             updated_at FROM {TOKEN_METADATA_JOBS_TABLE} {where_clause} ORDER BY id LIMIT ?"
+        );
+        let mut db_query = sqlx::query_as::<_, torii_sqlite_types::MetadataJob>(&sql);
+        for value in bind_values {
+            db_query = db_query.bind(value);
+        }
+        let mut jobs = db_query
+            .bind(limit as i64 + 1)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let next_cursor = if jobs.len() > limit as usize {
+            jobs.truncate(limit as usize);
+            jobs.last().map(|job| job.id.clone())
+        } else {
+            None
+        };
+
+        Ok(Page {
+            items: jobs.into_iter().map(Into::into).collect(),
+            next_cursor,
+        })
+    }
+
+    async fn due_metadata_jobs(&self, limit: u32) -> Result<Vec<MetadataJob>, StorageError> {
+        let jobs = sqlx::query_as::<_, torii_sqlite_types::MetadataJob>(&format!(
+            "SELECT id, contract_address, token_id, status, attempts, last_error, retry_at, \
+             updated_at FROM {TOKEN_METADATA_JOBS_TABLE} WHERE status = 'pending' OR \
+             (status = 'failed' AND retry_at IS NOT NULL AND retry_at <= ?) \
+             ORDER BY updated_at LIMIT ?"
+        ))
+        .bind(Utc::now().timestamp())
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(jobs.into_iter().map(Into::into).collect())
+    }
+
     async fn token_contracts(
         &self,
         query: &TokenContractQuery,
@@ -781,6 +995,14 @@ impl ReadOnlyStorage for Sql {
         Ok(page)
     }
 
+    /// Aggregates the entities of a model from the storage.
+    async fn aggregate_entities(
+        &self,
+        query: &EntityAggregateQuery,
+    ) -> Result<Vec<EntityAggregate>, StorageError> {
+        Ok(self.aggregate_entities_by_composite(query).await?)
+    }
+
     /// Queries the event messages from the storage.
     async fn event_messages(&self, query: &Query) -> Result<Page<Entity>, StorageError> {
         // Map other clauses to a composite clause
@@ -851,21 +1073,27 @@ impl ReadOnlyStorage for Sql {
     ) -> Result<Page<AggregationEntry>, StorageError> {
         let executor = PaginationExecutor::new(self.pool.clone());
 
-        // Use window function to calculate positions on-the-fly
-        let mut query_builder = QueryBuilder::new("aggregations").alias("a").select(&[
-            "a.id".to_string(),
-            "a.aggregator_id".to_string(),
-            "a.entity_id".to_string(),
-            "a.value".to_string(),
-            "a.display_value".to_string(),
-            "a.model_id".to_string(),
-            "a.created_at".to_string(),
-            "a.updated_at".to_string(),
-            // Calculate position using ROW_NUMBER() window function
+        // Use window function to calculate positions on-the-fly. They are ranked in a joined table,
+        // so that they are the positions among all the entries, and cast for the cursor
+        // conditions to compare them as integers.
+        let mut query_builder = QueryBuilder::new("aggregations")
+            .alias("a")
+            .select(&[
+                "a.id".to_string(),
+                "a.aggregator_id".to_string(),
+                "a.entity_id".to_string(),
+                "a.value".to_string(),
+                "a.display_value".to_string(),
+                "a.model_id".to_string(),
+                "a.created_at".to_string(),
+                "a.updated_at".to_string(),
+                "r.position as position".to_string(),
+            ])
             // Partitioned by aggregator_id and ordered by value DESC
-            "ROW_NUMBER() OVER (PARTITION BY a.aggregator_id ORDER BY a.value DESC) as position"
-                .to_string(),
-        ]);
+            .join(
+                "JOIN (SELECT id, CAST(ROW_NUMBER() OVER (PARTITION BY aggregator_id ORDER BY \
+                 value DESC) AS INTEGER) AS position FROM aggregations) r ON r.id = a.id",
+            );
 
         if !query.aggregator_ids.is_empty() {
             let placeholders = vec!["?"; query.aggregator_ids.len()].join(", ");
@@ -1844,6 +2072,20 @@ impl Storage for Sql {
         Ok(())
     }
 
+    /// Unregisters a contract from the storage, which stops its indexing.
+    async fn unregister_contract(&self, address: Felt) -> Result<(), StorageError> {
+        self.executor
+            .send(QueryMessage::other(
+                format!("DELETE FROM {CONTRACTS_TABLE} WHERE id = ?"),
+                vec![Argument::FieldElement(address)],
+            ))
+            .map_err(|e| {
+                Error::ExecutorQuery(Box::new(ExecutorQueryError::SendError(Box::new(e))))
+            })?;
+
+        Ok(())
+    }
+
     /// Sets an entity with the storage.
     /// It should insert or update the entity if it already exists.
     /// Along with its model state in the model table.
@@ -2307,6 +2549,7 @@ impl Storage for Sql {
                     contract_address,
                     token_id,
                     metadata,
+                    name_and_symbol: None,
                 }),
             ))
             .map_err(|e| {
@@ -2379,6 +2622,120 @@ impl Storage for Sql {
         Ok(())
     }
 
+    /// Queues a metadata refresh for each of the tokens.
+    async fn enqueue_metadata_jobs(&self, token_ids: Vec<TokenId>) -> Result<(), StorageError> {
+        let statement = format!(
+            "INSERT INTO {TOKEN_METADATA_JOBS_TABLE} (id, contract_address, token_id, status, \
+             attempts) VALUES (?, ?, ?, 'pending', 0) ON CONFLICT(id) DO UPDATE SET status = \
+             'pending', attempts = 0, last_error = NULL, retry_at = NULL, updated_at = \
+             CURRENT_TIMESTAMP"
+        );
+
+        for token_id in token_ids {
+            self.executor
+                .send(QueryMessage::other(
+                    statement.clone(),
+                    vec![
+                        Argument::String(token_id.to_string()),
+                        Argument::FieldElement(token_id.contract_address()),
+                        token_id
+                            .token_id()
+                            .map(|id| Argument::String(u256_to_sql_string(&id)))
+                            .unwrap_or(Argument::Null),
+                    ],
+                ))
+                .map_err(|e| {
+                    Error::ExecutorQuery(Box::new(ExecutorQueryError::SendError(Box::new(e))))
+                })?;
+        }
+
+        Ok(())
+    }
+
+    /// Queues a metadata refresh for a token contract and all of its registered tokens.
+    async fn enqueue_contract_metadata_jobs(
+        &self,
+        contract_address: Felt,
+    ) -> Result<(), StorageError> {
+        self.executor
+            .send(QueryMessage::other(
+                format!(
+                    "INSERT INTO {TOKEN_METADATA_JOBS_TABLE} (id, contract_address, token_id, \
+                     status, attempts) SELECT id, contract_address, NULLIF(token_id, ''), \
+                     'pending', 0 FROM {TOKENS_TABLE} WHERE contract_address = ? ON \
+                     CONFLICT(id) DO UPDATE SET status = 'pending', attempts = 0, last_error = \
+                     NULL, retry_at = NULL, updated_at = CURRENT_TIMESTAMP"
+                ),
+                vec![Argument::FieldElement(contract_address)],
+            ))
+            .map_err(|e| {
+                Error::ExecutorQuery(Box::new(ExecutorQueryError::SendError(Box::new(e))))
+            })?;
+
+        Ok(())
+    }
+
+    /// Rebuilds the full-text search index from the searchable data.
+    async fn rebuild_search_index(&self) -> Result<(), StorageError> {
+        let queries = std::iter::once(format!("DELETE FROM {SEARCH_INDEX_TABLE}"))
+            .chain(SEARCH_INDEX_POPULATE_QUERIES.map(str::to_string));
+        for query in queries {
+            self.executor
+                .send(QueryMessage::other(query, vec![]))
+                .map_err(|e| {
+                    Error::ExecutorQuery(Box::new(ExecutorQueryError::SendError(Box::new(e))))
+                })?;
+        }
+
+        Ok(())
+    }
+
+    /// Records the outcome of a metadata refresh.
+    async fn set_metadata_job_status(
+        &self,
+        token_id: TokenId,
+        status: MetadataJobStatus,
+        last_error: Option<String>,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> Result<(), StorageError> {
+        let attempts = if status == MetadataJobStatus::Failed {
+            1
+        } else {
+            0
+        };
+
+        self.executor
+            .send(QueryMessage::other(
+                format!(
+                    "INSERT INTO {TOKEN_METADATA_JOBS_TABLE} (id, contract_address, token_id, \
+                     status, attempts, last_error, retry_at) VALUES (?, ?, ?, ?, ?, ?, ?) ON \
+                     CONFLICT(id) DO UPDATE SET status = excluded.status, attempts = CASE WHEN \
+                     excluded.status = 'failed' THEN attempts + 1 ELSE 0 END, last_error = \
+                     excluded.last_error, retry_at = excluded.retry_at, updated_at = \
+                     CURRENT_TIMESTAMP"
+                ),
+                vec![
+                    Argument::String(token_id.to_string()),
+                    Argument::FieldElement(token_id.contract_address()),
+                    token_id
+                        .token_id()
+                        .map(|id| Argument::String(u256_to_sql_string(&id)))
+                        .unwrap_or(Argument::Null),
+                    Argument::String(status.as_str().to_string()),
+                    Argument::Int(attempts),
+                    last_error.map(Argument::String).unwrap_or(Argument::Null),
+                    retry_at
+                        .map(|t| Argument::Int(t.timestamp()))
+                        .unwrap_or(Argument::Null),
+                ],
+            ))
+            .map_err(|e| {
+                Error::ExecutorQuery(Box::new(ExecutorQueryError::SendError(Box::new(e))))
+            })?;
+
+        Ok(())
+    }
+
     /// Applies cached balance differences to the storage.
     async fn apply_balances_diff(
         &self,
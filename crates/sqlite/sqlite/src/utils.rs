@@ -45,6 +45,30 @@ pub fn sql_string_to_u256(sql_string: &str) -> U256 {
     U256::from(crypto_bigint::U256::from_be_hex(sql_string))
 }
 
+pub fn u256_to_crypto_bigint(u256: &U256) -> crypto_bigint::U256 {
+    let mut bytes = [0u8; 32];
+    bytes[..16].copy_from_slice(&u256.high().to_be_bytes());
+    bytes[16..].copy_from_slice(&u256.low().to_be_bytes());
+    crypto_bigint::U256::from_be_slice(&bytes)
+}
+
+/// Lossy conversion of a U256 to a f64, used for ratios and statistics.
+pub fn u256_to_f64(u256: &U256) -> f64 {
+    u256.high() as f64 * 2f64.powi(128) + u256.low() as f64
+}
+
+/// Computes the Gini coefficient of a distribution of `count` values summing to `total`, from
+/// the sum of the values weighted by their 1-based rank in ascending order.
+/// Returns 0 for a perfectly equal distribution and tends to 1 as it concentrates.
+pub fn gini_coefficient(count: u64, total: f64, weighted_sum: f64) -> f64 {
+    let n = count as f64;
+    if count == 0 || total <= 0.0 {
+        return 0.0;
+    }
+
+    ((2.0 * weighted_sum) / (n * total) - (n + 1.0) / n).max(0.0)
+}
+
 pub fn build_keys_pattern(clause: &torii_proto::KeysClause) -> String {
     const KEY_PATTERN: &str = "0x[0-9a-fA-F]+";
 
@@ -222,6 +246,34 @@ mod tests {
     use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
 
     use super::*;
+    use crate::constants::SEARCH_INDEX_POPULATE_QUERIES;
+
+    #[test]
+    fn test_gini_coefficient() {
+        assert_eq!(gini_coefficient(0, 0.0, 0.0), 0.0);
+        assert_eq!(gini_coefficient(2, 0.0, 0.0), 0.0);
+        // [5, 5, 5, 5]
+        assert!(gini_coefficient(4, 20.0, 50.0).abs() < 1e-9);
+
+        // A single holder owning everything among 4 holders, [0, 0, 0, 100]
+        let gini = gini_coefficient(4, 100.0, 400.0);
+        assert!((gini - 0.75).abs() < 1e-9);
+
+        // [1, 2, 3, 4]
+        let gini = gini_coefficient(4, 10.0, 30.0);
+        assert!((gini - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_u256_conversions() {
+        let value = U256::from_words(42, 1);
+        assert_eq!(u256_to_f64(&value), 2f64.powi(128) + 42.0);
+        assert_eq!(
+            U256::from(u256_to_crypto_bigint(&value)),
+            value,
+            "round trip through crypto_bigint"
+        );
+    }
 
     #[test]
     fn test_must_utc_datetime_from_timestamp() {
@@ -423,4 +475,32 @@ mod tests {
         let pattern = build_keys_pattern(&keys);
         assert_eq!(pattern, "^0x[0-9a-fA-F]+(/0x[0-9a-fA-F]+)*/$");
     }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn test_search_index_population(pool: sqlx::SqlitePool) {
+        sqlx::query(
+            "INSERT INTO controllers (id, username, address, deployed_at) VALUES ('alice', \
+             'alice', '0x1', CURRENT_TIMESTAMP)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("DELETE FROM search_index")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        for query in SEARCH_INDEX_POPULATE_QUERIES {
+            sqlx::query(query).execute(&pool).await.unwrap();
+        }
+
+        let (entity_type, entity_id): (String, String) = sqlx::query_as(
+            "SELECT entity_type, entity_id FROM search_index WHERE search_index MATCH 'alice'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(entity_type, "controller");
+        assert_eq!(entity_id, "alice");
+    }
 }
@@ -237,6 +237,44 @@ impl From<Token> for torii_proto::Token {
     }
 }
 
+#[derive(FromRow, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataJob {
+    pub id: String,
+    pub contract_address: String,
+    pub token_id: Option<String>,
+    pub status: String,
+    pub attempts: i64,
+    pub last_error: Option<String>,
+    pub retry_at: Option<i64>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<MetadataJob> for torii_proto::MetadataJob {
+    fn from(value: MetadataJob) -> Self {
+        let contract_address = Felt::from_str(&value.contract_address).unwrap();
+        let token_id = match value.token_id {
+            Some(token_id) => torii_proto::TokenId::Nft(
+                contract_address,
+                U256::from_be_hex(token_id.trim_start_matches("0x")).into(),
+            ),
+            None => torii_proto::TokenId::Contract(contract_address),
+        };
+
+        Self {
+            token_id,
+            status: torii_proto::MetadataJobStatus::from_str(&value.status)
+                .unwrap_or(torii_proto::MetadataJobStatus::Pending),
+            attempts: value.attempts as u32,
+            last_error: value.last_error,
+            retry_at: value
+                .retry_at
+                .and_then(|t| DateTime::<Utc>::from_timestamp(t, 0)),
+            updated_at: value.updated_at,
+        }
+    }
+}
+
 #[derive(FromRow, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TokenContract {
@@ -14,11 +14,12 @@ use torii_proto::schema::Entity;
 
 use torii_proto::{
     Achievement, AchievementQuery, Activity, ActivityQuery, AggregationEntry, AggregationQuery,
-    BalanceId, Contract, ContractCursor, ContractQuery, Controller, ControllerQuery, Event,
-    EventQuery, Model, Page, PlayerAchievementEntry, PlayerAchievementQuery, Query, SearchQuery,
-    SearchResponse, Token, TokenBalance, TokenBalanceQuery, TokenContract, TokenContractQuery,
-    TokenId, TokenQuery, TokenTransfer, TokenTransferQuery, Transaction, TransactionCall,
-    TransactionQuery,
+    BalanceId, Contract, ContractCursor, ContractQuery, Controller, ControllerQuery,
+    EntityAggregate, EntityAggregateQuery, Event, EventQuery, MetadataJob, MetadataJobQuery,
+    MetadataJobStatus, Model, Page, PlayerAchievementEntry, PlayerAchievementQuery, Query,
+    SearchQuery, SearchResponse, Token, TokenBalance, TokenBalanceQuery, TokenContract,
+    TokenContractQuery, TokenHolderStats, TokenHolderStatsQuery, TokenId, TokenQuery,
+    TokenTransfer, TokenTransferQuery, Transaction, TransactionCall, TransactionQuery,
 };
 
 pub mod utils;
@@ -61,6 +62,22 @@ pub trait ReadOnlyStorage: Send + Sync + Debug {
         query: &TokenBalanceQuery,
     ) -> Result<Page<TokenBalance>, StorageError>;
 
+    /// Returns holder statistics for each of the queried token contracts.
+    /// Includes holder counts, top holders, concentration and holder count history.
+    async fn token_holder_stats(
+        &self,
+        query: &TokenHolderStatsQuery,
+    ) -> Result<Vec<TokenHolderStats>, StorageError>;
+
+    /// Returns the metadata refresh jobs matching the query, ordered by token id.
+    async fn metadata_jobs(
+        &self,
+        query: &MetadataJobQuery,
+    ) -> Result<Page<MetadataJob>, StorageError>;
+
+    /// Returns the metadata jobs that are pending or due for a retry, oldest first.
+    async fn due_metadata_jobs(&self, limit: u32) -> Result<Vec<MetadataJob>, StorageError>;
+
     /// Returns the token contracts for the storage.
     async fn token_contracts(
         &self,
@@ -85,6 +102,12 @@ pub trait ReadOnlyStorage: Send + Sync + Debug {
     /// Returns entities for the storage.
     async fn entities(&self, query: &Query) -> Result<Page<Entity>, StorageError>;
 
+    /// Returns the aggregate of the entities of a model, for each group of their members.
+    async fn aggregate_entities(
+        &self,
+        query: &EntityAggregateQuery,
+    ) -> Result<Vec<EntityAggregate>, StorageError>;
+
     /// Returns event messages for the storage.
     async fn event_messages(&self, query: &Query) -> Result<Page<Entity>, StorageError>;
 
@@ -161,6 +184,10 @@ pub trait Storage: ReadOnlyStorage + Send + Sync + Debug {
         head: u64,
     ) -> Result<(), StorageError>;
 
+    /// Unregisters a contract from the storage, which stops its indexing.
+    /// The data indexed from the contract is kept.
+    async fn unregister_contract(&self, address: Felt) -> Result<(), StorageError>;
+
     /// Sets an entity with the storage.
     /// It should insert or update the entity if it already exists.
     /// Along with its model state in the model table.
@@ -298,6 +325,28 @@ pub trait Storage: ReadOnlyStorage + Send + Sync + Debug {
         metadata: String,
     ) -> Result<(), StorageError>;
 
+    /// Queues a metadata refresh for each of the tokens, resetting their failed attempts.
+    async fn enqueue_metadata_jobs(&self, token_ids: Vec<TokenId>) -> Result<(), StorageError>;
+
+    /// Queues a metadata refresh for a token contract and all of its registered tokens.
+    async fn enqueue_contract_metadata_jobs(
+        &self,
+        contract_address: Felt,
+    ) -> Result<(), StorageError>;
+
+    /// Records the outcome of a metadata refresh. A failed status increments the attempts of
+    /// the job, any other status resets them.
+    async fn set_metadata_job_status(
+        &self,
+        token_id: TokenId,
+        status: MetadataJobStatus,
+        last_error: Option<String>,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> Result<(), StorageError>;
+
+    /// Rebuilds the full-text search index from the searchable data.
+    async fn rebuild_search_index(&self) -> Result<(), StorageError>;
+
     /// Applies cached balance differences to the storage.
     async fn apply_balances_diff(
         &self,
@@ -11,7 +11,7 @@ use std::sync::Arc;
 use futures_util::future::try_join_all;
 use tokio::sync::Semaphore;
 use torii_adigraphmap::AcyclicDigraphMap;
-use tracing::{debug, error};
+use tracing::{debug, error, Instrument};
 
 const LOG_TARGET: &str = "torii::task_network";
 
@@ -104,33 +104,37 @@ where
                 let task_clone = task.clone();
                 let task_id = task_id.clone();
 
-                handles.push(tokio::spawn(async move {
-                    let _permit = semaphore
-                        .acquire()
-                        .await
-                        .map_err(TaskNetworkError::SemaphoreError)?;
-
-                    debug!(
-                        target: LOG_TARGET,
-                        task_id = ?task_id,
-                        level = level_idx,
-                        "Processing task."
-                    );
-
-                    match task_handler(task_id.clone(), task_clone).await {
-                        Ok(_) => Ok(()),
-                        Err(e) => {
-                            error!(
-                                target: LOG_TARGET,
-                                error = ?e,
-                                task_id = ?task_id,
-                                level = level_idx,
-                                "Error processing task."
-                            );
-                            Err(TaskNetworkError::TaskError(Box::new(e)))
+                handles.push(tokio::spawn(
+                    async move {
+                        let _permit = semaphore
+                            .acquire()
+                            .await
+                            .map_err(TaskNetworkError::SemaphoreError)?;
+
+                        debug!(
+                            target: LOG_TARGET,
+                            task_id = ?task_id,
+                            level = level_idx,
+                            "Processing task."
+                        );
+
+                        match task_handler(task_id.clone(), task_clone).await {
+                            Ok(_) => Ok(()),
+                            Err(e) => {
+                                error!(
+                                    target: LOG_TARGET,
+                                    error = ?e,
+                                    task_id = ?task_id,
+                                    level = level_idx,
+                                    "Error processing task."
+                                );
+                                Err(TaskNetworkError::TaskError(Box::new(e)))
+                            }
                         }
                     }
-                }));
+                    // The tasks are spans of the caller of process_tasks
+                    .in_current_span(),
+                ));
             }
 
             let results = try_join_all(handles)